@@ -6,19 +6,29 @@ use std::process;
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
 
+    for wasm_name in [
+        "room_contract.wasm",
+        "blob_contract.wasm",
+        "inbox_contract.wasm",
+    ] {
+        copy_contract_wasm(wasm_name);
+    }
+}
+
+fn copy_contract_wasm(wasm_name: &str) {
     // Get the output directory
     let out_dir = env::var("OUT_DIR").unwrap();
-    let dest_path = Path::new(&out_dir).join("room_contract.wasm");
+    let dest_path = Path::new(&out_dir).join(wasm_name);
 
     // Try to find the WASM file in several locations
     let possible_paths = [
         // When building from workspace
-        "../ui/public/contracts/room_contract.wasm",
+        format!("../ui/public/contracts/{wasm_name}"),
         // When building from workspace root
-        "ui/public/contracts/room_contract.wasm",
+        format!("ui/public/contracts/{wasm_name}"),
         // Pre-built WASM included in the package (required for crates.io)
         // This file MUST be committed to the repo for publishing
-        "contracts/room_contract.wasm",
+        format!("contracts/{wasm_name}"),
     ];
 
     let mut wasm_found = false;
@@ -27,10 +37,10 @@ fn main() {
         if Path::new(path).exists() {
             println!("cargo:rerun-if-changed={}", path);
             fs::copy(path, &dest_path).expect("Failed to copy WASM file");
-            println!("cargo:warning=Copied room_contract.wasm from {}", path);
+            println!("cargo:warning=Copied {} from {}", wasm_name, path);
             wasm_found = true;
 
-            verify_matches_built_artifact(&dest_path);
+            verify_matches_built_artifact(wasm_name, &dest_path);
             break;
         }
     }
@@ -43,20 +53,20 @@ fn main() {
             fs::write(&dest_path, b"dummy").expect("Failed to create dummy WASM file");
         } else {
             panic!(
-                "room_contract.wasm not found! Please ensure it exists in one of these locations: {:?}",
-                possible_paths
+                "{} not found! Please ensure it exists in one of these locations: {:?}",
+                wasm_name, possible_paths
             );
         }
     }
 }
 
-fn verify_matches_built_artifact(dest_path: &Path) {
+fn verify_matches_built_artifact(wasm_name: &str, dest_path: &Path) {
     if std::env::var("RIVER_SKIP_CONTRACT_CHECK").is_ok() {
         return;
     }
 
     let expected_built_wasm =
-        Path::new("..").join("target/wasm32-unknown-unknown/release/room_contract.wasm");
+        Path::new("..").join(format!("target/wasm32-unknown-unknown/release/{wasm_name}"));
 
     if !expected_built_wasm.exists() {
         // Nothing to compare against (contract probably not rebuilt yet)
@@ -67,7 +77,7 @@ fn verify_matches_built_artifact(dest_path: &Path) {
         Ok(bytes) => bytes,
         Err(err) => {
             eprintln!(
-                "Failed to read copied room_contract.wasm at {}: {err}",
+                "Failed to read copied {wasm_name} at {}: {err}",
                 dest_path.display()
             );
             process::exit(1);
@@ -78,7 +88,7 @@ fn verify_matches_built_artifact(dest_path: &Path) {
         Ok(bytes) => bytes,
         Err(err) => {
             eprintln!(
-                "Failed to read built room_contract.wasm at {}: {err}",
+                "Failed to read built {wasm_name} at {}: {err}",
                 expected_built_wasm.display()
             );
             process::exit(1);
@@ -87,7 +97,7 @@ fn verify_matches_built_artifact(dest_path: &Path) {
 
     if dest_bytes != built_bytes {
         panic!(
-            "room_contract.wasm is out of date.\n\
+            "{wasm_name} is out of date.\n\
              The CLI is bundling {}, but the freshly built artifact at {}\n\
              differs. Run `cargo make sync-cli-wasm` to refresh the bundled WASM.",
             dest_path.display(),
@@ -13,17 +13,26 @@ use freenet_stdlib::prelude::{
     ContractCode, ContractContainer, ContractInstanceId, ContractKey, ContractWasmAPIVersion,
     Parameters, UpdateData, WrappedContract, WrappedState,
 };
-use river_core::room_state::ban::{AuthorizedUserBan, UserBan};
+use river_core::room_state::ban::{AuthorizedUserBan, UserBan, MAX_BAN_REASON_BYTES};
+use river_core::room_state::ban_revocation::{AuthorizedBanRevocation, BanRevocation};
 use river_core::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+use river_core::room_state::invite::{
+    AuthorizedInvite, Invite, InvitePolicy, MAX_INVITE_LABEL_BYTES,
+};
+use river_core::room_state::join_request::{
+    AuthorizedJoinDecision, AuthorizedJoinRequest, JoinDecision, JoinOutcome, JoinRequest,
+    JoinRequestsV1, MAX_JOIN_REQUEST_MESSAGE_BYTES,
+};
 use river_core::room_state::member::{AuthorizedMember, Member, MemberId, MembersDelta};
-use river_core::room_state::member_info::{AuthorizedMemberInfo, MemberInfo};
+use river_core::room_state::member_info::{AuthorizedMemberInfo, DmPrekeyV1, MemberInfo};
+use river_core::room_state::mute::{AuthorizedMute, Mute, MuteAction};
 use river_core::room_state::privacy::{PrivacyMode, RoomDisplayMetadata, SealedBytes};
 use river_core::room_state::upgrade::OptionalUpgradeV1;
 use river_core::room_state::ChatRoomStateV1Delta;
 use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io::Write;
 use std::sync::Arc;
 use std::time::Duration;
@@ -33,6 +42,10 @@ use tracing::{debug, info, warn};
 
 // Load the room contract WASM copied by build.rs
 const ROOM_CONTRACT_WASM: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/room_contract.wasm"));
+// ...and the attachment blob contract (see `river_core::blob`)
+const BLOB_CONTRACT_WASM: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/blob_contract.wasm"));
+// ...and the per-member DM inbox contract (see `river_core::inbox`)
+const INBOX_CONTRACT_WASM: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/inbox_contract.wasm"));
 
 /// Timeout for the GET against the current room contract.
 const CURRENT_GET_TIMEOUT: Duration = Duration::from_secs(30);
@@ -150,7 +163,7 @@ async fn walk_upgrade_chain<'f>(
     mut fetch: impl FnMut(
         ContractInstanceId,
     ) -> std::pin::Pin<
-        Box<dyn std::future::Future<Output = Option<ChatRoomStateV1>> + 'f>,
+        Box<dyn std::future::Future<Output = Option<ChatRoomStateV1>> + Send + 'f>,
     >,
 ) -> (ChatRoomStateV1, ContractInstanceId) {
     let mut visited: HashSet<ContractInstanceId> = HashSet::new();
@@ -443,6 +456,18 @@ pub fn compute_contract_key(owner_vk: &VerifyingKey) -> ContractKey {
     ContractKey::from_params_and_code(Parameters::from(params_bytes), &contract_code)
 }
 
+/// Compute the contract key for an attachment blob from its parameters.
+pub fn blob_contract_key(params: &river_core::blob::BlobParametersV1) -> ContractKey {
+    let contract_code = ContractCode::from(BLOB_CONTRACT_WASM);
+    ContractKey::from_params_and_code(Parameters::from(params.to_bytes()), &contract_code)
+}
+
+/// Compute the contract key for a member's DM inbox from its parameters.
+pub fn inbox_contract_key(params: &river_core::inbox::InboxParametersV1) -> ContractKey {
+    let contract_code = ContractCode::from(INBOX_CONTRACT_WASM);
+    ContractKey::from_params_and_code(Parameters::from(params.to_bytes()), &contract_code)
+}
+
 /// Resolve a message's human-readable body for display.
 ///
 /// `effective_text` only yields text for `Text`/`Reply` bodies (and any edited
@@ -551,7 +576,8 @@ fn decrypt_private_body_text(
     secrets: &HashMap<u32, [u8; 32]>,
 ) -> Option<String> {
     use river_core::room_state::content::{
-        ReplyContentV1, TextContentV1, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
+        BlobContentV1, PollContentV1, ReplyContentV1, TextContentV1, CONTENT_TYPE_BLOB,
+        CONTENT_TYPE_POLL, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
     };
     use river_core::room_state::message::RoomMessageBody;
 
@@ -568,6 +594,11 @@ fn decrypt_private_body_text(
     let secret = secrets.get(secret_version)?;
     let plaintext =
         river_core::ecies::decrypt_with_symmetric_key(secret, ciphertext, nonce).ok()?;
+    if *content_type == CONTENT_TYPE_BLOB {
+        if let Ok(blob) = BlobContentV1::decode(&plaintext) {
+            return Some(blob.summary());
+        }
+    }
     if *content_type == CONTENT_TYPE_TEXT {
         if let Ok(text) = TextContentV1::decode(&plaintext) {
             return Some(text.text);
@@ -578,11 +609,104 @@ fn decrypt_private_body_text(
             return Some(reply.text);
         }
     }
+    if *content_type == CONTENT_TYPE_POLL {
+        if let Ok(poll) = PollContentV1::decode(&plaintext) {
+            return Some(poll.question);
+        }
+    }
     // Decrypted but not a known text-bearing content type: show the raw
     // plaintext rather than falling back to "<encrypted>" (matches the UI).
     Some(String::from_utf8_lossy(&plaintext).to_string())
 }
 
+/// Decode the attachment reference carried by `msg`, decrypting it with
+/// `secrets` when the body is private. `None` if `msg` is not an attachment or
+/// cannot be read.
+pub(crate) fn blob_content_with_secrets(
+    msg: &river_core::room_state::message::AuthorizedMessageV1,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> Option<river_core::room_state::content::BlobContentV1> {
+    use river_core::room_state::content::{BlobContentV1, DecodedContent};
+    use river_core::room_state::message::RoomMessageBody;
+
+    match &msg.message.content {
+        RoomMessageBody::Public { .. } => match msg.message.content.decode_content() {
+            Some(DecodedContent::Blob(blob)) => Some(blob),
+            _ => None,
+        },
+        RoomMessageBody::Private {
+            ciphertext,
+            nonce,
+            secret_version,
+            ..
+        } if msg.message.content.is_blob() => {
+            let secret = secrets.get(secret_version)?;
+            let plaintext =
+                river_core::ecies::decrypt_with_symmetric_key(secret, ciphertext, nonce).ok()?;
+            BlobContentV1::decode(&plaintext).ok()
+        }
+        RoomMessageBody::Private { .. } => None,
+    }
+}
+
+/// Decode the poll carried by `msg`, decrypting it with `secrets` when the
+/// body is private. `None` if `msg` is not a poll or cannot be read.
+pub(crate) fn poll_content_with_secrets(
+    msg: &river_core::room_state::message::AuthorizedMessageV1,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> Option<river_core::room_state::content::PollContentV1> {
+    use river_core::room_state::content::{DecodedContent, PollContentV1};
+    use river_core::room_state::message::RoomMessageBody;
+
+    match &msg.message.content {
+        RoomMessageBody::Public { .. } => match msg.message.content.decode_content() {
+            Some(DecodedContent::Poll(poll)) => Some(poll),
+            _ => None,
+        },
+        RoomMessageBody::Private {
+            ciphertext,
+            nonce,
+            secret_version,
+            ..
+        } if msg.message.content.is_poll() => {
+            let secret = secrets.get(secret_version)?;
+            let plaintext =
+                river_core::ecies::decrypt_with_symmetric_key(secret, ciphertext, nonce).ok()?;
+            PollContentV1::decode(&plaintext).ok()
+        }
+        RoomMessageBody::Private { .. } => None,
+    }
+}
+
+/// The message `msg` replies to, decrypting a private reply with `secrets`.
+/// `None` if `msg` is not a reply or cannot be read.
+pub(crate) fn reply_target_with_secrets(
+    msg: &river_core::room_state::message::AuthorizedMessageV1,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> Option<river_core::room_state::message::MessageId> {
+    use river_core::room_state::content::{ReplyContentV1, CONTENT_TYPE_REPLY};
+    use river_core::room_state::message::RoomMessageBody;
+
+    match &msg.message.content {
+        RoomMessageBody::Public { .. } => msg.message.content.reply_target_id(),
+        RoomMessageBody::Private {
+            content_type,
+            ciphertext,
+            nonce,
+            secret_version,
+            ..
+        } if *content_type == CONTENT_TYPE_REPLY => {
+            let secret = secrets.get(secret_version)?;
+            let plaintext =
+                river_core::ecies::decrypt_with_symmetric_key(secret, ciphertext, nonce).ok()?;
+            ReplyContentV1::decode(&plaintext)
+                .ok()
+                .map(|r| r.target_message_id)
+        }
+        RoomMessageBody::Private { .. } => None,
+    }
+}
+
 /// Replace `@[name](rv:id)` mention tokens with `@<name>` for terminal display.
 /// Prefers each member's *current* public nickname (so the rendered name
 /// follows renames); falls back to the token's snapshot name when the member is
@@ -753,6 +877,56 @@ pub(crate) fn resolve_outgoing_mentions(room_state: &ChatRoomStateV1, text: &str
     })
 }
 
+/// Refuse a send the room's slow mode would not keep. The contract does not
+/// reject such a message; it drops the author's own oldest message in the
+/// window instead, which would surprise the sender far more than an error.
+pub(crate) fn check_slow_mode(
+    room_state: &ChatRoomStateV1,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+) -> Result<()> {
+    let Some(slow_mode) = &room_state.configuration.configuration.slow_mode else {
+        return Ok(());
+    };
+    match room_state.recent_messages.slow_mode_retry_after(
+        slow_mode,
+        author_member_id(signing_key),
+        MemberId::from(room_owner_key),
+        std::time::SystemTime::now(),
+    ) {
+        None => Ok(()),
+        Some(wait) => Err(anyhow!(
+            "Slow mode is on in this room ({}); try again in {}s",
+            slow_mode,
+            wait.as_secs().max(1)
+        )),
+    }
+}
+
+/// Refuse a post the room's posting policy would drop. Reactions, votes,
+/// edits and deletes are actions, which the policy allows, so only the
+/// commands that post new messages call this.
+pub(crate) fn check_posting_policy(
+    room_state: &ChatRoomStateV1,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+) -> Result<()> {
+    let Some(policy) = &room_state.configuration.configuration.posting_policy else {
+        return Ok(());
+    };
+    if policy.may_post(
+        author_member_id(signing_key),
+        MemberId::from(room_owner_key),
+    ) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Only designated posters may post in this room (posting: {}); you can still react",
+            policy
+        ))
+    }
+}
+
 /// Truncate a reply preview to at most [`REPLY_PREVIEW_MAX_CHARS`] characters
 /// for display, appending `"..."` **only when characters were actually
 /// dropped**. A preview that fits is shown verbatim; a clipped one carries a
@@ -1008,7 +1182,8 @@ fn decrypt_private_quote_text(
     secrets: &HashMap<u32, [u8; 32]>,
 ) -> Option<String> {
     use river_core::room_state::content::{
-        ReplyContentV1, TextContentV1, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
+        BlobContentV1, PollContentV1, ReplyContentV1, TextContentV1, CONTENT_TYPE_BLOB,
+        CONTENT_TYPE_POLL, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
     };
     use river_core::room_state::message::RoomMessageBody;
 
@@ -1028,6 +1203,8 @@ fn decrypt_private_quote_text(
     match *content_type {
         CONTENT_TYPE_TEXT => TextContentV1::decode(&plaintext).ok().map(|c| c.text),
         CONTENT_TYPE_REPLY => ReplyContentV1::decode(&plaintext).ok().map(|r| r.text),
+        CONTENT_TYPE_POLL => PollContentV1::decode(&plaintext).ok().map(|p| p.question),
+        CONTENT_TYPE_BLOB => BlobContentV1::decode(&plaintext).ok().map(|b| b.summary()),
         _ => None,
     }
 }
@@ -1421,6 +1598,7 @@ fn build_new_room_state(
         version: 0,
         preferred_nickname: seal(nickname.as_bytes()),
         deputies: Vec::new(),
+        dm_prekey: None,
     };
     room_state
         .member_info
@@ -1867,6 +2045,31 @@ impl ApiClient {
         secrets
     }
 
+    /// Drop, in place, every message by a member the local identity has
+    /// blocked (`member block`) from a fetched `room_state`, for **display**
+    /// only: the room itself keeps them. Call after
+    /// [`Self::room_display_secrets`], whose rebuild reads every message. A
+    /// no-op when nobody is blocked or the room is not in local storage.
+    pub(crate) fn hide_blocked_authors(
+        &self,
+        room_owner_key: &VerifyingKey,
+        room_state: &mut ChatRoomStateV1,
+    ) {
+        let Some(identity) = self.storage.self_identity(room_owner_key).ok().flatten() else {
+            return;
+        };
+        let blocked = self
+            .storage
+            .blocked_members(room_owner_key, identity.member_id)
+            .unwrap_or_default();
+        if !blocked.is_empty() {
+            room_state
+                .recent_messages
+                .messages
+                .retain(|msg| !blocked.contains(&msg.message.author));
+        }
+    }
+
     pub async fn get_room(
         &self,
         room_owner_key: &VerifyingKey,
@@ -1928,6 +2131,15 @@ impl ApiClient {
             }
         };
 
+        // Keep everything we have seen past the retention horizon. Best-effort:
+        // a read must not fail because the local archive could not be written.
+        if let Err(e) = self
+            .storage
+            .archive_room_messages(room_owner_key, &room_state)
+        {
+            warn!("Failed to archive fetched messages: {e:#}");
+        }
+
         Ok(room_state)
     }
 
@@ -2422,7 +2634,16 @@ impl ApiClient {
         Ok(())
     }
 
-    pub async fn create_invitation(&self, room_owner_key: &VerifyingKey) -> Result<String> {
+    /// Create an invitation code. With an expiry, a use limit or a label, a
+    /// signed invite record carrying them is published to the room BEFORE the
+    /// code is returned, so the link is never handed out without its limits.
+    pub async fn create_invitation(
+        &self,
+        room_owner_key: &VerifyingKey,
+        expires_in: Option<Duration>,
+        label: Option<&str>,
+        max_uses: Option<u32>,
+    ) -> Result<String> {
         // `invite create <room>` honours a `--signing-key-file` override for
         // the room it targets (that is the override's whole purpose — pick
         // which of your identities in THIS room mints the invite), so resolve
@@ -2433,6 +2654,56 @@ impl ApiClient {
             .ok_or_else(|| anyhow!("Room not found in local storage. You must be a member of the room to create invitations."))?;
         let invitation = self.build_invitation(room_owner_key, &inviter_signing_key, &state)?;
 
+        if expires_in.is_some() || label.is_some() || max_uses.is_some() {
+            // Seal the label like a ban reason: plaintext in a public room,
+            // under the room secret in a private one.
+            let label = match label {
+                Some(label) if label.len() > MAX_INVITE_LABEL_BYTES => {
+                    return Err(anyhow!(
+                        "Invite label is {} bytes; the maximum is {}",
+                        label.len(),
+                        MAX_INVITE_LABEL_BYTES
+                    ));
+                }
+                Some(label) => {
+                    let invitation_secrets = self.storage.get_invitation_secrets(room_owner_key)?;
+                    let secrets = crate::private_room::collect_secrets_for_room(
+                        &state,
+                        &inviter_signing_key,
+                        &invitation_secrets,
+                    );
+                    Some(
+                        crate::private_room::seal_field_for_room(
+                            &state,
+                            &secrets,
+                            label.as_bytes(),
+                        )
+                        .map_err(|e| anyhow!(e))?,
+                    )
+                }
+                None => None,
+            };
+            let now = std::time::SystemTime::now();
+            let expires_at = match expires_in {
+                Some(duration) => Some(
+                    now.checked_add(duration)
+                        .ok_or_else(|| anyhow!("Invite expiry is too far in the future"))?,
+                ),
+                None => None,
+            };
+            self.publish_invite_record(
+                room_owner_key,
+                &inviter_signing_key,
+                invitation.invitee.member.id(),
+                InvitePolicy::Open {
+                    expires_at,
+                    label,
+                    max_uses,
+                },
+            )
+            .await?;
+        }
+
         // Encode as base58
         let mut data = Vec::new();
         ciborium::ser::into_writer(&invitation, &mut data)
@@ -2514,6 +2785,59 @@ impl ApiClient {
         })
     }
 
+    /// Revoke an invitation this identity issued (or, for the owner, any
+    /// invitation), named by its invitee ID from `invite list` or by the
+    /// invitation code itself. Returns the invitee's member ID.
+    pub async fn revoke_invitation(
+        &self,
+        room_owner_key: &VerifyingKey,
+        invitation: &str,
+    ) -> Result<MemberId> {
+        let (signing_key, _, _) = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to revoke invitations.")
+        })?;
+        let room_state = self.get_room(room_owner_key, false).await?;
+        let params = ChatRoomParametersV1 {
+            owner: *room_owner_key,
+        };
+        let invitee = resolve_invite_to_revoke(
+            &room_state,
+            &params,
+            author_member_id(&signing_key),
+            invitation,
+        )?;
+        info!("Revoking invitation for invitee: {}", invitee);
+        self.publish_invite_record(room_owner_key, &signing_key, invitee, InvitePolicy::Revoked)
+            .await?;
+        Ok(invitee)
+    }
+
+    /// Sign `policy` for the invitation whose invitee is `invitee` and send it
+    /// as an `invites`-only delta.
+    async fn publish_invite_record(
+        &self,
+        room_owner_key: &VerifyingKey,
+        signing_key: &SigningKey,
+        invitee: MemberId,
+        policy: InvitePolicy,
+    ) -> Result<()> {
+        let record = AuthorizedInvite::new(
+            Invite {
+                owner_member_id: room_owner_key.into(),
+                invitee,
+                issued_at: std::time::SystemTime::now(),
+                policy,
+            },
+            author_member_id(signing_key),
+            signing_key,
+        );
+        let delta = ChatRoomStateV1Delta {
+            invites: Some(vec![record]),
+            ..Default::default()
+        };
+        self.send_delta(room_owner_key, delta).await
+    }
+
     pub async fn accept_invitation(
         &self,
         invitation_code: &str,
@@ -2623,6 +2947,18 @@ impl ApiClient {
                             return Err(anyhow!("Room state has invalid owner_member_id"));
                         }
 
+                        // Refuse a link its issuer has closed before storing
+                        // anything: the contract would remove us as soon as
+                        // the join landed.
+                        if let Some(refusal) = invitation_refusal(
+                            &room_state,
+                            invitation.invitee.member.id(),
+                            std::time::SystemTime::now(),
+                        ) {
+                            return Err(refusal);
+                        }
+                        let invitation = joining_identity(&room_state, invitation);
+
                         // Compute invite chain before storing (walks up from invitee
                         // to owner through existing members — doesn't require the
                         // invitee to be in the members list)
@@ -2721,6 +3057,7 @@ impl ApiClient {
                                 version: 0,
                                 preferred_nickname: sealed,
                                 deputies: Vec::new(),
+                                dm_prekey: None,
                             };
                             let authorized_info = river_core::room_state::member_info::AuthorizedMemberInfo::new_with_member_key(
                                 member_info, signing_key,
@@ -3381,6 +3718,7 @@ impl ApiClient {
             // member_info (and any deputy grants) was already cleaned up; they
             // re-appoint deputies after rejoining if desired. (#410)
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         let authorized_info = AuthorizedMemberInfo::new_with_member_key(member_info, signing_key);
 
@@ -3407,6 +3745,8 @@ impl ApiClient {
 
         // Fetch room state from the network
         let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, signing_key)?;
+        check_posting_policy(&room_state, room_owner_key, signing_key)?;
 
         let sender_vk = signing_key.verifying_key();
         let sender_member_id = author_member_id(signing_key);
@@ -3538,6 +3878,8 @@ impl ApiClient {
 
         // Fetch fresh state from network so build_rejoin_delta can detect pruning
         let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
+        check_posting_policy(&room_state, room_owner_key, &signing_key)?;
 
         // Resolve any bare @nickname mentions to full mention tokens.
         let message_content = resolve_outgoing_mentions(&room_state, &message_content);
@@ -3706,6 +4048,7 @@ impl ApiClient {
 
         // Fetch fresh state from network so build_rejoin_delta can detect pruning
         let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
 
         // Build the action body — plaintext for a public room, AES-256-GCM
         // sealed for a private room (secret resolved from the contract's
@@ -3780,6 +4123,7 @@ impl ApiClient {
 
         // Fetch fresh state from network so build_rejoin_delta can detect pruning
         let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
 
         // Build the action body — plaintext (public) or AES-256-GCM sealed
         // (private). See `edit_message` for the storage / secret rationale.
@@ -3852,6 +4196,7 @@ impl ApiClient {
 
         // Fetch fresh state from network so build_rejoin_delta can detect pruning
         let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
 
         // Build the action body — plaintext (public) or AES-256-GCM sealed
         // (private). See `edit_message` for the storage / secret rationale.
@@ -3924,6 +4269,7 @@ impl ApiClient {
 
         // Fetch fresh state from network so build_rejoin_delta can detect pruning
         let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
 
         // Build the action body — plaintext (public) or AES-256-GCM sealed
         // (private). See `edit_message` for the storage / secret rationale.
@@ -3978,95 +4324,45 @@ impl ApiClient {
         self.send_delta(room_owner_key, delta).await
     }
 
-    /// Reply to a message
-    pub async fn send_reply(
+    /// Post a poll to a room
+    pub async fn send_poll(
         &self,
         room_owner_key: &VerifyingKey,
-        target_message_id: river_core::room_state::message::MessageId,
-        reply_text: String,
+        poll: river_core::room_state::content::PollContentV1,
     ) -> Result<()> {
         info!(
-            "Sending reply in room owned by: {}",
+            "Sending poll in room owned by: {}",
             bs58::encode(room_owner_key.as_bytes()).into_string()
         );
 
         // Get signing key from storage
         let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
-            anyhow!("Room not found. You must be a member of the room to send replies.")
+            anyhow!("Room not found. You must be a member of the room to post polls.")
         })?;
         let (signing_key, _, _contract_key_str) = room_data;
 
         // Fetch fresh state from network so build_rejoin_delta can detect pruning
         let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
+        check_posting_policy(&room_state, room_owner_key, &signing_key)?;
 
-        // Decrypt the room's private content BEFORE selecting the reply target:
-        // this rebuilds `actions_state` from the decrypted private edit/delete
-        // actions, so a target the user already deleted is correctly excluded
-        // (`display_messages()` hides it) and an edited target's quoted preview
-        // reflects the edit rather than the stale original. Returns the secrets
-        // used to decrypt the preview body below (empty map / no-op for a public
-        // room, so behaviour there is unchanged).
-        let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
-
-        // Find the target message to extract author name and content preview
-        let target_msg = room_state
-            .recent_messages
-            .display_messages()
-            .find(|m| m.id() == target_message_id)
-            .ok_or_else(|| {
-                anyhow!("Target message not found in recent messages. Cannot reply to expired messages via CLI.")
-            })?;
-
-        // Decrypt the target author's nickname with the room secrets — in a
-        // private room this is sealed, and `build_reply_body` seals it into
-        // `ReplyContentV1.target_author_name` and PERSISTS it to contract state.
-        // Without decrypting here, the reply's quoted author would read
-        // "[Encrypted: N bytes, vN]" to every reader (including the UI) forever.
-        let target_author_name = room_state
-            .member_info
-            .canonical(target_msg.message.author)
-            .map(|info| unseal_nickname_display(&info.member_info.preferred_nickname, &secrets))
-            .unwrap_or_else(|| target_msg.message.author.to_string());
-
-        // Quote the target's plaintext. A PRIVATE target body is decrypted via
-        // `secrets`; without this the CLI sealed "<encrypted>" into the reply's
-        // `ReplyContentV1`, so the quoted context read "<encrypted>" to every
-        // reader forever.
-        let target_content_preview: String =
-            message_display_text_with_secrets(&room_state, target_msg, &secrets)
-                .chars()
-                .take(100)
-                .collect();
-
-        // Resolve any bare @nickname mentions in the reply body.
-        let reply_text = resolve_outgoing_mentions(&room_state, &reply_text);
-
-        // Build the reply body — plaintext (public) or AES-256-GCM sealed
-        // (private). See `edit_message` for the storage / secret rationale. The
-        // target author name and content preview are sealed alongside the reply
-        // text in a private room (they are part of `ReplyContentV1`), so the
-        // reply context is not leaked in the clear.
+        // Build the poll body — plaintext (public) or AES-256-GCM sealed
+        // (private). See `edit_message` for the storage / secret rationale.
         let invitation_secrets = self.storage.get_invitation_secrets(room_owner_key)?;
-        let content = crate::private_room::build_reply_body(
+        let content = crate::private_room::build_poll_body(
             &room_state,
             &signing_key,
             &invitation_secrets,
-            reply_text,
-            target_message_id,
-            target_author_name,
-            target_content_preview,
+            poll,
         )
         .map_err(|e| anyhow!(e))?;
 
-        // Create the reply message
         let message = river_core::room_state::message::MessageV1 {
             room_owner: MemberId::from(*room_owner_key),
             author: author_member_id(&signing_key),
             content,
             time: std::time::SystemTime::now(),
         };
-
-        // Sign the message
         let auth_message =
             river_core::room_state::message::AuthorizedMessageV1::new(message, &signing_key);
 
@@ -4074,7 +4370,6 @@ impl ApiClient {
         let (members_delta, member_info_delta) =
             self.build_rejoin_delta(&room_state, room_owner_key, &signing_key);
 
-        // Create a delta with the reply message
         let delta = ChatRoomStateV1Delta {
             recent_messages: Some(vec![auth_message]),
             members: members_delta,
@@ -4088,899 +4383,2063 @@ impl ApiClient {
         };
         room_state
             .apply_delta(&room_state.clone(), &params, &Some(delta.clone()))
-            .map_err(|e| anyhow!("Failed to apply reply delta: {:?}", e))?;
+            .map_err(|e| anyhow!("Failed to apply poll delta: {:?}", e))?;
 
-        // Update the stored state
         self.storage.update_room_state(room_owner_key, room_state)?;
-
-        // Send the delta to the network
         self.send_delta(room_owner_key, delta).await
     }
 
-    /// Helper to send a delta to the network.
-    /// Assumes migration has already been triggered by the caller (via get_room
-    /// or ensure_room_migrated), so owner_vk_to_contract_key returns the correct key.
-    async fn send_delta(
+    /// Upload `data` as an attachment and post a message referencing it.
+    ///
+    /// The bytes go into their own blob contract first (encrypted under a
+    /// fresh key in a private room — see
+    /// [`crate::private_room::seal_attachment`]); only once the PUT succeeds
+    /// is the `BlobContentV1` reference sent to the room, so a message never
+    /// points at a blob that was not stored.
+    pub async fn send_attachment(
         &self,
         room_owner_key: &VerifyingKey,
-        delta: ChatRoomStateV1Delta,
-    ) -> Result<()> {
-        let contract_key = self.owner_vk_to_contract_key(room_owner_key);
+        data: Vec<u8>,
+        filename: String,
+        mime_type: String,
+    ) -> Result<river_core::room_state::content::BlobContentV1> {
+        use river_core::blob::{BlobParametersV1, MAX_BLOB_SIZE};
 
-        // Serialize the delta
-        let delta_bytes = {
-            let mut buf = Vec::new();
-            ciborium::ser::into_writer(&delta, &mut buf)
-                .map_err(|e| anyhow!("Failed to serialize delta: {}", e))?;
-            buf
-        };
+        info!(
+            "Sending attachment {} ({} bytes) in room owned by: {}",
+            filename,
+            data.len(),
+            bs58::encode(room_owner_key.as_bytes()).into_string()
+        );
 
-        let update_request = ContractRequest::Update {
-            key: contract_key,
-            data: UpdateData::Delta(delta_bytes.into()),
-        };
+        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to send attachments.")
+        })?;
+        let (signing_key, _, _contract_key_str) = room_data;
 
-        let client_request = ClientRequest::ContractOp(update_request);
+        let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
+        check_posting_policy(&room_state, room_owner_key, &signing_key)?;
+        let invitation_secrets = self.storage.get_invitation_secrets(room_owner_key)?;
 
-        let mut web_api = self.web_api.lock().await;
-        web_api
-            .send(client_request)
-            .await
-            .map_err(|e| anyhow!("Failed to send update request: {}", e))?;
+        let (stored, key) = crate::private_room::seal_attachment(
+            &room_state,
+            &signing_key,
+            &invitation_secrets,
+            &data,
+        )
+        .map_err(|e| anyhow!(e))?;
+        if stored.len() > MAX_BLOB_SIZE {
+            return Err(anyhow!(
+                "Attachment too large: {} bytes stored, the limit is {} bytes",
+                stored.len(),
+                MAX_BLOB_SIZE
+            ));
+        }
 
-        // Wait for response
-        let response =
-            match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
-                Ok(Ok(response)) => response,
-                Ok(Err(e)) => return Err(anyhow!("Failed to receive response: {}", e)),
-                Err(_) => {
-                    return Err(anyhow!(
-                        "Timeout waiting for update response after 60 seconds"
-                    ))
-                }
-            };
+        let params = BlobParametersV1::for_bytes(&stored);
+        let blob_key = self.put_blob(&params, stored).await?;
+
+        let blob = river_core::room_state::content::BlobContentV1 {
+            contract_id: blob_key
+                .id()
+                .as_bytes()
+                .try_into()
+                .expect("contract ids are 32 bytes"),
+            hash: params.hash,
+            size: data.len() as u64,
+            mime_type,
+            filename,
+            key,
+        };
+        let content = crate::private_room::build_blob_body(
+            &room_state,
+            &signing_key,
+            &invitation_secrets,
+            blob.clone(),
+        )
+        .map_err(|e| anyhow!(e))?;
 
-        match response {
-            HostResponse::ContractResponse(ContractResponse::UpdateResponse { key, .. }) => {
-                info!("Action sent successfully to contract: {}", key.id());
-                Ok(())
+        let message = river_core::room_state::message::MessageV1 {
+            room_owner: MemberId::from(*room_owner_key),
+            author: author_member_id(&signing_key),
+            content,
+            time: std::time::SystemTime::now(),
+        };
+        let auth_message =
+            river_core::room_state::message::AuthorizedMessageV1::new(message, &signing_key);
+
+        // Check if we need to re-add ourselves (pruned for inactivity)
+        let (members_delta, member_info_delta) =
+            self.build_rejoin_delta(&room_state, room_owner_key, &signing_key);
+
+        let delta = ChatRoomStateV1Delta {
+            recent_messages: Some(vec![auth_message]),
+            members: members_delta,
+            member_info: member_info_delta,
+            ..Default::default()
+        };
+
+        // Apply the delta to our local state for validation
+        let params = ChatRoomParametersV1 {
+            owner: *room_owner_key,
+        };
+        room_state
+            .apply_delta(&room_state.clone(), &params, &Some(delta.clone()))
+            .map_err(|e| anyhow!("Failed to apply attachment delta: {:?}", e))?;
+
+        self.storage.update_room_state(room_owner_key, room_state)?;
+        self.send_delta(room_owner_key, delta).await?;
+        Ok(blob)
+    }
+
+    /// PUT `stored` into the blob contract named by `params`.
+    async fn put_blob(
+        &self,
+        params: &river_core::blob::BlobParametersV1,
+        stored: Vec<u8>,
+    ) -> Result<ContractKey> {
+        let contract_code = ContractCode::from(BLOB_CONTRACT_WASM);
+        let contract_key = blob_contract_key(params);
+        let contract_container = ContractContainer::from(ContractWasmAPIVersion::V1(
+            WrappedContract::new(Arc::new(contract_code), Parameters::from(params.to_bytes())),
+        ));
+
+        // No subscription: a blob never changes after this PUT.
+        let put_request = ContractRequest::Put {
+            contract: contract_container,
+            state: WrappedState::new(stored),
+            related_contracts: Default::default(),
+            subscribe: false,
+            blocking_subscribe: false,
+        };
+
+        let mut web_api = self.web_api.lock().await;
+        web_api
+            .send(ClientRequest::ContractOp(put_request))
+            .await
+            .map_err(|e| anyhow!("Failed to send attachment PUT: {}", e))?;
+
+        match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
+            Ok(Ok(HostResponse::ContractResponse(ContractResponse::PutResponse { key }))) => {
+                if key != contract_key {
+                    return Err(anyhow!(
+                        "Attachment contract key mismatch: expected {}, got {}",
+                        contract_key.id(),
+                        key.id()
+                    ));
+                }
+                Ok(contract_key)
             }
-            _ => Err(anyhow!("Unexpected response type: {:?}", response)),
+            Ok(Ok(other)) => Err(anyhow!(
+                "Unexpected response to attachment PUT: {:?}",
+                other
+            )),
+            Ok(Err(e)) => Err(anyhow!("Failed to upload attachment: {}", e)),
+            Err(_) => Err(anyhow!(
+                "Timeout waiting for attachment PUT response after 60 seconds"
+            )),
         }
     }
 
-    /// Stream messages from a room by polling for updates
-    pub async fn stream_messages(
+    /// Fetch the attachment referenced by `message_id` and return it with its
+    /// metadata, checked against its content hash and decrypted if the room
+    /// is private.
+    pub async fn fetch_attachment(
         &self,
         room_owner_key: &VerifyingKey,
-        poll_interval_ms: u64,
-        timeout_secs: u64,
-        max_messages: usize,
-        initial_messages: usize,
-        format: OutputFormat,
-    ) -> Result<()> {
-        // Get the contract key for the room
-        let room = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
-            anyhow!("Room not found in local storage. You may need to create or join it first.")
-        })?;
+        message_id: &river_core::room_state::message::MessageId,
+    ) -> Result<(river_core::room_state::content::BlobContentV1, Vec<u8>)> {
+        let mut room_state = self.get_room(room_owner_key, false).await?;
+        let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+        let msg = room_state
+            .recent_messages
+            .display_messages()
+            .find(|m| &m.id() == message_id)
+            .ok_or_else(|| anyhow!("Message not found in recent messages"))?;
+        let blob = blob_content_with_secrets(msg, &secrets)
+            .ok_or_else(|| anyhow!("Message is not an attachment, or it cannot be decrypted"))?;
 
-        let (_signing_key, _room_state, contract_key_str) = room;
-        let _contract_key = contract_key_str.clone();
+        let get_request = ContractRequest::Get {
+            key: ContractInstanceId::new(blob.contract_id),
+            return_contract_code: false,
+            subscribe: false,
+            blocking_subscribe: false,
+        };
 
-        // Print header for human format
-        if matches!(format, OutputFormat::Human) {
-            eprintln!(
-                "Streaming messages from room {} (press Ctrl+C to stop)...\n",
-                bs58::encode(room_owner_key.as_bytes()).into_string()
-            );
-        }
+        let mut web_api = self.web_api.lock().await;
+        web_api
+            .send(ClientRequest::ContractOp(get_request))
+            .await
+            .map_err(|e| anyhow!("Failed to send attachment GET: {}", e))?;
 
-        // Track seen messages: key -> last-emitted effective content, so a later
-        // edit (content change) is detected and re-emitted, not just new ids.
-        let mut seen_messages: HashMap<String, String> = HashMap::new();
-        // Messages for which a deletion has already been emitted (one-shot). The
-        // polling path needs NO startup pre-seed (unlike subscribe): it only ever
-        // inserts into `seen` via `display_messages()` (initial window + each
-        // poll's emit_new_and_edited), which excludes deleted messages — so a
-        // pre-existing deletion is never in `seen` and `should_emit_deletion`
-        // returns false for it. (A future change that seeds `seen` from raw
-        // `messages` here would need a pre-seed like the subscribe path's.)
-        let mut deleted_emitted: HashSet<String> = HashSet::new();
-        // Reactions fingerprint per SURFACED message, so a reaction added/removed
-        // AFTER the message was streamed surfaces as a `reaction` event
-        // (freenet/river#325). Seeded for messages shown at start (below) and for
-        // messages emitted live by emit_new_and_edited (which seeds as it emits);
-        // emit_reaction_changes only acts on messages already in this map, so a
-        // brand-new message's initial reactions are not re-emitted as a change.
-        let mut seen_reactions: HashMap<String, String> = HashMap::new();
-        let mut new_message_count = 0;
-        let start_time = std::time::Instant::now();
+        let stored = match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv())
+            .await
+        {
+            Ok(Ok(HostResponse::ContractResponse(ContractResponse::GetResponse {
+                state, ..
+            }))) => state.as_ref().to_vec(),
+            Ok(Ok(other)) => {
+                return Err(anyhow!(
+                    "Unexpected response to attachment GET: {:?}",
+                    other
+                ))
+            }
+            Ok(Err(e)) => return Err(anyhow!("Failed to fetch attachment: {}", e)),
+            Err(_) => return Err(anyhow!("Timeout fetching attachment")),
+        };
 
-        // Show initial messages if requested
-        if initial_messages > 0 {
-            let mut room_state = self.get_room(room_owner_key, false).await?;
-            // Decrypt private-room content for display (no-op for public rooms).
-            let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+        let data = river_core::blob::open_blob(&blob, &stored, &secrets).map_err(|e| anyhow!(e))?;
+        Ok((blob, data))
+    }
 
-            // Use display_messages() to filter out action/deleted messages (matches `message list`)
-            let all_msgs: Vec<_> = room_state.recent_messages.display_messages().collect();
-            let start = all_msgs.len().saturating_sub(initial_messages);
+    /// Fetch the DM inbox named by `params`. An inbox nobody has written to
+    /// yet does not exist on the network and reads as empty.
+    pub async fn get_inbox(
+        &self,
+        params: &river_core::inbox::InboxParametersV1,
+    ) -> Result<river_core::inbox::InboxStateV1> {
+        let contract_key = inbox_contract_key(params);
+        let get_request = ContractRequest::Get {
+            key: *contract_key.id(),
+            return_contract_code: false,
+            subscribe: false,
+            blocking_subscribe: false,
+        };
 
-            for msg in &all_msgs[start..] {
-                let key = monitor_seen_key(msg);
-                seen_messages.insert(
-                    key.clone(),
-                    message_display_text_with_secrets(&room_state, msg, &secrets),
-                );
-                // Seed the reactions fingerprint for shown messages so reactions
-                // already present at startup aren't re-emitted as a live change;
-                // only later changes to them surface.
-                seen_reactions.insert(
-                    key,
-                    reactions_fingerprint(room_state.recent_messages.reactions(&msg.id())),
-                );
+        let mut web_api = self.web_api.lock().await;
+        web_api
+            .send(ClientRequest::ContractOp(get_request))
+            .await
+            .map_err(|e| anyhow!("Failed to send inbox GET: {}", e))?;
 
-                Self::output_message(&room_state, msg, room_owner_key, &format, false, &secrets)?;
+        match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
+            Ok(Ok(HostResponse::ContractResponse(ContractResponse::GetResponse {
+                state, ..
+            }))) => river_core::inbox::InboxStateV1::from_bytes(state.as_ref())
+                .map_err(|e| anyhow!("Failed to deserialize inbox: {}", e)),
+            Ok(Ok(HostResponse::ContractResponse(ContractResponse::NotFound { .. }))) => {
+                Ok(Default::default())
             }
+            Ok(Ok(other)) => Err(anyhow!("Unexpected response to inbox GET: {:?}", other)),
+            Ok(Err(e)) => Err(anyhow!("Failed to fetch inbox: {}", e)),
+            Err(_) => Err(anyhow!("Timeout fetching inbox")),
         }
+    }
 
-        // Set up Ctrl+C handler
-        let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
-
-        // Spawn task to handle Ctrl+C
-        tokio::spawn(async move {
-            tokio::signal::ctrl_c().await.ok();
-            let _ = shutdown_tx.send(()).await;
-        });
-
-        // Main polling loop
-        loop {
-            // Check for shutdown signal
-            if shutdown_rx.try_recv().is_ok() {
-                if matches!(format, OutputFormat::Human) {
-                    eprintln!("\nStopped monitoring.");
-                }
-                return Ok(());
-            }
+    /// PUT `update` into the DM inbox named by `params`. The contract merges
+    /// it into whatever the inbox already holds, so the first DM to a member
+    /// creates their inbox and later ones only need to carry what is new.
+    pub async fn put_inbox(
+        &self,
+        params: &river_core::inbox::InboxParametersV1,
+        update: river_core::inbox::InboxStateV1,
+    ) -> Result<()> {
+        let contract_code = ContractCode::from(INBOX_CONTRACT_WASM);
+        let contract_key = inbox_contract_key(params);
+        let contract_container = ContractContainer::from(ContractWasmAPIVersion::V1(
+            WrappedContract::new(Arc::new(contract_code), Parameters::from(params.to_bytes())),
+        ));
 
-            // Check timeout
-            if timeout_secs > 0 && start_time.elapsed().as_secs() >= timeout_secs {
-                debug!("Timeout reached, exiting stream");
-                return Ok(());
-            }
+        let put_request = ContractRequest::Put {
+            contract: contract_container,
+            state: WrappedState::new(update.to_bytes()),
+            related_contracts: Default::default(),
+            subscribe: false,
+            blocking_subscribe: false,
+        };
 
-            // Check max messages
-            if max_messages > 0 && new_message_count >= max_messages {
-                debug!("Maximum message count reached, exiting stream");
-                return Ok(());
-            }
+        let mut web_api = self.web_api.lock().await;
+        web_api
+            .send(ClientRequest::ContractOp(put_request))
+            .await
+            .map_err(|e| anyhow!("Failed to send inbox PUT: {}", e))?;
 
-            // Poll for new + edited messages. emit_new_and_edited re-emits a
-            // message whose effective content changed (an edit) and emits ones
-            // not seen before; it respects max_messages for NEW messages.
-            match self.get_room(room_owner_key, false).await {
-                Ok(mut room_state) => {
-                    // Decrypt private-room content for display (no-op for public rooms).
-                    let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
-                    Self::emit_new_and_edited(
-                        &room_state,
-                        &mut seen_messages,
-                        &mut deleted_emitted,
-                        &mut seen_reactions,
-                        room_owner_key,
-                        &format,
-                        max_messages,
-                        &mut new_message_count,
-                        &secrets,
-                    )?;
-                    Self::emit_deletions(
-                        &room_state,
-                        &seen_messages,
-                        &mut deleted_emitted,
-                        room_owner_key,
-                        &format,
-                        &secrets,
-                    )?;
-                    // Surface reactions added/removed since a message was already
-                    // streamed. Runs AFTER emit_new_and_edited so a brand-new
-                    // message is seeded (not re-emitted) on the same poll.
-                    Self::emit_reaction_changes(
-                        &room_state,
-                        &mut seen_reactions,
-                        room_owner_key,
-                        &format,
-                        &secrets,
-                    )?;
-                    if max_messages > 0 && new_message_count >= max_messages {
-                        return Ok(());
-                    }
-                }
-                Err(e) => {
-                    // Log error but continue polling
-                    debug!("Error fetching room state: {}", e);
+        match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
+            Ok(Ok(HostResponse::ContractResponse(ContractResponse::PutResponse { key }))) => {
+                if key != contract_key {
+                    return Err(anyhow!(
+                        "Inbox contract key mismatch: expected {}, got {}",
+                        contract_key.id(),
+                        key.id()
+                    ));
                 }
+                Ok(())
             }
-
-            // Wait for next poll interval
-            tokio::time::sleep(std::time::Duration::from_millis(poll_interval_ms)).await;
+            Ok(Ok(other)) => Err(anyhow!("Unexpected response to inbox PUT: {:?}", other)),
+            Ok(Err(e)) => Err(anyhow!("Failed to write inbox: {}", e)),
+            Err(_) => Err(anyhow!(
+                "Timeout waiting for inbox PUT response after 60 seconds"
+            )),
         }
     }
 
-    /// Scan the room's display messages and emit any that are NEW or whose
-    /// effective content changed (an EDIT) since last seen. `seen` maps each
-    /// message's dedup key to the content last emitted for it, so a later edit
-    /// is detected as a content change. `new_count` is incremented only for new
-    /// messages (edits don't count toward `max_new`); when `max_new > 0` the
+    /// Vote in a poll. `choices` are zero-based option indices; an empty list
+    /// retracts an earlier vote.
+    pub async fn vote_in_poll(
+        &self,
+        room_owner_key: &VerifyingKey,
+        poll_message_id: river_core::room_state::message::MessageId,
+        choices: Vec<u32>,
+    ) -> Result<()> {
+        info!(
+            "Voting in poll in room owned by: {}",
+            bs58::encode(room_owner_key.as_bytes()).into_string()
+        );
+
+        // Get signing key from storage
+        let room_data = self
+            .storage
+            .get_room(room_owner_key)?
+            .ok_or_else(|| anyhow!("Room not found. You must be a member of the room to vote."))?;
+        let (signing_key, _, _contract_key_str) = room_data;
+
+        // Fetch fresh state from network so build_rejoin_delta can detect pruning
+        let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
+
+        // Check the ballot against the poll before sending: a malformed or
+        // late vote is accepted by the contract but never counted, so it
+        // would otherwise report success while changing nothing.
+        let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+        let poll_msg = room_state
+            .recent_messages
+            .display_messages()
+            .find(|m| m.id() == poll_message_id)
+            .ok_or_else(|| anyhow!("Poll not found in recent messages"))?;
+        let poll = poll_content_with_secrets(poll_msg, &secrets)
+            .ok_or_else(|| anyhow!("Message is not a poll, or it cannot be decrypted"))?;
+        let now = std::time::SystemTime::now();
+        if !poll.is_open_at(now) {
+            return Err(anyhow!("This poll is closed"));
+        }
+        if !poll.accepts_choices(&choices) {
+            return Err(anyhow!(
+                "Invalid choice for this poll: pick {} of options 1-{} without repeats",
+                if poll.multiple_choice { "any" } else { "one" },
+                poll.options.len()
+            ));
+        }
+
+        let invitation_secrets = self.storage.get_invitation_secrets(room_owner_key)?;
+        let content = crate::private_room::build_action_body(
+            &room_state,
+            &signing_key,
+            &invitation_secrets,
+            river_core::room_state::content::ActionContentV1::poll_vote(poll_message_id, choices),
+        )
+        .map_err(|e| anyhow!(e))?;
+
+        let message = river_core::room_state::message::MessageV1 {
+            room_owner: MemberId::from(*room_owner_key),
+            author: author_member_id(&signing_key),
+            content,
+            time: now,
+        };
+        let auth_message =
+            river_core::room_state::message::AuthorizedMessageV1::new(message, &signing_key);
+
+        // Check if we need to re-add ourselves (pruned for inactivity)
+        let (members_delta, member_info_delta) =
+            self.build_rejoin_delta(&room_state, room_owner_key, &signing_key);
+
+        let delta = ChatRoomStateV1Delta {
+            recent_messages: Some(vec![auth_message]),
+            members: members_delta,
+            member_info: member_info_delta,
+            ..Default::default()
+        };
+
+        // Apply the delta to our local state for validation
+        let params = ChatRoomParametersV1 {
+            owner: *room_owner_key,
+        };
+        room_state
+            .apply_delta(&room_state.clone(), &params, &Some(delta.clone()))
+            .map_err(|e| anyhow!("Failed to apply vote delta: {:?}", e))?;
+
+        self.storage.update_room_state(room_owner_key, room_state)?;
+        self.send_delta(room_owner_key, delta).await
+    }
+
+    /// Post a signed leave event, which removes this member from the room
+    /// once it is applied (see `MessagesV1::departed_members`). Returns
+    /// `false` without sending anything if there is nothing to leave: the
+    /// owner is not a member, and a member already pruned is gone already.
+    pub async fn announce_leave(&self, room_owner_key: &VerifyingKey) -> Result<bool> {
+        info!(
+            "Leaving room owned by: {}",
+            bs58::encode(room_owner_key.as_bytes()).into_string()
+        );
+
+        let (signing_key, _, _contract_key_str) = self
+            .storage
+            .get_room(room_owner_key)?
+            .ok_or_else(|| anyhow!("Room not found in local storage."))?;
+        if signing_key.verifying_key() == *room_owner_key {
+            return Ok(false);
+        }
+
+        let mut room_state = self.get_room(room_owner_key, false).await?;
+        if !room_has_member_key(&room_state, room_owner_key, &signing_key.verifying_key()) {
+            return Ok(false);
+        }
+        let self_id = author_member_id(&signing_key);
+
+        // Like join events, leave events stay public in a private room: they
+        // carry nothing secret, and the contract has to read them.
+        let message = river_core::room_state::message::MessageV1 {
+            room_owner: MemberId::from(*room_owner_key),
+            author: self_id,
+            content: river_core::room_state::message::RoomMessageBody::leave_event(),
+            time: std::time::SystemTime::now(),
+        };
+        let auth_message =
+            river_core::room_state::message::AuthorizedMessageV1::new(message, &signing_key);
+        let delta = ChatRoomStateV1Delta {
+            recent_messages: Some(vec![auth_message]),
+            ..Default::default()
+        };
+
+        // Apply the delta to our local state for validation
+        let params = ChatRoomParametersV1 {
+            owner: *room_owner_key,
+        };
+        room_state
+            .apply_delta(&room_state.clone(), &params, &Some(delta.clone()))
+            .map_err(|e| anyhow!("Failed to apply leave delta: {:?}", e))?;
+
+        self.send_delta(room_owner_key, delta).await?;
+        Ok(true)
+    }
+
+    /// Pin (`pinned = true`) or unpin a message. Only the room owner and
+    /// owner-appointed deputies may do this.
+    pub async fn set_pinned(
+        &self,
+        room_owner_key: &VerifyingKey,
+        target_message_id: river_core::room_state::message::MessageId,
+        pinned: bool,
+    ) -> Result<()> {
+        info!(
+            "{} message in room owned by: {}",
+            if pinned { "Pinning" } else { "Unpinning" },
+            bs58::encode(room_owner_key.as_bytes()).into_string()
+        );
+
+        // Get signing key from storage
+        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to pin messages.")
+        })?;
+        let (signing_key, _, _contract_key_str) = room_data;
+
+        // Fetch fresh state from network so build_rejoin_delta can detect pruning
+        let mut room_state = self.get_room(room_owner_key, false).await?;
+
+        // The contract silently drops pins from anyone else, so refuse up
+        // front rather than report a success that changes nothing.
+        let self_id = author_member_id(&signing_key);
+        let owner_id = MemberId::from(*room_owner_key);
+        if !river_core::room_state::member::MembersV1::is_pin_authorized(
+            self_id,
+            &room_state.members.members_by_member_id(),
+            &room_state.member_info,
+            owner_id,
+        ) {
+            return Err(anyhow!(
+                "Only the room owner and owner-appointed deputies can pin messages"
+            ));
+        }
+        if !room_state
+            .recent_messages
+            .display_messages()
+            .any(|m| m.id() == target_message_id)
+        {
+            return Err(anyhow!("Message not found in recent messages"));
+        }
+
+        // Pin actions are always public, even in a private room: the contract
+        // reads them to keep pinned messages past the retention cap.
+        let content = if pinned {
+            river_core::room_state::message::RoomMessageBody::pin(target_message_id)
+        } else {
+            river_core::room_state::message::RoomMessageBody::unpin(target_message_id)
+        };
+        let message = river_core::room_state::message::MessageV1 {
+            room_owner: owner_id,
+            author: self_id,
+            content,
+            time: std::time::SystemTime::now(),
+        };
+        let auth_message =
+            river_core::room_state::message::AuthorizedMessageV1::new(message, &signing_key);
+
+        // Check if we need to re-add ourselves (pruned for inactivity)
+        let (members_delta, member_info_delta) =
+            self.build_rejoin_delta(&room_state, room_owner_key, &signing_key);
+
+        let delta = ChatRoomStateV1Delta {
+            recent_messages: Some(vec![auth_message]),
+            members: members_delta,
+            member_info: member_info_delta,
+            ..Default::default()
+        };
+
+        // Apply the delta to our local state for validation
+        let params = ChatRoomParametersV1 {
+            owner: *room_owner_key,
+        };
+        room_state
+            .apply_delta(&room_state.clone(), &params, &Some(delta.clone()))
+            .map_err(|e| anyhow!("Failed to apply pin delta: {:?}", e))?;
+
+        self.storage.update_room_state(room_owner_key, room_state)?;
+        self.send_delta(room_owner_key, delta).await
+    }
+
+    /// Reply to a message
+    pub async fn send_reply(
+        &self,
+        room_owner_key: &VerifyingKey,
+        target_message_id: river_core::room_state::message::MessageId,
+        reply_text: String,
+    ) -> Result<()> {
+        info!(
+            "Sending reply in room owned by: {}",
+            bs58::encode(room_owner_key.as_bytes()).into_string()
+        );
+
+        // Get signing key from storage
+        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to send replies.")
+        })?;
+        let (signing_key, _, _contract_key_str) = room_data;
+
+        // Fetch fresh state from network so build_rejoin_delta can detect pruning
+        let mut room_state = self.get_room(room_owner_key, false).await?;
+        check_slow_mode(&room_state, room_owner_key, &signing_key)?;
+        check_posting_policy(&room_state, room_owner_key, &signing_key)?;
+
+        // Decrypt the room's private content BEFORE selecting the reply target:
+        // this rebuilds `actions_state` from the decrypted private edit/delete
+        // actions, so a target the user already deleted is correctly excluded
+        // (`display_messages()` hides it) and an edited target's quoted preview
+        // reflects the edit rather than the stale original. Returns the secrets
+        // used to decrypt the preview body below (empty map / no-op for a public
+        // room, so behaviour there is unchanged).
+        let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+
+        // Find the target message to extract author name and content preview
+        let target_msg = room_state
+            .recent_messages
+            .display_messages()
+            .find(|m| m.id() == target_message_id)
+            .ok_or_else(|| {
+                anyhow!("Target message not found in recent messages. Cannot reply to expired messages via CLI.")
+            })?;
+
+        // Decrypt the target author's nickname with the room secrets — in a
+        // private room this is sealed, and `build_reply_body` seals it into
+        // `ReplyContentV1.target_author_name` and PERSISTS it to contract state.
+        // Without decrypting here, the reply's quoted author would read
+        // "[Encrypted: N bytes, vN]" to every reader (including the UI) forever.
+        let target_author_name = room_state
+            .member_info
+            .canonical(target_msg.message.author)
+            .map(|info| unseal_nickname_display(&info.member_info.preferred_nickname, &secrets))
+            .unwrap_or_else(|| target_msg.message.author.to_string());
+
+        // Quote the target's plaintext. A PRIVATE target body is decrypted via
+        // `secrets`; without this the CLI sealed "<encrypted>" into the reply's
+        // `ReplyContentV1`, so the quoted context read "<encrypted>" to every
+        // reader forever.
+        let target_content_preview: String =
+            message_display_text_with_secrets(&room_state, target_msg, &secrets)
+                .chars()
+                .take(100)
+                .collect();
+
+        // Resolve any bare @nickname mentions in the reply body.
+        let reply_text = resolve_outgoing_mentions(&room_state, &reply_text);
+
+        // Build the reply body — plaintext (public) or AES-256-GCM sealed
+        // (private). See `edit_message` for the storage / secret rationale. The
+        // target author name and content preview are sealed alongside the reply
+        // text in a private room (they are part of `ReplyContentV1`), so the
+        // reply context is not leaked in the clear.
+        let invitation_secrets = self.storage.get_invitation_secrets(room_owner_key)?;
+        let content = crate::private_room::build_reply_body(
+            &room_state,
+            &signing_key,
+            &invitation_secrets,
+            reply_text,
+            target_message_id,
+            target_author_name,
+            target_content_preview,
+        )
+        .map_err(|e| anyhow!(e))?;
+
+        // Create the reply message
+        let message = river_core::room_state::message::MessageV1 {
+            room_owner: MemberId::from(*room_owner_key),
+            author: author_member_id(&signing_key),
+            content,
+            time: std::time::SystemTime::now(),
+        };
+
+        // Sign the message
+        let auth_message =
+            river_core::room_state::message::AuthorizedMessageV1::new(message, &signing_key);
+
+        // Check if we need to re-add ourselves (pruned for inactivity)
+        let (members_delta, member_info_delta) =
+            self.build_rejoin_delta(&room_state, room_owner_key, &signing_key);
+
+        // Create a delta with the reply message
+        let delta = ChatRoomStateV1Delta {
+            recent_messages: Some(vec![auth_message]),
+            members: members_delta,
+            member_info: member_info_delta,
+            ..Default::default()
+        };
+
+        // Apply the delta to our local state for validation
+        let params = ChatRoomParametersV1 {
+            owner: *room_owner_key,
+        };
+        room_state
+            .apply_delta(&room_state.clone(), &params, &Some(delta.clone()))
+            .map_err(|e| anyhow!("Failed to apply reply delta: {:?}", e))?;
+
+        // Update the stored state
+        self.storage.update_room_state(room_owner_key, room_state)?;
+
+        // Send the delta to the network
+        self.send_delta(room_owner_key, delta).await
+    }
+
+    /// Helper to send a delta to the network.
+    /// Assumes migration has already been triggered by the caller (via get_room
+    /// or ensure_room_migrated), so owner_vk_to_contract_key returns the correct key.
+    async fn send_delta(
+        &self,
+        room_owner_key: &VerifyingKey,
+        delta: ChatRoomStateV1Delta,
+    ) -> Result<()> {
+        let contract_key = self.owner_vk_to_contract_key(room_owner_key);
+
+        // Serialize the delta
+        let delta_bytes = {
+            let mut buf = Vec::new();
+            ciborium::ser::into_writer(&delta, &mut buf)
+                .map_err(|e| anyhow!("Failed to serialize delta: {}", e))?;
+            buf
+        };
+
+        let update_request = ContractRequest::Update {
+            key: contract_key,
+            data: UpdateData::Delta(delta_bytes.into()),
+        };
+
+        let client_request = ClientRequest::ContractOp(update_request);
+
+        let mut web_api = self.web_api.lock().await;
+        web_api
+            .send(client_request)
+            .await
+            .map_err(|e| anyhow!("Failed to send update request: {}", e))?;
+
+        // Wait for response
+        let response =
+            match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
+                Ok(Ok(response)) => response,
+                Ok(Err(e)) => return Err(anyhow!("Failed to receive response: {}", e)),
+                Err(_) => {
+                    return Err(anyhow!(
+                        "Timeout waiting for update response after 60 seconds"
+                    ))
+                }
+            };
+
+        match response {
+            HostResponse::ContractResponse(ContractResponse::UpdateResponse { key, .. }) => {
+                info!("Action sent successfully to contract: {}", key.id());
+                Ok(())
+            }
+            _ => Err(anyhow!("Unexpected response type: {:?}", response)),
+        }
+    }
+
+    /// Stream messages from a room by polling for updates
+    pub async fn stream_messages(
+        &self,
+        room_owner_key: &VerifyingKey,
+        poll_interval_ms: u64,
+        timeout_secs: u64,
+        max_messages: usize,
+        initial_messages: usize,
+        format: OutputFormat,
+    ) -> Result<()> {
+        // Get the contract key for the room
+        let room = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found in local storage. You may need to create or join it first.")
+        })?;
+
+        let (_signing_key, _room_state, contract_key_str) = room;
+        let _contract_key = contract_key_str.clone();
+
+        // Print header for human format
+        if matches!(format, OutputFormat::Human) {
+            eprintln!(
+                "Streaming messages from room {} (press Ctrl+C to stop)...\n",
+                bs58::encode(room_owner_key.as_bytes()).into_string()
+            );
+        }
+
+        // Track seen messages: key -> last-emitted effective content, so a later
+        // edit (content change) is detected and re-emitted, not just new ids.
+        let mut seen_messages: HashMap<String, String> = HashMap::new();
+        // Messages for which a deletion has already been emitted (one-shot). The
+        // polling path needs NO startup pre-seed (unlike subscribe): it only ever
+        // inserts into `seen` via `display_messages()` (initial window + each
+        // poll's emit_new_and_edited), which excludes deleted messages — so a
+        // pre-existing deletion is never in `seen` and `should_emit_deletion`
+        // returns false for it. (A future change that seeds `seen` from raw
+        // `messages` here would need a pre-seed like the subscribe path's.)
+        let mut deleted_emitted: HashSet<String> = HashSet::new();
+        // Reactions fingerprint per SURFACED message, so a reaction added/removed
+        // AFTER the message was streamed surfaces as a `reaction` event
+        // (freenet/river#325). Seeded for messages shown at start (below) and for
+        // messages emitted live by emit_new_and_edited (which seeds as it emits);
+        // emit_reaction_changes only acts on messages already in this map, so a
+        // brand-new message's initial reactions are not re-emitted as a change.
+        let mut seen_reactions: HashMap<String, String> = HashMap::new();
+        let mut new_message_count = 0;
+        let start_time = std::time::Instant::now();
+
+        // Show initial messages if requested
+        if initial_messages > 0 {
+            let mut room_state = self.get_room(room_owner_key, false).await?;
+            // Decrypt private-room content for display (no-op for public rooms).
+            let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+            self.hide_blocked_authors(room_owner_key, &mut room_state);
+
+            // Use display_messages() to filter out action/deleted messages (matches `message list`)
+            let all_msgs: Vec<_> = room_state.recent_messages.display_messages().collect();
+            let start = all_msgs.len().saturating_sub(initial_messages);
+
+            for msg in &all_msgs[start..] {
+                let key = monitor_seen_key(msg);
+                seen_messages.insert(
+                    key.clone(),
+                    message_display_text_with_secrets(&room_state, msg, &secrets),
+                );
+                // Seed the reactions fingerprint for shown messages so reactions
+                // already present at startup aren't re-emitted as a live change;
+                // only later changes to them surface.
+                seen_reactions.insert(
+                    key,
+                    reactions_fingerprint(room_state.recent_messages.reactions(&msg.id())),
+                );
+
+                Self::output_message(&room_state, msg, room_owner_key, &format, false, &secrets)?;
+            }
+        }
+
+        // Set up Ctrl+C handler
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
+
+        // Spawn task to handle Ctrl+C
+        tokio::spawn(async move {
+            tokio::signal::ctrl_c().await.ok();
+            let _ = shutdown_tx.send(()).await;
+        });
+
+        // Main polling loop
+        loop {
+            // Check for shutdown signal
+            if shutdown_rx.try_recv().is_ok() {
+                if matches!(format, OutputFormat::Human) {
+                    eprintln!("\nStopped monitoring.");
+                }
+                return Ok(());
+            }
+
+            // Check timeout
+            if timeout_secs > 0 && start_time.elapsed().as_secs() >= timeout_secs {
+                debug!("Timeout reached, exiting stream");
+                return Ok(());
+            }
+
+            // Check max messages
+            if max_messages > 0 && new_message_count >= max_messages {
+                debug!("Maximum message count reached, exiting stream");
+                return Ok(());
+            }
+
+            // Poll for new + edited messages. emit_new_and_edited re-emits a
+            // message whose effective content changed (an edit) and emits ones
+            // not seen before; it respects max_messages for NEW messages.
+            match self.get_room(room_owner_key, false).await {
+                Ok(mut room_state) => {
+                    // Decrypt private-room content for display (no-op for public rooms).
+                    let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+                    self.hide_blocked_authors(room_owner_key, &mut room_state);
+                    Self::emit_new_and_edited(
+                        &room_state,
+                        &mut seen_messages,
+                        &mut deleted_emitted,
+                        &mut seen_reactions,
+                        room_owner_key,
+                        &format,
+                        max_messages,
+                        &mut new_message_count,
+                        &secrets,
+                    )?;
+                    Self::emit_deletions(
+                        &room_state,
+                        &seen_messages,
+                        &mut deleted_emitted,
+                        room_owner_key,
+                        &format,
+                        &secrets,
+                    )?;
+                    // Surface reactions added/removed since a message was already
+                    // streamed. Runs AFTER emit_new_and_edited so a brand-new
+                    // message is seeded (not re-emitted) on the same poll.
+                    Self::emit_reaction_changes(
+                        &room_state,
+                        &mut seen_reactions,
+                        room_owner_key,
+                        &format,
+                        &secrets,
+                    )?;
+                    if max_messages > 0 && new_message_count >= max_messages {
+                        return Ok(());
+                    }
+                }
+                Err(e) => {
+                    // Log error but continue polling
+                    debug!("Error fetching room state: {}", e);
+                }
+            }
+
+            // Wait for next poll interval
+            tokio::time::sleep(std::time::Duration::from_millis(poll_interval_ms)).await;
+        }
+    }
+
+    /// Scan the room's display messages and emit any that are NEW or whose
+    /// effective content changed (an EDIT) since last seen. `seen` maps each
+    /// message's dedup key to the content last emitted for it, so a later edit
+    /// is detected as a content change. `new_count` is incremented only for new
+    /// messages (edits don't count toward `max_new`); when `max_new > 0` the
     /// scan stops once that many new messages have been emitted this session.
     ///
-    /// This is the shared core of both monitor paths (polling `stream_messages`
-    /// and subscription-based `monitor`); before it, edits never surfaced in
-    /// either stream because dedup keyed on identity alone. freenet/river —
-    /// Rogue Worm report.
-    // The monitor's per-message tracking state (seen content, emitted deletions,
-    // seeded reaction fingerprints) is passed as separate &mut maps rather than a
-    // bundled struct to keep the data-flow of each tracking dimension explicit at
-    // the call sites — matching the existing edit/deletion design.
-    #[allow(clippy::too_many_arguments)]
-    fn emit_new_and_edited(
+    /// This is the shared core of both monitor paths (polling `stream_messages`
+    /// and subscription-based `monitor`); before it, edits never surfaced in
+    /// either stream because dedup keyed on identity alone. freenet/river —
+    /// Rogue Worm report.
+    // The monitor's per-message tracking state (seen content, emitted deletions,
+    // seeded reaction fingerprints) is passed as separate &mut maps rather than a
+    // bundled struct to keep the data-flow of each tracking dimension explicit at
+    // the call sites — matching the existing edit/deletion design.
+    #[allow(clippy::too_many_arguments)]
+    fn emit_new_and_edited(
+        room_state: &ChatRoomStateV1,
+        seen: &mut HashMap<String, String>,
+        deleted_emitted: &mut HashSet<String>,
+        seen_reactions: &mut HashMap<String, String>,
+        room_owner_key: &VerifyingKey,
+        format: &OutputFormat,
+        max_new: usize,
+        new_count: &mut usize,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Result<()> {
+        for msg in room_state.recent_messages.display_messages() {
+            let key = monitor_seen_key(msg);
+            let content = message_display_text_with_secrets(room_state, msg, secrets);
+            let is_edit = match classify_seen(seen, &key, &content) {
+                EmitKind::Unchanged => continue,
+                EmitKind::Edited => true,
+                EmitKind::New => false,
+            };
+            Self::output_message(room_state, msg, room_owner_key, format, is_edit, secrets)?;
+            // Surfacing a message (showing it new OR as an edit) lifts any
+            // start-time delete-suppression: a now-surfaced message's later
+            // deletion MUST be reportable. Without this, an unshown pre-existing
+            // message edited then deleted live would emit the edit but silently
+            // swallow the delete (#324 re-review).
+            deleted_emitted.remove(&key);
+            // Surfacing a message also seeds its reactions fingerprint, so a
+            // reaction added AFTER this point surfaces (emit_reaction_changes only
+            // acts on surfaced messages), while the reactions carried by the
+            // message/edit event just emitted are NOT re-emitted as a change. The
+            // current fingerprint is the right seed: the reactions output here are
+            // current, so emit_reaction_changes sees no change on this pass.
+            // freenet/river#325.
+            seen_reactions.insert(
+                key.clone(),
+                reactions_fingerprint(room_state.recent_messages.reactions(&msg.id())),
+            );
+            seen.insert(key, content);
+            if !is_edit {
+                *new_count += 1;
+                if max_new > 0 && *new_count >= max_new {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit a deletion event for any previously-surfaced message that has since
+    /// been deleted (once per message). Deleted messages are excluded from
+    /// `display_messages`, so `emit_new_and_edited` never sees them — this is
+    /// the only path that surfaces a deletion to the stream.
+    ///
+    /// `deleted_emitted` doubles as the suppression set: it is pre-seeded with
+    /// every pre-existing message NOT shown at start, and `emit_new_and_edited`
+    /// removes a key when it later surfaces that message (so its deletion
+    /// becomes reportable). A key is added here once its deletion is emitted, so
+    /// the event fires at most once. freenet/river#323 (#324 review).
+    fn emit_deletions(
+        room_state: &ChatRoomStateV1,
+        seen: &HashMap<String, String>,
+        deleted_emitted: &mut HashSet<String>,
+        room_owner_key: &VerifyingKey,
+        format: &OutputFormat,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Result<()> {
+        // We can only report a deletion while the original message is still in
+        // the recent-messages window. If a message is pruned out of the window
+        // (max_recent_messages) and only then deleted, no event is emitted —
+        // acceptable for a bounded live stream.
+        for msg in &room_state.recent_messages.messages {
+            if !room_state.recent_messages.is_deleted(&msg.id()) {
+                continue;
+            }
+            let key = monitor_seen_key(msg);
+            if should_emit_deletion(seen, deleted_emitted, &key) {
+                Self::output_deletion(room_state, msg, room_owner_key, format, secrets)?;
+                deleted_emitted.insert(key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit a `reaction` event for any SURFACED message whose reactions changed
+    /// since last recorded (a reaction added or removed live, after the message
+    /// was already streamed). `seen_reactions` holds a fingerprint ONLY for
+    /// surfaced messages — those shown at start, or emitted live by
+    /// `emit_new_and_edited` (which seeds the fingerprint as it emits). A message
+    /// absent from the map was never surfaced (e.g. room history outside the
+    /// `--subscribe` initial window), so a reaction to it is NOT emitted — the
+    /// same "only for messages the stream displayed" rule the deletion path
+    /// follows (#324). Only a *change* to a surfaced message emits.
+    ///
+    /// This is the reaction counterpart of `emit_new_and_edited` (edits) and
+    /// `emit_deletions` (deletions). It must run AFTER `emit_new_and_edited` so a
+    /// brand-new message that path just emitted (and seeded) is already in
+    /// `seen_reactions`, and the reactions it carried are never re-emitted as a
+    /// spurious change. freenet/river#325.
+    fn emit_reaction_changes(
+        room_state: &ChatRoomStateV1,
+        seen_reactions: &mut HashMap<String, String>,
+        room_owner_key: &VerifyingKey,
+        format: &OutputFormat,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Result<()> {
+        for msg in room_state.recent_messages.display_messages() {
+            let key = monitor_seen_key(msg);
+            let fingerprint =
+                reactions_fingerprint(room_state.recent_messages.reactions(&msg.id()));
+            match classify_reaction(seen_reactions, &key, &fingerprint) {
+                // Not surfaced by the stream → never seed or emit here.
+                ReactionEmit::NotSurfaced => continue,
+                ReactionEmit::Unchanged => continue,
+                ReactionEmit::Changed => {
+                    Self::output_reaction_change(room_state, msg, room_owner_key, format, secrets)?;
+                }
+            }
+            seen_reactions.insert(key, fingerprint);
+        }
+        Ok(())
+    }
+
+    /// Emit a `reaction` event carrying the message's *current* reactions map
+    /// (`emoji -> count`), so a downstream relay can reconcile the new state
+    /// without tracking per-reactor deltas. JSON `type: "reaction"`; the human
+    /// line is `[reaction]`-prefixed. The map is empty when the last reaction was
+    /// removed. freenet/river#325.
+    fn output_reaction_change(
+        room_state: &ChatRoomStateV1,
+        msg: &river_core::room_state::message::AuthorizedMessageV1,
+        room_owner_key: &VerifyingKey,
+        format: &OutputFormat,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Result<()> {
+        let msg_id = msg.id();
+        let reactions = room_state.recent_messages.reactions(&msg_id);
+        let author_str = msg.message.author.to_string();
+        let nickname = room_state
+            .member_info
+            .canonical(msg.message.author)
+            .map(|info| unseal_nickname_display(&info.member_info.preferred_nickname, secrets));
+        let datetime: DateTime<Utc> = msg.message.time.into();
+
+        match format {
+            OutputFormat::Human => {
+                let local_time: DateTime<Local> = datetime.into();
+                let display_name = nickname
+                    .clone()
+                    .unwrap_or_else(|| author_str.chars().take(8).collect());
+                let reactions_str = reactions
+                    .map(|r| {
+                        if r.is_empty() {
+                            "(none)".to_string()
+                        } else {
+                            let parts: Vec<_> = r
+                                .iter()
+                                .map(|(emoji, reactors)| format!("{}×{}", emoji, reactors.len()))
+                                .collect();
+                            parts.join(" ")
+                        }
+                    })
+                    .unwrap_or_else(|| "(none)".to_string());
+                println!(
+                    "[reaction] [{} - {}]: {}",
+                    local_time.format("%H:%M:%S"),
+                    display_name,
+                    reactions_str
+                );
+            }
+            OutputFormat::Json => {
+                let json_msg = reaction_event_json(room_state, msg, room_owner_key, secrets);
+                println!("{}", serde_json::to_string(&json_msg)?);
+            }
+        }
+        std::io::stdout().flush()?;
+        Ok(())
+    }
+
+    /// Emit a deletion event (the message's content is gone, so only its
+    /// identity/author/time are reported). JSON `type: "delete"`; human line is
+    /// `[deleted]`-prefixed. freenet/river#323.
+    fn output_deletion(
+        room_state: &ChatRoomStateV1,
+        msg: &river_core::room_state::message::AuthorizedMessageV1,
+        room_owner_key: &VerifyingKey,
+        format: &OutputFormat,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Result<()> {
+        let author_str = msg.message.author.to_string();
+        let nickname = room_state
+            .member_info
+            .canonical(msg.message.author)
+            .map(|info| unseal_nickname_display(&info.member_info.preferred_nickname, secrets));
+        let datetime: DateTime<Utc> = msg.message.time.into();
+
+        match format {
+            OutputFormat::Human => {
+                let local_time: DateTime<Local> = datetime.into();
+                let display_name = nickname
+                    .clone()
+                    .unwrap_or_else(|| author_str.chars().take(8).collect());
+                println!(
+                    "[deleted] [{} - {}]: (message deleted)",
+                    local_time.format("%H:%M:%S"),
+                    display_name
+                );
+            }
+            OutputFormat::Json => {
+                let json_msg = deletion_event_json(room_state, msg, room_owner_key, secrets);
+                println!("{}", serde_json::to_string(&json_msg)?);
+            }
+        }
+        std::io::stdout().flush()?;
+        Ok(())
+    }
+
+    /// Helper function to output a message in the requested format.
+    ///
+    /// `is_edit` marks a re-emission of a message whose content changed since it
+    /// was first streamed (the monitor's edit detection): the JSON `type`
+    /// becomes `"edit"` and the human line is prefixed so a downstream relay can
+    /// tell an edit from a fresh message.
+    ///
+    /// Note `type: "edit"` differs from the `edited` boolean: `edited` is true
+    /// whenever an edit action exists for the message (so a message already
+    /// edited *before* the stream first saw it is emitted once as
+    /// `type: "message"` with `edited: true`), whereas `type: "edit"` marks a
+    /// re-emission triggered by a content change observed live.
+    fn output_message(
         room_state: &ChatRoomStateV1,
-        seen: &mut HashMap<String, String>,
-        deleted_emitted: &mut HashSet<String>,
-        seen_reactions: &mut HashMap<String, String>,
+        msg: &river_core::room_state::message::AuthorizedMessageV1,
         room_owner_key: &VerifyingKey,
         format: &OutputFormat,
-        max_new: usize,
-        new_count: &mut usize,
+        is_edit: bool,
         secrets: &HashMap<u32, [u8; 32]>,
     ) -> Result<()> {
-        for msg in room_state.recent_messages.display_messages() {
-            let key = monitor_seen_key(msg);
-            let content = message_display_text_with_secrets(room_state, msg, secrets);
-            let is_edit = match classify_seen(seen, &key, &content) {
-                EmitKind::Unchanged => continue,
-                EmitKind::Edited => true,
-                EmitKind::New => false,
-            };
-            Self::output_message(room_state, msg, room_owner_key, format, is_edit, secrets)?;
-            // Surfacing a message (showing it new OR as an edit) lifts any
-            // start-time delete-suppression: a now-surfaced message's later
-            // deletion MUST be reportable. Without this, an unshown pre-existing
-            // message edited then deleted live would emit the edit but silently
-            // swallow the delete (#324 re-review).
-            deleted_emitted.remove(&key);
-            // Surfacing a message also seeds its reactions fingerprint, so a
-            // reaction added AFTER this point surfaces (emit_reaction_changes only
-            // acts on surfaced messages), while the reactions carried by the
-            // message/edit event just emitted are NOT re-emitted as a change. The
-            // current fingerprint is the right seed: the reactions output here are
-            // current, so emit_reaction_changes sees no change on this pass.
-            // freenet/river#325.
-            seen_reactions.insert(
-                key.clone(),
-                reactions_fingerprint(room_state.recent_messages.reactions(&msg.id())),
-            );
-            seen.insert(key, content);
-            if !is_edit {
-                *new_count += 1;
-                if max_new > 0 && *new_count >= max_new {
-                    return Ok(());
-                }
+        // Get display content (handles edits and non-text public content like
+        // join events; `secrets` decrypts private-room bodies — only a body
+        // whose secret is unavailable renders as "<encrypted>")
+        let content = message_display_text_with_secrets(room_state, msg, secrets);
+
+        // Get message ID for checking edited status and reactions
+        let msg_id = msg.id();
+        let edited = room_state.recent_messages.is_edited(&msg_id);
+        let reactions = room_state.recent_messages.reactions(&msg_id);
+        let reply = reply_context_display_with_secrets(room_state, msg, secrets);
+
+        match format {
+            OutputFormat::Human => {
+                let author_str = msg.message.author.to_string();
+                let author_short = author_str.chars().take(8).collect::<String>();
+
+                // Get nickname if available (decrypted for a private room)
+                let nickname = room_state
+                    .member_info
+                    .canonical(msg.message.author)
+                    .map(|info| {
+                        unseal_nickname_display(&info.member_info.preferred_nickname, secrets)
+                    })
+                    .unwrap_or(author_short);
+
+                let datetime: DateTime<Utc> = msg.message.time.into();
+                let local_time: DateTime<Local> = datetime.into();
+
+                let edited_indicator = if edited { " (edited)" } else { "" };
+                // Re-emission of an edited message — distinguish it from a fresh
+                // one for a downstream relay reading the human stream.
+                let edit_prefix = if is_edit { "[edit] " } else { "" };
+                let reply_prefix = reply_prefix_display(&reply);
+                let reactions_str = reactions
+                    .map(|r| {
+                        if r.is_empty() {
+                            String::new()
+                        } else {
+                            let parts: Vec<_> = r
+                                .iter()
+                                .map(|(emoji, reactors)| format!("{}×{}", emoji, reactors.len()))
+                                .collect();
+                            format!(" [{}]", parts.join(" "))
+                        }
+                    })
+                    .unwrap_or_default();
+
+                println!(
+                    "{}[{} - {}]: {}{}{}{}",
+                    edit_prefix,
+                    local_time.format("%H:%M:%S"),
+                    nickname,
+                    reply_prefix,
+                    content,
+                    edited_indicator,
+                    reactions_str
+                );
+            }
+            OutputFormat::Json => {
+                let json_msg =
+                    message_event_json(room_state, msg, room_owner_key, is_edit, secrets);
+                println!("{}", serde_json::to_string(&json_msg)?);
             }
         }
+
+        // Flush stdout immediately for real-time output
+        std::io::stdout().flush()?;
         Ok(())
     }
 
-    /// Emit a deletion event for any previously-surfaced message that has since
-    /// been deleted (once per message). Deleted messages are excluded from
-    /// `display_messages`, so `emit_new_and_edited` never sees them — this is
-    /// the only path that surfaces a deletion to the stream.
-    ///
-    /// `deleted_emitted` doubles as the suppression set: it is pre-seeded with
-    /// every pre-existing message NOT shown at start, and `emit_new_and_edited`
-    /// removes a key when it later surfaces that message (so its deletion
-    /// becomes reportable). A key is added here once its deletion is emitted, so
-    /// the event fires at most once. freenet/river#323 (#324 review).
-    fn emit_deletions(
-        room_state: &ChatRoomStateV1,
-        seen: &HashMap<String, String>,
-        deleted_emitted: &mut HashSet<String>,
+    /// Set the current user's nickname in a room
+    pub async fn set_nickname(
+        &self,
         room_owner_key: &VerifyingKey,
-        format: &OutputFormat,
-        secrets: &HashMap<u32, [u8; 32]>,
+        new_nickname: String,
     ) -> Result<()> {
-        // We can only report a deletion while the original message is still in
-        // the recent-messages window. If a message is pruned out of the window
-        // (max_recent_messages) and only then deleted, no event is emitted —
-        // acceptable for a bounded live stream.
-        for msg in &room_state.recent_messages.messages {
-            if !room_state.recent_messages.is_deleted(&msg.id()) {
-                continue;
-            }
-            let key = monitor_seen_key(msg);
-            if should_emit_deletion(seen, deleted_emitted, &key) {
-                Self::output_deletion(room_state, msg, room_owner_key, format, secrets)?;
-                deleted_emitted.insert(key);
+        info!(
+            "Setting nickname to '{}' in room owned by: {}",
+            new_nickname,
+            bs58::encode(room_owner_key.as_bytes()).into_string()
+        );
+
+        // Get signing key from storage
+        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to change your nickname.")
+        })?;
+        let (signing_key, _, _contract_key_str) = room_data;
+
+        // Fetch fresh state from network so build_rejoin_delta can detect pruning
+        let mut room_state = self.get_room(room_owner_key, false).await?;
+
+        let my_member_id = signing_key.verifying_key().into();
+
+        // Seal the nickname for the room's privacy mode. In a PRIVATE room the
+        // nickname MUST be AES-256-GCM sealed under the room secret — sending
+        // it as plaintext (the previous unconditional `SealedBytes::public`)
+        // silently deanonymised the member: the contract's `member_info`
+        // validation only checks signature + declared length, so the plaintext
+        // was accepted and published into the private room's state for every
+        // peer to read. Errors (rather than leaks) if the secret isn't available
+        // yet.
+        let invitation_secrets = self.storage.get_invitation_secrets(room_owner_key)?;
+        let secrets = crate::private_room::collect_secrets_for_room(
+            &room_state,
+            &signing_key,
+            &invitation_secrets,
+        );
+        let sealed_nickname = crate::private_room::seal_field_for_room(
+            &room_state,
+            &secrets,
+            new_nickname.as_bytes(),
+        )
+        .map_err(|e| anyhow!(e))?;
+
+        // Find our current member info to get the version AND our existing
+        // deputy grants and DM prekey — republishing member_info replaces the
+        // whole signed record, so we must carry `deputies` forward or a nickname
+        // change would silently revoke every deputy we appointed (#410), and
+        // likewise `dm_prekey` or peers would stop opening ratchet sessions
+        // with us. Routes through the shared `resolve_own_member_info_base`
+        // (canonical, #411 round 8 item A) so a duplicate-holding state can't
+        // resurrect a revoked record.
+        let current_self_info = resolve_own_member_info_base(&room_state, my_member_id);
+        let current_version = current_self_info
+            .as_ref()
+            .map(|info| info.version)
+            .unwrap_or(0);
+        let existing_dm_prekey = current_self_info.as_ref().and_then(|info| info.dm_prekey);
+        let existing_deputies = current_self_info
+            .map(|info| info.deputies)
+            .unwrap_or_default();
+
+        // Create new member info with incremented version
+        let new_member_info = MemberInfo {
+            member_id: my_member_id,
+            version: current_version + 1,
+            preferred_nickname: sealed_nickname,
+            deputies: existing_deputies,
+            dm_prekey: existing_dm_prekey,
+        };
+
+        // Sign with our member key
+        let authorized_member_info =
+            AuthorizedMemberInfo::new_with_member_key(new_member_info, &signing_key);
+
+        // Update local state first. Drop ALL existing records for our own
+        // member_id (not just the first match) before pushing the new one — the
+        // new record's version (`current_version + 1`) strictly outranks any
+        // existing duplicate, so this collapses to the same single canonical
+        // record `dedup_to_canonical` would converge on, rather than leaving a
+        // stale duplicate sitting in local state alongside the new republish.
+        room_state
+            .member_info
+            .member_info
+            .retain(|info| info.member_info.member_id != my_member_id);
+        room_state
+            .member_info
+            .member_info
+            .push(authorized_member_info.clone());
+
+        // Save the updated state locally
+        self.storage
+            .update_room_state(room_owner_key, room_state.clone())?;
+
+        // Persist our chosen nickname so a later rejoin (after an inactivity
+        // prune) restores it instead of "Member".
+        self.storage
+            .update_self_nickname(room_owner_key, &new_nickname)?;
+
+        // Check if we need to re-add ourselves (pruned for inactivity)
+        let (members_delta, _) = self.build_rejoin_delta(&room_state, room_owner_key, &signing_key);
+
+        // Create delta with member info update (and members delta if needed)
+        let delta = ChatRoomStateV1Delta {
+            member_info: Some(vec![authorized_member_info]),
+            members: members_delta,
+            ..Default::default()
+        };
+
+        // Serialize the delta
+        let delta_bytes = {
+            let mut buf = Vec::new();
+            ciborium::ser::into_writer(&delta, &mut buf)
+                .map_err(|e| anyhow!("Failed to serialize delta: {}", e))?;
+            buf
+        };
+
+        // Get contract key and send the update
+        let contract_key = self.owner_vk_to_contract_key(room_owner_key);
+
+        let update_request = ContractRequest::Update {
+            key: contract_key,
+            data: UpdateData::Delta(delta_bytes.into()),
+        };
+
+        let client_request = ClientRequest::ContractOp(update_request);
+
+        let mut web_api = self.web_api.lock().await;
+        web_api
+            .send(client_request)
+            .await
+            .map_err(|e| anyhow!("Failed to send update request: {}", e))?;
+
+        // Wait for response
+        let response =
+            match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
+                Ok(Ok(response)) => response,
+                Ok(Err(e)) => return Err(anyhow!("Failed to receive response: {}", e)),
+                Err(_) => {
+                    return Err(anyhow!(
+                        "Timeout waiting for update response after 60 seconds"
+                    ))
+                }
+            };
+
+        match response {
+            HostResponse::ContractResponse(ContractResponse::UpdateResponse { key, .. }) => {
+                info!("Nickname updated successfully for contract: {}", key.id());
+                Ok(())
             }
+            _ => Err(anyhow!("Unexpected response type: {:?}", response)),
         }
-        Ok(())
     }
 
-    /// Emit a `reaction` event for any SURFACED message whose reactions changed
-    /// since last recorded (a reaction added or removed live, after the message
-    /// was already streamed). `seen_reactions` holds a fingerprint ONLY for
-    /// surfaced messages — those shown at start, or emitted live by
-    /// `emit_new_and_edited` (which seeds the fingerprint as it emits). A message
-    /// absent from the map was never surfaced (e.g. room history outside the
-    /// `--subscribe` initial window), so a reaction to it is NOT emitted — the
-    /// same "only for messages the stream displayed" rule the deletion path
-    /// follows (#324). Only a *change* to a surfaced message emits.
+    /// Ban a member from the room
     ///
-    /// This is the reaction counterpart of `emit_new_and_edited` (edits) and
-    /// `emit_deletions` (deletions). It must run AFTER `emit_new_and_edited` so a
-    /// brand-new message that path just emitted (and seeded) is already in
-    /// `seen_reactions`, and the reactions it carried are never re-emitted as a
-    /// spurious change. freenet/river#325.
-    fn emit_reaction_changes(
-        room_state: &ChatRoomStateV1,
-        seen_reactions: &mut HashMap<String, String>,
+    /// The banning member must be either the room owner or an upstream member in the
+    /// invite chain of the member being banned.
+    pub async fn ban_member(
+        &self,
         room_owner_key: &VerifyingKey,
-        format: &OutputFormat,
-        secrets: &HashMap<u32, [u8; 32]>,
+        member_id_short: &str,
     ) -> Result<()> {
-        for msg in room_state.recent_messages.display_messages() {
-            let key = monitor_seen_key(msg);
-            let fingerprint =
-                reactions_fingerprint(room_state.recent_messages.reactions(&msg.id()));
-            match classify_reaction(seen_reactions, &key, &fingerprint) {
-                // Not surfaced by the stream → never seed or emit here.
-                ReactionEmit::NotSurfaced => continue,
-                ReactionEmit::Unchanged => continue,
-                ReactionEmit::Changed => {
-                    Self::output_reaction_change(room_state, msg, room_owner_key, format, secrets)?;
-                }
-            }
-            seen_reactions.insert(key, fingerprint);
-        }
-        Ok(())
+        self.ban_member_with_safety(
+            room_owner_key,
+            member_id_short,
+            BanSafety::default(),
+            None,
+            None,
+        )
+        .await
     }
 
-    /// Emit a `reaction` event carrying the message's *current* reactions map
-    /// (`emoji -> count`), so a downstream relay can reconcile the new state
-    /// without tracking per-reactor deltas. JSON `type: "reaction"`; the human
-    /// line is `[reaction]`-prefixed. The map is empty when the last reaction was
-    /// removed. freenet/river#325.
-    fn output_reaction_change(
-        room_state: &ChatRoomStateV1,
-        msg: &river_core::room_state::message::AuthorizedMessageV1,
+    /// Ban with fail-closed safety predicates evaluated against the same fresh
+    /// room state used to construct the signed ban delta. `reason` is sealed
+    /// for the room's privacy mode like a nickname; `duration` makes the ban
+    /// temporary (see `BansV1::ban_has_expired` for when it lapses).
+    pub async fn ban_member_with_safety(
+        &self,
         room_owner_key: &VerifyingKey,
-        format: &OutputFormat,
-        secrets: &HashMap<u32, [u8; 32]>,
+        member_id_short: &str,
+        safety: BanSafety,
+        reason: Option<&str>,
+        duration: Option<std::time::Duration>,
     ) -> Result<()> {
-        let msg_id = msg.id();
-        let reactions = room_state.recent_messages.reactions(&msg_id);
-        let author_str = msg.message.author.to_string();
-        let nickname = room_state
+        info!(
+            "Banning member '{}' from room owned by: {}",
+            member_id_short,
+            bs58::encode(room_owner_key.as_bytes()).into_string()
+        );
+
+        // Get the signing key from storage
+        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to ban members.")
+        })?;
+        let (signing_key, _stored_state, _contract_key_str) = room_data;
+
+        // Fetch fresh room state from the network
+        let room_state = self.get_room(room_owner_key, false).await?;
+
+        let my_member_id: MemberId = signing_key.verifying_key().into();
+        let owner_member_id: MemberId = room_owner_key.into();
+
+        // Find the member to ban by their short ID (first 8 chars of member_id string)
+        let target_member = room_state
             .member_info
-            .canonical(msg.message.author)
-            .map(|info| unseal_nickname_display(&info.member_info.preferred_nickname, secrets));
-        let datetime: DateTime<Utc> = msg.message.time.into();
+            .member_info
+            .iter()
+            .find(|info| {
+                let member_id_str = info.member_info.member_id.to_string();
+                member_id_str.starts_with(member_id_short)
+                    || member_id_str[..8.min(member_id_str.len())]
+                        .eq_ignore_ascii_case(member_id_short)
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "Member '{}' not found. Use 'member list' to see member IDs.",
+                    member_id_short
+                )
+            })?;
 
-        match format {
-            OutputFormat::Human => {
-                let local_time: DateTime<Local> = datetime.into();
-                let display_name = nickname
-                    .clone()
-                    .unwrap_or_else(|| author_str.chars().take(8).collect());
-                let reactions_str = reactions
-                    .map(|r| {
-                        if r.is_empty() {
-                            "(none)".to_string()
-                        } else {
-                            let parts: Vec<_> = r
-                                .iter()
-                                .map(|(emoji, reactors)| format!("{}×{}", emoji, reactors.len()))
-                                .collect();
-                            parts.join(" ")
-                        }
-                    })
-                    .unwrap_or_else(|| "(none)".to_string());
-                println!(
-                    "[reaction] [{} - {}]: {}",
-                    local_time.format("%H:%M:%S"),
-                    display_name,
-                    reactions_str
-                );
+        let banned_member_id = target_member.member_info.member_id;
+
+        if safety.require_exact_member_id && banned_member_id.to_string() != member_id_short {
+            return Err(anyhow!(
+                "Safety preflight refused: '{}' is not the exact current member ID '{}'.",
+                member_id_short,
+                banned_member_id
+            ));
+        }
+
+        if safety.require_no_descendants {
+            let removal = crate::deputies::ban_removal_set(&room_state, banned_member_id);
+            if removal.len() != 1 {
+                return Err(anyhow!(
+                    "Safety preflight refused: banning '{}' would remove {} descendants.",
+                    banned_member_id,
+                    removal.len().saturating_sub(1)
+                ));
             }
-            OutputFormat::Json => {
-                let reactions_map: std::collections::HashMap<String, usize> = reactions
-                    .map(|r| r.iter().map(|(k, v)| (k.clone(), v.len())).collect())
-                    .unwrap_or_default();
-                let json_msg = json!({
-                    "type": "reaction",
-                    "message_id": msg_id.0 .0.to_string(),
-                    "room": bs58::encode(room_owner_key.as_bytes()).into_string(),
-                    "author": author_str,
-                    "nickname": nickname,
-                    "timestamp": datetime.to_rfc3339(),
-                    "reactions": reactions_map,
-                });
-                println!("{}", serde_json::to_string(&json_msg)?);
+        }
+
+        if safety.require_not_deputy {
+            let is_deputy = room_state.member_info.member_info.iter().any(|record| {
+                room_state
+                    .member_info
+                    .deputies_of(record.member_info.member_id)
+                    .contains(&banned_member_id)
+            });
+            if is_deputy {
+                return Err(anyhow!(
+                    "Safety preflight refused: member '{}' is a deputy.",
+                    banned_member_id
+                ));
             }
         }
-        std::io::stdout().flush()?;
-        Ok(())
-    }
 
-    /// Emit a deletion event (the message's content is gone, so only its
-    /// identity/author/time are reported). JSON `type: "delete"`; human line is
-    /// `[deleted]`-prefixed. freenet/river#323.
-    fn output_deletion(
-        room_state: &ChatRoomStateV1,
-        msg: &river_core::room_state::message::AuthorizedMessageV1,
-        room_owner_key: &VerifyingKey,
-        format: &OutputFormat,
-        secrets: &HashMap<u32, [u8; 32]>,
-    ) -> Result<()> {
-        let msg_id = msg.id();
-        let author_str = msg.message.author.to_string();
-        let nickname = room_state
-            .member_info
-            .canonical(msg.message.author)
-            .map(|info| unseal_nickname_display(&info.member_info.preferred_nickname, secrets));
-        let datetime: DateTime<Utc> = msg.message.time.into();
+        // Prevent banning yourself out of the room, directly or transitively
+        // (freenet/river#478). ONE rule: refuse when the ban's cascade would
+        // remove US — the target being self, or the target being one of our own
+        // invite ancestors, whose `get_downstream_members` closure contains us.
+        // Both are contract-VALID, so nothing downstream stops them.
+        if let Some(reason) =
+            crate::deputies::self_removing_ban_reason(&room_state, my_member_id, banned_member_id)
+        {
+            return Err(anyhow!(reason));
+        }
 
-        match format {
-            OutputFormat::Human => {
-                let local_time: DateTime<Local> = datetime.into();
-                let display_name = nickname
-                    .clone()
-                    .unwrap_or_else(|| author_str.chars().take(8).collect());
-                println!(
-                    "[deleted] [{} - {}]: (message deleted)",
-                    local_time.format("%H:%M:%S"),
-                    display_name
-                );
-            }
-            OutputFormat::Json => {
-                let json_msg = json!({
-                    "type": "delete",
-                    "message_id": msg_id.0 .0.to_string(),
-                    "room": bs58::encode(room_owner_key.as_bytes()).into_string(),
-                    "author": author_str,
-                    "nickname": nickname,
-                    "timestamp": datetime.to_rfc3339(),
-                });
-                println!("{}", serde_json::to_string(&json_msg)?);
+        // Prevent banning the room owner
+        if banned_member_id == owner_member_id {
+            return Err(anyhow!("Cannot ban the room owner"));
+        }
+
+        // Verify authorization using the SAME `is_ban_authorized` predicate the
+        // contract enforces (owner OR strict-ancestor-of-target OR
+        // deputy-of-an-ancestor, including the "can't ban your deputizer"
+        // guardrail), so client-side rejection stays in lockstep with
+        // on-contract enforcement (#410).
+        if my_member_id != owner_member_id {
+            let members_by_id = room_state.members.members_by_member_id();
+            let authorized = river_core::room_state::member::MembersV1::is_ban_authorized(
+                my_member_id,
+                banned_member_id,
+                &members_by_id,
+                &room_state.member_info,
+                owner_member_id,
+            );
+            if !authorized {
+                return Err(anyhow!(
+                    "Not authorized to ban this member. You can ban members you invited \
+                     (directly or indirectly), or members within a subtree you have been \
+                     deputized over."
+                ));
             }
         }
-        std::io::stdout().flush()?;
-        Ok(())
-    }
 
-    /// Helper function to output a message in the requested format.
-    ///
-    /// `is_edit` marks a re-emission of a message whose content changed since it
-    /// was first streamed (the monitor's edit detection): the JSON `type`
-    /// becomes `"edit"` and the human line is prefixed so a downstream relay can
-    /// tell an edit from a fresh message.
-    ///
-    /// Note `type: "edit"` differs from the `edited` boolean: `edited` is true
-    /// whenever an edit action exists for the message (so a message already
-    /// edited *before* the stream first saw it is emitted once as
-    /// `type: "message"` with `edited: true`), whereas `type: "edit"` marks a
-    /// re-emission triggered by a content change observed live.
-    fn output_message(
-        room_state: &ChatRoomStateV1,
-        msg: &river_core::room_state::message::AuthorizedMessageV1,
-        room_owner_key: &VerifyingKey,
-        format: &OutputFormat,
-        is_edit: bool,
-        secrets: &HashMap<u32, [u8; 32]>,
-    ) -> Result<()> {
-        // Get display content (handles edits and non-text public content like
-        // join events; `secrets` decrypts private-room bodies — only a body
-        // whose secret is unavailable renders as "<encrypted>")
-        let content = message_display_text_with_secrets(room_state, msg, secrets);
+        info!("Banning member with ID: {}", banned_member_id.to_string());
 
-        // Get message ID for checking edited status and reactions
-        let msg_id = msg.id();
-        let edited = room_state.recent_messages.is_edited(&msg_id);
-        let reactions = room_state.recent_messages.reactions(&msg_id);
-        let reply = reply_context_display_with_secrets(room_state, msg, secrets);
+        // Seal the reason like a nickname: plaintext in a public room, under
+        // the room secret in a private one.
+        let reason = match reason {
+            Some(reason) if reason.len() > MAX_BAN_REASON_BYTES => {
+                return Err(anyhow!(
+                    "Ban reason is {} bytes; the maximum is {}",
+                    reason.len(),
+                    MAX_BAN_REASON_BYTES
+                ));
+            }
+            Some(reason) => {
+                let invitation_secrets = self.storage.get_invitation_secrets(room_owner_key)?;
+                let secrets = crate::private_room::collect_secrets_for_room(
+                    &room_state,
+                    &signing_key,
+                    &invitation_secrets,
+                );
+                Some(
+                    crate::private_room::seal_field_for_room(
+                        &room_state,
+                        &secrets,
+                        reason.as_bytes(),
+                    )
+                    .map_err(|e| anyhow!(e))?,
+                )
+            }
+            None => None,
+        };
 
-        match format {
-            OutputFormat::Human => {
-                let author_str = msg.message.author.to_string();
-                let author_short = author_str.chars().take(8).collect::<String>();
+        // Create the ban
+        let user_ban = UserBan {
+            owner_member_id,
+            banned_at: std::time::SystemTime::now(),
+            banned_user: banned_member_id,
+            reason,
+            duration,
+        };
 
-                // Get nickname if available (decrypted for a private room)
-                let nickname = room_state
-                    .member_info
-                    .canonical(msg.message.author)
-                    .map(|info| {
-                        unseal_nickname_display(&info.member_info.preferred_nickname, secrets)
-                    })
-                    .unwrap_or(author_short);
+        let authorized_ban = AuthorizedUserBan::new(user_ban, my_member_id, &signing_key);
 
-                let datetime: DateTime<Utc> = msg.message.time.into();
-                let local_time: DateTime<Local> = datetime.into();
+        // Create delta with just the ban
+        let delta = ChatRoomStateV1Delta {
+            bans: Some(vec![authorized_ban.clone()]),
+            ..Default::default()
+        };
 
-                let edited_indicator = if edited { " (edited)" } else { "" };
-                // Re-emission of an edited message — distinguish it from a fresh
-                // one for a downstream relay reading the human stream.
-                let edit_prefix = if is_edit { "[edit] " } else { "" };
-                let reply_prefix = reply_prefix_display(&reply);
-                let reactions_str = reactions
-                    .map(|r| {
-                        if r.is_empty() {
-                            String::new()
-                        } else {
-                            let parts: Vec<_> = r
-                                .iter()
-                                .map(|(emoji, reactors)| format!("{}×{}", emoji, reactors.len()))
-                                .collect();
-                            format!(" [{}]", parts.join(" "))
-                        }
-                    })
-                    .unwrap_or_default();
+        // Serialize the delta
+        let delta_bytes = {
+            let mut buf = Vec::new();
+            ciborium::ser::into_writer(&delta, &mut buf)
+                .map_err(|e| anyhow!("Failed to serialize delta: {}", e))?;
+            buf
+        };
 
-                println!(
-                    "{}[{} - {}]: {}{}{}{}",
-                    edit_prefix,
-                    local_time.format("%H:%M:%S"),
-                    nickname,
-                    reply_prefix,
-                    content,
-                    edited_indicator,
-                    reactions_str
-                );
-            }
-            OutputFormat::Json => {
-                let author_str = msg.message.author.to_string();
+        // Get contract key and send the update
+        let contract_key = self.owner_vk_to_contract_key(room_owner_key);
 
-                let nickname = room_state
-                    .member_info
-                    .canonical(msg.message.author)
-                    .map(|info| {
-                        unseal_nickname_display(&info.member_info.preferred_nickname, secrets)
-                    });
+        let update_request = ContractRequest::Update {
+            key: contract_key,
+            data: UpdateData::Delta(delta_bytes.into()),
+        };
 
-                let datetime: DateTime<Utc> = msg.message.time.into();
+        let client_request = ClientRequest::ContractOp(update_request);
 
-                let reactions_map: std::collections::HashMap<String, usize> = reactions
-                    .map(|r| r.iter().map(|(k, v)| (k.clone(), v.len())).collect())
-                    .unwrap_or_default();
+        let mut web_api = self.web_api.lock().await;
+        web_api
+            .send(client_request)
+            .await
+            .map_err(|e| anyhow!("Failed to send update request: {}", e))?;
 
-                let message_id_str = msg_id.0 .0.to_string();
-
-                // Reply context (null for non-replies) so a relay can thread the
-                // message; previously absent from the monitor's JSON output.
-                let reply_to = reply_to_json(&reply);
-
-                // Output as JSONL (one JSON object per line). `type` is "edit"
-                // for a re-emitted message whose content changed, else "message".
-                let json_msg = json!({
-                    "type": if is_edit { "edit" } else { "message" },
-                    "message_id": message_id_str,
-                    "room": bs58::encode(room_owner_key.as_bytes()).into_string(),
-                    "author": author_str,
-                    "nickname": nickname,
-                    "content": content,
-                    "timestamp": datetime.to_rfc3339(),
-                    "edited": edited,
-                    "reply_to": reply_to,
-                    "reactions": reactions_map,
-                });
+        // Wait for response
+        let response =
+            match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
+                Ok(Ok(response)) => response,
+                Ok(Err(e)) => return Err(anyhow!("Failed to receive response: {}", e)),
+                Err(_) => {
+                    return Err(anyhow!(
+                        "Timeout waiting for update response after 60 seconds"
+                    ))
+                }
+            };
 
-                println!("{}", serde_json::to_string(&json_msg)?);
+        match response {
+            HostResponse::ContractResponse(ContractResponse::UpdateResponse { key, .. }) => {
+                info!("Ban applied successfully for contract: {}", key.id());
+                Ok(())
             }
+            _ => Err(anyhow!("Unexpected response type: {:?}", response)),
         }
-
-        // Flush stdout immediately for real-time output
-        std::io::stdout().flush()?;
-        Ok(())
     }
 
-    /// Set the current user's nickname in a room
-    pub async fn set_nickname(
+    /// Lift every ban on a member (unban).
+    ///
+    /// Signs a ban revocation for each ban targeting the member. The contract
+    /// honours a revocation from the original banner, anyone above them in the
+    /// invite chain, or their deputies, so this refuses up front unless the
+    /// caller may revoke ALL of them: an unban that leaves one ban in force
+    /// would still keep the member out. Once it converges the member can be
+    /// re-invited.
+    pub async fn unban_member(
         &self,
         room_owner_key: &VerifyingKey,
-        new_nickname: String,
-    ) -> Result<()> {
-        info!(
-            "Setting nickname to '{}' in room owned by: {}",
-            new_nickname,
-            bs58::encode(room_owner_key.as_bytes()).into_string()
-        );
-
-        // Get signing key from storage
-        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
-            anyhow!("Room not found. You must be a member of the room to change your nickname.")
+        member_id_short: &str,
+    ) -> Result<MemberId> {
+        let (signing_key, _, _) = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to unban members.")
         })?;
-        let (signing_key, _, _contract_key_str) = room_data;
-
-        // Fetch fresh state from network so build_rejoin_delta can detect pruning
-        let mut room_state = self.get_room(room_owner_key, false).await?;
-
-        let my_member_id = signing_key.verifying_key().into();
+        let room_state = self.get_room(room_owner_key, false).await?;
+        let my_member_id: MemberId = signing_key.verifying_key().into();
 
-        // Seal the nickname for the room's privacy mode. In a PRIVATE room the
-        // nickname MUST be AES-256-GCM sealed under the room secret — sending
-        // it as plaintext (the previous unconditional `SealedBytes::public`)
-        // silently deanonymised the member: the contract's `member_info`
-        // validation only checks signature + declared length, so the plaintext
-        // was accepted and published into the private room's state for every
-        // peer to read. Errors (rather than leaks) if the secret isn't available
-        // yet.
-        let invitation_secrets = self.storage.get_invitation_secrets(room_owner_key)?;
-        let secrets = crate::private_room::collect_secrets_for_room(
-            &room_state,
-            &signing_key,
-            &invitation_secrets,
-        );
-        let sealed_nickname = crate::private_room::seal_field_for_room(
+        let (unbanned_id, bans) = resolve_unban_target(
             &room_state,
-            &secrets,
-            new_nickname.as_bytes(),
-        )
-        .map_err(|e| anyhow!(e))?;
-
-        // Find our current member info to get the version AND our existing
-        // deputy grants — republishing member_info replaces the whole signed
-        // record, so we must carry `deputies` forward or a nickname change would
-        // silently revoke every deputy we appointed (#410). Routes through the
-        // shared `resolve_own_member_info_base` (canonical, #411 round 8 item A)
-        // so a duplicate-holding state can't resurrect a revoked record.
-        let current_self_info = resolve_own_member_info_base(&room_state, my_member_id);
-        let current_version = current_self_info
-            .as_ref()
-            .map(|info| info.version)
-            .unwrap_or(0);
-        let existing_deputies = current_self_info
-            .map(|info| info.deputies)
-            .unwrap_or_default();
+            my_member_id,
+            room_owner_key.into(),
+            member_id_short,
+        )?;
+        info!("Unbanning member with ID: {}", unbanned_id);
 
-        // Create new member info with incremented version
-        let new_member_info = MemberInfo {
-            member_id: my_member_id,
-            version: current_version + 1,
-            preferred_nickname: sealed_nickname,
-            deputies: existing_deputies,
+        let revocations = bans
+            .iter()
+            .map(|ban| {
+                AuthorizedBanRevocation::new(
+                    BanRevocation::of(ban, std::time::SystemTime::now()),
+                    my_member_id,
+                    &signing_key,
+                )
+            })
+            .collect();
+        let delta = ChatRoomStateV1Delta {
+            ban_revocations: Some(revocations),
+            ..Default::default()
         };
+        self.send_delta(room_owner_key, delta).await?;
+        Ok(unbanned_id)
+    }
 
-        // Sign with our member key
-        let authorized_member_info =
-            AuthorizedMemberInfo::new_with_member_key(new_member_info, &signing_key);
-
-        // Update local state first. Drop ALL existing records for our own
-        // member_id (not just the first match) before pushing the new one — the
-        // new record's version (`current_version + 1`) strictly outranks any
-        // existing duplicate, so this collapses to the same single canonical
-        // record `dedup_to_canonical` would converge on, rather than leaving a
-        // stale duplicate sitting in local state alongside the new republish.
-        room_state
-            .member_info
-            .member_info
-            .retain(|info| info.member_info.member_id != my_member_id);
-        room_state
-            .member_info
-            .member_info
-            .push(authorized_member_info.clone());
-
-        // Save the updated state locally
-        self.storage
-            .update_room_state(room_owner_key, room_state.clone())?;
+    /// Mute a member: their messages stop being accepted into the room until
+    /// the mute is lifted or, with a `duration`, lapses. They stay a member and
+    /// keep their history. Authority follows the ban rules: the owner, anyone
+    /// above the member in the invite chain, or their deputies.
+    pub async fn mute_member(
+        &self,
+        room_owner_key: &VerifyingKey,
+        member_id_short: &str,
+        duration: Option<Duration>,
+    ) -> Result<MemberId> {
+        self.set_mute(
+            room_owner_key,
+            member_id_short,
+            MuteAction::Mute { duration },
+        )
+        .await
+    }
 
-        // Persist our chosen nickname so a later rejoin (after an inactivity
-        // prune) restores it instead of "Member".
-        self.storage
-            .update_self_nickname(room_owner_key, &new_nickname)?;
+    /// Lift a member's mute. Anyone who may mute the member may unmute them.
+    pub async fn unmute_member(
+        &self,
+        room_owner_key: &VerifyingKey,
+        member_id_short: &str,
+    ) -> Result<MemberId> {
+        self.set_mute(room_owner_key, member_id_short, MuteAction::Unmute)
+            .await
+    }
 
-        // Check if we need to re-add ourselves (pruned for inactivity)
-        let (members_delta, _) = self.build_rejoin_delta(&room_state, room_owner_key, &signing_key);
+    async fn set_mute(
+        &self,
+        room_owner_key: &VerifyingKey,
+        member_id_short: &str,
+        action: MuteAction,
+    ) -> Result<MemberId> {
+        let (signing_key, _, _) = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to mute members.")
+        })?;
+        let room_state = self.get_room(room_owner_key, false).await?;
+        let params = ChatRoomParametersV1 {
+            owner: *room_owner_key,
+        };
+        let my_member_id: MemberId = signing_key.verifying_key().into();
 
-        // Create delta with member info update (and members delta if needed)
+        let target = resolve_mute_target(
+            &room_state,
+            &params,
+            my_member_id,
+            member_id_short,
+            action.is_mute(),
+        )?;
+        info!("{:?} for member with ID: {}", action, target);
+
+        let record = AuthorizedMute::new(
+            Mute {
+                owner_member_id: params.owner_id(),
+                muted_user: target,
+                muted_at: std::time::SystemTime::now(),
+                action,
+            },
+            my_member_id,
+            &signing_key,
+        );
         let delta = ChatRoomStateV1Delta {
-            member_info: Some(vec![authorized_member_info]),
-            members: members_delta,
+            mutes: Some(vec![record]),
             ..Default::default()
         };
+        self.send_delta(room_owner_key, delta).await?;
+        Ok(target)
+    }
 
-        // Serialize the delta
-        let delta_bytes = {
-            let mut buf = Vec::new();
-            ciborium::ser::into_writer(&delta, &mut buf)
-                .map_err(|e| anyhow!("Failed to serialize delta: {}", e))?;
-            buf
-        };
+    /// Approve a join request: sign a member entry for the requester's key,
+    /// invited by this identity, and publish it inside the approval. The
+    /// requester joins with it the next time they run `room knock`. Any
+    /// member may approve; the newest decision on a request stands.
+    pub async fn approve_join_request(
+        &self,
+        room_owner_key: &VerifyingKey,
+        requester_short: &str,
+    ) -> Result<MemberId> {
+        self.decide_join_request(room_owner_key, requester_short, true)
+            .await
+    }
 
-        // Get contract key and send the update
-        let contract_key = self.owner_vk_to_contract_key(room_owner_key);
+    /// Deny a join request. The denial keeps the requester's later requests
+    /// out until a member approves them.
+    pub async fn deny_join_request(
+        &self,
+        room_owner_key: &VerifyingKey,
+        requester_short: &str,
+    ) -> Result<MemberId> {
+        self.decide_join_request(room_owner_key, requester_short, false)
+            .await
+    }
 
-        let update_request = ContractRequest::Update {
-            key: contract_key,
-            data: UpdateData::Delta(delta_bytes.into()),
+    async fn decide_join_request(
+        &self,
+        room_owner_key: &VerifyingKey,
+        requester_short: &str,
+        approve: bool,
+    ) -> Result<MemberId> {
+        let (signing_key, _, _) = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to answer join requests.")
+        })?;
+        let room_state = self.get_room(room_owner_key, false).await?;
+        let params = ChatRoomParametersV1 {
+            owner: *room_owner_key,
         };
+        let my_member_id = author_member_id(&signing_key);
+        let requester = resolve_join_requester(&room_state, requester_short)?;
+        let requester_id = MemberId::from(&requester);
+        info!(
+            "{} join request from {}",
+            if approve { "Approving" } else { "Denying" },
+            requester_id
+        );
 
-        let client_request = ClientRequest::ContractOp(update_request);
-
-        let mut web_api = self.web_api.lock().await;
-        web_api
-            .send(client_request)
-            .await
-            .map_err(|e| anyhow!("Failed to send update request: {}", e))?;
+        let outcome = if approve {
+            JoinOutcome::Approved(Box::new(AuthorizedMember::new(
+                Member {
+                    owner_member_id: params.owner_id(),
+                    invited_by: my_member_id,
+                    member_vk: requester,
+                },
+                &signing_key,
+            )))
+        } else {
+            JoinOutcome::Denied
+        };
+        let decision = AuthorizedJoinDecision::new(
+            JoinDecision {
+                owner_member_id: params.owner_id(),
+                requester,
+                decided_at: std::time::SystemTime::now(),
+                outcome,
+            },
+            my_member_id,
+            &signing_key,
+        );
+        let delta = ChatRoomStateV1Delta {
+            join_requests: Some(JoinRequestsV1 {
+                requests: Vec::new(),
+                decisions: vec![decision],
+            }),
+            ..Default::default()
+        };
+        self.send_delta(room_owner_key, delta).await?;
+        Ok(requester_id)
+    }
 
-        // Wait for response
-        let response =
-            match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
-                Ok(Ok(response)) => response,
-                Ok(Err(e)) => return Err(anyhow!("Failed to receive response: {}", e)),
-                Err(_) => {
-                    return Err(anyhow!(
-                        "Timeout waiting for update response after 60 seconds"
-                    ))
+    /// Ask to join a room without an invitation, or follow up on the request
+    /// made earlier. The first call generates the key this client will join
+    /// with, keeps it locally and publishes a request signed with it. Later
+    /// calls check for a decision: an approval is accepted like an
+    /// invitation (with `nickname` from the first call), a denial is reported
+    /// and the local request forgotten.
+    pub async fn knock(
+        &self,
+        room_owner_key: &VerifyingKey,
+        nickname: &str,
+        message: &str,
+    ) -> Result<KnockOutcome> {
+        if self.storage.get_room(room_owner_key)?.is_some() {
+            return Err(anyhow!("You are already a member of this room."));
+        }
+        let room_state = self.get_room(room_owner_key, false).await?;
+        let pending = self.storage.pending_join_request(room_owner_key)?;
+
+        if let Some(pending) = &pending {
+            let signing_key = SigningKey::from_bytes(&pending.signing_key_bytes);
+            let requester_id = author_member_id(&signing_key);
+            match knock_progress(&room_state, &signing_key.verifying_key()) {
+                KnockProgress::Approved(member) => {
+                    let invitation = Invitation {
+                        room: *room_owner_key,
+                        invitee_signing_key: signing_key,
+                        invitee: *member,
+                        room_secrets: Vec::new(),
+                    };
+                    self.accept_invitation_struct(invitation, &pending.nickname)
+                        .await?;
+                    self.storage
+                        .set_pending_join_request(room_owner_key, None)?;
+                    return Ok(KnockOutcome::Joined(requester_id));
                 }
-            };
-
-        match response {
-            HostResponse::ContractResponse(ContractResponse::UpdateResponse { key, .. }) => {
-                info!("Nickname updated successfully for contract: {}", key.id());
-                Ok(())
+                KnockProgress::Denied => {
+                    self.storage
+                        .set_pending_join_request(room_owner_key, None)?;
+                    return Ok(KnockOutcome::Denied(requester_id));
+                }
+                KnockProgress::Waiting => return Ok(KnockOutcome::Waiting(requester_id)),
+                // Pushed out of the queue: ask again with the same key.
+                KnockProgress::Dropped => {}
             }
-            _ => Err(anyhow!("Unexpected response type: {:?}", response)),
         }
+
+        let (signing_key, nickname) = match pending {
+            Some(pending) => (
+                SigningKey::from_bytes(&pending.signing_key_bytes),
+                pending.nickname,
+            ),
+            None => (
+                SigningKey::from_bytes(&rand::Rng::gen::<[u8; 32]>(&mut rand::thread_rng())),
+                nickname.to_string(),
+            ),
+        };
+        let max_nickname = room_state.configuration.configuration.max_nickname_size;
+        if nickname.len() > max_nickname {
+            return Err(anyhow!(
+                "Nickname is {} bytes; this room allows at most {}",
+                nickname.len(),
+                max_nickname
+            ));
+        }
+        if message.len() > MAX_JOIN_REQUEST_MESSAGE_BYTES {
+            return Err(anyhow!(
+                "Message is {} bytes; the maximum is {}",
+                message.len(),
+                MAX_JOIN_REQUEST_MESSAGE_BYTES
+            ));
+        }
+        // Keep the key before publishing, so an approval is never lost to a
+        // failed write.
+        self.storage.set_pending_join_request(
+            room_owner_key,
+            Some(crate::storage::PendingJoinRequest {
+                signing_key_bytes: signing_key.to_bytes(),
+                nickname: nickname.clone(),
+            }),
+        )?;
+        let request = AuthorizedJoinRequest::new(
+            JoinRequest {
+                owner_member_id: room_owner_key.into(),
+                requester: signing_key.verifying_key(),
+                nickname,
+                message: message.to_string(),
+                requested_at: std::time::SystemTime::now(),
+            },
+            &signing_key,
+        );
+        let delta = ChatRoomStateV1Delta {
+            join_requests: Some(JoinRequestsV1 {
+                requests: vec![request],
+                decisions: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        self.send_delta(room_owner_key, delta).await?;
+        Ok(KnockOutcome::Requested(author_member_id(&signing_key)))
     }
 
-    /// Ban a member from the room
-    ///
-    /// The banning member must be either the room owner or an upstream member in the
-    /// invite chain of the member being banned.
-    pub async fn ban_member(
+    /// Deputize a member (#410): grant them authority to ban within the
+    /// caller's invite subtree. Implemented by republishing the caller's own
+    /// `MemberInfo` at `version + 1` with the target added to `deputies`.
+    pub async fn deputize(
         &self,
         room_owner_key: &VerifyingKey,
         member_id_short: &str,
     ) -> Result<()> {
-        self.ban_member_with_safety(room_owner_key, member_id_short, BanSafety::default())
+        self.update_own_deputies(room_owner_key, member_id_short, true)
             .await
     }
 
-    /// Ban with fail-closed safety predicates evaluated against the same fresh
-    /// room state used to construct the signed ban delta.
-    pub async fn ban_member_with_safety(
+    /// Revoke a member's deputy authority (#410). Their prior bans stop
+    /// enforcing on the contract once this republish converges. Implemented by
+    /// republishing the caller's own `MemberInfo` at `version + 1` with the
+    /// target removed from `deputies`.
+    pub async fn revoke_deputy(
         &self,
         room_owner_key: &VerifyingKey,
         member_id_short: &str,
-        safety: BanSafety,
     ) -> Result<()> {
-        info!(
-            "Banning member '{}' from room owned by: {}",
-            member_id_short,
-            bs58::encode(room_owner_key.as_bytes()).into_string()
-        );
+        self.update_own_deputies(room_owner_key, member_id_short, false)
+            .await
+    }
+
+    /// Shared implementation for [`Self::deputize`] / [`Self::revoke_deputy`].
+    /// Republishes the caller's own signed `MemberInfo` at `version + 1` with
+    /// `target` added (`add = true`) or removed (`add = false`) from the
+    /// `deputies` list, preserving the existing sealed nickname, and sends it as
+    /// a `member_info`-only delta.
+    async fn update_own_deputies(
+        &self,
+        room_owner_key: &VerifyingKey,
+        member_id_short: &str,
+        add: bool,
+    ) -> Result<()> {
+        use river_core::room_state::member_info::MAX_DEPUTIES;
 
-        // Get the signing key from storage
         let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
-            anyhow!("Room not found. You must be a member of the room to ban members.")
+            anyhow!("Room not found. You must be a member of the room to manage deputies.")
         })?;
         let (signing_key, _stored_state, _contract_key_str) = room_data;
 
-        // Fetch fresh room state from the network
         let room_state = self.get_room(room_owner_key, false).await?;
 
         let my_member_id: MemberId = signing_key.verifying_key().into();
         let owner_member_id: MemberId = room_owner_key.into();
 
-        // Find the member to ban by their short ID (first 8 chars of member_id string)
-        let target_member = room_state
-            .member_info
-            .member_info
-            .iter()
-            .find(|info| {
-                let member_id_str = info.member_info.member_id.to_string();
-                member_id_str.starts_with(member_id_short)
-                    || member_id_str[..8.min(member_id_str.len())]
-                        .eq_ignore_ascii_case(member_id_short)
-            })
+        // Load the caller's current signed member_info FIRST: we must preserve the
+        // (already-sealed) nickname and version-continuity when republishing, and
+        // the revoke path resolves the target against the caller's OWN deputies
+        // list (below). Routes through the shared `resolve_own_member_info_base`
+        // (canonical, #411 round 8 item A) so a duplicate-holding state can't
+        // resurrect a revoked record's deputies at a higher rank.
+        let current_self_info = resolve_own_member_info_base(&room_state, my_member_id)
             .ok_or_else(|| {
                 anyhow!(
-                    "Member '{}' not found. Use 'member list' to see member IDs.",
-                    member_id_short
+                    "You don't have a member_info entry in this room yet. \
+                     Set your nickname first (`member set-nickname`), then retry."
                 )
             })?;
+        let current_version = current_self_info.version;
+        let preferred_nickname = current_self_info.preferred_nickname.clone();
+        let mut deputies = current_self_info.deputies.clone();
+        let dm_prekey = current_self_info.dm_prekey;
 
-        let banned_member_id = target_member.member_info.member_id;
-
-        if safety.require_exact_member_id && banned_member_id.to_string() != member_id_short {
-            return Err(anyhow!(
-                "Safety preflight refused: '{}' is not the exact current member ID '{}'.",
-                member_id_short,
-                banned_member_id
-            ));
-        }
-
-        if safety.require_no_descendants {
-            let removal = crate::deputies::ban_removal_set(&room_state, banned_member_id);
-            if removal.len() != 1 {
-                return Err(anyhow!(
-                    "Safety preflight refused: banning '{}' would remove {} descendants.",
-                    banned_member_id,
-                    removal.len().saturating_sub(1)
-                ));
-            }
-        }
-
-        if safety.require_not_deputy {
-            let is_deputy = room_state.member_info.member_info.iter().any(|record| {
-                room_state
-                    .member_info
-                    .deputies_of(record.member_info.member_id)
-                    .contains(&banned_member_id)
-            });
-            if is_deputy {
-                return Err(anyhow!(
-                    "Safety preflight refused: member '{}' is a deputy.",
-                    banned_member_id
-                ));
-            }
-        }
+        // Resolve the target's full MemberId from the short id. Primary: the
+        // member_info list (a present member), same lookup as `ban_member`.
+        // Revoke fallback (#411 round 4 C): a deputy pruned for inactivity has no
+        // member_info but still sits in the caller's signed `deputies` list, so
+        // the primary lookup can't find them — match the short id against the
+        // caller's own deputies too, so the lingering grant can be revoked
+        // (otherwise it silently reactivates if the deputy's membership is later
+        // replayed).
+        let target = resolve_deputy_target(
+            &room_state.member_info.member_info,
+            &deputies,
+            member_id_short,
+            !add, // allow the own-deputies fallback only when revoking
+        )
+        .ok_or_else(|| {
+            anyhow!(
+                "Member '{}' not found. Use 'member list' to see member IDs.",
+                member_id_short
+            )
+        })?;
 
-        // Prevent banning yourself out of the room, directly or transitively
-        // (freenet/river#478). ONE rule: refuse when the ban's cascade would
-        // remove US — the target being self, or the target being one of our own
-        // invite ancestors, whose `get_downstream_members` closure contains us.
-        // Both are contract-VALID, so nothing downstream stops them.
-        if let Some(reason) =
-            crate::deputies::self_removing_ban_reason(&room_state, my_member_id, banned_member_id)
-        {
-            return Err(anyhow!(reason));
+        if target == my_member_id {
+            return Err(anyhow!("You cannot deputize yourself"));
         }
-
-        // Prevent banning the room owner
-        if banned_member_id == owner_member_id {
-            return Err(anyhow!("Cannot ban the room owner"));
+        if target == owner_member_id {
+            return Err(anyhow!(
+                "The room owner already has full authority; deputizing them is a no-op"
+            ));
         }
 
-        // Verify authorization using the SAME `is_ban_authorized` predicate the
-        // contract enforces (owner OR strict-ancestor-of-target OR
-        // deputy-of-an-ancestor, including the "can't ban your deputizer"
-        // guardrail), so client-side rejection stays in lockstep with
-        // on-contract enforcement (#410).
-        if my_member_id != owner_member_id {
-            let members_by_id = room_state.members.members_by_member_id();
-            let authorized = river_core::room_state::member::MembersV1::is_ban_authorized(
-                my_member_id,
-                banned_member_id,
-                &members_by_id,
-                &room_state.member_info,
-                owner_member_id,
-            );
-            if !authorized {
+        if add {
+            if deputies.contains(&target) {
+                info!("Member is already a deputy; nothing to do");
+                return Ok(());
+            }
+            if deputies.len() >= MAX_DEPUTIES {
                 return Err(anyhow!(
-                    "Not authorized to ban this member. You can ban members you invited \
-                     (directly or indirectly), or members within a subtree you have been \
-                     deputized over."
+                    "You already have the maximum of {} deputies",
+                    MAX_DEPUTIES
                 ));
             }
+            deputies.push(target);
+        } else if let Some(pos) = deputies.iter().position(|d| *d == target) {
+            deputies.remove(pos);
+        } else {
+            info!("Member is not currently a deputy; nothing to do");
+            return Ok(());
         }
 
-        info!("Banning member with ID: {}", banned_member_id.to_string());
+        let new_member_info = MemberInfo {
+            member_id: my_member_id,
+            version: current_version + 1,
+            preferred_nickname,
+            deputies,
+            dm_prekey,
+        };
+        let authorized_member_info =
+            AuthorizedMemberInfo::new_with_member_key(new_member_info, &signing_key);
 
-        // Create the ban
-        let user_ban = UserBan {
-            owner_member_id,
-            banned_at: std::time::SystemTime::now(),
-            banned_user: banned_member_id,
+        let delta = ChatRoomStateV1Delta {
+            member_info: Some(vec![authorized_member_info]),
+            ..Default::default()
         };
+        self.send_delta(room_owner_key, delta).await
+    }
 
-        let authorized_ban = AuthorizedUserBan::new(user_ban, my_member_id, &signing_key);
+    /// Publish `prekey` as the caller's DM prekey (`river_core::dm_ratchet`),
+    /// or stop publishing one with `None`. Republishes the caller's own
+    /// `MemberInfo` at `version + 1` with the nickname and deputies unchanged,
+    /// like [`Self::update_own_deputies`].
+    pub async fn set_dm_prekey(
+        &self,
+        room_owner_key: &VerifyingKey,
+        prekey: Option<DmPrekeyV1>,
+    ) -> Result<()> {
+        let (signing_key, _, _) = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be a member of the room to publish a DM prekey.")
+        })?;
+        let room_state = self.get_room(room_owner_key, false).await?;
+        let my_member_id: MemberId = signing_key.verifying_key().into();
+
+        let current_self_info = resolve_own_member_info_base(&room_state, my_member_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "You don't have a member_info entry in this room yet. \
+                     Set your nickname first (`member set-nickname`), then retry."
+                )
+            })?;
+        let new_member_info = MemberInfo {
+            version: current_self_info.version + 1,
+            dm_prekey: prekey,
+            ..current_self_info
+        };
+        let authorized_member_info =
+            AuthorizedMemberInfo::new_with_member_key(new_member_info, &signing_key);
 
-        // Create delta with just the ban
         let delta = ChatRoomStateV1Delta {
-            bans: Some(vec![authorized_ban.clone()]),
+            member_info: Some(vec![authorized_member_info]),
             ..Default::default()
         };
+        self.send_delta(room_owner_key, delta).await
+    }
 
-        // Serialize the delta
+    /// Update room configuration. Only the room owner can do this.
+    pub async fn update_config(
+        &self,
+        room_owner_key: &VerifyingKey,
+        modify: impl FnOnce(&mut Configuration),
+    ) -> Result<()> {
+        // Get the signing key from storage
+        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found. You must be the room owner to update configuration.")
+        })?;
+        let (signing_key, _stored_state, _contract_key_str) = room_data;
+
+        // Verify we are the room owner
+        let my_vk = signing_key.verifying_key();
+        if my_vk != *room_owner_key {
+            return Err(anyhow!("Only the room owner can update configuration"));
+        }
+
+        // Fetch fresh room state from the network
+        let room_state = self.get_room(room_owner_key, false).await?;
+
+        // Clone current config and apply modifications
+        let mut new_config = room_state.configuration.configuration.clone();
+        new_config.configuration_version += 1;
+        modify(&mut new_config);
+
+        // Sign the new configuration
+        let authorized_config = AuthorizedConfigurationV1::new(new_config, &signing_key);
+
+        // Create delta with just the configuration change
+        let delta = ChatRoomStateV1Delta {
+            configuration: Some(authorized_config),
+            ..Default::default()
+        };
+
+        // Serialize and send
         let delta_bytes = {
             let mut buf = Vec::new();
             ciborium::ser::into_writer(&delta, &mut buf)
@@ -4988,7 +6447,6 @@ impl ApiClient {
             buf
         };
 
-        // Get contract key and send the update
         let contract_key = self.owner_vk_to_contract_key(room_owner_key);
 
         let update_request = ContractRequest::Update {
@@ -5004,7 +6462,6 @@ impl ApiClient {
             .await
             .map_err(|e| anyhow!("Failed to send update request: {}", e))?;
 
-        // Wait for response
         let response =
             match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
                 Ok(Ok(response)) => response,
@@ -5018,646 +6475,1417 @@ impl ApiClient {
 
         match response {
             HostResponse::ContractResponse(ContractResponse::UpdateResponse { key, .. }) => {
-                info!("Ban applied successfully for contract: {}", key.id());
+                info!(
+                    "Configuration updated successfully for contract: {}",
+                    key.id()
+                );
                 Ok(())
             }
-            _ => Err(anyhow!("Unexpected response type: {:?}", response)),
-        }
-    }
+            _ => Err(anyhow!("Unexpected response type: {:?}", response)),
+        }
+    }
+
+    /// Subscribe to a room and stream updates using Freenet subscriptions
+    ///
+    /// Unlike `stream_messages` which polls, this method subscribes to the contract
+    /// and receives push notifications when the contract state changes.
+    pub async fn subscribe_and_stream(
+        &self,
+        room_owner_key: &VerifyingKey,
+        timeout_secs: u64,
+        max_messages: usize,
+        initial_messages: usize,
+        format: OutputFormat,
+    ) -> Result<()> {
+        // Verify room exists in local storage before attempting to subscribe
+        self.storage.get_room(room_owner_key)?.ok_or_else(|| {
+            anyhow!("Room not found in local storage. You may need to create or join it first.")
+        })?;
+
+        // Print header for human format
+        if matches!(format, OutputFormat::Human) {
+            eprintln!(
+                "Subscribing to room {} (press Ctrl+C to stop)...",
+                bs58::encode(room_owner_key.as_bytes()).into_string()
+            );
+        }
+
+        // Track seen messages: key -> last-emitted effective content, so a later
+        // edit (content change) is detected and re-emitted, not just new ids.
+        let mut seen_messages: HashMap<String, String> = HashMap::new();
+        // Messages for which a deletion has already been emitted (one-shot).
+        // Pre-seeded below with deletions that existed at stream start, so only
+        // deletions observed live are surfaced.
+        let mut deleted_emitted: HashSet<String> = HashSet::new();
+        // Track each shown message's reactions fingerprint so a reaction
+        // added/removed AFTER it was streamed surfaces as a `reaction` event
+        // (freenet/river#325). Seeded ONLY for messages actually shown at start
+        // (below) and for messages surfaced live thereafter (lazily by
+        // emit_reaction_changes). A reaction on a pre-existing message the stream
+        // never showed is therefore NOT surfaced — the same "only for messages
+        // the stream displayed" rule the deletion path follows (#324 review).
+        let mut seen_reactions: HashMap<String, String> = HashMap::new();
+        let mut new_message_count = 0;
+        let start_time = std::time::Instant::now();
+
+        // Fetch current room state to pre-populate seen_messages and trigger
+        // migration if needed (get_room calls ensure_room_migrated internally).
+        {
+            let mut room_state = self.get_room(room_owner_key, false).await?;
+            // Decrypt private-room content for display (no-op for public rooms).
+            // Must run before the immutable `display_msgs` borrow below.
+            let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+            self.hide_blocked_authors(room_owner_key, &mut room_state);
+
+            // Determine which messages will be displayed initially (the last N
+            // non-deleted). Only these count as "shown" for deletion purposes.
+            let display_msgs: Vec<_> = room_state.recent_messages.display_messages().collect();
+            let display_start = if initial_messages > 0 {
+                display_msgs.len().saturating_sub(initial_messages)
+            } else {
+                display_msgs.len() // display nothing
+            };
+            let shown_keys: HashSet<String> = display_msgs[display_start..]
+                .iter()
+                .map(|m| monitor_seen_key(m))
+                .collect();
+
+            // Mark ALL non-action messages as seen (key -> effective content),
+            // including deleted ones, so old messages aren't re-shown as new
+            // (https://github.com/freenet/river/issues/173) and later edits are
+            // detected as content changes.
+            for msg in &room_state.recent_messages.messages {
+                if !msg.message.content.is_action() {
+                    seen_messages.insert(
+                        monitor_seen_key(msg),
+                        message_display_text_with_secrets(&room_state, msg, &secrets),
+                    );
+                }
+            }
+
+            // Suppress live deletion events for every pre-existing message we are
+            // NOT showing now (already-deleted ones, and history outside the
+            // initial window — including the default initial_messages == 0 which
+            // shows nothing). Only deletions of messages the stream actually
+            // showed — or emits live later — are surfaced. #324 review.
+            deleted_emitted.extend(deletions_to_suppress_at_start(
+                &room_state.recent_messages.messages,
+                &shown_keys,
+            ));
+
+            // Show the last N display messages.
+            for (i, msg) in display_msgs.iter().enumerate() {
+                if i >= display_start {
+                    // Seed the reactions fingerprint for each shown message so
+                    // reactions already present at startup aren't re-emitted as a
+                    // live change; only later changes to them surface.
+                    seen_reactions.insert(
+                        monitor_seen_key(msg),
+                        reactions_fingerprint(room_state.recent_messages.reactions(&msg.id())),
+                    );
+                    Self::output_message(
+                        &room_state,
+                        msg,
+                        room_owner_key,
+                        &format,
+                        false,
+                        &secrets,
+                    )?;
+                }
+            }
+        }
+
+        let mut subscription = self.subscribe_to_room(room_owner_key, format).await?;
+
+        // Set up Ctrl+C handler
+        let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
+
+        tokio::spawn(async move {
+            tokio::signal::ctrl_c().await.ok();
+            let _ = shutdown_tx.send(()).await;
+        });
+
+        // Main loop: wait for UpdateNotification messages
+        loop {
+            // Check for shutdown signal
+            if shutdown_rx.try_recv().is_ok() {
+                if matches!(format, OutputFormat::Human) {
+                    eprintln!("\nStopped monitoring.");
+                }
+                return Ok(());
+            }
+
+            // Check timeout
+            if timeout_secs > 0 && start_time.elapsed().as_secs() >= timeout_secs {
+                debug!("Timeout reached, exiting subscription stream");
+                return Ok(());
+            }
 
-    /// Deputize a member (#410): grant them authority to ban within the
-    /// caller's invite subtree. Implemented by republishing the caller's own
-    /// `MemberInfo` at `version + 1` with the target added to `deputies`.
-    pub async fn deputize(
-        &self,
-        room_owner_key: &VerifyingKey,
-        member_id_short: &str,
-    ) -> Result<()> {
-        self.update_own_deputies(room_owner_key, member_id_short, true)
-            .await
-    }
+            // Check max messages
+            if max_messages > 0 && new_message_count >= max_messages {
+                debug!("Maximum message count reached, exiting subscription stream");
+                return Ok(());
+            }
 
-    /// Revoke a member's deputy authority (#410). Their prior bans stop
-    /// enforcing on the contract once this republish converges. Implemented by
-    /// republishing the caller's own `MemberInfo` at `version + 1` with the
-    /// target removed from `deputies`.
-    pub async fn revoke_deputy(
-        &self,
-        room_owner_key: &VerifyingKey,
-        member_id_short: &str,
-    ) -> Result<()> {
-        self.update_own_deputies(room_owner_key, member_id_short, false)
-            .await
+            // Any notification — a delta (INCLUDING edit/delete/reaction
+            // action deltas) or a full-state update — can change what should
+            // be shown. Rather than parse the delta and skip actions (which
+            // made the stream oblivious to edits), re-fetch the authoritative
+            // full state and emit any NEW or EDITED messages. Deleted messages
+            // are excluded by display_messages and stay marked seen, so #173
+            // (phantom deleted messages) still holds.
+            if !self.next_room_update(&mut subscription).await? {
+                continue;
+            }
+            match self.get_room(room_owner_key, false).await {
+                Ok(mut room_state) => {
+                    // Decrypt private-room content for display (no-op for public rooms).
+                    let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+                    self.hide_blocked_authors(room_owner_key, &mut room_state);
+                    Self::emit_new_and_edited(
+                        &room_state,
+                        &mut seen_messages,
+                        &mut deleted_emitted,
+                        &mut seen_reactions,
+                        room_owner_key,
+                        &format,
+                        max_messages,
+                        &mut new_message_count,
+                        &secrets,
+                    )?;
+                    Self::emit_deletions(
+                        &room_state,
+                        &seen_messages,
+                        &mut deleted_emitted,
+                        room_owner_key,
+                        &format,
+                        &secrets,
+                    )?;
+                    // Surface reactions added/removed since a message was
+                    // already streamed. Runs AFTER emit_new_and_edited so a
+                    // brand-new message is seeded (not re-emitted) here.
+                    Self::emit_reaction_changes(
+                        &room_state,
+                        &mut seen_reactions,
+                        room_owner_key,
+                        &format,
+                        &secrets,
+                    )?;
+                }
+                Err(e) => {
+                    debug!("Failed to fetch room state after notification: {}", e);
+                }
+            }
+            if max_messages > 0 && new_message_count >= max_messages {
+                return Ok(());
+            }
+        }
     }
 
-    /// Shared implementation for [`Self::deputize`] / [`Self::revoke_deputy`].
-    /// Republishes the caller's own signed `MemberInfo` at `version + 1` with
-    /// `target` added (`add = true`) or removed (`add = false`) from the
-    /// `deputies` list, preserving the existing sealed nickname, and sends it as
-    /// a `member_info`-only delta.
-    async fn update_own_deputies(
+    /// Subscribe to a room's contract so its state changes are pushed to us.
+    /// Follow with [`next_room_update`](Self::next_room_update) in a loop.
+    ///
+    /// Long-running watchers (`message stream`, `moderate`) share this so they
+    /// all survive the handshake race described below.
+    pub async fn subscribe_to_room(
         &self,
         room_owner_key: &VerifyingKey,
-        member_id_short: &str,
-        add: bool,
-    ) -> Result<()> {
-        use river_core::room_state::member_info::MAX_DEPUTIES;
-
-        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
-            anyhow!("Room not found. You must be a member of the room to manage deputies.")
-        })?;
-        let (signing_key, _stored_state, _contract_key_str) = room_data;
-
-        let room_state = self.get_room(room_owner_key, false).await?;
+        format: OutputFormat,
+    ) -> Result<RoomSubscription> {
+        let contract_key = self.owner_vk_to_contract_key(room_owner_key);
+        let contract_instance_id = *contract_key.id();
 
-        let my_member_id: MemberId = signing_key.verifying_key().into();
-        let owner_member_id: MemberId = room_owner_key.into();
+        // Responses that arrive before the SUBSCRIBE acknowledgement; drained
+        // by `next_room_update` so none is lost.
+        let mut pending: VecDeque<HostResponse> = VecDeque::new();
 
-        // Load the caller's current signed member_info FIRST: we must preserve the
-        // (already-sealed) nickname and version-continuity when republishing, and
-        // the revoke path resolves the target against the caller's OWN deputies
-        // list (below). Routes through the shared `resolve_own_member_info_base`
-        // (canonical, #411 round 8 item A) so a duplicate-holding state can't
-        // resurrect a revoked record's deputies at a higher rank.
-        let current_self_info = resolve_own_member_info_base(&room_state, my_member_id)
-            .ok_or_else(|| {
-                anyhow!(
-                    "You don't have a member_info entry in this room yet. \
-                     Set your nickname first (`member set-nickname`), then retry."
-                )
-            })?;
-        let current_version = current_self_info.version;
-        let preferred_nickname = current_self_info.preferred_nickname.clone();
-        let mut deputies = current_self_info.deputies.clone();
+        // Subscribe to the contract
+        {
+            let subscribe_request = ContractRequest::Subscribe {
+                key: contract_instance_id, // Subscribe uses ContractInstanceId
+                summary: None,
+            };
 
-        // Resolve the target's full MemberId from the short id. Primary: the
-        // member_info list (a present member), same lookup as `ban_member`.
-        // Revoke fallback (#411 round 4 C): a deputy pruned for inactivity has no
-        // member_info but still sits in the caller's signed `deputies` list, so
-        // the primary lookup can't find them — match the short id against the
-        // caller's own deputies too, so the lingering grant can be revoked
-        // (otherwise it silently reactivates if the deputy's membership is later
-        // replayed).
-        let target = resolve_deputy_target(
-            &room_state.member_info.member_info,
-            &deputies,
-            member_id_short,
-            !add, // allow the own-deputies fallback only when revoking
-        )
-        .ok_or_else(|| {
-            anyhow!(
-                "Member '{}' not found. Use 'member list' to see member IDs.",
-                member_id_short
-            )
-        })?;
+            let client_request = ClientRequest::ContractOp(subscribe_request);
 
-        if target == my_member_id {
-            return Err(anyhow!("You cannot deputize yourself"));
-        }
-        if target == owner_member_id {
-            return Err(anyhow!(
-                "The room owner already has full authority; deputizing them is a no-op"
-            ));
-        }
+            let mut web_api = self.web_api.lock().await;
+            web_api
+                .send(client_request)
+                .await
+                .map_err(|e| anyhow!("Failed to send SUBSCRIBE request: {}", e))?;
 
-        if add {
-            if deputies.contains(&target) {
-                info!("Member is already a deputy; nothing to do");
-                return Ok(());
-            }
-            if deputies.len() >= MAX_DEPUTIES {
-                return Err(anyhow!(
-                    "You already have the maximum of {} deputies",
-                    MAX_DEPUTIES
-                ));
+            // The node's responses share one multiplexed connection, so an
+            // UpdateNotification for a contract we are already watching can
+            // arrive between our SUBSCRIBE and its acknowledgement. Treating
+            // the first message as the answer made that race fatal: the
+            // session died with "Unexpected response to SUBSCRIBE request",
+            // reconnected, and raced again. On 2026-07-27 that produced 299
+            // failures in four hours and up to 184 reconnects in a single
+            // hour against the official room (freenet-core#4970), and each
+            // reconnect dragged a catch-up batch behind it.
+            //
+            // So read until the acknowledgement actually arrives, and keep
+            // anything that overtakes it. The PUT path above already tolerates
+            // an interleaved UpdateNotification the same way.
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+            loop {
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                if remaining.is_zero() {
+                    return Err(anyhow!("Timeout waiting for SUBSCRIBE response"));
+                }
+                let response = match tokio::time::timeout(remaining, web_api.recv()).await {
+                    Ok(result) => {
+                        result.map_err(|e| anyhow!("Failed to receive response: {}", e))?
+                    }
+                    Err(_) => return Err(anyhow!("Timeout waiting for SUBSCRIBE response")),
+                };
+                match classify_subscribe_response(&response) {
+                    SubscribeAck::Subscribed => {
+                        if matches!(format, OutputFormat::Human) {
+                            eprintln!("Successfully subscribed. Waiting for updates...\n");
+                        }
+                        break;
+                    }
+                    SubscribeAck::Refused => {
+                        return Err(anyhow!("Failed to subscribe to contract"))
+                    }
+                    // Queued rather than dropped: this is a real state change,
+                    // and the main loop below drains `pending` before reading
+                    // the socket, so it goes through exactly the same handling
+                    // it would have had if it arrived a moment later.
+                    SubscribeAck::NotYet => {
+                        // Bounded because the node feeds this queue and a busy
+                        // room can emit a lot inside the handshake window; an
+                        // unbounded queue here would be a memory amplification
+                        // vector. Collapsing is lossless: the handler below
+                        // discards the delta (`let _ = update;`) and re-fetches
+                        // authoritative full state, so N queued notifications
+                        // produce exactly the same result as one.
+                        if pending.len() < MAX_PENDING_DURING_HANDSHAKE {
+                            debug!("Response overtook the SUBSCRIBE ack, queuing it");
+                            pending.push_back(response);
+                        } else {
+                            debug!("Response overtook the SUBSCRIBE ack; queue full, collapsing");
+                        }
+                    }
+                }
             }
-            deputies.push(target);
-        } else if let Some(pos) = deputies.iter().position(|d| *d == target) {
-            deputies.remove(pos);
-        } else {
-            info!("Member is not currently a deputy; nothing to do");
-            return Ok(());
         }
 
-        let new_member_info = MemberInfo {
-            member_id: my_member_id,
-            version: current_version + 1,
-            preferred_nickname,
-            deputies,
-        };
-        let authorized_member_info =
-            AuthorizedMemberInfo::new_with_member_key(new_member_info, &signing_key);
+        Ok(RoomSubscription { pending })
+    }
 
-        let delta = ChatRoomStateV1Delta {
-            member_info: Some(vec![authorized_member_info]),
-            ..Default::default()
-        };
-        self.send_delta(room_owner_key, delta).await
+    /// Wait briefly (500ms, so callers can check for shutdown) for the next
+    /// change to a subscribed room. Returns `true` when the room's state
+    /// changed and should be re-fetched with `get_room`, `false` when nothing
+    /// arrived.
+    ///
+    /// The notification's delta is advisory and discarded: every caller
+    /// re-fetches the authoritative full state instead, which is also what
+    /// makes collapsing the handshake queue lossless.
+    pub async fn next_room_update(&self, subscription: &mut RoomSubscription) -> Result<bool> {
+        Ok(self.next_contract_update(subscription).await?.is_some())
     }
 
-    /// Update room configuration. Only the room owner can do this.
-    pub async fn update_config(
+    /// [`next_room_update`](Self::next_room_update) for a subscription that
+    /// covers several rooms (see [`RoomSubscription::absorb`]): returns the
+    /// contract that changed, or `None` when nothing arrived.
+    pub async fn next_contract_update(
         &self,
-        room_owner_key: &VerifyingKey,
-        modify: impl FnOnce(&mut Configuration),
-    ) -> Result<()> {
-        // Get the signing key from storage
-        let room_data = self.storage.get_room(room_owner_key)?.ok_or_else(|| {
-            anyhow!("Room not found. You must be the room owner to update configuration.")
-        })?;
-        let (signing_key, _stored_state, _contract_key_str) = room_data;
+        subscription: &mut RoomSubscription,
+    ) -> Result<Option<ContractInstanceId>> {
+        // The guard is released on return, so the caller can `get_room`.
+        let mut web_api = self.web_api.lock().await;
+        let recv_result = if let Some(queued) = subscription.pending.pop_front() {
+            Ok(Ok(queued))
+        } else {
+            tokio::time::timeout(std::time::Duration::from_millis(500), web_api.recv()).await
+        };
 
-        // Verify we are the room owner
-        let my_vk = signing_key.verifying_key();
-        if my_vk != *room_owner_key {
-            return Err(anyhow!("Only the room owner can update configuration"));
+        match recv_result {
+            Ok(Ok(HostResponse::ContractResponse(ContractResponse::UpdateNotification {
+                key,
+                update,
+            }))) => {
+                debug!("Received update notification for contract: {}", key.id());
+                let _ = update;
+                Ok(Some(*key.id()))
+            }
+            Ok(Ok(other)) => {
+                // Other message type, log and continue
+                debug!("Received unexpected message: {:?}", other);
+                Ok(None)
+            }
+            // WebSocket error
+            Ok(Err(e)) => Err(anyhow!("WebSocket error: {}", e)),
+            // Timeout (allows the caller to check its shutdown signal)
+            Err(_) => Ok(None),
         }
+    }
+}
 
-        // Fetch fresh room state from the network
-        let room_state = self.get_room(room_owner_key, false).await?;
+/// An active room subscription from [`ApiClient::subscribe_to_room`]: holds
+/// the notifications that overtook the SUBSCRIBE acknowledgement until
+/// [`ApiClient::next_room_update`] drains them. The default one has nothing
+/// queued, for a watcher that starts before it has a room to subscribe to.
+#[derive(Default)]
+pub struct RoomSubscription {
+    pending: VecDeque<HostResponse>,
+}
 
-        // Clone current config and apply modifications
-        let mut new_config = room_state.configuration.configuration.clone();
-        new_config.configuration_version += 1;
-        modify(&mut new_config);
+impl RoomSubscription {
+    /// Take over `other`'s queued notifications. Every subscription shares
+    /// the client's one connection, so a watcher of several rooms keeps a
+    /// single `RoomSubscription` and reads it with
+    /// [`ApiClient::next_contract_update`].
+    pub fn absorb(&mut self, other: RoomSubscription) {
+        self.pending.extend(other.pending);
+    }
+}
 
-        // Sign the new configuration
-        let authorized_config = AuthorizedConfigurationV1::new(new_config, &signing_key);
+/// A `message stream --format json` event for a new message (`type:
+/// "message"`) or one whose content changed live (`type: "edit"`): the
+/// `message list --json` entry plus `type` and `room`.
+pub(crate) fn message_event_json(
+    room_state: &ChatRoomStateV1,
+    msg: &river_core::room_state::message::AuthorizedMessageV1,
+    room_owner_key: &VerifyingKey,
+    is_edit: bool,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> serde_json::Value {
+    let mut event = crate::commands::message::message_json(room_state, msg, secrets);
+    event["type"] = json!(if is_edit { "edit" } else { "message" });
+    event["room"] = json!(bs58::encode(room_owner_key.as_bytes()).into_string());
+    event
+}
 
-        // Create delta with just the configuration change
-        let delta = ChatRoomStateV1Delta {
-            configuration: Some(authorized_config),
-            ..Default::default()
-        };
+/// A `type: "delete"` stream event: the content is gone, so only the
+/// message's identity, author and time are reported.
+pub(crate) fn deletion_event_json(
+    room_state: &ChatRoomStateV1,
+    msg: &river_core::room_state::message::AuthorizedMessageV1,
+    room_owner_key: &VerifyingKey,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> serde_json::Value {
+    let nickname = room_state
+        .member_info
+        .canonical(msg.message.author)
+        .map(|info| unseal_nickname_display(&info.member_info.preferred_nickname, secrets));
+    let datetime: DateTime<Utc> = msg.message.time.into();
+    json!({
+        "type": "delete",
+        "message_id": msg.id().0 .0.to_string(),
+        "room": bs58::encode(room_owner_key.as_bytes()).into_string(),
+        "author": msg.message.author.to_string(),
+        "nickname": nickname,
+        "timestamp": datetime.to_rfc3339(),
+    })
+}
 
-        // Serialize and send
-        let delta_bytes = {
-            let mut buf = Vec::new();
-            ciborium::ser::into_writer(&delta, &mut buf)
-                .map_err(|e| anyhow!("Failed to serialize delta: {}", e))?;
-            buf
-        };
+/// A `type: "reaction"` stream event carrying the message's current
+/// reactions (`emoji -> count`); empty when the last one was removed.
+pub(crate) fn reaction_event_json(
+    room_state: &ChatRoomStateV1,
+    msg: &river_core::room_state::message::AuthorizedMessageV1,
+    room_owner_key: &VerifyingKey,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> serde_json::Value {
+    let msg_id = msg.id();
+    let nickname = room_state
+        .member_info
+        .canonical(msg.message.author)
+        .map(|info| unseal_nickname_display(&info.member_info.preferred_nickname, secrets));
+    let datetime: DateTime<Utc> = msg.message.time.into();
+    let reactions: HashMap<String, usize> = room_state
+        .recent_messages
+        .reactions(&msg_id)
+        .map(|r| r.iter().map(|(k, v)| (k.clone(), v.len())).collect())
+        .unwrap_or_default();
+    json!({
+        "type": "reaction",
+        "message_id": msg_id.0 .0.to_string(),
+        "room": bs58::encode(room_owner_key.as_bytes()).into_string(),
+        "author": msg.message.author.to_string(),
+        "nickname": nickname,
+        "timestamp": datetime.to_rfc3339(),
+        "reactions": reactions,
+    })
+}
 
-        let contract_key = self.owner_vk_to_contract_key(room_owner_key);
+/// The monitor stream's change detection without the printing, for a
+/// long-running consumer that hands events on (`riverctl serve`). Every
+/// message present when the tracker is created counts as already surfaced,
+/// so only later changes become events, and an edit, deletion or reaction to
+/// one of them is still reported.
+pub(crate) struct RoomEventTracker {
+    room_owner_key: VerifyingKey,
+    seen: HashMap<String, String>,
+    deleted_emitted: HashSet<String>,
+    seen_reactions: HashMap<String, String>,
+}
 
-        let update_request = ContractRequest::Update {
-            key: contract_key,
-            data: UpdateData::Delta(delta_bytes.into()),
+impl RoomEventTracker {
+    pub(crate) fn new(
+        room_owner_key: VerifyingKey,
+        room_state: &ChatRoomStateV1,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Self {
+        let mut tracker = Self {
+            room_owner_key,
+            seen: HashMap::new(),
+            deleted_emitted: HashSet::new(),
+            seen_reactions: HashMap::new(),
         };
+        // A message deleted before now is never in `seen`, so its deletion
+        // is not reported either.
+        tracker.changes(room_state, secrets);
+        tracker
+    }
 
-        let client_request = ClientRequest::ContractOp(update_request);
-
-        let mut web_api = self.web_api.lock().await;
-        web_api
-            .send(client_request)
-            .await
-            .map_err(|e| anyhow!("Failed to send update request: {}", e))?;
-
-        let response =
-            match tokio::time::timeout(std::time::Duration::from_secs(60), web_api.recv()).await {
-                Ok(Ok(response)) => response,
-                Ok(Err(e)) => return Err(anyhow!("Failed to receive response: {}", e)),
-                Err(_) => {
-                    return Err(anyhow!(
-                        "Timeout waiting for update response after 60 seconds"
-                    ))
-                }
+    /// The events that turn the last state seen into `room_state`, in the
+    /// order the monitor stream emits them: new and edited messages, then
+    /// deletions, then reaction changes.
+    pub(crate) fn changes(
+        &mut self,
+        room_state: &ChatRoomStateV1,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Vec<serde_json::Value> {
+        let owner = &self.room_owner_key;
+        let mut events = Vec::new();
+        for msg in room_state.recent_messages.display_messages() {
+            let key = monitor_seen_key(msg);
+            let content = message_display_text_with_secrets(room_state, msg, secrets);
+            let is_edit = match classify_seen(&self.seen, &key, &content) {
+                EmitKind::Unchanged => continue,
+                EmitKind::Edited => true,
+                EmitKind::New => false,
             };
-
-        match response {
-            HostResponse::ContractResponse(ContractResponse::UpdateResponse { key, .. }) => {
-                info!(
-                    "Configuration updated successfully for contract: {}",
-                    key.id()
-                );
-                Ok(())
+            events.push(message_event_json(room_state, msg, owner, is_edit, secrets));
+            self.deleted_emitted.remove(&key);
+            self.seen_reactions.insert(
+                key.clone(),
+                reactions_fingerprint(room_state.recent_messages.reactions(&msg.id())),
+            );
+            self.seen.insert(key, content);
+        }
+        for msg in &room_state.recent_messages.messages {
+            if !room_state.recent_messages.is_deleted(&msg.id()) {
+                continue;
+            }
+            let key = monitor_seen_key(msg);
+            if should_emit_deletion(&self.seen, &self.deleted_emitted, &key) {
+                events.push(deletion_event_json(room_state, msg, owner, secrets));
+                self.deleted_emitted.insert(key);
+            }
+        }
+        for msg in room_state.recent_messages.display_messages() {
+            let key = monitor_seen_key(msg);
+            let fingerprint =
+                reactions_fingerprint(room_state.recent_messages.reactions(&msg.id()));
+            if classify_reaction(&self.seen_reactions, &key, &fingerprint) == ReactionEmit::Changed
+            {
+                events.push(reaction_event_json(room_state, msg, owner, secrets));
+                self.seen_reactions.insert(key, fingerprint);
             }
-            _ => Err(anyhow!("Unexpected response type: {:?}", response)),
         }
+        events
     }
+}
 
-    /// Subscribe to a room and stream updates using Freenet subscriptions
-    ///
-    /// Unlike `stream_messages` which polls, this method subscribes to the contract
-    /// and receives push notifications when the contract state changes.
-    pub async fn subscribe_and_stream(
-        &self,
-        room_owner_key: &VerifyingKey,
-        timeout_secs: u64,
-        max_messages: usize,
-        initial_messages: usize,
-        format: OutputFormat,
-    ) -> Result<()> {
-        // Verify room exists in local storage before attempting to subscribe
-        self.storage.get_room(room_owner_key)?.ok_or_else(|| {
-            anyhow!("Room not found in local storage. You may need to create or join it first.")
-        })?;
+/// Resolve the caller's own CANONICAL `member_info` record to republish from —
+/// the shared base for `set_nickname`'s nickname change and
+/// `update_own_deputies`'s deputy add/revoke. Returns a clone of the winning
+/// `MemberInfo` (version, nickname, deputies) so callers can freely rebuild
+/// one field.
+///
+/// Routes through `MemberInfoV1::canonical`, NOT a bare `.find()` (#411 round
+/// 8 item A): `verify` deliberately ACCEPTS a state carrying more than one
+/// `member_info` record for the same member (migration-safety), so a client
+/// can hold such a duplicate-containing state before cleanup runs. A bare
+/// first-match `.find()` could then read a LOSING (e.g. revoked) record, and
+/// republishing it at `version + 1` would resurrect that losing record at a
+/// HIGHER rank — reactivating revoked deputy authority. `canonical` always
+/// selects the highest-`member_info_rank` record, matching what
+/// `apply_delta` / `summarize` converge on.
+fn resolve_own_member_info_base(
+    room_state: &ChatRoomStateV1,
+    member_id: MemberId,
+) -> Option<MemberInfo> {
+    room_state
+        .member_info
+        .canonical(member_id)
+        .map(|info| info.member_info.clone())
+}
 
-        // Print header for human format
-        if matches!(format, OutputFormat::Human) {
-            eprintln!(
-                "Subscribing to room {} (press Ctrl+C to stop)...",
-                bs58::encode(room_owner_key.as_bytes()).into_string()
-            );
-        }
+/// Resolve a `member set-deputy` / `revoke-deputy` short id to a full `MemberId`.
+///
+/// Primary: a present member, matched via their `member_info` (same rule as
+/// `ban_member` — full-string prefix, or a case-insensitive first-8-chars match).
+/// Revoke fallback (#411 round 4 C): when `allow_deputy_fallback` is set (the
+/// revoke path), also match the caller's OWN `deputies`, so a deputy who was
+/// pruned for inactivity — their `member_info` is gone but their id still sits in
+/// the caller's signed deputies list — can still be revoked.
+fn resolve_deputy_target(
+    member_infos: &[AuthorizedMemberInfo],
+    own_deputies: &[MemberId],
+    member_id_short: &str,
+    allow_deputy_fallback: bool,
+) -> Option<MemberId> {
+    let matches = |id: &MemberId| {
+        let s = id.to_string();
+        s.starts_with(member_id_short) || s[..8.min(s.len())].eq_ignore_ascii_case(member_id_short)
+    };
+    member_infos
+        .iter()
+        .map(|info| info.member_info.member_id)
+        .find(|id| matches(id))
+        .or_else(|| {
+            if allow_deputy_fallback {
+                own_deputies.iter().copied().find(|id| matches(id))
+            } else {
+                None
+            }
+        })
+}
 
-        // Track seen messages: key -> last-emitted effective content, so a later
-        // edit (content change) is detected and re-emitted, not just new ids.
-        let mut seen_messages: HashMap<String, String> = HashMap::new();
-        // Messages for which a deletion has already been emitted (one-shot).
-        // Pre-seeded below with deletions that existed at stream start, so only
-        // deletions observed live are surfaced.
-        let mut deleted_emitted: HashSet<String> = HashSet::new();
-        // Track each shown message's reactions fingerprint so a reaction
-        // added/removed AFTER it was streamed surfaces as a `reaction` event
-        // (freenet/river#325). Seeded ONLY for messages actually shown at start
-        // (below) and for messages surfaced live thereafter (lazily by
-        // emit_reaction_changes). A reaction on a pre-existing message the stream
-        // never showed is therefore NOT surfaced — the same "only for messages
-        // the stream displayed" rule the deletion path follows (#324 review).
-        let mut seen_reactions: HashMap<String, String> = HashMap::new();
-        let mut new_message_count = 0;
-        let start_time = std::time::Instant::now();
+#[cfg(test)]
+mod deputy_resolve_tests {
+    use super::resolve_deputy_target;
+    use ed25519_dalek::SigningKey;
+    use river_core::room_state::member::MemberId;
+    use river_core::room_state::member_info::{AuthorizedMemberInfo, MemberInfo};
 
-        // Fetch current room state to pre-populate seen_messages and trigger
-        // migration if needed (get_room calls ensure_room_migrated internally).
-        let contract_key = self.owner_vk_to_contract_key(room_owner_key);
-        let contract_instance_id = *contract_key.id();
-        {
-            let mut room_state = self.get_room(room_owner_key, false).await?;
-            // Decrypt private-room content for display (no-op for public rooms).
-            // Must run before the immutable `display_msgs` borrow below.
-            let secrets = self.room_display_secrets(room_owner_key, &mut room_state);
+    fn info(sk: &SigningKey) -> AuthorizedMemberInfo {
+        let id: MemberId = sk.verifying_key().into();
+        AuthorizedMemberInfo::new_with_member_key(
+            MemberInfo::new_public(id, 0, "n".to_string()),
+            sk,
+        )
+    }
+
+    fn short(id: &MemberId) -> String {
+        let s = id.to_string();
+        s[..8.min(s.len())].to_string()
+    }
+
+    /// #411 round 4 C: revoke can resolve a pruned deputy (no member_info) from
+    /// the caller's own deputies list; deputize cannot resolve a non-member.
+    #[test]
+    fn revoke_resolves_pruned_deputy_from_own_deputies_list() {
+        // Distinct fixed seeds → distinct keys (the cli crate's dalek does not
+        // enable the `rand` `generate` helper; mirrors the other cli tests).
+        let present = SigningKey::from_bytes(&[7u8; 32]);
+        let pruned_deputy = SigningKey::from_bytes(&[9u8; 32]);
+        let present_id: MemberId = present.verifying_key().into();
+        let pruned_id: MemberId = pruned_deputy.verifying_key().into();
+
+        // Only the present member has a member_info entry; the pruned deputy's
+        // member_info is gone but their id still sits in own_deputies.
+        let member_infos = vec![info(&present)];
+        let own_deputies = vec![pruned_id];
+
+        // A present member resolves via the primary lookup in both modes.
+        assert_eq!(
+            resolve_deputy_target(&member_infos, &own_deputies, &short(&present_id), false),
+            Some(present_id)
+        );
+
+        // The pruned deputy is NOT resolvable when deputizing (no member_info)...
+        assert_eq!(
+            resolve_deputy_target(&member_infos, &own_deputies, &short(&pruned_id), false),
+            None,
+            "deputize must not resolve a non-member"
+        );
+        // ...but IS resolvable when revoking (fallback to the caller's deputies).
+        assert_eq!(
+            resolve_deputy_target(&member_infos, &own_deputies, &short(&pruned_id), true),
+            Some(pruned_id),
+            "revoke resolves a pruned deputy from the caller's own deputies list"
+        );
 
-            // Determine which messages will be displayed initially (the last N
-            // non-deleted). Only these count as "shown" for deletion purposes.
-            let display_msgs: Vec<_> = room_state.recent_messages.display_messages().collect();
-            let display_start = if initial_messages > 0 {
-                display_msgs.len().saturating_sub(initial_messages)
-            } else {
-                display_msgs.len() // display nothing
-            };
-            let shown_keys: HashSet<String> = display_msgs[display_start..]
-                .iter()
-                .map(|m| monitor_seen_key(m))
-                .collect();
+        // An unknown short id resolves to nothing even with the fallback.
+        assert_eq!(
+            resolve_deputy_target(&member_infos, &own_deputies, "zzzzzzzz", true),
+            None
+        );
+    }
+}
 
-            // Mark ALL non-action messages as seen (key -> effective content),
-            // including deleted ones, so old messages aren't re-shown as new
-            // (https://github.com/freenet/river/issues/173) and later edits are
-            // detected as content changes.
-            for msg in &room_state.recent_messages.messages {
-                if !msg.message.content.is_action() {
-                    seen_messages.insert(
-                        monitor_seen_key(msg),
-                        message_display_text_with_secrets(&room_state, msg, &secrets),
-                    );
-                }
-            }
+/// Resolve a `member unban` short id against the room's bans, since a banned
+/// member no longer has `member_info`. Returns the target and every ban on
+/// them, or an error if there are none or the caller may not revoke one of
+/// them (same authority rule as `BanRevocationsV1::revocation_is_effective`).
+fn resolve_unban_target(
+    room_state: &ChatRoomStateV1,
+    my_member_id: MemberId,
+    owner_member_id: MemberId,
+    member_id_short: &str,
+) -> Result<(MemberId, Vec<AuthorizedUserBan>)> {
+    let target = room_state
+        .bans
+        .0
+        .iter()
+        .map(|ban| ban.ban.banned_user)
+        .find(|id| {
+            let s = id.to_string();
+            s.starts_with(member_id_short)
+                || s[..8.min(s.len())].eq_ignore_ascii_case(member_id_short)
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "No ban found for '{}'. Use 'debug bans' to see banned members.",
+                member_id_short
+            )
+        })?;
+    let bans: Vec<AuthorizedUserBan> = room_state
+        .bans
+        .0
+        .iter()
+        .filter(|ban| ban.ban.banned_user == target)
+        .cloned()
+        .collect();
 
-            // Suppress live deletion events for every pre-existing message we are
-            // NOT showing now (already-deleted ones, and history outside the
-            // initial window — including the default initial_messages == 0 which
-            // shows nothing). Only deletions of messages the stream actually
-            // showed — or emits live later — are surfaced. #324 review.
-            deleted_emitted.extend(deletions_to_suppress_at_start(
-                &room_state.recent_messages.messages,
-                &shown_keys,
+    let members_by_id = room_state.members.members_by_member_id();
+    for ban in &bans {
+        let authorized = my_member_id == ban.banned_by
+            || river_core::room_state::member::MembersV1::is_ban_authorized(
+                my_member_id,
+                ban.banned_by,
+                &members_by_id,
+                &room_state.member_info,
+                owner_member_id,
+            );
+        if !authorized {
+            return Err(anyhow!(
+                "Not authorized to lift the ban on {} issued by {}. A ban can be lifted by \
+                 whoever issued it, anyone above them in the invite chain, or their deputies.",
+                target,
+                ban.banned_by
             ));
-
-            // Show the last N display messages.
-            for (i, msg) in display_msgs.iter().enumerate() {
-                if i >= display_start {
-                    // Seed the reactions fingerprint for each shown message so
-                    // reactions already present at startup aren't re-emitted as a
-                    // live change; only later changes to them surface.
-                    seen_reactions.insert(
-                        monitor_seen_key(msg),
-                        reactions_fingerprint(room_state.recent_messages.reactions(&msg.id())),
-                    );
-                    Self::output_message(
-                        &room_state,
-                        msg,
-                        room_owner_key,
-                        &format,
-                        false,
-                        &secrets,
-                    )?;
-                }
-            }
         }
+    }
+    Ok((target, bans))
+}
 
-        // Responses that arrive before the SUBSCRIBE acknowledgement; drained
-        // by the main loop below so none is lost.
-        let mut pending: std::collections::VecDeque<HostResponse> =
-            std::collections::VecDeque::new();
+/// Resolve the member a `member mute`/`unmute` targets and check the caller
+/// may moderate them. Unmuting someone who is not muted is refused so a typo
+/// does not publish a pointless record.
+fn resolve_mute_target(
+    room_state: &ChatRoomStateV1,
+    params: &ChatRoomParametersV1,
+    my_member_id: MemberId,
+    member_id_short: &str,
+    muting: bool,
+) -> Result<MemberId> {
+    let owner_member_id = params.owner_id();
+    let target = room_state
+        .members
+        .members
+        .iter()
+        .map(|m| m.member.id())
+        .find(|id| {
+            let s = id.to_string();
+            s.starts_with(member_id_short)
+                || s[..8.min(s.len())].eq_ignore_ascii_case(member_id_short)
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Member '{}' not found in room. Use 'member list' to see members.",
+                member_id_short
+            )
+        })?;
 
-        // Subscribe to the contract
-        {
-            let subscribe_request = ContractRequest::Subscribe {
-                key: contract_instance_id, // Subscribe uses ContractInstanceId
-                summary: None,
-            };
+    let members_by_id = room_state.members.members_by_member_id();
+    if !river_core::room_state::member::MembersV1::is_ban_authorized(
+        my_member_id,
+        target,
+        &members_by_id,
+        &room_state.member_info,
+        owner_member_id,
+    ) {
+        return Err(anyhow!(
+            "Not authorized to moderate {}. A member can be muted or unmuted by the room \
+             owner, anyone above them in the invite chain, or their deputies.",
+            target
+        ));
+    }
 
-            let client_request = ClientRequest::ContractOp(subscribe_request);
+    let muted = room_state
+        .mutes
+        .muted_members(
+            &members_by_id,
+            &room_state.member_info,
+            &room_state.recent_messages,
+            owner_member_id,
+            &params.owner,
+            room_state.configuration.configuration.max_members,
+        )
+        .contains_key(&target);
+    if !muting && !muted {
+        return Err(anyhow!("Member {} is not muted.", target));
+    }
+    Ok(target)
+}
 
-            let mut web_api = self.web_api.lock().await;
-            web_api
-                .send(client_request)
-                .await
-                .map_err(|e| anyhow!("Failed to send SUBSCRIBE request: {}", e))?;
+/// Why the contract would refuse `invitee` joining at `now`: the newest
+/// invite record for them is a revocation, expired before `now`, or has no
+/// uses left. `None` when the invitation is still usable or carries no
+/// record.
+fn invitation_refusal(
+    room_state: &ChatRoomStateV1,
+    invitee: MemberId,
+    now: std::time::SystemTime,
+) -> Option<anyhow::Error> {
+    let record = room_state
+        .invites
+        .latest_for(&room_state.invite_uses, invitee)?;
+    match &record.invite.policy {
+        InvitePolicy::Revoked => Some(anyhow!(
+            "This invitation has been revoked. Ask whoever sent it for a new one."
+        )),
+        InvitePolicy::Open {
+            expires_at: Some(expires_at),
+            ..
+        } if *expires_at <= now => Some(anyhow!(
+            "This invitation expired on {}. Ask whoever sent it for a new one.",
+            DateTime::<Local>::from(*expires_at).format("%Y-%m-%d %H:%M")
+        )),
+        InvitePolicy::Open {
+            max_uses: Some(max_uses),
+            ..
+        } if room_state.invite_uses.count_for(invitee) >= *max_uses as usize => Some(anyhow!(
+            "This invitation has been used {} time(s), its limit. Ask whoever sent it for a new one.",
+            max_uses
+        )),
+        InvitePolicy::Open { .. } => None,
+    }
+}
 
-            // The node's responses share one multiplexed connection, so an
-            // UpdateNotification for a contract we are already watching can
-            // arrive between our SUBSCRIBE and its acknowledgement. Treating
-            // the first message as the answer made that race fatal: the
-            // session died with "Unexpected response to SUBSCRIBE request",
-            // reconnected, and raced again. On 2026-07-27 that produced 299
-            // failures in four hours and up to 184 reconnects in a single
-            // hour against the official room (freenet-core#4970), and each
-            // reconnect dragged a catch-up batch behind it.
-            //
-            // So read until the acknowledgement actually arrives, and keep
-            // anything that overtakes it. The PUT path above already tolerates
-            // an interleaved UpdateNotification the same way.
-            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
-            loop {
-                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
-                if remaining.is_zero() {
-                    return Err(anyhow!("Timeout waiting for SUBSCRIBE response"));
+/// The identity to join `room_state` with. An invitation admits its invitee;
+/// once they are in the room, a link with a use limit lets each later holder
+/// join as a fresh member the invitation's key signs in, which the room
+/// counts against the limit.
+fn joining_identity(room_state: &ChatRoomStateV1, invitation: Invitation) -> Invitation {
+    let invitee = invitation.invitee.member.id();
+    let multi_use = room_state
+        .invites
+        .latest_for(&room_state.invite_uses, invitee)
+        .is_some_and(|record| record.invite.policy.max_uses().is_some());
+    if !multi_use
+        || !room_state
+            .members
+            .members_by_member_id()
+            .contains_key(&invitee)
+    {
+        return invitation;
+    }
+    let signing_key = SigningKey::from_bytes(&rand::Rng::gen::<[u8; 32]>(&mut rand::thread_rng()));
+    let member = AuthorizedMember::new(
+        Member {
+            owner_member_id: invitation.invitee.member.owner_member_id,
+            invited_by: invitee,
+            member_vk: signing_key.verifying_key(),
+        },
+        &invitation.invitee_signing_key,
+    );
+    Invitation {
+        invitee: member,
+        invitee_signing_key: signing_key,
+        ..invitation
+    }
+}
+
+/// Resolve the invitation `invitation` names for `invite revoke`: a full
+/// invitation code, or a prefix of the invitee ID among the invite records
+/// `my_member_id` may revoke (their own; every record for the owner). Refuses
+/// an invitation already accepted, where a ban is the tool, or already
+/// revoked.
+fn resolve_invite_to_revoke(
+    room_state: &ChatRoomStateV1,
+    params: &ChatRoomParametersV1,
+    my_member_id: MemberId,
+    invitation: &str,
+) -> Result<MemberId> {
+    let is_owner = my_member_id == params.owner_id();
+    let decoded = bs58::decode(invitation)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| ciborium::de::from_reader::<Invitation, _>(&bytes[..]).ok());
+    let invitee = match decoded {
+        Some(decoded) => {
+            if decoded.room != params.owner {
+                return Err(anyhow!("This invitation is for a different room."));
+            }
+            if !is_owner && decoded.invitee.member.invited_by != my_member_id {
+                return Err(anyhow!(
+                    "This invitation was issued by someone else; only they or the room owner \
+                     can revoke it."
+                ));
+            }
+            decoded.invitee.member.id()
+        }
+        None => {
+            let matches: std::collections::BTreeSet<MemberId> = room_state
+                .invites
+                .0
+                .iter()
+                .filter(|record| is_owner || record.issued_by == my_member_id)
+                .map(|record| record.invite.invitee)
+                .filter(|id| {
+                    let s = id.to_string();
+                    s.starts_with(invitation)
+                        || s[..8.min(s.len())].eq_ignore_ascii_case(invitation)
+                })
+                .collect();
+            let mut matches = matches.into_iter();
+            match (matches.next(), matches.next()) {
+                (Some(invitee), None) => invitee,
+                (None, _) => {
+                    return Err(anyhow!(
+                        "No invitation '{}' found. Use 'invite list' to see your invitations, \
+                         or pass the invitation code.",
+                        invitation
+                    ))
                 }
-                let response = match tokio::time::timeout(remaining, web_api.recv()).await {
-                    Ok(result) => {
-                        result.map_err(|e| anyhow!("Failed to receive response: {}", e))?
-                    }
-                    Err(_) => return Err(anyhow!("Timeout waiting for SUBSCRIBE response")),
-                };
-                match classify_subscribe_response(&response) {
-                    SubscribeAck::Subscribed => {
-                        if matches!(format, OutputFormat::Human) {
-                            eprintln!("Successfully subscribed. Waiting for updates...\n");
-                        }
-                        break;
-                    }
-                    SubscribeAck::Refused => {
-                        return Err(anyhow!("Failed to subscribe to contract"))
-                    }
-                    // Queued rather than dropped: this is a real state change,
-                    // and the main loop below drains `pending` before reading
-                    // the socket, so it goes through exactly the same handling
-                    // it would have had if it arrived a moment later.
-                    SubscribeAck::NotYet => {
-                        // Bounded because the node feeds this queue and a busy
-                        // room can emit a lot inside the handshake window; an
-                        // unbounded queue here would be a memory amplification
-                        // vector. Collapsing is lossless: the handler below
-                        // discards the delta (`let _ = update;`) and re-fetches
-                        // authoritative full state, so N queued notifications
-                        // produce exactly the same result as one.
-                        if pending.len() < MAX_PENDING_DURING_HANDSHAKE {
-                            debug!("Response overtook the SUBSCRIBE ack, queuing it");
-                            pending.push_back(response);
-                        } else {
-                            debug!("Response overtook the SUBSCRIBE ack; queue full, collapsing");
-                        }
-                    }
+                (Some(_), Some(_)) => {
+                    return Err(anyhow!(
+                        "'{}' matches several invitations; give more of the ID.",
+                        invitation
+                    ))
                 }
             }
         }
+    };
 
-        // Set up Ctrl+C handler
-        let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
+    if room_state
+        .members
+        .members_by_member_id()
+        .contains_key(&invitee)
+    {
+        return Err(anyhow!(
+            "{} already accepted this invitation. Use 'member ban' to remove them.",
+            invitee
+        ));
+    }
+    if room_state
+        .invites
+        .latest_for(&room_state.invite_uses, invitee)
+        .is_some_and(|record| record.invite.policy == InvitePolicy::Revoked)
+    {
+        return Err(anyhow!(
+            "The invitation for {} is already revoked.",
+            invitee
+        ));
+    }
+    Ok(invitee)
+}
 
-        tokio::spawn(async move {
-            tokio::signal::ctrl_c().await.ok();
-            let _ = shutdown_tx.send(()).await;
-        });
+/// What [`ApiClient::knock`] did, with the requester's member ID.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KnockOutcome {
+    /// A new request was published.
+    Requested(MemberId),
+    /// The earlier request is still waiting for a decision.
+    Waiting(MemberId),
+    /// The request was approved and this client joined.
+    Joined(MemberId),
+    /// The request was denied.
+    Denied(MemberId),
+}
 
-        // Main loop: wait for UpdateNotification messages
-        loop {
-            // Check for shutdown signal
-            if shutdown_rx.try_recv().is_ok() {
-                if matches!(format, OutputFormat::Human) {
-                    eprintln!("\nStopped monitoring.");
-                }
-                return Ok(());
-            }
+/// Where the join request signed by `requester` stands in `room_state`.
+#[derive(Debug, PartialEq)]
+enum KnockProgress {
+    Approved(Box<AuthorizedMember>),
+    Denied,
+    Waiting,
+    /// Neither a request nor a decision is held, e.g. because the queue was
+    /// full.
+    Dropped,
+}
 
-            // Check timeout
-            if timeout_secs > 0 && start_time.elapsed().as_secs() >= timeout_secs {
-                debug!("Timeout reached, exiting subscription stream");
-                return Ok(());
-            }
+fn knock_progress(room_state: &ChatRoomStateV1, requester: &VerifyingKey) -> KnockProgress {
+    let requester_id = MemberId::from(requester);
+    let join_requests = &room_state.join_requests;
+    match join_requests.latest_decision_for(requester_id) {
+        Some(decision) => match &decision.decision.outcome {
+            JoinOutcome::Approved(member) => KnockProgress::Approved(member.clone()),
+            JoinOutcome::Denied => KnockProgress::Denied,
+        },
+        None if join_requests
+            .requests
+            .iter()
+            .any(|request| request.requester_id() == requester_id) =>
+        {
+            KnockProgress::Waiting
+        }
+        None => KnockProgress::Dropped,
+    }
+}
 
-            // Check max messages
-            if max_messages > 0 && new_message_count >= max_messages {
-                debug!("Maximum message count reached, exiting subscription stream");
-                return Ok(());
-            }
+/// Resolve the requester `member approve`/`deny` names: a prefix of a member
+/// ID among the held join requests and decisions. Refuses someone who is
+/// already a member.
+fn resolve_join_requester(
+    room_state: &ChatRoomStateV1,
+    requester_short: &str,
+) -> Result<VerifyingKey> {
+    let join_requests = &room_state.join_requests;
+    let candidates: std::collections::BTreeMap<MemberId, VerifyingKey> = join_requests
+        .requests
+        .iter()
+        .map(|request| (request.requester_id(), request.request.requester))
+        .chain(
+            join_requests
+                .decisions
+                .iter()
+                .map(|decision| (decision.requester_id(), decision.decision.requester)),
+        )
+        .filter(|(id, _)| {
+            let s = id.to_string();
+            s.starts_with(requester_short)
+                || s[..8.min(s.len())].eq_ignore_ascii_case(requester_short)
+        })
+        .collect();
+    let mut matches = candidates.into_iter();
+    let (requester_id, requester) = match (matches.next(), matches.next()) {
+        (Some(found), None) => found,
+        (None, _) => {
+            return Err(anyhow!(
+                "No join request from '{}'. Use 'member requests' to see them.",
+                requester_short
+            ))
+        }
+        (Some(_), Some(_)) => {
+            return Err(anyhow!(
+                "'{}' matches several join requests; give more of the ID.",
+                requester_short
+            ))
+        }
+    };
+    if room_state
+        .members
+        .members_by_member_id()
+        .contains_key(&requester_id)
+    {
+        return Err(anyhow!("{} is already a member.", requester_id));
+    }
+    Ok(requester)
+}
+
+#[cfg(test)]
+mod invite_revoke_tests {
+    use super::{invitation_refusal, joining_identity, resolve_invite_to_revoke, Invitation};
+    use ed25519_dalek::SigningKey;
+    use river_core::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+    use river_core::room_state::invite::{AuthorizedInvite, Invite, InvitePolicy, InvitesV1};
+    use river_core::room_state::invite_use::{InviteUse, InviteUsesV1};
+    use river_core::room_state::member::{AuthorizedMember, Member, MemberId, MembersV1};
+    use river_core::room_state::message::{AuthorizedMessageV1, MessageV1, RoomMessageBody};
+    use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1};
+    use std::time::{Duration, SystemTime};
+
+    fn id(sk: &SigningKey) -> MemberId {
+        sk.verifying_key().into()
+    }
 
-            // Wait for next message with a short timeout to allow checking shutdown
-            // The guard is taken in both branches so the notification handler
-            // below can still `drop(web_api)` before it calls `get_room`.
-            let mut web_api = self.web_api.lock().await;
-            let recv_result = if let Some(queued) = pending.pop_front() {
-                Ok(Ok(queued))
-            } else {
-                tokio::time::timeout(std::time::Duration::from_millis(500), web_api.recv()).await
+    /// Owner invites A and C; A has issued an expiring invitation to I.
+    #[test]
+    fn only_the_issuer_or_owner_revokes_a_pending_invitation() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let a = SigningKey::from_bytes(&[2u8; 32]);
+        let c = SigningKey::from_bytes(&[3u8; 32]);
+        let i = SigningKey::from_bytes(&[4u8; 32]);
+        let params = ChatRoomParametersV1 {
+            owner: owner.verifying_key(),
+        };
+        let member = |sk: &SigningKey, inviter: &SigningKey| {
+            AuthorizedMember::new(
+                Member {
+                    owner_member_id: id(&owner),
+                    invited_by: id(inviter),
+                    member_vk: sk.verifying_key(),
+                },
+                inviter,
+            )
+        };
+        let issued_at = SystemTime::now();
+        let record = |policy| {
+            AuthorizedInvite::new(
+                Invite {
+                    owner_member_id: id(&owner),
+                    invitee: id(&i),
+                    issued_at,
+                    policy,
+                },
+                id(&a),
+                &a,
+            )
+        };
+        let expires_at = issued_at + Duration::from_secs(3600);
+        let mut state = ChatRoomStateV1 {
+            configuration: AuthorizedConfigurationV1::new(Configuration::default(), &owner),
+            members: MembersV1 {
+                members: vec![member(&a, &owner), member(&c, &owner)],
+            },
+            invites: InvitesV1(vec![record(InvitePolicy::Open {
+                expires_at: Some(expires_at),
+                label: None,
+                max_uses: None,
+            })]),
+            ..Default::default()
+        };
+        let i_short = id(&i).to_string()[..8].to_string();
+        let code = {
+            let invitation = Invitation {
+                room: owner.verifying_key(),
+                invitee_signing_key: i.clone(),
+                invitee: member(&i, &a),
+                room_secrets: Vec::new(),
             };
+            let mut data = Vec::new();
+            ciborium::ser::into_writer(&invitation, &mut data).unwrap();
+            bs58::encode(data).into_string()
+        };
 
-            match recv_result {
-                Ok(Ok(HostResponse::ContractResponse(ContractResponse::UpdateNotification {
-                    key,
-                    update,
-                }))) => {
-                    // We received an update notification
-                    debug!("Received update notification for contract: {}", key.id());
-
-                    // Any notification — a delta (INCLUDING edit/delete/reaction
-                    // action deltas) or a full-state update — can change what
-                    // should be shown. Rather than parse the delta and skip
-                    // actions (which made the stream oblivious to edits), re-fetch
-                    // the authoritative full state and emit any NEW or EDITED
-                    // messages. Deleted messages are excluded by display_messages
-                    // and stay marked seen, so #173 (phantom deleted messages)
-                    // still holds. The delta payload itself is advisory here.
-                    let _ = update;
-                    drop(web_api); // get_room needs the web_api lock
-                    match self.get_room(room_owner_key, false).await {
-                        Ok(mut room_state) => {
-                            // Decrypt private-room content for display (no-op for public rooms).
-                            let secrets =
-                                self.room_display_secrets(room_owner_key, &mut room_state);
-                            Self::emit_new_and_edited(
-                                &room_state,
-                                &mut seen_messages,
-                                &mut deleted_emitted,
-                                &mut seen_reactions,
-                                room_owner_key,
-                                &format,
-                                max_messages,
-                                &mut new_message_count,
-                                &secrets,
-                            )?;
-                            Self::emit_deletions(
-                                &room_state,
-                                &seen_messages,
-                                &mut deleted_emitted,
-                                room_owner_key,
-                                &format,
-                                &secrets,
-                            )?;
-                            // Surface reactions added/removed since a message was
-                            // already streamed. Runs AFTER emit_new_and_edited so a
-                            // brand-new message is seeded (not re-emitted) here.
-                            Self::emit_reaction_changes(
-                                &room_state,
-                                &mut seen_reactions,
-                                room_owner_key,
-                                &format,
-                                &secrets,
-                            )?;
-                        }
-                        Err(e) => {
-                            debug!("Failed to fetch room state after notification: {}", e);
-                        }
-                    }
-                    if max_messages > 0 && new_message_count >= max_messages {
-                        return Ok(());
-                    }
-                }
-                Ok(Ok(other)) => {
-                    // Other message type, log and continue
-                    debug!("Received unexpected message: {:?}", other);
-                }
-                Ok(Err(e)) => {
-                    // WebSocket error
-                    return Err(anyhow!("WebSocket error: {}", e));
-                }
-                Err(_) => {
-                    // Timeout, continue loop (allows checking shutdown signal)
-                }
+        for revoker in [&a, &owner] {
+            for name in [&i_short, &code] {
+                let invitee = resolve_invite_to_revoke(&state, &params, id(revoker), name);
+                assert_eq!(invitee.unwrap(), id(&i));
             }
         }
+        for name in [&i_short, &code] {
+            assert!(resolve_invite_to_revoke(&state, &params, id(&c), name).is_err());
+        }
+
+        assert!(invitation_refusal(&state, id(&i), issued_at).is_none());
+        assert!(invitation_refusal(&state, id(&i), expires_at).is_some());
+
+        state.invites = InvitesV1(vec![record(InvitePolicy::Revoked)]);
+        assert!(invitation_refusal(&state, id(&i), issued_at).is_some());
+        assert!(resolve_invite_to_revoke(&state, &params, id(&a), &i_short).is_err());
+
+        // Once accepted, revoking is a ban's job.
+        state.invites = InvitesV1::default();
+        state.members.members.push(member(&i, &a));
+        assert!(resolve_invite_to_revoke(&state, &params, id(&a), &code).is_err());
     }
-}
 
-/// Resolve the caller's own CANONICAL `member_info` record to republish from —
-/// the shared base for `set_nickname`'s nickname change and
-/// `update_own_deputies`'s deputy add/revoke. Returns a clone of the winning
-/// `MemberInfo` (version, nickname, deputies) so callers can freely rebuild
-/// one field.
-///
-/// Routes through `MemberInfoV1::canonical`, NOT a bare `.find()` (#411 round
-/// 8 item A): `verify` deliberately ACCEPTS a state carrying more than one
-/// `member_info` record for the same member (migration-safety), so a client
-/// can hold such a duplicate-containing state before cleanup runs. A bare
-/// first-match `.find()` could then read a LOSING (e.g. revoked) record, and
-/// republishing it at `version + 1` would resurrect that losing record at a
-/// HIGHER rank — reactivating revoked deputy authority. `canonical` always
-/// selects the highest-`member_info_rank` record, matching what
-/// `apply_delta` / `summarize` converge on.
-fn resolve_own_member_info_base(
-    room_state: &ChatRoomStateV1,
-    member_id: MemberId,
-) -> Option<MemberInfo> {
-    room_state
-        .member_info
-        .canonical(member_id)
-        .map(|info| info.member_info.clone())
-}
+    /// A's link to I may be used twice, and I has joined with it.
+    #[test]
+    fn a_limited_link_admits_later_holders_as_fresh_members_until_used_up() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let a = SigningKey::from_bytes(&[2u8; 32]);
+        let i = SigningKey::from_bytes(&[4u8; 32]);
+        let member = |sk: &SigningKey, inviter: &SigningKey| {
+            AuthorizedMember::new(
+                Member {
+                    owner_member_id: id(&owner),
+                    invited_by: id(inviter),
+                    member_vk: sk.verifying_key(),
+                },
+                inviter,
+            )
+        };
+        let now = SystemTime::now();
+        let record = AuthorizedInvite::new(
+            Invite {
+                owner_member_id: id(&owner),
+                invitee: id(&i),
+                issued_at: now,
+                policy: InvitePolicy::Open {
+                    expires_at: None,
+                    label: None,
+                    max_uses: Some(2),
+                },
+            },
+            id(&a),
+            &a,
+        );
+        let used_by = |sk: &SigningKey| InviteUse {
+            invite: record.clone(),
+            join: AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: id(&owner),
+                    author: id(sk),
+                    content: RoomMessageBody::join_event(),
+                    time: now,
+                },
+                sk,
+            ),
+        };
+        let mut state = ChatRoomStateV1 {
+            members: MembersV1 {
+                members: vec![member(&a, &owner), member(&i, &a)],
+            },
+            invite_uses: InviteUsesV1(vec![used_by(&i)]),
+            ..Default::default()
+        };
+        let invitation = Invitation {
+            room: owner.verifying_key(),
+            invitee_signing_key: i.clone(),
+            invitee: member(&i, &a),
+            room_secrets: Vec::new(),
+        };
 
-/// Resolve a `member set-deputy` / `revoke-deputy` short id to a full `MemberId`.
-///
-/// Primary: a present member, matched via their `member_info` (same rule as
-/// `ban_member` — full-string prefix, or a case-insensitive first-8-chars match).
-/// Revoke fallback (#411 round 4 C): when `allow_deputy_fallback` is set (the
-/// revoke path), also match the caller's OWN `deputies`, so a deputy who was
-/// pruned for inactivity — their `member_info` is gone but their id still sits in
-/// the caller's signed deputies list — can still be revoked.
-fn resolve_deputy_target(
-    member_infos: &[AuthorizedMemberInfo],
-    own_deputies: &[MemberId],
-    member_id_short: &str,
-    allow_deputy_fallback: bool,
-) -> Option<MemberId> {
-    let matches = |id: &MemberId| {
-        let s = id.to_string();
-        s.starts_with(member_id_short) || s[..8.min(s.len())].eq_ignore_ascii_case(member_id_short)
-    };
-    member_infos
-        .iter()
-        .map(|info| info.member_info.member_id)
-        .find(|id| matches(id))
-        .or_else(|| {
-            if allow_deputy_fallback {
-                own_deputies.iter().copied().find(|id| matches(id))
-            } else {
-                None
-            }
-        })
+        assert!(invitation_refusal(&state, id(&i), now).is_none());
+        let joining = joining_identity(&state, invitation.clone());
+        assert_ne!(joining.invitee.member.id(), id(&i));
+        assert_eq!(joining.invitee.member.invited_by, id(&i));
+        assert!(joining.invitee.verify_signature(&i.verifying_key()).is_ok());
+
+        let second = SigningKey::from_bytes(&[5u8; 32]);
+        state.members.members.push(member(&second, &i));
+        state.invite_uses.0.push(used_by(&second));
+        assert!(invitation_refusal(&state, id(&i), now).is_some());
+    }
 }
 
 #[cfg(test)]
-mod deputy_resolve_tests {
-    use super::resolve_deputy_target;
+mod join_request_tests {
+    use super::{knock_progress, resolve_join_requester, KnockProgress};
     use ed25519_dalek::SigningKey;
-    use river_core::room_state::member::MemberId;
-    use river_core::room_state::member_info::{AuthorizedMemberInfo, MemberInfo};
-
-    fn info(sk: &SigningKey) -> AuthorizedMemberInfo {
-        let id: MemberId = sk.verifying_key().into();
-        AuthorizedMemberInfo::new_with_member_key(
-            MemberInfo::new_public(id, 0, "n".to_string()),
-            sk,
-        )
-    }
+    use river_core::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+    use river_core::room_state::join_request::{
+        AuthorizedJoinDecision, AuthorizedJoinRequest, JoinDecision, JoinOutcome, JoinRequest,
+        JoinRequestsV1,
+    };
+    use river_core::room_state::member::{AuthorizedMember, Member, MemberId, MembersV1};
+    use river_core::room_state::ChatRoomStateV1;
+    use std::time::SystemTime;
 
-    fn short(id: &MemberId) -> String {
-        let s = id.to_string();
-        s[..8.min(s.len())].to_string()
+    fn id(sk: &SigningKey) -> MemberId {
+        sk.verifying_key().into()
     }
 
-    /// #411 round 4 C: revoke can resolve a pruned deputy (no member_info) from
-    /// the caller's own deputies list; deputize cannot resolve a non-member.
+    /// Owner invited A; R has knocked.
     #[test]
-    fn revoke_resolves_pruned_deputy_from_own_deputies_list() {
-        // Distinct fixed seeds → distinct keys (the cli crate's dalek does not
-        // enable the `rand` `generate` helper; mirrors the other cli tests).
-        let present = SigningKey::from_bytes(&[7u8; 32]);
-        let pruned_deputy = SigningKey::from_bytes(&[9u8; 32]);
-        let present_id: MemberId = present.verifying_key().into();
-        let pruned_id: MemberId = pruned_deputy.verifying_key().into();
-
-        // Only the present member has a member_info entry; the pruned deputy's
-        // member_info is gone but their id still sits in own_deputies.
-        let member_infos = vec![info(&present)];
-        let own_deputies = vec![pruned_id];
+    fn approve_and_knock_follow_the_newest_decision() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let a = SigningKey::from_bytes(&[2u8; 32]);
+        let r = SigningKey::from_bytes(&[3u8; 32]);
+        let member = |sk: &SigningKey, inviter: &SigningKey| {
+            AuthorizedMember::new(
+                Member {
+                    owner_member_id: id(&owner),
+                    invited_by: id(inviter),
+                    member_vk: sk.verifying_key(),
+                },
+                inviter,
+            )
+        };
+        let request = AuthorizedJoinRequest::new(
+            JoinRequest {
+                owner_member_id: id(&owner),
+                requester: r.verifying_key(),
+                nickname: "Rosa".into(),
+                message: "friend of A".into(),
+                requested_at: SystemTime::now(),
+            },
+            &r,
+        );
+        let decision = |outcome| {
+            AuthorizedJoinDecision::new(
+                JoinDecision {
+                    owner_member_id: id(&owner),
+                    requester: r.verifying_key(),
+                    decided_at: SystemTime::now(),
+                    outcome,
+                },
+                id(&a),
+                &a,
+            )
+        };
+        let mut state = ChatRoomStateV1 {
+            configuration: AuthorizedConfigurationV1::new(Configuration::default(), &owner),
+            members: MembersV1 {
+                members: vec![member(&a, &owner)],
+            },
+            join_requests: JoinRequestsV1 {
+                requests: vec![request],
+                decisions: Vec::new(),
+            },
+            ..Default::default()
+        };
+        let r_short = id(&r).to_string()[..8].to_string();
 
-        // A present member resolves via the primary lookup in both modes.
         assert_eq!(
-            resolve_deputy_target(&member_infos, &own_deputies, &short(&present_id), false),
-            Some(present_id)
+            resolve_join_requester(&state, &r_short).unwrap(),
+            r.verifying_key()
+        );
+        assert!(resolve_join_requester(&state, "nobody").is_err());
+        assert_eq!(
+            knock_progress(&state, &r.verifying_key()),
+            KnockProgress::Waiting
         );
 
-        // The pruned deputy is NOT resolvable when deputizing (no member_info)...
+        // A denied requester's request is gone, yet they can still be approved.
+        state.join_requests = JoinRequestsV1 {
+            requests: Vec::new(),
+            decisions: vec![decision(JoinOutcome::Denied)],
+        };
         assert_eq!(
-            resolve_deputy_target(&member_infos, &own_deputies, &short(&pruned_id), false),
-            None,
-            "deputize must not resolve a non-member"
+            knock_progress(&state, &r.verifying_key()),
+            KnockProgress::Denied
         );
-        // ...but IS resolvable when revoking (fallback to the caller's deputies).
         assert_eq!(
-            resolve_deputy_target(&member_infos, &own_deputies, &short(&pruned_id), true),
-            Some(pruned_id),
-            "revoke resolves a pruned deputy from the caller's own deputies list"
+            resolve_join_requester(&state, &r_short).unwrap(),
+            r.verifying_key()
         );
 
-        // An unknown short id resolves to nothing even with the fallback.
+        let approved = member(&r, &a);
+        state
+            .join_requests
+            .decisions
+            .push(decision(JoinOutcome::Approved(Box::new(approved.clone()))));
         assert_eq!(
-            resolve_deputy_target(&member_infos, &own_deputies, "zzzzzzzz", true),
-            None
+            knock_progress(&state, &r.verifying_key()),
+            KnockProgress::Approved(Box::new(approved.clone()))
+        );
+
+        state.join_requests = JoinRequestsV1::default();
+        assert_eq!(
+            knock_progress(&state, &r.verifying_key()),
+            KnockProgress::Dropped
+        );
+
+        // Once R has joined there is nothing left to decide.
+        state.members.members.push(approved);
+        state
+            .join_requests
+            .decisions
+            .push(decision(JoinOutcome::Denied));
+        assert!(resolve_join_requester(&state, &r_short).is_err());
+    }
+}
+
+#[cfg(test)]
+mod mute_resolve_tests {
+    use super::resolve_mute_target;
+    use ed25519_dalek::SigningKey;
+    use river_core::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+    use river_core::room_state::member::{AuthorizedMember, Member, MemberId, MembersV1};
+    use river_core::room_state::mute::{AuthorizedMute, Mute, MuteAction, MutesV1};
+    use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1};
+
+    fn id(sk: &SigningKey) -> MemberId {
+        sk.verifying_key().into()
+    }
+
+    fn short(id: &MemberId) -> String {
+        id.to_string()[..8].to_string()
+    }
+
+    /// Owner invites A and C; A invites B.
+    #[test]
+    fn only_someone_above_the_member_can_mute_and_unmute_needs_a_mute() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let a = SigningKey::from_bytes(&[2u8; 32]);
+        let b = SigningKey::from_bytes(&[3u8; 32]);
+        let c = SigningKey::from_bytes(&[4u8; 32]);
+        let params = ChatRoomParametersV1 {
+            owner: owner.verifying_key(),
+        };
+        let member = |sk: &SigningKey, inviter: &SigningKey| {
+            AuthorizedMember::new(
+                Member {
+                    owner_member_id: id(&owner),
+                    invited_by: id(inviter),
+                    member_vk: sk.verifying_key(),
+                },
+                inviter,
+            )
+        };
+        let mut state = ChatRoomStateV1 {
+            configuration: AuthorizedConfigurationV1::new(Configuration::default(), &owner),
+            members: MembersV1 {
+                members: vec![member(&a, &owner), member(&b, &a), member(&c, &owner)],
+            },
+            ..Default::default()
+        };
+        let b_short = short(&id(&b));
+
+        for muter in [&a, &owner] {
+            let target = resolve_mute_target(&state, &params, id(muter), &b_short, true).unwrap();
+            assert_eq!(target, id(&b));
+        }
+        assert!(resolve_mute_target(&state, &params, id(&c), &b_short, true).is_err());
+        assert!(resolve_mute_target(&state, &params, id(&a), &b_short, false).is_err());
+
+        state.mutes = MutesV1(vec![AuthorizedMute::new(
+            Mute {
+                owner_member_id: id(&owner),
+                muted_user: id(&b),
+                muted_at: std::time::SystemTime::now(),
+                action: MuteAction::Mute { duration: None },
+            },
+            id(&a),
+            &a,
+        )]);
+        assert!(resolve_mute_target(&state, &params, id(&owner), &b_short, false).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod unban_resolve_tests {
+    use super::resolve_unban_target;
+    use ed25519_dalek::SigningKey;
+    use river_core::room_state::ban::{AuthorizedUserBan, BansV1, UserBan};
+    use river_core::room_state::member::{AuthorizedMember, Member, MemberId, MembersV1};
+    use river_core::room_state::ChatRoomStateV1;
+
+    fn id(sk: &SigningKey) -> MemberId {
+        sk.verifying_key().into()
+    }
+
+    fn short(id: &MemberId) -> String {
+        id.to_string()[..8].to_string()
+    }
+
+    /// Owner invites A and C; A invites B; A has banned B.
+    #[test]
+    fn only_the_banner_or_someone_above_them_can_unban() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let a = SigningKey::from_bytes(&[2u8; 32]);
+        let b = SigningKey::from_bytes(&[3u8; 32]);
+        let c = SigningKey::from_bytes(&[4u8; 32]);
+        let member = |sk: &SigningKey, inviter: &SigningKey| {
+            AuthorizedMember::new(
+                Member {
+                    owner_member_id: id(&owner),
+                    invited_by: id(inviter),
+                    member_vk: sk.verifying_key(),
+                },
+                inviter,
+            )
+        };
+        let ban = AuthorizedUserBan::new(
+            UserBan {
+                owner_member_id: id(&owner),
+                banned_at: std::time::SystemTime::now(),
+                banned_user: id(&b),
+                reason: None,
+                duration: None,
+            },
+            id(&a),
+            &a,
         );
+        let state = ChatRoomStateV1 {
+            members: MembersV1 {
+                members: vec![member(&a, &owner), member(&c, &owner)],
+            },
+            bans: BansV1(vec![ban.clone()]),
+            ..Default::default()
+        };
+
+        for unbanner in [&a, &owner] {
+            let (target, bans) =
+                resolve_unban_target(&state, id(unbanner), id(&owner), &short(&id(&b))).unwrap();
+            assert_eq!(target, id(&b));
+            assert_eq!(bans, vec![ban.clone()]);
+        }
+        assert!(resolve_unban_target(&state, id(&c), id(&owner), &short(&id(&b))).is_err());
+        assert!(resolve_unban_target(&state, id(&a), id(&owner), &short(&id(&c))).is_err());
     }
 }
 
@@ -6630,6 +8858,8 @@ mod migration_recovery_tests {
                 owner_member_id: owner_id,
                 banned_at: std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(500),
                 banned_user: member_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             owner_sk,
@@ -7527,6 +9757,7 @@ mod display_text_tests {
                     version: 0,
                     preferred_nickname: SealedBytes::public(b"Alice".to_vec()),
                     deputies: Vec::new(),
+                    dm_prekey: None,
                 },
                 &alice_sk,
             ));
@@ -8477,6 +10708,67 @@ mod monitor_tests {
         assert_eq!(fp_after, "", "no reactions left → empty fingerprint");
     }
 
+    /// The gateway's tracker reports only what changed after it was created,
+    /// with the monitor stream's event types and order: a new message, then a
+    /// reaction to an existing one, then an edit and a deletion.
+    #[test]
+    fn room_event_tracker_reports_changes_since_creation() {
+        let original = authored(RoomMessageBody::public("hello".to_string()));
+        let target = original.id();
+        let owner_vk = SigningKey::from_bytes(&[6u8; 32]).verifying_key();
+        let author_action = |content: RoomMessageBody, secs: u64| {
+            let sk = SigningKey::from_bytes(&[5u8; 32]);
+            AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: MemberId::from(owner_vk),
+                    author: MemberId::from(&sk.verifying_key()),
+                    content,
+                    time: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+                },
+                &sk,
+            )
+        };
+        let secrets = HashMap::new();
+        let types = |events: Vec<serde_json::Value>| -> Vec<String> {
+            events
+                .iter()
+                .map(|e| e["type"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let mut tracker =
+            RoomEventTracker::new(owner_vk, &state_with_reactions(&original, vec![]), &secrets);
+        assert!(tracker
+            .changes(&state_with_reactions(&original, vec![]), &secrets)
+            .is_empty());
+
+        let newer = author_action(RoomMessageBody::public("again".to_string()), 20);
+        let state = state_with_reactions(
+            &original,
+            vec![reaction_action(7, &target, "👍", false), newer.clone()],
+        );
+        let events = tracker.changes(&state, &secrets);
+        assert_eq!(types(events.clone()), ["message", "reaction"]);
+        assert_eq!(events[0]["content"], "again");
+        assert_eq!(events[1]["reactions"]["👍"], 1);
+        assert_eq!(
+            events[0]["room"],
+            bs58::encode(owner_vk.as_bytes()).into_string()
+        );
+        assert!(tracker.changes(&state, &secrets).is_empty());
+
+        let state = state_with_reactions(
+            &original,
+            vec![
+                reaction_action(7, &target, "👍", false),
+                newer.clone(),
+                author_action(RoomMessageBody::edit(target.clone(), "hi".to_string()), 30),
+                author_action(RoomMessageBody::delete(newer.id()), 31),
+            ],
+        );
+        assert_eq!(types(tracker.changes(&state, &secrets)), ["edit", "delete"]);
+    }
+
     /// `emit_new_and_edited` SEEDS `seen_reactions` for every message it surfaces
     /// (new or edited). This is the wiring that makes the suppression rule work:
     /// a brand-new message becomes eligible for later reaction events the moment
@@ -8551,6 +10843,7 @@ mod mention_cli_tests {
                 version: i as u32,
                 preferred_nickname: nickname.clone(),
                 deputies: Vec::new(),
+                dm_prekey: None,
             };
             state
                 .member_info
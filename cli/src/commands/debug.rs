@@ -16,6 +16,7 @@ pub enum DebugCommands {
     /// Perform a raw contract GET operation
     ContractGet {
         /// Room owner key (base58 encoded)
+        #[arg(value_parser = crate::config::room_arg)]
         room_owner_key: String,
     },
     /// Test WebSocket connection
@@ -23,21 +24,25 @@ pub enum DebugCommands {
     /// Show contract key for a room
     ContractKey {
         /// Room owner key (base58 encoded)
+        #[arg(value_parser = crate::config::room_arg)]
         room_owner_key: String,
     },
     /// Show room state summary including bans, members, and configuration
     RoomState {
         /// Room owner key (base58 encoded)
+        #[arg(value_parser = crate::config::room_arg)]
         room_owner_key: String,
     },
     /// Show current ban list for a room
     Bans {
         /// Room owner key (base58 encoded)
+        #[arg(value_parser = crate::config::room_arg)]
         room_owner_key: String,
     },
     /// Show room configuration
     Config {
         /// Room owner key (base58 encoded)
+        #[arg(value_parser = crate::config::room_arg)]
         room_owner_key: String,
     },
 }
@@ -138,6 +143,15 @@ struct BanInfo {
     banned_user_id: String,
     banned_by_id: String,
     banned_at_secs: u64,
+    /// The banner's stated reason, unsealed when we hold the room secret.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    /// Set only for a temporary ban. It lapses once someone able to lift it
+    /// posts at or after `expires_at_secs` (`BansV1::ban_has_expired`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at_secs: Option<u64>,
     /// See [`BanEnforcement`].
     ///
     /// Do NOT reduce this to a bare `is_ban_authorized`. That predicate returns
@@ -219,8 +233,13 @@ fn classify_ban(
 
 /// Classify every ban in `room_state`, projecting each to a `BanInfo`. Kept as a
 /// pure helper (no I/O) so the enforcement wiring is unit testable without a live
-/// node — see the tests at the bottom of this file.
-fn collect_ban_infos(room_state: &ChatRoomStateV1, owner_vk: &VerifyingKey) -> Vec<BanInfo> {
+/// node — see the tests at the bottom of this file. `secrets` unseals private-room
+/// ban reasons; pass an empty map for a public room.
+fn collect_ban_infos(
+    room_state: &ChatRoomStateV1,
+    owner_vk: &VerifyingKey,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> Vec<BanInfo> {
     let owner_id = MemberId::from(owner_vk);
     let members_by_id = room_state.members.members_by_member_id();
     room_state
@@ -228,16 +247,22 @@ fn collect_ban_infos(room_state: &ChatRoomStateV1, owner_vk: &VerifyingKey) -> V
         .0
         .iter()
         .map(|ban| {
-            let banned_at_secs = ban
-                .ban
-                .banned_at
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
+            let unix_secs = |time: std::time::SystemTime| {
+                time.duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0)
+            };
             BanInfo {
                 banned_user_id: ban.ban.banned_user.to_string(),
                 banned_by_id: ban.banned_by.to_string(),
-                banned_at_secs,
+                banned_at_secs: unix_secs(ban.ban.banned_at),
+                reason: ban
+                    .ban
+                    .reason
+                    .as_ref()
+                    .map(|reason| crate::api::unseal_nickname_display(reason, secrets)),
+                duration_secs: ban.ban.duration.map(|d| d.as_secs()),
+                expires_at_secs: ban.ban.expires_at().map(unix_secs),
                 enforcement: classify_ban(
                     ban,
                     &members_by_id,
@@ -276,13 +301,21 @@ fn ban_list_lines(bans: &[BanInfo]) -> Vec<String> {
     }
 
     for ban in bans {
+        let until = ban
+            .expires_at_secs
+            .map(|secs| format!(" until {}", secs))
+            .unwrap_or_default();
         lines.push(format!(
-            "  {} banned by {} at {}{}",
+            "  {} banned by {} at {}{}{}",
             ban.banned_user_id,
             ban.banned_by_id,
             ban.banned_at_secs,
+            until,
             ban.enforcement.marker()
         ));
+        if let Some(reason) = &ban.reason {
+            lines.push(format!("    reason: {}", reason));
+        }
     }
 
     // Each note is keyed to a state that is actually present, so the output does
@@ -627,9 +660,10 @@ pub async fn execute(command: DebugCommands, api: ApiClient, format: OutputForma
         }
         DebugCommands::Bans { room_owner_key } => {
             let owner_vk = parse_owner_key(&room_owner_key)?;
-            let room_state = api.get_room(&owner_vk, false).await?;
+            let mut room_state = api.get_room(&owner_vk, false).await?;
+            let secrets = api.room_display_secrets(&owner_vk, &mut room_state);
 
-            let bans = collect_ban_infos(&room_state, &owner_vk);
+            let bans = collect_ban_infos(&room_state, &owner_vk, &secrets);
 
             match format {
                 OutputFormat::Human => {
@@ -785,7 +819,7 @@ mod tests {
 
     /// The single ban's verdict in a state built by these fixtures.
     fn verdict(state: &ChatRoomStateV1, owner: &SigningKey) -> BanEnforcement {
-        let infos = collect_ban_infos(state, &owner.verifying_key());
+        let infos = collect_ban_infos(state, &owner.verifying_key(), &HashMap::new());
         assert_eq!(infos.len(), 1, "fixture must carry exactly one ban");
         infos[0].enforcement
     }
@@ -847,6 +881,8 @@ mod tests {
             owner_member_id: id(owner),
             banned_at: std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
             banned_user: id(target),
+            reason: None,
+            duration: None,
         };
         state
             .bans
@@ -865,13 +901,53 @@ mod tests {
         push_member(&mut state, &owner, &owner, &alice);
         push_ban(&mut state, &owner, &owner, &alice);
 
-        let bans = collect_ban_infos(&state, &owner.verifying_key());
+        let bans = collect_ban_infos(&state, &owner.verifying_key(), &HashMap::new());
         assert_eq!(bans.len(), 1);
         assert_eq!(bans[0].banned_user_id, id(&alice).to_string());
         assert_eq!(bans[0].banned_by_id, id(&owner).to_string());
         assert_eq!(bans[0].enforcement, BanEnforcement::Enforcing);
     }
 
+    #[test]
+    fn temporary_ban_reports_its_reason_and_expiry() {
+        let owner = key(1);
+        let alice = key(2);
+
+        let mut state = ChatRoomStateV1::default();
+        push_member(&mut state, &owner, &owner, &alice);
+        let ban = UserBan {
+            owner_member_id: id(&owner),
+            banned_at: std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+            banned_user: id(&alice),
+            reason: Some(river_core::room_state::privacy::SealedBytes::public(
+                b"spamming links".to_vec(),
+            )),
+            duration: Some(std::time::Duration::from_secs(3600)),
+        };
+        state
+            .bans
+            .0
+            .push(AuthorizedUserBan::new(ban, id(&owner), &owner));
+
+        let bans = collect_ban_infos(&state, &owner.verifying_key(), &HashMap::new());
+        assert_eq!(bans[0].reason.as_deref(), Some("spamming links"));
+        assert_eq!(bans[0].duration_secs, Some(3600));
+        assert_eq!(bans[0].expires_at_secs, Some(1_700_003_600));
+
+        let rendered = ban_list_lines(&bans).join("\n");
+        assert!(
+            rendered.contains("at 1700000000 until 1700003600"),
+            "{rendered}"
+        );
+        assert!(rendered.contains("reason: spamming links"), "{rendered}");
+
+        let json = serde_json::to_value(&bans[0]).unwrap();
+        assert_eq!(json["duration_secs"], 3600);
+        // A permanent ban without a reason keeps the old JSON shape.
+        let plain = serde_json::to_value(info("X", BanEnforcement::Enforcing)).unwrap();
+        assert!(plain.get("reason").is_none() && plain.get("expires_at_secs").is_none());
+    }
+
     #[test]
     fn deputy_ban_is_enforcing_while_the_grant_stands() {
         // The other half of the revocation pair below: the SAME ban, differing
@@ -1098,6 +1174,8 @@ mod tests {
             owner_member_id: id(&owner),
             banned_at: std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
             banned_user: id(&alice),
+            reason: None,
+            duration: None,
         };
         let forged = sign_struct(&ban, &mallory);
         state
@@ -1247,7 +1325,7 @@ mod tests {
 
         push_info(&mut state, &owner, 1, vec![]); // revoke bob
 
-        let bans = collect_ban_infos(&state, &owner.verifying_key());
+        let bans = collect_ban_infos(&state, &owner.verifying_key(), &HashMap::new());
         assert_eq!(bans.len(), 3);
 
         let by_target: Vec<(String, BanEnforcement)> = bans
@@ -1280,7 +1358,7 @@ mod tests {
         push_member(&mut state, &owner, &owner, &alice);
         push_ban(&mut state, &owner, &owner, &alice);
 
-        let bans = collect_ban_infos(&state, &owner.verifying_key());
+        let bans = collect_ban_infos(&state, &owner.verifying_key(), &HashMap::new());
         let json = serde_json::to_value(&bans).unwrap();
         let entry = &json[0];
 
@@ -1310,6 +1388,9 @@ mod tests {
             banned_user_id: user.to_string(),
             banned_by_id: "BANNER".to_string(),
             banned_at_secs: 100,
+            reason: None,
+            duration_secs: None,
+            expires_at_secs: None,
             enforcement: state,
         }
     }
@@ -1,4 +1,4 @@
-//! In-room direct messages (#243 Phase 3).
+//! Direct messages between room members (#243 Phase 3).
 //!
 //! `dm send` / `dm list` / `dm purge` produce the same wire bytes as the
 //! River UI does for the same operation: encryption goes through
@@ -7,6 +7,26 @@
 //! WASM doesn't care which client posted the state — verification rests on
 //! the sender + recipient signatures, both produced from helpers that live
 //! in the `river-core` crate.
+//!
+//! DMs are delivered to the recipient's inbox contract
+//! (`river_core::inbox`), not the room state. DMs to the local member that
+//! older clients left in the room are moved into their inbox whenever it is
+//! read ([`sync_inbox`]).
+//!
+//! `dm group-send` writes to several members at once (`river_core::group_dm`):
+//! one envelope, delivered to every participant's inbox including the
+//! sender's own, so `dm list` shows group threads without a local cache.
+//!
+//! `dm prekey` opts the local member into forward-secret DMs
+//! (`river_core::dm_ratchet`): it publishes a prekey in their member info, and
+//! DMs to a member who publishes one go through a ratchet session instead of
+//! being sealed to their member key. Sessions and the plaintext of opened
+//! ratcheted DMs live in `dm_ratchet.json`; `dm purge` deletes the plaintext,
+//! after which the DM cannot be read again.
+//!
+//! `dm list` tells each peer how far you have got in your thread with them
+//! (`river_core::dm_receipts`): a receipt envelope PUT into their inbox, and
+//! theirs, from your own inbox, mark your sent DMs delivered or read.
 
 use crate::api::{ApiClient, Invitation};
 use crate::commands::invite::{print_invitation_accepted, resolve_nickname};
@@ -16,12 +36,19 @@ use chrono::{DateTime, Local, Utc};
 use clap::Subcommand;
 use ed25519_dalek::{SigningKey, VerifyingKey};
 use river_core::chat_delegate::OutboundDmEntry;
+use river_core::dm_ratchet::RatchetStore;
+use river_core::dm_receipts::{advance_dm_receipts, AuthorizedDmReceipts};
+use river_core::group_dm::{
+    compose_group_direct_message, open_group_direct_message, MAX_GROUP_DM_PARTICIPANTS,
+};
+use river_core::inbox::{
+    InboxGroupMessageV1, InboxMessageV1, InboxParametersV1, InboxReceiptsV1, InboxStateV1,
+};
 use river_core::room_state::direct_messages::{
-    advance_recipient_purges, compose_direct_message, open_direct_message, PurgeToken,
-    MAX_DM_MESSAGES_PER_PAIR,
+    advance_recipient_purges, AuthorizedDirectMessage, PurgeToken, MAX_DM_MESSAGES_PER_PAIR,
 };
 use river_core::room_state::dm_body::{decode_body, encode_body, DirectMessageBody, InvitePayload};
-use river_core::room_state::member::MemberId;
+use river_core::room_state::member::{AuthorizedMember, MemberId};
 use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1, ChatRoomStateV1Delta};
 use serde_json::json;
 use std::collections::{HashMap, HashSet};
@@ -35,18 +62,39 @@ pub enum DmCommands {
     /// shown by `riverctl member list`) or a full MemberId.
     Send {
         /// Room ID (base58-encoded room owner verifying key)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Recipient member ID (short prefix accepted)
         recipient: String,
         /// Message body (plaintext, encrypted on send)
         message: String,
     },
+    /// Send one direct message to several co-members of a room at once.
+    ///
+    /// Everyone named with `--to` (2 to 7 members) and you form the group;
+    /// each of them can read the message and reply to the whole group, and
+    /// each purges it from their own inbox independently.
+    GroupSend {
+        /// Room ID (base58-encoded room owner verifying key)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// A recipient member ID (short prefix accepted); repeat for each
+        #[arg(long = "to", required = true)]
+        recipients: Vec<String>,
+        /// Message body (plaintext, encrypted on send)
+        message: String,
+    },
     /// List direct messages addressed to or sent by your local member in a
-    /// room. Decrypted on display.
+    /// room, group conversations included. Decrypted on display.
+    ///
+    /// Your sent DMs show whether the peer has received or read them, and
+    /// each peer you have DMs from is told how far you have got.
     List {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
-        /// Show only DMs exchanged with this counterparty (short prefix accepted)
+        /// Show only DMs exchanged with this counterparty, and group DMs
+        /// they take part in (short prefix accepted)
         #[arg(long)]
         with: Option<String>,
         /// Maximum messages to show per counterparty
@@ -75,20 +123,21 @@ pub enum DmCommands {
         /// Carrier room ID (base58 room owner key) — the room whose DM thread
         /// the invitation is delivered in. You and the recipient must both be
         /// members.
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Recipient member ID (short prefix accepted).
         recipient: String,
         /// Target room ID (base58 room owner key) — the room you are inviting
         /// the recipient to join. You must be a member of it, and it must be
         /// different from the carrier room.
-        #[arg(long)]
+        #[arg(long, value_parser = crate::config::room_arg)]
         room: String,
         /// Optional personal note shown above the Accept button.
         #[arg(short = 'm', long)]
         message: Option<String>,
     },
     /// Purge a direct message addressed to you. Builds a recipient purge
-    /// envelope listing the message's token; once accepted by the room
+    /// envelope listing the message's token; once accepted by your inbox
     /// contract, any peer holding the message drops it on merge.
     ///
     /// The token to pass is printed under each inbound DM by `dm list`
@@ -98,12 +147,29 @@ pub enum DmCommands {
     /// filter you used to find the message.
     Purge {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// 32-character hex PurgeToken (16 bytes) — copy from the
         /// `purge token: ...` line shown beneath each inbound DM in
         /// `dm list` output.
         token: String,
     },
+    /// Opt in to forward-secret DMs in a room, or rotate your prekey.
+    ///
+    /// Publishes a fresh DM prekey in your member info. Members who send you
+    /// a DM after that open a ratchet session with it, so each message gets
+    /// its own key, deleted once used; `dm purge` then makes the message
+    /// unrecoverable. Your previous prekey stays usable for handshakes
+    /// already in flight. `--remove` stops publishing one: new DMs are sealed
+    /// to your member key again, while existing sessions carry on.
+    Prekey {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Stop publishing a prekey and delete the prekey secrets
+        #[arg(long)]
+        remove: bool,
+    },
     /// Accept a room invitation that arrived as a direct message.
     ///
     /// The River UI can "share an invite via DM" (#252): the invitation is
@@ -120,6 +186,7 @@ pub enum DmCommands {
     Accept {
         /// Carrier room ID (base58 room owner key) — the room whose DM
         /// thread contains the invitation.
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Only consider invite DMs sent by this member (short MemberId
         /// prefix accepted). Narrows by *sender*; if a single sender invited
@@ -130,7 +197,7 @@ pub enum DmCommands {
         /// the target room's owner key — e.g. the 8-char prefix shown by
         /// `dm list`). Use this to pick one when invitations to several rooms
         /// are present, including several from the same sender.
-        #[arg(long)]
+        #[arg(long, value_parser = crate::config::room_arg)]
         room: Option<String>,
         /// Your nickname in the room you are joining.
         #[arg(short = 'N', long)]
@@ -144,7 +211,12 @@ pub async fn execute(command: DmCommands, api: ApiClient, format: OutputFormat)
             room_id,
             recipient,
             message,
-        } => execute_send(api, format, &room_id, &recipient, &message).await,
+        } => execute_send(&api, format, &room_id, &recipient, &message).await,
+        DmCommands::GroupSend {
+            room_id,
+            recipients,
+            message,
+        } => execute_group_send(&api, format, &room_id, &recipients, &message).await,
         DmCommands::Invite {
             room_id,
             recipient,
@@ -158,6 +230,9 @@ pub async fn execute(command: DmCommands, api: ApiClient, format: OutputFormat)
             since_minutes,
         } => execute_list(api, format, &room_id, with.as_deref(), limit, since_minutes).await,
         DmCommands::Purge { room_id, token } => execute_purge(api, format, &room_id, &token).await,
+        DmCommands::Prekey { room_id, remove } => {
+            execute_prekey(&api, format, &room_id, remove).await
+        }
         DmCommands::Accept {
             room_id,
             from,
@@ -177,14 +252,28 @@ pub async fn execute(command: DmCommands, api: ApiClient, format: OutputFormat)
     }
 }
 
-async fn execute_send(
-    api: ApiClient,
+/// `dm send`; also how `moderate` delivers reports to a moderator.
+pub(crate) async fn execute_send(
+    api: &ApiClient,
     format: OutputFormat,
     room_id: &str,
     recipient: &str,
     message: &str,
 ) -> Result<()> {
     let room_owner_key = parse_room_id(room_id)?;
+    let sent = send_text_dm(api, &room_owner_key, recipient, message).await?;
+    report_dm_sent(format, DmKind::Text, &sent)
+}
+
+/// Send a text DM without printing anything; `execute_send` and the TUI's
+/// `/dm` share it.
+pub(crate) async fn send_text_dm(
+    api: &ApiClient,
+    room_owner_key: &VerifyingKey,
+    recipient: &str,
+    message: &str,
+) -> Result<DmSent> {
+    let room_owner_key = *room_owner_key;
 
     // Local signing key + cached state for resolving the recipient.
     let (signing_key, _, _) = api
@@ -204,51 +293,84 @@ async fn execute_send(
     // bytes, NOT `encode_body(&Text{..})`. Only NEW variants (`Invite`) opt
     // into the magic-byte + CBOR shape — see `execute_invite`.
     deliver_dm(
-        &api,
-        format,
+        api,
         room_owner_key,
         &signing_key,
         &room_state,
         recipient_vk,
         message.as_bytes(),
         message.to_string(),
-        DmKind::Text,
     )
     .await
 }
 
-/// What kind of DM `deliver_dm` is delivering — only affects the
-/// success-message wording, not the wire bytes (the caller has already
-/// encoded those into `body_bytes`).
+/// What kind of DM was delivered — only affects the success-message wording,
+/// not the wire bytes (the caller has already encoded those into
+/// `body_bytes`).
 #[derive(Clone, Copy)]
 enum DmKind {
     Text,
     Invite,
 }
 
+/// A delivered DM: who it went to and the token that purges it.
+pub(crate) struct DmSent {
+    pub(crate) recipient: MemberId,
+    pub(crate) token: PurgeToken,
+}
+
+fn report_dm_sent(format: OutputFormat, kind: DmKind, sent: &DmSent) -> Result<()> {
+    let token_hex = hex_token(&sent.token);
+    let noun = match kind {
+        DmKind::Text => "DM",
+        DmKind::Invite => "Invitation DM",
+    };
+    match format {
+        OutputFormat::Human => {
+            println!(
+                "{} sent to {} (purge token: {})",
+                noun,
+                short_member_id(&sent.recipient),
+                token_hex
+            );
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&dm_sent_json(sent))?);
+        }
+    }
+    Ok(())
+}
+
+/// The `dm send --format json` report.
+pub(crate) fn dm_sent_json(sent: &DmSent) -> serde_json::Value {
+    json!({
+        "status": "success",
+        "recipient": sent.recipient.to_string(),
+        "purge_token": hex_token(&sent.token),
+    })
+}
+
 /// Shared delivery core for `dm send` and `dm invite`.
 ///
 /// Given an ALREADY-ENCODED DM `body_bytes` (raw UTF-8 for a legacy `Text`
 /// DM, magic-byte+CBOR for a structured `Invite`), this runs the identical
-/// membership / per-pair-cap pre-flight, composes and signs the DM, publishes
-/// the delta (bundling a member-rejoin when the sender was pruned), verifies
-/// the DM actually lands in a local pre-flight `apply_delta`, caches
-/// `cache_label` for the sender's own `dm list` bubble, and prints the result.
+/// membership pre-flight, composes and signs the DM, wraps it with the
+/// sender's invite chain, verifies it exactly as the inbox contract will,
+/// PUTs it into the recipient's inbox (`river_core::inbox`), and caches
+/// `cache_label` for the sender's own `dm list` bubble. Callers report the
+/// result.
 ///
-/// Keeping one body means the anti-silent-drop guards (#269) and the rejoin
-/// bundling (#256 / Ivvor Bug #1) are byte-identical for text and invite DMs.
-#[allow(clippy::too_many_arguments)]
+/// Keeping one body means the anti-silent-drop guards (#269) are
+/// byte-identical for text and invite DMs.
 async fn deliver_dm(
     api: &ApiClient,
-    format: OutputFormat,
     room_owner_key: VerifyingKey,
     signing_key: &SigningKey,
     room_state: &ChatRoomStateV1,
     recipient_vk: VerifyingKey,
     body_bytes: &[u8],
     cache_label: String,
-    kind: DmKind,
-) -> Result<()> {
+) -> Result<DmSent> {
     let recipient_id = MemberId::from(&recipient_vk);
     let self_id = MemberId::from(&signing_key.verifying_key());
 
@@ -256,126 +378,82 @@ async fn deliver_dm(
         return Err(anyhow!("Cannot send a DM to yourself."));
     }
 
-    // The recipient must already be in the room — the sender does not hold
-    // the recipient's owner-signed `AuthorizedMember`, so a pruned recipient
-    // cannot be bundled into the DM delta (that side is follow-up
-    // territory; see issue #110 for the related "pruned member" gap).
-    // Sender membership is handled by the rejoin bundle below — Bug #1.
+    // The recipient must currently be in the room: their inbox accepts any
+    // member's DMs, but one addressed to a pruned or departed member would
+    // sit unread.
     let owner_id = MemberId::from(&room_owner_key);
-    let is_recipient_member = recipient_id == owner_id
-        || room_state
-            .members
-            .members
-            .iter()
-            .any(|m| m.member.id() == recipient_id);
-    if !is_recipient_member {
+    if !room_has_member(room_state, owner_id, recipient_id) {
         return Err(anyhow!("Recipient is not currently a member of the room."));
     }
 
-    // Bug #1 (Ivvor, Matrix 2026-05-16): an invited-but-inactive sender
-    // can be pruned from `members.members` by `post_apply_cleanup`, after
-    // which the contract's `DirectMessagesV1::apply_delta` silent-drops
-    // any DM whose sender isn't currently in members. Bundle the same
-    // rejoin pieces the regular `send_message_with_key` path produces so
-    // the DM lands atomically with the member-rejoin. `MembersV1`
-    // precedes `DirectMessagesV1` in the macro's field order so the
-    // sender is back in members by the time the DM sub-state apply runs.
-    // Contract-level pin: `pruned_sender_can_dm_when_bundling_rejoin_delta`
-    // in `common/tests/direct_messages_test.rs`.
-    let (rejoin_members, rejoin_member_info) =
-        api.build_rejoin_delta(room_state, &room_owner_key, signing_key);
-
-    // Codex P2 (PR #269 review): if the sender is pruned AND
-    // `build_rejoin_delta` returned no credentials (e.g. older stored
-    // rooms missing `self_authorized_member`), the contract will
-    // silent-drop the DM but the CLI's local pre-flight `apply_delta`
-    // returns Ok — meaning we'd `send_state_delta` and print success
-    // even though no DM lands. Surface the failure here with a clear
-    // diagnostic instead.
-    let is_self_member = self_id == owner_id
-        || room_state
-            .members
-            .members
-            .iter()
-            .any(|m| m.member.id() == self_id);
-    if !is_self_member && rejoin_members.is_none() {
-        return Err(anyhow!(
-            "Your member entry is not in the room and no stored rejoin credentials \
-             are available. Re-accept your invitation with `riverctl invite accept` \
-             before sending a DM."
-        ));
-    }
+    // The inbox contract cannot see the room, so the DM carries the sender's
+    // invite chain as proof of membership. A pruned sender (Bug #1, Ivvor)
+    // no longer needs a rejoin bundled in: the chain stored at join time
+    // proves membership just as well.
+    let sender_chain = own_sender_chain(api, &room_owner_key, signing_key, room_state)?;
 
     // NO per-pair cap guard here — deliberately.
     //
     // This used to hard-error at `MAX_DM_MESSAGES_PER_PAIR`, because the
     // contract's per-pair cap was first-come-wins: at the cap it silently
     // DROPPED the arrival, so the CLI would otherwise have printed "DM sent"
-    // with nothing delivered. That is no longer true. The cap is now newest-N
-    // (`trim_pairs_to_cap`): a newer DM is admitted and the pair's OLDEST is
-    // evicted, so the send genuinely succeeds.
+    // with nothing delivered. That is no longer true. The cap is newest-N, in
+    // the room (`trim_pairs_to_cap`) and per sender in an inbox alike: a
+    // newer DM is admitted and the pair's OLDEST is evicted, so the send
+    // genuinely succeeds.
     //
     // Re-adding a client-side block here would make the contract fix invisible
     // — the user still could not send, now for a purely client-side reason.
     // Pinned by `dm_send_has_no_client_side_pair_cap_guard`.
 
+    // Through the ratchet session with the recipient if there is one or they
+    // publish a prekey, else sealed to their member key. The session is saved
+    // as it advances, even if the PUT below fails: reusing its message key
+    // for the next DM would be worse than a gap the recipient skips over.
+    let recipient_prekey = room_state
+        .member_info
+        .canonical(recipient_id)
+        .and_then(|info| info.member_info.dm_prekey);
     let now = unix_now()?;
-    let auth = compose_direct_message(
-        signing_key,
-        &recipient_vk,
-        &room_owner_key,
-        now,
-        now,
-        body_bytes,
-    )
-    .map_err(|e| anyhow!("Failed to compose DM: {}", e))?;
-
-    let delta = ChatRoomStateV1Delta {
-        members: rejoin_members,
-        member_info: rejoin_member_info,
-        direct_messages: Some(
-            river_core::room_state::direct_messages::DirectMessagesDelta {
-                new_messages: vec![auth.clone()],
-                advanced_purges: vec![],
-            },
-        ),
-        ..Default::default()
-    };
+    let auth = api.storage().mutate_dm_ratchet(&room_owner_key, |store| {
+        store
+            .compose_direct_message(
+                signing_key,
+                &recipient_vk,
+                recipient_prekey.as_ref(),
+                &room_owner_key,
+                now,
+                now,
+                body_bytes,
+            )
+            .map_err(|e| anyhow!("Failed to compose DM: {}", e))
+    })?;
 
-    // Local pre-flight: walk the same apply_delta the contract will run.
-    let params = ChatRoomParametersV1 {
-        owner: room_owner_key,
+    // Local pre-flight: the same verification the inbox contract runs, so a
+    // DM it would reject (e.g. a broken stored invite chain) fails here with
+    // a reason instead of as an opaque PUT error.
+    let inbox = InboxParametersV1 {
+        room_owner: room_owner_key,
+        recipient: recipient_vk,
     };
-    let mut local = room_state.clone();
-    {
-        use freenet_scaffold::ComposableState;
-        local
-            .apply_delta(room_state, &params, &Some(delta.clone()))
-            .map_err(|e| anyhow!("Local pre-flight apply_delta failed: {:?}", e))?;
-    }
-
-    // Codex P2 defence-in-depth (PR #269 review): `DirectMessagesV1::apply_delta`
-    // silent-drops DMs whose sender isn't a current member, so the pre-flight
-    // above can return Ok even though our DM was dropped. Verify the message
-    // we tried to send is actually present in the post-merge state before
-    // we report success to the user.
-    let landed = local
-        .direct_messages
-        .messages
-        .iter()
-        .any(|m| m.sender_signature == auth.sender_signature);
-    if !landed {
-        return Err(anyhow!(
-            "Local pre-flight: the DM was silently dropped by the contract \
-             (likely because the sender or recipient is not a current member). \
-             Refusing to claim a successful send."
-        ));
-    }
+    let message = InboxMessageV1 {
+        sender_chain,
+        message: auth.clone(),
+    };
+    message
+        .verify(&inbox)
+        .map_err(|e| anyhow!("Local pre-flight: the inbox would reject this DM: {}", e))?;
 
-    api.send_state_delta(&room_owner_key, &delta).await?;
+    api.put_inbox(
+        &inbox,
+        InboxStateV1 {
+            messages: vec![message],
+            ..Default::default()
+        },
+    )
+    .await?;
 
     let token = auth.purge_token();
-    let token_hex = hex_token(&token);
 
     // Persist a label in the local outbound-DM cache so a future `dm list`
     // renders the sender's own bubble as text instead of
@@ -396,30 +474,122 @@ async fn deliver_dm(
         tracing::warn!("Failed to persist outbound DM plaintext locally: {}", e);
     }
 
-    let noun = match kind {
-        DmKind::Text => "DM",
-        DmKind::Invite => "Invitation DM",
-    };
-    match format {
-        OutputFormat::Human => {
-            println!(
-                "{} sent to {} (purge token: {})",
-                noun,
-                short_member_id(&recipient_id),
-                token_hex
-            );
+    Ok(DmSent {
+        recipient: recipient_id,
+        token,
+    })
+}
+
+/// `dm group-send`: compose one group DM and PUT it into the inbox of every
+/// participant, the sender's included. Runs the same membership pre-flight
+/// and inbox verification as [`deliver_dm`], once per participant, before
+/// sending anything.
+async fn execute_group_send(
+    api: &ApiClient,
+    format: OutputFormat,
+    room_id: &str,
+    recipients: &[String],
+    message: &str,
+) -> Result<()> {
+    let room_owner_key = parse_room_id(room_id)?;
+    let (signing_key, _, _) = api
+        .storage()
+        .get_room(&room_owner_key)?
+        .ok_or_else(|| anyhow!("Room not found. You must be a member of the room to send DMs."))?;
+    let self_vk = signing_key.verifying_key();
+    let room_state = api.get_room(&room_owner_key, false).await?;
+
+    let owner_id = MemberId::from(&room_owner_key);
+    let mut others: Vec<VerifyingKey> = Vec::new();
+    for needle in recipients {
+        let vk = resolve_recipient_vk(&room_state, &room_owner_key, needle)?;
+        if vk == self_vk {
+            return Err(anyhow!(
+                "You are in the group already; leave yourself out of --to."
+            ));
         }
-        OutputFormat::Json => {
-            println!(
-                "{}",
-                serde_json::to_string_pretty(&json!({
-                    "status": "success",
-                    "recipient": recipient_id.to_string(),
-                    "purge_token": token_hex,
-                }))?
-            );
+        if !room_has_member(&room_state, owner_id, MemberId::from(&vk)) {
+            return Err(anyhow!(
+                "Recipient {} is not currently a member of the room.",
+                needle
+            ));
+        }
+        if !others.contains(&vk) {
+            others.push(vk);
         }
     }
+    if others.len() < 2 {
+        return Err(anyhow!(
+            "A group DM needs at least two other members; use `dm send` for one."
+        ));
+    }
+    if others.len() + 1 > MAX_GROUP_DM_PARTICIPANTS {
+        return Err(anyhow!(
+            "A group DM has at most {} members including you.",
+            MAX_GROUP_DM_PARTICIPANTS
+        ));
+    }
+
+    let sender_chain = own_sender_chain(api, &room_owner_key, &signing_key, &room_state)?;
+    let now = unix_now()?;
+    let auth = compose_group_direct_message(
+        &signing_key,
+        &others,
+        &room_owner_key,
+        now,
+        now,
+        message.as_bytes(),
+    )
+    .map_err(|e| anyhow!("Failed to compose group DM: {}", e))?;
+    let group = InboxGroupMessageV1 {
+        sender_chain,
+        message: auth.clone(),
+    };
+
+    let inboxes: Vec<InboxParametersV1> = std::iter::once(self_vk)
+        .chain(others.iter().copied())
+        .map(|recipient| InboxParametersV1 {
+            room_owner: room_owner_key,
+            recipient,
+        })
+        .collect();
+    for inbox in &inboxes {
+        group
+            .verify(inbox)
+            .map_err(|e| anyhow!("Local pre-flight: an inbox would reject this DM: {}", e))?;
+    }
+    for inbox in &inboxes {
+        api.put_inbox(
+            inbox,
+            InboxStateV1 {
+                group_messages: vec![group.clone()],
+                ..Default::default()
+            },
+        )
+        .await?;
+    }
+
+    let token = auth.purge_token();
+    let others: Vec<MemberId> = auth.message.others(MemberId::from(&self_vk)).collect();
+    match format {
+        OutputFormat::Human => println!(
+            "Group DM sent to {} (purge token: {})",
+            others
+                .iter()
+                .map(short_member_id)
+                .collect::<Vec<_>>()
+                .join(", "),
+            hex_token(&token)
+        ),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "status": "success",
+                "participants": auth.message.participants.iter().map(|p| p.to_string()).collect::<Vec<_>>(),
+                "purge_token": hex_token(&token),
+            }))?
+        ),
+    }
     Ok(())
 }
 
@@ -541,18 +711,17 @@ async fn execute_invite(
     // bubble isn't a bare `<sent: ciphertext only>`.
     let cache_label = format_dm_body_for_cli(&body, &HashMap::new());
 
-    deliver_dm(
+    let sent = deliver_dm(
         &api,
-        format,
         carrier_room_key,
         &signing_key,
         &room_state,
         recipient_vk,
         &body_bytes,
         cache_label,
-        DmKind::Invite,
     )
-    .await
+    .await?;
+    report_dm_sent(format, DmKind::Invite, &sent)
 }
 
 async fn execute_list(
@@ -568,8 +737,6 @@ async fn execute_list(
         .storage()
         .get_room(&room_owner_key)?
         .ok_or_else(|| anyhow!("Room not found. You must be a member of the room to read DMs."))?;
-    let self_vk = signing_key.verifying_key();
-    let self_id = MemberId::from(&self_vk);
 
     let mut room_state = api.get_room(&room_owner_key, false).await?;
 
@@ -591,108 +758,34 @@ async fn execute_list(
 
     // Build a nickname lookup so output is human-readable (decrypted for a
     // private room).
-    let nicknames: HashMap<MemberId, String> = room_state
-        .member_info
-        .member_info
-        .iter()
-        .map(|info| {
-            (
-                info.member_info.member_id,
-                crate::api::unseal_nickname_display(&info.member_info.preferred_nickname, &secrets),
-            )
-        })
-        .collect();
-
-    // Load the local outbound-DM plaintext cache so we can render the
-    // sender's own bubbles as plaintext instead of `<sent: ciphertext
-    // only>`. See issue freenet/river#256. Missing entries (e.g. DMs
-    // sent before this cache shipped, or from another device) still
-    // fall back to the legacy placeholder.
-    let outbound_lookup: HashMap<(MemberId, PurgeToken), String> = api
-        .storage()
-        .load_outbound_dms()
-        .map(|store| {
-            store
-                .entries
-                .into_iter()
-                .filter(|e| e.room_owner_vk == room_owner_key.to_bytes())
-                .map(|e| ((e.recipient, e.purge_token), e.plaintext))
-                .collect()
-        })
-        .unwrap_or_else(|e| {
-            tracing::warn!("Failed to load outbound DM cache: {}", e);
-            HashMap::new()
-        });
-
-    // Walk every DM we are party to; decrypt on display.
-    let mut decrypted: Vec<DecryptedDm> = Vec::new();
-    for msg in &room_state.direct_messages.messages {
-        let is_self_sender = msg.message.sender == self_id;
-        let is_self_recipient = msg.message.recipient == self_id;
-        if !is_self_sender && !is_self_recipient {
-            continue;
-        }
-
-        let counterparty = if is_self_sender {
-            msg.message.recipient
-        } else {
-            msg.message.sender
-        };
-
-        if let Some(filter) = with_filter {
-            if counterparty != filter {
-                continue;
-            }
-        }
-
-        if let Some(cut) = cutoff {
-            if msg.message.timestamp < cut {
-                continue;
-            }
-        }
-
-        // Render the body as a String. For inbound DMs we decrypt the
-        // ECIES envelope, then decode the structured `DirectMessageBody`
-        // (which falls back to legacy raw-UTF-8 → `Text` for pre-#XXX
-        // peers). Outbound DMs go through the local plaintext cache as
-        // before — the cache stores the user-facing string regardless of
-        // wire shape.
-        let (body_str, is_invite) = if is_self_recipient {
-            match open_direct_message(&signing_key, msg) {
-                Ok(bytes) => match decode_body(&bytes) {
-                    Ok(body) => {
-                        let invite = matches!(body, DirectMessageBody::Invite(_));
-                        (format_dm_body_for_cli(&body, &nicknames), invite)
-                    }
-                    Err(_) => ("<unable to decode body>".to_string(), false),
-                },
-                Err(_) => ("<unable to decrypt>".to_string(), false),
-            }
-        } else {
-            let plaintext = match outbound_lookup.get(&(msg.message.recipient, msg.purge_token())) {
-                Some(plaintext) => plaintext.clone(),
-                None => "<sent: ciphertext only>".to_string(),
-            };
-            (plaintext, false)
-        };
-
-        decrypted.push(DecryptedDm {
-            counterparty,
-            outgoing: is_self_sender,
-            timestamp: msg.message.timestamp,
-            body: body_str,
-            token: msg.purge_token(),
-            is_invite,
-        });
-    }
+    let nicknames = member_nicknames(&room_state, &secrets);
 
-    // Best-effort prune: drop cached entries whose ciphertext is gone
-    // from this room's state (recipient purged or contract cap
-    // evicted). We only touch entries for THIS room — other rooms'
-    // entries are left alone since their state isn't in scope here.
-    if let Err(e) = prune_outbound_cache_for_room(&api, &room_owner_key, &room_state) {
-        tracing::warn!("Failed to prune outbound DM cache: {}", e);
-    }
+    let inbox = sync_inbox(&api, &room_owner_key, &signing_key, &room_state).await?;
+    let mut decrypted = decrypt_dms(
+        &api,
+        &room_owner_key,
+        &signing_key,
+        &room_state,
+        &inbox,
+        &nicknames,
+    );
+    // Everything fetched counts as delivered, whatever the filters show.
+    let mut receipt_marks: HashMap<MemberId, (u64, u64)> = HashMap::new();
+    for dm in decrypted.iter().filter(|dm| !dm.outgoing) {
+        let (delivered, _) = receipt_marks.entry(dm.counterparty).or_default();
+        *delivered = (*delivered).max(dm.timestamp);
+    }
+    decrypted.retain(|dm| {
+        with_filter.is_none_or(|filter| dm.counterparty == filter)
+            && cutoff.is_none_or(|cut| dm.timestamp >= cut)
+    });
+    let blocked = blocked_members(&api, &room_owner_key, &signing_key);
+    let mut group_dms =
+        decrypt_group_dms(&room_owner_key, &signing_key, &room_state, &inbox, &blocked);
+    group_dms.retain(|dm| {
+        with_filter.is_none_or(|filter| dm.participants.contains(&filter))
+            && cutoff.is_none_or(|cut| dm.timestamp >= cut)
+    });
 
     // Sort by counterparty, then chronological.
     decrypted.sort_by(|a, b| {
@@ -718,10 +811,38 @@ async fn execute_list(
             *thread = thread.split_off(take_from);
         }
     }
+    // ...and only what is printed counts as read.
+    for dm in by_peer.values().flatten().filter(|dm| !dm.outgoing) {
+        if let Some((_, read)) = receipt_marks.get_mut(&dm.counterparty) {
+            *read = (*read).max(dm.timestamp);
+        }
+    }
+    // Group threads are keyed by their participant set; `group_dms` is
+    // already oldest first.
+    let mut by_group: HashMap<Vec<MemberId>, Vec<DecryptedGroupDm>> = HashMap::new();
+    for dm in group_dms {
+        by_group
+            .entry(dm.participants.clone())
+            .or_default()
+            .push(dm);
+    }
+    for thread in by_group.values_mut() {
+        if thread.len() > limit {
+            let take_from = thread.len() - limit;
+            *thread = thread.split_off(take_from);
+        }
+    }
+    let self_id = MemberId::from(&signing_key.verifying_key());
+    let name_of = |id: &MemberId| {
+        nicknames
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| short_member_id(id))
+    };
 
     match format {
         OutputFormat::Human => {
-            if by_peer.is_empty() {
+            if by_peer.is_empty() && by_group.is_empty() {
                 println!("No direct messages found.");
                 return Ok(());
             }
@@ -733,11 +854,20 @@ async fn execute_list(
                     .cloned()
                     .unwrap_or_else(|| short_member_id(&peer));
                 println!("--- DM thread with {} ({}) ---", nickname, peer);
+                let receipts = inbox.receipts_from(peer);
                 let mut idx = 1usize;
                 for dm in by_peer.get(&peer).unwrap() {
                     let local_time = format_unix_local(dm.timestamp);
                     let direction = if dm.outgoing { "->" } else { "<-" };
-                    println!("[{:>3}] {} [{}] {}", idx, direction, local_time, dm.body);
+                    let status = receipts
+                        .filter(|_| dm.outgoing)
+                        .and_then(|r| r.status_of(dm.timestamp))
+                        .map(|status| format!(" ({})", status.label()))
+                        .unwrap_or_default();
+                    println!(
+                        "[{:>3}] {} [{}] {}{}",
+                        idx, direction, local_time, dm.body, status
+                    );
                     if !dm.outgoing {
                         println!("        purge token: {}", hex_token(&dm.token));
                     }
@@ -745,6 +875,27 @@ async fn execute_list(
                 }
                 println!();
             }
+            let mut groups: Vec<_> = by_group.keys().cloned().collect();
+            groups.sort();
+            for group in groups {
+                let names: Vec<String> = group
+                    .iter()
+                    .filter(|p| **p != self_id)
+                    .map(name_of)
+                    .collect();
+                println!("--- Group DM with {} ---", names.join(", "));
+                for (idx, dm) in by_group[&group].iter().enumerate() {
+                    let local_time = format_unix_local(dm.timestamp);
+                    let from = if dm.outgoing {
+                        "me".to_string()
+                    } else {
+                        name_of(&dm.sender)
+                    };
+                    println!("[{:>3}] {} [{}] {}", idx + 1, from, local_time, dm.body);
+                    println!("        purge token: {}", hex_token(&dm.token));
+                }
+                println!();
+            }
             // Discoverability: invite-via-DM (#252) bodies render as
             // `[Invitation to room …]` but aren't actionable until the user
             // knows the accept command. Point them at it when any is present.
@@ -757,45 +908,123 @@ async fn execute_list(
             }
         }
         OutputFormat::Json => {
-            let threads: Vec<_> = by_peer
+            let mut threads: Vec<_> = by_peer
                 .into_iter()
                 .map(|(peer, dms)| {
-                    json!({
-                        "counterparty": peer.to_string(),
-                        "counterparty_nickname": nicknames.get(&peer).cloned(),
-                        "messages": dms
-                            .into_iter()
-                            .map(|dm| {
-                                let datetime: DateTime<Utc> =
-                                    SystemTime::UNIX_EPOCH
-                                        .checked_add(std::time::Duration::from_secs(dm.timestamp))
-                                        .map(DateTime::<Utc>::from)
-                                        .unwrap_or_else(Utc::now);
-                                json!({
-                                    "direction": if dm.outgoing { "outgoing" } else { "incoming" },
-                                    "timestamp": datetime.to_rfc3339(),
-                                    "timestamp_unix": dm.timestamp,
-                                    "body": dm.body,
-                                    "purge_token": hex_token(&dm.token),
-                                    "is_invitation": dm.is_invite,
-                                })
-                            })
-                            .collect::<Vec<_>>(),
-                    })
+                    dm_thread_json(
+                        peer,
+                        nicknames.get(&peer).cloned(),
+                        inbox.receipts_from(peer),
+                        &dms,
+                    )
                 })
                 .collect();
+            threads.extend(
+                by_group
+                    .into_iter()
+                    .map(|(participants, dms)| group_thread_json(&participants, &nicknames, &dms)),
+            );
             println!("{}", serde_json::to_string_pretty(&threads)?);
         }
     }
+
+    // After the listing, so a slow peer inbox never holds it up.
+    send_receipts(
+        &api,
+        &room_owner_key,
+        &signing_key,
+        &room_state,
+        receipt_marks,
+    )
+    .await;
     Ok(())
 }
 
-async fn execute_purge(
-    api: ApiClient,
-    format: OutputFormat,
-    room_id: &str,
-    token: &str,
-) -> Result<()> {
+/// Tell each peer in `marks` how far the local member has got with their
+/// DMs: `(delivered up to, read up to)`. Each peer's inbox is read for the
+/// envelope it already holds from us, so the new one continues its version
+/// and never takes a mark back; nothing is sent when neither mark moved.
+/// Failures are only logged: receipts are a courtesy, not part of listing.
+async fn send_receipts(
+    api: &ApiClient,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+    room_state: &ChatRoomStateV1,
+    marks: HashMap<MemberId, (u64, u64)>,
+) {
+    if marks.is_empty() {
+        return;
+    }
+    let reader_chain = match own_sender_chain(api, room_owner_key, signing_key, room_state) {
+        Ok(chain) => chain,
+        Err(e) => {
+            tracing::warn!("Not sending DM receipts: {}", e);
+            return;
+        }
+    };
+    let owner_id = MemberId::from(room_owner_key);
+    for (peer, (delivered, read)) in marks {
+        let peer_vk = if peer == owner_id {
+            *room_owner_key
+        } else {
+            match room_state
+                .members
+                .members
+                .iter()
+                .find(|m| m.member.id() == peer)
+            {
+                Some(member) => member.member.member_vk,
+                // Departed: their inbox is nobody's to read.
+                None => continue,
+            }
+        };
+        let params = InboxParametersV1 {
+            room_owner: *room_owner_key,
+            recipient: peer_vk,
+        };
+        let previous = match api.get_inbox(&params).await {
+            Ok(inbox) => inbox
+                .receipts_from(MemberId::from(&signing_key.verifying_key()))
+                .cloned(),
+            Err(e) => {
+                tracing::warn!("Failed to read {}'s inbox for DM receipts: {}", peer, e);
+                continue;
+            }
+        };
+        let receipts = match advance_dm_receipts(
+            signing_key,
+            peer,
+            room_owner_key,
+            previous.as_ref().map(|p| &p.state),
+            delivered,
+            read,
+        ) {
+            Ok(Some(receipts)) => receipts,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!("Failed to build DM receipts for {}: {}", peer, e);
+                continue;
+            }
+        };
+        let update = InboxStateV1 {
+            receipts: vec![InboxReceiptsV1 {
+                reader_chain: reader_chain.clone(),
+                receipts,
+            }],
+            ..Default::default()
+        };
+        if let Err(e) = api.put_inbox(&params, update).await {
+            tracing::warn!("Failed to send DM receipts to {}: {}", peer, e);
+        }
+    }
+}
+
+async fn execute_purge(
+    api: ApiClient,
+    format: OutputFormat,
+    room_id: &str,
+    token: &str,
+) -> Result<()> {
     let room_owner_key = parse_room_id(room_id)?;
     let (signing_key, _, _) = api
         .storage()
@@ -812,61 +1041,101 @@ async fn execute_purge(
         )
     })?;
 
-    // Sanity: confirm the token names a DM we actually received. The
-    // contract `apply_delta` is silent-drop on no-op envelopes; without
-    // this guard a typo in the token would print "Purge envelope sent"
-    // and tombstone nothing.
-    let matched = room_state
+    // Sanity: confirm the token names a DM we actually received. Both the
+    // inbox and the room contract accept a purge envelope naming tokens they
+    // do not hold; without this guard a typo in the token would print "Purge
+    // envelope sent" and tombstone nothing.
+    let inbox = sync_inbox(&api, &room_owner_key, &signing_key, &room_state).await?;
+    let in_inbox = inbox
+        .messages
+        .iter()
+        .any(|m| m.message.purge_token() == resolved_token)
+        || inbox
+            .group_messages
+            .iter()
+            .any(|m| m.message.purge_token() == resolved_token);
+    let in_room = room_state
         .direct_messages
         .messages
         .iter()
         .any(|m| m.message.recipient == self_id && m.purge_token() == resolved_token);
-    if !matched {
+    if !in_inbox && !in_room {
         return Err(anyhow!(
             "No inbound DM in this room matches that purge token. Run `dm list` to confirm."
         ));
     }
 
+    // DMs live in the inbox; only one that could not be migrated (its sender
+    // has since left the room) is still purged from the room state.
+    let previous = if in_inbox {
+        inbox.purges.clone()
+    } else {
+        room_state
+            .direct_messages
+            .purges
+            .iter()
+            .find(|p| p.recipient_id == self_id)
+            .cloned()
+    };
+
     // Skip if the recipient already has this token in their current envelope.
-    let already_purged = room_state
-        .direct_messages
-        .purges
-        .iter()
-        .find(|p| p.recipient_id == self_id)
-        .map(|p| p.state.purged.contains(&resolved_token))
-        .unwrap_or(false);
-    if already_purged {
+    if previous
+        .as_ref()
+        .is_some_and(|p| p.state.purged.contains(&resolved_token))
+    {
         return Err(anyhow!(
             "That DM is already in your purge envelope; nothing to do."
         ));
     }
 
     // Compose the new envelope on top of any existing one for this recipient.
-    let previous = room_state
-        .direct_messages
-        .purges
-        .iter()
-        .find(|p| p.recipient_id == self_id)
-        .cloned();
-    let envelope = advance_recipient_purges(
-        &signing_key,
-        &room_owner_key,
-        previous.as_ref(),
-        [resolved_token],
-    )
+    // The inbox's compacts behind a horizon once it is full.
+    let envelope = if in_inbox {
+        inbox.advance_purges(&signing_key, &room_owner_key, [resolved_token])
+    } else {
+        advance_recipient_purges(
+            &signing_key,
+            &room_owner_key,
+            previous.as_ref(),
+            [resolved_token],
+        )
+    }
     .map_err(|e| anyhow!("Failed to build purge envelope: {}", e))?;
 
-    let delta = ChatRoomStateV1Delta {
-        direct_messages: Some(
-            river_core::room_state::direct_messages::DirectMessagesDelta {
-                new_messages: vec![],
-                advanced_purges: vec![envelope.clone()],
+    if in_inbox {
+        let params = InboxParametersV1 {
+            room_owner: room_owner_key,
+            recipient: signing_key.verifying_key(),
+        };
+        api.put_inbox(
+            &params,
+            InboxStateV1 {
+                purges: Some(envelope.clone()),
+                ..Default::default()
             },
-        ),
-        ..Default::default()
-    };
+        )
+        .await?;
+    } else {
+        let delta = ChatRoomStateV1Delta {
+            direct_messages: Some(
+                river_core::room_state::direct_messages::DirectMessagesDelta {
+                    new_messages: vec![],
+                    advanced_purges: vec![envelope.clone()],
+                },
+            ),
+            ..Default::default()
+        };
+        api.send_state_delta(&room_owner_key, &delta).await?;
+    }
 
-    api.send_state_delta(&room_owner_key, &delta).await?;
+    // A ratcheted DM's key is already gone; dropping its plaintext leaves
+    // nothing that reads it.
+    if let Err(e) = api.storage().mutate_dm_ratchet(&room_owner_key, |store| {
+        store.forget(&[resolved_token]);
+        Ok(())
+    }) {
+        tracing::warn!("Failed to forget the purged DM's plaintext: {}", e);
+    }
 
     match format {
         OutputFormat::Human => println!(
@@ -887,6 +1156,50 @@ async fn execute_purge(
     Ok(())
 }
 
+/// `dm prekey`: rotate the local prekey and publish it, or stop publishing
+/// one. A new secret is saved before its public half goes out, and the old
+/// secrets are deleted only after the removal is published, so a peer never
+/// holds a prekey this client cannot answer.
+async fn execute_prekey(
+    api: &ApiClient,
+    format: OutputFormat,
+    room_id: &str,
+    remove: bool,
+) -> Result<()> {
+    let room_owner_key = parse_room_id(room_id)?;
+    let storage = api.storage();
+    let prekey = if remove {
+        api.set_dm_prekey(&room_owner_key, None).await?;
+        storage.mutate_dm_ratchet(&room_owner_key, |store| {
+            store.clear_prekeys();
+            Ok(None)
+        })?
+    } else {
+        let prekey =
+            storage.mutate_dm_ratchet(&room_owner_key, |store| Ok(store.rotate_prekey()))?;
+        api.set_dm_prekey(&room_owner_key, Some(prekey)).await?;
+        Some(prekey)
+    };
+
+    match format {
+        OutputFormat::Human => match prekey {
+            Some(prekey) => println!(
+                "Published DM prekey {}; new DMs to you are forward-secret.",
+                prekey.id
+            ),
+            None => println!("Stopped publishing a DM prekey."),
+        },
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "status": "success",
+                "prekey_id": prekey.map(|p| p.id),
+            }))?
+        ),
+    }
+    Ok(())
+}
+
 /// `dm accept` — join a room from an invitation that arrived as a DM.
 ///
 /// The invite-via-DM flow (#252) embeds a CBOR `Invitation` inside a
@@ -916,8 +1229,24 @@ async fn execute_accept(
     })?;
 
     let room_state = api.get_room(&room_owner_key, false).await?;
-
-    let invites = collect_inbound_invites(&room_state, &signing_key, from);
+    let inbox = sync_inbox(&api, &room_owner_key, &signing_key, &room_state).await?;
+
+    let invites = collect_inbound_invites(
+        open_inbound_dms(
+            &api,
+            &room_owner_key,
+            &signing_key,
+            inbound_dms(
+                &room_state,
+                &room_owner_key,
+                &signing_key,
+                &inbox,
+                &blocked_members(&api, &room_owner_key, &signing_key),
+            ),
+        ),
+        &signing_key,
+        from,
+    );
     if invites.is_empty() {
         return Err(match from {
             Some(f) => anyhow!(
@@ -934,146 +1263,667 @@ async fn execute_accept(
         });
     }
 
-    let chosen = select_invite_to_accept(invites, room)?;
-    let nickname = resolve_nickname(nickname)?;
+    let chosen = select_invite_to_accept(invites, room)?;
+    let nickname = resolve_nickname(nickname)?;
+
+    if !matches!(format, OutputFormat::Json) {
+        eprintln!(
+            "Accepting invitation to room {} (from {})...",
+            short_room_key(&chosen.target),
+            short_member_id(&chosen.sender)
+        );
+    }
+
+    let (room_owner_vk, contract_key) = api
+        .accept_invitation_struct(chosen.invitation, &nickname)
+        .await?;
+    print_invitation_accepted(format, &room_owner_vk, &contract_key);
+    Ok(())
+}
+
+/// A decoded, room-matched invite candidate — an [`InboundInvite`] whose
+/// embedded `Invitation` decoded cleanly and targets the room its payload
+/// advertised.
+#[derive(Debug)]
+struct ValidInvite {
+    sender: MemberId,
+    timestamp: u64,
+    target: VerifyingKey,
+    invitation: Invitation,
+}
+
+/// Choose the single invitation `dm accept` should act on, or explain why it
+/// can't. Pure (no I/O) so the selection rules are unit-testable.
+///
+/// Steps, in order:
+/// 1. **Target filter.** When `room_filter` is set, drop invites whose target
+///    room's base58 key doesn't start with the given prefix.
+/// 2. **Decode + validate.** Decode each remaining invite's embedded
+///    `Invitation` and keep only the well-formed, room-matched ones
+///    (`decode_invitation_from_payload`). Malformed invites — undecodable CBOR
+///    or a target-room mismatch — are dropped, NOT fatal: a later corrupt or
+///    malicious invite to a room must not block accepting an earlier valid one
+///    (Codex review of #343). If every candidate is malformed we say so, with
+///    a count.
+/// 3. **Disambiguate.** If the surviving valid invites target more than one
+///    distinct room, refuse and list them, pointing at `--room` / `--from`.
+/// 4. **Pick newest.** For the single surviving target, take the highest
+///    `timestamp`. Several valid invites to one room are interchangeable
+///    bearer credentials, so the choice only needs to be deterministic.
+fn select_invite_to_accept(
+    invites: Vec<InboundInvite>,
+    room_filter: Option<&str>,
+) -> Result<ValidInvite> {
+    let filtered: Vec<InboundInvite> = match room_filter {
+        Some(prefix) => invites
+            .into_iter()
+            .filter(|i| {
+                bs58::encode(i.payload.room_owner_vk.as_bytes())
+                    .into_string()
+                    .starts_with(prefix)
+            })
+            .collect(),
+        None => invites,
+    };
+    if filtered.is_empty() {
+        return Err(anyhow!(
+            "No invitation DMs target a room matching '{}'. Drop or widen `--room`.",
+            room_filter.unwrap_or_default()
+        ));
+    }
+
+    // Decode + validate; drop (don't fail on) malformed invites.
+    let total = filtered.len();
+    let mut valid: Vec<ValidInvite> = Vec::new();
+    for inv in &filtered {
+        if let Ok(invitation) = decode_invitation_from_payload(&inv.payload) {
+            valid.push(ValidInvite {
+                sender: inv.sender,
+                timestamp: inv.timestamp,
+                target: inv.payload.room_owner_vk,
+                invitation,
+            });
+        }
+    }
+    if valid.is_empty() {
+        return Err(anyhow!(
+            "All {} matching invitation DM(s) were malformed (undecodable, or targeting a \
+             mismatched room) and were skipped. Nothing to accept.",
+            total
+        ));
+    }
+
+    // Disambiguate to a single target room among the VALID invites.
+    let distinct_targets: HashSet<[u8; 32]> = valid.iter().map(|v| v.target.to_bytes()).collect();
+    if distinct_targets.len() > 1 {
+        let mut lines: Vec<String> = valid
+            .iter()
+            .map(|v| {
+                format!(
+                    "  - room {} (from {})",
+                    short_room_key(&v.target),
+                    short_member_id(&v.sender)
+                )
+            })
+            .collect();
+        lines.sort();
+        lines.dedup();
+        // Tailor the hint: if the user already passed `--room` and it still
+        // matched several rooms (a too-short prefix / prefix collision),
+        // telling them to "use --room" again is a dead-end — ask for a longer
+        // prefix instead.
+        let hint = if room_filter.is_some() {
+            "That `--room` prefix matches more than one room — pass a longer prefix \
+             (or add `--from <sender prefix>`) to pick one."
+        } else {
+            "Re-run with `--room <target room prefix>` (or `--from <sender prefix>`) to pick one."
+        };
+        return Err(anyhow!(
+            "Found invitations to {} different rooms in this DM thread:\n{}\n{}",
+            distinct_targets.len(),
+            lines.join("\n"),
+            hint
+        ));
+    }
+
+    // Single target: newest valid invite. `max_by_key` returns the last
+    // maximal element on ties, which is fine — interchangeable credentials.
+    Ok(valid
+        .into_iter()
+        .max_by_key(|v| v.timestamp)
+        .expect("valid is non-empty (checked above)"))
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Every DM the local member sent or received in this room, decrypted for
+/// display, oldest first. Inbound DMs come from `inbox` plus any still held
+/// in the room state; outbound bodies come from the local plaintext cache,
+/// since a DM sent into an inbox is readable only by its recipient. Shared
+/// by `dm list`, the TUI and the gateways.
+pub(crate) fn decrypt_dms(
+    api: &ApiClient,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+    room_state: &ChatRoomStateV1,
+    inbox: &InboxStateV1,
+    nicknames: &HashMap<MemberId, String>,
+) -> Vec<DecryptedDm> {
+    let self_id = MemberId::from(&signing_key.verifying_key());
+
+    // Load the local outbound-DM plaintext cache so we can render the
+    // sender's own bubbles as plaintext instead of `<sent: ciphertext
+    // only>`. See issue freenet/river#256. Missing entries (e.g. DMs
+    // sent before this cache shipped, or from another device) still
+    // fall back to the legacy placeholder.
+    let mut outbound: HashMap<(MemberId, PurgeToken), OutboundDmEntry> = api
+        .storage()
+        .load_outbound_dms()
+        .map(|store| {
+            store
+                .entries
+                .into_iter()
+                .filter(|e| e.room_owner_vk == room_owner_key.to_bytes() && e.sender == self_id)
+                .map(|e| ((e.recipient, e.purge_token), e))
+                .collect()
+        })
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to load outbound DM cache: {}", e);
+            HashMap::new()
+        });
+
+    let mut decrypted: Vec<DecryptedDm> = Vec::new();
+
+    // Inbound: decrypt the ECIES envelope, then decode the structured
+    // `DirectMessageBody` (which falls back to legacy raw-UTF-8 → `Text`
+    // for pre-#243 peers).
+    let inbound = open_inbound_dms(
+        api,
+        room_owner_key,
+        signing_key,
+        inbound_dms(
+            room_state,
+            room_owner_key,
+            signing_key,
+            inbox,
+            &blocked_members(api, room_owner_key, signing_key),
+        ),
+    );
+    for (msg, opened) in inbound {
+        let (body, is_invite) = match opened {
+            Ok(bytes) => match decode_body(&bytes) {
+                Ok(body) => {
+                    let invite = matches!(body, DirectMessageBody::Invite(_));
+                    (format_dm_body_for_cli(&body, nicknames), invite)
+                }
+                Err(_) => ("<unable to decode body>".to_string(), false),
+            },
+            Err(_) => ("<unable to decrypt>".to_string(), false),
+        };
+        decrypted.push(DecryptedDm {
+            counterparty: msg.message.sender,
+            outgoing: false,
+            timestamp: msg.message.timestamp,
+            body,
+            token: msg.purge_token(),
+            is_invite,
+        });
+    }
+
+    // Outbound DMs sent before inboxes are still in the room state.
+    for msg in &room_state.direct_messages.messages {
+        if msg.message.sender != self_id {
+            continue;
+        }
+        let token = msg.purge_token();
+        let body = match outbound.remove(&(msg.message.recipient, token)) {
+            Some(entry) => entry.plaintext,
+            None => "<sent: ciphertext only>".to_string(),
+        };
+        decrypted.push(DecryptedDm {
+            counterparty: msg.message.recipient,
+            outgoing: true,
+            timestamp: msg.message.timestamp,
+            body,
+            token,
+            is_invite: false,
+        });
+    }
+
+    // Everything else we sent went into a recipient's inbox; the cache's
+    // per-pair cap keeps this bounded like the inbox's per-sender one.
+    for entry in outbound.into_values() {
+        decrypted.push(DecryptedDm {
+            counterparty: entry.recipient,
+            outgoing: true,
+            timestamp: entry.timestamp,
+            body: entry.plaintext,
+            token: entry.purge_token,
+            is_invite: false,
+        });
+    }
+
+    decrypted.sort_by_key(|dm| dm.timestamp);
+    decrypted
+}
+
+/// The DMs addressed to the local member: those in their inbox from senders
+/// the room does not ban, then any still in the room state that the inbox
+/// does not already hold (sent by an older client, or not yet migrated).
+/// DMs from members in `blocked` are left out wherever they are held.
+pub(crate) fn inbound_dms<'a>(
+    room_state: &'a ChatRoomStateV1,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+    inbox: &'a InboxStateV1,
+    blocked: &HashSet<MemberId>,
+) -> Vec<&'a AuthorizedDirectMessage> {
+    let self_id = MemberId::from(&signing_key.verifying_key());
+    let params = ChatRoomParametersV1 {
+        owner: *room_owner_key,
+    };
+    let mut dms: Vec<&AuthorizedDirectMessage> =
+        inbox.visible_messages(room_state, &params).collect();
+    let held: HashSet<PurgeToken> = inbox
+        .messages
+        .iter()
+        .map(|m| m.message.purge_token())
+        .collect();
+    dms.extend(
+        room_state
+            .direct_messages
+            .messages
+            .iter()
+            .filter(|m| m.message.recipient == self_id && !held.contains(&m.purge_token())),
+    );
+    dms.retain(|m| !blocked.contains(&m.message.sender));
+    dms
+}
+
+/// Open inbound DMs, oldest first so ratchet sessions advance in order,
+/// through the local [`RatchetStore`], saving it if a session moved. If the
+/// store cannot be read or saved the DMs are opened without it, and
+/// ratcheted ones fail.
+fn open_inbound_dms<'a>(
+    api: &ApiClient,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+    mut dms: Vec<&'a AuthorizedDirectMessage>,
+) -> Vec<(&'a AuthorizedDirectMessage, Result<Vec<u8>, String>)> {
+    dms.sort_by_key(|m| m.message.timestamp);
+    let open_all = |store: &mut RatchetStore| {
+        dms.iter()
+            .map(|msg| (*msg, store.open(signing_key, room_owner_key, msg)))
+            .collect::<Vec<_>>()
+    };
+    api.storage()
+        .mutate_dm_ratchet(room_owner_key, |store| Ok(open_all(store)))
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to use the local DM ratchet store: {}", e);
+            open_all(&mut RatchetStore::default())
+        })
+}
+
+/// The group DMs in the local member's inbox from senders the room does not
+/// ban and the local member has not blocked, decrypted for display, oldest
+/// first.
+fn decrypt_group_dms(
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+    room_state: &ChatRoomStateV1,
+    inbox: &InboxStateV1,
+    blocked: &HashSet<MemberId>,
+) -> Vec<DecryptedGroupDm> {
+    let self_id = MemberId::from(&signing_key.verifying_key());
+    let params = ChatRoomParametersV1 {
+        owner: *room_owner_key,
+    };
+    let nicknames = HashMap::new();
+    inbox
+        .visible_group_messages(room_state, &params)
+        .filter(|msg| !blocked.contains(&msg.message.sender))
+        .map(|msg| {
+            let body = match open_group_direct_message(signing_key, msg) {
+                Ok(bytes) => match decode_body(&bytes) {
+                    Ok(body) => format_dm_body_for_cli(&body, &nicknames),
+                    Err(_) => "<unable to decode body>".to_string(),
+                },
+                Err(_) => "<unable to decrypt>".to_string(),
+            };
+            DecryptedGroupDm {
+                participants: msg.message.participants.clone(),
+                sender: msg.message.sender,
+                outgoing: msg.message.sender == self_id,
+                timestamp: msg.message.timestamp,
+                body,
+                token: msg.purge_token(),
+            }
+        })
+        .collect()
+}
+
+/// Read the local member's inbox in this room, first moving into it any DMs
+/// to them still held in the room state (`river_core::inbox::messages_from_room`)
+/// and then purging the room copies. Migration is best-effort: a failure is
+/// logged and retried on the next read, and the room copies keep showing
+/// until it succeeds.
+///
+/// DMs from members the local identity has blocked (`member block`) are
+/// purged from the inbox first when its purge envelope has room (a failure
+/// is logged; listings hide them regardless), and their room copies are
+/// purged without being moved.
+pub(crate) async fn sync_inbox(
+    api: &ApiClient,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+    room_state: &ChatRoomStateV1,
+) -> Result<InboxStateV1> {
+    let self_id = MemberId::from(&signing_key.verifying_key());
+    let params = InboxParametersV1 {
+        room_owner: *room_owner_key,
+        recipient: signing_key.verifying_key(),
+    };
+    let mut inbox = api.get_inbox(&params).await?;
+
+    let blocked = blocked_members(api, room_owner_key, signing_key);
+    if let Err(e) = purge_blocked_dms(api, &params, signing_key, &mut inbox, &blocked).await {
+        tracing::warn!("{}", e);
+    }
+
+    let room_params = ChatRoomParametersV1 {
+        owner: *room_owner_key,
+    };
+    let legacy = river_core::inbox::messages_from_room(room_state, &room_params, self_id);
+    if legacy.is_empty() {
+        return Ok(inbox);
+    }
+    let tokens: Vec<PurgeToken> = legacy.iter().map(|m| m.message.purge_token()).collect();
 
-    if !matches!(format, OutputFormat::Json) {
-        eprintln!(
-            "Accepting invitation to room {} (from {})...",
-            short_room_key(&chosen.target),
-            short_member_id(&chosen.sender)
-        );
+    // A blocked member's room DMs are purged below without being moved.
+    let held: HashSet<PurgeToken> = inbox
+        .messages
+        .iter()
+        .map(|m| m.message.purge_token())
+        .collect();
+    let missing = InboxStateV1 {
+        messages: legacy
+            .into_iter()
+            .filter(|m| {
+                !held.contains(&m.message.purge_token())
+                    && !blocked.contains(&m.message.message.sender)
+            })
+            .collect(),
+        ..Default::default()
+    };
+    if !missing.is_empty() {
+        if let Err(e) = api.put_inbox(&params, missing.clone()).await {
+            tracing::warn!("Failed to move room DMs into your inbox: {}", e);
+            return Ok(inbox);
+        }
+        if let Err(e) = inbox.merge(&params, missing) {
+            tracing::warn!("Failed to merge moved DMs locally: {}", e);
+        }
     }
 
-    let (room_owner_vk, contract_key) = api
-        .accept_invitation_struct(chosen.invitation, &nickname)
-        .await?;
-    print_invitation_accepted(format, &room_owner_vk, &contract_key);
-    Ok(())
+    let previous = room_state
+        .direct_messages
+        .purges
+        .iter()
+        .find(|p| p.recipient_id == self_id);
+    match advance_recipient_purges(signing_key, room_owner_key, previous, tokens) {
+        Ok(envelope) => {
+            let delta = ChatRoomStateV1Delta {
+                direct_messages: Some(
+                    river_core::room_state::direct_messages::DirectMessagesDelta {
+                        new_messages: vec![],
+                        advanced_purges: vec![envelope],
+                    },
+                ),
+                ..Default::default()
+            };
+            if let Err(e) = api.send_state_delta(room_owner_key, &delta).await {
+                tracing::warn!("Failed to purge moved DMs from the room: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to build purge envelope for moved DMs: {}", e),
+    }
+    Ok(inbox)
 }
 
-/// A decoded, room-matched invite candidate — an [`InboundInvite`] whose
-/// embedded `Invitation` decoded cleanly and targets the room its payload
-/// advertised.
-#[derive(Debug)]
-struct ValidInvite {
-    sender: MemberId,
-    timestamp: u64,
-    target: VerifyingKey,
-    invitation: Invitation,
+/// Purge from the local member's inbox every DM it holds from a member they
+/// have blocked, for `member block`. Unlike [`sync_inbox`], which only logs
+/// a failed purge, this returns the error: the DMs stay hidden either way,
+/// but the caller should know they are still held.
+pub(crate) async fn purge_blocked_senders(
+    api: &ApiClient,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+) -> Result<()> {
+    let params = InboxParametersV1 {
+        room_owner: *room_owner_key,
+        recipient: signing_key.verifying_key(),
+    };
+    let mut inbox = api.get_inbox(&params).await?;
+    let blocked = blocked_members(api, room_owner_key, signing_key);
+    purge_blocked_dms(api, &params, signing_key, &mut inbox, &blocked).await
 }
 
-/// Choose the single invitation `dm accept` should act on, or explain why it
-/// can't. Pure (no I/O) so the selection rules are unit-testable.
-///
-/// Steps, in order:
-/// 1. **Target filter.** When `room_filter` is set, drop invites whose target
-///    room's base58 key doesn't start with the given prefix.
-/// 2. **Decode + validate.** Decode each remaining invite's embedded
-///    `Invitation` and keep only the well-formed, room-matched ones
-///    (`decode_invitation_from_payload`). Malformed invites — undecodable CBOR
-///    or a target-room mismatch — are dropped, NOT fatal: a later corrupt or
-///    malicious invite to a room must not block accepting an earlier valid one
-///    (Codex review of #343). If every candidate is malformed we say so, with
-///    a count.
-/// 3. **Disambiguate.** If the surviving valid invites target more than one
-///    distinct room, refuse and list them, pointing at `--room` / `--from`.
-/// 4. **Pick newest.** For the single surviving target, take the highest
-///    `timestamp`. Several valid invites to one room are interchangeable
-///    bearer credentials, so the choice only needs to be deterministic.
-fn select_invite_to_accept(
-    invites: Vec<InboundInvite>,
-    room_filter: Option<&str>,
-) -> Result<ValidInvite> {
-    let filtered: Vec<InboundInvite> = match room_filter {
-        Some(prefix) => invites
-            .into_iter()
-            .filter(|i| {
-                bs58::encode(i.payload.room_owner_vk.as_bytes())
-                    .into_string()
-                    .starts_with(prefix)
-            })
-            .collect(),
-        None => invites,
+/// The members the local identity has blocked (`member block`) in this
+/// room. A block list that cannot be read counts as empty.
+fn blocked_members(
+    api: &ApiClient,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+) -> HashSet<MemberId> {
+    api.storage()
+        .blocked_members(room_owner_key, signing_key.verifying_key().into())
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to read your block list: {}", e);
+            HashSet::new()
+        })
+}
+
+/// Fold every DM `inbox` holds from a member in `blocked` into the
+/// recipient's inbox purge envelope, compacting it if full, and drop their
+/// opened plaintext. On failure `inbox` is left as it was; its DMs from
+/// `blocked` are still hidden from every listing.
+async fn purge_blocked_dms(
+    api: &ApiClient,
+    params: &InboxParametersV1,
+    signing_key: &SigningKey,
+    inbox: &mut InboxStateV1,
+    blocked: &HashSet<MemberId>,
+) -> Result<()> {
+    let tokens = inbox.tokens_from(blocked);
+    if tokens.is_empty() {
+        return Ok(());
+    }
+    let envelope = inbox
+        .advance_purges(signing_key, &params.room_owner, tokens.iter().copied())
+        .map_err(|e| anyhow!("Failed to build purge envelope for blocked members: {}", e))?;
+    let purge = InboxStateV1 {
+        purges: Some(envelope),
+        ..Default::default()
     };
-    if filtered.is_empty() {
-        return Err(anyhow!(
-            "No invitation DMs target a room matching '{}'. Drop or widen `--room`.",
-            room_filter.unwrap_or_default()
-        ));
+    api.put_inbox(params, purge.clone())
+        .await
+        .map_err(|e| anyhow!("Failed to purge DMs from blocked members: {}", e))?;
+    if let Err(e) = inbox.merge(params, purge) {
+        tracing::warn!("Failed to merge the purge locally: {}", e);
+    }
+    if let Err(e) = api
+        .storage()
+        .mutate_dm_ratchet(&params.room_owner, |store| {
+            store.forget(&tokens);
+            Ok(())
+        })
+    {
+        tracing::warn!("Failed to forget blocked members' DM plaintext: {}", e);
     }
+    Ok(())
+}
 
-    // Decode + validate; drop (don't fail on) malformed invites.
-    let total = filtered.len();
-    let mut valid: Vec<ValidInvite> = Vec::new();
-    for inv in &filtered {
-        if let Ok(invitation) = decode_invitation_from_payload(&inv.payload) {
-            valid.push(ValidInvite {
-                sender: inv.sender,
-                timestamp: inv.timestamp,
-                target: inv.payload.room_owner_vk,
-                invitation,
-            });
-        }
+/// The membership proof the local member attaches to a DM: nothing for the
+/// owner, otherwise their invite chain from the room state, or the one
+/// stored at join time if they have been pruned from the member list.
+fn own_sender_chain(
+    api: &ApiClient,
+    room_owner_key: &VerifyingKey,
+    signing_key: &SigningKey,
+    room_state: &ChatRoomStateV1,
+) -> Result<Vec<AuthorizedMember>> {
+    let self_vk = signing_key.verifying_key();
+    if self_vk == *room_owner_key {
+        return Ok(Vec::new());
     }
-    if valid.is_empty() {
-        return Err(anyhow!(
-            "All {} matching invitation DM(s) were malformed (undecodable, or targeting a \
-             mismatched room) and were skipped. Nothing to accept.",
-            total
-        ));
+    let params = ChatRoomParametersV1 {
+        owner: *room_owner_key,
+    };
+    if let Some(member) = room_state
+        .members
+        .members
+        .iter()
+        .find(|m| m.member.member_vk == self_vk)
+    {
+        return river_core::inbox::sender_chain(room_state, &params, member)
+            .map_err(|e| anyhow!("Failed to build your invite chain: {}", e));
+    }
+
+    // Codex P2 (PR #269 review): a pruned sender with no stored credentials
+    // (older stored rooms missing `self_authorized_member`) cannot prove
+    // membership; surface that instead of sending a DM the inbox rejects.
+    let key_str = bs58::encode(room_owner_key.as_bytes()).into_string();
+    match api.storage().load_rooms()?.rooms.get(&key_str) {
+        Some(info) => match &info.self_authorized_member {
+            Some(member) => Ok(std::iter::once(member.clone())
+                .chain(info.invite_chain.iter().cloned())
+                .collect()),
+            None => Err(anyhow!(
+                "Your member entry is not in the room and no stored rejoin credentials \
+                 are available. Re-accept your invitation with `riverctl invite accept` \
+                 before sending a DM."
+            )),
+        },
+        None => Err(anyhow!("Room not found in local storage")),
     }
+}
 
-    // Disambiguate to a single target room among the VALID invites.
-    let distinct_targets: HashSet<[u8; 32]> = valid.iter().map(|v| v.target.to_bytes()).collect();
-    if distinct_targets.len() > 1 {
-        let mut lines: Vec<String> = valid
+/// One `dm list --format json` thread: the counterparty, its DMs in the
+/// order given, and how far the counterparty has got with ours per their
+/// `receipts`, overall and as each sent DM's `receipt`.
+pub(crate) fn dm_thread_json(
+    peer: MemberId,
+    nickname: Option<String>,
+    receipts: Option<&AuthorizedDmReceipts>,
+    dms: &[DecryptedDm],
+) -> serde_json::Value {
+    json!({
+        "counterparty": peer.to_string(),
+        "counterparty_nickname": nickname,
+        "delivered_up_to": receipts.map(|r| r.state.delivered_up_to),
+        "read_up_to": receipts.map(|r| r.state.read_up_to),
+        "messages": dms
             .iter()
-            .map(|v| {
-                format!(
-                    "  - room {} (from {})",
-                    short_room_key(&v.target),
-                    short_member_id(&v.sender)
-                )
+            .map(|dm| {
+                let mut message = dm_json(dm);
+                if dm.outgoing {
+                    message["receipt"] = json!(receipts
+                        .and_then(|r| r.status_of(dm.timestamp))
+                        .map(|status| status.label()));
+                }
+                message
             })
-            .collect();
-        lines.sort();
-        lines.dedup();
-        // Tailor the hint: if the user already passed `--room` and it still
-        // matched several rooms (a too-short prefix / prefix collision),
-        // telling them to "use --room" again is a dead-end — ask for a longer
-        // prefix instead.
-        let hint = if room_filter.is_some() {
-            "That `--room` prefix matches more than one room — pass a longer prefix \
-             (or add `--from <sender prefix>`) to pick one."
-        } else {
-            "Re-run with `--room <target room prefix>` (or `--from <sender prefix>`) to pick one."
-        };
-        return Err(anyhow!(
-            "Found invitations to {} different rooms in this DM thread:\n{}\n{}",
-            distinct_targets.len(),
-            lines.join("\n"),
-            hint
-        ));
-    }
+            .collect::<Vec<_>>(),
+    })
+}
 
-    // Single target: newest valid invite. `max_by_key` returns the last
-    // maximal element on ties, which is fine — interchangeable credentials.
-    Ok(valid
-        .into_iter()
-        .max_by_key(|v| v.timestamp)
-        .expect("valid is non-empty (checked above)"))
+/// One group thread in `dm list --format json`: the participants (the local
+/// member included) and its DMs in the order given.
+fn group_thread_json(
+    participants: &[MemberId],
+    nicknames: &HashMap<MemberId, String>,
+    dms: &[DecryptedGroupDm],
+) -> serde_json::Value {
+    json!({
+        "participants": participants
+            .iter()
+            .map(|p| json!({"member": p.to_string(), "nickname": nicknames.get(p)}))
+            .collect::<Vec<_>>(),
+        "messages": dms
+            .iter()
+            .map(|dm| {
+                let mut message = dm_json(&DecryptedDm {
+                    counterparty: dm.sender,
+                    outgoing: dm.outgoing,
+                    timestamp: dm.timestamp,
+                    body: dm.body.clone(),
+                    token: dm.token,
+                    is_invite: false,
+                });
+                message["sender"] = json!(dm.sender.to_string());
+                message
+            })
+            .collect::<Vec<_>>(),
+    })
 }
 
-// ---------------------------------------------------------------------------
-// Helpers
-// ---------------------------------------------------------------------------
+/// One DM within a `dm list --format json` thread.
+fn dm_json(dm: &DecryptedDm) -> serde_json::Value {
+    let datetime: DateTime<Utc> = SystemTime::UNIX_EPOCH
+        .checked_add(std::time::Duration::from_secs(dm.timestamp))
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(Utc::now);
+    json!({
+        "direction": if dm.outgoing { "outgoing" } else { "incoming" },
+        "timestamp": datetime.to_rfc3339(),
+        "timestamp_unix": dm.timestamp,
+        "body": dm.body,
+        "purge_token": hex_token(&dm.token),
+        "is_invitation": dm.is_invite,
+    })
+}
 
-struct DecryptedDm {
-    counterparty: MemberId,
-    outgoing: bool,
-    timestamp: u64,
-    body: String,
+/// A `type: "dm"` room event: one DM with its room and counterparty, for the
+/// live streams of `riverctl serve` and `riverctl irc-gateway`.
+pub(crate) fn dm_event_json(
+    room_owner_key: &VerifyingKey,
+    dm: &DecryptedDm,
+    nickname: Option<String>,
+) -> serde_json::Value {
+    let mut event = dm_json(dm);
+    event["type"] = json!("dm");
+    event["room"] = json!(bs58::encode(room_owner_key.as_bytes()).into_string());
+    event["counterparty"] = json!(dm.counterparty.to_string());
+    event["counterparty_nickname"] = json!(nickname);
+    event
+}
+
+/// Every member's display nickname (decrypted for a private room), for
+/// labelling DM counterparties.
+pub(crate) fn member_nicknames(
+    room_state: &ChatRoomStateV1,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> HashMap<MemberId, String> {
+    room_state
+        .member_info
+        .member_info
+        .iter()
+        .map(|info| {
+            (
+                info.member_info.member_id,
+                crate::api::unseal_nickname_display(&info.member_info.preferred_nickname, secrets),
+            )
+        })
+        .collect()
+}
+
+pub(crate) struct DecryptedDm {
+    pub(crate) counterparty: MemberId,
+    pub(crate) outgoing: bool,
+    pub(crate) timestamp: u64,
+    pub(crate) body: String,
     token: PurgeToken,
     /// True when the decrypted body decoded to a
     /// `DirectMessageBody::Invite` — drives the `dm list` "accept with…"
@@ -1081,6 +1931,23 @@ struct DecryptedDm {
     is_invite: bool,
 }
 
+/// A decrypted group DM, for `dm list`.
+struct DecryptedGroupDm {
+    participants: Vec<MemberId>,
+    sender: MemberId,
+    outgoing: bool,
+    timestamp: u64,
+    body: String,
+    token: PurgeToken,
+}
+
+impl DecryptedDm {
+    /// The purge token as `dm list` prints it, which also identifies the DM.
+    pub(crate) fn token_hex(&self) -> String {
+        hex_token(&self.token)
+    }
+}
+
 /// One inbound DM that decoded to a `DirectMessageBody::Invite`, surfaced by
 /// [`collect_inbound_invites`] for `dm accept`.
 struct InboundInvite {
@@ -1089,25 +1956,25 @@ struct InboundInvite {
     payload: InvitePayload,
 }
 
-/// Decrypt every inbound DM in `state` addressed to the local member and
-/// return those that decode to a `DirectMessageBody::Invite`. `from_filter`,
+/// From the opened inbound DMs `dms` (see [`open_inbound_dms`]), return
+/// those that decode to a `DirectMessageBody::Invite`. `from_filter`,
 /// when set, keeps only invites whose sender's MemberId string starts with
 /// the given prefix. This is a plain `starts_with` on the MemberId — unlike
 /// `dm list --with` (which routes through `resolve_recipient_vk`) it does NOT
 /// consult the member list or error on an ambiguous prefix; over-broad
 /// filters simply fall through to the target-room disambiguation in
 /// [`select_invite_to_accept`]. Pure (no I/O) so `dm accept`'s selection
-/// logic is unit-testable against a hand-built room state. Undecryptable or
+/// logic is unit-testable against hand-built DMs. Undecryptable or
 /// undecodable DMs are skipped rather than failing the whole command — a
 /// single corrupt entry shouldn't block accepting a valid invitation.
-fn collect_inbound_invites(
-    state: &ChatRoomStateV1,
+fn collect_inbound_invites<'a>(
+    dms: impl IntoIterator<Item = (&'a AuthorizedDirectMessage, Result<Vec<u8>, String>)>,
     signing_key: &SigningKey,
     from_filter: Option<&str>,
 ) -> Vec<InboundInvite> {
     let self_id = MemberId::from(&signing_key.verifying_key());
     let mut out = Vec::new();
-    for msg in &state.direct_messages.messages {
+    for (msg, opened) in dms {
         if msg.message.recipient != self_id {
             continue;
         }
@@ -1116,7 +1983,7 @@ fn collect_inbound_invites(
                 continue;
             }
         }
-        let Ok(bytes) = open_direct_message(signing_key, msg) else {
+        let Ok(bytes) = opened else {
             continue;
         };
         let Ok(body) = decode_body(&bytes) else {
@@ -1325,53 +2192,6 @@ fn apply_per_pair_cap(store: &mut river_core::chat_delegate::OutboundDmStore) {
     }
 }
 
-/// Drop cached outbound-DM entries for `room_owner_vk` whose token
-/// appears in some recipient's purge envelope in the supplied
-/// `room_state`. Only entries for this room are considered — other
-/// rooms' state isn't in scope so their entries are left alone.
-///
-/// **Why we ONLY act on purge envelopes (not on "ciphertext missing
-/// from `messages`")** — see the UI-side
-/// `prune_outbound_dms_for_purges` docs for the cold-start
-/// race rationale; the CLI shares the same risk because `dm list`
-/// could be invoked against a freshly-republished node where the
-/// contract state hasn't fully resynced. Issue freenet/river#256.
-fn prune_outbound_cache_for_room(
-    api: &ApiClient,
-    room_owner_vk: &VerifyingKey,
-    room_state: &river_core::ChatRoomStateV1,
-) -> Result<()> {
-    let room_bytes = room_owner_vk.to_bytes();
-
-    // The purge set is derived from `room_state`, not the cache, so compute it
-    // (and short-circuit when empty) BEFORE taking the storage lock — no point
-    // serializing on an empty prune.
-    let purged: HashSet<(MemberId, PurgeToken)> = room_state
-        .direct_messages
-        .purges
-        .iter()
-        .flat_map(|envelope| {
-            envelope
-                .state
-                .purged
-                .iter()
-                .map(move |token| (envelope.recipient_id, *token))
-        })
-        .collect();
-    if purged.is_empty() {
-        return Ok(());
-    }
-
-    // Single locked load→mutate→save so a concurrent DM-send can't clobber this
-    // prune (issue freenet/river#307).
-    api.storage().mutate_outbound_dms(|store| {
-        store.entries.retain(|e| {
-            e.room_owner_vk != room_bytes || !purged.contains(&(e.recipient, e.purge_token))
-        });
-        Ok(())
-    })
-}
-
 fn unix_now() -> Result<u64> {
     Ok(SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -1422,6 +2242,7 @@ fn format_unix_local(unix_secs: u64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use river_core::room_state::direct_messages::{compose_direct_message, open_direct_message};
 
     /// The DM send path must NOT carry a client-side per-pair cap guard.
     ///
@@ -1543,6 +2364,53 @@ mod tests {
         assert!(id.to_string().starts_with(&s));
     }
 
+    #[test]
+    fn dm_thread_json_marks_sent_dms_by_the_peers_receipts() {
+        use ed25519_dalek::SigningKey;
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let me = SigningKey::from_bytes(&[2u8; 32]);
+        let peer = SigningKey::from_bytes(&[3u8; 32]);
+        let receipts = advance_dm_receipts(
+            &peer,
+            MemberId::from(&me.verifying_key()),
+            &owner,
+            None,
+            20,
+            10,
+        )
+        .unwrap();
+        let dm = |outgoing: bool, timestamp: u64| DecryptedDm {
+            counterparty: MemberId::from(&peer.verifying_key()),
+            outgoing,
+            timestamp,
+            body: String::new(),
+            token: PurgeToken([timestamp as u8; 16]),
+            is_invite: false,
+        };
+        let thread = dm_thread_json(
+            MemberId::from(&peer.verifying_key()),
+            None,
+            receipts.as_ref(),
+            &[dm(true, 10), dm(false, 15), dm(true, 20), dm(true, 30)],
+        );
+        let marks: Vec<_> = thread["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m.get("receipt").cloned())
+            .collect();
+        assert_eq!(
+            marks,
+            vec![
+                Some(json!("read")),
+                None,
+                Some(json!("delivered")),
+                Some(serde_json::Value::Null),
+            ]
+        );
+        assert_eq!(thread["read_up_to"], json!(10));
+    }
+
     /// Codex review of #244 found that the previous `resolve_recipient_vk`
     /// silently picked the first prefix match, which could route a private
     /// DM to the wrong recipient on accidental or malicious prefix
@@ -1639,10 +2507,7 @@ mod tests {
         let sender = MemberId(FastHash(11));
         let recipient = MemberId(FastHash(22));
 
-        let mut store = OutboundDmStore {
-            entries: vec![],
-            hidden_threads: vec![],
-        };
+        let mut store = OutboundDmStore::default();
         let over_cap = MAX_DM_MESSAGES_PER_PAIR + 5;
         for i in 0..over_cap {
             store.entries.push(OutboundDmEntry {
@@ -1675,10 +2540,7 @@ mod tests {
         let alice = MemberId(FastHash(11));
         let bob = MemberId(FastHash(22));
 
-        let mut store = OutboundDmStore {
-            entries: vec![],
-            hidden_threads: vec![],
-        };
+        let mut store = OutboundDmStore::default();
         // Fill (me -> alice) to over-cap; (me -> bob) only one entry.
         for i in 0..MAX_DM_MESSAGES_PER_PAIR + 3 {
             store.entries.push(OutboundDmEntry {
@@ -1812,13 +2674,21 @@ mod tests {
             ts,
         ));
 
+        let opened = || {
+            state
+                .direct_messages
+                .messages
+                .iter()
+                .map(|m| (m, open_direct_message(&me, m)))
+        };
+
         // No filter: both invites addressed to me, none of the noise.
-        let all = collect_inbound_invites(&state, &me, None);
+        let all = collect_inbound_invites(opened(), &me, None);
         assert_eq!(all.len(), 2, "should find Alice's and Bob's invites only");
 
         // `--from` Alice's MemberId prefix: just her invite.
         let alice_id = MemberId::from(&alice.verifying_key()).to_string();
-        let from_alice = collect_inbound_invites(&state, &me, Some(&alice_id[..8]));
+        let from_alice = collect_inbound_invites(opened(), &me, Some(&alice_id[..8]));
         assert_eq!(from_alice.len(), 1);
         assert_eq!(
             from_alice[0].payload.room_owner_vk, target_a,
@@ -1826,7 +2696,7 @@ mod tests {
         );
 
         // `--from` a prefix matching nobody: empty.
-        assert!(collect_inbound_invites(&state, &me, Some("zzzzzzzz")).is_empty());
+        assert!(collect_inbound_invites(opened(), &me, Some("zzzzzzzz")).is_empty());
     }
 
     /// `decode_invitation_from_payload` round-trips a matching invitation and
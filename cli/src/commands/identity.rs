@@ -21,6 +21,7 @@ pub enum IdentityCommands {
     /// are per-room, so there is no single global member ID.
     Whoami {
         /// Room owner's verifying key (base58). Omit for all rooms.
+        #[arg(value_parser = crate::config::room_arg)]
         room: Option<String>,
         /// Signing key (base64-encoded 32-byte Ed25519 signing key), matching
         /// `message send --signing-key` / `RIVER_SIGNING_KEY`.
@@ -36,6 +37,7 @@ pub enum IdentityCommands {
     /// Export your identity for a room as a portable token
     Export {
         /// Room owner's verifying key (base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room: String,
     },
     /// Import an identity from a portable token
@@ -1,17 +1,56 @@
-use crate::api::ApiClient;
+use crate::api::{author_member_id, ApiClient};
+use crate::commands::member::parse_ban_duration;
 use crate::output::OutputFormat;
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
 use clap::Subcommand;
 use colored::Colorize;
 use ed25519_dalek::VerifyingKey;
 use freenet_stdlib::prelude::ContractKey;
+use river_core::room_state::invite::InvitePolicy;
+use river_core::room_state::member::MemberId;
+use river_core::room_state::ChatRoomStateV1;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Subcommand)]
 pub enum InviteCommands {
     /// Create an invitation for a room
     Create {
         /// Room owner key (base58 encoded)
+        #[arg(value_parser = crate::config::room_arg)]
         room_owner_key: String,
+
+        /// Stop the invitation working after this long, e.g. `30m`, `12h` or
+        /// `7d` (default: no limit)
+        #[arg(long, value_parser = parse_ban_duration)]
+        expires: Option<Duration>,
+
+        /// A note to tell your invitations apart in `invite list`, e.g. where
+        /// you posted the link
+        #[arg(long)]
+        label: Option<String>,
+
+        /// Let this many people join with the link, each as their own member
+        /// (default: one)
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        max_uses: Option<u32>,
+    },
+    /// List the invitations you issued that are not used up yet
+    List {
+        /// Room owner key (base58 encoded)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_owner_key: String,
+    },
+    /// Revoke an invitation that has not been used yet
+    Revoke {
+        /// Room owner key (base58 encoded)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_owner_key: String,
+
+        /// Invitee ID from `invite list`, or the invitation code itself
+        invitation: String,
     },
     /// Accept an invitation
     Accept {
@@ -26,29 +65,22 @@ pub enum InviteCommands {
 
 pub async fn execute(command: InviteCommands, api: ApiClient, format: OutputFormat) -> Result<()> {
     match command {
-        InviteCommands::Create { room_owner_key } => {
-            // Decode the room owner key from base58
-            let decoded = bs58::decode(&room_owner_key)
-                .into_vec()
-                .map_err(|e| anyhow!("Failed to decode room owner key: {}", e))?;
-
-            if decoded.len() != 32 {
-                return Err(anyhow!(
-                    "Invalid room owner key length: expected 32 bytes, got {}",
-                    decoded.len()
-                ));
-            }
-
-            let mut key_bytes = [0u8; 32];
-            key_bytes.copy_from_slice(&decoded);
-            let owner_vk = VerifyingKey::from_bytes(&key_bytes)
-                .map_err(|e| anyhow!("Invalid verifying key: {}", e))?;
+        InviteCommands::Create {
+            room_owner_key,
+            expires,
+            label,
+            max_uses,
+        } => {
+            let owner_vk = parse_owner_key(&room_owner_key)?;
 
             if !matches!(format, OutputFormat::Json) {
                 eprintln!("Creating invitation for room owned by: {}", room_owner_key);
             }
 
-            match api.create_invitation(&owner_vk).await {
+            match api
+                .create_invitation(&owner_vk, expires, label.as_deref(), max_uses)
+                .await
+            {
                 Ok(invitation_code) => {
                     match format {
                         OutputFormat::Human => {
@@ -74,6 +106,72 @@ pub async fn execute(command: InviteCommands, api: ApiClient, format: OutputForm
                 }
             }
         }
+        InviteCommands::List { room_owner_key } => {
+            let owner_vk = parse_owner_key(&room_owner_key)?;
+            let (signing_key, _, _) = api.storage().get_room(&owner_vk)?.ok_or_else(|| {
+                anyhow!("Room not found. You must be a member of the room to list invitations.")
+            })?;
+            let mut room_state = api.get_room(&owner_vk, false).await?;
+            let secrets = api.room_display_secrets(&owner_vk, &mut room_state);
+            let invites = collect_issued_invites(
+                &room_state,
+                author_member_id(&signing_key),
+                &secrets,
+                SystemTime::now(),
+            );
+
+            match format {
+                OutputFormat::Human => {
+                    if invites.is_empty() {
+                        println!("No outstanding invitations with an expiry, label, use limit or revocation.");
+                    } else {
+                        println!("\n{} invitation(s):\n", invites.len());
+                        for invite in &invites {
+                            let mut line = format!("  {}  {}", invite.invitee_id, invite.status);
+                            if let Some(expires_at) = invite.expires_at_secs {
+                                line.push_str(&format!("  expires {}", format_time(expires_at)));
+                            }
+                            if let Some(max_uses) = invite.max_uses {
+                                line.push_str(&format!("  used {}/{}", invite.uses, max_uses));
+                            }
+                            if let Some(label) = &invite.label {
+                                line.push_str(&format!("  {:?}", label));
+                            }
+                            println!("{}", line);
+                        }
+                        println!(
+                            "\nUsed single-use invitations are not listed; see 'member list'."
+                        );
+                    }
+                }
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&invites)?);
+                }
+            }
+            Ok(())
+        }
+        InviteCommands::Revoke {
+            room_owner_key,
+            invitation,
+        } => {
+            let owner_vk = parse_owner_key(&room_owner_key)?;
+            let invitee = api.revoke_invitation(&owner_vk, &invitation).await?;
+            match format {
+                OutputFormat::Human => {
+                    println!("{}", format!("Invitation for {} revoked.", invitee).green());
+                }
+                OutputFormat::Json => {
+                    println!(
+                        "{}",
+                        serde_json::json!({
+                            "status": "success",
+                            "invitee_id": invitee.to_string(),
+                        })
+                    );
+                }
+            }
+            Ok(())
+        }
         InviteCommands::Accept {
             invitation_code,
             nickname,
@@ -98,6 +196,114 @@ pub async fn execute(command: InviteCommands, api: ApiClient, format: OutputForm
     }
 }
 
+/// Decode a base58 room owner key.
+pub(crate) fn parse_owner_key(room_owner_key: &str) -> Result<VerifyingKey> {
+    let decoded = bs58::decode(room_owner_key)
+        .into_vec()
+        .map_err(|e| anyhow!("Failed to decode room owner key: {}", e))?;
+
+    if decoded.len() != 32 {
+        return Err(anyhow!(
+            "Invalid room owner key length: expected 32 bytes, got {}",
+            decoded.len()
+        ));
+    }
+
+    let mut key_bytes = [0u8; 32];
+    key_bytes.copy_from_slice(&decoded);
+    VerifyingKey::from_bytes(&key_bytes).map_err(|e| anyhow!("Invalid verifying key: {}", e))
+}
+
+#[derive(Serialize)]
+pub(crate) struct IssuedInvite {
+    invitee_id: String,
+    /// `pending`, `used` (no uses left), `expired` (by this machine's clock)
+    /// or `revoked`.
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at_secs: Option<u64>,
+    /// Members who joined with the invitation so far.
+    uses: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_uses: Option<u32>,
+    /// Unsealed when we hold the room secret.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+}
+
+/// The invite records `me` issued, oldest first. The room keeps a record
+/// until its invitee joins, or while it limits how many may join, so every
+/// one listed can still admit or refuse someone. `secrets` unseals
+/// private-room labels; pass an empty map for a public room.
+pub(crate) fn collect_issued_invites(
+    room_state: &ChatRoomStateV1,
+    me: MemberId,
+    secrets: &HashMap<u32, [u8; 32]>,
+    now: SystemTime,
+) -> Vec<IssuedInvite> {
+    let unix_secs = |time: SystemTime| {
+        time.duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    };
+    let uses = &room_state.invite_uses;
+    let mut records: Vec<_> = room_state
+        .invites
+        .0
+        .iter()
+        .chain(
+            uses.0
+                .iter()
+                .map(|invite_use| &invite_use.invite)
+                .filter(|record| record.invite.policy.max_uses().is_some()),
+        )
+        .filter(|record| record.issued_by == me)
+        .collect();
+    records.sort_by_key(|record| (record.invite.issued_at, record.id()));
+    records.dedup_by_key(|record| record.id());
+    records
+        .into_iter()
+        .map(|record| {
+            let used = uses.count_for(record.invite.invitee);
+            let (status, expires_at) = match &record.invite.policy {
+                InvitePolicy::Revoked => ("revoked", None),
+                InvitePolicy::Open {
+                    expires_at,
+                    max_uses,
+                    ..
+                } => (
+                    if expires_at.is_some_and(|at| at <= now) {
+                        "expired"
+                    } else if max_uses.is_some_and(|max_uses| used >= max_uses as usize) {
+                        "used"
+                    } else {
+                        "pending"
+                    },
+                    *expires_at,
+                ),
+            };
+            IssuedInvite {
+                invitee_id: record.invite.invitee.to_string(),
+                status,
+                expires_at_secs: expires_at.map(unix_secs),
+                uses: used,
+                max_uses: record.invite.policy.max_uses(),
+                label: record
+                    .invite
+                    .policy
+                    .label()
+                    .map(|label| crate::api::unseal_nickname_display(label, secrets)),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn format_time(unix_secs: u64) -> String {
+    DateTime::<Local>::from(UNIX_EPOCH + std::time::Duration::from_secs(unix_secs))
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
 /// Resolve the invitee nickname: use the value the user passed, else prompt
 /// interactively on a TTY, else fall back to "Anonymous". Shared by
 /// `invite accept` and `dm accept` so both entry points behave identically.
@@ -148,3 +354,117 @@ pub fn print_invitation_accepted(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use ed25519_dalek::SigningKey;
+    use river_core::room_state::invite::{AuthorizedInvite, Invite, InvitesV1};
+    use river_core::room_state::privacy::SealedBytes;
+
+    /// Minimal harness so the `InviteCommands` clap surface can be parsed
+    /// without pulling in the whole binary's global flags.
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(subcommand)]
+        command: InviteCommands,
+    }
+
+    fn parse(args: &[&str]) -> Result<InviteCommands, clap::Error> {
+        let mut argv = vec!["invite"];
+        argv.extend_from_slice(args);
+        TestCli::try_parse_from(argv).map(|cli| cli.command)
+    }
+
+    #[test]
+    fn create_takes_an_expiry_a_label_and_a_use_limit() {
+        match parse(&[
+            "create",
+            "ROOM",
+            "--expires",
+            "12h",
+            "--label",
+            "forum",
+            "--max-uses",
+            "5",
+        ])
+        .unwrap()
+        {
+            InviteCommands::Create {
+                expires,
+                label,
+                max_uses,
+                ..
+            } => {
+                assert_eq!(expires, Some(Duration::from_secs(12 * 60 * 60)));
+                assert_eq!(label.as_deref(), Some("forum"));
+                assert_eq!(max_uses, Some(5));
+            }
+            other => panic!("wrong subcommand: {:?}", std::mem::discriminant(&other)),
+        }
+        match parse(&["create", "ROOM"]).unwrap() {
+            InviteCommands::Create { expires, label, .. } => {
+                assert_eq!(expires, None);
+                assert_eq!(label, None);
+            }
+            other => panic!("wrong subcommand: {:?}", std::mem::discriminant(&other)),
+        }
+        assert!(parse(&["create", "ROOM", "--expires", "0d"]).is_err());
+        assert!(parse(&["create", "ROOM", "--max-uses", "0"]).is_err());
+        assert!(parse(&["revoke", "ROOM", "ABCDEFGH"]).is_ok());
+    }
+
+    #[test]
+    fn list_reports_only_my_records_with_their_status() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let me = SigningKey::from_bytes(&[2u8; 32]);
+        let now = SystemTime::now();
+        let record = |issuer: &SigningKey, invitee: u8, policy| {
+            AuthorizedInvite::new(
+                Invite {
+                    owner_member_id: owner.verifying_key().into(),
+                    invitee: SigningKey::from_bytes(&[invitee; 32])
+                        .verifying_key()
+                        .into(),
+                    issued_at: now + Duration::from_secs(invitee.into()),
+                    policy,
+                },
+                issuer.verifying_key().into(),
+                issuer,
+            )
+        };
+        let state = ChatRoomStateV1 {
+            invites: InvitesV1(vec![
+                record(
+                    &me,
+                    10,
+                    InvitePolicy::Open {
+                        expires_at: Some(now + Duration::from_secs(60)),
+                        label: Some(SealedBytes::public(b"forum".to_vec())),
+                        max_uses: None,
+                    },
+                ),
+                record(
+                    &me,
+                    11,
+                    InvitePolicy::Open {
+                        expires_at: Some(now),
+                        label: None,
+                        max_uses: None,
+                    },
+                ),
+                record(&me, 12, InvitePolicy::Revoked),
+                record(&owner, 13, InvitePolicy::Revoked),
+            ]),
+            ..Default::default()
+        };
+
+        let invites =
+            collect_issued_invites(&state, me.verifying_key().into(), &HashMap::new(), now);
+        let statuses: Vec<_> = invites.iter().map(|invite| invite.status).collect();
+        assert_eq!(statuses, ["pending", "expired", "revoked"]);
+        assert_eq!(invites[0].label.as_deref(), Some("forum"));
+        assert!(invites[2].expires_at_secs.is_none());
+    }
+}
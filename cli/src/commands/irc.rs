@@ -0,0 +1,1134 @@
+use crate::api::ApiClient;
+use crate::commands::dm;
+use crate::commands::member::members_json;
+use crate::commands::message::parse_message_id;
+use crate::watch::{watch_rooms, RoomEvent, EVENT_BUFFER};
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use ed25519_dalek::VerifyingKey;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+
+/// The server name in replies and the host part of every member's mask.
+const SERVER: &str = "river";
+
+/// Longest text put in one PRIVMSG, in bytes, leaving room for the prefix
+/// and target within IRC's 512-byte line.
+const TEXT_CHUNK: usize = 350;
+
+/// Run a local IRC server on which every room you are in is a channel:
+/// channel messages post to the room, private messages are DMs, and the
+/// room's messages and DMs to you arrive live.
+#[derive(Args)]
+pub struct IrcGatewayArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:6667")]
+    pub listen: SocketAddr,
+    /// Server password clients must send (PASS); defaults to the
+    /// `riverctl serve` token in `serve.token`
+    #[arg(long, env = "RIVERCTL_IRC_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+    /// Allow a non-loopback --listen address. IRC here is plain text, so the
+    /// password and every message cross the network in the clear.
+    #[arg(long)]
+    pub allow_remote: bool,
+    /// Seconds between full re-reads of every room, which pick up a change
+    /// whose notification was missed
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+    pub resync_secs: u64,
+}
+
+pub async fn execute(args: IrcGatewayArgs, api: ApiClient) -> Result<()> {
+    if !args.listen.ip().is_loopback() && !args.allow_remote {
+        return Err(anyhow!(
+            "Refusing to listen on {}: IRC here is plain text. Pass --allow-remote if that \
+             network is trusted.",
+            args.listen
+        ));
+    }
+    let password = match args.password {
+        Some(password) if password.trim().is_empty() => {
+            return Err(anyhow!("The IRC password is empty"))
+        }
+        Some(password) => password,
+        None => crate::commands::serve::load_or_create_token(api.storage().data_dir())?,
+    };
+
+    let api = Arc::new(api);
+    let (events, _) = broadcast::channel(EVENT_BUFFER);
+    // IRC cannot join new rooms, so nothing is ever sent on this.
+    let (_joined, joined_rx) = mpsc::unbounded_channel();
+    let gateway = Arc::new(Gateway {
+        api: api.clone(),
+        password,
+        events: events.clone(),
+    });
+
+    let listener = TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("failed to listen on {}", args.listen))?;
+    eprintln!(
+        "IRC gateway listening on {} (Ctrl+C to stop)",
+        listener.local_addr()?
+    );
+
+    tokio::select! {
+        accepted = accept_clients(listener, gateway) => accepted,
+        watched = watch_rooms(&api, &events, joined_rx, Duration::from_secs(args.resync_secs)) => {
+            watched
+        }
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+/// Shared by every client connection.
+struct Gateway {
+    api: Arc<ApiClient>,
+    password: String,
+    events: broadcast::Sender<Arc<RoomEvent>>,
+}
+
+async fn accept_clients(listener: TcpListener, gateway: Arc<Gateway>) -> Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let gateway = gateway.clone();
+        tokio::spawn(async move {
+            if let Err(e) = Session::run(gateway, stream).await {
+                tracing::debug!("IRC client {}: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// One IRC client connection.
+struct Session {
+    gateway: Arc<Gateway>,
+    writer: OwnedWriteHalf,
+    nick: Option<String>,
+    user: bool,
+    password_ok: bool,
+    /// Between `CAP LS`/`CAP REQ` and `CAP END` registration waits.
+    negotiating: bool,
+    /// IRCv3 `message-tags`: message IDs go out as `msgid` and a
+    /// `+draft/reply` tag on a channel message makes it a reply.
+    tags: bool,
+    directory: Option<Directory>,
+    events: Option<broadcast::Receiver<Arc<RoomEvent>>>,
+}
+
+impl Session {
+    async fn run(gateway: Arc<Gateway>, stream: TcpStream) -> Result<()> {
+        let (reader, writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut session = Session {
+            gateway,
+            writer,
+            nick: None,
+            user: false,
+            password_ok: false,
+            negotiating: false,
+            tags: false,
+            directory: None,
+            events: None,
+        };
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Some(line) = line? else { return Ok(()) };
+                    let Some(message) = Message::parse(&line) else { continue };
+                    if !session.handle(message).await? {
+                        return Ok(());
+                    }
+                }
+                event = next_event(&mut session.events) => match event {
+                    Ok(event) => session.relay(&event).await?,
+                    Err(RecvError::Lagged(missed)) => {
+                        let notice = format!("Missed {missed} room events; some messages are not shown");
+                        session.server_notice(&notice).await?;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            }
+        }
+    }
+
+    async fn send(&mut self, line: String) -> Result<()> {
+        self.writer.write_all(line.as_bytes()).await?;
+        self.writer.write_all(b"\r\n").await?;
+        Ok(())
+    }
+
+    /// A numeric reply to this client.
+    async fn reply(&mut self, numeric: &str, params: &[&str]) -> Result<()> {
+        let nick = self.nick.clone().unwrap_or_else(|| "*".to_string());
+        let mut all = vec![nick.as_str()];
+        all.extend_from_slice(params);
+        self.send(format_line(Some(SERVER), numeric, &all)).await
+    }
+
+    async fn server_notice(&mut self, text: &str) -> Result<()> {
+        let nick = self.nick.clone().unwrap_or_else(|| "*".to_string());
+        self.send(format_line(Some(SERVER), "NOTICE", &[&nick, text]))
+            .await
+    }
+
+    /// Handle one client line; `false` ends the session.
+    async fn handle(&mut self, message: Message) -> Result<bool> {
+        let params: Vec<&str> = message.params.iter().map(String::as_str).collect();
+        match (message.command.as_str(), params.as_slice()) {
+            ("CAP", [sub, rest @ ..]) => self.cap(&sub.to_ascii_uppercase(), rest).await?,
+            ("PASS", [password, ..]) => {
+                self.password_ok = tokens_match(password, &self.gateway.password)
+            }
+            ("NICK", [nick, ..]) => self.change_nick(nick).await?,
+            ("USER", [_, ..]) => {
+                self.user = true;
+                self.try_register().await?;
+            }
+            ("PING", [token, ..]) => {
+                let token = token.to_string();
+                self.send(format_line(Some(SERVER), "PONG", &[SERVER, &token]))
+                    .await?
+            }
+            ("PONG", _) => {}
+            ("QUIT", _) => {
+                self.send("ERROR :Closing link".to_string()).await?;
+                return Ok(false);
+            }
+            (_, _) if self.directory.is_none() => {
+                self.reply("451", &["You have not registered"]).await?
+            }
+            ("JOIN", [channels, ..]) => {
+                for channel in channels.split(',') {
+                    self.join(channel).await?;
+                }
+            }
+            ("PART", [channels, ..]) => {
+                for channel in channels.split(',') {
+                    self.part(channel).await?;
+                }
+            }
+            ("PRIVMSG", [target, text]) => {
+                let reply_to = message.tags.get("+draft/reply").cloned();
+                self.privmsg(target, text, reply_to).await?
+            }
+            ("NOTICE", _) => {}
+            ("NAMES", [channel, ..]) => self.names(channel).await?,
+            ("TOPIC", [channel, ..]) => self.topic(channel).await?,
+            ("LIST", _) => self.list().await?,
+            ("WHO", [mask, ..]) => self.who(mask).await?,
+            ("WHOIS", [.., nick]) => self.whois(nick).await?,
+            ("MODE", [target, rest @ ..]) => self.mode(target, rest).await?,
+            ("AWAY", _) => {}
+            ("PRIVMSG" | "JOIN" | "PART" | "NAMES" | "TOPIC" | "WHO" | "WHOIS" | "MODE", _) => {
+                let command = message.command.clone();
+                self.reply("461", &[&command, "Not enough parameters"])
+                    .await?
+            }
+            (command, _) => {
+                let command = command.to_string();
+                self.reply("421", &[&command, "Unknown command"]).await?
+            }
+        }
+        Ok(true)
+    }
+
+    async fn cap(&mut self, sub: &str, params: &[&str]) -> Result<()> {
+        match sub {
+            "LS" => {
+                self.negotiating = true;
+                self.send(format_line(
+                    Some(SERVER),
+                    "CAP",
+                    &["*", "LS", "message-tags"],
+                ))
+                .await
+            }
+            "REQ" => {
+                self.negotiating = true;
+                let requested = params.first().copied().unwrap_or_default();
+                let all_known = requested
+                    .split_whitespace()
+                    .all(|cap| cap == "message-tags" || cap == "-message-tags");
+                if all_known {
+                    self.tags = requested
+                        .split_whitespace()
+                        .any(|cap| cap == "message-tags");
+                }
+                let answer = if all_known { "ACK" } else { "NAK" };
+                self.send(format_line(Some(SERVER), "CAP", &["*", answer, requested]))
+                    .await
+            }
+            "LIST" => {
+                let enabled = if self.tags { "message-tags" } else { "" };
+                self.send(format_line(Some(SERVER), "CAP", &["*", "LIST", enabled]))
+                    .await
+            }
+            "END" => {
+                self.negotiating = false;
+                self.try_register().await
+            }
+            _ => self.reply("410", &[sub, "Invalid CAP command"]).await,
+        }
+    }
+
+    async fn change_nick(&mut self, nick: &str) -> Result<()> {
+        if !is_valid_nick(nick) {
+            return self.reply("432", &[nick, "Erroneous nickname"]).await;
+        }
+        let Some(directory) = &self.directory else {
+            self.nick = Some(nick.to_string());
+            return self.try_register().await;
+        };
+        if directory.member_by_nick(nick).is_some() {
+            return self
+                .reply("433", &[nick, "Nickname is already in use"])
+                .await;
+        }
+        let old = self.nick.replace(nick.to_string()).unwrap_or_default();
+        self.send(format_line(
+            Some(&format!("{old}!{old}@{SERVER}")),
+            "NICK",
+            &[nick],
+        ))
+        .await
+    }
+
+    /// Welcome the client once it has sent NICK, USER and, if negotiating
+    /// capabilities, CAP END; then join it to every room.
+    async fn try_register(&mut self) -> Result<()> {
+        if self.directory.is_some() || !self.user || self.negotiating {
+            return Ok(());
+        }
+        let Some(nick) = self.nick.clone() else {
+            return Ok(());
+        };
+        if !self.password_ok {
+            self.reply("464", &["Password incorrect"]).await?;
+            return Err(anyhow!("wrong or missing password"));
+        }
+
+        let directory = Directory::load(&self.gateway.api, &nick).await?;
+        self.events = Some(self.gateway.events.subscribe());
+        let version = format!("riverctl-{}", env!("CARGO_PKG_VERSION"));
+        self.reply("001", &[&format!("Welcome to River, {nick}")])
+            .await?;
+        self.reply(
+            "002",
+            &[&format!("Your host is {SERVER}, running {version}")],
+        )
+        .await?;
+        self.reply("003", &["This server relays your River rooms"])
+            .await?;
+        self.reply("004", &[SERVER, &version, "i", "nt"]).await?;
+        self.reply(
+            "005",
+            &[
+                "CHANTYPES=#",
+                "CASEMAPPING=ascii",
+                "NETWORK=River",
+                "are supported by this server",
+            ],
+        )
+        .await?;
+        self.reply("422", &["MOTD File is missing"]).await?;
+
+        let channels: Vec<String> = directory.channels.iter().map(|c| c.name.clone()).collect();
+        self.directory = Some(directory);
+        for channel in channels {
+            self.join(&channel).await?;
+        }
+        Ok(())
+    }
+
+    fn channel_index(&self, name: &str) -> Option<usize> {
+        self.directory.as_ref()?.channel_index(name)
+    }
+
+    async fn join(&mut self, name: &str) -> Result<()> {
+        let Some(index) = self.channel_index(name) else {
+            return self.reply("403", &[name, "No such channel"]).await;
+        };
+        let directory = self.directory.as_mut().expect("registered");
+        directory.channels[index].joined = true;
+        let name = directory.channels[index].name.clone();
+        let mask = self.mask();
+        self.send(format_line(Some(&mask), "JOIN", &[&name]))
+            .await?;
+        self.topic(&name).await?;
+        self.names(&name).await
+    }
+
+    async fn part(&mut self, name: &str) -> Result<()> {
+        let Some(index) = self.channel_index(name) else {
+            return self.reply("403", &[name, "No such channel"]).await;
+        };
+        let directory = self.directory.as_mut().expect("registered");
+        directory.channels[index].joined = false;
+        let name = directory.channels[index].name.clone();
+        let mask = self.mask();
+        self.send(format_line(Some(&mask), "PART", &[&name])).await
+    }
+
+    async fn topic(&mut self, name: &str) -> Result<()> {
+        let Some(index) = self.channel_index(name) else {
+            return self.reply("403", &[name, "No such channel"]).await;
+        };
+        let channel = &self.directory.as_ref().expect("registered").channels[index];
+        let (name, topic) = (channel.name.clone(), channel.topic.clone());
+        self.reply("332", &[&name, &topic]).await
+    }
+
+    async fn names(&mut self, name: &str) -> Result<()> {
+        let Some(index) = self.channel_index(name) else {
+            return self.reply("366", &[name, "End of /NAMES list"]).await;
+        };
+        let directory = self.directory.as_ref().expect("registered");
+        let name = directory.channels[index].name.clone();
+        let mut nicks = directory.nicks_in(index);
+        nicks.push(self.nick.clone().unwrap_or_default());
+        for chunk in chunk_words(&nicks, TEXT_CHUNK) {
+            self.reply("353", &["=", &name, &chunk]).await?;
+        }
+        self.reply("366", &[&name, "End of /NAMES list"]).await
+    }
+
+    async fn list(&mut self) -> Result<()> {
+        let rows: Vec<(String, String, String)> = {
+            let directory = self.directory.as_ref().expect("registered");
+            directory
+                .channels
+                .iter()
+                .enumerate()
+                .map(|(index, channel)| {
+                    let count = directory.nicks_in(index).len() + 1;
+                    (
+                        channel.name.clone(),
+                        count.to_string(),
+                        channel.topic.clone(),
+                    )
+                })
+                .collect()
+        };
+        for (name, count, topic) in rows {
+            self.reply("322", &[&name, &count, &topic]).await?;
+        }
+        self.reply("323", &["End of /LIST"]).await
+    }
+
+    async fn who(&mut self, mask: &str) -> Result<()> {
+        if let Some(index) = self.channel_index(mask) {
+            let directory = self.directory.as_ref().expect("registered");
+            let name = directory.channels[index].name.clone();
+            let members = directory.members_in(index);
+            for (nick, member_id, nickname) in members {
+                self.reply(
+                    "352",
+                    &[
+                        &name,
+                        &member_id,
+                        SERVER,
+                        SERVER,
+                        &nick,
+                        "H",
+                        &format!("0 {nickname}"),
+                    ],
+                )
+                .await?;
+            }
+        }
+        self.reply("315", &[mask, "End of /WHO list"]).await
+    }
+
+    async fn whois(&mut self, nick: &str) -> Result<()> {
+        let found = self.directory.as_ref().and_then(|directory| {
+            let (index, member_id) = directory.member_by_nick(nick)?;
+            let nickname = directory.nicknames.get(&(index, member_id.clone()))?;
+            Some((
+                member_id.clone(),
+                nickname.clone(),
+                directory.channels[index].name.clone(),
+            ))
+        });
+        match found {
+            Some((member_id, nickname, channel)) => {
+                self.reply("311", &[nick, &member_id, SERVER, "*", &nickname])
+                    .await?;
+                self.reply("319", &[nick, &channel]).await?;
+            }
+            None => self.reply("401", &[nick, "No such nick"]).await?,
+        }
+        self.reply("318", &[nick, "End of /WHOIS list"]).await
+    }
+
+    async fn mode(&mut self, target: &str, rest: &[&str]) -> Result<()> {
+        if self.channel_index(target).is_none() {
+            let nick = self.nick.clone().unwrap_or_default();
+            if target.eq_ignore_ascii_case(&nick) {
+                return self.reply("221", &["+i"]).await;
+            }
+            return self.reply("403", &[target, "No such channel"]).await;
+        }
+        match rest.first() {
+            Some(modes) if modes.contains('b') => {
+                self.reply("368", &[target, "End of channel ban list"])
+                    .await
+            }
+            _ => self.reply("324", &[target, "+nt"]).await,
+        }
+    }
+
+    /// Post a channel message (a reply when tagged `+draft/reply`) or send a
+    /// private message as a DM. A failure comes back as a NOTICE.
+    async fn privmsg(&mut self, target: &str, text: &str, reply_to: Option<String>) -> Result<()> {
+        let Some(text) = outgoing_text(text) else {
+            return Ok(());
+        };
+        let directory = self.directory.as_ref().expect("registered");
+        let api = self.gateway.api.clone();
+        let result = if let Some(index) = directory.channel_index(target) {
+            let owner_vk = directory.channels[index].owner_vk;
+            match reply_to.map(|id| parse_message_id(&id)).transpose() {
+                Ok(Some(target_id)) => api.send_reply(&owner_vk, target_id, text).await,
+                Ok(None) => api.send_message(&owner_vk, text).await,
+                Err(e) => Err(e),
+            }
+        } else if let Some((index, member_id)) = directory.member_by_nick(target) {
+            let owner_vk = directory.channels[index].owner_vk;
+            let member_id = member_id.clone();
+            dm::send_text_dm(&api, &owner_vk, &member_id, &text)
+                .await
+                .map(|_| ())
+        } else if target.starts_with('#') {
+            return self.reply("403", &[target, "No such channel"]).await;
+        } else {
+            return self.reply("401", &[target, "No such nick"]).await;
+        };
+        if let Err(e) = result {
+            let nick = self.nick.clone().unwrap_or_default();
+            let to = if target.starts_with('#') {
+                target
+            } else {
+                &nick
+            };
+            let text = format!("Not sent: {e}");
+            self.send(format_line(Some(SERVER), "NOTICE", &[to, &text]))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn relay(&mut self, event: &RoomEvent) -> Result<()> {
+        let nick = self.nick.clone().unwrap_or_default();
+        let tags = self.tags;
+        let lines = match &mut self.directory {
+            Some(directory) => directory.render(event, &nick, tags),
+            None => Vec::new(),
+        };
+        for line in lines {
+            self.send(line).await?;
+        }
+        Ok(())
+    }
+
+    fn mask(&self) -> String {
+        let nick = self.nick.clone().unwrap_or_default();
+        format!("{nick}!{nick}@{SERVER}")
+    }
+}
+
+async fn next_event(
+    events: &mut Option<broadcast::Receiver<Arc<RoomEvent>>>,
+) -> Result<Arc<RoomEvent>, RecvError> {
+    match events {
+        Some(events) => events.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Same constant-time comparison as the HTTP gateway's token check.
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// One channel per room.
+struct Channel {
+    name: String,
+    owner_vk: VerifyingKey,
+    /// The member we post as, whose messages are not echoed back.
+    self_id: String,
+    topic: String,
+    /// Whether the client is in it (it can PART and JOIN again).
+    joined: bool,
+}
+
+/// The client's view of its rooms: channel names, and an IRC nick for every
+/// member of every room. A nick names one member of one room, so it is also
+/// the target of a private message (a DM in that room).
+struct Directory {
+    channels: Vec<Channel>,
+    /// Lowercased nick to (channel index, member ID).
+    by_nick: HashMap<String, (usize, String)>,
+    nicks: HashMap<(usize, String), String>,
+    nicknames: HashMap<(usize, String), String>,
+}
+
+impl Directory {
+    async fn load(api: &ApiClient, own_nick: &str) -> Result<Self> {
+        let mut directory = Directory::new();
+        for room in api.list_rooms().await? {
+            let mut room_state = match api.get_room(&room.owner_vk, false).await {
+                Ok(room_state) => room_state,
+                Err(e) => {
+                    tracing::warn!("Failed to read room {}: {}", room.name, e);
+                    continue;
+                }
+            };
+            let secrets = api.room_display_secrets(&room.owner_vk, &mut room_state);
+            let members: Vec<(String, String)> =
+                members_json(&room_state, &room.owner_vk, &secrets)
+                    .iter()
+                    .filter_map(|member| {
+                        Some((
+                            member["member_id"].as_str()?.to_string(),
+                            member["nickname"].as_str()?.to_string(),
+                        ))
+                    })
+                    .collect();
+            directory.add_channel(
+                &room.name,
+                room.owner_vk,
+                room.self_identity.member_id.to_string(),
+                &members,
+                own_nick,
+            );
+        }
+        Ok(directory)
+    }
+
+    fn new() -> Self {
+        Directory {
+            channels: Vec::new(),
+            by_nick: HashMap::new(),
+            nicks: HashMap::new(),
+            nicknames: HashMap::new(),
+        }
+    }
+
+    fn add_channel(
+        &mut self,
+        room_name: &str,
+        owner_vk: VerifyingKey,
+        self_id: String,
+        members: &[(String, String)],
+        own_nick: &str,
+    ) {
+        let room_key = bs58::encode(owner_vk.as_bytes()).into_string();
+        let base = channel_name(room_name, &room_key);
+        let name = if self.channel_index(&base).is_some() {
+            format!("{base}-{}", &room_key[..6])
+        } else {
+            base
+        };
+        let index = self.channels.len();
+        self.channels.push(Channel {
+            name,
+            owner_vk,
+            topic: room_name.to_string(),
+            self_id: self_id.clone(),
+            joined: false,
+        });
+        for (member_id, nickname) in members {
+            if *member_id != self_id {
+                self.nick_for(index, member_id, nickname, own_nick);
+            }
+        }
+    }
+
+    fn channel_index(&self, name: &str) -> Option<usize> {
+        self.channels
+            .iter()
+            .position(|channel| channel.name.eq_ignore_ascii_case(name))
+    }
+
+    fn member_by_nick(&self, nick: &str) -> Option<(usize, &String)> {
+        self.by_nick
+            .get(&nick.to_ascii_lowercase())
+            .map(|(index, member_id)| (*index, member_id))
+    }
+
+    /// The nick of `member_id` in channel `index`, assigned from `nickname`
+    /// the first time it is seen. A nick already taken gets the member ID
+    /// appended, then the channel number.
+    fn nick_for(
+        &mut self,
+        index: usize,
+        member_id: &str,
+        nickname: &str,
+        own_nick: &str,
+    ) -> String {
+        let key = (index, member_id.to_string());
+        if let Some(nick) = self.nicks.get(&key) {
+            return nick.clone();
+        }
+        let base = irc_nick(nickname, member_id);
+        let nick = [
+            base.clone(),
+            format!("{base}|{member_id}"),
+            format!("{base}|{member_id}-{index}"),
+        ]
+        .into_iter()
+        .find(|nick| {
+            !self.by_nick.contains_key(&nick.to_ascii_lowercase())
+                && !nick.eq_ignore_ascii_case(own_nick)
+        })
+        .unwrap_or_else(|| format!("{base}|{member_id}-{index}"));
+        self.by_nick
+            .insert(nick.to_ascii_lowercase(), (index, member_id.to_string()));
+        self.nicks.insert(key.clone(), nick.clone());
+        self.nicknames.insert(key, nickname.to_string());
+        nick
+    }
+
+    fn nicks_in(&self, index: usize) -> Vec<String> {
+        let mut nicks: Vec<String> = self
+            .nicks
+            .iter()
+            .filter(|((i, _), _)| *i == index)
+            .map(|(_, nick)| nick.clone())
+            .collect();
+        nicks.sort();
+        nicks
+    }
+
+    /// (nick, member ID, River nickname) of everyone in channel `index`.
+    fn members_in(&self, index: usize) -> Vec<(String, String, String)> {
+        let mut members: Vec<_> = self
+            .nicks
+            .iter()
+            .filter(|((i, _), _)| *i == index)
+            .map(|(key, nick)| {
+                let nickname = self.nicknames.get(key).cloned().unwrap_or_default();
+                (nick.clone(), key.1.clone(), nickname)
+            })
+            .collect();
+        members.sort();
+        members
+    }
+
+    /// The IRC lines that show `event` to a client using `own_nick`. Our own
+    /// messages are not echoed, as on any IRC server, and nothing is shown
+    /// for a channel the client has left.
+    fn render(&mut self, event: &RoomEvent, own_nick: &str, tags: bool) -> Vec<String> {
+        let Some(index) = self
+            .channels
+            .iter()
+            .position(|channel| channel.owner_vk == event.room)
+        else {
+            return Vec::new();
+        };
+        let event = &event.event;
+        let field = |name: &str| event[name].as_str().unwrap_or_default().to_string();
+        let kind = field("type");
+        let (author, nickname) = if kind == "dm" {
+            (field("counterparty"), field("counterparty_nickname"))
+        } else {
+            (field("author"), field("nickname"))
+        };
+        // Reactions name the reacted-to message's author, so ours are kept.
+        let own = author == self.channels[index].self_id;
+        if author.is_empty() || (own && kind != "reaction") {
+            return Vec::new();
+        }
+        let channel = self.channels[index].name.clone();
+        if kind != "dm" && !self.channels[index].joined {
+            return Vec::new();
+        }
+        let nick = if own {
+            own_nick.to_string()
+        } else {
+            self.nick_for(index, &author, &nickname, own_nick)
+        };
+        let mut mask = format!("{nick}!{author}@{SERVER}");
+
+        let (target, command, text, tag) = match kind.as_str() {
+            "message" => {
+                let mut text = field("content");
+                let reply = &event["reply_to"];
+                if let Some(quoted) = reply["author"].as_str() {
+                    let preview = reply["preview"].as_str().unwrap_or_default();
+                    text = format!("[reply to {quoted}: {preview}] {text}");
+                }
+                let mut tag = format!("msgid={}", field("message_id"));
+                if let Some(parent) = reply["message_id"].as_str() {
+                    tag.push_str(&format!(";+draft/reply={parent}"));
+                }
+                (channel, "PRIVMSG", text, Some(tag))
+            }
+            "edit" => (
+                channel,
+                "PRIVMSG",
+                format!("[edit] {}", field("content")),
+                None,
+            ),
+            "delete" => (
+                channel,
+                "NOTICE",
+                "[deleted] (message deleted)".into(),
+                None,
+            ),
+            "reaction" => {
+                let reactions = event["reactions"]
+                    .as_object()
+                    .map(|reactions| {
+                        let mut parts: Vec<String> = reactions
+                            .iter()
+                            .map(|(emoji, count)| format!("{emoji}×{count}"))
+                            .collect();
+                        parts.sort();
+                        parts.join(" ")
+                    })
+                    .filter(|parts| !parts.is_empty())
+                    .unwrap_or_else(|| "(none)".to_string());
+                // From the server: who reacted is not in the event.
+                mask = SERVER.to_string();
+                let text = format!(
+                    "[reaction] on {nick}'s message {}: {reactions}",
+                    field("message_id")
+                );
+                (channel, "NOTICE", text, None)
+            }
+            "dm" => (own_nick.to_string(), "PRIVMSG", field("body"), None),
+            _ => return Vec::new(),
+        };
+        let prefix = match (&tag, tags) {
+            (Some(tag), true) => format!("@{tag} "),
+            _ => String::new(),
+        };
+        incoming_lines(&text)
+            .into_iter()
+            .map(|chunk| {
+                format!(
+                    "{prefix}{}",
+                    format_line(Some(&mask), command, &[&target, &chunk])
+                )
+            })
+            .collect()
+    }
+}
+
+/// `#` and the room name in lowercase, with spaces as dashes and the
+/// characters IRC reserves removed; the room key's start if nothing is left.
+fn channel_name(room_name: &str, room_key: &str) -> String {
+    let name: String = room_name
+        .trim()
+        .chars()
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .filter(|c| !c.is_control() && !matches!(c, ',' | ':' | '#'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    if name.is_empty() {
+        format!("#{}", &room_key[..8])
+    } else {
+        format!("#{name}")
+    }
+}
+
+/// A nick made of the nickname's letters, digits and the punctuation IRC
+/// allows in nicks; the member ID if that leaves nothing.
+fn irc_nick(nickname: &str, member_id: &str) -> String {
+    let nick: String = nickname
+        .trim()
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .filter(|c| c.is_ascii_alphanumeric() || "_-[]\\`^{}|".contains(*c))
+        .take(30)
+        .collect();
+    match nick.chars().next() {
+        None => member_id.to_string(),
+        Some(first) if first.is_ascii_digit() || first == '-' => format!("_{nick}"),
+        Some(_) => nick,
+    }
+}
+
+fn is_valid_nick(nick: &str) -> bool {
+    !nick.is_empty()
+        && !nick.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '#')
+        && nick
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-[]\\`^{}|".contains(c))
+}
+
+/// The text to post for a PRIVMSG: CTCP ACTION (`/me`) becomes italics,
+/// other CTCP requests are not posted.
+fn outgoing_text(text: &str) -> Option<String> {
+    match text.strip_prefix('\x01') {
+        Some(ctcp) => ctcp
+            .trim_end_matches('\x01')
+            .strip_prefix("ACTION ")
+            .map(|action| format!("*{action}*")),
+        None => Some(text.to_string()),
+    }
+}
+
+/// River text as IRC message bodies: one per line, control characters (CTCP
+/// and formatting codes among them) removed, long lines split.
+fn incoming_lines(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    for line in text.lines() {
+        let clean: String = line.chars().filter(|c| !c.is_control()).collect();
+        let mut chunk = String::new();
+        for c in clean.chars() {
+            if chunk.len() + c.len_utf8() > TEXT_CHUNK {
+                out.push(std::mem::take(&mut chunk));
+            }
+            chunk.push(c);
+        }
+        if !chunk.is_empty() {
+            out.push(chunk);
+        }
+    }
+    if out.is_empty() {
+        out.push(String::new());
+    }
+    out
+}
+
+/// `words` joined by spaces into lines of at most `max` bytes.
+fn chunk_words(words: &[String], max: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in words {
+        let line = lines.last_mut().expect("never empty");
+        if !line.is_empty() && line.len() + 1 + word.len() > max {
+            lines.push(word.clone());
+        } else {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+    lines
+}
+
+/// A line in IRC syntax; the last parameter always goes as the trailing one.
+fn format_line(prefix: Option<&str>, command: &str, params: &[&str]) -> String {
+    let mut line = String::new();
+    if let Some(prefix) = prefix {
+        line.push(':');
+        line.push_str(prefix);
+        line.push(' ');
+    }
+    line.push_str(command);
+    if let Some((last, middle)) = params.split_last() {
+        for param in middle {
+            line.push(' ');
+            line.push_str(param);
+        }
+        line.push_str(" :");
+        line.push_str(last);
+    }
+    line
+}
+
+/// A line from the client.
+#[derive(Debug, PartialEq)]
+struct Message {
+    tags: HashMap<String, String>,
+    command: String,
+    params: Vec<String>,
+}
+
+impl Message {
+    fn parse(line: &str) -> Option<Message> {
+        let mut rest = line.trim_end_matches(['\r', '\n']);
+        let mut tags = HashMap::new();
+        if let Some(tagged) = rest.strip_prefix('@') {
+            let (raw, after) = tagged.split_once(' ')?;
+            for tag in raw.split(';') {
+                let (name, value) = tag.split_once('=').unwrap_or((tag, ""));
+                tags.insert(name.to_string(), unescape_tag(value));
+            }
+            rest = after;
+        }
+        rest = rest.trim_start();
+        if let Some(prefixed) = rest.strip_prefix(':') {
+            rest = prefixed.split_once(' ')?.1.trim_start();
+        }
+        let (middle, trailing) = match rest.split_once(" :") {
+            Some((middle, trailing)) => (middle, Some(trailing)),
+            None => (rest, None),
+        };
+        let mut words = middle.split(' ').filter(|word| !word.is_empty());
+        let command = words.next()?.to_ascii_uppercase();
+        let mut params: Vec<String> = words.map(str::to_string).collect();
+        params.extend(trailing.map(str::to_string));
+        Some(Message {
+            tags,
+            command,
+            params,
+        })
+    }
+}
+
+fn unescape_tag(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(':') => out.push(';'),
+            Some('s') => out.push(' '),
+            Some('r') => out.push('\r'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn client_lines_parse() {
+        let message =
+            Message::parse("@+draft/reply=-42;x=a\\sb :me PRIVMSG #general :hi there\r").unwrap();
+        assert_eq!(message.command, "PRIVMSG");
+        assert_eq!(message.params, ["#general", "hi there"]);
+        assert_eq!(message.tags["+draft/reply"], "-42");
+        assert_eq!(message.tags["x"], "a b");
+
+        let message = Message::parse("nick alice").unwrap();
+        assert_eq!(message.command, "NICK");
+        assert_eq!(message.params, ["alice"]);
+        assert!(Message::parse("").is_none());
+
+        assert_eq!(
+            format_line(Some(SERVER), "001", &["alice", "Welcome"]),
+            ":river 001 alice :Welcome"
+        );
+        assert_eq!(
+            outgoing_text("\x01ACTION waves\x01").as_deref(),
+            Some("*waves*")
+        );
+        assert_eq!(outgoing_text("\x01VERSION\x01"), None);
+    }
+
+    #[test]
+    fn names_become_channels_and_nicks() {
+        assert_eq!(channel_name("Freenet Dev", "abcdefghij"), "#freenet-dev");
+        assert_eq!(channel_name(" ,:# ", "abcdefghij"), "#abcdefgh");
+        assert_eq!(irc_nick("Ann Lee", "id"), "Ann_Lee");
+        assert_eq!(irc_nick("2fast", "id"), "_2fast");
+        assert_eq!(irc_nick("日本", "ABCDEFGH"), "ABCDEFGH");
+        assert!(is_valid_nick("ann|away"));
+        assert!(!is_valid_nick("#ann"));
+
+        let room = SigningKey::from_bytes(&[1; 32]).verifying_key();
+        let other = SigningKey::from_bytes(&[2; 32]).verifying_key();
+        let mut directory = Directory::new();
+        let members = [
+            ("SELF0001".to_string(), "me".to_string()),
+            ("AAAA0001".to_string(), "ann".to_string()),
+            ("BBBB0001".to_string(), "ann".to_string()),
+            ("CCCC0001".to_string(), "bob".to_string()),
+        ];
+        directory.add_channel("General", room, "SELF0001".into(), &members, "bob");
+        directory.add_channel("general", other, "SELF0002".into(), &[], "bob");
+        assert_eq!(
+            directory.channels[1].name,
+            "#general-".to_string() + &bs58::encode(other.as_bytes()).into_string()[..6]
+        );
+        assert_eq!(
+            directory.nicks_in(0),
+            ["ann", "ann|BBBB0001", "bob|CCCC0001"]
+        );
+        assert_eq!(
+            directory.member_by_nick("ANN"),
+            Some((0, &"AAAA0001".to_string()))
+        );
+    }
+
+    #[test]
+    fn events_render_as_irc_lines() {
+        let room = SigningKey::from_bytes(&[1; 32]).verifying_key();
+        let mut directory = Directory::new();
+        directory.add_channel(
+            "general",
+            room,
+            "SELF0001".into(),
+            &[("AAAA0001".into(), "ann".into())],
+            "me",
+        );
+        let event = |event: Value| RoomEvent { room, event };
+
+        let message = event(json!({
+            "type": "message", "author": "AAAA0001", "nickname": "ann",
+            "message_id": "7", "content": "line one\nline \x01two",
+            "reply_to": { "author": "bob", "message_id": "5", "preview": "hey" },
+        }));
+        // Nothing for a channel the client has not joined.
+        assert!(directory.render(&message, "me", true).is_empty());
+        directory.channels[0].joined = true;
+        assert_eq!(
+            directory.render(&message, "me", true),
+            [
+                "@msgid=7;+draft/reply=5 :ann!AAAA0001@river PRIVMSG #general :[reply to bob: hey] line one",
+                "@msgid=7;+draft/reply=5 :ann!AAAA0001@river PRIVMSG #general :line two",
+            ]
+        );
+        assert_eq!(
+            directory.render(&message, "me", false)[1],
+            ":ann!AAAA0001@river PRIVMSG #general :line two"
+        );
+
+        let own = event(json!({ "type": "message", "author": "SELF0001", "content": "mine" }));
+        assert!(directory.render(&own, "me", false).is_empty());
+
+        let dm = event(json!({
+            "type": "dm", "counterparty": "DDDD0001", "counterparty_nickname": "dee",
+            "body": "psst",
+        }));
+        assert_eq!(
+            directory.render(&dm, "me", false),
+            [":dee!DDDD0001@river PRIVMSG me :psst"]
+        );
+        assert_eq!(
+            directory.member_by_nick("dee"),
+            Some((0, &"DDDD0001".to_string()))
+        );
+
+        let reaction = event(json!({
+            "type": "reaction", "author": "SELF0001", "message_id": "9",
+            "reactions": { "👍": 2 },
+        }));
+        assert_eq!(
+            directory.render(&reaction, "me", false),
+            [":river NOTICE #general :[reaction] on me's message 9: 👍×2"]
+        );
+
+        let long = "x".repeat(TEXT_CHUNK + 1);
+        assert_eq!(incoming_lines(&long).len(), 2);
+        assert_eq!(incoming_lines(""), [""]);
+    }
+}
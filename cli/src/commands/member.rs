@@ -1,4 +1,5 @@
 use crate::api::ApiClient;
+use crate::commands::invite::format_time;
 use crate::deputies::{
     display_nickname, grant_status_line, party_label, DeputyParty, ResolveError, RoomDeputies,
 };
@@ -6,18 +7,24 @@ use crate::output::OutputFormat;
 use anyhow::{anyhow, Result};
 use clap::Subcommand;
 use colored::Colorize;
+use river_core::room_state::join_request::JoinOutcome;
 use river_core::room_state::member::MemberId;
+use river_core::room_state::ChatRoomStateV1;
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Subcommand)]
 pub enum MemberCommands {
     /// List members of a room
     List {
         /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
     },
     /// Set your nickname in a room
     SetNickname {
         /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Your new nickname
         nickname: String,
@@ -25,6 +32,7 @@ pub enum MemberCommands {
     /// Ban a member from a room
     Ban {
         /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Member ID to ban (8-character short ID from member list)
         member_id: String,
@@ -37,10 +45,77 @@ pub enum MemberCommands {
         /// Refuse if any current canonical member record deputizes this member.
         #[arg(long)]
         require_not_deputy: bool,
+        /// Why the member is banned (sealed in private rooms)
+        #[arg(long)]
+        reason: Option<String>,
+        /// Make the ban temporary, e.g. `90m`, `12h` or `7d` (default: permanent)
+        #[arg(long, value_parser = parse_ban_duration)]
+        duration: Option<Duration>,
+    },
+    /// Lift the bans on a member so they can be re-invited
+    ///
+    /// Signs a revocation for every ban on MEMBER_ID. Allowed for whoever
+    /// issued each ban, anyone above them in the invite chain, and their
+    /// deputies; only the room owner can lift the owner's bans.
+    Unban {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Banned member ID (8-character short ID from `debug bans`)
+        member_id: String,
+    },
+    /// Mute a member: they stay in the room but cannot post until unmuted
+    ///
+    /// Allowed for the room owner, anyone above MEMBER_ID in the invite chain,
+    /// and their deputies. Existing messages are kept.
+    Mute {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Member ID to mute (8-character short ID from member list)
+        member_id: String,
+        /// Make the mute temporary, e.g. `90m`, `12h` or `7d` (default: until unmuted)
+        #[arg(long, value_parser = parse_ban_duration)]
+        duration: Option<Duration>,
+    },
+    /// Lift a member's mute
+    Unmute {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Muted member ID (8-character short ID from member list)
+        member_id: String,
+    },
+    /// Block a member for yourself: hide their messages and purge their DMs
+    ///
+    /// The block is local to this identity and never published. `message`
+    /// commands stop showing MEMBER_ID's messages, and every DM from them is
+    /// folded into your inbox purge envelope now and on each later sync.
+    Block {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Member ID to block (8-character short ID from member list)
+        member_id: String,
+    },
+    /// Lift a block; DMs already purged stay purged
+    Unblock {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Blocked member ID (8-character short ID from `member blocked`)
+        member_id: String,
+    },
+    /// List the members you have blocked in a room
+    Blocked {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
     },
     /// Deputize a member so they can help moderate (ban) within your invite subtree
     Deputize {
         /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Member ID to deputize (8-character short ID from member list)
         member_id: String,
@@ -48,6 +123,7 @@ pub enum MemberCommands {
     /// Revoke a member's deputy authority (their prior bans stop enforcing)
     RevokeDeputy {
         /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Member ID whose deputy authority to revoke (8-character short ID)
         member_id: String,
@@ -62,6 +138,7 @@ pub enum MemberCommands {
     /// `member deputized-by`.
     Deputies {
         /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Member ID whose deputies to list (8-character short ID from member
         /// list). Defaults to your own identity in this room.
@@ -75,10 +152,118 @@ pub enum MemberCommands {
     /// regular member's (limited to that member's invite subtree).
     DeputizedBy {
         /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Member ID to look up (8-character short ID from member list)
         member_id: String,
     },
+    /// List join requests from people without an invitation
+    ///
+    /// Shows waiting requests with the nickname and message the requester
+    /// sent, and requesters already approved or denied who have not joined.
+    Requests {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+    },
+    /// Approve a join request, admitting the requester as invited by you
+    Approve {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Requester ID (8-character short ID from `member requests`)
+        requester_id: String,
+    },
+    /// Deny a join request; later requests from them are dropped until approved
+    Deny {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Requester ID (8-character short ID from `member requests`)
+        requester_id: String,
+    },
+}
+
+#[derive(Serialize)]
+struct JoinRequestRow {
+    requester_id: String,
+    /// `pending`, `approved` (not joined yet) or `denied`.
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nickname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requested_at_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decided_by: Option<String>,
+}
+
+/// One row per requester held in the room: waiting requests first, oldest
+/// first, then decided ones. A denied requester's request is gone, so their
+/// row has no nickname or message.
+fn collect_join_requests(room_state: &ChatRoomStateV1) -> Vec<JoinRequestRow> {
+    let unix_secs = |time: SystemTime| {
+        time.duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    };
+    let join_requests = &room_state.join_requests;
+    let mut rows: Vec<JoinRequestRow> = join_requests
+        .pending()
+        .map(|request| JoinRequestRow {
+            requester_id: request.requester_id().to_string(),
+            status: "pending",
+            nickname: Some(request.request.nickname.clone()),
+            message: Some(request.request.message.clone()),
+            requested_at_secs: Some(unix_secs(request.request.requested_at)),
+            decided_by: None,
+        })
+        .collect();
+    for decision in &join_requests.decisions {
+        let request = join_requests
+            .requests
+            .iter()
+            .find(|request| request.requester_id() == decision.requester_id());
+        rows.push(JoinRequestRow {
+            requester_id: decision.requester_id().to_string(),
+            status: match decision.decision.outcome {
+                JoinOutcome::Approved(_) => "approved",
+                JoinOutcome::Denied => "denied",
+            },
+            nickname: request.map(|r| r.request.nickname.clone()),
+            message: request.map(|r| r.request.message.clone()),
+            requested_at_secs: request.map(|r| unix_secs(r.request.requested_at)),
+            decided_by: Some(decision.decided_by.to_string()),
+        });
+    }
+    rows
+}
+
+/// Parse a `--duration` value: a positive whole number followed by `s`, `m`,
+/// `h` or `d`.
+pub(crate) fn parse_ban_duration(value: &str) -> Result<Duration, String> {
+    let usage = || format!("invalid duration '{value}': expected e.g. 90s, 30m, 12h or 7d");
+    let trimmed = value.trim();
+    let unit_at = trimmed.len() - trimmed.chars().last().map_or(0, char::len_utf8);
+    let (number, unit) = trimmed.split_at(unit_at);
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(usage()),
+    };
+    let count: u64 = number.parse().map_err(|_| usage())?;
+    if count == 0 {
+        return Err(format!(
+            "invalid duration '{value}': omit the option for no time limit"
+        ));
+    }
+    count
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(usage)
 }
 
 pub async fn execute(command: MemberCommands, api: ApiClient, format: OutputFormat) -> Result<()> {
@@ -131,6 +316,23 @@ pub async fn execute(command: MemberCommands, api: ApiClient, format: OutputForm
                 })
                 .collect();
 
+            let muted = {
+                let params = river_core::room_state::ChatRoomParametersV1 { owner: owner_vk };
+                room_state
+                    .mutes
+                    .muted_members(
+                        &room_state.members.members_by_member_id(),
+                        &room_state.member_info,
+                        &room_state.recent_messages,
+                        params.owner_id(),
+                        &owner_vk,
+                        room_state.configuration.configuration.max_members,
+                    )
+                    .into_keys()
+                    .map(|id| id.to_string())
+                    .collect::<std::collections::HashSet<_>>()
+            };
+
             match format {
                 OutputFormat::Human => {
                     if members.is_empty() {
@@ -155,18 +357,16 @@ pub async fn execute(command: MemberCommands, api: ApiClient, format: OutputForm
                                     .collect();
                                 print!("{}", format!("  deputy of: {}", names.join(", ")).yellow());
                             }
+                            if muted.contains(&party.member_id) {
+                                print!("{}", "  muted".red());
+                            }
                             println!();
                         }
                         println!();
                     }
                 }
                 OutputFormat::Json => {
-                    let json_members: Vec<_> = members
-                        .into_iter()
-                        .map(|(party, own_deputies, granted_by)| {
-                            member_list_json(&party, &own_deputies, &granted_by)
-                        })
-                        .collect();
+                    let json_members = members_json(&room_state, &owner_vk, &secrets);
                     println!("{}", serde_json::to_string_pretty(&json_members)?);
                 }
             }
@@ -217,6 +417,8 @@ pub async fn execute(command: MemberCommands, api: ApiClient, format: OutputForm
             require_exact_member_id,
             require_no_descendants,
             require_not_deputy,
+            reason,
+            duration,
         } => {
             if !matches!(format, OutputFormat::Json) {
                 eprintln!("Banning member '{}' from room: {}", member_id, room_id);
@@ -240,7 +442,7 @@ pub async fn execute(command: MemberCommands, api: ApiClient, format: OutputForm
                 require_not_deputy,
             };
             match api
-                .ban_member_with_safety(&owner_vk, &member_id, safety)
+                .ban_member_with_safety(&owner_vk, &member_id, safety, reason.as_deref(), duration)
                 .await
             {
                 Ok(()) => match format {
@@ -267,6 +469,284 @@ pub async fn execute(command: MemberCommands, api: ApiClient, format: OutputForm
             }
             Ok(())
         }
+        MemberCommands::Unban { room_id, member_id } => {
+            if !matches!(format, OutputFormat::Json) {
+                eprintln!("Unbanning member '{}' in room: {}", member_id, room_id);
+            }
+
+            let owner_key_bytes = bs58::decode(&room_id)
+                .into_vec()
+                .map_err(|e| anyhow!("Invalid room ID: {}", e))?;
+            if owner_key_bytes.len() != 32 {
+                return Err(anyhow!("Invalid room ID: expected 32 bytes"));
+            }
+            let mut key_array = [0u8; 32];
+            key_array.copy_from_slice(&owner_key_bytes);
+            let owner_vk = ed25519_dalek::VerifyingKey::from_bytes(&key_array)
+                .map_err(|e| anyhow!("Invalid room ID: {}", e))?;
+
+            match api.unban_member(&owner_vk, &member_id).await {
+                Ok(unbanned) => match format {
+                    OutputFormat::Human => {
+                        println!(
+                            "{}",
+                            format!(
+                                "Member '{}' has been unbanned and can be invited again.",
+                                unbanned
+                            )
+                            .green()
+                        );
+                    }
+                    OutputFormat::Json => {
+                        println!(
+                            "{}",
+                            serde_json::json!({
+                                "success": true,
+                                "unbanned_member_id": unbanned.to_string(),
+                            })
+                        );
+                    }
+                },
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    return Err(e);
+                }
+            }
+            Ok(())
+        }
+        MemberCommands::Mute {
+            room_id,
+            member_id,
+            duration,
+        } => {
+            if !matches!(format, OutputFormat::Json) {
+                eprintln!("Muting member '{}' in room: {}", member_id, room_id);
+            }
+            let owner_vk = parse_room_id(&room_id)?;
+            match api.mute_member(&owner_vk, &member_id, duration).await {
+                Ok(muted) => match format {
+                    OutputFormat::Human => {
+                        println!("{}", format!("Member '{}' has been muted.", muted).green());
+                    }
+                    OutputFormat::Json => {
+                        println!(
+                            "{}",
+                            serde_json::json!({
+                                "success": true,
+                                "muted_member_id": muted.to_string(),
+                                "duration_secs": duration.map(|d| d.as_secs()),
+                            })
+                        );
+                    }
+                },
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    return Err(e);
+                }
+            }
+            Ok(())
+        }
+        MemberCommands::Unmute { room_id, member_id } => {
+            if !matches!(format, OutputFormat::Json) {
+                eprintln!("Unmuting member '{}' in room: {}", member_id, room_id);
+            }
+            let owner_vk = parse_room_id(&room_id)?;
+            match api.unmute_member(&owner_vk, &member_id).await {
+                Ok(unmuted) => match format {
+                    OutputFormat::Human => {
+                        println!(
+                            "{}",
+                            format!("Member '{}' has been unmuted.", unmuted).green()
+                        );
+                    }
+                    OutputFormat::Json => {
+                        println!(
+                            "{}",
+                            serde_json::json!({
+                                "success": true,
+                                "unmuted_member_id": unmuted.to_string(),
+                            })
+                        );
+                    }
+                },
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    return Err(e);
+                }
+            }
+            Ok(())
+        }
+        MemberCommands::Block { room_id, member_id } => {
+            let owner_vk = parse_room_id(&room_id)?;
+            let self_id = own_member_id(&api, &owner_vk)?;
+            let mut room_state = api.get_room(&owner_vk, false).await?;
+            let secrets = api.room_display_secrets(&owner_vk, &mut room_state);
+            let deputies = RoomDeputies::new(&room_state, &owner_vk, &secrets);
+            let blocked = resolve_or_explain(&deputies, &member_id)?;
+            if blocked == self_id {
+                return Err(anyhow!("You cannot block yourself."));
+            }
+            let blocked_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let added = api
+                .storage()
+                .block_member(&owner_vk, self_id, blocked, blocked_at)?;
+
+            // Purge what they already sent rather than waiting for the next
+            // read. The block stands if that fails; their DMs stay hidden.
+            if let Some((signing_key, _, _)) = api.storage().get_room(&owner_vk)? {
+                crate::commands::dm::purge_blocked_senders(&api, &owner_vk, &signing_key)
+                    .await
+                    .map_err(|e| {
+                        anyhow!(
+                            "Blocked {}; their messages are hidden, but their DMs could \
+                             not be purged: {}",
+                            party_label(&deputies.party(blocked)),
+                            e
+                        )
+                    })?;
+                crate::commands::dm::sync_inbox(&api, &owner_vk, &signing_key, &room_state).await?;
+            }
+
+            match format {
+                OutputFormat::Human => {
+                    let who = party_label(&deputies.party(blocked));
+                    if added {
+                        println!(
+                            "{}",
+                            format!(
+                                "Blocked {}. Their messages are hidden and their DMs purged.",
+                                who
+                            )
+                            .green()
+                        );
+                    } else {
+                        println!("{} is already blocked.", who);
+                    }
+                }
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::json!({
+                        "success": true,
+                        "blocked_member_id": blocked.to_string(),
+                        "already_blocked": !added,
+                    })
+                ),
+            }
+            Ok(())
+        }
+        MemberCommands::Unblock { room_id, member_id } => {
+            let owner_vk = parse_room_id(&room_id)?;
+            let self_id = own_member_id(&api, &owner_vk)?;
+            // Resolved against the block list, so a member who has since
+            // left the room can still be unblocked.
+            let blocked = api.storage().blocked_members(&owner_vk, self_id)?;
+            let unblocked = blocked
+                .into_iter()
+                .find(|id| id.to_string().eq_ignore_ascii_case(&member_id))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Member '{}' is not blocked. Use 'member blocked' to see who is.",
+                        member_id
+                    )
+                })?;
+            api.storage()
+                .unblock_member(&owner_vk, self_id, unblocked)?;
+            match format {
+                OutputFormat::Human => println!(
+                    "{}",
+                    format!("Member '{}' is no longer blocked.", unblocked).green()
+                ),
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::json!({
+                        "success": true,
+                        "unblocked_member_id": unblocked.to_string(),
+                    })
+                ),
+            }
+            Ok(())
+        }
+        MemberCommands::Blocked { room_id } => {
+            let owner_vk = parse_room_id(&room_id)?;
+            let self_id = own_member_id(&api, &owner_vk)?;
+            let mut blocked: Vec<String> = api
+                .storage()
+                .blocked_members(&owner_vk, self_id)?
+                .iter()
+                .map(|id| id.to_string())
+                .collect();
+            blocked.sort();
+            match format {
+                OutputFormat::Human => {
+                    if blocked.is_empty() {
+                        println!("You have not blocked anyone in this room.");
+                    } else {
+                        println!("\nBlocked ({}):\n", blocked.len());
+                        for id in &blocked {
+                            println!("  {}", id);
+                        }
+                        println!();
+                    }
+                }
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({
+                        "room_id": room_id,
+                        "blocked_member_ids": blocked,
+                    }))?
+                ),
+            }
+            Ok(())
+        }
+        MemberCommands::Requests { room_id } => {
+            let owner_vk = parse_room_id(&room_id)?;
+            let room_state = api.get_room(&owner_vk, false).await?;
+            let rows = collect_join_requests(&room_state);
+            match format {
+                OutputFormat::Human => {
+                    if rows.is_empty() {
+                        println!("No join requests.");
+                    } else {
+                        println!("\n{} join request(s):\n", rows.len());
+                        for row in &rows {
+                            // Nickname and message come from a non-member;
+                            // escape them like any nickname.
+                            let mut line = format!("  {}  {}", row.requester_id, row.status);
+                            if let Some(nickname) = &row.nickname {
+                                line.push_str(&format!("  {}", display_nickname(nickname)));
+                            }
+                            if let Some(requested_at) = row.requested_at_secs {
+                                line.push_str(&format!("  {}", format_time(requested_at)));
+                            }
+                            if let Some(decided_by) = &row.decided_by {
+                                line.push_str(&format!("  by {}", decided_by));
+                            }
+                            println!("{}", line);
+                            if let Some(message) = row.message.as_deref().filter(|m| !m.is_empty())
+                            {
+                                println!("      {}", display_nickname(message));
+                            }
+                        }
+                        println!();
+                    }
+                }
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&rows)?);
+                }
+            }
+            Ok(())
+        }
+        MemberCommands::Approve {
+            room_id,
+            requester_id,
+        } => answer_join_request(&api, format, &room_id, &requester_id, true).await,
+        MemberCommands::Deny {
+            room_id,
+            requester_id,
+        } => answer_join_request(&api, format, &room_id, &requester_id, false).await,
         MemberCommands::Deputize { room_id, member_id } => {
             if !matches!(format, OutputFormat::Json) {
                 eprintln!("Deputizing member '{}' in room: {}", member_id, room_id);
@@ -493,6 +973,24 @@ fn member_list_json(
     })
 }
 
+/// The `member list --format json` rows: one per member, deduplicated as
+/// the human listing is.
+pub(crate) fn members_json(
+    room_state: &ChatRoomStateV1,
+    owner_vk: &ed25519_dalek::VerifyingKey,
+    secrets: &std::collections::HashMap<u32, [u8; 32]>,
+) -> Vec<serde_json::Value> {
+    let deputies = RoomDeputies::new(room_state, owner_vk, secrets);
+    let deputized_by = deputies.deputizers_by_deputy();
+    deputies
+        .members_with_info()
+        .map(|id| {
+            let granted_by = deputized_by.get(&id).cloned().unwrap_or_default();
+            member_list_json(&deputies.party(id), deputies.deputies_of(id), &granted_by)
+        })
+        .collect()
+}
+
 /// `"1 grant"` / `"3 grants"`, so counted output reads as English rather than
 /// as the `N thing(s)` form.
 fn count(n: usize, noun: &str) -> String {
@@ -542,6 +1040,52 @@ fn own_member_id(
         })
 }
 
+/// `member approve` / `member deny`.
+async fn answer_join_request(
+    api: &ApiClient,
+    format: OutputFormat,
+    room_id: &str,
+    requester_id: &str,
+    approve: bool,
+) -> Result<()> {
+    let owner_vk = parse_room_id(room_id)?;
+    let result = if approve {
+        api.approve_join_request(&owner_vk, requester_id).await
+    } else {
+        api.deny_join_request(&owner_vk, requester_id).await
+    };
+    match result {
+        Ok(requester) => match format {
+            OutputFormat::Human => {
+                let text = if approve {
+                    format!(
+                        "Approved {}. They join the next time they run 'room knock'.",
+                        requester
+                    )
+                } else {
+                    format!("Denied {}.", requester)
+                };
+                println!("{}", text.green());
+            }
+            OutputFormat::Json => {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "success": true,
+                        "requester_id": requester.to_string(),
+                        "decision": if approve { "approved" } else { "denied" },
+                    })
+                );
+            }
+        },
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red(), e);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
 /// Decode a base58 room id (owner verifying key) into a `VerifyingKey`.
 fn parse_room_id(room_id: &str) -> Result<ed25519_dalek::VerifyingKey> {
     let owner_key_bytes = bs58::decode(room_id)
@@ -593,17 +1137,185 @@ mod tests {
                 require_exact_member_id,
                 require_no_descendants,
                 require_not_deputy,
+                reason,
+                duration,
             } => {
                 assert_eq!(room_id, "ROOM");
                 assert_eq!(member_id, "ABCDEFGH");
                 assert!(require_exact_member_id);
                 assert!(require_no_descendants);
                 assert!(require_not_deputy);
+                assert_eq!(reason, None);
+                assert_eq!(duration, None);
             }
             other => panic!("wrong subcommand: {:?}", std::mem::discriminant(&other)),
         }
     }
 
+    #[test]
+    fn ban_takes_a_reason_and_a_duration() {
+        match parse(&[
+            "ban",
+            "ROOM",
+            "ABCDEFGH",
+            "--reason",
+            "spam",
+            "--duration",
+            "7d",
+        ])
+        .expect("must parse")
+        {
+            MemberCommands::Ban {
+                reason, duration, ..
+            } => {
+                assert_eq!(reason.as_deref(), Some("spam"));
+                assert_eq!(duration, Some(Duration::from_secs(7 * 24 * 60 * 60)));
+            }
+            other => panic!("wrong subcommand: {:?}", std::mem::discriminant(&other)),
+        }
+        assert_eq!(parse_ban_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_ban_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_ban_duration("12h"), Ok(Duration::from_secs(43_200)));
+        for bad in ["", "7", "d", "0h", "-1d", "1.5h", "7w"] {
+            assert!(parse_ban_duration(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn unban_takes_room_and_member_id() {
+        match parse(&["unban", "ROOM", "ABCDEFGH"]).expect("must parse") {
+            MemberCommands::Unban { room_id, member_id } => {
+                assert_eq!(room_id, "ROOM");
+                assert_eq!(member_id, "ABCDEFGH");
+            }
+            other => panic!("wrong subcommand: {:?}", std::mem::discriminant(&other)),
+        }
+    }
+
+    #[test]
+    fn join_request_commands_take_a_room_and_requester() {
+        assert!(matches!(
+            parse(&["requests", "ROOM"]).expect("must parse"),
+            MemberCommands::Requests { room_id } if room_id == "ROOM"
+        ));
+        for verb in ["approve", "deny"] {
+            match parse(&[verb, "ROOM", "ABCDEFGH"]).expect("must parse") {
+                MemberCommands::Approve {
+                    room_id,
+                    requester_id,
+                }
+                | MemberCommands::Deny {
+                    room_id,
+                    requester_id,
+                } => {
+                    assert_eq!(room_id, "ROOM");
+                    assert_eq!(requester_id, "ABCDEFGH");
+                }
+                other => panic!("wrong subcommand: {:?}", std::mem::discriminant(&other)),
+            }
+            assert!(parse(&[verb, "ROOM"]).is_err());
+        }
+    }
+
+    #[test]
+    fn request_rows_list_pending_before_decided() {
+        use river_core::room_state::join_request::{
+            AuthorizedJoinDecision, AuthorizedJoinRequest, JoinDecision, JoinRequest,
+            JoinRequestsV1,
+        };
+        let owner = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]);
+        let [p, d] = [2u8, 3].map(|b| ed25519_dalek::SigningKey::from_bytes(&[b; 32]));
+        let request = |sk: &ed25519_dalek::SigningKey| {
+            AuthorizedJoinRequest::new(
+                JoinRequest {
+                    owner_member_id: owner.verifying_key().into(),
+                    requester: sk.verifying_key(),
+                    nickname: "knocker".into(),
+                    message: "hello".into(),
+                    requested_at: UNIX_EPOCH + Duration::from_secs(60),
+                },
+                sk,
+            )
+        };
+        let denial = AuthorizedJoinDecision::new(
+            JoinDecision {
+                owner_member_id: owner.verifying_key().into(),
+                requester: d.verifying_key(),
+                decided_at: UNIX_EPOCH + Duration::from_secs(120),
+                outcome: JoinOutcome::Denied,
+            },
+            owner.verifying_key().into(),
+            &owner,
+        );
+        let state = ChatRoomStateV1 {
+            join_requests: JoinRequestsV1 {
+                requests: vec![request(&p)],
+                decisions: vec![denial],
+            },
+            ..Default::default()
+        };
+
+        let rows = collect_join_requests(&state);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].status, "pending");
+        assert_eq!(rows[0].nickname.as_deref(), Some("knocker"));
+        assert_eq!(rows[0].requested_at_secs, Some(60));
+        assert_eq!(rows[1].status, "denied");
+        assert_eq!(
+            rows[1].requester_id,
+            MemberId::from(d.verifying_key()).to_string()
+        );
+        assert_eq!(rows[1].nickname, None);
+        assert_eq!(
+            rows[1].decided_by.as_deref(),
+            Some(MemberId::from(owner.verifying_key()).to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn mute_takes_an_optional_duration_and_unmute_takes_none() {
+        match parse(&["mute", "ROOM", "ABCDEFGH", "--duration", "30m"]).expect("must parse") {
+            MemberCommands::Mute {
+                room_id,
+                member_id,
+                duration,
+            } => {
+                assert_eq!(room_id, "ROOM");
+                assert_eq!(member_id, "ABCDEFGH");
+                assert_eq!(duration, Some(Duration::from_secs(1800)));
+            }
+            other => panic!("wrong subcommand: {:?}", std::mem::discriminant(&other)),
+        }
+        assert!(matches!(
+            parse(&["mute", "ROOM", "ABCDEFGH"]).expect("must parse"),
+            MemberCommands::Mute { duration: None, .. }
+        ));
+        assert!(matches!(
+            parse(&["unmute", "ROOM", "ABCDEFGH"]).expect("must parse"),
+            MemberCommands::Unmute { .. }
+        ));
+        assert!(parse(&["unmute", "ROOM", "ABCDEFGH", "--duration", "1h"]).is_err());
+    }
+
+    #[test]
+    fn block_commands_take_a_room_and_member() {
+        for verb in ["block", "unblock"] {
+            match parse(&[verb, "ROOM", "ABCDEFGH"]).expect("must parse") {
+                MemberCommands::Block { room_id, member_id }
+                | MemberCommands::Unblock { room_id, member_id } => {
+                    assert_eq!(room_id, "ROOM");
+                    assert_eq!(member_id, "ABCDEFGH");
+                }
+                other => panic!("wrong subcommand: {:?}", std::mem::discriminant(&other)),
+            }
+        }
+        assert!(matches!(
+            parse(&["blocked", "ROOM"]).expect("must parse"),
+            MemberCommands::Blocked { room_id } if room_id == "ROOM"
+        ));
+        assert!(parse(&["block", "ROOM"]).is_err());
+    }
+
     #[test]
     fn deputies_accepts_an_explicit_member_id() {
         match parse(&["deputies", "ROOM", "7XSOGJTK"]).expect("must parse") {
@@ -5,8 +5,10 @@ use base64::Engine;
 use chrono::{DateTime, Local, Utc};
 use clap::Subcommand;
 use ed25519_dalek::{SigningKey, VerifyingKey};
-use river_core::room_state::message::MessageId;
+use river_core::room_state::message::{AuthorizedMessageV1, MessageId};
+use river_core::ChatRoomStateV1;
 use serde_json::json;
+use std::collections::HashMap;
 
 #[derive(Subcommand)]
 pub enum MessageCommands {
@@ -20,6 +22,7 @@ pub enum MessageCommands {
     /// an @-autocomplete picker for the same result.
     Send {
         /// Room ID (base58-encoded room owner verifying key)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Message content. Write `@nickname` to mention a member.
         message: String,
@@ -32,6 +35,7 @@ pub enum MessageCommands {
     /// List recent messages in a room
     List {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Number of messages to show
         #[arg(short, long, default_value = "20")]
@@ -43,6 +47,7 @@ pub enum MessageCommands {
     /// Stream messages from a room in real-time
     Stream {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Polling interval in milliseconds (only used without --subscribe)
         #[arg(short, long, default_value = "1000")]
@@ -63,6 +68,7 @@ pub enum MessageCommands {
     /// Edit a message you sent
     Edit {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Message ID (from 'message list --json', use the signature field)
         #[arg(allow_hyphen_values = true)]
@@ -73,6 +79,7 @@ pub enum MessageCommands {
     /// Delete a message you sent
     Delete {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Message ID (from 'message list --json', use the signature field)
         #[arg(allow_hyphen_values = true)]
@@ -81,6 +88,7 @@ pub enum MessageCommands {
     /// Add a reaction to a message
     React {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Message ID (from 'message list --json', use the signature field)
         #[arg(allow_hyphen_values = true)]
@@ -91,6 +99,7 @@ pub enum MessageCommands {
     /// Remove a reaction from a message
     Unreact {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Message ID (from 'message list --json', use the signature field)
         #[arg(allow_hyphen_values = true)]
@@ -103,6 +112,7 @@ pub enum MessageCommands {
     /// `@nickname` mentions in the reply text are resolved exactly as in `send`.
     Reply {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
         /// Message ID of the message to reply to
         #[arg(allow_hyphen_values = true)]
@@ -110,8 +120,142 @@ pub enum MessageCommands {
         /// Reply text. Write `@nickname` to mention a member.
         message: String,
     },
+    /// Show the thread a message belongs to: its root and every reply
+    /// under it, directly or through other replies
+    Thread {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Message ID of any message in the thread
+        #[arg(allow_hyphen_values = true)]
+        message_id: String,
+    },
+    /// Search the room's message history. Edited messages match on their
+    /// current text; deleted messages never match.
+    Search {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Words that must all appear in the message (case-insensitive)
+        #[arg(default_value = "")]
+        query: String,
+        /// Only messages by this member (nickname or member ID)
+        #[arg(long)]
+        author: Option<String>,
+        /// Only messages mentioning this member (nickname or member ID)
+        #[arg(long)]
+        mentions: Option<String>,
+        /// Only messages sent on or after this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only messages sent on or before this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+        /// Only messages of this kind: text, reply, poll or attachment
+        #[arg(long = "type")]
+        content_type: Option<String>,
+        /// Show at most the N newest matches
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// Post a poll
+    Poll {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// The question to ask
+        question: String,
+        /// Two or more answer options
+        #[arg(required = true, num_args = 2..)]
+        options: Vec<String>,
+        /// Allow each member to pick more than one option
+        #[arg(long)]
+        multiple_choice: bool,
+        /// Stop counting votes after N minutes
+        #[arg(long)]
+        closes_in_minutes: Option<u64>,
+    },
+    /// Vote in a poll (replaces your earlier vote)
+    Vote {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Message ID of the poll (from 'message list --json')
+        #[arg(allow_hyphen_values = true)]
+        message_id: String,
+        /// Option numbers to vote for, starting at 1 (as shown by 'poll-results')
+        #[arg(required_unless_present = "retract")]
+        choices: Vec<u32>,
+        /// Withdraw your vote instead of casting one
+        #[arg(long, conflicts_with = "choices")]
+        retract: bool,
+    },
+    /// Show the current results of a poll
+    PollResults {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Message ID of the poll (from 'message list --json')
+        #[arg(allow_hyphen_values = true)]
+        message_id: String,
+    },
+    /// Send a file as an attachment. The file is uploaded to its own
+    /// content-addressed contract (encrypted in private rooms) and the
+    /// message carries a reference to it.
+    Attach {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Path of the file to attach
+        file: std::path::PathBuf,
+        /// MIME type (guessed from the file extension if omitted)
+        #[arg(long)]
+        mime_type: Option<String>,
+    },
+    /// Download the attachment of a message
+    Download {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Message ID of the attachment (from 'message list --json')
+        #[arg(allow_hyphen_values = true)]
+        message_id: String,
+        /// Where to write the file (defaults to the attachment's file name in
+        /// the current directory)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Pin a message so it stays visible past the recent-message limit
+    /// (room owner and owner-appointed deputies only)
+    Pin {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Message ID (from 'message list --json', use the signature field)
+        #[arg(allow_hyphen_values = true)]
+        message_id: String,
+    },
+    /// Unpin a pinned message (room owner and owner-appointed deputies only)
+    Unpin {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// Message ID (from 'message pinned --json')
+        #[arg(allow_hyphen_values = true)]
+        message_id: String,
+    },
+    /// List pinned messages
+    Pinned {
+        /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+    },
 }
 
+/// Most options a poll created from the CLI may carry; keeps the encoded body
+/// comfortably inside the default `max_message_size`.
+const MAX_POLL_OPTIONS: usize = 20;
+
 pub async fn execute(command: MessageCommands, api: ApiClient, format: OutputFormat) -> Result<()> {
     match command {
         MessageCommands::Send {
@@ -203,6 +347,7 @@ pub async fn execute(command: MessageCommands, api: ApiClient, format: OutputFor
             // display_messages() so a decrypted private *deletion* hides its
             // message.
             let secrets = api.room_display_secrets(&room_owner_key, &mut room_state);
+            api.hide_blocked_authors(&room_owner_key, &mut room_state);
 
             // Get only display messages (non-deleted, non-action)
             let mut messages: Vec<_> = room_state.recent_messages.display_messages().collect();
@@ -227,153 +372,14 @@ pub async fn execute(command: MessageCommands, api: ApiClient, format: OutputFor
                         println!("No messages found");
                     } else {
                         for msg in &messages {
-                            let author_str = msg.message.author.to_string();
-                            let author_short = author_str.chars().take(8).collect::<String>();
-
-                            // Get nickname if available (decrypted for a private room).
-                            // `canonical`, not a bare `.find()` (#411 round 8 item A): a
-                            // duplicate-holding state could otherwise display a stale
-                            // (e.g. revoked) record's nickname.
-                            let nickname = room_state
-                                .member_info
-                                .canonical(msg.message.author)
-                                .map(|info| {
-                                    crate::api::unseal_nickname_display(
-                                        &info.member_info.preferred_nickname,
-                                        &secrets,
-                                    )
-                                })
-                                .unwrap_or(author_short);
-
-                            let datetime: DateTime<Utc> = msg.message.time.into();
-                            let local_time: DateTime<Local> = datetime.into();
-
-                            // Get display content (handles edits, non-text
-                            // public content like join events, and — via
-                            // `secrets` — decrypted private-room bodies; only a
-                            // body whose secret is unavailable renders as
-                            // "<encrypted>")
-                            let content = crate::api::message_display_text_with_secrets(
-                                &room_state,
-                                msg,
-                                &secrets,
-                            );
-
-                            // Check if message is edited
-                            let msg_id = msg.id();
-                            let edited = room_state.recent_messages.is_edited(&msg_id);
-                            let edited_indicator = if edited { " (edited)" } else { "" };
-
-                            // Check for reply context (shared with the monitor
-                            // stream via crate::api::reply_context_display so the
-                            // two renderings can't drift, including the truncation
-                            // marker appended by truncate_reply_preview).
-                            let reply_prefix = crate::api::reply_prefix_display(
-                                &crate::api::reply_context_display_with_secrets(
-                                    &room_state,
-                                    msg,
-                                    &secrets,
-                                ),
-                            );
-
-                            // Get reactions
-                            let reactions_str = room_state
-                                .recent_messages
-                                .reactions(&msg_id)
-                                .map(|reactions| {
-                                    if reactions.is_empty() {
-                                        String::new()
-                                    } else {
-                                        let parts: Vec<_> = reactions
-                                            .iter()
-                                            .map(|(emoji, reactors)| {
-                                                format!("{}×{}", emoji, reactors.len())
-                                            })
-                                            .collect();
-                                        format!(" [{}]", parts.join(" "))
-                                    }
-                                })
-                                .unwrap_or_default();
-
-                            println!(
-                                "[{} - {}]: {}{}{}{}",
-                                local_time.format("%H:%M:%S"),
-                                nickname,
-                                reply_prefix,
-                                content,
-                                edited_indicator,
-                                reactions_str
-                            );
+                            println!("{}", message_line(&room_state, msg, &secrets));
                         }
                     }
                 }
                 OutputFormat::Json => {
                     let json_messages: Vec<_> = messages
                         .iter()
-                        .map(|msg| {
-                            let author_str = msg.message.author.to_string();
-                            let msg_id = msg.id();
-
-                            // `canonical`, not a bare `.find()` (#411 round 8 item A).
-                            let nickname = room_state
-                                .member_info
-                                .canonical(msg.message.author)
-                                .map(|info| {
-                                    crate::api::unseal_nickname_display(
-                                        &info.member_info.preferred_nickname,
-                                        &secrets,
-                                    )
-                                });
-
-                            let datetime: DateTime<Utc> = msg.message.time.into();
-
-                            // Get display content (handles edits, non-text
-                            // public content like join events, and — via
-                            // `secrets` — decrypted private-room bodies; only a
-                            // body whose secret is unavailable renders as
-                            // "<encrypted>")
-                            let content = crate::api::message_display_text_with_secrets(
-                                &room_state,
-                                msg,
-                                &secrets,
-                            );
-
-                            // Check edited status
-                            let edited = room_state.recent_messages.is_edited(&msg_id);
-
-                            // Get reactions
-                            let reactions: std::collections::HashMap<String, usize> = room_state
-                                .recent_messages
-                                .reactions(&msg_id)
-                                .map(|r| r.iter().map(|(k, v)| (k.clone(), v.len())).collect())
-                                .unwrap_or_default();
-
-                            // Encode message ID for use in edit/delete/react commands
-                            let message_id_str = msg_id.0 .0.to_string();
-
-                            // Reply context (null for non-replies) — same shape
-                            // as the monitor stream's JSON, so a bridge sees
-                            // reply_to on both the backfill and the live feed.
-                            // Shared helper so the two cannot drift.
-                            let reply_to = crate::api::reply_to_json(
-                                &crate::api::reply_context_display_with_secrets(
-                                    &room_state,
-                                    msg,
-                                    &secrets,
-                                ),
-                            );
-
-                            json!({
-                                "message_id": message_id_str,
-                                "author": author_str,
-                                "nickname": nickname,
-                                "content": content,
-                                "timestamp": datetime.to_rfc3339(),
-                                "edited": edited,
-                                "reply_to": reply_to,
-                                "reactions": reactions,
-                            })
-                        })
+                        .map(|msg| message_json(&room_state, msg, &secrets))
                         .collect();
 
                     println!("{}", serde_json::to_string_pretty(&json_messages)?);
@@ -520,9 +526,469 @@ pub async fn execute(command: MessageCommands, api: ApiClient, format: OutputFor
             }
             Ok(())
         }
+        MessageCommands::Thread {
+            room_id,
+            message_id,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            let message_id = parse_message_id(&message_id)?;
+
+            let mut room_state = api.get_room(&room_owner_key, false).await?;
+            // Decrypt private replies so they thread like public ones.
+            let secrets = api.room_display_secrets(&room_owner_key, &mut room_state);
+            api.hide_blocked_authors(&room_owner_key, &mut room_state);
+            let index = room_state
+                .recent_messages
+                .thread_index_with(|msg| crate::api::reply_target_with_secrets(msg, &secrets));
+
+            let root = index.root_of(&message_id);
+            let by_id: HashMap<MessageId, &AuthorizedMessageV1> = room_state
+                .recent_messages
+                .display_messages()
+                .map(|msg| (msg.id(), msg))
+                .collect();
+            let Some(root_msg) = by_id.get(&root) else {
+                return Err(anyhow::anyhow!("Message not found: {}", message_id.0 .0));
+            };
+            let thread: Vec<&AuthorizedMessageV1> = std::iter::once(*root_msg)
+                .chain(
+                    index
+                        .descendants(&root)
+                        .iter()
+                        .filter_map(|id| by_id.get(id).copied()),
+                )
+                .collect();
+            let depth = |id: &MessageId| {
+                let mut depth = 0;
+                let mut current = id;
+                while let Some(parent) = index.parent(current) {
+                    depth += 1;
+                    current = parent;
+                }
+                depth
+            };
+
+            match format {
+                OutputFormat::Human => {
+                    for msg in &thread {
+                        println!(
+                            "{}{}",
+                            "  ".repeat(depth(&msg.id())),
+                            message_line(&room_state, msg, &secrets)
+                        );
+                    }
+                }
+                OutputFormat::Json => {
+                    let json_messages: Vec<_> = thread
+                        .iter()
+                        .map(|msg| {
+                            let mut entry = message_json(&room_state, msg, &secrets);
+                            entry["parent_id"] = json!(index
+                                .parent(&msg.id())
+                                .map(|parent| parent.0 .0.to_string()));
+                            entry["depth"] = json!(depth(&msg.id()));
+                            entry
+                        })
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&json_messages)?);
+                }
+            }
+            Ok(())
+        }
+        MessageCommands::Search {
+            room_id,
+            query,
+            author,
+            mentions,
+            since,
+            until,
+            content_type,
+            limit,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            let mut room_state = api.get_room(&room_owner_key, false).await?;
+            // Decrypts private bodies, and rebuilds the actions state from
+            // decrypted private edits/deletes so search sees them too.
+            let secrets = api.room_display_secrets(&room_owner_key, &mut room_state);
+            api.hide_blocked_authors(&room_owner_key, &mut room_state);
+
+            let search_query = river_core::search::SearchQuery {
+                text: query,
+                author: author
+                    .map(|who| resolve_member_filter(&room_state, &secrets, &who))
+                    .transpose()?,
+                since: since
+                    .map(|date| parse_search_date(&date, false))
+                    .transpose()?,
+                until: until
+                    .map(|date| parse_search_date(&date, true))
+                    .transpose()?,
+                mentions: mentions
+                    .map(|who| resolve_member_filter(&room_state, &secrets, &who))
+                    .transpose()?,
+                content_type: content_type
+                    .map(|kind| parse_search_content_type(&kind))
+                    .transpose()?,
+            };
+            let hits = room_state
+                .recent_messages
+                .search_with_secrets(&search_query, &secrets);
+            let hits = &hits[hits.len().saturating_sub(limit)..];
+
+            match format {
+                OutputFormat::Human => {
+                    if hits.is_empty() {
+                        println!("No matching messages");
+                    }
+                    for hit in hits {
+                        println!("{}", message_line(&room_state, hit.message, &secrets));
+                    }
+                }
+                OutputFormat::Json => {
+                    let json_messages: Vec<_> = hits
+                        .iter()
+                        .map(|hit| message_json(&room_state, hit.message, &secrets))
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&json_messages)?);
+                }
+            }
+            Ok(())
+        }
+        MessageCommands::Poll {
+            room_id,
+            question,
+            options,
+            multiple_choice,
+            closes_in_minutes,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            if options.len() > MAX_POLL_OPTIONS {
+                return Err(anyhow::anyhow!(
+                    "A poll can have at most {} options",
+                    MAX_POLL_OPTIONS
+                ));
+            }
+            let closes_at = closes_in_minutes.map(|minutes| {
+                std::time::SystemTime::now() + std::time::Duration::from_secs(minutes * 60)
+            });
+            let poll = river_core::room_state::content::PollContentV1::new(
+                question,
+                options,
+                multiple_choice,
+                closes_at,
+            );
+
+            api.send_poll(&room_owner_key, poll).await?;
+
+            match format {
+                OutputFormat::Human => println!("Poll posted successfully"),
+                OutputFormat::Json => println!(r#"{{"status":"success","action":"poll"}}"#),
+            }
+            Ok(())
+        }
+        MessageCommands::Vote {
+            room_id,
+            message_id,
+            choices,
+            retract,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            let target_message_id = parse_message_id(&message_id)?;
+            let indices = if retract {
+                Vec::new()
+            } else {
+                choices_to_indices(&choices)?
+            };
+
+            api.vote_in_poll(&room_owner_key, target_message_id, indices)
+                .await?;
+
+            match format {
+                OutputFormat::Human if retract => println!("Vote withdrawn successfully"),
+                OutputFormat::Human => println!("Vote cast successfully"),
+                OutputFormat::Json => println!(
+                    "{}",
+                    json!({"status": "success", "action": "vote", "choices": choices})
+                ),
+            }
+            Ok(())
+        }
+        MessageCommands::PollResults {
+            room_id,
+            message_id,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            let poll_id = parse_message_id(&message_id)?;
+
+            let mut room_state = api.get_room(&room_owner_key, false).await?;
+            let secrets = api.room_display_secrets(&room_owner_key, &mut room_state);
+            let poll_msg = room_state
+                .recent_messages
+                .display_messages()
+                .find(|m| m.id() == poll_id)
+                .ok_or_else(|| anyhow::anyhow!("Poll not found in recent messages"))?;
+            let poll =
+                crate::api::poll_content_with_secrets(poll_msg, &secrets).ok_or_else(|| {
+                    anyhow::anyhow!("Message is not a poll, or it cannot be decrypted")
+                })?;
+            let tally = room_state.recent_messages.poll_tally(&poll_id, &poll);
+            let closed = !poll.is_open_at(std::time::SystemTime::now());
+
+            match format {
+                OutputFormat::Human => {
+                    println!("📊 {}", poll.question);
+                    for (i, (option, voters)) in poll.options.iter().zip(&tally.voters).enumerate()
+                    {
+                        println!("  {}. {} — {}", i + 1, option, voters.len());
+                    }
+                    let mut footer = format!("{} voter(s)", tally.total_voters);
+                    if poll.multiple_choice {
+                        footer.push_str(", multiple choice");
+                    }
+                    if let Some(closes_at) = poll.closes_at {
+                        let closes_at: DateTime<Local> = DateTime::<Utc>::from(closes_at).into();
+                        footer.push_str(&format!(
+                            ", {} {}",
+                            if closed { "closed" } else { "closes" },
+                            closes_at.format("%Y-%m-%d %H:%M")
+                        ));
+                    }
+                    println!("{}", footer);
+                }
+                OutputFormat::Json => {
+                    let options: Vec<_> = poll
+                        .options
+                        .iter()
+                        .zip(&tally.voters)
+                        .map(|(option, voters)| {
+                            json!({
+                                "option": option,
+                                "votes": voters.len(),
+                                "voters": voters.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+                            })
+                        })
+                        .collect();
+                    let result = json!({
+                        "message_id": poll_id.0 .0.to_string(),
+                        "question": poll.question,
+                        "multiple_choice": poll.multiple_choice,
+                        "closes_at": poll
+                            .closes_at
+                            .map(|t| DateTime::<Utc>::from(t).to_rfc3339()),
+                        "closed": closed,
+                        "total_voters": tally.total_voters,
+                        "options": options,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&result)?);
+                }
+            }
+            Ok(())
+        }
+        MessageCommands::Attach {
+            room_id,
+            file,
+            mime_type,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            let data = std::fs::read(&file)
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", file.display(), e))?;
+            let filename = file
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .ok_or_else(|| anyhow::anyhow!("{} is not a file", file.display()))?;
+            let mime_type = mime_type.unwrap_or_else(|| {
+                mime_guess::from_path(&file)
+                    .first_or_octet_stream()
+                    .to_string()
+            });
+
+            let blob = api
+                .send_attachment(&room_owner_key, data, filename, mime_type)
+                .await?;
+
+            match format {
+                OutputFormat::Human => println!("Attachment sent: {}", blob.summary()),
+                OutputFormat::Json => println!(
+                    "{}",
+                    json!({
+                        "status": "success",
+                        "action": "attach",
+                        "filename": blob.filename,
+                        "mime_type": blob.mime_type,
+                        "size": blob.size,
+                        "contract_id": bs58::encode(blob.contract_id).into_string(),
+                    })
+                ),
+            }
+            Ok(())
+        }
+        MessageCommands::Download {
+            room_id,
+            message_id,
+            output,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            let message_id = parse_message_id(&message_id)?;
+
+            let (blob, data) = api.fetch_attachment(&room_owner_key, &message_id).await?;
+            // Never let a sender-chosen name escape the current directory.
+            let path = output.unwrap_or_else(|| {
+                std::path::Path::new(&blob.filename)
+                    .file_name()
+                    .map(std::path::PathBuf::from)
+                    .unwrap_or_else(|| std::path::PathBuf::from("attachment"))
+            });
+            std::fs::write(&path, &data)
+                .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+
+            match format {
+                OutputFormat::Human => println!(
+                    "Saved {} ({}) to {}",
+                    blob.filename,
+                    blob.mime_type,
+                    path.display()
+                ),
+                OutputFormat::Json => println!(
+                    "{}",
+                    json!({
+                        "status": "success",
+                        "action": "download",
+                        "filename": blob.filename,
+                        "mime_type": blob.mime_type,
+                        "size": data.len(),
+                        "path": path.display().to_string(),
+                    })
+                ),
+            }
+            Ok(())
+        }
+        MessageCommands::Pin {
+            room_id,
+            message_id,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            let target_message_id = parse_message_id(&message_id)?;
+
+            api.set_pinned(&room_owner_key, target_message_id, true)
+                .await?;
+
+            match format {
+                OutputFormat::Human => println!("Message pinned successfully"),
+                OutputFormat::Json => println!(r#"{{"status":"success","action":"pin"}}"#),
+            }
+            Ok(())
+        }
+        MessageCommands::Unpin {
+            room_id,
+            message_id,
+        } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+            let target_message_id = parse_message_id(&message_id)?;
+
+            api.set_pinned(&room_owner_key, target_message_id, false)
+                .await?;
+
+            match format {
+                OutputFormat::Human => println!("Message unpinned successfully"),
+                OutputFormat::Json => println!(r#"{{"status":"success","action":"unpin"}}"#),
+            }
+            Ok(())
+        }
+        MessageCommands::Pinned { room_id } => {
+            let room_owner_key = parse_room_id(&room_id)?;
+
+            let mut room_state = api.get_room(&room_owner_key, false).await?;
+            // Rebuilds actions_state from decrypted private actions, so a
+            // pinned message its author deleted is left out below.
+            let secrets = api.room_display_secrets(&room_owner_key, &mut room_state);
+
+            let nickname = |member: river_core::room_state::member::MemberId| {
+                room_state
+                    .member_info
+                    .canonical(member)
+                    .map(|info| {
+                        crate::api::unseal_nickname_display(
+                            &info.member_info.preferred_nickname,
+                            &secrets,
+                        )
+                    })
+                    .unwrap_or_else(|| member.to_string().chars().take(8).collect())
+            };
+            let pinned: Vec<_> = room_state
+                .recent_messages
+                .pins()
+                .into_iter()
+                .filter(|pin| room_state.recent_messages.is_pinned(&pin.target))
+                .filter_map(|pin| {
+                    let msg = room_state
+                        .recent_messages
+                        .messages
+                        .iter()
+                        .find(|m| m.id() == pin.target)?;
+                    Some((pin, msg))
+                })
+                .collect();
+
+            match format {
+                OutputFormat::Human => {
+                    if pinned.is_empty() {
+                        println!("No pinned messages");
+                    }
+                    for (pin, msg) in &pinned {
+                        let local_time: DateTime<Local> =
+                            DateTime::<Utc>::from(msg.message.time).into();
+                        println!(
+                            "📌 [{} - {}]: {} (pinned by {})",
+                            local_time.format("%Y-%m-%d %H:%M"),
+                            nickname(msg.message.author),
+                            crate::api::message_display_text_with_secrets(
+                                &room_state,
+                                msg,
+                                &secrets
+                            ),
+                            nickname(pin.pinned_by)
+                        );
+                    }
+                }
+                OutputFormat::Json => {
+                    let json_pins: Vec<_> = pinned
+                        .iter()
+                        .map(|(pin, msg)| {
+                            json!({
+                                "message_id": pin.target.0 .0.to_string(),
+                                "author": msg.message.author.to_string(),
+                                "nickname": nickname(msg.message.author),
+                                "timestamp": DateTime::<Utc>::from(msg.message.time).to_rfc3339(),
+                                "content": crate::api::message_display_text_with_secrets(
+                                    &room_state,
+                                    msg,
+                                    &secrets,
+                                ),
+                                "pinned_by": pin.pinned_by.to_string(),
+                                "pinned_at": DateTime::<Utc>::from(pin.pinned_at).to_rfc3339(),
+                            })
+                        })
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&json_pins)?);
+                }
+            }
+            Ok(())
+        }
     }
 }
 
+/// Convert the 1-based option numbers a user types into the 0-based indices
+/// stored in a vote.
+fn choices_to_indices(choices: &[u32]) -> Result<Vec<u32>> {
+    choices
+        .iter()
+        .map(|&c| {
+            c.checked_sub(1)
+                .ok_or_else(|| anyhow::anyhow!("Option numbers start at 1"))
+        })
+        .collect()
+}
+
 /// Helper to parse room ID from base58-encoded string
 fn parse_room_id(room_id: &str) -> Result<VerifyingKey> {
     let room_owner_key_bytes = bs58::decode(room_id)
@@ -540,8 +1006,219 @@ fn parse_room_id(room_id: &str) -> Result<VerifyingKey> {
         .map_err(|e| anyhow::anyhow!("Invalid room ID: {}", e))
 }
 
+/// One `message list` line: `[time - nickname]: reply-prefix content`, with
+/// the edited marker and reaction counts.
+pub(crate) fn message_line(
+    room_state: &ChatRoomStateV1,
+    msg: &AuthorizedMessageV1,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> String {
+    let author_str = msg.message.author.to_string();
+    let author_short = author_str.chars().take(8).collect::<String>();
+
+    // Get nickname if available (decrypted for a private room).
+    // `canonical`, not a bare `.find()` (#411 round 8 item A): a
+    // duplicate-holding state could otherwise display a stale
+    // (e.g. revoked) record's nickname.
+    let nickname = room_state
+        .member_info
+        .canonical(msg.message.author)
+        .map(|info| {
+            crate::api::unseal_nickname_display(&info.member_info.preferred_nickname, secrets)
+        })
+        .unwrap_or(author_short);
+
+    let datetime: DateTime<Utc> = msg.message.time.into();
+    let local_time: DateTime<Local> = datetime.into();
+
+    // Get display content (handles edits, non-text
+    // public content like join events, and — via
+    // `secrets` — decrypted private-room bodies; only a
+    // body whose secret is unavailable renders as
+    // "<encrypted>")
+    let content = crate::api::message_display_text_with_secrets(room_state, msg, secrets);
+
+    // Check if message is edited
+    let msg_id = msg.id();
+    let edited = room_state.recent_messages.is_edited(&msg_id);
+    let edited_indicator = if edited { " (edited)" } else { "" };
+
+    // Check for reply context (shared with the monitor
+    // stream via crate::api::reply_context_display so the
+    // two renderings can't drift, including the truncation
+    // marker appended by truncate_reply_preview).
+    let reply_prefix = crate::api::reply_prefix_display(
+        &crate::api::reply_context_display_with_secrets(room_state, msg, secrets),
+    );
+
+    // Get reactions
+    let reactions_str = room_state
+        .recent_messages
+        .reactions(&msg_id)
+        .map(|reactions| {
+            if reactions.is_empty() {
+                String::new()
+            } else {
+                let parts: Vec<_> = reactions
+                    .iter()
+                    .map(|(emoji, reactors)| format!("{}×{}", emoji, reactors.len()))
+                    .collect();
+                format!(" [{}]", parts.join(" "))
+            }
+        })
+        .unwrap_or_default();
+
+    format!(
+        "[{} - {}]: {}{}{}{}",
+        local_time.format("%H:%M:%S"),
+        nickname,
+        reply_prefix,
+        content,
+        edited_indicator,
+        reactions_str
+    )
+}
+
+/// One `message list --json` entry.
+pub(crate) fn message_json(
+    room_state: &ChatRoomStateV1,
+    msg: &AuthorizedMessageV1,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> serde_json::Value {
+    let author_str = msg.message.author.to_string();
+    let msg_id = msg.id();
+
+    // `canonical`, not a bare `.find()` (#411 round 8 item A).
+    let nickname = room_state
+        .member_info
+        .canonical(msg.message.author)
+        .map(|info| {
+            crate::api::unseal_nickname_display(&info.member_info.preferred_nickname, secrets)
+        });
+
+    let datetime: DateTime<Utc> = msg.message.time.into();
+
+    // Get display content (handles edits, non-text
+    // public content like join events, and — via
+    // `secrets` — decrypted private-room bodies; only a
+    // body whose secret is unavailable renders as
+    // "<encrypted>")
+    let content = crate::api::message_display_text_with_secrets(room_state, msg, secrets);
+
+    // Check edited status
+    let edited = room_state.recent_messages.is_edited(&msg_id);
+
+    // Get reactions
+    let reactions: std::collections::HashMap<String, usize> = room_state
+        .recent_messages
+        .reactions(&msg_id)
+        .map(|r| r.iter().map(|(k, v)| (k.clone(), v.len())).collect())
+        .unwrap_or_default();
+
+    // Encode message ID for use in edit/delete/react commands
+    let message_id_str = msg_id.0 .0.to_string();
+
+    // Reply context (null for non-replies) — same shape
+    // as the monitor stream's JSON, so a bridge sees
+    // reply_to on both the backfill and the live feed.
+    // Shared helper so the two cannot drift.
+    let reply_to = crate::api::reply_to_json(&crate::api::reply_context_display_with_secrets(
+        room_state, msg, secrets,
+    ));
+
+    json!({
+        "message_id": message_id_str,
+        "author": author_str,
+        "nickname": nickname,
+        "content": content,
+        "timestamp": datetime.to_rfc3339(),
+        "edited": edited,
+        "reply_to": reply_to,
+        "reactions": reactions,
+    })
+}
+
+/// Resolve a `--author` / `--mentions` argument: a member ID (or unambiguous
+/// prefix of one), or a member's current nickname, case-insensitively.
+fn resolve_member_filter(
+    room_state: &ChatRoomStateV1,
+    secrets: &HashMap<u32, [u8; 32]>,
+    who: &str,
+) -> Result<river_core::room_state::member::MemberId> {
+    let wanted = who.trim_start_matches('@').to_lowercase();
+    let mut matches: Vec<_> = room_state
+        .member_info
+        .member_info
+        .iter()
+        .map(|info| &info.member_info)
+        .filter(|info| {
+            info.member_id
+                .to_string()
+                .to_lowercase()
+                .starts_with(&wanted)
+                || crate::api::unseal_nickname_display(&info.preferred_nickname, secrets)
+                    .to_lowercase()
+                    == wanted
+        })
+        .map(|info| info.member_id)
+        .collect();
+    matches.sort();
+    matches.dedup();
+    match matches.as_slice() {
+        [id] => Ok(*id),
+        [] => Err(anyhow::anyhow!(
+            "No member '{}' in this room. Use 'member list' to see members.",
+            who
+        )),
+        ids => Err(anyhow::anyhow!(
+            "'{}' matches {} members; pass more of the member ID.",
+            who,
+            ids.len()
+        )),
+    }
+}
+
+/// Parse a `--since` / `--until` date: RFC 3339, or a local `YYYY-MM-DD`
+/// meaning the start of that day (`end_of_day`: the start of the next day, so
+/// the whole day is included).
+fn parse_search_date(date: &str, end_of_day: bool) -> Result<std::time::SystemTime> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(date) {
+        return Ok(time.into());
+    }
+    let day = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| anyhow::anyhow!("Invalid date '{}': expected YYYY-MM-DD or RFC 3339", date))?;
+    let day = if end_of_day {
+        day.succ_opt()
+            .ok_or_else(|| anyhow::anyhow!("Date out of range: {}", date))?
+    } else {
+        day
+    };
+    day.and_time(chrono::NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .map(Into::into)
+        .ok_or_else(|| anyhow::anyhow!("Invalid local date: {}", date))
+}
+
+/// Parse a `--type` argument into a `CONTENT_TYPE_*`.
+fn parse_search_content_type(kind: &str) -> Result<u32> {
+    use river_core::room_state::content::{
+        CONTENT_TYPE_BLOB, CONTENT_TYPE_POLL, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
+    };
+    match kind.to_lowercase().as_str() {
+        "text" => Ok(CONTENT_TYPE_TEXT),
+        "reply" => Ok(CONTENT_TYPE_REPLY),
+        "poll" => Ok(CONTENT_TYPE_POLL),
+        "attachment" => Ok(CONTENT_TYPE_BLOB),
+        _ => Err(anyhow::anyhow!(
+            "Unknown message type '{}': expected text, reply, poll or attachment",
+            kind
+        )),
+    }
+}
+
 /// Helper to parse message ID from string (i64 hash value)
-fn parse_message_id(message_id: &str) -> Result<MessageId> {
+pub(crate) fn parse_message_id(message_id: &str) -> Result<MessageId> {
     let hash_value: i64 = message_id
         .parse()
         .map_err(|e| anyhow::anyhow!("Invalid message ID (expected integer): {}", e))?;
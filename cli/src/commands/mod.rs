@@ -2,6 +2,10 @@ pub mod debug;
 pub mod dm;
 pub mod identity;
 pub mod invite;
+pub mod irc;
 pub mod member;
 pub mod message;
+pub mod moderate;
 pub mod room;
+pub mod serve;
+pub mod tui;
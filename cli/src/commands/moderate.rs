@@ -0,0 +1,201 @@
+use crate::api::{ApiClient, BanSafety};
+use crate::deputies::display_nickname;
+use crate::moderation::{Finding, ModerationRules, Moderator, RuleAction};
+use crate::output::OutputFormat;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use colored::Colorize;
+use ed25519_dalek::VerifyingKey;
+use river_core::room_state::member::MemberId;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Watch a room and enforce moderation rules until stopped.
+///
+/// Members whose names are confusable with a protected member's are banned
+/// (identical skeleton) or reported (near miss); messages are checked against
+/// keyword and flood rules. Bans run with every safety check on, so a deputy,
+/// or a member whose ban would remove others they invited, is reported
+/// instead. See `moderation.rs` for the rule file format.
+#[derive(Args)]
+pub struct ModerateArgs {
+    /// Room owner key (base58)
+    #[arg(value_parser = crate::config::room_arg)]
+    pub room_id: String,
+
+    /// Rule file (TOML); repeat to merge several
+    #[arg(long = "rules", required = true)]
+    pub rules: Vec<PathBuf>,
+
+    /// Append findings to this file, one line each
+    #[arg(long)]
+    pub report_log: Option<PathBuf>,
+
+    /// Also send reports as a DM to this member (member ID)
+    #[arg(long)]
+    pub report_dm: Option<String>,
+
+    /// Report what would be banned without banning
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Stop after this many seconds (0 = run until Ctrl+C)
+    #[arg(long, default_value = "0")]
+    pub timeout: u64,
+}
+
+pub async fn execute(args: ModerateArgs, api: ApiClient, format: OutputFormat) -> Result<()> {
+    let owner_vk = parse_room_id(&args.room_id)?;
+    let rules = ModerationRules::load(&args.rules)?;
+    if rules.is_empty() {
+        return Err(anyhow!("The rule files define no rules"));
+    }
+    let (signing_key, _, _) = api.storage().get_room(&owner_vk)?.ok_or_else(|| {
+        anyhow!("Room not found. You must be a member of the room to moderate it.")
+    })?;
+    let mut moderator = Moderator::new(rules, MemberId::from(&signing_key.verifying_key()));
+
+    let mut room_state = api.get_room(&owner_vk, false).await?;
+    for spec in moderator.unresolved_protections(&owner_vk, &room_state) {
+        eprintln!(
+            "{} protected member '{}' is not in the room; not protecting it",
+            "Warning:".yellow(),
+            spec
+        );
+    }
+    let secrets = api.room_display_secrets(&owner_vk, &mut room_state);
+    let findings = moderator.review(&owner_vk, &room_state, &secrets);
+    handle_findings(&api, &args, &owner_vk, format, findings).await;
+
+    let mut subscription = api.subscribe_to_room(&owner_vk, format).await?;
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c().await.ok();
+        let _ = shutdown_tx.send(()).await;
+    });
+    let start_time = std::time::Instant::now();
+
+    loop {
+        if shutdown_rx.try_recv().is_ok() {
+            if matches!(format, OutputFormat::Human) {
+                eprintln!("\nStopped moderating.");
+            }
+            return Ok(());
+        }
+        if args.timeout > 0 && start_time.elapsed().as_secs() >= args.timeout {
+            return Ok(());
+        }
+        if !api.next_room_update(&mut subscription).await? {
+            continue;
+        }
+        match api.get_room(&owner_vk, false).await {
+            Ok(mut room_state) => {
+                let secrets = api.room_display_secrets(&owner_vk, &mut room_state);
+                let findings = moderator.review(&owner_vk, &room_state, &secrets);
+                handle_findings(&api, &args, &owner_vk, format, findings).await;
+            }
+            Err(e) => {
+                tracing::debug!("Failed to fetch room state after notification: {}", e);
+            }
+        }
+    }
+}
+
+/// Ban or report each finding. Failures are printed and the daemon carries
+/// on: one refused ban must not stop moderation of the rest of the room.
+async fn handle_findings(
+    api: &ApiClient,
+    args: &ModerateArgs,
+    owner_vk: &VerifyingKey,
+    format: OutputFormat,
+    findings: Vec<Finding>,
+) {
+    for finding in findings {
+        let outcome = match finding.action {
+            RuleAction::Ban if args.dry_run => "would ban".to_string(),
+            RuleAction::Ban => {
+                let safety = BanSafety {
+                    require_exact_member_id: true,
+                    require_no_descendants: true,
+                    require_not_deputy: true,
+                };
+                match api
+                    .ban_member_with_safety(
+                        owner_vk,
+                        &finding.member_id.to_string(),
+                        safety,
+                        None,
+                        None,
+                    )
+                    .await
+                {
+                    Ok(()) => "banned".to_string(),
+                    Err(e) => format!("ban failed, reported instead ({e})"),
+                }
+            }
+            RuleAction::Report => "reported".to_string(),
+        };
+        let line = format!(
+            "{} {} {} ({}): {}",
+            chrono::Utc::now().to_rfc3339(),
+            outcome,
+            display_nickname(&finding.nickname),
+            finding.member_id,
+            finding.reason
+        );
+
+        match format {
+            OutputFormat::Human => println!("{}", line),
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::json!({
+                    "member_id": finding.member_id.to_string(),
+                    "nickname": finding.nickname,
+                    "action": finding.action,
+                    "outcome": outcome,
+                    "reason": finding.reason,
+                })
+            ),
+        }
+        if let Some(path) = &args.report_log {
+            if let Err(e) = append_line(path, &line) {
+                eprintln!(
+                    "{} failed to write {}: {}",
+                    "Error:".red(),
+                    path.display(),
+                    e
+                );
+            }
+        }
+        if outcome != "banned" {
+            if let Some(recipient) = &args.report_dm {
+                let room_id = bs58::encode(owner_vk.as_bytes()).into_string();
+                let message = format!("riverctl moderate: {line}");
+                if let Err(e) =
+                    super::dm::execute_send(api, format, &room_id, recipient, &message).await
+                {
+                    eprintln!("{} DM to {} failed: {}", "Error:".red(), recipient, e);
+                }
+            }
+        }
+    }
+}
+
+fn append_line(path: &Path, line: &str) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)
+}
+
+fn parse_room_id(room_id: &str) -> Result<VerifyingKey> {
+    let bytes = bs58::decode(room_id)
+        .into_vec()
+        .map_err(|e| anyhow!("Invalid room ID: {}", e))?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow!("Invalid room ID length"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| anyhow!("Invalid room owner key: {}", e))
+}
@@ -1,9 +1,18 @@
-use crate::api::ApiClient;
+use crate::api::{ApiClient, KnockOutcome};
+use crate::commands::invite::parse_owner_key;
+use crate::commands::message::{message_json, message_line, parse_message_id};
 use crate::output::OutputFormat;
+use crate::room_export::RoomExport;
 use anyhow::Result;
 use clap::Subcommand;
 use colored::Colorize;
+use river_core::room_state::configuration::{PostingPolicy, SlowMode, SlowModeWindow};
+use river_core::room_state::member::MemberId;
+use river_core::room_state::message::MessageId;
 use river_core::room_state::privacy::SealedBytes;
+use river_core::room_state::ChatRoomParametersV1;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 #[derive(Subcommand)]
 pub enum RoomCommands {
@@ -39,12 +48,79 @@ pub enum RoomCommands {
     /// Join a room
     Join {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
     },
-    /// Leave a room
+    /// Ask to join a room without an invitation
+    ///
+    /// Publishes a join request with your nickname and a short message; any
+    /// member can approve or deny it. Run the command again to check: once
+    /// approved it joins the room. The nickname and message are readable by
+    /// anyone who can read the room, even a private one.
+    Knock {
+        /// Room ID (owner key in base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+        /// The nickname you would like (kept from the first request)
+        #[arg(short = 'N', long, default_value = "Anonymous")]
+        nickname: String,
+        /// A short note to the members, e.g. who sent you
+        #[arg(short, long, default_value = "")]
+        message: String,
+    },
+    /// Leave a room.
+    ///
+    /// Posts a signed leave event, which removes you from the member list for
+    /// everyone (and, in a private room, prompts the owner to rotate the room
+    /// secret), then forgets the room's local credentials.
     Leave {
         /// Room ID
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
+        /// Only forget the local credentials; stay listed as a member
+        #[arg(long)]
+        local_only: bool,
+    },
+    /// Page through the room's local message archive.
+    ///
+    /// Every message riverctl has seen is archived locally with its original
+    /// signature, so history stays readable after the room's retention limit
+    /// drops it from the network. Shows the newest page; pass the printed
+    /// `--before` id to see the page before it.
+    History {
+        /// Room owner key (base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+
+        /// Messages per page
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+
+        /// Show the messages before this message ID
+        #[arg(long)]
+        before: Option<String>,
+    },
+    /// Export the room as a signed bundle for archival or audit.
+    ///
+    /// The bundle holds the room state exactly as signed — configuration,
+    /// members with their invite chains back to the owner, member info,
+    /// bans and messages — plus the messages archived locally past the
+    /// retention limit. `room verify-export` re-checks it offline.
+    Export {
+        /// Room owner key (base58)
+        #[arg(value_parser = crate::config::room_arg)]
+        room_id: String,
+
+        /// Write the bundle here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Verify every signature in a bundle written by `room export`.
+    ///
+    /// Works offline. Exits with an error if any record fails.
+    VerifyExport {
+        /// Export file
+        file: PathBuf,
     },
     /// Republish a room to the network
     ///
@@ -53,11 +129,13 @@ pub enum RoomCommands {
     /// served on the network.
     Republish {
         /// Room owner key (base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
     },
     /// Update room configuration (owner only)
     Config {
         /// Room owner key (base58)
+        #[arg(value_parser = crate::config::room_arg)]
         room_id: String,
 
         /// Set room name (sealed automatically in a private room)
@@ -95,9 +173,132 @@ pub enum RoomCommands {
         /// Set maximum room description length
         #[arg(long)]
         max_room_description: Option<usize>,
+
+        /// Limit how many messages each member may have in the room:
+        /// `N` or `N/retained`, `N/SECSs` (e.g. `5/60s`, advisory: it trusts
+        /// message timestamps), or `off`
+        #[arg(long, value_parser = parse_slow_mode)]
+        slow_mode: Option<SlowModeSetting>,
+
+        /// Who may post: `anyone`, `owner`, or a comma-separated list of
+        /// member IDs who may post alongside the owner. Everyone can still react.
+        #[arg(long, value_parser = parse_posting)]
+        posting: Option<PostingSetting>,
     },
 }
 
+/// A parsed `--slow-mode` value; `None` turns slow mode off.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlowModeSetting(Option<SlowMode>);
+
+fn parse_slow_mode(value: &str) -> Result<SlowModeSetting, String> {
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(SlowModeSetting(None));
+    }
+    let usage = || format!("invalid slow mode '{value}': expected N, N/retained, N/SECSs or off");
+    let (count, window) = value.split_once('/').unwrap_or((value, "retained"));
+    let max_messages: u32 = count.trim().parse().map_err(|_| usage())?;
+    let window = match window.trim() {
+        "retained" => SlowModeWindow::Retained,
+        secs => SlowModeWindow::Seconds(
+            secs.strip_suffix('s')
+                .unwrap_or(secs)
+                .parse()
+                .map_err(|_| usage())?,
+        ),
+    };
+    if max_messages == 0 || window == SlowModeWindow::Seconds(0) {
+        return Err(format!(
+            "invalid slow mode '{value}': use `off` rather than a zero budget or window"
+        ));
+    }
+    Ok(SlowModeSetting(Some(SlowMode {
+        max_messages,
+        window,
+    })))
+}
+
+/// A parsed `--posting` value. Member IDs are resolved against the room's
+/// members once its state is loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PostingSetting {
+    Anyone,
+    Owner,
+    Members(Vec<String>),
+}
+
+fn parse_posting(value: &str) -> Result<PostingSetting, String> {
+    match value.trim() {
+        v if v.eq_ignore_ascii_case("anyone") => Ok(PostingSetting::Anyone),
+        v if v.eq_ignore_ascii_case("owner") => Ok(PostingSetting::Owner),
+        v => {
+            let ids: Vec<String> = v
+                .split(',')
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect();
+            if ids.is_empty() {
+                return Err(format!(
+                    "invalid posting '{value}': expected anyone, owner or member IDs"
+                ));
+            }
+            Ok(PostingSetting::Members(ids))
+        }
+    }
+}
+
+/// Resolve each `--posting` member ID (short or full) to a current member.
+fn resolve_posters(
+    room_state: &river_core::ChatRoomStateV1,
+    ids: &[String],
+) -> Result<Vec<MemberId>> {
+    let members: Vec<MemberId> = room_state
+        .members
+        .members
+        .iter()
+        .map(|m| m.member.id())
+        .collect();
+    let mut posters = Vec::new();
+    for short in ids {
+        let matches: Vec<MemberId> = members
+            .iter()
+            .copied()
+            .filter(|id| {
+                let s = id.to_string();
+                s.starts_with(short.as_str()) || s[..8.min(s.len())].eq_ignore_ascii_case(short)
+            })
+            .collect();
+        match matches.as_slice() {
+            [id] => {
+                if !posters.contains(id) {
+                    posters.push(*id);
+                }
+            }
+            [] => {
+                return Err(anyhow::anyhow!(
+                    "Member '{}' not found in room. Use 'member list' to see members.",
+                    short
+                ))
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Member ID '{}' is ambiguous; use more characters.",
+                    short
+                ))
+            }
+        }
+    }
+    Ok(posters)
+}
+
+fn posting_label(policy: Option<&PostingPolicy>) -> String {
+    policy.map_or_else(|| "anyone".to_string(), |p| p.to_string())
+}
+
+fn slow_mode_label(slow_mode: Option<&SlowMode>) -> String {
+    slow_mode.map_or_else(|| "off".to_string(), |s| s.to_string())
+}
+
 /// Build the JSON payload emitted by `room join --format json`.
 ///
 /// `room join` cannot make the caller a member (River requires an
@@ -263,6 +464,8 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
                     println!("{}", "Joining a room requires an invitation.".yellow());
                     println!("Ask an existing member for an invitation code, then run:");
                     println!("  riverctl invite accept <invitation-code>");
+                    println!("Or ask the members to let you in:");
+                    println!("  riverctl room knock {}", room_id);
                 }
                 OutputFormat::Json => {
                     println!(
@@ -273,7 +476,57 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
             }
             Ok(())
         }
-        RoomCommands::Leave { room_id } => {
+        RoomCommands::Knock {
+            room_id,
+            nickname,
+            message,
+        } => {
+            let owner_key = parse_owner_key(&room_id)?;
+            let outcome = api.knock(&owner_key, &nickname, &message).await?;
+            let (status, requester, text) = match outcome {
+                KnockOutcome::Requested(id) => (
+                    "requested",
+                    id,
+                    "Join request sent. Run this command again to check for an answer.",
+                ),
+                KnockOutcome::Waiting(id) => (
+                    "waiting",
+                    id,
+                    "Your join request is still waiting for an answer.",
+                ),
+                KnockOutcome::Joined(id) => (
+                    "joined",
+                    id,
+                    "Your request was approved; you joined the room.",
+                ),
+                KnockOutcome::Denied(id) => ("denied", id, "Your join request was denied."),
+            };
+            match format {
+                OutputFormat::Human => {
+                    let text = match status {
+                        "denied" => text.red(),
+                        "joined" => text.green(),
+                        _ => text.normal(),
+                    };
+                    println!("{} (requester {})", text, requester);
+                }
+                OutputFormat::Json => {
+                    println!(
+                        "{}",
+                        serde_json::json!({
+                            "status": status,
+                            "room_id": room_id,
+                            "requester_id": requester.to_string(),
+                        })
+                    );
+                }
+            }
+            Ok(())
+        }
+        RoomCommands::Leave {
+            room_id,
+            local_only,
+        } => {
             // Parse the room owner key (base58) into a verifying key.
             let owner_bytes = bs58::decode(&room_id)
                 .into_vec()
@@ -286,16 +539,28 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
             )
             .map_err(|e| anyhow::anyhow!("Invalid room owner key: {}", e))?;
 
+            // Announce the departure first: it needs the stored signing key.
+            // A failure leaves the credentials in place so the leave can be
+            // retried (or forced with --local-only).
+            let announced = if !local_only && api.storage().get_room(&owner_key)?.is_some() {
+                api.announce_leave(&owner_key).await?
+            } else {
+                false
+            };
+
             // Forget the locally-stored credentials for this room. This is the
             // deliberate-replace escape hatch for the re-accept guard
             // (freenet/river#308): once removed, `riverctl invite accept` can
-            // store a fresh identity for the same room. Local-only — the room
-            // contract and on-chain membership are untouched.
+            // store a fresh identity for the same room.
             let removed = api.storage().remove_room(&owner_key)?;
 
             match (format, removed) {
                 (OutputFormat::Human, true) => {
-                    println!("{}", "Left room (local credentials removed).".green());
+                    if announced {
+                        println!("{}", "Left room (leave event sent).".green());
+                    } else {
+                        println!("{}", "Left room (local credentials removed).".green());
+                    }
                     println!("To rejoin, accept a fresh invitation:");
                     println!("  riverctl invite accept <invitation-code>");
                 }
@@ -309,6 +574,7 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
                             "status": "success",
                             "room_id": room_id,
                             "removed": removed,
+                            "announced": announced,
                         })
                     );
                 }
@@ -326,6 +592,8 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
             max_nickname_size,
             max_room_name,
             max_room_description,
+            slow_mode,
+            posting,
         } => {
             let has_changes = name.is_some()
                 || description.is_some()
@@ -335,7 +603,9 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
                 || max_message_size.is_some()
                 || max_nickname_size.is_some()
                 || max_room_name.is_some()
-                || max_room_description.is_some();
+                || max_room_description.is_some()
+                || slow_mode.is_some()
+                || posting.is_some();
 
             let owner_bytes = bs58::decode(&room_id)
                 .into_vec()
@@ -379,6 +649,8 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
                 println!("  max_nickname_size: {}", cfg.max_nickname_size);
                 println!("  max_room_name: {}", cfg.max_room_name);
                 println!("  max_room_description: {}", cfg.max_room_description);
+                println!("  slow_mode: {}", slow_mode_label(cfg.slow_mode.as_ref()));
+                println!("  posting: {}", posting_label(cfg.posting_policy.as_ref()));
                 return Ok(());
             }
 
@@ -425,6 +697,20 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
                 (None, None)
             };
 
+            // Outer Option: was --posting passed; inner: the policy (None is
+            // anyone).
+            let posting_policy: Option<Option<PostingPolicy>> = match &posting {
+                None => None,
+                Some(PostingSetting::Anyone) => Some(None),
+                Some(PostingSetting::Owner) => Some(Some(PostingPolicy::OwnerOnly)),
+                Some(PostingSetting::Members(ids)) => {
+                    let state = api.get_room(&owner_key, false).await?;
+                    Some(Some(PostingPolicy::Allowlist(resolve_posters(
+                        &state, ids,
+                    )?)))
+                }
+            };
+
             match api
                 .update_config(&owner_key, |cfg| {
                     if let Some(ref n) = sealed_name {
@@ -454,6 +740,12 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
                     if let Some(v) = max_room_description {
                         cfg.max_room_description = v;
                     }
+                    if let Some(SlowModeSetting(v)) = &slow_mode {
+                        cfg.slow_mode = *v;
+                    }
+                    if let Some(p) = &posting_policy {
+                        cfg.set_posting_policy(p.clone(), SystemTime::now());
+                    }
                 })
                 .await
             {
@@ -488,6 +780,12 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
                             if let Some(v) = max_room_description {
                                 println!("  max_room_description: {}", v);
                             }
+                            if let Some(SlowModeSetting(v)) = &slow_mode {
+                                println!("  slow_mode: {}", slow_mode_label(v.as_ref()));
+                            }
+                            if let Some(p) = &posting_policy {
+                                println!("  posting: {}", posting_label(p.as_ref()));
+                            }
                         }
                         OutputFormat::Json => {
                             println!(
@@ -507,6 +805,126 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
                 }
             }
         }
+        RoomCommands::History {
+            room_id,
+            limit,
+            before,
+        } => {
+            let owner_bytes = bs58::decode(&room_id)
+                .into_vec()
+                .map_err(|e| anyhow::anyhow!("Invalid room ID: {}", e))?;
+            let owner_key = ed25519_dalek::VerifyingKey::from_bytes(
+                owner_bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Invalid room ID length"))?,
+            )
+            .map_err(|e| anyhow::anyhow!("Invalid room owner key: {}", e))?;
+            let before = before.as_deref().map(parse_message_id).transpose()?;
+
+            // Fetching archives whatever is new, so the newest page is current.
+            // The fetched state also supplies nicknames and room secrets for
+            // display; its messages are swapped for the archived ones.
+            let mut room_state = api.get_room(&owner_key, false).await?;
+            let archive = api.storage().load_archive(&owner_key)?;
+            room_state.recent_messages = archive.to_messages();
+            let secrets = api.room_display_secrets(&owner_key, &mut room_state);
+            api.hide_blocked_authors(&owner_key, &mut room_state);
+
+            let messages: Vec<_> = room_state.recent_messages.display_messages().collect();
+            let (page, older) = history_page(&messages, before.as_ref(), limit, |m| m.id())?;
+
+            match format {
+                OutputFormat::Human => {
+                    if page.is_empty() {
+                        println!("No archived messages");
+                    }
+                    for msg in page {
+                        println!("{}", message_line(&room_state, msg, &secrets));
+                    }
+                    if older {
+                        println!(
+                            "{}",
+                            format!(
+                                "Older messages: riverctl room history {} --before {}",
+                                room_id,
+                                page[0].id().0 .0
+                            )
+                            .dimmed()
+                        );
+                    }
+                }
+                OutputFormat::Json => {
+                    let messages: Vec<_> = page
+                        .iter()
+                        .map(|msg| message_json(&room_state, msg, &secrets))
+                        .collect();
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&serde_json::json!({
+                            "messages": messages,
+                            "next_before": older.then(|| page[0].id().0 .0.to_string()),
+                        }))?
+                    );
+                }
+            }
+            Ok(())
+        }
+        RoomCommands::Export { room_id, output } => {
+            let owner_bytes = bs58::decode(&room_id)
+                .into_vec()
+                .map_err(|e| anyhow::anyhow!("Invalid room ID: {}", e))?;
+            let owner_key = ed25519_dalek::VerifyingKey::from_bytes(
+                owner_bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Invalid room ID length"))?,
+            )
+            .map_err(|e| anyhow::anyhow!("Invalid room owner key: {}", e))?;
+
+            // Fetching also archives anything new, so the bundle is current.
+            let room_state = api.get_room(&owner_key, false).await?;
+            let archive = api.storage().load_archive(&owner_key)?;
+            let export = RoomExport::new(
+                ChatRoomParametersV1 { owner: owner_key },
+                room_state,
+                &archive,
+            )?;
+            let json = serde_json::to_string_pretty(&export)?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, json).map_err(|e| {
+                        anyhow::anyhow!("Failed to write {}: {}", path.display(), e)
+                    })?;
+                    match format {
+                        OutputFormat::Human => {
+                            println!(
+                                "{} {} member(s), {} message(s), {} archived message(s) to {}",
+                                "Exported".green(),
+                                export.state.members.members.len(),
+                                export.state.recent_messages.messages.len(),
+                                export.archived_messages.len(),
+                                path.display()
+                            );
+                        }
+                        OutputFormat::Json => {
+                            println!(
+                                "{}",
+                                serde_json::json!({
+                                    "status": "success",
+                                    "room_id": room_id,
+                                    "path": path,
+                                })
+                            );
+                        }
+                    }
+                }
+                None => println!("{}", json),
+            }
+            Ok(())
+        }
+        RoomCommands::VerifyExport { file } => verify_export(&file, format),
         RoomCommands::Republish { room_id } => {
             // Parse the room owner key
             let owner_bytes = bs58::decode(&room_id)
@@ -552,6 +970,73 @@ pub async fn execute(command: RoomCommands, api: ApiClient, format: OutputFormat
     }
 }
 
+/// `room verify-export`: re-check every signature in an export bundle. Needs
+/// no node, so `main` calls it before connecting.
+pub fn verify_export(file: &Path, format: OutputFormat) -> Result<()> {
+    let json = std::fs::read_to_string(file)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", file.display(), e))?;
+    let report = RoomExport::from_json(&json)?.verify();
+
+    match format {
+        OutputFormat::Human => {
+            println!("Room:        {}", report.room_owner);
+            if let Some(exported_at) = report.exported_at {
+                println!("Exported at: {}", exported_at.to_rfc3339());
+            }
+            println!(
+                "Verified {} configuration, {} member(s), {} invite chain(s), {} member info record(s), {} ban(s), {} message(s), {} archived message(s)",
+                report.configuration,
+                report.members,
+                report.invite_chains,
+                report.member_info,
+                report.bans,
+                report.messages,
+                report.archived_messages
+            );
+            for failure in &report.failures {
+                println!("{} {}", "FAILED".red(), failure);
+            }
+            if report.is_valid() {
+                println!("{}", "All signatures verified".green());
+            }
+        }
+        OutputFormat::Json => {
+            let mut value = serde_json::to_value(&report)?;
+            value["valid"] = report.is_valid().into();
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
+    }
+
+    if report.is_valid() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "{} record(s) in {} failed verification",
+            report.failures.len(),
+            file.display()
+        ))
+    }
+}
+
+/// The `limit` messages just before `before` (or the newest `limit`), in
+/// chronological order, and whether any older ones remain.
+fn history_page<'a, T>(
+    messages: &'a [T],
+    before: Option<&MessageId>,
+    limit: usize,
+    id_of: impl Fn(&T) -> MessageId,
+) -> Result<(&'a [T], bool)> {
+    let end = match before {
+        Some(id) => messages
+            .iter()
+            .position(|m| id_of(m) == *id)
+            .ok_or_else(|| anyhow::anyhow!("Message {} is not in the archive", id.0 .0))?,
+        None => messages.len(),
+    };
+    let start = end.saturating_sub(limit);
+    Ok((&messages[start..end], start > 0))
+}
+
 #[derive(serde::Serialize)]
 struct CreateRoomResult {
     room_name: String,
@@ -574,4 +1059,55 @@ mod tests {
         assert!(json["reason"].as_str().unwrap().contains("invitation"));
         assert_eq!(json["hint"], "riverctl invite accept <invitation-code>");
     }
+
+    #[test]
+    fn history_pages_back_from_the_newest() {
+        let id = |n: i64| MessageId(freenet_scaffold::util::FastHash(n));
+        let messages: Vec<i64> = (1..=5).collect();
+        let page = |before: Option<i64>, limit| {
+            history_page(&messages, before.map(id).as_ref(), limit, |n| id(*n))
+                .map(|(page, older)| (page.to_vec(), older))
+        };
+
+        assert_eq!(page(None, 2).unwrap(), (vec![4, 5], true));
+        assert_eq!(page(Some(4), 2).unwrap(), (vec![2, 3], true));
+        assert_eq!(page(Some(2), 2).unwrap(), (vec![1], false));
+        assert_eq!(page(None, 10).unwrap(), (messages.clone(), false));
+        assert!(page(Some(9), 2).is_err());
+    }
+
+    #[test]
+    fn slow_mode_flag_parses_windows_and_off() {
+        let on = |max_messages, window| {
+            Ok(SlowModeSetting(Some(SlowMode {
+                max_messages,
+                window,
+            })))
+        };
+        assert_eq!(parse_slow_mode("5/60s"), on(5, SlowModeWindow::Seconds(60)));
+        assert_eq!(parse_slow_mode("5/60"), on(5, SlowModeWindow::Seconds(60)));
+        assert_eq!(
+            parse_slow_mode("3/retained"),
+            on(3, SlowModeWindow::Retained)
+        );
+        assert_eq!(parse_slow_mode("3"), on(3, SlowModeWindow::Retained));
+        assert_eq!(parse_slow_mode("off"), Ok(SlowModeSetting(None)));
+        for bad in ["0/60s", "5/0s", "0", "five/60s", "5/minute"] {
+            assert!(parse_slow_mode(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn posting_flag_parses_anyone_owner_and_member_lists() {
+        assert_eq!(parse_posting("anyone"), Ok(PostingSetting::Anyone));
+        assert_eq!(parse_posting("Owner"), Ok(PostingSetting::Owner));
+        assert_eq!(
+            parse_posting("ABCDEFGH, 12345678"),
+            Ok(PostingSetting::Members(vec![
+                "ABCDEFGH".to_string(),
+                "12345678".to_string()
+            ]))
+        );
+        assert!(parse_posting(" , ").is_err());
+    }
 }
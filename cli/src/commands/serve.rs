@@ -0,0 +1,665 @@
+use crate::api::{author_member_id, ApiClient};
+use crate::commands::dm::{self, DecryptedDm};
+use crate::commands::invite::{collect_issued_invites, parse_owner_key};
+use crate::commands::member::members_json;
+use crate::commands::message::{message_json, parse_message_id};
+use crate::watch::{watch_rooms, RoomEvent, RoomEvents, EVENT_BUFFER};
+use anyhow::{anyhow, Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use clap::Args;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use river_core::room_state::member::MemberId;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+
+/// Name of the file in the data directory holding the API token when
+/// `--token` is not given.
+const TOKEN_FILE: &str = "serve.token";
+
+/// Serve a local HTTP API over one node connection: REST endpoints for
+/// rooms, messages, members, DMs and invites, and the live room events (see
+/// `watch.rs`) as Server-Sent Events or over a WebSocket.
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7510")]
+    pub listen: SocketAddr,
+    /// Token clients must present; defaults to the one in `serve.token` in the
+    /// data directory, created on first start
+    #[arg(long, env = "RIVERCTL_SERVE_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+    /// Allow a non-loopback --listen address. The API is plain HTTP, so the
+    /// token crosses the network in the clear.
+    #[arg(long)]
+    pub allow_remote: bool,
+    /// Seconds between full re-reads of every room, which pick up a change
+    /// whose notification was missed
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+    pub resync_secs: u64,
+}
+
+pub async fn execute(args: ServeArgs, api: ApiClient) -> Result<()> {
+    if !args.listen.ip().is_loopback() && !args.allow_remote {
+        return Err(anyhow!(
+            "Refusing to listen on {}: the API is plain HTTP. Pass --allow-remote if that \
+             network is trusted.",
+            args.listen
+        ));
+    }
+    let token = match args.token {
+        Some(token) if token.trim().is_empty() => return Err(anyhow!("The API token is empty")),
+        Some(token) => token,
+        None => load_or_create_token(api.storage().data_dir())?,
+    };
+
+    let api = Arc::new(api);
+    let (events, _) = broadcast::channel(EVENT_BUFFER);
+    let (joined, joined_rx) = mpsc::unbounded_channel();
+    let gateway = Arc::new(Gateway {
+        api: api.clone(),
+        token,
+        events: events.clone(),
+        joined,
+    });
+
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("failed to listen on {}", args.listen))?;
+    eprintln!(
+        "Serving the River API on http://{} (Ctrl+C to stop)",
+        listener.local_addr()?
+    );
+
+    let server = axum::serve(listener, router(gateway)).with_graceful_shutdown(async {
+        let _ = tokio::signal::ctrl_c().await;
+    });
+    tokio::select! {
+        served = server => served.context("HTTP server failed"),
+        watched = watch_rooms(&api, &events, joined_rx, Duration::from_secs(args.resync_secs)) => {
+            watched
+        }
+    }
+}
+
+/// Shared by every request handler.
+struct Gateway {
+    api: Arc<ApiClient>,
+    token: String,
+    events: RoomEvents,
+    /// Rooms joined through the API, for the watcher to start following.
+    joined: mpsc::UnboundedSender<VerifyingKey>,
+}
+
+impl Gateway {
+    /// The room a path names (owner key or `config.toml` alias), with the
+    /// key we sign as in it.
+    fn member_of(&self, room: &str) -> Result<(VerifyingKey, SigningKey), ApiError> {
+        let room = crate::config::room_arg(room).unwrap_or_else(|room| room);
+        let owner_vk = parse_owner_key(&room).map_err(ApiError::bad_request)?;
+        let (signing_key, _, _) = self
+            .api
+            .storage()
+            .get_room(&owner_vk)?
+            .ok_or_else(|| ApiError::not_found("You are not a member of this room"))?;
+        Ok((owner_vk, signing_key))
+    }
+}
+
+fn router(gateway: Arc<Gateway>) -> Router {
+    Router::new()
+        .route("/v1/rooms", get(list_rooms))
+        .route(
+            "/v1/rooms/{room}/messages",
+            get(list_messages).post(send_message),
+        )
+        .route(
+            "/v1/rooms/{room}/messages/{message}",
+            delete(delete_message),
+        )
+        .route(
+            "/v1/rooms/{room}/messages/{message}/reactions",
+            post(add_reaction),
+        )
+        .route(
+            "/v1/rooms/{room}/messages/{message}/reactions/{emoji}",
+            delete(remove_reaction),
+        )
+        .route("/v1/rooms/{room}/members", get(list_members))
+        .route("/v1/rooms/{room}/dms", get(list_dms).post(send_dm))
+        .route(
+            "/v1/rooms/{room}/invites",
+            get(list_invites).post(create_invite),
+        )
+        .route(
+            "/v1/rooms/{room}/invites/{invitation}",
+            delete(revoke_invite),
+        )
+        .route("/v1/invites/accept", post(accept_invite))
+        .route("/v1/events", get(sse_events))
+        .route("/v1/events/ws", get(ws_events))
+        .route_layer(middleware::from_fn_with_state(
+            gateway.clone(),
+            authenticate,
+        ))
+        .with_state(gateway)
+}
+
+/// An error response: `{"error": "..."}` with a status saying whose fault it
+/// was.
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(e: impl std::fmt::Display) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: e.to_string(),
+        }
+    }
+
+    fn not_found(message: &str) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Anything that fails past request validation was refused by the room or
+/// the node, so it is a gateway error rather than the client's.
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self {
+            status: StatusCode::BAD_GATEWAY,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+type ApiResult = Result<Json<Value>, ApiError>;
+
+fn success() -> ApiResult {
+    Ok(Json(json!({ "status": "success" })))
+}
+
+/// Requires the token as `Authorization: Bearer <token>`, or as a `token`
+/// query parameter for clients that cannot set headers (a browser's
+/// `EventSource` and `WebSocket`).
+async fn authenticate(
+    State(gateway): State<Arc<Gateway>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match presented_token(request.headers(), request.uri().query()) {
+        Some(token) if tokens_match(&token, &gateway.token) => next.run(request).await,
+        _ => ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            message: "Missing or wrong API token".to_string(),
+        }
+        .into_response(),
+    }
+}
+
+fn presented_token(headers: &HeaderMap, query: Option<&str>) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    bearer.or_else(|| {
+        url::form_urlencoded::parse(query?.as_bytes())
+            .find(|(name, _)| name == "token")
+            .map(|(_, token)| token.into_owned())
+    })
+}
+
+/// Compares every byte whatever the first mismatch, so response timing does
+/// not reveal how much of a guess was right.
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Read the token from `serve.token` in `data_dir`, creating the file with a
+/// fresh random token (readable only by the owner) if there is none.
+pub(crate) fn load_or_create_token(data_dir: &std::path::Path) -> Result<String> {
+    let path = data_dir.join(TOKEN_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(token) if token.trim().is_empty() => {
+            return Err(anyhow!("{} is empty", path.display()));
+        }
+        Ok(token) => return Ok(token.trim().to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    }
+
+    std::fs::create_dir_all(data_dir)
+        .with_context(|| format!("failed to create {}", data_dir.display()))?;
+    let token: String = rand::random::<[u8; 32]>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&path)
+        .and_then(|mut file| file.write_all(token.as_bytes()))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    eprintln!("Created an API token in {}", path.display());
+    Ok(token)
+}
+
+async fn list_rooms(State(gateway): State<Arc<Gateway>>) -> ApiResult {
+    let rooms: Vec<Value> = gateway
+        .api
+        .list_rooms()
+        .await?
+        .into_iter()
+        .map(|room| {
+            json!({
+                "name": room.name,
+                "owner_key": bs58::encode(room.owner_vk.as_bytes()).into_string(),
+                "contract_key": room.contract_key,
+                "self_member_id": room.self_identity.member_id.to_string(),
+            })
+        })
+        .collect();
+    Ok(Json(Value::from(rooms)))
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    /// Newest messages to return; 50 when absent.
+    limit: Option<usize>,
+}
+
+async fn list_messages(
+    State(gateway): State<Arc<Gateway>>,
+    Path(room): Path<String>,
+    Query(query): Query<ListQuery>,
+) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    let mut room_state = gateway.api.get_room(&owner_vk, false).await?;
+    let secrets = gateway.api.room_display_secrets(&owner_vk, &mut room_state);
+    gateway.api.hide_blocked_authors(&owner_vk, &mut room_state);
+    let mut messages: Vec<_> = room_state.recent_messages.display_messages().collect();
+    messages.sort_by_key(|msg| msg.message.time);
+    let start = messages.len().saturating_sub(query.limit.unwrap_or(50));
+    let messages: Vec<Value> = messages[start..]
+        .iter()
+        .map(|msg| message_json(&room_state, msg, &secrets))
+        .collect();
+    Ok(Json(Value::from(messages)))
+}
+
+#[derive(Deserialize)]
+struct NewMessage {
+    text: String,
+    /// Message ID to reply to.
+    reply_to: Option<String>,
+}
+
+async fn send_message(
+    State(gateway): State<Arc<Gateway>>,
+    Path(room): Path<String>,
+    Json(body): Json<NewMessage>,
+) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    match body.reply_to {
+        Some(target) => {
+            let target = parse_message_id(&target).map_err(ApiError::bad_request)?;
+            gateway.api.send_reply(&owner_vk, target, body.text).await?
+        }
+        None => gateway.api.send_message(&owner_vk, body.text).await?,
+    }
+    success()
+}
+
+async fn delete_message(
+    State(gateway): State<Arc<Gateway>>,
+    Path((room, message)): Path<(String, String)>,
+) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    let target = parse_message_id(&message).map_err(ApiError::bad_request)?;
+    gateway.api.delete_message(&owner_vk, target).await?;
+    success()
+}
+
+#[derive(Deserialize)]
+struct NewReaction {
+    emoji: String,
+}
+
+async fn add_reaction(
+    State(gateway): State<Arc<Gateway>>,
+    Path((room, message)): Path<(String, String)>,
+    Json(body): Json<NewReaction>,
+) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    let target = parse_message_id(&message).map_err(ApiError::bad_request)?;
+    gateway
+        .api
+        .add_reaction(&owner_vk, target, body.emoji)
+        .await?;
+    success()
+}
+
+async fn remove_reaction(
+    State(gateway): State<Arc<Gateway>>,
+    Path((room, message, emoji)): Path<(String, String, String)>,
+) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    let target = parse_message_id(&message).map_err(ApiError::bad_request)?;
+    gateway
+        .api
+        .remove_reaction(&owner_vk, target, emoji)
+        .await?;
+    success()
+}
+
+async fn list_members(State(gateway): State<Arc<Gateway>>, Path(room): Path<String>) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    let mut room_state = gateway.api.get_room(&owner_vk, false).await?;
+    let secrets = gateway.api.room_display_secrets(&owner_vk, &mut room_state);
+    Ok(Json(Value::from(members_json(
+        &room_state,
+        &owner_vk,
+        &secrets,
+    ))))
+}
+
+/// Every DM thread you are part of in the room, as `dm list` shows them but
+/// without its per-thread cap.
+async fn list_dms(State(gateway): State<Arc<Gateway>>, Path(room): Path<String>) -> ApiResult {
+    let (owner_vk, signing_key) = gateway.member_of(&room)?;
+    let mut room_state = gateway.api.get_room(&owner_vk, false).await?;
+    let secrets = gateway.api.room_display_secrets(&owner_vk, &mut room_state);
+    let nicknames = dm::member_nicknames(&room_state, &secrets);
+    let inbox = dm::sync_inbox(&gateway.api, &owner_vk, &signing_key, &room_state).await?;
+
+    let mut threads: BTreeMap<MemberId, Vec<DecryptedDm>> = BTreeMap::new();
+    for dm in dm::decrypt_dms(
+        &gateway.api,
+        &owner_vk,
+        &signing_key,
+        &room_state,
+        &inbox,
+        &nicknames,
+    ) {
+        threads.entry(dm.counterparty).or_default().push(dm);
+    }
+    let threads: Vec<Value> = threads
+        .into_iter()
+        .map(|(peer, mut dms)| {
+            dms.sort_by_key(|dm| dm.timestamp);
+            dm::dm_thread_json(
+                peer,
+                nicknames.get(&peer).cloned(),
+                inbox.receipts_from(peer),
+                &dms,
+            )
+        })
+        .collect();
+    Ok(Json(Value::from(threads)))
+}
+
+#[derive(Deserialize)]
+struct NewDm {
+    /// Member ID, short or full.
+    recipient: String,
+    text: String,
+}
+
+async fn send_dm(
+    State(gateway): State<Arc<Gateway>>,
+    Path(room): Path<String>,
+    Json(body): Json<NewDm>,
+) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    let sent = dm::send_text_dm(&gateway.api, &owner_vk, &body.recipient, &body.text).await?;
+    Ok(Json(dm::dm_sent_json(&sent)))
+}
+
+async fn list_invites(State(gateway): State<Arc<Gateway>>, Path(room): Path<String>) -> ApiResult {
+    let (owner_vk, signing_key) = gateway.member_of(&room)?;
+    let mut room_state = gateway.api.get_room(&owner_vk, false).await?;
+    let secrets = gateway.api.room_display_secrets(&owner_vk, &mut room_state);
+    let invites = collect_issued_invites(
+        &room_state,
+        author_member_id(&signing_key),
+        &secrets,
+        SystemTime::now(),
+    );
+    Ok(Json(
+        serde_json::to_value(invites).map_err(anyhow::Error::from)?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct NewInvite {
+    expires_in_secs: Option<u64>,
+    label: Option<String>,
+    max_uses: Option<u32>,
+}
+
+async fn create_invite(
+    State(gateway): State<Arc<Gateway>>,
+    Path(room): Path<String>,
+    Json(body): Json<NewInvite>,
+) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    let invitation_code = gateway
+        .api
+        .create_invitation(
+            &owner_vk,
+            body.expires_in_secs.map(Duration::from_secs),
+            body.label.as_deref(),
+            body.max_uses,
+        )
+        .await?;
+    Ok(Json(json!({
+        "status": "success",
+        "invitation_code": invitation_code,
+    })))
+}
+
+async fn revoke_invite(
+    State(gateway): State<Arc<Gateway>>,
+    Path((room, invitation)): Path<(String, String)>,
+) -> ApiResult {
+    let (owner_vk, _) = gateway.member_of(&room)?;
+    let invitee = gateway
+        .api
+        .revoke_invitation(&owner_vk, &invitation)
+        .await?;
+    Ok(Json(json!({
+        "status": "success",
+        "invitee_id": invitee.to_string(),
+    })))
+}
+
+#[derive(Deserialize)]
+struct AcceptInvite {
+    invitation_code: String,
+    nickname: Option<String>,
+}
+
+async fn accept_invite(
+    State(gateway): State<Arc<Gateway>>,
+    Json(body): Json<AcceptInvite>,
+) -> ApiResult {
+    let nickname = body.nickname.unwrap_or_else(|| "Anonymous".to_string());
+    let (owner_vk, contract_key) = gateway
+        .api
+        .accept_invitation(&body.invitation_code, &nickname)
+        .await?;
+    let _ = gateway.joined.send(owner_vk);
+    Ok(Json(json!({
+        "status": "success",
+        "room_owner_key": bs58::encode(owner_vk.as_bytes()).into_string(),
+        "contract_key": contract_key.id().to_string(),
+    })))
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    /// Only this room's events; every room's when absent. An owner key or a
+    /// `config.toml` alias, resolved by [`Gateway::member_of`] like `{room}`.
+    room: Option<String>,
+}
+
+impl EventsQuery {
+    fn filter(&self, gateway: &Gateway) -> Result<Option<VerifyingKey>, ApiError> {
+        self.room
+            .as_deref()
+            .map(|room| gateway.member_of(room).map(|(owner_vk, _)| owner_vk))
+            .transpose()
+    }
+}
+
+async fn sse_events(
+    State(gateway): State<Arc<Gateway>>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Response, ApiError> {
+    let filter = query.filter(&gateway)?;
+    let receiver = gateway.events.subscribe();
+    let stream = futures::stream::unfold(receiver, move |mut receiver| async move {
+        let json = next_event(&mut receiver, filter).await?;
+        Some((Ok::<_, Infallible>(Event::default().data(json)), receiver))
+    });
+    Ok(Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+async fn ws_events(
+    State(gateway): State<Arc<Gateway>>,
+    Query(query): Query<EventsQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let filter = query.filter(&gateway)?;
+    let receiver = gateway.events.subscribe();
+    Ok(upgrade.on_upgrade(move |socket| forward_events(socket, receiver, filter)))
+}
+
+async fn forward_events(
+    mut socket: WebSocket,
+    mut receiver: broadcast::Receiver<Arc<RoomEvent>>,
+    filter: Option<VerifyingKey>,
+) {
+    loop {
+        tokio::select! {
+            event = next_event(&mut receiver, filter) => {
+                let Some(json) = event else { break };
+                if socket.send(Message::Text(json.into())).await.is_err() {
+                    break;
+                }
+            }
+            // Nothing is expected from the client; reading only notices it
+            // leaving.
+            incoming = socket.recv() => {
+                if matches!(incoming, None | Some(Err(_)) | Some(Ok(Message::Close(_)))) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// The next event for a stream client, or `None` once the gateway stops. A
+/// client that fell more than [`EVENT_BUFFER`] events behind is told how many
+/// it missed, so it can re-read what it needs.
+async fn next_event(
+    receiver: &mut broadcast::Receiver<Arc<RoomEvent>>,
+    filter: Option<VerifyingKey>,
+) -> Option<String> {
+    loop {
+        match receiver.recv().await {
+            Ok(event) if filter.is_none_or(|room| room == event.room) => {
+                return Some(event.event.to_string())
+            }
+            Ok(_) => continue,
+            Err(RecvError::Lagged(missed)) => {
+                return Some(json!({ "type": "lagged", "missed": missed }).to_string())
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_is_taken_from_the_header_or_the_query() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(presented_token(&headers, None), None);
+        assert_eq!(
+            presented_token(&headers, Some("room=abc&token=s3cret")).as_deref(),
+            Some("s3cret")
+        );
+        headers.insert(header::AUTHORIZATION, "Bearer h3ader".parse().unwrap());
+        assert_eq!(
+            presented_token(&headers, Some("token=s3cret")).as_deref(),
+            Some("h3ader")
+        );
+
+        assert!(tokens_match("abc", "abc"));
+        assert!(!tokens_match("abd", "abc"));
+        assert!(!tokens_match("ab", "abc"));
+        assert!(!tokens_match("", "abc"));
+    }
+
+    #[test]
+    fn token_file_is_created_once_and_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let token = load_or_create_token(dir.path()).unwrap();
+        assert_eq!(token.len(), 64);
+        assert_eq!(load_or_create_token(dir.path()).unwrap(), token);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.path().join(TOKEN_FILE))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::write(dir.path().join(TOKEN_FILE), "\n").unwrap();
+        assert!(load_or_create_token(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::api::{ApiClient, RoomSubscription};
+use crate::output::OutputFormat;
+use crate::tui::{Action, App, Frame, RoomEntry, RoomView};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use console::Term;
+use ed25519_dalek::VerifyingKey;
+use river_core::room_state::member::MemberId;
+
+/// Full-screen chat client: room list, conversation, composer and member
+/// panel, updated live from room subscriptions. See `tui.rs` for the keys.
+#[derive(Args)]
+pub struct TuiArgs {
+    /// Room to open first (room owner key or alias); defaults to the first
+    /// room you joined
+    #[arg(value_parser = crate::config::room_arg)]
+    pub room_id: Option<String>,
+}
+
+pub async fn execute(args: TuiArgs, api: ApiClient) -> Result<()> {
+    let term = Term::stdout();
+    if !term.is_term() {
+        return Err(anyhow!("riverctl tui needs an interactive terminal"));
+    }
+
+    let listings = api.list_rooms().await?;
+    if listings.is_empty() {
+        return Err(anyhow!(
+            "You are not in any room yet. Create one with `riverctl room create` \
+             or join one with `riverctl invite accept`."
+        ));
+    }
+    let current = match &args.room_id {
+        Some(room_id) => {
+            let owner_vk = parse_room_id(room_id)?;
+            listings
+                .iter()
+                .position(|room| room.owner_vk == owner_vk)
+                .ok_or_else(|| anyhow!("Room not found. You must be a member of it."))?
+        }
+        None => 0,
+    };
+    let rooms = listings
+        .into_iter()
+        .map(|room| RoomEntry {
+            owner_vk: room.owner_vk,
+            name: room.name,
+            view: None,
+            unread: 0,
+        })
+        .collect();
+    let mut app = App::new(rooms, current);
+
+    let _screen = Screen::enter(&term)?;
+    let mut renderer = Renderer::default();
+
+    // Keys are read on a thread because `Term::read_key` blocks.
+    let (key_tx, mut keys) = tokio::sync::mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        let term = Term::stdout();
+        while let Ok(key) = term.read_key_raw() {
+            if key_tx.send(key).is_err() {
+                break;
+            }
+        }
+    });
+    let mut ctrl_c = Box::pin(tokio::signal::ctrl_c());
+
+    renderer.draw(&term, &app)?;
+    refresh(&api, &mut app, current).await;
+    renderer.draw(&term, &app)?;
+
+    // One subscription for every room: they share the connection, and the
+    // notification names the contract that changed.
+    let mut subscription: Option<RoomSubscription> = None;
+    for room in &app.rooms {
+        match api
+            .subscribe_to_room(&room.owner_vk, OutputFormat::Json)
+            .await
+        {
+            Ok(sub) => match &mut subscription {
+                Some(all) => all.absorb(sub),
+                None => subscription = Some(sub),
+            },
+            Err(e) => tracing::warn!("Failed to subscribe to {}: {}", room.name, e),
+        }
+    }
+    let Some(mut subscription) = subscription else {
+        return Err(anyhow!("Could not subscribe to any of your rooms"));
+    };
+
+    loop {
+        renderer.draw(&term, &app)?;
+        tokio::select! {
+            key = keys.recv() => {
+                let Some(key) = key else { break };
+                let action = app.key(key);
+                if action == Action::Quit {
+                    break;
+                }
+                perform(&api, &mut app, &mut renderer, action).await;
+            }
+            changed = api.next_contract_update(&mut subscription) => {
+                if let Some(contract) = changed? {
+                    let index = app.rooms.iter().position(|room| {
+                        *api.owner_vk_to_contract_key(&room.owner_vk).id() == contract
+                    });
+                    if let Some(index) = index {
+                        refresh(&api, &mut app, index).await;
+                    }
+                }
+            }
+            _ = &mut ctrl_c => break,
+        }
+    }
+    Ok(())
+}
+
+async fn perform(api: &ApiClient, app: &mut App, renderer: &mut Renderer, action: Action) {
+    let room = app.current_room().owner_vk;
+    let result = match action {
+        Action::None | Action::Quit => return,
+        Action::Redraw => {
+            renderer.invalidate();
+            return;
+        }
+        Action::Open(_) => {
+            refresh(api, app, app.current).await;
+            return;
+        }
+        Action::Send(text) => api.send_message(&room, text).await,
+        Action::Reply { target, text } => api.send_reply(&room, target, text).await,
+        Action::React { target, emoji } => api.add_reaction(&room, target, emoji).await,
+        Action::Unreact { target, emoji } => api.remove_reaction(&room, target, emoji).await,
+        Action::Dm { recipient, text } => {
+            crate::commands::dm::send_text_dm(api, &room, &recipient, &text)
+                .await
+                .map(|_| ())
+        }
+    };
+    match result {
+        Ok(()) => refresh(api, app, app.current).await,
+        Err(e) => app.status = format!("Error: {e}"),
+    }
+}
+
+/// Re-fetch room `index` and hand the new view to the app. A failure only
+/// reaches the status line: one unreachable room must not end the session.
+async fn refresh(api: &ApiClient, app: &mut App, index: usize) {
+    let owner_vk = app.rooms[index].owner_vk;
+    match load_view(api, &owner_vk).await {
+        Ok(view) => app.set_view(index, view),
+        Err(e) => app.status = format!("Could not load {}: {e}", app.rooms[index].name),
+    }
+}
+
+async fn load_view(api: &ApiClient, owner_vk: &VerifyingKey) -> Result<RoomView> {
+    let (signing_key, _, _) = api
+        .storage()
+        .get_room(owner_vk)?
+        .ok_or_else(|| anyhow!("Room not found in local storage"))?;
+    let mut room_state = api.get_room(owner_vk, false).await?;
+    let secrets = api.room_display_secrets(owner_vk, &mut room_state);
+    api.hide_blocked_authors(owner_vk, &mut room_state);
+    let view = RoomView::build(
+        &room_state,
+        owner_vk,
+        MemberId::from(&signing_key.verifying_key()),
+        &secrets,
+    );
+    let inbox = crate::commands::dm::sync_inbox(api, owner_vk, &signing_key, &room_state).await?;
+    let dms = crate::commands::dm::decrypt_dms(
+        api,
+        owner_vk,
+        &signing_key,
+        &room_state,
+        &inbox,
+        &view.nicknames(),
+    );
+    Ok(view.with_dms(dms))
+}
+
+/// Redraws only the rows that changed since the last frame, which keeps the
+/// client usable over a slow SSH link.
+#[derive(Default)]
+struct Renderer {
+    last: Vec<String>,
+    size: (u16, u16),
+}
+
+impl Renderer {
+    fn invalidate(&mut self) {
+        self.last.clear();
+    }
+
+    fn draw(&mut self, term: &Term, app: &App) -> Result<()> {
+        let size = term.size();
+        if size != self.size {
+            self.size = size;
+            self.invalidate();
+        }
+        let (height, width) = (size.0 as usize, size.1 as usize);
+        let Frame { rows, cursor } = app.render(width, height);
+
+        let mut out = String::new();
+        if self.last.is_empty() {
+            out.push_str("\x1b[2J");
+        }
+        for (i, row) in rows.iter().enumerate() {
+            if self.last.get(i) != Some(row) {
+                out.push_str(&format!("\x1b[{};1H{}", i + 1, row));
+            }
+        }
+        out.push_str(&format!("\x1b[{};{}H", cursor.1 + 1, cursor.0 + 1));
+        term.write_str(&out)?;
+        term.flush()?;
+        self.last = rows;
+        Ok(())
+    }
+}
+
+/// The alternate screen with echo off, restored on drop however the session
+/// ends.
+struct Screen {
+    term: Term,
+    #[cfg(unix)]
+    saved: Option<libc::termios>,
+}
+
+impl Screen {
+    fn enter(term: &Term) -> Result<Self> {
+        term.write_str("\x1b[?1049h")?;
+        Ok(Self {
+            term: term.clone(),
+            // Keys typed while a send is in flight would otherwise echo over
+            // the screen: `read_key` only turns echo off while it reads.
+            #[cfg(unix)]
+            saved: disable_echo(),
+        })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(saved) = &self.saved {
+            // SAFETY: restoring attributes read from the same descriptor.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, saved);
+            }
+        }
+        let _ = self.term.write_str("\x1b[?1049l");
+        let _ = self.term.show_cursor();
+    }
+}
+
+#[cfg(unix)]
+fn disable_echo() -> Option<libc::termios> {
+    // SAFETY: `termios` is plain data filled in by `tcgetattr`.
+    unsafe {
+        let mut saved: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut saved) != 0 {
+            return None;
+        }
+        let mut quiet = saved;
+        quiet.c_lflag &= !libc::ECHO;
+        libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &quiet);
+        Some(saved)
+    }
+}
+
+fn parse_room_id(room_id: &str) -> Result<VerifyingKey> {
+    let bytes = bs58::decode(room_id)
+        .into_vec()
+        .map_err(|e| anyhow!("Invalid room ID: {}", e))?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow!("Invalid room ID length"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| anyhow!("Invalid room owner key: {}", e))
+}
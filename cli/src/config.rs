@@ -1,15 +1,294 @@
-use anyhow::Result;
+//! The optional `config.toml`: named profiles and room aliases.
+//!
+//! ```toml
+//! default_profile = "bot"
+//!
+//! [profiles.bot]
+//! node_url = "ws://10.0.0.5:7509/v1/contract/command?encodingProtocol=native"
+//! signing_key_file = "/etc/river/bot.key"
+//! format = "json"
+//! config_dir = "/var/lib/river-bot"
+//!
+//! [rooms]
+//! general = "<room owner key in base58>"
+//! ```
+//!
+//! The file is read from the directory riverctl stores rooms in when no
+//! profile is selected: `--config-dir`, else `RIVER_CONFIG_DIR`, else the
+//! platform data directory. A profile's `config_dir` only moves room storage;
+//! the file itself is not looked for there. Command-line flags and their
+//! environment variables override the selected profile, which overrides the
+//! built-in defaults.
+
+use crate::output::OutputFormat;
+use anyhow::{anyhow, Context, Result};
+use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Node URL used when neither `--node-url` nor the profile sets one.
+pub const DEFAULT_NODE_URL: &str =
+    "ws://127.0.0.1:7509/v1/contract/command?encodingProtocol=native";
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
-    pub default_room: Option<String>,
-    pub user_key_path: Option<String>,
+    /// Profile used when `--profile` is not given.
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Room aliases: name to room owner key (base58).
+    #[serde(default)]
+    pub rooms: BTreeMap<String, String>,
+}
+
+/// Settings a profile can supply. Every field is optional; an unset one falls
+/// back to the built-in default.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub node_url: Option<String>,
+    pub signing_key_file: Option<PathBuf>,
+    /// `human` or `json`.
+    pub format: Option<String>,
+    pub config_dir: Option<String>,
 }
 
 impl Config {
-    pub fn load() -> Result<Self> {
-        // TODO: Load from config file
-        Ok(Self::default())
+    /// Read `config.toml` from `config_dir` (see the module docs for the
+    /// fallback). A missing file is an empty configuration; an unreadable or
+    /// invalid one is an error, so a typo is not silently ignored.
+    pub fn load(config_dir: Option<&str>) -> Result<Self> {
+        let Some(path) = config_path(config_dir) else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let config: Config = toml::from_str(contents).map_err(|e| anyhow!("{}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(name) = &self.default_profile {
+            if !self.profiles.contains_key(name) {
+                return Err(anyhow!(
+                    "default_profile '{}' is not defined under [profiles]",
+                    name
+                ));
+            }
+        }
+        for (name, profile) in &self.profiles {
+            if let Some(format) = &profile.format {
+                format
+                    .parse::<OutputFormat>()
+                    .map_err(|e| anyhow!("profile '{}': {}", name, e))?;
+            }
+        }
+        for (alias, key) in &self.rooms {
+            // An alias that is itself a room key would shadow that room.
+            if is_room_key(alias) {
+                return Err(anyhow!(
+                    "room alias '{}' is itself a room key; pick a name",
+                    alias
+                ));
+            }
+            if !is_room_key(key) {
+                return Err(anyhow!(
+                    "room alias '{}' does not map to a room owner key (base58, 32 bytes)",
+                    alias
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The profile named `name`, else the `default_profile`, else an empty
+    /// one. Naming a profile the file does not define is an error.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile> {
+        match name.or(self.default_profile.as_deref()) {
+            Some(name) => self.profiles.get(name).cloned().ok_or_else(|| {
+                let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                anyhow!(
+                    "profile '{}' is not defined in {}{}",
+                    name,
+                    CONFIG_FILE_NAME,
+                    if known.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" (known: {})", known.join(", "))
+                    }
+                )
+            }),
+            None => Ok(Profile::default()),
+        }
+    }
+}
+
+impl Profile {
+    pub fn output_format(&self) -> Option<OutputFormat> {
+        // Checked by `Config::validate`.
+        self.format.as_deref().and_then(|f| f.parse().ok())
+    }
+}
+
+/// Where `config.toml` is looked for, mirroring how `Storage` picks its
+/// directory.
+pub fn config_path(config_dir: Option<&str>) -> Option<PathBuf> {
+    let dir = if let Some(dir) = config_dir {
+        PathBuf::from(dir)
+    } else if let Ok(dir) = std::env::var("RIVER_CONFIG_DIR") {
+        PathBuf::from(dir)
+    } else {
+        ProjectDirs::from("", "Freenet", "River")?
+            .data_dir()
+            .to_path_buf()
+    };
+    Some(dir.join(CONFIG_FILE_NAME))
+}
+
+fn is_room_key(value: &str) -> bool {
+    bs58::decode(value)
+        .into_vec()
+        .is_ok_and(|bytes| bytes.len() == 32)
+}
+
+/// The value of `--config-dir` in `args` (`--config-dir DIR` or
+/// `--config-dir=DIR`, before any `--`), so `config.toml` can be loaded
+/// before the command line is parsed.
+pub fn config_dir_flag<S: AsRef<OsStr>>(args: &[S]) -> Option<String> {
+    let mut args = args
+        .iter()
+        .map(|arg| arg.as_ref().to_string_lossy())
+        .take_while(|arg| arg != "--");
+    let mut dir = None;
+    while let Some(arg) = args.next() {
+        if arg == "--config-dir" {
+            dir = args.next().map(|value| value.into_owned());
+        } else if let Some(value) = arg.strip_prefix("--config-dir=") {
+            dir = Some(value.to_string());
+        }
+    }
+    dir
+}
+
+static ROOM_ALIASES: OnceLock<BTreeMap<String, String>> = OnceLock::new();
+
+/// Make `aliases` the table [`room_arg`] resolves against. Only the first
+/// call takes effect.
+pub fn set_room_aliases(aliases: BTreeMap<String, String>) {
+    let _ = ROOM_ALIASES.set(aliases);
+}
+
+/// Clap value parser for every argument that takes a room: an alias from
+/// `[rooms]` becomes its room key, anything else passes through unchanged.
+pub fn room_arg(value: &str) -> std::result::Result<String, String> {
+    Ok(resolve_room_alias(ROOM_ALIASES.get(), value))
+}
+
+fn resolve_room_alias(aliases: Option<&BTreeMap<String, String>>, value: &str) -> String {
+    aliases
+        .and_then(|aliases| aliases.get(value))
+        .cloned()
+        .unwrap_or_else(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOM: &str = "6M6jdvazf6HBYJnjPQq5bkfbwQ8ehGE7Af5E6vw5XnMu";
+
+    #[test]
+    fn profiles_and_aliases_parse_and_resolve() {
+        let config = Config::parse(&format!(
+            r#"
+            default_profile = "bot"
+
+            [profiles.bot]
+            node_url = "ws://10.0.0.5:7509/v1/contract/command"
+            signing_key_file = "/etc/river/bot.key"
+            format = "json"
+
+            [profiles.local]
+            config_dir = "/tmp/river"
+
+            [rooms]
+            general = "{ROOM}"
+            "#
+        ))
+        .unwrap();
+
+        let bot = config.profile(None).unwrap();
+        assert_eq!(
+            bot.node_url.as_deref(),
+            Some("ws://10.0.0.5:7509/v1/contract/command")
+        );
+        assert_eq!(
+            bot.signing_key_file,
+            Some(PathBuf::from("/etc/river/bot.key"))
+        );
+        assert!(matches!(bot.output_format(), Some(OutputFormat::Json)));
+
+        let local = config.profile(Some("local")).unwrap();
+        assert_eq!(local.config_dir.as_deref(), Some("/tmp/river"));
+        assert_eq!(local.node_url, None);
+        assert!(config.profile(Some("missing")).is_err());
+        assert_eq!(Config::default().profile(None).unwrap(), Profile::default());
+
+        assert_eq!(resolve_room_alias(Some(&config.rooms), "general"), ROOM);
+        assert_eq!(resolve_room_alias(Some(&config.rooms), ROOM), ROOM);
+        assert_eq!(resolve_room_alias(None, "general"), "general");
+    }
+
+    #[test]
+    fn mistakes_in_the_file_are_errors() {
+        for bad in [
+            "default_profile = \"nope\"",
+            "[profiles.a]\nformat = \"yaml\"",
+            "[profiles.a]\nnode = \"typo\"",
+            "[rooms]\ngeneral = \"not-a-key\"",
+            &format!("[rooms]\n{ROOM} = \"{ROOM}\""),
+        ] {
+            assert!(Config::parse(bad).is_err(), "accepted: {bad}");
+        }
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn config_dir_is_read_from_the_arguments_before_parsing() {
+        let dir = |args: &[&str]| config_dir_flag(args);
+        assert_eq!(dir(&["river", "room", "list"]), None);
+        assert_eq!(
+            dir(&["river", "--config-dir", "/tmp/a", "room", "list"]).as_deref(),
+            Some("/tmp/a")
+        );
+        assert_eq!(
+            dir(&["river", "room", "list", "--config-dir=/tmp/b"]).as_deref(),
+            Some("/tmp/b")
+        );
+        assert_eq!(
+            dir(&[
+                "river",
+                "message",
+                "send",
+                "general",
+                "--",
+                "--config-dir=x"
+            ]),
+            None
+        );
     }
 }
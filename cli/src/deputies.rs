@@ -1623,6 +1623,7 @@ mod tests {
             version: 0,
             preferred_nickname: sealed,
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         state
             .member_info
@@ -3,7 +3,11 @@ pub mod commands;
 pub mod config;
 pub mod deputies;
 pub mod error;
+pub mod moderation;
 pub mod output;
 pub mod private_room;
+pub mod room_export;
 pub mod storage;
+pub mod tui;
 pub mod version_check;
+pub mod watch;
@@ -8,7 +8,7 @@ use tracing_subscriber::EnvFilter;
 
 use riverctl::{
     api,
-    commands::{debug, dm, identity, invite, member, message, room},
+    commands::{debug, dm, identity, invite, irc, member, message, moderate, room, serve, tui},
     config, output,
 };
 
@@ -21,22 +21,26 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Output format (human, json)
-    #[arg(short, long, global = true, default_value = "human")]
-    format: output::OutputFormat,
+    /// Output format (human, json) [default: human]
+    #[arg(short, long, global = true)]
+    format: Option<output::OutputFormat>,
 
     /// Freenet node WebSocket URL
-    #[arg(
-        long,
-        global = true,
-        default_value = "ws://127.0.0.1:7509/v1/contract/command?encodingProtocol=native"
-    )]
-    node_url: String,
+    /// [default: ws://127.0.0.1:7509/v1/contract/command?encodingProtocol=native]
+    #[arg(long, global = true)]
+    node_url: Option<String>,
 
-    /// Configuration directory for storing room data
+    /// Configuration directory for storing room data. Also where
+    /// `config.toml` is read from.
     #[arg(long, global = true)]
     config_dir: Option<String>,
 
+    /// Profile from `config.toml` supplying the node URL, signing key file,
+    /// output format and config directory. Flags given on the command line
+    /// still win. Defaults to the file's `default_profile`.
+    #[arg(long, global = true, value_name = "NAME", env = "RIVERCTL_PROFILE")]
+    profile: Option<String>,
+
     /// Enable debug logging
     #[arg(short, long, global = true)]
     debug: bool,
@@ -117,11 +121,46 @@ enum Commands {
         #[command(subcommand)]
         command: dm::DmCommands,
     },
+    /// Run a moderation bot for a room: auto-ban impersonators, and apply
+    /// keyword and flood rules
+    Moderate(moderate::ModerateArgs),
+    /// Full-screen terminal chat client
+    Tui(tui::TuiArgs),
+    /// Local HTTP API and event stream for bots and tools
+    Serve(serve::ServeArgs),
+    /// IRC server exposing your rooms as channels
+    IrcGateway(irc::IrcGatewayArgs),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    // Room arguments are resolved against `config.toml`'s aliases by
+    // `config::room_arg` while clap parses, so the file has to be loaded
+    // first. It is found through `--config-dir`, which is read straight from
+    // the arguments for that.
+    let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let config = config::Config::load(config::config_dir_flag(&args).as_deref())?;
+    config::set_room_aliases(config.rooms.clone());
+    let cli = Cli::parse_from(args);
+    let profile = config.profile(cli.profile.as_deref())?;
+
+    let format = cli
+        .format
+        .or_else(|| profile.output_format())
+        .unwrap_or_default();
+    let node_url = cli
+        .node_url
+        .clone()
+        .or_else(|| profile.node_url.clone())
+        .unwrap_or_else(|| config::DEFAULT_NODE_URL.to_string());
+    let config_dir = cli
+        .config_dir
+        .clone()
+        .or_else(|| profile.config_dir.clone());
+    let signing_key_file = cli
+        .signing_key_file
+        .clone()
+        .or_else(|| profile.signing_key_file.clone());
 
     // Initialize logging (keep stdout clean for user/JSON output)
     let _log_guard = init_logging(cli.debug, cli.log_file.as_deref())?;
@@ -136,12 +175,9 @@ async fn main() -> Result<()> {
     let version_disabled =
         cli.no_version_check || std::env::var_os("RIVERCTL_NO_VERSION_CHECK").is_some();
 
-    // Load configuration
-    let config = config::Config::load()?;
-
-    // Resolve optional --signing-key-file override (or RIVER_SIGNING_KEY_FILE env var).
-    let signing_key_override = cli
-        .signing_key_file
+    // Resolve optional --signing-key-file override (or RIVER_SIGNING_KEY_FILE
+    // env var, or the profile's signing_key_file).
+    let signing_key_override = signing_key_file
         .as_deref()
         .map(load_signing_key_from_file)
         .transpose()?;
@@ -157,44 +193,53 @@ async fn main() -> Result<()> {
         _ => None,
     };
 
+    // `room verify-export` likewise checks a bundle offline: everything it
+    // needs is in the file.
+    let verify_export_file = match &cli.command {
+        Commands::Room {
+            command: room::RoomCommands::VerifyExport { file },
+        } => Some(file.clone()),
+        _ => None,
+    };
+
     if let Some((room, inline_signing_key)) = whoami_args {
         let storage = riverctl::storage::Storage::new_with_override(
-            cli.config_dir.as_deref(),
+            config_dir.as_deref(),
             signing_key_override,
         )?;
         identity::whoami(
             &storage,
             room.as_deref(),
             inline_signing_key.as_deref(),
-            cli.format,
+            format,
         )?;
+    } else if let Some(file) = verify_export_file {
+        room::verify_export(&file, format)?;
     } else {
         // Create API client
         let api_client = api::ApiClient::new_with_signing_key_override(
-            &cli.node_url,
+            &node_url,
             config,
-            cli.config_dir.as_deref(),
+            config_dir.as_deref(),
             signing_key_override,
         )
         .await?;
 
         // Execute command
         match cli.command {
-            Commands::Room { command } => room::execute(command, api_client, cli.format).await?,
-            Commands::Message { command } => {
-                message::execute(command, api_client, cli.format).await?
-            }
-            Commands::Member { command } => {
-                member::execute(command, api_client, cli.format).await?
-            }
-            Commands::Invite { command } => {
-                invite::execute(command, api_client, cli.format).await?
-            }
+            Commands::Room { command } => room::execute(command, api_client, format).await?,
+            Commands::Message { command } => message::execute(command, api_client, format).await?,
+            Commands::Member { command } => member::execute(command, api_client, format).await?,
+            Commands::Invite { command } => invite::execute(command, api_client, format).await?,
             Commands::Identity { command } => {
-                identity::execute(command, api_client, cli.format).await?
+                identity::execute(command, api_client, format).await?
             }
-            Commands::Debug { command } => debug::execute(command, api_client, cli.format).await?,
-            Commands::Dm { command } => dm::execute(command, api_client, cli.format).await?,
+            Commands::Debug { command } => debug::execute(command, api_client, format).await?,
+            Commands::Dm { command } => dm::execute(command, api_client, format).await?,
+            Commands::Moderate(args) => moderate::execute(args, api_client, format).await?,
+            Commands::Tui(args) => tui::execute(args, api_client).await?,
+            Commands::Serve(args) => serve::execute(args, api_client).await?,
+            Commands::IrcGateway(args) => irc::execute(args, api_client).await?,
         }
     }
 
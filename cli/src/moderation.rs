@@ -0,0 +1,626 @@
+//! Rule engine behind `riverctl moderate`.
+//!
+//! The daemon in `commands::moderate` feeds every fresh room state to
+//! [`Moderator::review`], which returns what should happen to whom. Three kinds
+//! of rule, all loaded from TOML rule files:
+//!
+//! * **Impersonation** (`[[protect]]`): the members whose names must not be
+//!   imitated. Every member's display name is checked with
+//!   [`river_core::confusable`] when it first appears or changes. A tier-1
+//!   match (the names fold to the same skeleton) is banned; a tier-2 near miss
+//!   is only reported, matching the error budgets of the autoban script the
+//!   engine was ported from.
+//! * **Keywords** (`[[keyword]]`): case-insensitive substrings of a message.
+//! * **Flood** (`[[flood]]`): more than `max_messages` from one member within
+//!   `window_secs`, measured on the messages' own timestamps.
+//!
+//! Only messages that arrive after the daemon starts are judged; names are
+//! judged from the start, so an impostor already in the room is caught.
+//! The room owner, the moderator itself and protected members are never
+//! judged by the message rules.
+//!
+//! ```toml
+//! [[protect]]
+//! member = "AbCdEfGh"      # member ID, full or its first 8 characters
+//! name = "Ian Clarke"      # optional; defaults to their current nickname
+//!
+//! [[keyword]]
+//! patterns = ["free crypto", "t.me/"]
+//! action = "ban"           # or "report" (the default)
+//!
+//! [[flood]]
+//! max_messages = 5
+//! window_secs = 10
+//! ```
+
+use anyhow::{anyhow, Result};
+use ed25519_dalek::VerifyingKey;
+use river_core::confusable::{ConfusableTier, ImpersonationChecker, ProtectedName, ProtectedRole};
+use river_core::display_name::sanitize_display_name;
+use river_core::room_state::member::MemberId;
+use river_core::room_state::message::MessageId;
+use river_core::search::SearchQuery;
+use river_core::ChatRoomStateV1;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// What to do about a rule match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Log it (and DM it, if configured) for a human to decide.
+    #[default]
+    Report,
+    /// Ban the member, with every fail-closed safety check on.
+    Ban,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProtectRule {
+    pub member: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeywordRule {
+    pub patterns: Vec<String>,
+    #[serde(default)]
+    pub action: RuleAction,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FloodRule {
+    pub max_messages: usize,
+    pub window_secs: u64,
+    #[serde(default)]
+    pub action: RuleAction,
+}
+
+/// Every rule file passed to `moderate`, merged.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModerationRules {
+    #[serde(default)]
+    pub protect: Vec<ProtectRule>,
+    #[serde(default)]
+    pub keyword: Vec<KeywordRule>,
+    #[serde(default)]
+    pub flood: Vec<FloodRule>,
+}
+
+impl ModerationRules {
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let rules: Self = toml::from_str(text)?;
+        // Each of these would match every message, so every poster would be
+        // reported or banned.
+        for keyword in &rules.keyword {
+            if keyword.patterns.iter().any(|p| p.trim().is_empty()) {
+                return Err(anyhow!("keyword rule patterns must not be empty"));
+            }
+        }
+        for flood in &rules.flood {
+            if flood.max_messages == 0 {
+                return Err(anyhow!("flood rule max_messages must be at least 1"));
+            }
+            if flood.window_secs == 0 {
+                return Err(anyhow!("flood rule window_secs must be at least 1"));
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Load and merge rule files, in order.
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut merged = Self::default();
+        for path in paths {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read rule file {}: {}", path.display(), e))?;
+            let rules = Self::from_toml(&text)
+                .map_err(|e| anyhow!("Invalid rule file {}: {}", path.display(), e))?;
+            merged.protect.extend(rules.protect);
+            merged.keyword.extend(rules.keyword);
+            merged.flood.extend(rules.flood);
+        }
+        Ok(merged)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.protect.is_empty() && self.keyword.is_empty() && self.flood.is_empty()
+    }
+}
+
+/// One rule match.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Finding {
+    pub member_id: MemberId,
+    /// The member's display name, sanitised as the UI renders it.
+    pub nickname: String,
+    pub action: RuleAction,
+    pub reason: String,
+}
+
+/// Judges successive states of one room. Remembers what it has already seen
+/// and acted on, so each name change, message and member is acted on once.
+pub struct Moderator {
+    rules: ModerationRules,
+    self_id: MemberId,
+    /// The protected names the last review used; a change re-checks everyone.
+    protected: Vec<(MemberId, String)>,
+    /// Each member's display name as last checked.
+    names: HashMap<MemberId, String>,
+    seen_messages: HashSet<MessageId>,
+    started: bool,
+    banned: HashSet<MemberId>,
+    flooded: HashSet<MemberId>,
+}
+
+impl Moderator {
+    pub fn new(rules: ModerationRules, self_id: MemberId) -> Self {
+        Self {
+            rules,
+            self_id,
+            protected: Vec::new(),
+            names: HashMap::new(),
+            seen_messages: HashSet::new(),
+            started: false,
+            banned: HashSet::new(),
+            flooded: HashSet::new(),
+        }
+    }
+
+    /// `[[protect]]` entries that name no member of the room, for the daemon
+    /// to warn about.
+    pub fn unresolved_protections(
+        &self,
+        owner_vk: &VerifyingKey,
+        state: &ChatRoomStateV1,
+    ) -> Vec<String> {
+        let candidates = member_ids(owner_vk, state);
+        self.rules
+            .protect
+            .iter()
+            .filter(|rule| resolve_member(&candidates, &rule.member).is_none())
+            .map(|rule| rule.member.clone())
+            .collect()
+    }
+
+    /// Everything in `state` that breaks a rule and has not been acted on yet.
+    /// `secrets` decrypts a private room's nicknames and messages.
+    pub fn review(
+        &mut self,
+        owner_vk: &VerifyingKey,
+        state: &ChatRoomStateV1,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Vec<Finding> {
+        let owner_id = MemberId::from(owner_vk);
+        let names: HashMap<MemberId, String> = state
+            .member_info
+            .member_info
+            .iter()
+            .filter_map(|info| {
+                let id = info.member_info.member_id;
+                let canonical = state.member_info.canonical(id)?;
+                let raw = crate::api::unseal_nickname_display(
+                    &canonical.member_info.preferred_nickname,
+                    secrets,
+                );
+                Some((id, sanitize_display_name(&raw)))
+            })
+            .collect();
+        let name_of = |id: &MemberId| names.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+        let mut findings = Vec::new();
+        self.review_names(owner_id, state, &names, &mut findings);
+
+        let exempt: HashSet<MemberId> = self
+            .protected
+            .iter()
+            .map(|(id, _)| *id)
+            .chain([owner_id, self.self_id])
+            .collect();
+        let hits = state
+            .recent_messages
+            .search_with_secrets(&SearchQuery::default(), secrets);
+        let started = std::mem::replace(&mut self.started, true);
+        for hit in hits {
+            let message_id = hit.message.id();
+            if !self.seen_messages.insert(message_id.clone()) || !started {
+                continue;
+            }
+            let author = hit.message.message.author;
+            if exempt.contains(&author) || self.banned.contains(&author) {
+                continue;
+            }
+            let text = hit.text.to_lowercase();
+            let keyword = self.rules.keyword.iter().find_map(|rule| {
+                rule.patterns
+                    .iter()
+                    .find(|p| !p.is_empty() && text.contains(&p.to_lowercase()))
+                    .map(|p| (rule.action, p))
+            });
+            if let Some((action, pattern)) = keyword {
+                findings.push(Finding {
+                    member_id: author,
+                    nickname: name_of(&author),
+                    action,
+                    reason: format!(
+                        "message {} contains {:?}",
+                        message_id.0 .0,
+                        pattern.as_str()
+                    ),
+                });
+                if action == RuleAction::Ban {
+                    self.banned.insert(author);
+                    continue;
+                }
+            }
+
+            if self.flooded.contains(&author) {
+                continue;
+            }
+            let time = hit.message.message.time;
+            for rule in &self.rules.flood {
+                let window = Duration::from_secs(rule.window_secs);
+                let in_window = state
+                    .recent_messages
+                    .messages
+                    .iter()
+                    .filter(|m| m.message.author == author && !m.message.content.is_action())
+                    .filter(|m| {
+                        m.message.time <= time
+                            && time.duration_since(m.message.time).unwrap_or_default() < window
+                    })
+                    .count();
+                if in_window > rule.max_messages {
+                    findings.push(Finding {
+                        member_id: author,
+                        nickname: name_of(&author),
+                        action: rule.action,
+                        reason: format!(
+                            "flood: {} messages within {}s",
+                            in_window, rule.window_secs
+                        ),
+                    });
+                    // One flood report per member per run; a ban ends it anyway.
+                    self.flooded.insert(author);
+                    if rule.action == RuleAction::Ban {
+                        self.banned.insert(author);
+                    }
+                    break;
+                }
+            }
+        }
+        findings
+    }
+
+    /// Check every new or renamed member against the protected names.
+    fn review_names(
+        &mut self,
+        owner_id: MemberId,
+        state: &ChatRoomStateV1,
+        names: &HashMap<MemberId, String>,
+        findings: &mut Vec<Finding>,
+    ) {
+        let candidates: Vec<MemberId> = names.keys().copied().chain([owner_id]).collect();
+        let protected: Vec<(MemberId, String)> = self
+            .rules
+            .protect
+            .iter()
+            .filter_map(|rule| {
+                let id = resolve_member(&candidates, &rule.member)?;
+                let name = match &rule.name {
+                    Some(name) => sanitize_display_name(name),
+                    None => names.get(&id)?.clone(),
+                };
+                // A generated handle is River's assignment, not an identity;
+                // protecting one accuses everyone assigned a neighbouring handle.
+                (!river_core::nickname::folds_to_generated_handle(&name)).then_some((id, name))
+            })
+            .collect();
+        if protected != self.protected {
+            self.protected = protected;
+            self.names.clear();
+        }
+        let checker = ImpersonationChecker::new(
+            self.protected
+                .iter()
+                .map(|(id, name)| {
+                    let role = if *id == owner_id {
+                        ProtectedRole::Owner
+                    } else {
+                        ProtectedRole::Deputy
+                    };
+                    ProtectedName::new(role, name.clone(), *id)
+                })
+                .collect(),
+        );
+
+        let mut ids: Vec<&MemberId> = names.keys().collect();
+        ids.sort_by_key(|id| id.to_string());
+        for id in ids {
+            let name = &names[id];
+            if self.names.get(id) == Some(name) {
+                continue;
+            }
+            self.names.insert(*id, name.clone());
+            if *id == owner_id || *id == self.self_id || self.banned.contains(id) {
+                continue;
+            }
+            // Only members still in the room can be banned.
+            if !state.members.members.iter().any(|m| m.member.id() == *id) {
+                continue;
+            }
+            let Some(warning) = checker.check(*id, name) else {
+                continue;
+            };
+            let (action, closeness) = match warning.tier {
+                ConfusableTier::Identical => (RuleAction::Ban, "impersonates"),
+                ConfusableTier::NearMiss => (RuleAction::Report, "resembles"),
+            };
+            if action == RuleAction::Ban {
+                self.banned.insert(*id);
+            }
+            findings.push(Finding {
+                member_id: *id,
+                nickname: name.clone(),
+                action,
+                reason: format!(
+                    "name {} protected member {} ({:?})",
+                    closeness, warning.impersonated.source, warning.impersonated.display_name
+                ),
+            });
+        }
+    }
+}
+
+fn member_ids(owner_vk: &VerifyingKey, state: &ChatRoomStateV1) -> Vec<MemberId> {
+    state
+        .member_info
+        .member_info
+        .iter()
+        .map(|info| info.member_info.member_id)
+        .chain([MemberId::from(owner_vk)])
+        .collect()
+}
+
+/// The member `spec` names: a full member ID, or its first 8 characters
+/// (case-insensitive), the same rule `member ban` uses.
+fn resolve_member(candidates: &[MemberId], spec: &str) -> Option<MemberId> {
+    candidates.iter().copied().find(|id| {
+        let s = id.to_string();
+        s == spec || (spec.len() >= 8 && s[..8.min(s.len())].eq_ignore_ascii_case(spec))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use river_core::room_state::member::{AuthorizedMember, Member};
+    use river_core::room_state::member_info::{AuthorizedMemberInfo, MemberInfo};
+    use river_core::room_state::message::{AuthorizedMessageV1, MessageV1, RoomMessageBody};
+    use river_core::room_state::privacy::SealedBytes;
+    use std::time::UNIX_EPOCH;
+
+    struct Room {
+        owner: SigningKey,
+        state: ChatRoomStateV1,
+    }
+
+    impl Room {
+        fn new(owner_name: &str) -> Self {
+            let owner = SigningKey::from_bytes(&[1; 32]);
+            let mut room = Self {
+                owner,
+                state: ChatRoomStateV1::default(),
+            };
+            let owner = room.owner.clone();
+            room.set_name(&owner, owner_name);
+            room
+        }
+
+        fn owner_vk(&self) -> VerifyingKey {
+            self.owner.verifying_key()
+        }
+
+        fn join(&mut self, seed: u8, name: &str) -> SigningKey {
+            let sk = SigningKey::from_bytes(&[seed; 32]);
+            let owner_id = MemberId::from(&self.owner_vk());
+            self.state.members.members.push(AuthorizedMember::new(
+                Member {
+                    owner_member_id: owner_id,
+                    invited_by: owner_id,
+                    member_vk: sk.verifying_key(),
+                },
+                &self.owner,
+            ));
+            self.set_name(&sk, name);
+            sk
+        }
+
+        fn set_name(&mut self, sk: &SigningKey, name: &str) {
+            let id = MemberId::from(&sk.verifying_key());
+            let version = self
+                .state
+                .member_info
+                .member_info
+                .iter()
+                .filter(|i| i.member_info.member_id == id)
+                .map(|i| i.member_info.version + 1)
+                .max()
+                .unwrap_or(0);
+            self.state
+                .member_info
+                .member_info
+                .retain(|i| i.member_info.member_id != id);
+            self.state
+                .member_info
+                .member_info
+                .push(AuthorizedMemberInfo::new_with_member_key(
+                    MemberInfo {
+                        member_id: id,
+                        version,
+                        preferred_nickname: SealedBytes::public(name.as_bytes().to_vec()),
+                        deputies: Vec::new(),
+                        dm_prekey: None,
+                    },
+                    sk,
+                ));
+        }
+
+        fn post(&mut self, sk: &SigningKey, secs: u64, text: &str) {
+            self.state
+                .recent_messages
+                .messages
+                .push(AuthorizedMessageV1::new(
+                    MessageV1 {
+                        room_owner: MemberId::from(&self.owner_vk()),
+                        author: MemberId::from(&sk.verifying_key()),
+                        time: UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs),
+                        content: RoomMessageBody::public(text.to_string()),
+                    },
+                    sk,
+                ));
+        }
+
+        fn review(&self, moderator: &mut Moderator) -> Vec<(String, RuleAction)> {
+            moderator
+                .review(&self.owner_vk(), &self.state, &HashMap::new())
+                .into_iter()
+                .map(|f| (f.nickname, f.action))
+                .collect()
+        }
+    }
+
+    fn rules(toml: &str) -> ModerationRules {
+        ModerationRules::from_toml(toml).unwrap()
+    }
+
+    fn moderator(room: &Room, toml: &str) -> Moderator {
+        Moderator::new(rules(toml), MemberId::from(&room.owner_vk()))
+    }
+
+    #[test]
+    fn rule_files_parse_and_reject_unknown_fields() {
+        let parsed = rules(
+            r#"
+            [[protect]]
+            member = "AbCdEfGh"
+
+            [[keyword]]
+            patterns = ["spam"]
+            action = "ban"
+
+            [[flood]]
+            max_messages = 3
+            window_secs = 5
+            "#,
+        );
+        assert_eq!(parsed.protect.len(), 1);
+        assert_eq!(parsed.keyword[0].action, RuleAction::Ban);
+        assert_eq!(parsed.flood[0].action, RuleAction::Report);
+        assert!(ModerationRules::from_toml("[[keyword]]\npatern = [\"x\"]").is_err());
+        assert!(
+            ModerationRules::from_toml("[[flood]]\nmax_messages = 1\nwindow_secs = 0").is_err()
+        );
+    }
+
+    #[test]
+    fn rules_that_would_match_every_message_are_rejected() {
+        for toml in [
+            "[[keyword]]\npatterns = [\"\"]",
+            "[[keyword]]\npatterns = [\"spam\", \"  \"]",
+            "[[flood]]\nmax_messages = 0\nwindow_secs = 10",
+        ] {
+            let err = ModerationRules::from_toml(toml).unwrap_err().to_string();
+            assert!(err.contains("must"), "{toml}: {err}");
+        }
+        assert!(ModerationRules::from_toml("[[keyword]]\npatterns = []").is_ok());
+    }
+
+    #[test]
+    fn identical_names_are_banned_and_near_misses_reported() {
+        let mut room = Room::new("Ian Clarke");
+        let owner_id = MemberId::from(&room.owner_vk()).to_string();
+        let mut moderator = moderator(&room, &format!("[[protect]]\nmember = {owner_id:?}\n"));
+        room.join(2, "Alice");
+        assert!(room.review(&mut moderator).is_empty());
+
+        let impostor = room.join(3, "lan Clarke");
+        room.join(4, "Ian Clarkee");
+        let mut findings = room.review(&mut moderator);
+        findings.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            findings,
+            vec![
+                ("Ian Clarkee".to_string(), RuleAction::Report),
+                ("lan Clarke".to_string(), RuleAction::Ban),
+            ]
+        );
+
+        // Each is acted on once, even when the impostor renames again.
+        room.set_name(&impostor, "Ian C1arke");
+        assert!(room.review(&mut moderator).is_empty());
+    }
+
+    #[test]
+    fn only_new_messages_are_judged_by_keyword_and_flood_rules() {
+        let mut room = Room::new("Owner");
+        let spammer = room.join(2, "Spammer");
+        let chatty = room.join(3, "Chatty");
+        room.post(&spammer, 0, "old news: FREE CRYPTO");
+        let mut moderator = moderator(
+            &room,
+            "[[keyword]]\npatterns = [\"free crypto\"]\naction = \"ban\"\n\
+             [[flood]]\nmax_messages = 2\nwindow_secs = 10\n",
+        );
+        assert!(room.review(&mut moderator).is_empty());
+
+        room.post(&spammer, 100, "Free Crypto here");
+        for secs in [200, 203, 206] {
+            room.post(&chatty, secs, "hi");
+        }
+        let findings = room.review(&mut moderator);
+        assert_eq!(
+            findings,
+            vec![
+                ("Spammer".to_string(), RuleAction::Ban),
+                ("Chatty".to_string(), RuleAction::Report),
+            ]
+        );
+
+        // Spread out, the same number of messages is not a flood.
+        room.post(&chatty, 300, "a");
+        room.post(&chatty, 320, "b");
+        assert!(room.review(&mut moderator).is_empty());
+    }
+
+    #[test]
+    fn the_owner_and_protected_members_are_never_judged() {
+        let mut room = Room::new("Owner");
+        let owner = room.owner.clone();
+        let deputy = room.join(2, "Deputy");
+        let deputy_id = MemberId::from(&deputy.verifying_key()).to_string();
+        let mut moderator = Moderator::new(
+            rules(&format!(
+                "[[protect]]\nmember = {:?}\n[[keyword]]\npatterns = [\"ban me\"]\n",
+                &deputy_id[..8]
+            )),
+            MemberId::from(&SigningKey::from_bytes(&[9; 32]).verifying_key()),
+        );
+        assert!(moderator
+            .unresolved_protections(&room.owner_vk(), &room.state)
+            .is_empty());
+        assert!(room.review(&mut moderator).is_empty());
+
+        room.post(&owner, 10, "ban me");
+        room.post(&deputy, 11, "ban me");
+        assert!(room.review(&mut moderator).is_empty());
+    }
+}
@@ -2,8 +2,9 @@ use serde::Serialize;
 use std::fmt::Display;
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum OutputFormat {
+    #[default]
     Human,
     Json,
 }
@@ -8,11 +8,14 @@
 //! invitation are wire-interchangeable.
 
 use ed25519_dalek::{SigningKey, VerifyingKey};
+use river_core::blob::seal_blob;
 use river_core::ecies::{
     decrypt_secret_from_member_blob_raw, encrypt_with_symmetric_key, seal_bytes,
 };
 use river_core::room_state::content::{
-    ActionContentV1, ReplyContentV1, TextContentV1, CONTENT_TYPE_REPLY, REPLY_CONTENT_VERSION,
+    ActionContentV1, BlobContentV1, PollContentV1, ReplyContentV1, TextContentV1,
+    BLOB_CONTENT_VERSION, CONTENT_TYPE_BLOB, CONTENT_TYPE_POLL, CONTENT_TYPE_REPLY,
+    POLL_CONTENT_VERSION, REPLY_CONTENT_VERSION,
 };
 use river_core::room_state::member::MemberId;
 use river_core::room_state::member_info::{AuthorizedMemberInfo, MemberInfo};
@@ -233,9 +236,9 @@ pub fn seal_invitee_nickname(
 /// uses, so the CLI stays self-consistent. (`self_nickname` is `None` only for
 /// a room joined before that field existed, or the room owner, who is skipped
 /// above.) The CLI deliberately does NOT mirror the UI's deterministic
-/// "hacker-handle" default: that 30x30 word table lives in `ui/src/nickname.rs`
-/// and is not shared via `river-core`, and duplicating it here would be a
-/// drift hazard for no functional benefit.
+/// "hacker-handle" default: that 30x30 word table lives in `river_core::nickname`
+/// behind the `names` feature for the confusable-name checks, but a generated
+/// handle adds nothing to a terminal session that the member ID does not.
 ///
 /// For a **public** room the nickname is published as public bytes. For a
 /// **private** room it must be encrypted: it is sealed under the room's
@@ -342,6 +345,7 @@ pub fn build_member_info_heal(
         version: 0,
         preferred_nickname: sealed,
         deputies: Vec::new(),
+        dm_prekey: None,
     };
     Some(AuthorizedMemberInfo::new_with_member_key(info, self_sk))
 }
@@ -500,6 +504,79 @@ pub fn build_reply_body(
     guard_message_size(state, content)
 }
 
+/// Build the `RoomMessageBody` for an outgoing **poll**.
+///
+/// Public rooms get the plaintext body from `RoomMessageBody::poll`; private
+/// rooms seal the CBOR-encoded `PollContentV1` exactly like
+/// [`build_reply_body`]. Votes are ordinary actions and go through
+/// [`build_action_body`].
+pub fn build_poll_body(
+    state: &ChatRoomStateV1,
+    self_sk: &SigningKey,
+    invitation_secrets: &HashMap<u32, [u8; 32]>,
+    poll: PollContentV1,
+) -> Result<RoomMessageBody, String> {
+    let content = if state.configuration.configuration.privacy_mode != PrivacyMode::Private {
+        RoomMessageBody::poll(poll)
+    } else {
+        let (secret, version) = resolve_current_secret(state, self_sk, invitation_secrets)?;
+        let (ciphertext, nonce) = encrypt_with_symmetric_key(&secret, &poll.encode());
+        RoomMessageBody::private(
+            CONTENT_TYPE_POLL,
+            POLL_CONTENT_VERSION,
+            ciphertext,
+            nonce,
+            version,
+        )
+    };
+
+    guard_message_size(state, content)
+}
+
+/// Prepare an attachment for upload: the bytes to PUT into its blob contract
+/// and the key to carry in its `BlobContentV1`.
+///
+/// A private room encrypts the bytes under a fresh per-attachment key sealed
+/// with the current room secret (the same secret-resolution rule as every
+/// message body); a public room uploads them as-is with no key.
+pub fn seal_attachment(
+    state: &ChatRoomStateV1,
+    self_sk: &SigningKey,
+    invitation_secrets: &HashMap<u32, [u8; 32]>,
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Option<SealedBytes>), String> {
+    if state.configuration.configuration.privacy_mode != PrivacyMode::Private {
+        return Ok(seal_blob(plaintext, None));
+    }
+    let (secret, version) = resolve_current_secret(state, self_sk, invitation_secrets)?;
+    Ok(seal_blob(plaintext, Some((&secret, version))))
+}
+
+/// Build the `RoomMessageBody` for an outgoing **attachment** reference,
+/// sealed in a private room exactly like [`build_poll_body`].
+pub fn build_blob_body(
+    state: &ChatRoomStateV1,
+    self_sk: &SigningKey,
+    invitation_secrets: &HashMap<u32, [u8; 32]>,
+    blob: BlobContentV1,
+) -> Result<RoomMessageBody, String> {
+    let content = if state.configuration.configuration.privacy_mode != PrivacyMode::Private {
+        RoomMessageBody::blob(blob)
+    } else {
+        let (secret, version) = resolve_current_secret(state, self_sk, invitation_secrets)?;
+        let (ciphertext, nonce) = encrypt_with_symmetric_key(&secret, &blob.encode());
+        RoomMessageBody::private(
+            CONTENT_TYPE_BLOB,
+            BLOB_CONTENT_VERSION,
+            ciphertext,
+            nonce,
+            version,
+        )
+    };
+
+    guard_message_size(state, content)
+}
+
 /// Resolve the room's **current-version** secret for the member holding
 /// `self_sk`, for sealing an outgoing private-room body.
 ///
@@ -512,8 +589,9 @@ pub fn build_reply_body(
 /// (never a stale version other members can't read) mirrors the
 /// nickname-sealing guard in [`seal_invitee_nickname`].
 ///
-/// Shared by [`build_message_body`], [`build_action_body`], and
-/// [`build_reply_body`] so all four message kinds (text / action / reply) make
+/// Shared by [`build_message_body`], [`build_action_body`],
+/// [`build_reply_body`], [`build_poll_body`], [`build_blob_body`] and
+/// [`seal_attachment`] so every message kind makes
 /// the identical secret-resolution decision — a divergence here would leak one
 /// kind of private content as an unsealed public body.
 fn resolve_current_secret(
@@ -896,6 +974,7 @@ mod tests {
             version: 0,
             preferred_nickname: sealed,
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         state
             .member_info
@@ -1715,6 +1794,99 @@ mod tests {
         );
     }
 
+    // ------------------------------------------------------------------
+    // build_poll_body
+    // ------------------------------------------------------------------
+
+    fn sample_poll() -> PollContentV1 {
+        PollContentV1::new(
+            "Release today?".to_string(),
+            vec!["Yes".to_string(), "No".to_string()],
+            false,
+            None,
+        )
+    }
+
+    /// Public room → byte-identical to `RoomMessageBody::poll`.
+    #[test]
+    fn build_poll_body_public_room_matches_dedicated_constructor() {
+        let owner = fresh_signing_key();
+        let state = state_with_privacy(&owner, PrivacyMode::Public);
+        let via_helper = build_poll_body(&state, &owner, &HashMap::new(), sample_poll())
+            .expect("public room always builds a body");
+        assert_eq!(via_helper, RoomMessageBody::poll(sample_poll()));
+    }
+
+    /// Private room → the poll seals under the current version with
+    /// `CONTENT_TYPE_POLL` in the clear, so `rebuild_actions_state` can still
+    /// recognise votes on it.
+    #[test]
+    fn build_poll_body_private_room_seals_and_roundtrips() {
+        let owner = fresh_signing_key();
+        let state = state_with_privacy(&owner, PrivacyMode::Private);
+        let secret = [0x42u8; 32];
+        let mut inv = HashMap::new();
+        inv.insert(0u32, secret);
+
+        let body = build_poll_body(&state, &owner, &inv, sample_poll())
+            .expect("invitation-carried secret seals the poll body");
+        assert!(body.is_poll());
+        match body {
+            RoomMessageBody::Private {
+                ciphertext, nonce, ..
+            } => {
+                let plaintext = decrypt_with_symmetric_key(&secret, &ciphertext, &nonce)
+                    .expect("the sealed poll decrypts under the room secret");
+                assert_eq!(PollContentV1::decode(&plaintext).unwrap(), sample_poll());
+            }
+            RoomMessageBody::Public { .. } => panic!("private room must seal the poll body"),
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // seal_attachment / build_blob_body
+    // ------------------------------------------------------------------
+
+    /// Private room → the uploaded bytes are ciphertext, the reference body is
+    /// sealed, and a reader holding the room secret gets the file back.
+    #[test]
+    fn private_attachment_is_encrypted_and_opens_with_the_room_secret() {
+        let owner = fresh_signing_key();
+        let state = state_with_privacy(&owner, PrivacyMode::Private);
+        let secret = [0x42u8; 32];
+        let inv = HashMap::from([(0u32, secret)]);
+
+        let (stored, key) = seal_attachment(&state, &owner, &inv, b"crash log")
+            .expect("invitation-carried secret seals the attachment");
+        assert!(!stored.windows(9).any(|w| w == b"crash log"));
+        let blob = BlobContentV1 {
+            contract_id: [0; 32],
+            hash: river_core::blob::BlobParametersV1::for_bytes(&stored).hash,
+            size: 9,
+            mime_type: "text/plain".to_string(),
+            filename: "crash.log".to_string(),
+            key,
+        };
+
+        let body = build_blob_body(&state, &owner, &inv, blob.clone())
+            .expect("invitation-carried secret seals the reference");
+        assert!(body.is_blob() && body.is_private());
+        assert_eq!(
+            river_core::blob::open_blob(&blob, &stored, &inv).unwrap(),
+            b"crash log"
+        );
+    }
+
+    /// Public room → bytes are uploaded as-is and the body is public.
+    #[test]
+    fn public_attachment_is_stored_in_the_clear() {
+        let owner = fresh_signing_key();
+        let state = state_with_privacy(&owner, PrivacyMode::Public);
+        let (stored, key) = seal_attachment(&state, &owner, &HashMap::new(), b"patch").unwrap();
+        assert_eq!(stored, b"patch");
+        assert!(key.is_none());
+    }
+
     // ------------------------------------------------------------------
     // build_reply_body (#351)
     // ------------------------------------------------------------------
@@ -1803,6 +1975,49 @@ mod tests {
         }
     }
 
+    /// A sealed reply still threads under its target once decrypted, and
+    /// only then.
+    #[test]
+    fn private_reply_target_resolves_with_the_room_secret() {
+        use river_core::room_state::message::{AuthorizedMessageV1, MessageV1};
+
+        let owner = fresh_signing_key();
+        let state = state_with_privacy(&owner, PrivacyMode::Private);
+        let mut inv = HashMap::new();
+        inv.insert(0u32, [0x42u8; 32]);
+        let tgt = target_id();
+
+        let body = build_reply_body(
+            &state,
+            &owner,
+            &inv,
+            "in thread".to_string(),
+            tgt.clone(),
+            "Name".to_string(),
+            "preview".to_string(),
+        )
+        .expect("invitation-carried secret seals the reply body");
+        let owner_id = owner.verifying_key().into();
+        let reply = AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: owner_id,
+                author: owner_id,
+                time: std::time::SystemTime::now(),
+                content: body,
+            },
+            &owner,
+        );
+
+        assert_eq!(
+            crate::api::reply_target_with_secrets(&reply, &inv),
+            Some(tgt)
+        );
+        assert_eq!(
+            crate::api::reply_target_with_secrets(&reply, &HashMap::new()),
+            None
+        );
+    }
+
     /// Private room, no secret → error (never a public reply body).
     #[test]
     fn build_reply_body_private_room_errors_without_secret() {
@@ -0,0 +1,396 @@
+//! Self-verifying room exports (`riverctl room export` / `room verify-export`).
+//!
+//! An export is a JSON bundle of the room exactly as the network holds it —
+//! the full [`ChatRoomStateV1`] and its [`ChatRoomParametersV1`] — plus each
+//! member's invite chain back to the owner and the messages riverctl has
+//! archived past the room's retention limit. Every record in it is carried in
+//! its original signed form, so [`RoomExport::verify`] can re-check all of it
+//! offline with nothing but the bundle: the owner key in the parameters is the
+//! single root of trust.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::VerifyingKey;
+use freenet_scaffold::ComposableState;
+use river_core::archive::{ArchivedMessage, MessageArchive};
+use river_core::room_state::member::{AuthorizedMember, MemberId};
+use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Value of [`RoomExport::format`], so a stray JSON file is not mistaken for
+/// an export.
+pub const EXPORT_FORMAT: &str = "river-room-export";
+/// Bundle layout version; bumped on incompatible changes.
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomExport {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub parameters: ChatRoomParametersV1,
+    pub state: ChatRoomStateV1,
+    /// One chain per member: the member first, then each inviter in turn,
+    /// ending with the member the owner invited.
+    pub invite_chains: Vec<InviteChain>,
+    /// Archived messages the room no longer holds, oldest first.
+    #[serde(default)]
+    pub archived_messages: Vec<ArchivedMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InviteChain {
+    pub member_id: MemberId,
+    pub chain: Vec<AuthorizedMember>,
+}
+
+/// What [`RoomExport::verify`] checked: how many records of each kind carried
+/// a valid signature, and every failure.
+#[derive(Debug, Default, Serialize)]
+pub struct ExportReport {
+    pub room_owner: String,
+    pub exported_at: Option<DateTime<Utc>>,
+    pub configuration: usize,
+    pub members: usize,
+    pub invite_chains: usize,
+    pub member_info: usize,
+    pub bans: usize,
+    pub messages: usize,
+    pub archived_messages: usize,
+    pub failures: Vec<String>,
+}
+
+impl ExportReport {
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl RoomExport {
+    /// Bundle `state` with its invite chains and the archived messages it no
+    /// longer holds. Fails if a member's invite chain does not verify, since
+    /// such a state would not be accepted by the contract either.
+    pub fn new(
+        parameters: ChatRoomParametersV1,
+        state: ChatRoomStateV1,
+        archive: &MessageArchive,
+    ) -> Result<Self> {
+        let invite_chains = state
+            .members
+            .members
+            .iter()
+            .map(|member| {
+                let inviters = state
+                    .members
+                    .get_invite_chain(member, &parameters)
+                    .map_err(|e| anyhow!("Cannot export room: {e}"))?;
+                Ok(InviteChain {
+                    member_id: member.member.id(),
+                    chain: std::iter::once(member.clone()).chain(inviters).collect(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        let archived_messages = archive
+            .entries()
+            .iter()
+            .filter(|e| {
+                !state
+                    .recent_messages
+                    .messages
+                    .iter()
+                    .any(|m| m.id() == e.message.id())
+            })
+            .cloned()
+            .collect();
+        Ok(Self {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            parameters,
+            state,
+            invite_chains,
+            archived_messages,
+        })
+    }
+
+    /// Parse a bundle, rejecting files that are not a River room export of a
+    /// version this riverctl understands.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let export: Self =
+            serde_json::from_str(json).map_err(|e| anyhow!("Not a valid room export: {e}"))?;
+        if export.format != EXPORT_FORMAT {
+            return Err(anyhow!(
+                "Not a River room export (format {:?})",
+                export.format
+            ));
+        }
+        if export.version != EXPORT_VERSION {
+            return Err(anyhow!(
+                "Unsupported room export version {} (this riverctl reads version {})",
+                export.version,
+                EXPORT_VERSION
+            ));
+        }
+        Ok(export)
+    }
+
+    /// Re-check every signature in the bundle against the owner key in its
+    /// parameters: the configuration, each member's invite (both in the state
+    /// and in the bundled chains), member info, bans, messages and archived
+    /// messages, then the contract's own whole-state validation.
+    pub fn verify(&self) -> ExportReport {
+        let params = &self.parameters;
+        let state = &self.state;
+        let owner_id = params.owner_id();
+        let mut report = ExportReport {
+            room_owner: bs58::encode(params.owner.as_bytes()).into_string(),
+            exported_at: Some(self.exported_at),
+            ..Default::default()
+        };
+        let mut check = |counter: &mut usize, result: Result<(), String>, what: String| match result
+        {
+            Ok(()) => *counter += 1,
+            Err(e) => report.failures.push(format!("{what}: {e}")),
+        };
+
+        // Keys of everyone who can sign: the owner plus each member, whose key
+        // is only trusted once their invite verifies.
+        let mut keys: HashMap<MemberId, VerifyingKey> = HashMap::from([(owner_id, params.owner)]);
+
+        let mut configuration = 0;
+        check(
+            &mut configuration,
+            state
+                .configuration
+                .verify_signature(&params.owner)
+                .map_err(|e| e.to_string()),
+            "configuration".to_string(),
+        );
+
+        let mut members = 0;
+        for member in &state.members.members {
+            let result = state.members.get_invite_chain(member, params).map(|_| ());
+            if result.is_ok() {
+                keys.insert(member.member.id(), member.member.member_vk);
+            }
+            check(
+                &mut members,
+                result,
+                format!("member {}", member.member.id()),
+            );
+        }
+
+        let mut invite_chains = 0;
+        for chain in &self.invite_chains {
+            check(
+                &mut invite_chains,
+                verify_invite_chain(chain, params),
+                format!("invite chain of {}", chain.member_id),
+            );
+        }
+
+        let mut member_info = 0;
+        for info in &state.member_info.member_info {
+            let id = info.member_info.member_id;
+            let result = match keys.get(&id) {
+                Some(vk) => info.verify_signature_with_key(vk),
+                None => Err("signer is not a verified member".to_string()),
+            };
+            check(&mut member_info, result, format!("member info of {id}"));
+        }
+
+        let mut bans = 0;
+        for ban in &state.bans.0 {
+            let result = match keys.get(&ban.banned_by) {
+                Some(vk) => ban.verify_signature(vk),
+                None => Err(format!("banner {} is not a verified member", ban.banned_by)),
+            };
+            check(&mut bans, result, format!("ban of {}", ban.ban.banned_user));
+        }
+
+        let mut messages = 0;
+        for msg in &state.recent_messages.messages {
+            let author = msg.message.author;
+            let result = match keys.get(&author) {
+                Some(vk) => msg.validate(vk).map_err(|e| e.to_string()),
+                None => Err(format!("author {author} is not a verified member")),
+            };
+            check(&mut messages, result, format!("message {}", msg.id().0 .0));
+        }
+
+        let mut archived_messages = 0;
+        for entry in &self.archived_messages {
+            check(
+                &mut archived_messages,
+                entry.verify(),
+                format!("archived message {}", entry.message.id().0 .0),
+            );
+        }
+
+        // The contract's own validation also covers what has no standalone
+        // signature check above: secrets, direct messages, the upgrade pointer.
+        let mut whole_state = 0;
+        check(
+            &mut whole_state,
+            state.verify(state, params),
+            "room state".to_string(),
+        );
+
+        report.configuration = configuration;
+        report.members = members;
+        report.invite_chains = invite_chains;
+        report.member_info = member_info;
+        report.bans = bans;
+        report.messages = messages;
+        report.archived_messages = archived_messages;
+        report
+    }
+}
+
+/// Check a bundled chain on its own: each link must name the next as its
+/// inviter and be signed by it, and the last must be signed by the owner.
+fn verify_invite_chain(chain: &InviteChain, params: &ChatRoomParametersV1) -> Result<(), String> {
+    let first = chain.chain.first().ok_or("empty chain")?;
+    if first.member.id() != chain.member_id {
+        return Err("chain does not start with its member".to_string());
+    }
+    for (i, link) in chain.chain.iter().enumerate() {
+        let (inviter_id, inviter_vk) = match chain.chain.get(i + 1) {
+            Some(next) => (next.member.id(), next.member.member_vk),
+            None => (params.owner_id(), params.owner),
+        };
+        if link.member.invited_by != inviter_id {
+            return Err(format!(
+                "{} names {} as inviter, not {}",
+                link.member.id(),
+                link.member.invited_by,
+                inviter_id
+            ));
+        }
+        link.verify_signature(&inviter_vk)
+            .map_err(|e| format!("invite of {}: {e}", link.member.id()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use river_core::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+    use river_core::room_state::member::Member;
+    use river_core::room_state::message::{AuthorizedMessageV1, MessageV1, RoomMessageBody};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn message(sk: &SigningKey, owner: MemberId, secs: u64, text: &str) -> AuthorizedMessageV1 {
+        AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: owner,
+                author: MemberId::from(&sk.verifying_key()),
+                time: UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs),
+                content: RoomMessageBody::public(text.to_string()),
+            },
+            sk,
+        )
+    }
+
+    /// A room where the owner invited alice, who invited bob, and both posted.
+    fn export() -> RoomExport {
+        let owner = SigningKey::from_bytes(&[1; 32]);
+        let alice = SigningKey::from_bytes(&[2; 32]);
+        let bob = SigningKey::from_bytes(&[3; 32]);
+        let owner_id = MemberId::from(&owner.verifying_key());
+        let alice_id = MemberId::from(&alice.verifying_key());
+
+        let mut state = ChatRoomStateV1 {
+            configuration: AuthorizedConfigurationV1::new(
+                Configuration {
+                    owner_member_id: owner_id,
+                    ..Default::default()
+                },
+                &owner,
+            ),
+            ..Default::default()
+        };
+        state.members.members = vec![
+            AuthorizedMember::new(
+                Member {
+                    owner_member_id: owner_id,
+                    invited_by: owner_id,
+                    member_vk: alice.verifying_key(),
+                },
+                &owner,
+            ),
+            AuthorizedMember::new(
+                Member {
+                    owner_member_id: owner_id,
+                    invited_by: alice_id,
+                    member_vk: bob.verifying_key(),
+                },
+                &alice,
+            ),
+        ];
+        let old = message(&alice, owner_id, 0, "last month's decision");
+        state.recent_messages.messages = vec![
+            message(&alice, owner_id, 1, "hello"),
+            message(&bob, owner_id, 2, "hi"),
+        ];
+
+        let mut archive = MessageArchive::default();
+        archive
+            .insert(ArchivedMessage {
+                author_vk: alice.verifying_key(),
+                message: old,
+            })
+            .unwrap();
+        let params = ChatRoomParametersV1 {
+            owner: owner.verifying_key(),
+        };
+        RoomExport::new(params, state, &archive).unwrap()
+    }
+
+    #[test]
+    fn export_round_trips_and_verifies() {
+        let bundle = export();
+        assert_eq!(bundle.invite_chains.len(), 2);
+        assert_eq!(bundle.archived_messages.len(), 1);
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let report = RoomExport::from_json(&json).unwrap().verify();
+        assert!(report.is_valid(), "{:?}", report.failures);
+        assert_eq!(report.members, 2);
+        assert_eq!(report.invite_chains, 2);
+        assert_eq!(report.messages, 2);
+        assert_eq!(report.archived_messages, 1);
+    }
+
+    #[test]
+    fn tampering_is_reported() {
+        let mut bundle = export();
+        bundle.state.recent_messages.messages[1].message.content =
+            RoomMessageBody::public("forged".to_string());
+        // Swap bob's chain for one that skips alice.
+        let bob_chain = &mut bundle.invite_chains[1];
+        bob_chain.chain.truncate(1);
+
+        let report = bundle.verify();
+        assert!(!report.is_valid());
+        assert_eq!(report.messages, 1);
+        assert_eq!(report.invite_chains, 1);
+        assert!(report.failures.iter().any(|f| f.starts_with("message ")));
+        assert!(report
+            .failures
+            .iter()
+            .any(|f| f.starts_with("invite chain of ")));
+    }
+
+    #[test]
+    fn other_json_is_rejected() {
+        let mut bundle = export();
+        bundle.format = "something-else".to_string();
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(RoomExport::from_json(&json).is_err());
+        assert!(RoomExport::from_json("{}").is_err());
+    }
+}
@@ -4,14 +4,40 @@ use directories::ProjectDirs;
 use ed25519_dalek::{SigningKey, VerifyingKey};
 use freenet_stdlib::prelude::ContractKey;
 use fs2::FileExt;
-use river_core::chat_delegate::OutboundDmStore;
+use river_core::archive::{ArchivedMessage, MessageArchive};
+use river_core::chat_delegate::{BlockedMemberEntry, OutboundDmStore};
+use river_core::dm_ratchet::RatchetStore;
 use river_core::room_state::member::{AuthorizedMember, MemberId};
+use river_core::room_state::message::MessageId;
 use river_core::room_state::ChatRoomStateV1;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use tracing::info;
+use tracing::{info, warn};
+
+/// A join request sent with `room knock`: the key the requester will join
+/// with once approved, and the nickname they asked for. Plaintext on disk,
+/// like the signing keys in `rooms.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingJoinRequest {
+    pub signing_key_bytes: [u8; 32],
+    pub nickname: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PendingJoinRequests {
+    pub rooms: HashMap<String, PendingJoinRequest>,
+}
+
+/// Forward-secret DM state (`river_core::dm_ratchet`) by room owner key:
+/// prekey secrets, sessions and the opened plaintext of ratcheted DMs.
+/// Plaintext on disk, like the signing keys in `rooms.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DmRatchetStores {
+    pub rooms: HashMap<String, RatchetStore>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredRoomInfo {
@@ -279,6 +305,17 @@ pub struct Storage {
     /// Side file so the larger `rooms.json` blob stays untouched on
     /// each DM send. JSON-serialized [`OutboundDmStore`].
     outbound_dms_path: PathBuf,
+    /// Directory of per-room message archives, `<owner>.jsonl`: one
+    /// [`ArchivedMessage`] per line, only ever appended to. Keeps history the
+    /// contract has already pruned; see [`Self::archive_room_messages`].
+    archive_dir: PathBuf,
+    /// Keys of join requests this client has sent but that have not been
+    /// decided yet, keyed by room owner (`join_requests.json`). JSON-serialized
+    /// [`PendingJoinRequests`].
+    join_requests_path: PathBuf,
+    /// Forward-secret DM state (`dm_ratchet.json`). JSON-serialized
+    /// [`DmRatchetStores`].
+    dm_ratchet_path: PathBuf,
     /// Dedicated advisory-lock file (`.river.lock`) guarding the whole
     /// `load → mutate → save` critical section against concurrent riverctl
     /// invocations (issue freenet/river#307). A SEPARATE file from the data
@@ -330,10 +367,16 @@ impl Storage {
         let storage_path = data_dir.join("rooms.json");
         let outbound_dms_path = data_dir.join("outbound_dms.json");
         let lock_path = data_dir.join(".river.lock");
+        let archive_dir = data_dir.join("archive");
+        let join_requests_path = data_dir.join("join_requests.json");
+        let dm_ratchet_path = data_dir.join("dm_ratchet.json");
 
         Ok(Self {
             storage_path,
             outbound_dms_path,
+            archive_dir,
+            join_requests_path,
+            dm_ratchet_path,
             lock_path,
             signing_key_override,
         })
@@ -405,6 +448,11 @@ impl Storage {
         result
     }
 
+    /// The directory `rooms.json` and the other data files live in.
+    pub fn data_dir(&self) -> &Path {
+        self.storage_path.parent().unwrap_or(Path::new("."))
+    }
+
     /// Resolve the signing key to use for the current command: prefer
     /// the in-memory override if set, otherwise reconstruct from the
     /// per-room `signing_key_bytes`. Used by both [`Storage::get_room`]
@@ -627,6 +675,72 @@ impl Storage {
         Self::atomic_write(&self.outbound_dms_path, &contents)
     }
 
+    /// The members `blocker` has blocked in `owner_vk`'s room, from
+    /// `outbound_dms.json`. Empty when the file does not exist.
+    pub fn blocked_members(
+        &self,
+        owner_vk: &VerifyingKey,
+        blocker: MemberId,
+    ) -> Result<HashSet<MemberId>> {
+        let store = self.load_outbound_dms()?;
+        Ok(river_core::chat_delegate::blocked_by(
+            &store.blocked_members,
+            &owner_vk.to_bytes(),
+            blocker,
+        ))
+    }
+
+    /// Record that `blocker` blocks `member` in `owner_vk`'s room. Returns
+    /// `false`, writing nothing, if the block already exists.
+    pub fn block_member(
+        &self,
+        owner_vk: &VerifyingKey,
+        blocker: MemberId,
+        member: MemberId,
+        blocked_at: u64,
+    ) -> Result<bool> {
+        let room_bytes = owner_vk.to_bytes();
+        self.with_lock(|| {
+            let mut store = self.load_outbound_dms_unlocked()?;
+            if river_core::chat_delegate::blocked_by(&store.blocked_members, &room_bytes, blocker)
+                .contains(&member)
+            {
+                return Ok(false);
+            }
+            store.blocked_members.push(BlockedMemberEntry {
+                room_owner_vk: room_bytes,
+                blocker,
+                member,
+                blocked_at,
+            });
+            self.save_outbound_dms_unlocked(&store)?;
+            Ok(true)
+        })
+    }
+
+    /// Lift `blocker`'s block on `member` in `owner_vk`'s room. Returns
+    /// `false` if there was none. DMs already purged stay purged.
+    pub fn unblock_member(
+        &self,
+        owner_vk: &VerifyingKey,
+        blocker: MemberId,
+        member: MemberId,
+    ) -> Result<bool> {
+        let room_bytes = owner_vk.to_bytes();
+        self.with_lock(|| {
+            let mut store = self.load_outbound_dms_unlocked()?;
+            let before = store.blocked_members.len();
+            store.blocked_members.retain(|b| {
+                !(b.room_owner_vk == room_bytes && b.blocker == blocker && b.member == member)
+            });
+            if store.blocked_members.len() == before {
+                return Ok(false);
+            }
+            self.save_outbound_dms_unlocked(&store)?;
+            Ok(true)
+        })
+    }
+
     pub fn add_room(
         &self,
         owner_vk: &VerifyingKey,
@@ -872,6 +986,11 @@ impl Storage {
                         "import: failed to prune the previous identity's DM cache for {owner_key_str}: {e}"
                     );
                 }
+                if let Err(e) = self.prune_dm_ratchet_for_room_unlocked(owner_vk) {
+                    tracing::warn!(
+                        "import: failed to prune the previous identity's DM sessions for {owner_key_str}: {e}"
+                    );
+                }
             }
 
             Ok(ImportOutcome::Imported {
@@ -939,6 +1058,11 @@ impl Storage {
             let owner_key_str = bs58::encode(owner_vk.as_bytes()).into_string();
 
             if let Some(room_info) = storage.rooms.get_mut(&owner_key_str) {
+                // Best-effort, like the outbound-DM prune in `remove_room`: a
+                // failed archive append must not lose the state update.
+                if let Err(e) = self.archive_room_messages_unlocked(owner_vk, &state) {
+                    warn!("Failed to archive messages for room {owner_key_str}: {e:#}");
+                }
                 room_info.state = state;
                 self.save_rooms_unlocked(&storage)
             } else {
@@ -947,6 +1071,193 @@ impl Storage {
         })
     }
 
+    fn load_join_requests_unlocked(&self) -> Result<PendingJoinRequests> {
+        if !self.join_requests_path.exists() {
+            return Ok(PendingJoinRequests::default());
+        }
+        let contents = fs::read_to_string(&self.join_requests_path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// The join request this client has outstanding for `owner_vk`'s room.
+    pub fn pending_join_request(
+        &self,
+        owner_vk: &VerifyingKey,
+    ) -> Result<Option<PendingJoinRequest>> {
+        let key = bs58::encode(owner_vk.as_bytes()).into_string();
+        self.with_lock(|| Ok(self.load_join_requests_unlocked()?.rooms.remove(&key)))
+    }
+
+    /// Record (`Some`) or forget (`None`) the join request this client has
+    /// outstanding for `owner_vk`'s room.
+    pub fn set_pending_join_request(
+        &self,
+        owner_vk: &VerifyingKey,
+        request: Option<PendingJoinRequest>,
+    ) -> Result<()> {
+        let key = bs58::encode(owner_vk.as_bytes()).into_string();
+        self.with_lock(|| {
+            let mut store = self.load_join_requests_unlocked()?;
+            match request {
+                Some(request) => store.rooms.insert(key, request),
+                None => store.rooms.remove(&key),
+            };
+            let contents = serde_json::to_string_pretty(&store)?;
+            Self::atomic_write(&self.join_requests_path, &contents)
+        })
+    }
+
+    fn load_dm_ratchets_unlocked(&self) -> Result<DmRatchetStores> {
+        if !self.dm_ratchet_path.exists() {
+            return Ok(DmRatchetStores::default());
+        }
+        let contents = fs::read_to_string(&self.dm_ratchet_path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    fn save_dm_ratchets_unlocked(&self, stores: &DmRatchetStores) -> Result<()> {
+        let contents = serde_json::to_string_pretty(stores)?;
+        Self::atomic_write(&self.dm_ratchet_path, &contents)
+    }
+
+    /// The forward-secret DM state for `owner_vk`'s room, empty if none.
+    pub fn load_dm_ratchet(&self, owner_vk: &VerifyingKey) -> Result<RatchetStore> {
+        let key = bs58::encode(owner_vk.as_bytes()).into_string();
+        self.with_lock(|| {
+            Ok(self
+                .load_dm_ratchets_unlocked()?
+                .rooms
+                .remove(&key)
+                .unwrap_or_default())
+        })
+    }
+
+    /// Load, run `f` on, and save the forward-secret DM state for
+    /// `owner_vk`'s room under one advisory lock, writing only if `f` changed
+    /// it. A session that moved forward must be saved before the next
+    /// message, or its message keys would be reused.
+    pub fn mutate_dm_ratchet<T>(
+        &self,
+        owner_vk: &VerifyingKey,
+        f: impl FnOnce(&mut RatchetStore) -> Result<T>,
+    ) -> Result<T> {
+        let key = bs58::encode(owner_vk.as_bytes()).into_string();
+        self.with_lock(|| {
+            let mut stores = self.load_dm_ratchets_unlocked()?;
+            let store = stores.rooms.entry(key).or_default();
+            let before = store.clone();
+            let out = f(store)?;
+            if *store != before {
+                self.save_dm_ratchets_unlocked(&stores)?;
+            }
+            Ok(out)
+        })
+    }
+
+    /// Drop `owner_vk`'s room from `dm_ratchet.json`. Caller MUST already
+    /// hold the advisory lock.
+    fn prune_dm_ratchet_for_room_unlocked(&self, owner_vk: &VerifyingKey) -> Result<()> {
+        let mut stores = self.load_dm_ratchets_unlocked()?;
+        let key = bs58::encode(owner_vk.as_bytes()).into_string();
+        if stores.rooms.remove(&key).is_some() {
+            self.save_dm_ratchets_unlocked(&stores)?;
+        }
+        Ok(())
+    }
+
+    fn archive_path(&self, owner_vk: &VerifyingKey) -> PathBuf {
+        self.archive_dir.join(format!(
+            "{}.jsonl",
+            bs58::encode(owner_vk.as_bytes()).into_string()
+        ))
+    }
+
+    /// Append every message in `state` that the room's archive does not hold
+    /// yet, verified against its author's key. Returns how many were appended.
+    ///
+    /// The archive outlives the contract's `max_recent_messages` window, so
+    /// calling this on each fetched or updated state keeps every message this
+    /// client has seen. [`Self::update_room_state`] does so itself; the API's
+    /// `get_room` calls it for fetched state.
+    pub fn archive_room_messages(
+        &self,
+        owner_vk: &VerifyingKey,
+        state: &ChatRoomStateV1,
+    ) -> Result<usize> {
+        self.with_lock(|| self.archive_room_messages_unlocked(owner_vk, state))
+    }
+
+    /// Lock-free body of [`Self::archive_room_messages`]. Caller MUST hold the
+    /// advisory lock.
+    fn archive_room_messages_unlocked(
+        &self,
+        owner_vk: &VerifyingKey,
+        state: &ChatRoomStateV1,
+    ) -> Result<usize> {
+        let path = self.archive_path(owner_vk);
+        let contents = Self::read_archive(&path)?;
+        // Only the ids are needed to append; verification happens on load.
+        let archived: HashSet<MessageId> = Self::parse_archive(&contents)
+            .map(|entry| entry.message.id())
+            .collect();
+        let added = river_core::archive::new_entries(owner_vk, state, |id| archived.contains(id));
+        if added.is_empty() {
+            return Ok(0);
+        }
+        let mut lines = String::new();
+        // Terminate a torn last line so it does not swallow the first new one.
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            lines.push('\n');
+        }
+        for entry in &added {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        fs::create_dir_all(&self.archive_dir)
+            .with_context(|| format!("creating {}", self.archive_dir.display()))?;
+        // Appended in one write, so a crash leaves at most one torn last line,
+        // which loading skips.
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .with_context(|| format!("appending to {}", path.display()))?;
+        Ok(added.len())
+    }
+
+    /// The archive file at `path`, or empty if there is no archive yet.
+    fn read_archive(path: &Path) -> Result<String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+        }
+    }
+
+    /// Parse archive file contents line by line, skipping lines that do not
+    /// parse (a torn append).
+    fn parse_archive(contents: &str) -> impl Iterator<Item = ArchivedMessage> + '_ {
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+    }
+
+    /// Load and verify the room's message archive. Entries that fail
+    /// verification are dropped with a warning rather than failing the load.
+    pub fn load_archive(&self, owner_vk: &VerifyingKey) -> Result<MessageArchive> {
+        let contents = self.with_lock(|| Self::read_archive(&self.archive_path(owner_vk)))?;
+        let (archive, rejected) = MessageArchive::from_entries(Self::parse_archive(&contents));
+        if rejected > 0 {
+            warn!(
+                "Dropped {rejected} archived message(s) for room {} that failed signature verification",
+                bs58::encode(owner_vk.as_bytes()).into_string()
+            );
+        }
+        Ok(archive)
+    }
+
     /// Update the contract key for a room (used during migration to new contract version)
     pub fn update_contract_key(
         &self,
@@ -1003,6 +1314,11 @@ impl Storage {
                         "room leave: failed to prune outbound-DM cache for {owner_key_str}: {e}"
                     );
                 }
+                if let Err(e) = self.prune_dm_ratchet_for_room_unlocked(owner_vk) {
+                    tracing::warn!(
+                        "room leave: failed to prune DM sessions for {owner_key_str}: {e}"
+                    );
+                }
             }
             Ok(removed)
         })
@@ -1023,19 +1339,25 @@ impl Storage {
         self.with_lock(|| self.prune_outbound_dms_for_room_unlocked(owner_vk))
     }
 
-    /// Drop every cached outbound-DM plaintext entry and archived-thread entry
-    /// for `owner_vk`'s room from `outbound_dms.json`. No-op if the cache file
+    /// Drop every cached outbound-DM plaintext entry, archived-thread entry and
+    /// block for `owner_vk`'s room from `outbound_dms.json`. No-op if the cache file
     /// holds nothing for that room. Called by [`Self::remove_room`]; caller MUST
     /// already hold the advisory lock (see the [`Storage`] no-nesting rule).
     fn prune_outbound_dms_for_room_unlocked(&self, owner_vk: &VerifyingKey) -> Result<()> {
         let mut store = self.load_outbound_dms_unlocked()?;
         let room_bytes = owner_vk.to_bytes();
-        let before = store.entries.len() + store.hidden_threads.len();
+        let held = |store: &OutboundDmStore| {
+            store.entries.len() + store.hidden_threads.len() + store.blocked_members.len()
+        };
+        let before = held(&store);
         store.entries.retain(|e| e.room_owner_vk != room_bytes);
         store
             .hidden_threads
             .retain(|h| h.room_owner_vk != room_bytes);
-        if store.entries.len() + store.hidden_threads.len() != before {
+        store
+            .blocked_members
+            .retain(|b| b.room_owner_vk != room_bytes);
+        if held(&store) != before {
             self.save_outbound_dms_unlocked(&store)?;
         }
         Ok(())
@@ -1260,6 +1582,7 @@ mod tests {
             version: 0,
             preferred_nickname: nickname,
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         state
             .member_info
@@ -2177,6 +2500,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn archive_keeps_messages_past_the_retention_window() {
+        use river_core::room_state::message::{AuthorizedMessageV1, MessageV1, RoomMessageBody};
+
+        let (storage, temp_dir) = create_test_storage();
+        let owner_sk = create_test_signing_key();
+        let owner_vk = owner_sk.verifying_key();
+        let mut state = create_test_state(&owner_sk);
+        storage
+            .add_room(
+                &owner_vk,
+                &owner_sk,
+                state.clone(),
+                &expected_contract_key(&owner_vk),
+            )
+            .unwrap();
+        let message = |secs: u64, text: &str| {
+            AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: owner_vk.into(),
+                    author: owner_vk.into(),
+                    time: std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+                    content: RoomMessageBody::public(text.to_string()),
+                },
+                &owner_sk,
+            )
+        };
+        let first = message(1, "first");
+        let second = message(2, "second");
+
+        state.recent_messages.messages = vec![first.clone()];
+        storage.update_room_state(&owner_vk, state.clone()).unwrap();
+        // `first` has scrolled out of the contract's window.
+        state.recent_messages.messages = vec![second.clone()];
+        assert_eq!(storage.archive_room_messages(&owner_vk, &state).unwrap(), 1);
+        assert_eq!(storage.archive_room_messages(&owner_vk, &state).unwrap(), 0);
+
+        // A torn append and a tampered line are both skipped on load.
+        let path = temp_dir.path().join("archive").join(format!(
+            "{}.jsonl",
+            bs58::encode(owner_vk.as_bytes()).into_string()
+        ));
+        let mut tampered = ArchivedMessage {
+            author_vk: owner_vk,
+            message: message(4, "genuine"),
+        };
+        tampered.message.message.content = RoomMessageBody::public("forged".to_string());
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{}", serde_json::to_string(&tampered).unwrap()).unwrap();
+        write!(file, "{{\"author_vk\":").unwrap();
+        // The next append starts on a fresh line after the torn one.
+        let third = message(3, "third");
+        state.recent_messages.messages = vec![second.clone(), third.clone()];
+        assert_eq!(storage.archive_room_messages(&owner_vk, &state).unwrap(), 1);
+
+        let ids: Vec<_> = storage
+            .load_archive(&owner_vk)
+            .unwrap()
+            .entries()
+            .iter()
+            .map(|e| e.message.id())
+            .collect();
+        assert_eq!(ids, vec![first.id(), second.id(), third.id()]);
+    }
+
     #[test]
     fn test_load_rooms_sets_previous_contract_key_on_mismatch() {
         let (storage, _temp_dir) = create_test_storage();
@@ -2564,7 +2952,9 @@ mod tests {
     #[test]
     fn remove_room_prunes_outbound_dm_cache() {
         use freenet_scaffold::util::FastHash;
-        use river_core::chat_delegate::{HiddenDmThreadEntry, OutboundDmEntry, OutboundDmStore};
+        use river_core::chat_delegate::{
+            BlockedMemberEntry, HiddenDmThreadEntry, OutboundDmEntry, OutboundDmStore,
+        };
         use river_core::room_state::direct_messages::PurgeToken;
         use river_core::room_state::member::MemberId;
 
@@ -2614,6 +3004,12 @@ mod tests {
                     hidden_at_ts: 2,
                 },
             ],
+            blocked_members: vec![BlockedMemberEntry {
+                room_owner_vk: left_vk.to_bytes(),
+                blocker: peer,
+                member: peer,
+                blocked_at: 1,
+            }],
         };
         storage.save_outbound_dms(&store).unwrap();
 
@@ -2632,6 +3028,77 @@ mod tests {
             "the left room's archived-thread entry must be pruned"
         );
         assert_eq!(after.hidden_threads[0].room_owner_vk, kept_vk.to_bytes());
+        assert!(after.blocked_members.is_empty());
+    }
+
+    /// A block is per identity: another identity in the same room sees
+    /// nobody blocked, and blocking twice or unblocking a stranger is a no-op.
+    #[test]
+    fn block_list_is_per_identity_and_idempotent() {
+        use freenet_scaffold::util::FastHash;
+
+        let (storage, _temp_dir) = create_test_storage();
+        let owner_vk = create_test_signing_key().verifying_key();
+        let (me, other_me, troll) = (
+            MemberId(FastHash(1)),
+            MemberId(FastHash(2)),
+            MemberId(FastHash(3)),
+        );
+
+        assert!(storage.block_member(&owner_vk, me, troll, 10).unwrap());
+        assert!(!storage.block_member(&owner_vk, me, troll, 11).unwrap());
+        assert_eq!(
+            storage.blocked_members(&owner_vk, me).unwrap(),
+            HashSet::from([troll])
+        );
+        assert!(storage
+            .blocked_members(&owner_vk, other_me)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            storage.load_outbound_dms().unwrap().blocked_members.len(),
+            1
+        );
+
+        assert!(!storage.unblock_member(&owner_vk, other_me, troll).unwrap());
+        assert!(storage.unblock_member(&owner_vk, me, troll).unwrap());
+        assert!(storage.blocked_members(&owner_vk, me).unwrap().is_empty());
+    }
+
+    /// Leaving a room deletes its DM prekey secrets, sessions and opened
+    /// plaintext from `dm_ratchet.json`; other rooms' state survives.
+    #[test]
+    fn remove_room_prunes_dm_ratchet_store() {
+        let (storage, _temp_dir) = create_test_storage();
+        let left_sk = create_test_signing_key();
+        let left_vk = left_sk.verifying_key();
+        let kept_vk = create_test_signing_key().verifying_key();
+        let key = expected_contract_key(&left_vk);
+        storage
+            .add_room(
+                &left_vk,
+                &create_test_signing_key(),
+                create_test_state(&left_sk),
+                &key,
+            )
+            .unwrap();
+
+        for vk in [&left_vk, &kept_vk] {
+            storage
+                .mutate_dm_ratchet(vk, |store| Ok(store.rotate_prekey()))
+                .unwrap();
+        }
+        assert!(storage.remove_room(&left_vk).unwrap());
+
+        assert_eq!(
+            storage.load_dm_ratchet(&left_vk).unwrap().current_prekey(),
+            None
+        );
+        assert!(storage
+            .load_dm_ratchet(&kept_vk)
+            .unwrap()
+            .current_prekey()
+            .is_some());
     }
 
     /// freenet/river#414 (Codex round 4): `identity import --force` that swaps a
@@ -2685,6 +3152,7 @@ mod tests {
                         peer,
                         hidden_at_ts: 1,
                     }],
+                    blocked_members: vec![],
                 })
                 .unwrap();
         };
@@ -2868,6 +3336,7 @@ mod tests {
                     plaintext: "old-identity secret".to_string(),
                 }],
                 hidden_threads: vec![],
+                blocked_members: vec![],
             })
             .unwrap();
 
@@ -0,0 +1,1477 @@
+//! State, key handling and rendering for `riverctl tui`. Nothing here touches
+//! the terminal or the network: [`App::key`] turns a key press into an
+//! [`Action`] for `commands/tui.rs` to carry out, and [`App::render`] lays the
+//! screen out as plain rows, so both can be tested directly.
+
+use crate::commands::dm::DecryptedDm;
+use chrono::{DateTime, Local};
+use console::{Key, Style};
+use ed25519_dalek::VerifyingKey;
+use river_core::mention::{encode_mention, resolve_typed_mentions};
+use river_core::room_state::member::MemberId;
+use river_core::room_state::message::MessageId;
+use river_core::ChatRoomStateV1;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::SystemTime;
+use unicode_width::UnicodeWidthChar;
+
+/// Columns for the room list, shown when the terminal is at least
+/// [`ROOMS_MIN_WIDTH`] wide.
+const ROOMS_WIDTH: usize = 22;
+const ROOMS_MIN_WIDTH: usize = 70;
+/// Columns for the member panel, shown when toggled on and the terminal is at
+/// least [`MEMBERS_MIN_WIDTH`] wide.
+const MEMBERS_WIDTH: usize = 24;
+const MEMBERS_MIN_WIDTH: usize = 100;
+/// Lines moved by PageUp / PageDown.
+const PAGE: usize = 10;
+
+const HINTS: &str =
+    "Enter send · Tab @complete · ↑↓ select · ^R reply · ^N/^P room · ^D DMs · ^T members · /help";
+const HELP: &str = "/react E · /unreact E · /reply · /dm MEMBER [text] · /dms · /room · /go ROOM · /members · /quit";
+
+/// One room's state, reduced to what the screen shows.
+#[derive(Default)]
+pub struct RoomView {
+    pub messages: Vec<MessageItem>,
+    pub members: Vec<MemberItem>,
+    pub dms: Vec<DmItem>,
+}
+
+pub struct MessageItem {
+    pub id: MessageId,
+    pub time: String,
+    pub author: String,
+    pub mine: bool,
+    /// `[reply to …]` prefix, empty for a message that is not a reply.
+    pub reply: String,
+    pub text: String,
+    pub edited: bool,
+    pub reactions: Vec<ReactionItem>,
+}
+
+pub struct ReactionItem {
+    pub emoji: String,
+    pub count: usize,
+    pub mine: bool,
+}
+
+pub struct MemberItem {
+    pub id: MemberId,
+    pub nickname: String,
+    pub badge: &'static str,
+}
+
+pub struct DmItem {
+    pub peer: MemberId,
+    pub peer_name: String,
+    pub outgoing: bool,
+    pub time: String,
+    pub body: String,
+}
+
+impl RoomView {
+    /// `secrets` decrypt a private room's nicknames and bodies, as for
+    /// `message list`; DMs are added separately with [`RoomView::with_dms`].
+    pub fn build(
+        room_state: &ChatRoomStateV1,
+        owner_vk: &VerifyingKey,
+        self_id: MemberId,
+        secrets: &HashMap<u32, [u8; 32]>,
+    ) -> Self {
+        // `canonical`, not a bare `.find()` (#411 round 8 item A).
+        let nickname = |id: MemberId| {
+            room_state
+                .member_info
+                .canonical(id)
+                .map(|info| {
+                    crate::api::unseal_nickname_display(
+                        &info.member_info.preferred_nickname,
+                        secrets,
+                    )
+                })
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        let messages = room_state
+            .recent_messages
+            .display_messages()
+            .map(|msg| {
+                let id = msg.id();
+                let mut reactions: Vec<ReactionItem> = room_state
+                    .recent_messages
+                    .reactions(&id)
+                    .map(|reactions| {
+                        reactions
+                            .iter()
+                            .map(|(emoji, reactors)| ReactionItem {
+                                emoji: emoji.clone(),
+                                count: reactors.len(),
+                                mine: reactors.contains(&self_id),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                reactions.sort_by(|a, b| a.emoji.cmp(&b.emoji));
+                MessageItem {
+                    id: id.clone(),
+                    time: format_time(msg.message.time),
+                    author: nickname(msg.message.author),
+                    mine: msg.message.author == self_id,
+                    reply: crate::api::reply_prefix_display(
+                        &crate::api::reply_context_display_with_secrets(room_state, msg, secrets),
+                    )
+                    .trim_end()
+                    .to_string(),
+                    text: crate::api::message_display_text_with_secrets(room_state, msg, secrets),
+                    edited: room_state.recent_messages.is_edited(&id),
+                    reactions,
+                }
+            })
+            .collect();
+
+        let owner_id = MemberId::from(owner_vk);
+        let badge = |id: MemberId| match (id == owner_id, id == self_id) {
+            (true, true) => "owner, you",
+            (true, false) => "owner",
+            (false, true) => "you",
+            (false, false) => "",
+        };
+        let mut members: Vec<MemberItem> = room_state
+            .members
+            .members
+            .iter()
+            .map(|m| m.member.id())
+            .filter(|id| *id != owner_id)
+            .map(|id| MemberItem {
+                id,
+                nickname: nickname(id),
+                badge: badge(id),
+            })
+            .collect();
+        members.sort_by_key(|m| m.nickname.to_lowercase());
+        members.insert(
+            0,
+            MemberItem {
+                id: owner_id,
+                nickname: nickname(owner_id),
+                badge: badge(owner_id),
+            },
+        );
+
+        Self {
+            messages,
+            members,
+            dms: Vec::new(),
+        }
+    }
+
+    /// Member nicknames by id, as `dm::decrypt_dms` wants them.
+    pub fn nicknames(&self) -> HashMap<MemberId, String> {
+        self.members
+            .iter()
+            .map(|m| (m.id, m.nickname.clone()))
+            .collect()
+    }
+
+    pub(crate) fn with_dms(mut self, dms: Vec<DecryptedDm>) -> Self {
+        let names = self.nicknames();
+        self.dms = dms
+            .into_iter()
+            .map(|dm| DmItem {
+                peer: dm.counterparty,
+                peer_name: names
+                    .get(&dm.counterparty)
+                    .cloned()
+                    .unwrap_or_else(|| dm.counterparty.to_string()),
+                outgoing: dm.outgoing,
+                time: format_time(
+                    SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(dm.timestamp),
+                ),
+                body: dm.body,
+            })
+            .collect();
+        self
+    }
+}
+
+fn format_time(time: SystemTime) -> String {
+    let local: DateTime<Local> = time.into();
+    local.format("%H:%M").to_string()
+}
+
+/// Which of the room's two timelines the centre pane shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pane {
+    Room,
+    Dms,
+}
+
+pub struct RoomEntry {
+    pub owner_vk: VerifyingKey,
+    pub name: String,
+    /// `None` until the room's state has been fetched.
+    pub view: Option<RoomView>,
+    /// Messages from others that arrived while another room was open.
+    pub unread: usize,
+}
+
+/// What the driver has to do after a key press. Everything that sends acts on
+/// the current room.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    None,
+    Quit,
+    /// Repaint the whole screen.
+    Redraw,
+    /// The current room changed to one whose state has not been fetched.
+    Open(VerifyingKey),
+    Send(String),
+    Reply {
+        target: MessageId,
+        text: String,
+    },
+    React {
+        target: MessageId,
+        emoji: String,
+    },
+    Unreact {
+        target: MessageId,
+        emoji: String,
+    },
+    Dm {
+        recipient: String,
+        text: String,
+    },
+}
+
+pub struct App {
+    pub rooms: Vec<RoomEntry>,
+    pub current: usize,
+    pub pane: Pane,
+    pub show_members: bool,
+    /// One-line feedback, cleared by the next key press.
+    pub status: String,
+    composer: Composer,
+    selected: Option<MessageId>,
+    reply_to: Option<MessageId>,
+    /// Lines scrolled up from the bottom of the centre pane.
+    scroll: usize,
+    /// Furthest `scroll` can usefully go, as of the last render.
+    max_scroll: Cell<usize>,
+    dm_peer: Option<String>,
+    completion: Option<Completion>,
+}
+
+/// An in-progress Tab completion of an `@name`.
+struct Completion {
+    start: usize,
+    inserted: usize,
+    candidates: Vec<(String, MemberId)>,
+    index: usize,
+}
+
+#[derive(Default)]
+struct Composer {
+    chars: Vec<char>,
+    cursor: usize,
+    /// Members picked by completion, so the send can link them even when
+    /// `@name` alone would be ambiguous or private.
+    picked: Vec<(String, MemberId)>,
+}
+
+impl Composer {
+    fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    fn delete_word(&mut self) {
+        while self.cursor > 0 && self.chars[self.cursor - 1].is_whitespace() {
+            self.backspace();
+        }
+        while self.cursor > 0 && !self.chars[self.cursor - 1].is_whitespace() {
+            self.backspace();
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    fn take(&mut self) -> (String, Vec<(String, MemberId)>) {
+        let text = self.text();
+        let picked = std::mem::take(&mut self.picked);
+        self.clear();
+        (text, picked)
+    }
+}
+
+/// Link the mentions in an outgoing message: members picked by completion
+/// (including nicknames with spaces, which a typed `@name` cannot express),
+/// then any other `@name` that matches a picked nickname. The send path links
+/// the remaining public nicknames itself.
+pub(crate) fn expand_mentions(text: &str, picked: &[(String, MemberId)]) -> String {
+    let mut out = text.to_string();
+    for (name, id) in picked {
+        if name.chars().any(char::is_whitespace) {
+            out = out.replace(&format!("@{name}"), &encode_mention(*id, name));
+        }
+    }
+    resolve_typed_mentions(&out, |typed| {
+        picked
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(typed))
+            .map(|(name, id)| (*id, name.clone()))
+    })
+}
+
+impl App {
+    pub fn new(rooms: Vec<RoomEntry>, current: usize) -> Self {
+        Self {
+            rooms,
+            current,
+            pane: Pane::Room,
+            show_members: true,
+            status: String::new(),
+            composer: Composer::default(),
+            selected: None,
+            reply_to: None,
+            scroll: 0,
+            max_scroll: Cell::new(0),
+            dm_peer: None,
+            completion: None,
+        }
+    }
+
+    pub fn current_room(&self) -> &RoomEntry {
+        &self.rooms[self.current]
+    }
+
+    fn view(&self) -> Option<&RoomView> {
+        self.current_room().view.as_ref()
+    }
+
+    fn message(&self, id: &MessageId) -> Option<&MessageItem> {
+        self.view()?.messages.iter().find(|m| &m.id == id)
+    }
+
+    /// Install a freshly fetched view for room `index`. New messages from
+    /// others count as unread unless the room is open; the selection survives
+    /// as long as its message does.
+    pub fn set_view(&mut self, index: usize, view: RoomView) {
+        let room = &mut self.rooms[index];
+        if index != self.current {
+            if let Some(old) = &room.view {
+                let seen: HashSet<&MessageId> = old.messages.iter().map(|m| &m.id).collect();
+                room.unread += view
+                    .messages
+                    .iter()
+                    .filter(|m| !m.mine && !seen.contains(&m.id))
+                    .count();
+            }
+        }
+        room.view = Some(view);
+        if index == self.current {
+            if let Some(id) = self.selected.clone() {
+                if self.message(&id).is_none() {
+                    self.selected = None;
+                }
+            }
+            if let Some(id) = self.reply_to.clone() {
+                if self.message(&id).is_none() {
+                    self.reply_to = None;
+                    self.status = "The message you were replying to is gone".to_string();
+                }
+            }
+        }
+    }
+
+    fn switch_to(&mut self, index: usize) -> Action {
+        if index == self.current {
+            return Action::None;
+        }
+        self.current = index;
+        self.selected = None;
+        self.reply_to = None;
+        self.scroll = 0;
+        self.completion = None;
+        self.dm_peer = None;
+        let room = &mut self.rooms[index];
+        room.unread = 0;
+        if room.view.is_none() {
+            Action::Open(room.owner_vk)
+        } else {
+            Action::None
+        }
+    }
+
+    fn cycle_room(&mut self, forward: bool) -> Action {
+        let n = self.rooms.len();
+        let next = if forward {
+            (self.current + 1) % n
+        } else {
+            (self.current + n - 1) % n
+        };
+        self.switch_to(next)
+    }
+
+    fn move_selection(&mut self, up: bool) {
+        let Some(view) = self.view() else { return };
+        let ids: Vec<&MessageId> = view.messages.iter().map(|m| &m.id).collect();
+        if ids.is_empty() {
+            return;
+        }
+        let position = self
+            .selected
+            .as_ref()
+            .and_then(|id| ids.iter().position(|m| *m == id));
+        let next = match (position, up) {
+            (None, true) => Some(ids.len() - 1),
+            (None, false) => None,
+            (Some(0), true) => Some(0),
+            (Some(i), true) => Some(i - 1),
+            (Some(i), false) if i + 1 < ids.len() => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.selected = next.map(|i| ids[i].clone());
+        if self.selected.is_none() {
+            self.scroll = 0;
+        }
+    }
+
+    /// The message actions apply to: the selection, else the newest.
+    fn target(&self) -> Option<MessageId> {
+        self.selected
+            .clone()
+            .or_else(|| self.view()?.messages.last().map(|m| m.id.clone()))
+    }
+
+    pub fn key(&mut self, key: Key) -> Action {
+        self.status.clear();
+        if !matches!(key, Key::Tab) {
+            self.completion = None;
+        }
+        match key {
+            Key::CtrlC => Action::Quit,
+            Key::Enter => self.submit(),
+            Key::Tab => {
+                self.complete();
+                Action::None
+            }
+            Key::Backspace => {
+                self.composer.backspace();
+                Action::None
+            }
+            Key::Del => {
+                self.composer.delete();
+                Action::None
+            }
+            Key::ArrowLeft => {
+                self.composer.cursor = self.composer.cursor.saturating_sub(1);
+                Action::None
+            }
+            Key::ArrowRight => {
+                self.composer.cursor = (self.composer.cursor + 1).min(self.composer.chars.len());
+                Action::None
+            }
+            Key::Home => {
+                self.composer.cursor = 0;
+                Action::None
+            }
+            Key::End => {
+                self.composer.cursor = self.composer.chars.len();
+                Action::None
+            }
+            Key::ArrowUp | Key::ArrowDown if self.pane == Pane::Room => {
+                self.move_selection(matches!(key, Key::ArrowUp));
+                Action::None
+            }
+            Key::ArrowUp => {
+                self.scroll = (self.scroll + 1).min(self.max_scroll.get());
+                Action::None
+            }
+            Key::ArrowDown => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::None
+            }
+            Key::PageUp => {
+                self.selected = None;
+                self.scroll = (self.scroll + PAGE).min(self.max_scroll.get());
+                Action::None
+            }
+            Key::PageDown => {
+                self.selected = None;
+                self.scroll = self.scroll.saturating_sub(PAGE);
+                Action::None
+            }
+            Key::Escape => {
+                if self.reply_to.take().is_none() && self.selected.take().is_none() {
+                    self.scroll = 0;
+                }
+                Action::None
+            }
+            // Ctrl-N / Ctrl-P
+            Key::Char('\x0e') => self.cycle_room(true),
+            Key::Char('\x10') => self.cycle_room(false),
+            // Ctrl-R
+            Key::Char('\x12') => {
+                self.start_reply();
+                Action::None
+            }
+            // Ctrl-D
+            Key::Char('\x04') => {
+                self.toggle_pane();
+                Action::None
+            }
+            // Ctrl-T
+            Key::Char('\x14') => {
+                self.show_members = !self.show_members;
+                Action::None
+            }
+            // Ctrl-L
+            Key::Char('\x0c') => Action::Redraw,
+            // Ctrl-U
+            Key::Char('\x15') => {
+                self.composer.clear();
+                Action::None
+            }
+            // Ctrl-W
+            Key::Char('\x17') => {
+                self.composer.delete_word();
+                Action::None
+            }
+            Key::Char(c) if !c.is_control() => {
+                self.composer.insert(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn toggle_pane(&mut self) {
+        self.pane = match self.pane {
+            Pane::Room => Pane::Dms,
+            Pane::Dms => Pane::Room,
+        };
+        self.scroll = 0;
+    }
+
+    fn start_reply(&mut self) {
+        match self.target() {
+            Some(id) => {
+                self.reply_to = Some(id);
+                self.pane = Pane::Room;
+            }
+            None => self.status = "No message to reply to".to_string(),
+        }
+    }
+
+    fn complete(&mut self) {
+        if let Some(completion) = &mut self.completion {
+            let start = completion.start;
+            let end = start + completion.inserted;
+            completion.index = (completion.index + 1) % completion.candidates.len();
+            let (name, id) = completion.candidates[completion.index].clone();
+            let insert = format!("@{name} ");
+            completion.inserted = insert.chars().count();
+            self.composer.chars.splice(start..end, insert.chars());
+            self.composer.cursor = start + completion.inserted;
+            self.composer.picked.push((name, id));
+            return;
+        }
+
+        let chars = &self.composer.chars;
+        let mut start = self.composer.cursor;
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        if chars.get(start) != Some(&'@') {
+            return;
+        }
+        let prefix: String = chars[start + 1..self.composer.cursor]
+            .iter()
+            .collect::<String>()
+            .to_lowercase();
+        let candidates: Vec<(String, MemberId)> = self
+            .view()
+            .map(|view| {
+                view.members
+                    .iter()
+                    .filter(|m| m.nickname.to_lowercase().starts_with(&prefix))
+                    .map(|m| (m.nickname.clone(), m.id))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if candidates.is_empty() {
+            self.status = format!("No member matches @{prefix}");
+            return;
+        }
+        let (name, id) = candidates[0].clone();
+        let insert = format!("@{name} ");
+        let end = self.composer.cursor;
+        self.composer.chars.splice(start..end, insert.chars());
+        self.composer.cursor = start + insert.chars().count();
+        self.composer.picked.push((name, id));
+        if candidates.len() > 1 {
+            self.completion = Some(Completion {
+                start,
+                inserted: insert.chars().count(),
+                candidates,
+                index: 0,
+            });
+        }
+    }
+
+    fn submit(&mut self) -> Action {
+        let (text, picked) = self.composer.take();
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Action::None;
+        }
+        if let Some(command) = trimmed.strip_prefix('/') {
+            return self.command(command);
+        }
+        let text = expand_mentions(trimmed, &picked);
+        match self.pane {
+            Pane::Dms => match &self.dm_peer {
+                Some(peer) => Action::Dm {
+                    recipient: peer.clone(),
+                    text,
+                },
+                None => {
+                    self.status =
+                        "Pick who to write to with /dm MEMBER (nickname or member ID)".to_string();
+                    Action::None
+                }
+            },
+            Pane::Room => match self.reply_to.take() {
+                Some(target) => Action::Reply { target, text },
+                None => Action::Send(text),
+            },
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Action {
+        let (name, rest) = command
+            .split_once(char::is_whitespace)
+            .map(|(name, rest)| (name, rest.trim()))
+            .unwrap_or((command, ""));
+        match name {
+            "q" | "quit" => Action::Quit,
+            "help" => {
+                self.status = HELP.to_string();
+                Action::None
+            }
+            "dms" => {
+                self.pane = Pane::Dms;
+                self.scroll = 0;
+                Action::None
+            }
+            "room" => {
+                self.pane = Pane::Room;
+                self.scroll = 0;
+                Action::None
+            }
+            "members" => {
+                self.show_members = !self.show_members;
+                Action::None
+            }
+            "go" => self.go(rest),
+            "reply" => {
+                self.start_reply();
+                Action::None
+            }
+            "react" | "unreact" if !rest.is_empty() => match self.target() {
+                Some(target) if name == "react" => Action::React {
+                    target,
+                    emoji: rest.to_string(),
+                },
+                Some(target) => Action::Unreact {
+                    target,
+                    emoji: rest.to_string(),
+                },
+                None => {
+                    self.status = "No message to react to".to_string();
+                    Action::None
+                }
+            },
+            "dm" if !rest.is_empty() => {
+                let (recipient, text) = rest
+                    .split_once(char::is_whitespace)
+                    .map(|(r, t)| (r, t.trim()))
+                    .unwrap_or((rest, ""));
+                self.dm_peer = Some(recipient.to_string());
+                self.pane = Pane::Dms;
+                if text.is_empty() {
+                    Action::None
+                } else {
+                    Action::Dm {
+                        recipient: recipient.to_string(),
+                        text: text.to_string(),
+                    }
+                }
+            }
+            "react" | "unreact" | "dm" => {
+                self.status = format!("Usage: {}", usage(name));
+                Action::None
+            }
+            _ => {
+                self.status = format!("Unknown command /{name}. {HELP}");
+                Action::None
+            }
+        }
+    }
+
+    /// `/go`: a room by its 1-based position in the list, or the first whose
+    /// name starts with `query`.
+    fn go(&mut self, query: &str) -> Action {
+        if query.is_empty() {
+            self.status = format!("Usage: {}", usage("go"));
+            return Action::None;
+        }
+        let query_lower = query.to_lowercase();
+        let index = query
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .filter(|i| *i < self.rooms.len())
+            .or_else(|| {
+                self.rooms
+                    .iter()
+                    .position(|r| r.name.to_lowercase().starts_with(&query_lower))
+            });
+        match index {
+            Some(index) => self.switch_to(index),
+            None => {
+                self.status = format!("No room matches '{query}'");
+                Action::None
+            }
+        }
+    }
+
+    /// Lay the screen out as `height` rows of exactly `width` columns, plus
+    /// where the cursor goes.
+    pub fn render(&self, width: usize, height: usize) -> Frame {
+        if width < 30 || height < 6 {
+            let mut rows = vec![pad(&Line::plain("Terminal too small"), width)];
+            rows.resize(height, " ".repeat(width));
+            return Frame {
+                rows,
+                cursor: (0, 0),
+            };
+        }
+
+        let body_height = height - 3;
+        let show_rooms = width >= ROOMS_MIN_WIDTH;
+        let show_members =
+            self.show_members && self.pane == Pane::Room && width >= MEMBERS_MIN_WIDTH;
+        let mut centre_width = width;
+        if show_rooms {
+            centre_width -= ROOMS_WIDTH + 1;
+        }
+        if show_members {
+            centre_width -= MEMBERS_WIDTH + 1;
+        }
+
+        let centre = match self.pane {
+            Pane::Room => self.conversation(centre_width, body_height),
+            Pane::Dms => self.dm_lines(centre_width, body_height),
+        };
+        let rooms = show_rooms.then(|| self.room_list(body_height));
+        let members = show_members.then(|| self.member_list(body_height));
+
+        let separator = Style::new().dim().apply_to("│").to_string();
+        let mut rows = Vec::with_capacity(height);
+        rows.push(self.title(width));
+        for i in 0..body_height {
+            let mut row = String::new();
+            if let Some(rooms) = &rooms {
+                row.push_str(&pad(&rooms[i], ROOMS_WIDTH));
+                row.push_str(&separator);
+            }
+            row.push_str(&pad(&centre[i], centre_width));
+            if let Some(members) = &members {
+                row.push_str(&separator);
+                row.push_str(&pad(&members[i], MEMBERS_WIDTH));
+            }
+            rows.push(row);
+        }
+        rows.push(pad(&self.status_line(), width));
+        let (composer, cursor) = self.composer_line(width);
+        rows.push(composer);
+        Frame {
+            rows,
+            cursor: (cursor, height - 1),
+        }
+    }
+
+    fn title(&self, width: usize) -> String {
+        let room = self.current_room();
+        let pane = match self.pane {
+            Pane::Room => "room",
+            Pane::Dms => "direct messages",
+        };
+        let members = room
+            .view
+            .as_ref()
+            .map(|v| match v.members.len() {
+                1 => "1 member ".to_string(),
+                n => format!("{n} members "),
+            })
+            .unwrap_or_default();
+        let left = format!(" River │ {} │ {}", clean(&room.name), pane);
+        let gap = width.saturating_sub(text_width(&left) + text_width(&members));
+        let line = Line::plain(&format!("{left}{}{members}", " ".repeat(gap)));
+        pad(&line.reversed(), width)
+    }
+
+    fn status_line(&self) -> Line {
+        let dim = Style::new().dim();
+        if let Some(completion) = &self.completion {
+            let names: Vec<&str> = completion
+                .candidates
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            return Line::styled(&format!("Tab: {}", names.join(", ")), dim);
+        }
+        if !self.status.is_empty() {
+            return Line::styled(&self.status, Style::new().yellow());
+        }
+        if let Some(message) = self.reply_to.as_ref().and_then(|id| self.message(id)) {
+            return Line::styled(
+                &format!(
+                    "Replying to {}: {} (Esc cancels)",
+                    clean(&message.author),
+                    clean(&message.text)
+                ),
+                Style::new().cyan(),
+            );
+        }
+        if self.pane == Pane::Dms {
+            if let Some(peer) = &self.dm_peer {
+                return Line::styled(
+                    &format!("Writing to {peer} · /dm MEMBER to switch · ^D back"),
+                    dim,
+                );
+            }
+        }
+        Line::styled(HINTS, dim)
+    }
+
+    fn composer_line(&self, width: usize) -> (String, usize) {
+        let prompt = match (&self.pane, &self.dm_peer) {
+            (Pane::Dms, Some(peer)) => format!("dm {} › ", clean(peer)),
+            _ => "› ".to_string(),
+        };
+        let prompt_width = text_width(&prompt);
+        let available = width.saturating_sub(prompt_width + 1).max(1);
+
+        // Scroll horizontally so the cursor stays on screen.
+        let widths: Vec<usize> = self.composer.chars.iter().map(|c| char_width(*c)).collect();
+        let mut start = 0;
+        while widths[start..self.composer.cursor].iter().sum::<usize>() > available {
+            start += 1;
+        }
+        let mut shown = String::new();
+        let mut used = 0;
+        for (c, w) in self.composer.chars[start..].iter().zip(&widths[start..]) {
+            if used + w > available {
+                break;
+            }
+            shown.push(if c.is_control() { ' ' } else { *c });
+            used += w;
+        }
+        let cursor = prompt_width + widths[start..self.composer.cursor].iter().sum::<usize>();
+        let line = Line {
+            segments: vec![(prompt, Style::new().bold()), (shown, Style::new())],
+        };
+        (pad(&line, width), cursor)
+    }
+
+    fn room_list(&self, height: usize) -> Vec<Line> {
+        let mut lines = vec![Line::styled(" Rooms", Style::new().bold())];
+        for (i, room) in self.rooms.iter().enumerate() {
+            let unread = if room.unread > 0 {
+                format!(" ({})", room.unread)
+            } else {
+                String::new()
+            };
+            let text = format!(" {}{}", clean(&room.name), unread);
+            let line = if i == self.current {
+                Line::plain(&text).reversed()
+            } else if room.unread > 0 {
+                Line::styled(&text, Style::new().bold())
+            } else {
+                Line::plain(&text)
+            };
+            lines.push(line);
+        }
+        fit_top(lines, height)
+    }
+
+    fn member_list(&self, height: usize) -> Vec<Line> {
+        let Some(view) = self.view() else {
+            return fit_top(Vec::new(), height);
+        };
+        let mut lines = vec![Line::styled(
+            &format!(" Members ({})", view.members.len()),
+            Style::new().bold(),
+        )];
+        for member in &view.members {
+            let mut line = Line::plain(&format!(" {}", clean(&member.nickname)));
+            if !member.badge.is_empty() {
+                line.segments
+                    .push((format!(" ({})", member.badge), Style::new().dim()));
+            }
+            lines.push(line);
+        }
+        fit_top(lines, height)
+    }
+
+    fn conversation(&self, width: usize, height: usize) -> Vec<Line> {
+        let Some(view) = self.view() else {
+            return fit_bottom(
+                vec![Line::plain(" Loading…")],
+                height,
+                0,
+                None,
+                &self.max_scroll,
+            );
+        };
+        if view.messages.is_empty() {
+            return fit_bottom(
+                vec![Line::styled(" No messages yet", Style::new().dim())],
+                height,
+                0,
+                None,
+                &self.max_scroll,
+            );
+        }
+
+        let mut lines = Vec::new();
+        let mut selected_range = None;
+        for message in &view.messages {
+            let first = lines.len();
+            let highlight = self.selected.as_ref() == Some(&message.id);
+            let mut block = Vec::new();
+            if !message.reply.is_empty() {
+                block.push(Line::styled(
+                    &format!("  ↳ {}", clean(&message.reply)),
+                    Style::new().dim(),
+                ));
+            }
+            let author_style = if message.mine {
+                Style::new().green().bold()
+            } else {
+                Style::new().cyan().bold()
+            };
+            let head = format!("{} ", message.time);
+            let name = format!("{}: ", clean(&message.author));
+            let head_width = text_width(&head) + text_width(&name);
+            let mut text = message.text.clone();
+            if message.edited {
+                text.push_str(" (edited)");
+            }
+            let first_width = width.saturating_sub(head_width).max(10);
+            let wrapped = wrap(&text, first_width, width.saturating_sub(2).max(10));
+            for (i, part) in wrapped.into_iter().enumerate() {
+                if i == 0 {
+                    block.push(Line {
+                        segments: vec![
+                            (head.clone(), Style::new().dim()),
+                            (name.clone(), author_style.clone()),
+                            (part, Style::new()),
+                        ],
+                    });
+                } else {
+                    block.push(Line::plain(&format!("  {part}")));
+                }
+            }
+            if !message.reactions.is_empty() {
+                let mut line = Line::plain("  ");
+                for reaction in &message.reactions {
+                    let style = if reaction.mine {
+                        Style::new().bold()
+                    } else {
+                        Style::new().dim()
+                    };
+                    line.segments.push((
+                        format!("{} {}  ", clean(&reaction.emoji), reaction.count),
+                        style,
+                    ));
+                }
+                block.push(line);
+            }
+            if highlight {
+                block = block.into_iter().map(Line::reversed).collect();
+            }
+            lines.extend(block);
+            if highlight {
+                selected_range = Some((first, lines.len()));
+            }
+        }
+        fit_bottom(lines, height, self.scroll, selected_range, &self.max_scroll)
+    }
+
+    fn dm_lines(&self, width: usize, height: usize) -> Vec<Line> {
+        let Some(view) = self.view() else {
+            return fit_bottom(
+                vec![Line::plain(" Loading…")],
+                height,
+                0,
+                None,
+                &self.max_scroll,
+            );
+        };
+        if view.dms.is_empty() {
+            return fit_bottom(
+                vec![Line::styled(
+                    " No direct messages in this room",
+                    Style::new().dim(),
+                )],
+                height,
+                0,
+                None,
+                &self.max_scroll,
+            );
+        }
+        let mut threads: BTreeMap<(String, MemberId), Vec<&DmItem>> = BTreeMap::new();
+        for dm in &view.dms {
+            threads
+                .entry((dm.peer_name.to_lowercase(), dm.peer))
+                .or_default()
+                .push(dm);
+        }
+        let mut lines = Vec::new();
+        for ((_, peer), dms) in threads {
+            lines.push(Line::styled(
+                &format!("── {} ({}) ──", clean(&dms[0].peer_name), peer),
+                Style::new().bold(),
+            ));
+            for dm in dms {
+                let head = format!("{} {} ", dm.time, if dm.outgoing { "→" } else { "←" });
+                let first_width = width.saturating_sub(text_width(&head)).max(10);
+                for (i, part) in wrap(&dm.body, first_width, width.saturating_sub(2).max(10))
+                    .into_iter()
+                    .enumerate()
+                {
+                    if i == 0 {
+                        lines.push(Line {
+                            segments: vec![
+                                (head.clone(), Style::new().dim()),
+                                (part, Style::new()),
+                            ],
+                        });
+                    } else {
+                        lines.push(Line::plain(&format!("  {part}")));
+                    }
+                }
+            }
+        }
+        fit_bottom(lines, height, self.scroll, None, &self.max_scroll)
+    }
+}
+
+/// A rendered screen.
+pub struct Frame {
+    pub rows: Vec<String>,
+    /// Column and row of the cursor.
+    pub cursor: (usize, usize),
+}
+
+/// A row made of styled runs, kept unstyled until [`pad`] fits it.
+#[derive(Clone)]
+struct Line {
+    segments: Vec<(String, Style)>,
+}
+
+impl Line {
+    fn plain(text: &str) -> Self {
+        Self {
+            segments: vec![(text.to_string(), Style::new())],
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            segments: Vec::new(),
+        }
+    }
+
+    fn styled(text: &str, style: Style) -> Self {
+        Self {
+            segments: vec![(text.to_string(), style)],
+        }
+    }
+
+    /// Reverse video over the whole line, keeping each run's colour.
+    fn reversed(mut self) -> Self {
+        for (_, style) in &mut self.segments {
+            *style = style.clone().reverse();
+        }
+        self
+    }
+}
+
+/// Fit `line` to exactly `width` columns: truncate, then pad with spaces
+/// carrying the last run's style (so a reversed row stays reversed).
+fn pad(line: &Line, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    let mut last_style = Style::new();
+    for (text, style) in &line.segments {
+        let mut run = String::new();
+        for c in text.chars() {
+            let w = char_width(c);
+            if used + w > width {
+                break;
+            }
+            run.push(c);
+            used += w;
+        }
+        out.push_str(&style.apply_to(&run).to_string());
+        last_style = style.clone();
+        if used >= width {
+            break;
+        }
+    }
+    if used < width {
+        out.push_str(&last_style.apply_to(" ".repeat(width - used)).to_string());
+    }
+    out
+}
+
+/// Lines for a top-anchored pane.
+fn fit_top(mut lines: Vec<Line>, height: usize) -> Vec<Line> {
+    lines.truncate(height);
+    lines.resize(height, Line::empty());
+    lines
+}
+
+/// Lines for a bottom-anchored pane scrolled `scroll` lines up, moved as
+/// needed to keep the `selected` line range in view. Records how far the
+/// pane can scroll in `max_scroll`.
+fn fit_bottom(
+    lines: Vec<Line>,
+    height: usize,
+    scroll: usize,
+    selected: Option<(usize, usize)>,
+    max_scroll: &Cell<usize>,
+) -> Vec<Line> {
+    let total = lines.len();
+    max_scroll.set(total.saturating_sub(height));
+    let mut end = total - scroll.min(total.saturating_sub(height));
+    if let Some((first, last)) = selected {
+        if last > end {
+            end = last;
+        } else if first < end.saturating_sub(height) {
+            end = (first + height).min(total);
+        }
+    }
+    let start = end.saturating_sub(height);
+    let mut window: Vec<Line> = vec![Line::empty(); height - (end - start)];
+    window.extend(lines.into_iter().skip(start).take(end - start));
+    window
+}
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Replace control characters, so text from other members cannot move the
+/// cursor or restyle the terminal.
+fn clean(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// Word-wrap `text` to `first` columns on the first line and `rest` after,
+/// breaking inside words only when a word is longer than a line. Newlines in
+/// the text start a new line.
+fn wrap(text: &str, first: usize, rest: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut used = 0;
+    let limit = |lines: &Vec<String>| if lines.is_empty() { first } else { rest };
+    for (i, paragraph) in text.split('\n').enumerate() {
+        if i > 0 {
+            lines.push(std::mem::take(&mut line));
+            used = 0;
+        }
+        for word in clean(paragraph).split(' ') {
+            let word_width = text_width(word);
+            let space = usize::from(used > 0);
+            if used + space + word_width <= limit(&lines) {
+                if space == 1 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                used += space + word_width;
+                continue;
+            }
+            if used > 0 {
+                lines.push(std::mem::take(&mut line));
+                used = 0;
+            }
+            for c in word.chars() {
+                let w = char_width(c);
+                if used + w > limit(&lines) && used > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    used = 0;
+                }
+                line.push(c);
+                used += w;
+            }
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+fn usage(command: &str) -> &'static str {
+    match command {
+        "react" => "/react EMOJI (reacts to the selected message, else the newest)",
+        "unreact" => "/unreact EMOJI",
+        "dm" => "/dm MEMBER [text] (nickname or member ID)",
+        _ => "/go ROOM (list position or name prefix)",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use freenet_scaffold::util::FastHash;
+
+    fn member(seed: u8) -> MemberId {
+        MemberId::from(&SigningKey::from_bytes(&[seed; 32]).verifying_key())
+    }
+
+    fn message(n: i64, author: &str, text: &str, mine: bool) -> MessageItem {
+        MessageItem {
+            id: MessageId(FastHash(n)),
+            time: "12:00".to_string(),
+            author: author.to_string(),
+            mine,
+            reply: String::new(),
+            text: text.to_string(),
+            edited: false,
+            reactions: Vec::new(),
+        }
+    }
+
+    fn view(messages: Vec<MessageItem>) -> RoomView {
+        RoomView {
+            messages,
+            members: vec![
+                MemberItem {
+                    id: member(1),
+                    nickname: "alice".to_string(),
+                    badge: "owner",
+                },
+                MemberItem {
+                    id: member(2),
+                    nickname: "alex".to_string(),
+                    badge: "",
+                },
+                MemberItem {
+                    id: member(3),
+                    nickname: "Bob Smith".to_string(),
+                    badge: "you",
+                },
+            ],
+            dms: Vec::new(),
+        }
+    }
+
+    fn app() -> App {
+        let room = |seed: u8, name: &str, view: Option<RoomView>| RoomEntry {
+            owner_vk: SigningKey::from_bytes(&[seed; 32]).verifying_key(),
+            name: name.to_string(),
+            view,
+            unread: 0,
+        };
+        App::new(
+            vec![
+                room(
+                    1,
+                    "general",
+                    Some(view(vec![message(1, "alice", "hi", false)])),
+                ),
+                room(2, "random", None),
+            ],
+            0,
+        )
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.key(Key::Char(c));
+        }
+    }
+
+    #[test]
+    fn tab_completes_and_cycles_mentions_then_links_them_on_send() {
+        let mut app = app();
+        type_text(&mut app, "hey @al");
+        app.key(Key::Tab);
+        assert_eq!(app.composer.text(), "hey @alice ");
+        app.key(Key::Tab);
+        assert_eq!(app.composer.text(), "hey @alex ");
+        app.key(Key::Tab);
+        assert_eq!(app.composer.text(), "hey @alice ");
+
+        type_text(&mut app, "and @b");
+        app.key(Key::Tab);
+        assert_eq!(app.composer.text(), "hey @alice and @Bob Smith ");
+        let Action::Send(text) = app.key(Key::Enter) else {
+            panic!("expected a send");
+        };
+        assert_eq!(
+            text,
+            format!(
+                "hey {} and {}",
+                encode_mention(member(1), "alice"),
+                encode_mention(member(3), "Bob Smith")
+            )
+        );
+        assert_eq!(app.composer.text(), "");
+    }
+
+    #[test]
+    fn commands_act_on_the_selected_message_and_the_reply_target() {
+        let mut app = app();
+        app.set_view(
+            0,
+            view(vec![
+                message(1, "alice", "first", false),
+                message(2, "alex", "second", false),
+            ]),
+        );
+        type_text(&mut app, "/react 👍");
+        assert_eq!(
+            app.key(Key::Enter),
+            Action::React {
+                target: MessageId(FastHash(2)),
+                emoji: "👍".to_string()
+            }
+        );
+
+        app.key(Key::ArrowUp);
+        app.key(Key::ArrowUp);
+        app.key(Key::Char('\x12'));
+        type_text(&mut app, "agreed");
+        assert_eq!(
+            app.key(Key::Enter),
+            Action::Reply {
+                target: MessageId(FastHash(1)),
+                text: "agreed".to_string()
+            }
+        );
+
+        type_text(&mut app, "/dm alex hello there");
+        assert_eq!(
+            app.key(Key::Enter),
+            Action::Dm {
+                recipient: "alex".to_string(),
+                text: "hello there".to_string()
+            }
+        );
+        assert_eq!(app.pane, Pane::Dms);
+        type_text(&mut app, "again");
+        assert!(matches!(app.key(Key::Enter), Action::Dm { .. }));
+
+        type_text(&mut app, "/nope");
+        assert_eq!(app.key(Key::Enter), Action::None);
+        assert!(app.status.starts_with("Unknown command /nope"));
+        type_text(&mut app, "/quit");
+        assert_eq!(app.key(Key::Enter), Action::Quit);
+    }
+
+    #[test]
+    fn switching_rooms_opens_unloaded_ones_and_counts_unread() {
+        let mut app = app();
+        let random = app.rooms[1].owner_vk;
+        assert_eq!(app.key(Key::Char('\x0e')), Action::Open(random));
+        app.set_view(1, view(Vec::new()));
+
+        // The first load of a room is history, not unread.
+        app.set_view(0, view(vec![message(1, "alice", "hi", false)]));
+        app.set_view(
+            0,
+            view(vec![
+                message(1, "alice", "hi", false),
+                message(2, "alex", "new", false),
+                message(3, "Bob Smith", "mine", true),
+            ]),
+        );
+        assert_eq!(app.rooms[0].unread, 1);
+        type_text(&mut app, "/go gen");
+        assert_eq!(app.key(Key::Enter), Action::None);
+        assert_eq!(app.current, 0);
+        assert_eq!(app.rooms[0].unread, 0);
+    }
+
+    #[test]
+    fn render_fills_the_screen_and_keeps_the_newest_message_visible() {
+        let mut app = app();
+        let long = "word ".repeat(60);
+        let mut messages: Vec<MessageItem> = (0..40)
+            .map(|n| message(n, "alice", &format!("message {n}"), false))
+            .collect();
+        messages.push(message(40, "alex", &long, false));
+        messages.push(message(41, "alex", "\x1b[2Jlast", false));
+        app.set_view(0, view(messages));
+
+        for (width, height) in [(120, 30), (80, 24), (40, 10)] {
+            let frame = app.render(width, height);
+            assert_eq!(frame.rows.len(), height);
+            for row in &frame.rows {
+                assert_eq!(console::measure_text_width(row), width, "{row:?}");
+            }
+            let body = frame.rows.join("\n");
+            assert!(body.contains("last"));
+            assert!(
+                !body.contains("\x1b[2J"),
+                "control characters are not passed through"
+            );
+        }
+
+        app.key(Key::PageUp);
+        let frame = app.render(120, 30);
+        assert!(!frame.rows.join("\n").contains("last"));
+    }
+
+    #[test]
+    fn wrap_breaks_on_words_and_splits_long_ones() {
+        assert_eq!(wrap("one two three", 7, 7), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4, 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("a\nb", 10, 10), vec!["a", "b"]);
+        assert_eq!(wrap("", 10, 10), vec![""]);
+    }
+}
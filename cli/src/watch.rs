@@ -0,0 +1,186 @@
+//! Follows every room this identity is in over one node connection and
+//! publishes what changes as decoded events, for the long-running front ends
+//! (`riverctl serve`, `riverctl irc-gateway`).
+//!
+//! The events are those of `message stream --format json` (`message`,
+//! `edit`, `delete`, `reaction`) plus `dm` for a direct message addressed to
+//! us. Only changes after a room is first read are published.
+
+use crate::api::{ApiClient, RoomEventTracker, RoomSubscription};
+use crate::commands::dm;
+use crate::output::OutputFormat;
+use anyhow::Result;
+use ed25519_dalek::VerifyingKey;
+use freenet_stdlib::prelude::ContractInstanceId;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+/// Events held for a slow receiver before it starts missing them.
+pub const EVENT_BUFFER: usize = 1024;
+
+/// One decoded event of `room`.
+pub struct RoomEvent {
+    pub room: VerifyingKey,
+    pub event: Value,
+}
+
+pub type RoomEvents = broadcast::Sender<Arc<RoomEvent>>;
+
+/// Follow every room for as long as the caller polls, publishing to
+/// `events`; rooms sent on `joined` are followed from then on. A notification
+/// can be consumed by another task reading the shared connection, and DM
+/// inboxes are not subscribed to, so every room and inbox is also re-read
+/// every `resync`. Returns only when the node connection
+/// fails.
+pub async fn watch_rooms(
+    api: &ApiClient,
+    events: &RoomEvents,
+    mut joined: mpsc::UnboundedReceiver<VerifyingKey>,
+    resync: Duration,
+) -> Result<()> {
+    let mut rooms: HashMap<ContractInstanceId, WatchedRoom> = HashMap::new();
+    let mut subscription = RoomSubscription::default();
+    for room in api.list_rooms().await? {
+        follow(api, events, &mut rooms, &mut subscription, room.owner_vk).await;
+    }
+
+    let mut resync = tokio::time::interval_at(tokio::time::Instant::now() + resync, resync);
+    loop {
+        let wake = tokio::select! {
+            Some(owner_vk) = joined.recv() => Wake::Joined(owner_vk),
+            changed = api.next_contract_update(&mut subscription) => Wake::Changed(changed?),
+            _ = resync.tick() => Wake::Resync,
+        };
+        match wake {
+            Wake::Joined(owner_vk) => {
+                follow(api, events, &mut rooms, &mut subscription, owner_vk).await
+            }
+            Wake::Changed(Some(contract)) => {
+                if let Some(room) = rooms.get_mut(&contract) {
+                    room.refresh(api, events).await;
+                }
+            }
+            Wake::Changed(None) => {}
+            Wake::Resync => {
+                for room in rooms.values_mut() {
+                    room.refresh(api, events).await;
+                }
+            }
+        }
+    }
+}
+
+/// What woke the watcher.
+enum Wake {
+    Joined(VerifyingKey),
+    Changed(Option<ContractInstanceId>),
+    Resync,
+}
+
+async fn follow(
+    api: &ApiClient,
+    events: &RoomEvents,
+    rooms: &mut HashMap<ContractInstanceId, WatchedRoom>,
+    subscription: &mut RoomSubscription,
+    owner_vk: VerifyingKey,
+) {
+    let contract = *api.owner_vk_to_contract_key(&owner_vk).id();
+    if rooms.contains_key(&contract) {
+        return;
+    }
+    match api.subscribe_to_room(&owner_vk, OutputFormat::Json).await {
+        Ok(room_subscription) => subscription.absorb(room_subscription),
+        // Still followed: the periodic re-read picks its changes up.
+        Err(e) => tracing::warn!(
+            "Failed to subscribe to room {}: {}",
+            bs58::encode(owner_vk.as_bytes()).into_string(),
+            e
+        ),
+    }
+    let mut room = WatchedRoom {
+        owner_vk,
+        tracker: None,
+        seen_dms: HashSet::new(),
+    };
+    room.refresh(api, events).await;
+    rooms.insert(contract, room);
+}
+
+/// A followed room. `tracker` is set by the first successful read, so what
+/// was in the room before that is never replayed as events.
+struct WatchedRoom {
+    owner_vk: VerifyingKey,
+    tracker: Option<RoomEventTracker>,
+    /// Purge tokens of the incoming DMs in the last state read.
+    seen_dms: HashSet<String>,
+}
+
+impl WatchedRoom {
+    async fn refresh(&mut self, api: &ApiClient, events: &RoomEvents) {
+        let room = bs58::encode(self.owner_vk.as_bytes()).into_string();
+        let mut room_state = match api.get_room(&self.owner_vk, false).await {
+            Ok(room_state) => room_state,
+            Err(e) => {
+                tracing::warn!("Failed to read room {}: {}", room, e);
+                return;
+            }
+        };
+        let secrets = api.room_display_secrets(&self.owner_vk, &mut room_state);
+        api.hide_blocked_authors(&self.owner_vk, &mut room_state);
+        let signing_key = match api.storage().get_room(&self.owner_vk) {
+            Ok(Some((signing_key, _, _))) => signing_key,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Failed to load room {}: {}", room, e);
+                return;
+            }
+        };
+        // A failed read would look like every DM vanishing, and then
+        // reappearing as new, so skip this refresh instead.
+        let inbox = match dm::sync_inbox(api, &self.owner_vk, &signing_key, &room_state).await {
+            Ok(inbox) => inbox,
+            Err(e) => {
+                tracing::warn!("Failed to read your DM inbox in {}: {}", room, e);
+                return;
+            }
+        };
+        let nicknames = dm::member_nicknames(&room_state, &secrets);
+        let incoming: Vec<_> = dm::decrypt_dms(
+            api,
+            &self.owner_vk,
+            &signing_key,
+            &room_state,
+            &inbox,
+            &nicknames,
+        )
+        .into_iter()
+        .filter(|dm| !dm.outgoing)
+        .collect();
+
+        let Some(tracker) = &mut self.tracker else {
+            self.tracker = Some(RoomEventTracker::new(self.owner_vk, &room_state, &secrets));
+            self.seen_dms = incoming.iter().map(|dm| dm.token_hex()).collect();
+            return;
+        };
+        let mut published = tracker.changes(&room_state, &secrets);
+        for dm in &incoming {
+            if !self.seen_dms.contains(&dm.token_hex()) {
+                let nickname = nicknames.get(&dm.counterparty).cloned();
+                published.push(dm::dm_event_json(&self.owner_vk, dm, nickname));
+            }
+        }
+        // Only what is still held, so a purged DM's token is dropped.
+        self.seen_dms = incoming.iter().map(|dm| dm.token_hex()).collect();
+
+        for event in published {
+            // No receivers just means nobody is listening right now.
+            let _ = events.send(Arc::new(RoomEvent {
+                room: self.owner_vk,
+                event,
+            }));
+        }
+    }
+}
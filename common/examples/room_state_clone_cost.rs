@@ -73,6 +73,7 @@ fn build_room(members: usize, messages: usize, msg_len: usize) -> ChatRoomStateV
                         format!("Member Nickname {i}").into_bytes(),
                     ),
                     deputies: Vec::new(),
+                    dm_prekey: None,
                 },
                 sk,
             )
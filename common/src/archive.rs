@@ -0,0 +1,250 @@
+//! Local archive of every message a client has seen in a room.
+//!
+//! The contract keeps only `max_recent_messages`, and anything behind the
+//! [`RetentionHorizon`](crate::room_state::message::RetentionHorizon) is gone
+//! from the network for good. Clients that want older history record each
+//! message as they see it, in its original signed form, together with the
+//! author's verifying key, so the archive can be re-verified on load without
+//! the (possibly since-pruned) member entry that vouched for it.
+//!
+//! The archive is append-only: entries are never edited or removed, and edits,
+//! deletes and reactions are archived as the action messages they arrived as.
+//! [`MessageArchive::to_messages`] replays them for display.
+
+use crate::room_state::member::MemberId;
+use crate::room_state::message::{AuthorizedMessageV1, MessageId, MessagesV1};
+use crate::room_state::ChatRoomStateV1;
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// One archived message: the message exactly as signed, plus the key that
+/// signed it.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ArchivedMessage {
+    pub author_vk: VerifyingKey,
+    pub message: AuthorizedMessageV1,
+}
+
+impl ArchivedMessage {
+    /// Check that `author_vk` is the message's author and that it signed the
+    /// message.
+    pub fn verify(&self) -> Result<(), String> {
+        if MemberId::from(&self.author_vk) != self.message.message.author {
+            return Err(format!(
+                "archived message {:?} names author {} but carries another member's key",
+                self.message.id(),
+                self.message.message.author
+            ));
+        }
+        self.message
+            .validate(&self.author_vk)
+            .map_err(|e| format!("archived message {:?}: {e}", self.message.id()))
+    }
+}
+
+/// A room's archived messages, verified and in retention order (oldest
+/// first).
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct MessageArchive {
+    entries: Vec<ArchivedMessage>,
+    ids: HashSet<MessageId>,
+}
+
+impl MessageArchive {
+    /// Build an archive from stored entries, dropping any that fail
+    /// verification. Returns the archive and the number of entries dropped.
+    pub fn from_entries(entries: impl IntoIterator<Item = ArchivedMessage>) -> (Self, usize) {
+        let mut archive = Self::default();
+        let mut rejected = 0;
+        for entry in entries {
+            if archive.insert(entry).is_err() {
+                rejected += 1;
+            }
+        }
+        (archive, rejected)
+    }
+
+    /// Add `entry` if it verifies and is not already archived. Returns whether
+    /// it was new.
+    pub fn insert(&mut self, entry: ArchivedMessage) -> Result<bool, String> {
+        entry.verify()?;
+        let id = entry.message.id();
+        if !self.ids.insert(id) {
+            return Ok(false);
+        }
+        let key = entry.message.order_key();
+        // Usually appended at the end; a message that arrived late slots in.
+        let at = self
+            .entries
+            .partition_point(|e| e.message.order_key() <= key);
+        self.entries.insert(at, entry);
+        Ok(true)
+    }
+
+    /// Archive every message in `state` not archived yet, returning the new
+    /// entries (in retention order) so the caller can persist just those.
+    /// See [`new_entries`] for which messages qualify.
+    pub fn record_state(
+        &mut self,
+        owner_vk: &VerifyingKey,
+        state: &ChatRoomStateV1,
+    ) -> Vec<ArchivedMessage> {
+        let added = new_entries(owner_vk, state, |id| self.ids.contains(id));
+        for entry in &added {
+            // Already verified by `new_entries`.
+            let _ = self.insert(entry.clone());
+        }
+        added
+    }
+
+    pub fn entries(&self) -> &[ArchivedMessage] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, id: &MessageId) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// The whole archive as a [`MessagesV1`] with public actions applied.
+    /// Private rooms rebuild the actions state from the decrypted actions
+    /// afterwards, as they do for `recent_messages`.
+    pub fn to_messages(&self) -> MessagesV1 {
+        let mut messages = MessagesV1 {
+            messages: self.entries.iter().map(|e| e.message.clone()).collect(),
+            ..Default::default()
+        };
+        messages.rebuild_actions_state();
+        messages
+    }
+}
+
+/// The messages in `state` for which `is_archived` is false, as verified
+/// archive entries in retention order. Messages whose author is no longer in
+/// `state.members`, or whose signature does not verify, are skipped: without
+/// the author's key they could never be re-verified.
+///
+/// For callers that keep the archive on disk and only need its ids to append
+/// to it, rather than a loaded [`MessageArchive`].
+pub fn new_entries(
+    owner_vk: &VerifyingKey,
+    state: &ChatRoomStateV1,
+    is_archived: impl Fn(&MessageId) -> bool,
+) -> Vec<ArchivedMessage> {
+    let owner_id = MemberId::from(owner_vk);
+    let mut added: Vec<ArchivedMessage> = state
+        .recent_messages
+        .messages
+        .iter()
+        .filter(|message| !is_archived(&message.id()))
+        .filter_map(|message| {
+            let author = message.message.author;
+            let author_vk = if author == owner_id {
+                *owner_vk
+            } else {
+                state
+                    .members
+                    .members
+                    .iter()
+                    .find(|m| m.member.id() == author)?
+                    .member
+                    .member_vk
+            };
+            let entry = ArchivedMessage {
+                author_vk,
+                message: message.clone(),
+            };
+            entry.verify().is_ok().then_some(entry)
+        })
+        .collect();
+    added.sort_by_key(|e| e.message.order_key());
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_state::member::{AuthorizedMember, Member};
+    use crate::room_state::message::{MessageV1, RoomMessageBody};
+    use ed25519_dalek::SigningKey;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn message(sk: &SigningKey, owner: &SigningKey, secs: u64, text: &str) -> AuthorizedMessageV1 {
+        AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: MemberId::from(&owner.verifying_key()),
+                author: MemberId::from(&sk.verifying_key()),
+                time: UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs),
+                content: RoomMessageBody::public(text.to_string()),
+            },
+            sk,
+        )
+    }
+
+    #[test]
+    fn record_state_archives_new_messages_once_and_keeps_pruned_ones() {
+        let owner = SigningKey::from_bytes(&[1; 32]);
+        let alice = SigningKey::from_bytes(&[2; 32]);
+        let stranger = SigningKey::from_bytes(&[3; 32]);
+        let owner_vk = owner.verifying_key();
+
+        let mut state = ChatRoomStateV1::default();
+        state.members.members.push(AuthorizedMember::new(
+            Member {
+                owner_member_id: MemberId::from(&owner_vk),
+                invited_by: MemberId::from(&owner_vk),
+                member_vk: alice.verifying_key(),
+            },
+            &owner,
+        ));
+        let first = message(&owner, &owner, 0, "decided: ship friday");
+        let second = message(&alice, &owner, 1, "agreed");
+        let unknown = message(&stranger, &owner, 2, "not a member");
+        state.recent_messages.messages = vec![first.clone(), second.clone(), unknown];
+
+        let mut archive = MessageArchive::default();
+        let added = archive.record_state(&owner_vk, &state);
+        assert_eq!(added.len(), 2, "the non-member's message is not archived");
+        assert!(archive.record_state(&owner_vk, &state).is_empty());
+
+        // The first message falls behind the retention horizon but stays archived.
+        let third = message(&alice, &owner, 3, "done");
+        state.recent_messages.messages = vec![second.clone(), third.clone()];
+        assert_eq!(archive.record_state(&owner_vk, &state).len(), 1);
+        let ids: Vec<_> = archive.entries().iter().map(|e| e.message.id()).collect();
+        assert_eq!(ids, vec![first.id(), second.id(), third.id()]);
+
+        // Reloading from the stored entries gives the same archive.
+        let (reloaded, rejected) = MessageArchive::from_entries(archive.entries().to_vec());
+        assert_eq!(rejected, 0);
+        assert_eq!(reloaded, archive);
+    }
+
+    #[test]
+    fn tampered_or_misattributed_entries_are_rejected() {
+        let owner = SigningKey::from_bytes(&[1; 32]);
+        let alice = SigningKey::from_bytes(&[2; 32]);
+        let mut genuine = ArchivedMessage {
+            author_vk: alice.verifying_key(),
+            message: message(&alice, &owner, 0, "original"),
+        };
+        assert!(genuine.verify().is_ok());
+
+        let misattributed = ArchivedMessage {
+            author_vk: owner.verifying_key(),
+            ..genuine.clone()
+        };
+        genuine.message.message.content = RoomMessageBody::public("rewritten".to_string());
+        let (archive, rejected) = MessageArchive::from_entries([genuine, misattributed]);
+        assert!(archive.is_empty());
+        assert_eq!(rejected, 2);
+    }
+}
@@ -0,0 +1,190 @@
+//! Content-addressed attachment blobs (`CONTENT_TYPE_BLOB`).
+//!
+//! An attachment is too large to ride inside a room message
+//! (`Configuration::max_message_size`), so its bytes live in a contract of
+//! their own and the message carries only a [`crate::room_state::content::BlobContentV1`]
+//! reference. The blob contract's parameters are a [`BlobParametersV1`] — the
+//! BLAKE3 hash of the stored bytes — and its only valid non-empty state is
+//! bytes with that hash, so the contract key names the content and nobody can
+//! swap an attachment out from under a message that references it.
+//!
+//! In a private room the stored bytes are AES-256-GCM ciphertext under a fresh
+//! per-attachment key (see [`encrypt_blob`]); the key itself travels in the
+//! message, sealed with the room secret. The hash is over the *stored* bytes,
+//! which is what the contract can check.
+
+#[cfg(feature = "ecies")]
+use crate::room_state::content::BlobContentV1;
+#[cfg(feature = "ecies-randomized")]
+use crate::room_state::privacy::SealedBytes;
+use serde::{Deserialize, Serialize};
+
+/// Largest blob a client will upload or accept, in stored bytes.
+pub const MAX_BLOB_SIZE: usize = 2 * 1024 * 1024;
+
+/// Length of a per-attachment symmetric key.
+pub const BLOB_KEY_LEN: usize = 32;
+
+/// Nonce prepended to an encrypted blob: `nonce (12) || ciphertext`.
+#[cfg(feature = "ecies")]
+const BLOB_NONCE_LEN: usize = 12;
+
+/// Parameters of a blob contract.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BlobParametersV1 {
+    /// BLAKE3 hash of the stored bytes.
+    pub hash: [u8; 32],
+}
+
+impl BlobParametersV1 {
+    pub fn for_bytes(bytes: &[u8]) -> Self {
+        Self {
+            hash: *blake3::hash(bytes).as_bytes(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        ciborium::ser::into_writer(self, &mut buf).expect("Serialization should not fail");
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        ciborium::de::from_reader(bytes)
+            .map_err(|e| format!("Failed to decode BlobParametersV1: {}", e))
+    }
+}
+
+/// Check that `state` is the blob named by `parameters`.
+pub fn verify_blob(parameters: &BlobParametersV1, state: &[u8]) -> Result<(), String> {
+    if state.len() > MAX_BLOB_SIZE {
+        return Err(format!(
+            "Blob is {} bytes, larger than the {} byte limit",
+            state.len(),
+            MAX_BLOB_SIZE
+        ));
+    }
+    if *blake3::hash(state).as_bytes() != parameters.hash {
+        return Err("Blob does not match its content hash".to_string());
+    }
+    Ok(())
+}
+
+/// Encrypt `plaintext` under a fresh per-attachment key, returning the bytes
+/// to store and the key to seal into the message.
+///
+/// Available only with the `ecies-randomized` feature.
+#[cfg(feature = "ecies-randomized")]
+pub fn encrypt_blob(plaintext: &[u8]) -> (Vec<u8>, [u8; BLOB_KEY_LEN]) {
+    let key = rand::random::<[u8; BLOB_KEY_LEN]>();
+    let (ciphertext, nonce) = crate::ecies::encrypt_with_symmetric_key(&key, plaintext);
+    let mut stored = Vec::with_capacity(BLOB_NONCE_LEN + ciphertext.len());
+    stored.extend_from_slice(&nonce);
+    stored.extend_from_slice(&ciphertext);
+    (stored, key)
+}
+
+/// Reverse [`encrypt_blob`].
+#[cfg(feature = "ecies")]
+pub fn decrypt_blob(stored: &[u8], key: &[u8]) -> Result<Vec<u8>, String> {
+    let key: &[u8; BLOB_KEY_LEN] = key
+        .try_into()
+        .map_err(|_| format!("Attachment key must be {} bytes", BLOB_KEY_LEN))?;
+    if stored.len() < BLOB_NONCE_LEN {
+        return Err("Encrypted blob is too short".to_string());
+    }
+    let (nonce, ciphertext) = stored.split_at(BLOB_NONCE_LEN);
+    let nonce: &[u8; BLOB_NONCE_LEN] = nonce.try_into().expect("split at nonce length");
+    crate::ecies::decrypt_with_symmetric_key(key, ciphertext, nonce)
+}
+
+/// Turn an attachment into the bytes to upload plus the key to put in its
+/// [`BlobContentV1`]. With a room secret (`Some((secret, version))`, private
+/// rooms) the bytes are encrypted under a fresh key sealed with that secret;
+/// without one they are stored as-is.
+///
+/// Available only with the `ecies-randomized` feature.
+#[cfg(feature = "ecies-randomized")]
+pub fn seal_blob(
+    plaintext: &[u8],
+    room_secret: Option<(&[u8; 32], u32)>,
+) -> (Vec<u8>, Option<SealedBytes>) {
+    match room_secret {
+        Some((secret, version)) => {
+            let (stored, key) = encrypt_blob(plaintext);
+            (
+                stored,
+                Some(crate::ecies::seal_bytes(&key, secret, version)),
+            )
+        }
+        None => (plaintext.to_vec(), None),
+    }
+}
+
+/// Check fetched `stored` bytes against `blob` and recover the attachment,
+/// unsealing its key with the room `secrets` when it has one.
+#[cfg(feature = "ecies")]
+pub fn open_blob(
+    blob: &BlobContentV1,
+    stored: &[u8],
+    secrets: &std::collections::HashMap<u32, [u8; 32]>,
+) -> Result<Vec<u8>, String> {
+    verify_blob(&BlobParametersV1 { hash: blob.hash }, stored)?;
+    match &blob.key {
+        Some(sealed) => {
+            let key = crate::ecies::unseal_bytes_with_secrets(sealed, secrets)?;
+            decrypt_blob(stored, &key)
+        }
+        None => Ok(stored.to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_verifies_only_against_its_own_hash() {
+        let params = BlobParametersV1::for_bytes(b"patch");
+        assert!(verify_blob(&params, b"patch").is_ok());
+        assert!(verify_blob(&params, b"patched").is_err());
+
+        let decoded = BlobParametersV1::from_bytes(&params.to_bytes()).unwrap();
+        assert_eq!(decoded, params);
+    }
+
+    #[cfg(feature = "ecies-randomized")]
+    #[test]
+    fn encrypted_blob_roundtrips_only_with_its_key() {
+        let (stored, key) = encrypt_blob(b"screenshot bytes");
+        assert_ne!(&stored[BLOB_NONCE_LEN..], b"screenshot bytes");
+        assert_eq!(decrypt_blob(&stored, &key).unwrap(), b"screenshot bytes");
+        assert!(decrypt_blob(&stored, &[0u8; BLOB_KEY_LEN]).is_err());
+    }
+
+    #[cfg(feature = "ecies-randomized")]
+    #[test]
+    fn sealed_blob_opens_with_the_room_secret() {
+        let secret = [4u8; 32];
+        let (stored, key) = seal_blob(b"notes.txt body", Some((&secret, 3)));
+        let blob = BlobContentV1 {
+            contract_id: [0; 32],
+            hash: BlobParametersV1::for_bytes(&stored).hash,
+            size: 14,
+            mime_type: "text/plain".to_string(),
+            filename: "notes.txt".to_string(),
+            key,
+        };
+
+        let secrets = std::collections::HashMap::from([(3, secret)]);
+        assert_eq!(
+            open_blob(&blob, &stored, &secrets).unwrap(),
+            b"notes.txt body"
+        );
+        assert!(open_blob(&blob, &stored, &Default::default()).is_err());
+
+        let mut tampered = stored.clone();
+        tampered[0] ^= 1;
+        assert!(open_blob(&blob, &tampered, &secrets).is_err());
+    }
+}
@@ -39,6 +39,11 @@ pub struct OutboundDmStore {
     /// `OutboundDmStore`) keep decoding into an empty `hidden_threads`.
     #[serde(default)]
     pub hidden_threads: Vec<HiddenDmThreadEntry>,
+    /// Members each local identity has blocked. Rides in the same blob as
+    /// `hidden_threads` so a block made on one device reaches the others;
+    /// `#[serde(default)]` keeps older blobs decoding with nobody blocked.
+    #[serde(default)]
+    pub blocked_members: Vec<BlockedMemberEntry>,
 }
 
 /// A single user-driven "hide this DM thread until further notice" entry.
@@ -68,6 +73,27 @@ pub struct HiddenDmThreadEntry {
     pub hidden_at_ts: u64,
 }
 
+/// One member blocked by one local identity in one room.
+///
+/// Purely client-side: the blocked member's room messages are hidden from
+/// `blocker`'s view, and every DM from them is purged from `blocker`'s inbox
+/// through the ordinary signed purge envelope. Nothing about the block is
+/// published. Keyed by `blocker` as well as the room because a client may
+/// hold several identities in one room over time (e.g. after
+/// `identity import --force`), and a block belongs to the identity that made
+/// it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlockedMemberEntry {
+    /// Room owner verifying key, raw bytes as in [`HiddenDmThreadEntry`].
+    pub room_owner_vk: [u8; 32],
+    /// The local identity that made the block.
+    pub blocker: MemberId,
+    /// The member being blocked.
+    pub member: MemberId,
+    /// Unix seconds when the block was made; informational only.
+    pub blocked_at: u64,
+}
+
 /// A single outbound DM the local user composed and sent.
 ///
 /// `purge_token` matches `AuthorizedDirectMessage::purge_token()` for
@@ -366,6 +392,21 @@ pub fn is_thread_hidden(
         .is_some_and(|h| max_message_ts <= h.hidden_at_ts)
 }
 
+/// Pure helper: the members `blocker` has blocked in the room owned by
+/// `room_owner_vk`, out of the full `blocked_members` list as loaded from
+/// storage.
+pub fn blocked_by(
+    blocked_members: &[BlockedMemberEntry],
+    room_owner_vk: &[u8; 32],
+    blocker: MemberId,
+) -> std::collections::HashSet<MemberId> {
+    blocked_members
+        .iter()
+        .filter(|b| &b.room_owner_vk == room_owner_vk && b.blocker == blocker)
+        .map(|b| b.member)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -401,6 +442,7 @@ mod tests {
         let store = OutboundDmStore {
             entries: vec![sample_entry()],
             hidden_threads: vec![],
+            blocked_members: vec![],
         };
         let json = serde_json::to_string(&store).expect("serialize JSON");
         let parsed: OutboundDmStore = serde_json::from_str(&json).expect("parse JSON");
@@ -414,6 +456,7 @@ mod tests {
         let store = OutboundDmStore {
             entries: vec![sample_entry(), sample_entry()],
             hidden_threads: vec![],
+            blocked_members: vec![],
         };
         let mut buf = Vec::new();
         ciborium::ser::into_writer(&store, &mut buf).expect("serialize CBOR");
@@ -442,6 +485,7 @@ mod tests {
         let store = OutboundDmStore {
             entries: vec![sample_entry()],
             hidden_threads: vec![sample_hidden()],
+            blocked_members: vec![],
         };
         let json = serde_json::to_string(&store).expect("serialize JSON");
         let parsed: OutboundDmStore = serde_json::from_str(&json).expect("parse JSON");
@@ -455,6 +499,7 @@ mod tests {
         let store = OutboundDmStore {
             entries: vec![],
             hidden_threads: vec![sample_hidden(), sample_hidden()],
+            blocked_members: vec![],
         };
         let mut buf = Vec::new();
         ciborium::ser::into_writer(&store, &mut buf).expect("serialize CBOR");
@@ -505,6 +550,36 @@ mod tests {
             ciborium::de::from_reader(buf.as_slice()).expect("legacy CBOR must decode");
         assert_eq!(parsed.entries.len(), 1);
         assert!(parsed.hidden_threads.is_empty());
+        assert!(parsed.blocked_members.is_empty());
+    }
+
+    /// A block belongs to one identity in one room: the same member
+    /// blocked by another identity, or in another room, stays visible.
+    #[test]
+    fn blocked_by_is_scoped_to_room_and_blocker() {
+        let (me, other_me) = (MemberId(FastHash(1)), MemberId(FastHash(2)));
+        let (troll, friend) = (MemberId(FastHash(3)), MemberId(FastHash(4)));
+        let block = |room: u8, blocker, member| BlockedMemberEntry {
+            room_owner_vk: [room; 32],
+            blocker,
+            member,
+            blocked_at: 1_700_000_000,
+        };
+        let store = OutboundDmStore {
+            blocked_members: vec![
+                block(9, me, troll),
+                block(9, other_me, friend),
+                block(8, me, friend),
+            ],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&store).expect("serialize JSON");
+        let parsed: OutboundDmStore = serde_json::from_str(&json).expect("parse JSON");
+        assert_eq!(parsed, store);
+
+        let blocked = blocked_by(&parsed.blocked_members, &[9; 32], me);
+        assert!(blocked.contains(&troll));
+        assert!(!blocked.contains(&friend));
     }
 
     /// `is_thread_hidden` returns false on an empty hidden list. This
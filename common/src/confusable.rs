@@ -3,7 +3,7 @@
 //!
 //! ## Why this exists
 //!
-//! [`crate::util::display_name`] stops a nickname from *forging* the 🛡 shield,
+//! [`crate::display_name`] stops a nickname from *forging* the 🛡 shield,
 //! and its module header states plainly that homoglyphs are out of scope for
 //! it. That leaves the other half of the same attack: a member who cannot draw
 //! a shield can still call themselves `lan Clarke` (lowercase L for capital i)
@@ -36,10 +36,11 @@
 //!
 //! In the autoban script the tiers have wildly different error budgets: tier 1
 //! *bans* (a false positive removes an innocent person) and tier 2 only
-//! *reports*.
+//! *reports*. `riverctl moderate` is the native replacement for that script and
+//! keeps the same split.
 //!
-//! **This UI renders tier 1 only.** Tier 2 is computed, returned and tested, but
-//! [`crate::components::members::impersonation_warning_for_display`] — the one
+//! **The UI renders tier 1 only.** Tier 2 is computed, returned and tested, but
+//! the UI's `members::impersonation_warning_for_display` — the one
 //! function every render surface goes through — drops it. The reason is
 //! measured, not assumed: River assigns every member one of 10,000 generated
 //! handles ([`crate::nickname`]), and two of them, `Amber Worm` and
@@ -127,8 +128,8 @@
 //! * This module compares names. It does not, and cannot, tell you whether the
 //!   person behind an unflagged name is who they say they are.
 
-use crate::util::display_name::is_display_hidden;
-use river_core::room_state::member::MemberId;
+use crate::display_name::is_display_hidden;
+use crate::room_state::member::MemberId;
 
 /// A `MemberId` no real member can hold, used by `check_name` so a test that
 /// does not care about identity still routes through the same per-name
@@ -146,7 +147,7 @@ const NO_SUCH_MEMBER: MemberId = MemberId(freenet_scaffold::util::FastHash(i64::
 ///
 /// **The UI renders [`Identical`](ConfusableTier::Identical) only** — see the
 /// module header, and
-/// [`crate::components::members::impersonation_warning_for_display`], which is
+/// the UI's `members::impersonation_warning_for_display`, which is
 /// where that decision lives. Both tiers are computed and returned so the
 /// distinction stays testable: a future change that silently promoted every
 /// near-miss to an identical match (or the reverse) would otherwise be
@@ -256,7 +257,7 @@ pub struct ImpersonationWarning {
     pub impersonated: ProtectedName,
     /// How close the resemblance is. **Callers must not render a warning
     /// straight from this value** — go through
-    /// [`crate::components::members::impersonation_warning_for_display`], which
+    /// the UI's `members::impersonation_warning_for_display`, which
     /// applies the tier decision (only `Identical` is shown).
     pub tier: ConfusableTier,
     /// The privilege the **FLAGGED** member themselves holds in this viewer's
@@ -264,7 +265,7 @@ pub struct ImpersonationWarning {
     ///
     /// [`ImpersonationChecker`] cannot know this — it is asked about a name and
     /// an id, not about the room's badge map — so it always leaves this `None`.
-    /// [`crate::components::members::impersonation_warning_for_display`] fills
+    /// the UI's `members::impersonation_warning_for_display` fills
     /// it in, from the same badge map the 🛡 comes from.
     ///
     /// It exists because [`tooltip`](Self::tooltip) used to switch on the
@@ -278,7 +279,7 @@ pub struct ImpersonationWarning {
 /// The glyph rendered for a warning.
 ///
 /// U+26A0 sits inside the Miscellaneous Symbols range that
-/// [`crate::util::display_name::is_display_hidden`] strips, so a nickname can
+/// [`crate::display_name::is_display_hidden`] strips, so a nickname can
 /// never contain this character — the warning cannot be forged, and an impostor
 /// cannot pre-empt it by putting one in their own name to make the real signal
 /// look like decoration. `badge_glyphs_cannot_survive_a_nickname` in that
@@ -293,7 +294,7 @@ impl ImpersonationWarning {
     /// that this member is *not* who they resemble, and what to look for
     /// instead. One definition for every surface, so the warning cannot say
     /// different things in different places — the same rule
-    /// [`crate::components::members::DeputyBadge::tooltip`] follows for the
+    /// the UI's `members::DeputyBadge::tooltip` follows for the
     /// shield.
     ///
     /// ## No nickname reaches this string, and none may be added
@@ -343,7 +344,7 @@ impl ImpersonationWarning {
     /// The badge stays; the SENTENCE changes to one that is true of both
     /// members, and points at the disambiguator that actually works (the member
     /// ID River shows on hover). Pinned by
-    /// `crate::components::members::a_shield_and_a_warning_can_both_render`.
+    /// the UI test `members::a_shield_and_a_warning_can_both_render`.
     ///
     /// ## Rewording this text can break browser tests in unrelated files
     ///
@@ -414,7 +415,7 @@ impl ImpersonationWarning {
 /// Decides which members' names are confusable with a privileged member's.
 ///
 /// Built once per render pass from the viewer's room state (see
-/// [`crate::components::members::impersonation_checker_for_viewer`]) and then
+/// the UI's `members::impersonation_checker_for_viewer`) and then
 /// asked about each rendered name. Construction folds the protected names once;
 /// [`ImpersonationChecker::check`] is the per-name hot path.
 #[derive(Clone, PartialEq, Debug, Default)]
@@ -438,7 +439,7 @@ impl ImpersonationChecker {
     /// The warning for `display_name` as shown for member `id`, if any.
     ///
     /// `display_name` must be the text actually rendered — i.e. the output of
-    /// [`crate::util::display_name::display_nickname`]. Checking the raw
+    /// the UI's `display_nickname`. Checking the raw
     /// nickname instead would compare something the reader never sees.
     ///
     /// ## The exemption is PER-NAME, not per-member
@@ -465,9 +466,7 @@ impl ImpersonationChecker {
     /// themselves to a moderator's name IS now flagged, and that is correct —
     /// the name is not theirs.
     pub fn check(&self, id: MemberId, display_name: &str) -> Option<ImpersonationWarning> {
-        let Some(folds) = self.candidate_folds(display_name) else {
-            return None;
-        };
+        let folds = self.candidate_folds(display_name)?;
         if let Some(hit) = self.tier_one_for(id, &folds) {
             return Some(hit);
         }
@@ -477,7 +476,7 @@ impl ImpersonationChecker {
     /// [`check`](Self::check) restricted to [`ConfusableTier::Identical`].
     ///
     /// **This is what the UI renders** (see
-    /// [`crate::components::members::impersonation_warning_for_display`]), and
+    /// the UI's `members::impersonation_warning_for_display`), and
     /// it exists so the render path never pays for the tier it discards: it
     /// returns before the Damerau-Levenshtein sweep, which is `O(protected x
     /// len^2)` with a `Vec` allocation per DP row and runs for every
@@ -618,7 +617,7 @@ fn edit_budget(len: usize) -> usize {
 /// (`~/bin/river-official-autoban.sh`), with the Unicode normalisation steps
 /// replaced by explicit tables — this crate deliberately carries no Unicode
 /// property or normalisation dependency (the wasm bundle size is a standing
-/// concern, and [`crate::util::display_name`] made the same trade for the same
+/// concern, and [`crate::display_name`] made the same trade for the same
 /// reason).
 ///
 /// Order is load-bearing and is pinned by `skeleton_folds_in_reference_order`.
@@ -642,7 +641,7 @@ pub fn skeleton(name: &str) -> String {
 /// It exists because the filters deciding which names ENTER the protected set
 /// used to compare exact strings while the matcher they gate compares folds — a
 /// guard weaker than the thing it guards, which is not a guard. See
-/// [`crate::components::members::impersonation_checker_for_viewer`].
+/// the UI's `members::impersonation_checker_for_viewer`.
 pub fn comparison_folds(name: &str) -> [String; 2] {
     [
         strip_spaces(&skeleton_with(name, Fold::Visual)),
@@ -1420,7 +1419,7 @@ fn fold_letterlike(cp: u32) -> Option<char> {
         0x2102 | 0x212D => 'C',
         0x2107 => 'E',
         0x210A => 'g',
-        0x210B | 0x210C | 0x210D => 'H',
+        0x210B..=0x210D => 'H',
         0x210E | 0x210F => 'h',
         0x2110 | 0x2111 => 'I',
         0x2112 => 'L',
@@ -1428,7 +1427,7 @@ fn fold_letterlike(cp: u32) -> Option<char> {
         0x2115 => 'N',
         0x2119 => 'P',
         0x211A => 'Q',
-        0x211B | 0x211C | 0x211D => 'R',
+        0x211B..=0x211D => 'R',
         0x2124 | 0x2128 => 'Z',
         0x212C => 'B',
         0x212F | 0x2130 => 'E',
@@ -2858,7 +2857,7 @@ mod tests {
             // Precondition: sanitisation KEEPS the joiner, so the two members
             // really do render alike and the existing defence does not catch it.
             assert_eq!(
-                crate::util::display_name::sanitize_display_name(clone),
+                crate::display_name::sanitize_display_name(clone),
                 clone,
                 "{script}: precondition — #488 keeps an interior joiner"
             );
@@ -3221,16 +3220,20 @@ mod tests {
         // characters is a sentence-style TEST name in this codebase, never a
         // field or an ordinary function — verified against the whole tree when
         // this was written. Anything else in backticks is left alone, so this
-        // cannot start failing on a normal identifier.
+        // cannot start failing on a normal identifier. The UI files are read
+        // from the sibling crate, where the warning is rendered.
         let sources: [(&str, &str); 5] = [
             ("confusable.rs", include_str!("confusable.rs")),
             ("display_name.rs", include_str!("display_name.rs")),
-            ("members.rs", include_str!("../components/members.rs")),
+            (
+                "members.rs",
+                include_str!("../../ui/src/components/members.rs"),
+            ),
             (
                 "conversation.rs",
-                include_str!("../components/conversation.rs"),
+                include_str!("../../ui/src/components/conversation.rs"),
             ),
-            ("nickname.rs", include_str!("../nickname.rs")),
+            ("nickname.rs", include_str!("nickname.rs")),
         ];
         let all: String = sources.iter().map(|(_, s)| *s).collect();
 
@@ -3277,19 +3280,15 @@ mod tests {
         let glyph = WARNING_GLYPH.chars().next().expect("one char");
         assert_eq!(WARNING_GLYPH.chars().count(), 1, "one codepoint, no VS16");
         assert!(is_display_hidden(glyph));
-        assert!(crate::util::display_name::contains_hidden_chars(
-            WARNING_GLYPH
-        ));
+        assert!(crate::display_name::contains_hidden_chars(WARNING_GLYPH));
         assert_eq!(
-            crate::util::display_name::sanitize_display_name(&format!("Eve {WARNING_GLYPH}")),
+            crate::display_name::sanitize_display_name(&format!("Eve {WARNING_GLYPH}")),
             "Eve"
         );
         // Also in its emoji-presentation form, which is what a phone keyboard
         // inserts and what a copy-paste from most web pages carries.
         assert_eq!(
-            crate::util::display_name::sanitize_display_name(&format!(
-                "Eve {WARNING_GLYPH}\u{FE0F}"
-            )),
+            crate::display_name::sanitize_display_name(&format!("Eve {WARNING_GLYPH}\u{FE0F}")),
             "Eve"
         );
     }
@@ -0,0 +1,807 @@
+//! Forward-secret direct messages: an X3DH-style handshake against a prekey
+//! the recipient publishes in their `MemberInfo`, then a double ratchet.
+//!
+//! A DM sealed by [`crate::ecies::seal_dm_for_recipient`] opens with the
+//! recipient's member key for as long as the ciphertext exists, so one leaked
+//! key reads the whole history. A member who opts in publishes a
+//! [`DmPrekeyV1`] and keeps its secret in a [`RatchetStore`]. A sender then
+//! opens a session from both members' identity keys, a fresh ephemeral key and
+//! that prekey, and every later message in either direction advances it: each
+//! message has its own key, derived from chains that move forward and from a
+//! Diffie-Hellman step whenever the conversation changes direction, and each
+//! key is deleted once used.
+//!
+//! The ratchet envelope still travels in
+//! [`DirectMessage::ciphertext`](crate::room_state::direct_messages::DirectMessage::ciphertext),
+//! prefixed with [`RATCHET_ENVELOPE_MAGIC`]; anything else is an ECIES
+//! envelope, and [`RatchetStore::open`] reads both. Because a message key is
+//! gone once used, the store keeps the plaintext of what it has opened.
+//! Forgetting that plaintext ([`RatchetStore::forget`]), as a purge does,
+//! leaves the message unreadable even to someone holding the member key and
+//! the current store. Prekey secrets go the same way:
+//! [`RatchetStore::rotate_prekey`] keeps only the newest two.
+//!
+//! The store is local state. The UI keeps it in the chat delegate and
+//! `riverctl` keeps it on disk; neither syncs it between devices, so a second
+//! device shows ratcheted DMs it did not open as undecryptable. Group DMs
+//! ([`crate::group_dm`]) are not ratcheted.
+
+use crate::ecies::{ed25519_to_x25519_private_key, ed25519_to_x25519_public_key};
+use crate::room_state::direct_messages::{
+    check_dm_future_skew, compose_direct_message, open_direct_message, sign_direct_message,
+    AuthorizedDirectMessage, PurgeToken, MAX_DM_CIPHERTEXT_BYTES, MAX_DM_MESSAGES_PER_PAIR,
+};
+use crate::room_state::member::MemberId;
+use crate::room_state::member_info::DmPrekeyV1;
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// First bytes of every ratchet envelope. An ECIES envelope starts with a
+/// random ephemeral key, so it matches with probability 2^-32, and
+/// [`RatchetStore::open`] falls back to ECIES if it does.
+pub const RATCHET_ENVELOPE_MAGIC: [u8; 4] = *b"RvDR";
+
+/// Most message keys a session keeps for messages it has not seen yet
+/// (delivered out of order, or evicted from the inbox before being read).
+/// Also the largest gap one message may skip.
+pub const MAX_SKIPPED_MESSAGE_KEYS: usize = 512;
+
+/// Sessions kept per peer. More than one exists only when both members
+/// opened a session at the same time; the most recently used one sends.
+const MAX_SESSIONS_PER_PEER: usize = 4;
+
+/// Prekey secrets kept: the published one and the one before it, for
+/// handshakes already in flight when it was rotated.
+const PREKEYS_KEPT: usize = 2;
+
+const X3DH_CONTEXT: &str = "river dm x3dh v1 2026-10";
+const ROOT_CHAIN_CONTEXT: &str = "river dm ratchet root v1 2026-10";
+
+/// The secret half of a [`DmPrekeyV1`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrekeySecret {
+    pub id: u32,
+    secret: [u8; 32],
+}
+
+impl PrekeySecret {
+    fn generate(id: u32) -> Self {
+        Self {
+            id,
+            secret: rand::random(),
+        }
+    }
+
+    /// What to publish in `MemberInfo::dm_prekey`.
+    pub fn public(&self) -> DmPrekeyV1 {
+        DmPrekeyV1 {
+            id: self.id,
+            public_key: public_of(&self.secret),
+        }
+    }
+}
+
+/// The handshake the first messages of a session carry, repeated until the
+/// peer answers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Handshake {
+    /// The initiator's member key.
+    identity: [u8; 32],
+    ephemeral: [u8; 32],
+    prekey_id: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Header {
+    /// The sender's current ratchet public key.
+    ratchet: [u8; 32],
+    /// Messages the sender sent under its previous ratchet key.
+    prev_chain_len: u32,
+    /// This message's number under `ratchet`.
+    n: u32,
+    handshake: Option<Handshake>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SkippedKey {
+    ratchet: [u8; 32],
+    n: u32,
+    key: [u8; 32],
+}
+
+/// One double-ratchet session with a peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RatchetSession {
+    root_key: [u8; 32],
+    /// Our current ratchet secret.
+    self_ratchet: [u8; 32],
+    peer_ratchet: Option<[u8; 32]>,
+    send_chain: Option<[u8; 32]>,
+    recv_chain: Option<[u8; 32]>,
+    send_n: u32,
+    recv_n: u32,
+    prev_send_n: u32,
+    skipped: Vec<SkippedKey>,
+    /// Set on the initiator's side until the peer's first answer.
+    pending_handshake: Option<Handshake>,
+    /// Set on the responder's side: the handshake that opened the session,
+    /// so repeats of it are routed here.
+    opened_by: Option<Handshake>,
+}
+
+impl RatchetSession {
+    fn initiate(self_sk: &SigningKey, peer_vk: &VerifyingKey, prekey: &DmPrekeyV1) -> Self {
+        let identity = ed25519_to_x25519_private_key(self_sk).to_bytes();
+        let peer_identity = ed25519_to_x25519_public_key(peer_vk).to_bytes();
+        let ephemeral: [u8; 32] = rand::random();
+        let shared = x3dh(
+            &dh(&identity, &prekey.public_key),
+            &dh(&ephemeral, &peer_identity),
+            &dh(&ephemeral, &prekey.public_key),
+        );
+        let self_ratchet: [u8; 32] = rand::random();
+        let (root_key, send_chain) = kdf_root(&shared, &dh(&self_ratchet, &prekey.public_key));
+        Self {
+            root_key,
+            self_ratchet,
+            peer_ratchet: Some(prekey.public_key),
+            send_chain: Some(send_chain),
+            recv_chain: None,
+            send_n: 0,
+            recv_n: 0,
+            prev_send_n: 0,
+            skipped: Vec::new(),
+            pending_handshake: Some(Handshake {
+                identity: self_sk.verifying_key().to_bytes(),
+                ephemeral: public_of(&ephemeral),
+                prekey_id: prekey.id,
+            }),
+            opened_by: None,
+        }
+    }
+
+    fn respond(
+        self_sk: &SigningKey,
+        peer_vk: &VerifyingKey,
+        prekey: &PrekeySecret,
+        handshake: &Handshake,
+    ) -> Self {
+        let identity = ed25519_to_x25519_private_key(self_sk).to_bytes();
+        let peer_identity = ed25519_to_x25519_public_key(peer_vk).to_bytes();
+        let shared = x3dh(
+            &dh(&prekey.secret, &peer_identity),
+            &dh(&identity, &handshake.ephemeral),
+            &dh(&prekey.secret, &handshake.ephemeral),
+        );
+        Self {
+            root_key: shared,
+            self_ratchet: prekey.secret,
+            peer_ratchet: None,
+            send_chain: None,
+            recv_chain: None,
+            send_n: 0,
+            recv_n: 0,
+            prev_send_n: 0,
+            skipped: Vec::new(),
+            pending_handshake: None,
+            opened_by: Some(handshake.clone()),
+        }
+    }
+
+    fn encrypt(&mut self, associated_data: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let chain = self
+            .send_chain
+            .ok_or("DM session cannot send before it has received")?;
+        let (next_chain, message_key) = kdf_chain(&chain);
+        let header = Header {
+            ratchet: public_of(&self.self_ratchet),
+            prev_chain_len: self.prev_send_n,
+            n: self.send_n,
+            handshake: self.pending_handshake.clone(),
+        };
+        let mut envelope = RATCHET_ENVELOPE_MAGIC.to_vec();
+        let mut header_bytes = Vec::new();
+        ciborium::ser::into_writer(&header, &mut header_bytes)
+            .map_err(|e| format!("Failed to encode DM ratchet header: {}", e))?;
+        let header_len: u16 = header_bytes
+            .len()
+            .try_into()
+            .map_err(|_| "DM ratchet header too long".to_string())?;
+        envelope.extend_from_slice(&header_len.to_le_bytes());
+        envelope.extend_from_slice(&header_bytes);
+        let ciphertext = message_cipher(&message_key)
+            .encrypt(
+                &message_nonce(&message_key),
+                Payload {
+                    msg: plaintext,
+                    aad: &[associated_data, &envelope].concat(),
+                },
+            )
+            .map_err(|e| format!("DM encryption failed: {}", e))?;
+        envelope.extend_from_slice(&ciphertext);
+        self.send_chain = Some(next_chain);
+        self.send_n += 1;
+        Ok(envelope)
+    }
+
+    /// Decrypt one message, changing the session only if it authenticates.
+    fn decrypt(
+        &mut self,
+        envelope: &ParsedEnvelope<'_>,
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let mut next = self.clone();
+        let plaintext = next.decrypt_in_place(envelope, associated_data)?;
+        *self = next;
+        Ok(plaintext)
+    }
+
+    fn decrypt_in_place(
+        &mut self,
+        envelope: &ParsedEnvelope<'_>,
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let header = &envelope.header;
+        let skipped = self
+            .skipped
+            .iter()
+            .position(|k| k.ratchet == header.ratchet && k.n == header.n);
+        let message_key = match skipped {
+            Some(i) => self.skipped.remove(i).key,
+            None => {
+                if self.peer_ratchet != Some(header.ratchet) {
+                    self.skip_to(header.prev_chain_len)?;
+                    self.dh_ratchet(header.ratchet);
+                }
+                self.skip_to(header.n)?;
+                let chain = self.recv_chain.ok_or("DM session has no receiving chain")?;
+                let (next_chain, message_key) = kdf_chain(&chain);
+                self.recv_chain = Some(next_chain);
+                self.recv_n += 1;
+                message_key
+            }
+        };
+        let plaintext = message_cipher(&message_key)
+            .decrypt(
+                &message_nonce(&message_key),
+                Payload {
+                    msg: envelope.ciphertext,
+                    aad: &[associated_data, envelope.authenticated].concat(),
+                },
+            )
+            .map_err(|_| "DM decryption failed (wrong session or tampered bytes)".to_string())?;
+        // The peer has answered, so it holds the session.
+        self.pending_handshake = None;
+        Ok(plaintext)
+    }
+
+    /// Step the receiving chain up to message `until`, keeping the keys of
+    /// the messages passed over.
+    fn skip_to(&mut self, until: u32) -> Result<(), String> {
+        let (Some(mut chain), Some(ratchet)) = (self.recv_chain, self.peer_ratchet) else {
+            return Ok(());
+        };
+        if until <= self.recv_n {
+            return Ok(());
+        }
+        if (until - self.recv_n) as usize > MAX_SKIPPED_MESSAGE_KEYS {
+            return Err(format!(
+                "DM skips {} messages; at most {} are kept",
+                until - self.recv_n,
+                MAX_SKIPPED_MESSAGE_KEYS
+            ));
+        }
+        while self.recv_n < until {
+            let (next_chain, key) = kdf_chain(&chain);
+            self.skipped.push(SkippedKey {
+                ratchet,
+                n: self.recv_n,
+                key,
+            });
+            chain = next_chain;
+            self.recv_n += 1;
+        }
+        self.recv_chain = Some(chain);
+        let excess = self.skipped.len().saturating_sub(MAX_SKIPPED_MESSAGE_KEYS);
+        self.skipped.drain(..excess);
+        Ok(())
+    }
+
+    fn dh_ratchet(&mut self, peer_ratchet: [u8; 32]) {
+        self.prev_send_n = self.send_n;
+        self.send_n = 0;
+        self.recv_n = 0;
+        self.peer_ratchet = Some(peer_ratchet);
+        let (root_key, recv_chain) =
+            kdf_root(&self.root_key, &dh(&self.self_ratchet, &peer_ratchet));
+        self.self_ratchet = rand::random();
+        let (root_key, send_chain) = kdf_root(&root_key, &dh(&self.self_ratchet, &peer_ratchet));
+        self.root_key = root_key;
+        self.recv_chain = Some(recv_chain);
+        self.send_chain = Some(send_chain);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PeerSession {
+    peer: MemberId,
+    session: RatchetSession,
+}
+
+/// The plaintext of a ratcheted DM this store has opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenedDm {
+    pub sender: MemberId,
+    pub purge_token: PurgeToken,
+    pub timestamp: u64,
+    pub plaintext: Vec<u8>,
+}
+
+/// One member's ratchet state in one room: prekey secrets, sessions, and the
+/// plaintext of the ratcheted DMs it has opened. `Vec`s rather than maps so
+/// it serializes to JSON as well as CBOR, like
+/// [`crate::chat_delegate::OutboundDmStore`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatchetStore {
+    #[serde(default)]
+    prekeys: Vec<PrekeySecret>,
+    /// Most recently used first.
+    #[serde(default)]
+    sessions: Vec<PeerSession>,
+    #[serde(default)]
+    opened: Vec<OpenedDm>,
+}
+
+impl RatchetStore {
+    /// The prekey to publish, if the member has opted in.
+    pub fn current_prekey(&self) -> Option<DmPrekeyV1> {
+        self.prekeys.iter().max_by_key(|p| p.id).map(|p| p.public())
+    }
+
+    /// Make a new prekey to publish, deleting all but the newest
+    /// [`PREKEYS_KEPT`] secrets.
+    pub fn rotate_prekey(&mut self) -> DmPrekeyV1 {
+        let id = self.prekeys.iter().map(|p| p.id + 1).max().unwrap_or(1);
+        self.prekeys.push(PrekeySecret::generate(id));
+        self.prekeys.sort_by_key(|p| std::cmp::Reverse(p.id));
+        self.prekeys.truncate(PREKEYS_KEPT);
+        self.prekeys[0].public()
+    }
+
+    /// Delete every prekey secret, when the member stops publishing one.
+    /// Open sessions carry on.
+    pub fn clear_prekeys(&mut self) {
+        self.prekeys.clear();
+    }
+
+    /// The plaintext of a ratcheted DM this store has already opened.
+    pub fn opened(&self, token: &PurgeToken) -> Option<&[u8]> {
+        self.opened
+            .iter()
+            .find(|o| o.purge_token == *token)
+            .map(|o| o.plaintext.as_slice())
+    }
+
+    /// Every ratcheted DM this store has opened and not forgotten.
+    pub fn opened_dms(&self) -> &[OpenedDm] {
+        &self.opened
+    }
+
+    /// Delete the opened plaintext of these DMs, leaving them unreadable.
+    pub fn forget(&mut self, tokens: &[PurgeToken]) {
+        self.opened.retain(|o| !tokens.contains(&o.purge_token));
+    }
+
+    /// [`compose_direct_message`] through a ratchet session with the
+    /// recipient: the current one, or a new one opened against
+    /// `recipient_prekey`. With neither, the recipient has not opted in and
+    /// the DM is sealed to their member key as before. A session outlives
+    /// the prekey it was opened against, so a member who stops publishing
+    /// one only stops new sessions.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compose_direct_message(
+        &mut self,
+        sender_sk: &SigningKey,
+        recipient_vk: &VerifyingKey,
+        recipient_prekey: Option<&DmPrekeyV1>,
+        room_owner_vk: &VerifyingKey,
+        timestamp: u64,
+        now_secs: u64,
+        body: &[u8],
+    ) -> Result<AuthorizedDirectMessage, String> {
+        let sender = MemberId::from(&sender_sk.verifying_key());
+        let recipient = MemberId::from(recipient_vk);
+        let current = self.sessions.iter().position(|s| s.peer == recipient);
+        let mut session = match (current, recipient_prekey) {
+            (Some(i), _) => self.sessions[i].session.clone(),
+            (None, Some(prekey)) => RatchetSession::initiate(sender_sk, recipient_vk, prekey),
+            (None, None) => {
+                return compose_direct_message(
+                    sender_sk,
+                    recipient_vk,
+                    room_owner_vk,
+                    timestamp,
+                    now_secs,
+                    body,
+                )
+            }
+        };
+        check_dm_future_skew(timestamp, now_secs)?;
+        if sender == recipient {
+            return Err("DM sender and recipient must differ".to_string());
+        }
+        let envelope = session.encrypt(&associated_data(room_owner_vk, sender, recipient), body)?;
+        if envelope.len() > MAX_DM_CIPHERTEXT_BYTES {
+            return Err(format!(
+                "DM body too large: envelope {} bytes exceeds cap {} (body {} bytes)",
+                envelope.len(),
+                MAX_DM_CIPHERTEXT_BYTES,
+                body.len()
+            ));
+        }
+        let message = sign_direct_message(
+            sender_sk,
+            sender,
+            recipient,
+            room_owner_vk,
+            timestamp,
+            envelope,
+        )?;
+        if let Some(i) = current {
+            self.sessions.remove(i);
+        }
+        self.promote(PeerSession {
+            peer: recipient,
+            session,
+        });
+        Ok(message)
+    }
+
+    /// Open an inbound DM: from the opened plaintext if this store has read
+    /// it before, through its session if ratcheted, else as an ECIES
+    /// envelope. A ratcheted DM can be opened once; its plaintext is kept
+    /// until [`Self::forget`].
+    pub fn open(
+        &mut self,
+        recipient_sk: &SigningKey,
+        room_owner_vk: &VerifyingKey,
+        msg: &AuthorizedDirectMessage,
+    ) -> Result<Vec<u8>, String> {
+        let token = msg.purge_token();
+        if let Some(plaintext) = self.opened(&token) {
+            return Ok(plaintext.to_vec());
+        }
+        let Some(envelope) = parse_envelope(&msg.message.ciphertext) else {
+            return open_direct_message(recipient_sk, msg);
+        };
+        match self.open_ratcheted(recipient_sk, room_owner_vk, msg, &envelope) {
+            Ok(plaintext) => {
+                self.record_opened(OpenedDm {
+                    sender: msg.message.sender,
+                    purge_token: token,
+                    timestamp: msg.message.timestamp,
+                    plaintext: plaintext.clone(),
+                });
+                Ok(plaintext)
+            }
+            // An ECIES envelope that happens to start with the magic.
+            Err(e) => open_direct_message(recipient_sk, msg).map_err(|_| e),
+        }
+    }
+
+    fn open_ratcheted(
+        &mut self,
+        recipient_sk: &SigningKey,
+        room_owner_vk: &VerifyingKey,
+        msg: &AuthorizedDirectMessage,
+        envelope: &ParsedEnvelope<'_>,
+    ) -> Result<Vec<u8>, String> {
+        let sender = msg.message.sender;
+        let recipient = MemberId::from(&recipient_sk.verifying_key());
+        if msg.message.recipient != recipient {
+            return Err("DM is addressed to another member".to_string());
+        }
+        let ad = associated_data(room_owner_vk, sender, recipient);
+
+        if let Some(handshake) = &envelope.header.handshake {
+            let sender_vk = VerifyingKey::from_bytes(&handshake.identity)
+                .map_err(|e| format!("DM handshake has an invalid identity key: {}", e))?;
+            if MemberId::from(&sender_vk) != sender {
+                return Err("DM handshake identity is not the sender's".to_string());
+            }
+            let answered = self
+                .sessions
+                .iter()
+                .position(|s| s.peer == sender && s.session.opened_by.as_ref() == Some(handshake));
+            if let Some(i) = answered {
+                let mut entry = self.sessions.remove(i);
+                let result = entry.session.decrypt(envelope, &ad);
+                self.promote(entry);
+                return result;
+            }
+            let prekey = self
+                .prekeys
+                .iter()
+                .find(|p| p.id == handshake.prekey_id)
+                .ok_or_else(|| {
+                    format!(
+                        "DM session was opened against prekey {}, which is no longer held",
+                        handshake.prekey_id
+                    )
+                })?;
+            let mut session = RatchetSession::respond(recipient_sk, &sender_vk, prekey, handshake);
+            let plaintext = session.decrypt(envelope, &ad)?;
+            self.promote(PeerSession {
+                peer: sender,
+                session,
+            });
+            return Ok(plaintext);
+        }
+
+        let candidates: Vec<usize> = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.peer == sender)
+            .map(|(i, _)| i)
+            .collect();
+        for i in candidates {
+            if let Ok(plaintext) = self.sessions[i].session.decrypt(envelope, &ad) {
+                let entry = self.sessions.remove(i);
+                self.promote(entry);
+                return Ok(plaintext);
+            }
+        }
+        Err("No DM session with the sender opens this message".to_string())
+    }
+
+    /// Put a session first, dropping the peer's least recently used ones
+    /// beyond [`MAX_SESSIONS_PER_PEER`].
+    fn promote(&mut self, entry: PeerSession) {
+        let peer = entry.peer;
+        self.sessions.insert(0, entry);
+        let mut kept = 0;
+        self.sessions.retain(|s| {
+            if s.peer != peer {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_SESSIONS_PER_PEER
+        });
+    }
+
+    /// Keep the newest [`MAX_DM_MESSAGES_PER_PAIR`] opened DMs per sender,
+    /// as many as an inbox holds from them.
+    fn record_opened(&mut self, opened: OpenedDm) {
+        let sender = opened.sender;
+        self.opened.push(opened);
+        self.opened
+            .sort_by_key(|o| std::cmp::Reverse((o.timestamp, o.purge_token)));
+        let mut kept = 0;
+        self.opened.retain(|o| {
+            if o.sender != sender {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_DM_MESSAGES_PER_PAIR
+        });
+    }
+}
+
+/// Whether a DM's ciphertext is a ratchet envelope rather than ECIES.
+pub fn is_ratchet_envelope(ciphertext: &[u8]) -> bool {
+    parse_envelope(ciphertext).is_some()
+}
+
+struct ParsedEnvelope<'a> {
+    header: Header,
+    /// Magic, header length and header: authenticated with the body.
+    authenticated: &'a [u8],
+    ciphertext: &'a [u8],
+}
+
+fn parse_envelope(bytes: &[u8]) -> Option<ParsedEnvelope<'_>> {
+    let rest = bytes.strip_prefix(&RATCHET_ENVELOPE_MAGIC)?;
+    let len = u16::from_le_bytes(rest.get(..2)?.try_into().ok()?) as usize;
+    let header_end = RATCHET_ENVELOPE_MAGIC.len() + 2 + len;
+    let header_bytes = bytes.get(RATCHET_ENVELOPE_MAGIC.len() + 2..header_end)?;
+    let header = ciborium::de::from_reader(header_bytes).ok()?;
+    Some(ParsedEnvelope {
+        header,
+        authenticated: &bytes[..header_end],
+        ciphertext: &bytes[header_end..],
+    })
+}
+
+/// Binds each message to its room and both members.
+fn associated_data(room_owner_vk: &VerifyingKey, sender: MemberId, recipient: MemberId) -> Vec<u8> {
+    let mut ad = Vec::with_capacity(32 + 8 + 8);
+    ad.extend_from_slice(room_owner_vk.as_bytes());
+    ad.extend_from_slice(&sender.0 .0.to_le_bytes());
+    ad.extend_from_slice(&recipient.0 .0.to_le_bytes());
+    ad
+}
+
+fn dh(secret: &[u8; 32], public: &[u8; 32]) -> [u8; 32] {
+    StaticSecret::from(*secret)
+        .diffie_hellman(&PublicKey::from(*public))
+        .to_bytes()
+}
+
+fn public_of(secret: &[u8; 32]) -> [u8; 32] {
+    PublicKey::from(&StaticSecret::from(*secret)).to_bytes()
+}
+
+fn x3dh(dh1: &[u8; 32], dh2: &[u8; 32], dh3: &[u8; 32]) -> [u8; 32] {
+    blake3::derive_key(X3DH_CONTEXT, &[&dh1[..], dh2, dh3].concat())
+}
+
+/// Root-chain step: a new root key and a new sending or receiving chain key.
+fn kdf_root(root_key: &[u8; 32], dh_out: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut out = [0u8; 64];
+    blake3::Hasher::new_derive_key(ROOT_CHAIN_CONTEXT)
+        .update(root_key)
+        .update(dh_out)
+        .finalize_xof()
+        .fill(&mut out);
+    let (root, chain) = out.split_at(32);
+    (root.try_into().unwrap(), chain.try_into().unwrap())
+}
+
+/// Symmetric-chain step: the next chain key and this message's key.
+fn kdf_chain(chain_key: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    (
+        *blake3::keyed_hash(chain_key, &[1]).as_bytes(),
+        *blake3::keyed_hash(chain_key, &[2]).as_bytes(),
+    )
+}
+
+fn message_cipher(message_key: &[u8; 32]) -> Aes256Gcm {
+    Aes256Gcm::new_from_slice(message_key).expect("32-byte AES-256 key")
+}
+
+/// Each message key is used once, so its nonce can be derived from it.
+fn message_nonce(message_key: &[u8; 32]) -> Nonce<aes_gcm::aead::consts::U12> {
+    let hash = blake3::keyed_hash(message_key, b"nonce");
+    let mut nonce = [0u8; 12];
+    nonce.copy_from_slice(&hash.as_bytes()[..12]);
+    Nonce::from(nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Member {
+        sk: SigningKey,
+        store: RatchetStore,
+    }
+
+    impl Member {
+        fn new(seed: u8) -> Self {
+            Self {
+                sk: SigningKey::from_bytes(&[seed; 32]),
+                store: RatchetStore::default(),
+            }
+        }
+
+        fn send(&mut self, to: &mut Member, ts: u64, body: &str) -> AuthorizedDirectMessage {
+            let prekey = to.store.current_prekey();
+            self.store
+                .compose_direct_message(
+                    &self.sk,
+                    &to.sk.verifying_key(),
+                    prekey.as_ref(),
+                    &owner(),
+                    ts,
+                    ts,
+                    body.as_bytes(),
+                )
+                .unwrap()
+        }
+
+        fn read(&mut self, msg: &AuthorizedDirectMessage) -> Result<String, String> {
+            self.store
+                .open(&self.sk, &owner(), msg)
+                .map(|b| String::from_utf8(b).unwrap())
+        }
+    }
+
+    fn owner() -> VerifyingKey {
+        SigningKey::from_bytes(&[1; 32]).verifying_key()
+    }
+
+    #[test]
+    fn sessions_survive_replies_reordering_and_rereads() {
+        let mut alice = Member::new(2);
+        let mut bob = Member::new(3);
+        bob.store.rotate_prekey();
+
+        let first = alice.send(&mut bob, 10, "one");
+        let second = alice.send(&mut bob, 11, "two");
+        let third = alice.send(&mut bob, 12, "three");
+        assert!(is_ratchet_envelope(&first.message.ciphertext));
+        assert_eq!(bob.read(&third).unwrap(), "three");
+        assert_eq!(bob.read(&first).unwrap(), "one");
+        assert_eq!(bob.read(&second).unwrap(), "two");
+        // Opened plaintext is kept; the message key is not.
+        assert_eq!(bob.read(&first).unwrap(), "one");
+
+        let reply = bob.send(&mut alice, 13, "hi back");
+        assert_eq!(alice.read(&reply).unwrap(), "hi back");
+        let after = alice.send(&mut bob, 14, "four");
+        assert!(parse_envelope(&after.message.ciphertext)
+            .unwrap()
+            .header
+            .handshake
+            .is_none());
+        assert_eq!(bob.read(&after).unwrap(), "four");
+
+        // Dropping the prekey stops new sessions only.
+        bob.store.clear_prekeys();
+        let still = alice.send(&mut bob, 15, "five");
+        assert!(is_ratchet_envelope(&still.message.ciphertext));
+        assert_eq!(bob.read(&still).unwrap(), "five");
+        let mut carol = Member::new(4);
+        let plain = carol.send(&mut bob, 16, "sealed");
+        assert!(!is_ratchet_envelope(&plain.message.ciphertext));
+        assert_eq!(bob.read(&plain).unwrap(), "sealed");
+    }
+
+    #[test]
+    fn forgotten_messages_cannot_be_opened_again() {
+        let mut alice = Member::new(2);
+        let mut bob = Member::new(3);
+        bob.store.rotate_prekey();
+        let msg = alice.send(&mut bob, 10, "gone soon");
+        assert_eq!(bob.read(&msg).unwrap(), "gone soon");
+
+        bob.store.forget(&[msg.purge_token()]);
+        assert!(bob.read(&msg).is_err());
+        assert!(open_direct_message(&bob.sk, &msg).is_err());
+    }
+
+    #[test]
+    fn tampered_messages_leave_the_session_unchanged() {
+        let mut alice = Member::new(2);
+        let mut bob = Member::new(3);
+        bob.store.rotate_prekey();
+        let msg = alice.send(&mut bob, 10, "intact");
+
+        let mut tampered = msg.clone();
+        let last = tampered.message.ciphertext.len() - 1;
+        tampered.message.ciphertext[last] ^= 1;
+        let before = bob.store.clone();
+        assert!(bob.read(&tampered).is_err());
+        assert_eq!(bob.store, before);
+        assert_eq!(bob.read(&msg).unwrap(), "intact");
+
+        // Someone else's copy of the same bytes is not addressed to them.
+        let mut carol = Member::new(4);
+        carol.store.rotate_prekey();
+        assert!(carol.read(&msg).is_err());
+    }
+
+    #[test]
+    fn handshakes_against_rotated_out_prekeys_fail() {
+        let mut alice = Member::new(2);
+        let mut bob = Member::new(3);
+        bob.store.rotate_prekey();
+        let early = alice.send(&mut bob, 10, "early");
+
+        bob.store.rotate_prekey();
+        assert_eq!(bob.store.current_prekey().unwrap().id, 2);
+        assert_eq!(bob.read(&early).unwrap(), "early");
+
+        let mut carol = Member::new(4);
+        let stale = carol.send(&mut bob, 11, "stale");
+        bob.store.rotate_prekey();
+        bob.store.rotate_prekey();
+        assert!(bob.read(&stale).unwrap_err().contains("no longer held"));
+    }
+}
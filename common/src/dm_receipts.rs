@@ -0,0 +1,281 @@
+//! Delivery and read receipts for one-to-one direct messages.
+//!
+//! A sender cannot see the recipient's inbox contents change, and the inbox
+//! caps can evict a DM before it is ever read. A receipt lets the recipient
+//! (the *reader*) say how far they have got in one conversation: the newest
+//! DM from the peer their client has fetched ([`DmReceipts::delivered_up_to`])
+//! and the newest one they have actually looked at
+//! ([`DmReceipts::read_up_to`]), both as DM timestamps.
+//!
+//! The envelope is signed by the reader and delivered into the inbox of the
+//! peer it reports on, alongside the reader's invite chain (see
+//! [`crate::inbox::InboxReceiptsV1`]), so a sender learns about their DMs from
+//! the inbox they already read. Like
+//! [`AuthorizedRecipientPurges`](crate::room_state::direct_messages::AuthorizedRecipientPurges)
+//! it carries a monotonic version: an inbox holds one envelope per reader, a
+//! newer version replaces it, and one that moves either mark backwards is
+//! refused. Group DMs have no receipts.
+
+use crate::room_state::member::MemberId;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+/// Domain-separation tag for [`build_dm_receipts_signed_bytes`], distinct
+/// from the DM (`b'M'`), purge (`b'P'`) and group DM (`b'G'`) tags.
+pub const DOMAIN_TAG_RECEIPTS: u8 = b'R';
+
+/// A reader-signed receipt envelope for one conversation.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuthorizedDmReceipts {
+    /// Who received and read the DMs. MUST equal the `MemberId` derived from
+    /// the signing key.
+    pub reader: MemberId,
+    /// Whose DMs these are; the owner of the inbox the envelope goes to.
+    pub sender: MemberId,
+    pub state: DmReceipts,
+    /// Reader's Ed25519 signature over the bytes produced by
+    /// [`build_dm_receipts_signed_bytes`].
+    pub reader_signature: Signature,
+}
+
+/// How far the reader has got with the sender's DMs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DmReceipts {
+    /// Monotonically increasing per reader and sender, starting at 1. A newer
+    /// version must not move either mark backwards.
+    #[serde(default)]
+    pub version: u64,
+    /// Timestamp of the newest DM from the sender the reader's client has
+    /// fetched; 0 for none.
+    #[serde(default)]
+    pub delivered_up_to: u64,
+    /// Timestamp of the newest DM from the sender the reader has seen; never
+    /// above `delivered_up_to`.
+    #[serde(default)]
+    pub read_up_to: u64,
+}
+
+/// What a receipt says about one DM, for display next to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DmReceiptStatus {
+    Delivered,
+    Read,
+}
+
+impl DmReceiptStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            DmReceiptStatus::Delivered => "delivered",
+            DmReceiptStatus::Read => "read",
+        }
+    }
+}
+
+/// Build the bytes the reader signs for an [`AuthorizedDmReceipts`].
+///
+/// ```text
+///     domain_tag                  ( 1 byte, = DOMAIN_TAG_RECEIPTS)
+///     reader_member_id_le_i64     ( 8 bytes)
+///     sender_member_id_le_i64     ( 8 bytes)
+///     room_owner_vk               (32 bytes)
+///     version_le_u64              ( 8 bytes)
+///     delivered_up_to_le_u64      ( 8 bytes)
+///     read_up_to_le_u64           ( 8 bytes)
+/// ```
+pub fn build_dm_receipts_signed_bytes(
+    reader: MemberId,
+    sender: MemberId,
+    room_owner_vk: &VerifyingKey,
+    state: &DmReceipts,
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 8 + 8 + 32 + 8 + 8 + 8);
+    out.push(DOMAIN_TAG_RECEIPTS);
+    out.extend_from_slice(&reader.0 .0.to_le_bytes());
+    out.extend_from_slice(&sender.0 .0.to_le_bytes());
+    out.extend_from_slice(room_owner_vk.as_bytes());
+    out.extend_from_slice(&state.version.to_le_bytes());
+    out.extend_from_slice(&state.delivered_up_to.to_le_bytes());
+    out.extend_from_slice(&state.read_up_to.to_le_bytes());
+    out
+}
+
+/// Sign a receipt envelope about `sender`'s DMs as `reader_sk`.
+pub fn sign_dm_receipts(
+    reader_sk: &SigningKey,
+    sender: MemberId,
+    room_owner_vk: &VerifyingKey,
+    state: DmReceipts,
+) -> AuthorizedDmReceipts {
+    let reader = MemberId::from(&reader_sk.verifying_key());
+    let bytes = build_dm_receipts_signed_bytes(reader, sender, room_owner_vk, &state);
+    AuthorizedDmReceipts {
+        reader,
+        sender,
+        state,
+        reader_signature: reader_sk.sign(&bytes),
+    }
+}
+
+/// The next receipt envelope about `sender`'s DMs, given the one `previous`ly
+/// held for this reader: version bumped, each mark the greater of the old and
+/// new. `None` when neither mark would move, so there is nothing to send.
+pub fn advance_dm_receipts(
+    reader_sk: &SigningKey,
+    sender: MemberId,
+    room_owner_vk: &VerifyingKey,
+    previous: Option<&DmReceipts>,
+    delivered_up_to: u64,
+    read_up_to: u64,
+) -> Result<Option<AuthorizedDmReceipts>, String> {
+    let previous = previous.cloned().unwrap_or_default();
+    let read_up_to = previous.read_up_to.max(read_up_to);
+    // Reading a DM implies having it.
+    let delivered_up_to = previous
+        .delivered_up_to
+        .max(delivered_up_to)
+        .max(read_up_to);
+    if delivered_up_to == previous.delivered_up_to && read_up_to == previous.read_up_to {
+        return Ok(None);
+    }
+    let version = previous
+        .version
+        .checked_add(1)
+        .ok_or_else(|| "DM receipts version overflow".to_string())?;
+    Ok(Some(sign_dm_receipts(
+        reader_sk,
+        sender,
+        room_owner_vk,
+        DmReceipts {
+            version,
+            delivered_up_to,
+            read_up_to,
+        },
+    )))
+}
+
+impl AuthorizedDmReceipts {
+    /// Check the marks are consistent and the reader signed them.
+    pub fn verify(
+        &self,
+        reader_vk: &VerifyingKey,
+        room_owner_vk: &VerifyingKey,
+    ) -> Result<(), String> {
+        if self.reader == self.sender {
+            return Err("DM receipts reader and sender must differ".to_string());
+        }
+        if self.state.version == 0 {
+            return Err("DM receipts version 0 is reserved".to_string());
+        }
+        if self.state.read_up_to > self.state.delivered_up_to {
+            return Err("DM receipts mark a DM read before delivered".to_string());
+        }
+        let bytes =
+            build_dm_receipts_signed_bytes(self.reader, self.sender, room_owner_vk, &self.state);
+        reader_vk
+            .verify(&bytes, &self.reader_signature)
+            .map_err(|e| format!("Invalid DM receipts signature: {}", e))
+    }
+
+    /// Whether this envelope may replace `current`, from the same reader:
+    /// a newer version that keeps both marks, or the same version with the
+    /// greater signature so every holder picks the same one. `Err` when it
+    /// is newer but moves a mark backwards.
+    pub fn supersedes(&self, current: &AuthorizedDmReceipts) -> Result<bool, String> {
+        if self.state.version > current.state.version {
+            if self.state.delivered_up_to < current.state.delivered_up_to
+                || self.state.read_up_to < current.state.read_up_to
+            {
+                return Err("DM receipts move a mark backwards".to_string());
+            }
+            return Ok(true);
+        }
+        Ok(self.state.version == current.state.version
+            && self.reader_signature.to_bytes() > current.reader_signature.to_bytes())
+    }
+
+    /// What this envelope says about the sender's DM sent at `timestamp`.
+    pub fn status_of(&self, timestamp: u64) -> Option<DmReceiptStatus> {
+        if timestamp <= self.state.read_up_to {
+            Some(DmReceiptStatus::Read)
+        } else if timestamp <= self.state.delivered_up_to {
+            Some(DmReceiptStatus::Delivered)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sk(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn advance_only_moves_forward_and_signs() {
+        let owner = sk(1).verifying_key();
+        let (alice, bob) = (sk(2), sk(3));
+        let alice_id = MemberId::from(&alice.verifying_key());
+
+        let first = advance_dm_receipts(&bob, alice_id, &owner, None, 100, 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.state.version, 1);
+        first.verify(&bob.verifying_key(), &owner).unwrap();
+        assert_eq!(first.status_of(100), Some(DmReceiptStatus::Delivered));
+        assert_eq!(first.status_of(101), None);
+
+        // Nothing new: nothing to send.
+        assert!(
+            advance_dm_receipts(&bob, alice_id, &owner, Some(&first.state), 90, 0)
+                .unwrap()
+                .is_none()
+        );
+
+        // Reading past the delivered mark drags it along.
+        let second = advance_dm_receipts(&bob, alice_id, &owner, Some(&first.state), 0, 120)
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.state.version, 2);
+        assert_eq!(second.state.delivered_up_to, 120);
+        assert_eq!(second.status_of(110), Some(DmReceiptStatus::Read));
+        assert_eq!(second.supersedes(&first), Ok(true));
+        assert_eq!(first.supersedes(&second), Ok(false));
+
+        // The signature covers the marks and names the reader.
+        let mut forged = second.clone();
+        forged.state.read_up_to = 90;
+        assert!(forged.verify(&bob.verifying_key(), &owner).is_err());
+        assert!(second.verify(&alice.verifying_key(), &owner).is_err());
+    }
+
+    #[test]
+    fn a_newer_envelope_cannot_take_a_mark_back() {
+        let owner = sk(1).verifying_key();
+        let alice_id = MemberId::from(&sk(2).verifying_key());
+        let bob = sk(3);
+        let read = sign_dm_receipts(
+            &bob,
+            alice_id,
+            &owner,
+            DmReceipts {
+                version: 3,
+                delivered_up_to: 200,
+                read_up_to: 200,
+            },
+        );
+        let regressed = sign_dm_receipts(
+            &bob,
+            alice_id,
+            &owner,
+            DmReceipts {
+                version: 4,
+                delivered_up_to: 200,
+                read_up_to: 150,
+            },
+        );
+        assert!(regressed.supersedes(&read).is_err());
+    }
+}
@@ -39,7 +39,7 @@ use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret as X25519Ephemeral
 /// observable to anyone byte-comparing).
 const ECIES_EPHEMERAL_DOMAIN: &str = "river-ecies-ephemeral-v1 2026-05";
 
-fn ed25519_to_x25519_public_key(ed25519_pk: &VerifyingKey) -> X25519PublicKey {
+pub(crate) fn ed25519_to_x25519_public_key(ed25519_pk: &VerifyingKey) -> X25519PublicKey {
     let ed_y = CompressedEdwardsY(ed25519_pk.to_bytes())
         .decompress()
         .expect("Invalid Edwards point");
@@ -47,7 +47,7 @@ fn ed25519_to_x25519_public_key(ed25519_pk: &VerifyingKey) -> X25519PublicKey {
     X25519PublicKey::from(mont_u)
 }
 
-fn ed25519_to_x25519_private_key(ed25519_sk: &SigningKey) -> X25519EphemeralSecret {
+pub(crate) fn ed25519_to_x25519_private_key(ed25519_sk: &SigningKey) -> X25519EphemeralSecret {
     let h = Sha512::digest(ed25519_sk.to_bytes());
     let mut key = [0u8; 32];
     key.copy_from_slice(&h[..32]);
@@ -0,0 +1,353 @@
+//! Group direct messages: one DM to several members of a room.
+//!
+//! An [`AuthorizedDirectMessage`](crate::room_state::direct_messages::AuthorizedDirectMessage)
+//! binds exactly one recipient, so writing to three people meant three
+//! separately sealed copies. A [`GroupDirectMessage`] is encrypted once under
+//! a fresh content key, and that key is sealed to each participant's
+//! `member_vk` ([`GroupDirectMessage::wrapped_keys`]). The sender is a
+//! participant too, so they can read their own messages back.
+//!
+//! A conversation is identified by its participant set: every message with
+//! the same sorted [`GroupDirectMessage::participants`] belongs to the same
+//! thread. The signed envelope is delivered to the inbox of every participant
+//! (see [`crate::inbox::InboxGroupMessageV1`]), where each one purges it
+//! independently with their own purge envelope, keyed by the same
+//! signature-derived [`PurgeToken`] as a one-to-one DM.
+//!
+//! # Bounds
+//!
+//! - [`MIN_GROUP_DM_PARTICIPANTS`]..=[`MAX_GROUP_DM_PARTICIPANTS`] members,
+//!   sender included. Two people use a plain DM.
+//! - [`MAX_DM_CIPHERTEXT_BYTES`] for the body ciphertext, as for a DM.
+//! - Each wrapped key is exactly [`WRAPPED_KEY_BYTES`].
+
+use crate::room_state::direct_messages::{
+    DmOrderKey, PurgeToken, SignatureBytes, MAX_DM_CIPHERTEXT_BYTES,
+};
+use crate::room_state::member::MemberId;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+/// Domain-separation tag for [`build_group_message_signed_bytes`], distinct
+/// from the one-to-one DM (`b'M'`) and purge (`b'P'`) tags.
+pub const DOMAIN_TAG_GROUP_MESSAGE: u8 = b'G';
+
+/// Fewest members in a group conversation, sender included.
+pub const MIN_GROUP_DM_PARTICIPANTS: usize = 3;
+
+/// Most members in a group conversation, sender included.
+pub const MAX_GROUP_DM_PARTICIPANTS: usize = 8;
+
+/// Size of a content key sealed to one participant: ephemeral X25519 key
+/// (32), nonce (12), the 32-byte key and the AES-GCM tag (16).
+pub const WRAPPED_KEY_BYTES: usize = 32 + 12 + 32 + 16;
+
+/// A sender-signed group direct message.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuthorizedGroupDirectMessage {
+    pub message: GroupDirectMessage,
+    /// Sender's Ed25519 signature over the bytes produced by
+    /// [`build_group_message_signed_bytes`].
+    pub sender_signature: Signature,
+}
+
+/// The signed payload of a group direct message.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GroupDirectMessage {
+    pub sender: MemberId,
+    /// Everyone in the conversation, the sender included, sorted ascending
+    /// without duplicates. Identifies the thread.
+    pub participants: Vec<MemberId>,
+    /// Unix timestamp (seconds since epoch).
+    pub timestamp: u64,
+    /// The content key sealed to each participant, in `participants` order.
+    pub wrapped_keys: Vec<Vec<u8>>,
+    /// AES-GCM nonce for `ciphertext`.
+    pub nonce: [u8; 12],
+    /// The body, encrypted once under the content key.
+    pub ciphertext: Vec<u8>,
+}
+
+/// Build the bytes the sender signs for an [`AuthorizedGroupDirectMessage`].
+///
+/// ```text
+///     domain_tag                  ( 1 byte, = DOMAIN_TAG_GROUP_MESSAGE)
+///     sender_member_id_le_i64     ( 8 bytes)
+///     room_owner_vk               (32 bytes)
+///     timestamp_le_u64            ( 8 bytes)
+///     participant_count_le_u32    ( 4 bytes)
+///     participants                ( 8 bytes each)
+///     per wrapped key: len_le_u32 ( 4 bytes) + key bytes
+///     nonce                       (12 bytes)
+///     ciphertext_len_le_u32       ( 4 bytes)
+///     ciphertext                  (variable)
+/// ```
+pub fn build_group_message_signed_bytes(
+    message: &GroupDirectMessage,
+    room_owner_vk: &VerifyingKey,
+) -> Result<Vec<u8>, String> {
+    let len_u32 = |len: usize, what: &str| -> Result<[u8; 4], String> {
+        u32::try_from(len)
+            .map(u32::to_le_bytes)
+            .map_err(|_| format!("Group DM {} length {} does not fit in u32", what, len))
+    };
+    let mut out = Vec::with_capacity(
+        1 + 8
+            + 32
+            + 8
+            + 4
+            + message.participants.len() * (8 + 4 + WRAPPED_KEY_BYTES)
+            + 12
+            + 4
+            + message.ciphertext.len(),
+    );
+    out.push(DOMAIN_TAG_GROUP_MESSAGE);
+    out.extend_from_slice(&message.sender.0 .0.to_le_bytes());
+    out.extend_from_slice(room_owner_vk.as_bytes());
+    out.extend_from_slice(&message.timestamp.to_le_bytes());
+    out.extend_from_slice(&len_u32(message.participants.len(), "participant list")?);
+    for participant in &message.participants {
+        out.extend_from_slice(&participant.0 .0.to_le_bytes());
+    }
+    out.extend_from_slice(&len_u32(message.wrapped_keys.len(), "wrapped key list")?);
+    for key in &message.wrapped_keys {
+        out.extend_from_slice(&len_u32(key.len(), "wrapped key")?);
+        out.extend_from_slice(key);
+    }
+    out.extend_from_slice(&message.nonce);
+    out.extend_from_slice(&len_u32(message.ciphertext.len(), "ciphertext")?);
+    out.extend_from_slice(&message.ciphertext);
+    Ok(out)
+}
+
+/// Canonical participant list for a conversation between `members`: sorted,
+/// without duplicates.
+pub fn participant_set(members: impl IntoIterator<Item = MemberId>) -> Vec<MemberId> {
+    let mut participants: Vec<MemberId> = members.into_iter().collect();
+    participants.sort();
+    participants.dedup();
+    participants
+}
+
+/// Encrypt `body` once for everyone in the conversation, seal the content
+/// key to each participant and sign as the sender. `others` are the other
+/// participants' member keys; the sender is added. Caps are checked here so
+/// a client never sends what an inbox will refuse.
+#[cfg(feature = "ecies-randomized")]
+pub fn compose_group_direct_message(
+    sender_sk: &SigningKey,
+    others: &[VerifyingKey],
+    room_owner_vk: &VerifyingKey,
+    timestamp: u64,
+    now_secs: u64,
+    body: &[u8],
+) -> Result<AuthorizedGroupDirectMessage, String> {
+    crate::room_state::direct_messages::check_dm_future_skew(timestamp, now_secs)?;
+
+    let sender_vk = sender_sk.verifying_key();
+    let mut members: Vec<(MemberId, VerifyingKey)> = std::iter::once(sender_vk)
+        .chain(others.iter().copied())
+        .map(|vk| (MemberId::from(&vk), vk))
+        .collect();
+    members.sort_by_key(|(id, _)| *id);
+    members.dedup_by_key(|(id, _)| *id);
+
+    let content_key = crate::ecies::generate_room_secret();
+    let (ciphertext, nonce) = crate::ecies::encrypt_with_symmetric_key(&content_key, body);
+    let message = GroupDirectMessage {
+        sender: MemberId::from(&sender_vk),
+        participants: members.iter().map(|(id, _)| *id).collect(),
+        timestamp,
+        wrapped_keys: members
+            .iter()
+            .map(|(_, vk)| crate::ecies::seal_dm_for_recipient(vk, &content_key))
+            .collect(),
+        nonce,
+        ciphertext,
+    };
+    message.check_shape()?;
+    sign_group_direct_message(sender_sk, room_owner_vk, message)
+}
+
+/// Sign `message` as its sender. The sender's `MemberId` MUST match
+/// `sender_sk.verifying_key()`.
+pub fn sign_group_direct_message(
+    sender_sk: &SigningKey,
+    room_owner_vk: &VerifyingKey,
+    message: GroupDirectMessage,
+) -> Result<AuthorizedGroupDirectMessage, String> {
+    debug_assert_eq!(
+        message.sender,
+        MemberId::from(&sender_sk.verifying_key()),
+        "sender MemberId must derive from sender_sk"
+    );
+    let bytes = build_group_message_signed_bytes(&message, room_owner_vk)?;
+    let sender_signature = sender_sk.sign(&bytes);
+    Ok(AuthorizedGroupDirectMessage {
+        message,
+        sender_signature,
+    })
+}
+
+/// Inverse of [`compose_group_direct_message`]: unwrap the content key with a
+/// participant's signing key and decrypt the body. Does NOT verify the sender
+/// signature.
+#[cfg(feature = "ecies")]
+pub fn open_group_direct_message(
+    participant_sk: &SigningKey,
+    msg: &AuthorizedGroupDirectMessage,
+) -> Result<Vec<u8>, String> {
+    let me = MemberId::from(&participant_sk.verifying_key());
+    let index = msg
+        .message
+        .participants
+        .iter()
+        .position(|p| *p == me)
+        .ok_or_else(|| "Not a participant of this group DM".to_string())?;
+    let wrapped = msg
+        .message
+        .wrapped_keys
+        .get(index)
+        .ok_or_else(|| "Group DM has no key for this participant".to_string())?;
+    let key: [u8; 32] = crate::ecies::unseal_dm_from_sender(participant_sk, wrapped)?
+        .try_into()
+        .map_err(|_| "Group DM content key has the wrong length".to_string())?;
+    crate::ecies::decrypt_with_symmetric_key(&key, &msg.message.ciphertext, &msg.message.nonce)
+}
+
+impl GroupDirectMessage {
+    /// Check everything that does not need keys: participant list canonical
+    /// and within bounds, sender among them, one wrapped key each, sizes.
+    pub fn check_shape(&self) -> Result<(), String> {
+        let count = self.participants.len();
+        if !(MIN_GROUP_DM_PARTICIPANTS..=MAX_GROUP_DM_PARTICIPANTS).contains(&count) {
+            return Err(format!(
+                "Group DM has {} participants; {} to {} allowed",
+                count, MIN_GROUP_DM_PARTICIPANTS, MAX_GROUP_DM_PARTICIPANTS
+            ));
+        }
+        if self.participants.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("Group DM participants must be sorted and distinct".to_string());
+        }
+        if !self.participants.contains(&self.sender) {
+            return Err("Group DM sender is not a participant".to_string());
+        }
+        if self.wrapped_keys.len() != count {
+            return Err(format!(
+                "Group DM has {} wrapped keys for {} participants",
+                self.wrapped_keys.len(),
+                count
+            ));
+        }
+        if self
+            .wrapped_keys
+            .iter()
+            .any(|k| k.len() != WRAPPED_KEY_BYTES)
+        {
+            return Err("Group DM wrapped key has the wrong length".to_string());
+        }
+        if self.ciphertext.len() > MAX_DM_CIPHERTEXT_BYTES {
+            return Err(format!(
+                "Group DM ciphertext too large: {} > {}",
+                self.ciphertext.len(),
+                MAX_DM_CIPHERTEXT_BYTES
+            ));
+        }
+        Ok(())
+    }
+
+    /// The participants other than `me`.
+    pub fn others(&self, me: MemberId) -> impl Iterator<Item = MemberId> + '_ {
+        self.participants.iter().copied().filter(move |p| *p != me)
+    }
+}
+
+impl AuthorizedGroupDirectMessage {
+    /// Verify the sender signature against the sender's verifying key.
+    pub fn verify_signature(
+        &self,
+        sender_vk: &VerifyingKey,
+        room_owner_vk: &VerifyingKey,
+    ) -> Result<(), String> {
+        let bytes = build_group_message_signed_bytes(&self.message, room_owner_vk)?;
+        sender_vk
+            .verify(&bytes, &self.sender_signature)
+            .map_err(|e| format!("Invalid group DM sender signature: {}", e))
+    }
+
+    /// Tombstone token for this signature, as for a one-to-one DM.
+    pub fn purge_token(&self) -> PurgeToken {
+        PurgeToken::from_signature(&self.sender_signature)
+    }
+
+    /// `(timestamp, signature)` retention order, as for a one-to-one DM.
+    pub fn order_key(&self) -> DmOrderKey {
+        DmOrderKey {
+            timestamp: self.message.timestamp,
+            signature: SignatureBytes(self.sender_signature.to_bytes()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ecies-randomized"))]
+mod tests {
+    use super::*;
+
+    fn sk(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn every_participant_can_open_and_others_cannot() {
+        let owner = sk(1).verifying_key();
+        let (alice, bob, carol, dave) = (sk(2), sk(3), sk(4), sk(5));
+        let msg = compose_group_direct_message(
+            &alice,
+            &[bob.verifying_key(), carol.verifying_key()],
+            &owner,
+            100,
+            100,
+            b"hello all",
+        )
+        .unwrap();
+
+        assert_eq!(msg.message.participants.len(), 3);
+        msg.message.check_shape().unwrap();
+        msg.verify_signature(&alice.verifying_key(), &owner)
+            .unwrap();
+        for reader in [&alice, &bob, &carol] {
+            assert_eq!(
+                open_group_direct_message(reader, &msg).unwrap(),
+                b"hello all"
+            );
+        }
+        assert!(open_group_direct_message(&dave, &msg).is_err());
+    }
+
+    #[test]
+    fn shape_and_signature_checks_reject_tampering() {
+        let owner = sk(1).verifying_key();
+        let alice = sk(2);
+        // Just the sender and one other: a plain DM, not a group.
+        assert!(
+            compose_group_direct_message(&alice, &[sk(3).verifying_key()], &owner, 1, 1, b"x")
+                .is_err()
+        );
+
+        let msg = compose_group_direct_message(
+            &alice,
+            &[sk(3).verifying_key(), sk(4).verifying_key()],
+            &owner,
+            1,
+            1,
+            b"x",
+        )
+        .unwrap();
+        let mut dropped = msg.clone();
+        dropped.message.participants.pop();
+        assert!(dropped
+            .verify_signature(&alice.verifying_key(), &owner)
+            .is_err());
+        assert!(dropped.message.check_shape().is_err());
+    }
+}
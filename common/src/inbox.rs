@@ -0,0 +1,1422 @@
+//! Per-member DM inbox contracts.
+//!
+//! In-room DMs (`DirectMessagesV1`) share one owner-tunable cap across the
+//! whole room, so in a busy room one member's traffic evicts another member's
+//! unread DMs. An inbox moves a member's incoming DMs into a contract of their
+//! own: its parameters are an [`InboxParametersV1`] naming the room and the
+//! recipient's member key, and its state is an [`InboxStateV1`] holding only
+//! DMs addressed to that member plus their purge envelope.
+//!
+//! The wire formats are the in-room ones. Each DM is an
+//! [`AuthorizedDirectMessage`] signed by its sender (still bound to the room
+//! owner key), and purges are one [`AuthorizedRecipientPurges`] signed by the
+//! recipient, with the same monotonic-version, no-un-purging rules. What the
+//! inbox adds is the sender's proof of membership: the contract cannot read
+//! the room, so each [`InboxMessageV1`] carries the sender's invite chain up
+//! to the owner. That keeps strangers from filling an inbox; a member banned
+//! after the fact still has a valid chain, so clients hide DMs from currently
+//! banned senders (see [`InboxStateV1::visible_messages`]).
+//!
+//! An inbox is a CRDT like the room state: [`InboxStateV1::merge`] is the
+//! only way in, and a delta is itself a (partial) state, so a full PUT and an
+//! update merge the same way. Bounds are per sender
+//! ([`MAX_DM_MESSAGES_PER_PAIR`], newest kept, with the summary carrying each
+//! full sender's horizon so peers never re-offer a DM the other side's cap
+//! would drop) and per inbox ([`MAX_INBOX_MESSAGES`]). A member can invite
+//! any number of extra identities, so the inbox cap is shared out by invite
+//! subtree rather than by age: an overfull inbox drops the oldest DM of the
+//! largest subtree, level by level down from the owner, and a member flooding
+//! through identities they invited only pushes out their own DMs.
+//!
+//! The purge envelope is capped at [`MAX_PURGED_TOMBSTONES_PER_RECIPIENT`]
+//! tokens. Its `purged_before` horizon purges every DM older than it, so a
+//! newer envelope that moves the horizon forward may drop tokens; see
+//! [`InboxStateV1::advance_purges`].
+//!
+//! Existing in-room DMs move over with [`messages_from_room`]: the recipient
+//! copies them into their inbox and then purges them from the room.
+//!
+//! Group DMs ([`crate::group_dm`]) live alongside as [`InboxGroupMessageV1`]:
+//! the sender delivers the one signed envelope to every participant's inbox,
+//! their own included, and each participant purges it from theirs with the
+//! same purge envelope as their one-to-one DMs. Group DMs are capped apart
+//! from one-to-one DMs, with the same limits.
+//!
+//! Receipts ([`crate::dm_receipts`]) travel the other way: a reader puts their
+//! signed envelope about the inbox owner's DMs into the owner's inbox, with
+//! their invite chain, as an [`InboxReceiptsV1`]. The inbox keeps one per
+//! reader, the newest version, and at most [`MAX_INBOX_RECEIPTS`].
+
+use crate::dm_receipts::AuthorizedDmReceipts;
+use crate::group_dm::AuthorizedGroupDirectMessage;
+use crate::room_state::direct_messages::{
+    advance_recipient_purges, sign_recipient_purges, AuthorizedDirectMessage,
+    AuthorizedRecipientPurges, DmOrderKey, PurgeToken, RecipientPurges, SignatureBytes,
+    MAX_DM_CIPHERTEXT_BYTES, MAX_DM_MESSAGES_PER_PAIR, MAX_PURGED_TOMBSTONES_PER_RECIPIENT,
+};
+use crate::room_state::member::{AuthorizedMember, MemberId};
+use crate::room_state::ChatRoomParametersV1;
+use crate::ChatRoomStateV1;
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+/// Most DMs one inbox holds; the largest invite subtree's oldest go first.
+pub const MAX_INBOX_MESSAGES: usize = 1000;
+
+/// Longest invite chain an [`InboxMessageV1`] may carry.
+pub const MAX_SENDER_CHAIN_LEN: usize = 64;
+
+/// Most readers' receipt envelopes one inbox holds; those about the most
+/// recent DMs are kept.
+pub const MAX_INBOX_RECEIPTS: usize = 256;
+
+/// Parameters of an inbox contract.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InboxParametersV1 {
+    /// The room the inbox belongs to; every DM signature binds it.
+    pub room_owner: VerifyingKey,
+    /// The inbox owner's member key: the only recipient accepted and the only
+    /// key that may sign purges.
+    pub recipient: VerifyingKey,
+}
+
+impl InboxParametersV1 {
+    pub fn recipient_id(&self) -> MemberId {
+        MemberId::from(&self.recipient)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        ciborium::ser::into_writer(self, &mut buf).expect("Serialization should not fail");
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        ciborium::de::from_reader(bytes)
+            .map_err(|e| format!("Failed to decode InboxParametersV1: {}", e))
+    }
+}
+
+/// A DM as stored in an inbox: the signed in-room envelope plus the sender's
+/// membership proof.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct InboxMessageV1 {
+    /// The sender's `AuthorizedMember` followed by each inviter up to one
+    /// invited by the room owner; empty when the owner is the sender.
+    pub sender_chain: Vec<AuthorizedMember>,
+    pub message: AuthorizedDirectMessage,
+}
+
+impl InboxMessageV1 {
+    /// The key the sender signed with, per its chain.
+    pub fn sender_vk(&self, parameters: &InboxParametersV1) -> VerifyingKey {
+        self.sender_chain
+            .first()
+            .map(|member| member.member.member_vk)
+            .unwrap_or(parameters.room_owner)
+    }
+
+    /// Check the envelope against the inbox it is offered to: addressed to its
+    /// recipient, within the size cap, signed by a sender whose invite chain
+    /// leads to the room owner.
+    pub fn verify(&self, parameters: &InboxParametersV1) -> Result<(), String> {
+        let message = &self.message.message;
+        if message.recipient != parameters.recipient_id() {
+            return Err(format!(
+                "DM addressed to {:?} offered to the inbox of {:?}",
+                message.recipient,
+                parameters.recipient_id()
+            ));
+        }
+        if message.sender == message.recipient {
+            return Err("DM sender and recipient must differ".to_string());
+        }
+        if message.ciphertext.len() > MAX_DM_CIPHERTEXT_BYTES {
+            return Err(format!(
+                "DM ciphertext too large: {} > {}",
+                message.ciphertext.len(),
+                MAX_DM_CIPHERTEXT_BYTES
+            ));
+        }
+        let sender_vk = self.sender_vk(parameters);
+        if MemberId::from(&sender_vk) != message.sender {
+            return Err(format!(
+                "DM sender {:?} does not match its invite chain",
+                message.sender
+            ));
+        }
+        verify_sender_chain(&self.sender_chain, &parameters.room_owner)?;
+        self.message
+            .verify_signature(&sender_vk, &parameters.room_owner)
+    }
+}
+
+/// A group DM as stored in the inbox of one of its participants: the signed
+/// envelope plus the sender's membership proof.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct InboxGroupMessageV1 {
+    /// As for [`InboxMessageV1::sender_chain`].
+    pub sender_chain: Vec<AuthorizedMember>,
+    pub message: AuthorizedGroupDirectMessage,
+}
+
+impl InboxGroupMessageV1 {
+    /// The key the sender signed with, per its chain.
+    pub fn sender_vk(&self, parameters: &InboxParametersV1) -> VerifyingKey {
+        self.sender_chain
+            .first()
+            .map(|member| member.member.member_vk)
+            .unwrap_or(parameters.room_owner)
+    }
+
+    /// Check the envelope against the inbox it is offered to: the inbox owner
+    /// among its participants (the sender's own inbox keeps a copy too), well
+    /// formed, signed by a sender whose invite chain leads to the room owner.
+    pub fn verify(&self, parameters: &InboxParametersV1) -> Result<(), String> {
+        let message = &self.message.message;
+        if !message.participants.contains(&parameters.recipient_id()) {
+            return Err(format!(
+                "Group DM offered to the inbox of non-participant {:?}",
+                parameters.recipient_id()
+            ));
+        }
+        message.check_shape()?;
+        let sender_vk = self.sender_vk(parameters);
+        if MemberId::from(&sender_vk) != message.sender {
+            return Err(format!(
+                "Group DM sender {:?} does not match its invite chain",
+                message.sender
+            ));
+        }
+        verify_sender_chain(&self.sender_chain, &parameters.room_owner)?;
+        self.message
+            .verify_signature(&sender_vk, &parameters.room_owner)
+    }
+}
+
+/// A reader's receipts about the inbox owner's DMs, with the reader's
+/// membership proof.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct InboxReceiptsV1 {
+    /// As for [`InboxMessageV1::sender_chain`], for the reader.
+    pub reader_chain: Vec<AuthorizedMember>,
+    pub receipts: AuthorizedDmReceipts,
+}
+
+impl InboxReceiptsV1 {
+    /// The key the reader signed with, per its chain.
+    pub fn reader_vk(&self, parameters: &InboxParametersV1) -> VerifyingKey {
+        self.reader_chain
+            .first()
+            .map(|member| member.member.member_vk)
+            .unwrap_or(parameters.room_owner)
+    }
+
+    /// Check the envelope against the inbox it is offered to: about the inbox
+    /// owner's DMs, signed by a reader whose invite chain leads to the room
+    /// owner.
+    pub fn verify(&self, parameters: &InboxParametersV1) -> Result<(), String> {
+        let receipts = &self.receipts;
+        if receipts.sender != parameters.recipient_id() {
+            return Err(format!(
+                "DM receipts about {:?} offered to the inbox of {:?}",
+                receipts.sender,
+                parameters.recipient_id()
+            ));
+        }
+        let reader_vk = self.reader_vk(parameters);
+        if MemberId::from(&reader_vk) != receipts.reader {
+            return Err(format!(
+                "DM receipts reader {:?} does not match its invite chain",
+                receipts.reader
+            ));
+        }
+        verify_sender_chain(&self.reader_chain, &parameters.room_owner)?;
+        receipts.verify(&reader_vk, &parameters.room_owner)
+    }
+
+    /// Which receipts the cap keeps first: those about the newest DMs, then
+    /// by reader so every holder picks the same ones.
+    fn retention_key(&self) -> (u64, u64, MemberId) {
+        let state = &self.receipts.state;
+        (
+            state.delivered_up_to,
+            state.read_up_to,
+            self.receipts.reader,
+        )
+    }
+}
+
+/// What the caps, purges and summaries need from either kind of held DM.
+trait HeldMessage {
+    fn sender(&self) -> MemberId;
+    fn sender_chain(&self) -> &[AuthorizedMember];
+    fn order_key(&self) -> DmOrderKey;
+    fn purge_token(&self) -> PurgeToken;
+
+    fn signature_bytes(&self) -> SignatureBytes {
+        self.order_key().signature
+    }
+
+    /// The sender's place in the invite tree: the member the owner invited,
+    /// then each invitee down to the sender. Empty for the owner.
+    fn invite_path(&self) -> Vec<MemberId> {
+        self.sender_chain()
+            .iter()
+            .rev()
+            .map(|member| member.member.id())
+            .collect()
+    }
+}
+
+impl HeldMessage for InboxMessageV1 {
+    fn sender(&self) -> MemberId {
+        self.message.message.sender
+    }
+    fn sender_chain(&self) -> &[AuthorizedMember] {
+        &self.sender_chain
+    }
+    fn order_key(&self) -> DmOrderKey {
+        self.message.order_key()
+    }
+    fn purge_token(&self) -> PurgeToken {
+        self.message.purge_token()
+    }
+}
+
+impl HeldMessage for InboxGroupMessageV1 {
+    fn sender(&self) -> MemberId {
+        self.message.message.sender
+    }
+    fn sender_chain(&self) -> &[AuthorizedMember] {
+        &self.sender_chain
+    }
+    fn order_key(&self) -> DmOrderKey {
+        self.message.order_key()
+    }
+    fn purge_token(&self) -> PurgeToken {
+        self.message.purge_token()
+    }
+}
+
+/// What the recipient's purge envelope covers: its tokens and everything
+/// older than its horizon.
+#[derive(Default)]
+struct Purged {
+    tokens: HashSet<PurgeToken>,
+    before: u64,
+}
+
+impl Purged {
+    fn covers<T: HeldMessage>(&self, message: &T) -> bool {
+        message.order_key().timestamp < self.before || self.tokens.contains(&message.purge_token())
+    }
+}
+
+/// Check one list of held DMs: within the inbox cap, no duplicates, nothing
+/// purged, within the per-sender cap. `kind` names the list in errors.
+fn verify_held<T: HeldMessage>(messages: &[T], purged: &Purged, kind: &str) -> Result<(), String> {
+    if messages.len() > MAX_INBOX_MESSAGES {
+        return Err(format!(
+            "Inbox holds {} {}, more than {}",
+            messages.len(),
+            kind,
+            MAX_INBOX_MESSAGES
+        ));
+    }
+    let mut seen = HashSet::new();
+    let mut per_sender: HashMap<MemberId, usize> = HashMap::new();
+    for message in messages {
+        if !seen.insert(message.signature_bytes()) {
+            return Err(format!("Inbox holds one of its {} twice", kind));
+        }
+        if purged.covers(message) {
+            return Err(format!("Inbox holds one of its {} after purging it", kind));
+        }
+        let count = per_sender.entry(message.sender()).or_default();
+        *count += 1;
+        if *count > MAX_DM_MESSAGES_PER_PAIR {
+            return Err(format!(
+                "Inbox holds more than {} {} from {:?}",
+                MAX_DM_MESSAGES_PER_PAIR,
+                kind,
+                message.sender()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Verify and append those of `incoming` not already in `messages`.
+fn merge_held<T: HeldMessage>(
+    messages: &mut Vec<T>,
+    incoming: Vec<T>,
+    verify: impl Fn(&T) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut held: HashSet<SignatureBytes> = messages.iter().map(|m| m.signature_bytes()).collect();
+    for message in incoming {
+        if held.contains(&message.signature_bytes()) {
+            continue;
+        }
+        verify(&message)?;
+        held.insert(message.signature_bytes());
+        messages.push(message);
+    }
+    Ok(())
+}
+
+/// Drop purged DMs, apply the caps and sort.
+fn normalize_held<T: HeldMessage>(messages: &mut Vec<T>, purged: &Purged) {
+    messages.retain(|m| !purged.covers(m));
+    messages.sort_by_key(|m| m.order_key());
+
+    let mut per_sender: HashMap<MemberId, usize> = HashMap::new();
+    for message in messages.iter() {
+        *per_sender.entry(message.sender()).or_default() += 1;
+    }
+    // Oldest first, so each sender's excess is the first of theirs seen.
+    messages.retain(|m| {
+        let count = per_sender.get_mut(&m.sender()).expect("counted above");
+        if *count > MAX_DM_MESSAGES_PER_PAIR {
+            *count -= 1;
+            false
+        } else {
+            true
+        }
+    });
+    if messages.len() > MAX_INBOX_MESSAGES {
+        let mut tree = InviteSubtree::default();
+        for (index, message) in messages.iter().enumerate() {
+            tree.insert(&message.invite_path(), index);
+        }
+        let evicted: HashSet<usize> = (MAX_INBOX_MESSAGES..messages.len())
+            .map(|_| tree.evict())
+            .collect();
+        let mut index = 0;
+        messages.retain(|_| {
+            index += 1;
+            !evicted.contains(&(index - 1))
+        });
+    }
+}
+
+/// Held DMs grouped by where their senders sit in the invite tree, for
+/// sharing the inbox cap out by subtree.
+#[derive(Default)]
+struct InviteSubtree {
+    /// DMs sent from anywhere in this subtree.
+    count: usize,
+    /// Indices of the DMs this subtree's root member sent, oldest first.
+    own: VecDeque<usize>,
+    /// The subtrees of the members this one invited.
+    children: BTreeMap<MemberId, InviteSubtree>,
+}
+
+impl InviteSubtree {
+    fn insert(&mut self, path: &[MemberId], index: usize) {
+        self.count += 1;
+        match path.split_first() {
+            None => self.own.push_back(index),
+            Some((child, rest)) => self.children.entry(*child).or_default().insert(rest, index),
+        }
+    }
+
+    /// Remove the oldest DM of the largest share below this member (their own
+    /// DMs count as one share, each invitee's subtree as another) and return
+    /// its index. Ties go to the invitee with the greatest ID, so every
+    /// holder evicts the same DM. Only called while `count > 0`.
+    fn evict(&mut self) -> usize {
+        self.count -= 1;
+        match self.children.values_mut().max_by_key(|child| child.count) {
+            Some(child) if child.count > self.own.len() => child.evict(),
+            _ => self
+                .own
+                .pop_front()
+                .expect("a subtree with DMs has some of its own or in a child"),
+        }
+    }
+}
+
+/// The signatures and per-sender horizons of one list of held DMs, as
+/// carried in an [`InboxSummaryV1`]. There is no inbox-wide horizon: which
+/// DM the subtree-shared cap drops is not a matter of age alone.
+fn summarize_held<T: HeldMessage>(
+    messages: &[T],
+) -> (BTreeSet<SignatureBytes>, Vec<(MemberId, DmOrderKey)>) {
+    let mut per_sender: HashMap<MemberId, (usize, DmOrderKey)> = HashMap::new();
+    for message in messages {
+        let key = message.order_key();
+        per_sender
+            .entry(message.sender())
+            .and_modify(|(count, oldest)| {
+                *count += 1;
+                if key < *oldest {
+                    *oldest = key.clone();
+                }
+            })
+            .or_insert((1, key));
+    }
+    let mut sender_horizons: Vec<(MemberId, DmOrderKey)> = per_sender
+        .into_iter()
+        .filter(|(_, (count, _))| *count >= MAX_DM_MESSAGES_PER_PAIR)
+        .map(|(sender, (_, oldest))| (sender, oldest))
+        .collect();
+    sender_horizons.sort();
+
+    (
+        messages.iter().map(|m| m.signature_bytes()).collect(),
+        sender_horizons,
+    )
+}
+
+/// The held DMs a peer lacks and would keep, given its signatures and
+/// per-sender horizons for this list.
+fn missing_held<T: HeldMessage + Clone>(
+    messages: &[T],
+    signatures: &BTreeSet<SignatureBytes>,
+    sender_horizons: &[(MemberId, DmOrderKey)],
+) -> Vec<T> {
+    let horizons: HashMap<MemberId, &DmOrderKey> = sender_horizons
+        .iter()
+        .map(|(sender, oldest)| (*sender, oldest))
+        .collect();
+    messages
+        .iter()
+        .filter(|m| !signatures.contains(&m.signature_bytes()))
+        .filter(|m| {
+            horizons
+                .get(&m.sender())
+                .is_none_or(|oldest| m.order_key() > **oldest)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Check that each member in `chain` was invited by the next, and the last by
+/// the owner.
+fn verify_sender_chain(
+    chain: &[AuthorizedMember],
+    room_owner: &VerifyingKey,
+) -> Result<(), String> {
+    if chain.len() > MAX_SENDER_CHAIN_LEN {
+        return Err(format!(
+            "Sender invite chain too long: {} > {}",
+            chain.len(),
+            MAX_SENDER_CHAIN_LEN
+        ));
+    }
+    let owner_id = MemberId::from(room_owner);
+    for (index, member) in chain.iter().enumerate() {
+        let inviter_vk = match chain.get(index + 1) {
+            Some(inviter) => inviter.member.member_vk,
+            None => *room_owner,
+        };
+        let inviter_id = MemberId::from(&inviter_vk);
+        if member.member.invited_by != inviter_id || member.member.owner_member_id != owner_id {
+            return Err(format!(
+                "Sender invite chain is broken at {:?}",
+                member.member.id()
+            ));
+        }
+        member.verify_signature(&inviter_vk)?;
+    }
+    Ok(())
+}
+
+/// The membership proof to attach when `member` sends a DM in this room:
+/// `member` followed by its inviters from `room_state`. The owner sends with
+/// an empty chain.
+pub fn sender_chain(
+    room_state: &ChatRoomStateV1,
+    parameters: &ChatRoomParametersV1,
+    member: &AuthorizedMember,
+) -> Result<Vec<AuthorizedMember>, String> {
+    let mut chain = vec![member.clone()];
+    chain.extend(room_state.members.get_invite_chain(member, parameters)?);
+    Ok(chain)
+}
+
+/// State of an inbox contract.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq, Debug)]
+pub struct InboxStateV1 {
+    /// Held DMs, oldest first.
+    #[serde(default)]
+    pub messages: Vec<InboxMessageV1>,
+    /// The recipient's purge envelope, if they have purged anything. It
+    /// covers group DMs too.
+    #[serde(default)]
+    pub purges: Option<AuthorizedRecipientPurges>,
+    /// Held group DMs the inbox owner takes part in, oldest first.
+    #[serde(default)]
+    pub group_messages: Vec<InboxGroupMessageV1>,
+    /// Receipts from the inbox owner's DM peers, one per reader, sorted by
+    /// reader.
+    #[serde(default)]
+    pub receipts: Vec<InboxReceiptsV1>,
+}
+
+impl InboxStateV1 {
+    /// Decode a state or delta; empty bytes are the empty inbox.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.is_empty() {
+            return Ok(Self::default());
+        }
+        ciborium::de::from_reader(bytes).map_err(|e| format!("Failed to decode inbox: {}", e))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        ciborium::ser::into_writer(self, &mut buf).expect("Serialization should not fail");
+        buf
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+            && self.purges.is_none()
+            && self.group_messages.is_empty()
+            && self.receipts.is_empty()
+    }
+
+    /// The receipts `reader` has sent about the inbox owner's DMs.
+    pub fn receipts_from(&self, reader: MemberId) -> Option<&AuthorizedDmReceipts> {
+        self.receipts
+            .iter()
+            .map(|r| &r.receipts)
+            .find(|r| r.reader == reader)
+    }
+
+    /// The purge tokens of every held DM, one-to-one or group, sent by one
+    /// of `senders`: what a recipient folds into their purge envelope to
+    /// drop everything from members they have blocked.
+    pub fn tokens_from(&self, senders: &HashSet<MemberId>) -> Vec<PurgeToken> {
+        let one_to_one = self.messages.iter().map(|m| (m.sender(), m.purge_token()));
+        let group = self
+            .group_messages
+            .iter()
+            .map(|m| (m.sender(), m.purge_token()));
+        one_to_one
+            .chain(group)
+            .filter(|(sender, _)| senders.contains(sender))
+            .map(|(_, token)| token)
+            .collect()
+    }
+
+    fn purged(&self) -> Purged {
+        self.purges
+            .as_ref()
+            .map(|p| Purged {
+                tokens: p.state.purged.iter().copied().collect(),
+                before: p.state.purged_before,
+            })
+            .unwrap_or_default()
+    }
+
+    /// The recipient's next purge envelope: the current one plus `tokens`,
+    /// which should be of DMs this inbox holds. When that would pass
+    /// [`MAX_PURGED_TOMBSTONES_PER_RECIPIENT`] the envelope is compacted
+    /// instead: its horizon moves up to the oldest held DM that stays, every
+    /// earlier token is dropped, and only the tokens of held DMs at or past
+    /// the horizon are kept. A DM purged before the compaction and newer than
+    /// the horizon can then come back from a peer that still holds it, and
+    /// would have to be purged again.
+    pub fn advance_purges(
+        &self,
+        recipient_sk: &ed25519_dalek::SigningKey,
+        room_owner_vk: &VerifyingKey,
+        tokens: impl IntoIterator<Item = PurgeToken>,
+    ) -> Result<AuthorizedRecipientPurges, String> {
+        let tokens: HashSet<PurgeToken> = tokens.into_iter().collect();
+        let previous = self.purges.as_ref();
+        let held = previous.map_or(0, |p| p.state.purged.len());
+        if held + tokens.len() <= MAX_PURGED_TOMBSTONES_PER_RECIPIENT {
+            return advance_recipient_purges(recipient_sk, room_owner_vk, previous, tokens);
+        }
+
+        let held_dms = || {
+            let one_to_one = self
+                .messages
+                .iter()
+                .map(|m| (m.order_key(), m.purge_token()));
+            let group = self
+                .group_messages
+                .iter()
+                .map(|m| (m.order_key(), m.purge_token()));
+            one_to_one.chain(group)
+        };
+        let kept_from = held_dms()
+            .filter(|(_, token)| !tokens.contains(token))
+            .map(|(key, _)| key.timestamp)
+            .min();
+        let purged_from = held_dms()
+            .filter(|(_, token)| tokens.contains(token))
+            .map(|(key, _)| key.timestamp.saturating_add(1))
+            .max();
+        let purged_before = kept_from
+            .or(purged_from)
+            .unwrap_or(0)
+            .max(previous.map_or(0, |p| p.state.purged_before));
+        let mut purged: Vec<PurgeToken> = held_dms()
+            .filter(|(key, token)| key.timestamp >= purged_before && tokens.contains(token))
+            .map(|(_, token)| token)
+            .collect();
+        purged.sort();
+        purged.dedup();
+        if purged.len() > MAX_PURGED_TOMBSTONES_PER_RECIPIENT {
+            return Err(format!(
+                "recipient purge list would exceed cap even after compacting: {} > {}",
+                purged.len(),
+                MAX_PURGED_TOMBSTONES_PER_RECIPIENT
+            ));
+        }
+        let version = previous
+            .map_or(0, |p| p.state.version)
+            .checked_add(1)
+            .ok_or_else(|| "recipient purges version overflow".to_string())?;
+        sign_recipient_purges(
+            recipient_sk,
+            MemberId::from(&recipient_sk.verifying_key()),
+            room_owner_vk,
+            RecipientPurges {
+                version,
+                purged,
+                purged_before,
+            },
+        )
+    }
+
+    /// Check a whole state: every DM and the purge envelope valid, nothing
+    /// purged still held, and both caps respected for one-to-one and group
+    /// DMs alike.
+    pub fn verify(&self, parameters: &InboxParametersV1) -> Result<(), String> {
+        if let Some(purges) = &self.purges {
+            verify_purges(purges, parameters)?;
+        }
+        for message in &self.messages {
+            message.verify(parameters)?;
+        }
+        for message in &self.group_messages {
+            message.verify(parameters)?;
+        }
+        let purged = self.purged();
+        verify_held(&self.messages, &purged, "DMs")?;
+        verify_held(&self.group_messages, &purged, "group DMs")?;
+        if self.receipts.len() > MAX_INBOX_RECEIPTS {
+            return Err(format!(
+                "Inbox holds {} receipt envelopes, more than {}",
+                self.receipts.len(),
+                MAX_INBOX_RECEIPTS
+            ));
+        }
+        let mut readers = HashSet::new();
+        for receipts in &self.receipts {
+            receipts.verify(parameters)?;
+            if !readers.insert(receipts.receipts.reader) {
+                return Err("Inbox holds two receipt envelopes from one reader".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge `other` (a full state or a delta) into this one. Anything in it
+    /// that fails verification rejects the whole update; so is a purge
+    /// envelope that lowers the horizon of an older one, or drops its tokens
+    /// without raising the horizon, and a receipt envelope that takes back a
+    /// mark. Afterwards purged DMs are gone and every cap holds.
+    pub fn merge(
+        &mut self,
+        parameters: &InboxParametersV1,
+        other: InboxStateV1,
+    ) -> Result<(), String> {
+        if let Some(incoming) = other.purges {
+            verify_purges(&incoming, parameters)?;
+            let replace = match &self.purges {
+                None => true,
+                Some(current) if incoming.state.version > current.state.version => {
+                    if incoming.state.purged_before < current.state.purged_before {
+                        return Err("Purge envelope lowers the purge horizon".to_string());
+                    }
+                    let kept: HashSet<_> = incoming.state.purged.iter().collect();
+                    if incoming.state.purged_before == current.state.purged_before
+                        && !current.state.purged.iter().all(|t| kept.contains(t))
+                    {
+                        return Err("Purge envelope un-purges an earlier token".to_string());
+                    }
+                    true
+                }
+                // Two envelopes of one version can only come from the
+                // recipient signing twice; pick one the same way everywhere.
+                Some(current) if incoming.state.version == current.state.version => {
+                    incoming.recipient_signature.to_bytes() > current.recipient_signature.to_bytes()
+                }
+                Some(_) => false,
+            };
+            if replace {
+                self.purges = Some(incoming);
+            }
+        }
+
+        merge_held(&mut self.messages, other.messages, |m| m.verify(parameters))?;
+        merge_held(&mut self.group_messages, other.group_messages, |m| {
+            m.verify(parameters)
+        })?;
+        for incoming in other.receipts {
+            incoming.verify(parameters)?;
+            let reader = incoming.receipts.reader;
+            match self
+                .receipts
+                .iter_mut()
+                .find(|r| r.receipts.reader == reader)
+            {
+                Some(current) => {
+                    if incoming.receipts.supersedes(&current.receipts)? {
+                        *current = incoming;
+                    }
+                }
+                None => self.receipts.push(incoming),
+            }
+        }
+        self.normalize();
+        Ok(())
+    }
+
+    /// Drop purged DMs, apply the caps (newest kept) and sort.
+    fn normalize(&mut self) {
+        let purged = self.purged();
+        normalize_held(&mut self.messages, &purged);
+        normalize_held(&mut self.group_messages, &purged);
+        if self.receipts.len() > MAX_INBOX_RECEIPTS {
+            self.receipts
+                .sort_by_key(|r| std::cmp::Reverse(r.retention_key()));
+            self.receipts.truncate(MAX_INBOX_RECEIPTS);
+        }
+        self.receipts.sort_by_key(|r| r.receipts.reader);
+    }
+
+    pub fn summarize(&self) -> InboxSummaryV1 {
+        let (message_signatures, sender_horizons) = summarize_held(&self.messages);
+        let (group_signatures, group_sender_horizons) = summarize_held(&self.group_messages);
+        InboxSummaryV1 {
+            message_signatures,
+            purge_version: self.purges.as_ref().map_or(0, |p| p.state.version),
+            sender_horizons,
+            group_signatures,
+            group_sender_horizons,
+            receipt_versions: self
+                .receipts
+                .iter()
+                .map(|r| (r.receipts.reader, r.receipts.state.version))
+                .collect(),
+        }
+    }
+
+    /// What a peer with `summary` lacks and would keep: DMs it does not hold
+    /// that are newer than its horizons, and newer purge and receipt
+    /// envelopes.
+    pub fn delta(&self, summary: &InboxSummaryV1) -> InboxStateV1 {
+        let purges = self
+            .purges
+            .clone()
+            .filter(|p| p.state.version > summary.purge_version);
+        InboxStateV1 {
+            messages: missing_held(
+                &self.messages,
+                &summary.message_signatures,
+                &summary.sender_horizons,
+            ),
+            purges,
+            group_messages: missing_held(
+                &self.group_messages,
+                &summary.group_signatures,
+                &summary.group_sender_horizons,
+            ),
+            receipts: self
+                .receipts
+                .iter()
+                .filter(|r| {
+                    summary
+                        .receipt_versions
+                        .iter()
+                        .find(|(reader, _)| *reader == r.receipts.reader)
+                        .is_none_or(|(_, version)| r.receipts.state.version > *version)
+                })
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// The held DMs a client should show: all but those from members the room
+    /// currently bans. A banned sender's invite chain still verifies, so the
+    /// contract keeps their DMs; hiding them is the client's job.
+    pub fn visible_messages<'a>(
+        &'a self,
+        room_state: &ChatRoomStateV1,
+        parameters: &ChatRoomParametersV1,
+    ) -> impl Iterator<Item = &'a AuthorizedDirectMessage> + 'a {
+        let banned = room_state.members.banned_member_ids(
+            &room_state.bans,
+            &room_state.member_info,
+            parameters,
+        );
+        self.messages
+            .iter()
+            .map(|m| &m.message)
+            .filter(move |m| !banned.contains(&m.message.sender))
+    }
+
+    /// The held group DMs a client should show, filtered like
+    /// [`Self::visible_messages`].
+    pub fn visible_group_messages<'a>(
+        &'a self,
+        room_state: &ChatRoomStateV1,
+        parameters: &ChatRoomParametersV1,
+    ) -> impl Iterator<Item = &'a AuthorizedGroupDirectMessage> + 'a {
+        let banned = room_state.members.banned_member_ids(
+            &room_state.bans,
+            &room_state.member_info,
+            parameters,
+        );
+        self.group_messages
+            .iter()
+            .map(|m| &m.message)
+            .filter(move |m| !banned.contains(&m.message.sender))
+    }
+}
+
+fn verify_purges(
+    purges: &AuthorizedRecipientPurges,
+    parameters: &InboxParametersV1,
+) -> Result<(), String> {
+    if purges.recipient_id != parameters.recipient_id() {
+        return Err("Purge envelope is for another recipient".to_string());
+    }
+    if purges.state.version == 0 {
+        return Err("Purge envelope version 0 is reserved".to_string());
+    }
+    if purges.state.purged.len() > MAX_PURGED_TOMBSTONES_PER_RECIPIENT {
+        return Err(format!(
+            "Purge envelope exceeds cap: {} > {}",
+            purges.state.purged.len(),
+            MAX_PURGED_TOMBSTONES_PER_RECIPIENT
+        ));
+    }
+    purges.verify_signature(&parameters.recipient, &parameters.room_owner)
+}
+
+/// Summary of an inbox, for computing what a peer lacks.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq, Debug)]
+pub struct InboxSummaryV1 {
+    /// Signatures of the held DMs. A `BTreeSet` so equal inboxes summarize to
+    /// equal bytes.
+    #[serde(default)]
+    pub message_signatures: BTreeSet<SignatureBytes>,
+    /// Version of the held purge envelope, 0 for none.
+    #[serde(default)]
+    pub purge_version: u64,
+    /// For each sender at the per-sender cap, the oldest DM of theirs held.
+    #[serde(default)]
+    pub sender_horizons: Vec<(MemberId, DmOrderKey)>,
+    /// As `message_signatures`, for group DMs.
+    #[serde(default)]
+    pub group_signatures: BTreeSet<SignatureBytes>,
+    /// As `sender_horizons`, for group DMs.
+    #[serde(default)]
+    pub group_sender_horizons: Vec<(MemberId, DmOrderKey)>,
+    /// Version of the held receipt envelope from each reader, by reader.
+    #[serde(default)]
+    pub receipt_versions: Vec<(MemberId, u64)>,
+}
+
+impl InboxSummaryV1 {
+    /// Decode a summary; empty bytes are a peer holding nothing.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.is_empty() {
+            return Ok(Self::default());
+        }
+        ciborium::de::from_reader(bytes)
+            .map_err(|e| format!("Failed to decode inbox summary: {}", e))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        ciborium::ser::into_writer(self, &mut buf).expect("Serialization should not fail");
+        buf
+    }
+}
+
+/// The in-room DMs to `recipient` that are not purged, as inbox messages,
+/// with each sender's invite chain taken from `room_state`. This is what a
+/// recipient copies into their inbox before purging the room copies.
+pub fn messages_from_room(
+    room_state: &ChatRoomStateV1,
+    parameters: &ChatRoomParametersV1,
+    recipient: MemberId,
+) -> Vec<InboxMessageV1> {
+    let purged: HashSet<PurgeToken> = room_state
+        .direct_messages
+        .purges
+        .iter()
+        .filter(|p| p.recipient_id == recipient)
+        .flat_map(|p| p.state.purged.iter().copied())
+        .collect();
+    let members = room_state.members.members_by_member_id();
+    let owner_id = parameters.owner_id();
+    room_state
+        .direct_messages
+        .messages
+        .iter()
+        .filter(|m| m.message.recipient == recipient && !purged.contains(&m.purge_token()))
+        .filter_map(|m| {
+            let sender_chain = if m.message.sender == owner_id {
+                Vec::new()
+            } else {
+                let member = members.get(&m.message.sender)?;
+                sender_chain(room_state, parameters, member).ok()?
+            };
+            Some(InboxMessageV1 {
+                sender_chain,
+                message: m.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dm_receipts::{advance_dm_receipts, DmReceiptStatus};
+    use crate::room_state::direct_messages::{advance_recipient_purges, sign_direct_message};
+    use crate::room_state::member::Member;
+    use ed25519_dalek::SigningKey;
+
+    struct Room {
+        owner: SigningKey,
+        alice: SigningKey,
+        bob: SigningKey,
+    }
+
+    impl Room {
+        fn new() -> Self {
+            Room {
+                owner: SigningKey::from_bytes(&[1; 32]),
+                alice: SigningKey::from_bytes(&[2; 32]),
+                bob: SigningKey::from_bytes(&[3; 32]),
+            }
+        }
+
+        fn bob_id(&self) -> MemberId {
+            MemberId::from(&self.bob.verifying_key())
+        }
+
+        /// Bob's inbox.
+        fn params(&self) -> InboxParametersV1 {
+            InboxParametersV1 {
+                room_owner: self.owner.verifying_key(),
+                recipient: self.bob.verifying_key(),
+            }
+        }
+
+        fn member(&self, key: &SigningKey, inviter: &SigningKey) -> AuthorizedMember {
+            AuthorizedMember::new(
+                Member {
+                    owner_member_id: self.owner.verifying_key().into(),
+                    invited_by: inviter.verifying_key().into(),
+                    member_vk: key.verifying_key(),
+                },
+                inviter,
+            )
+        }
+
+        /// A DM from Alice (invited by the owner) to Bob.
+        fn dm(&self, timestamp: u64) -> InboxMessageV1 {
+            self.dm_from(
+                &self.alice,
+                vec![self.member(&self.alice, &self.owner)],
+                timestamp,
+            )
+        }
+
+        /// A DM to Bob from `sender`, whose invite chain runs up from them.
+        fn dm_from(
+            &self,
+            sender: &SigningKey,
+            sender_chain: Vec<AuthorizedMember>,
+            timestamp: u64,
+        ) -> InboxMessageV1 {
+            InboxMessageV1 {
+                sender_chain,
+                message: sign_direct_message(
+                    sender,
+                    sender.verifying_key().into(),
+                    self.bob.verifying_key().into(),
+                    &self.owner.verifying_key(),
+                    timestamp,
+                    vec![timestamp as u8; 8],
+                )
+                .unwrap(),
+            }
+        }
+    }
+
+    fn inbox(messages: Vec<InboxMessageV1>) -> InboxStateV1 {
+        InboxStateV1 {
+            messages,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn only_members_can_write_to_an_inbox() {
+        let room = Room::new();
+        let params = room.params();
+        assert!(room.dm(1).verify(&params).is_ok());
+
+        let stranger = SigningKey::from_bytes(&[9; 32]);
+        let mut forged = room.dm(1);
+        // Alice "invited" by someone who is not in the room's chain.
+        forged.sender_chain = vec![room.member(&room.alice, &stranger)];
+        assert!(forged.verify(&params).is_err());
+
+        // Addressed to Bob but offered to Alice's inbox.
+        let alice_inbox = InboxParametersV1 {
+            recipient: room.alice.verifying_key(),
+            ..params
+        };
+        assert!(room.dm(1).verify(&alice_inbox).is_err());
+
+        let mut state = InboxStateV1::default();
+        assert!(state.merge(&params, inbox(vec![forged])).is_err());
+        assert!(state.is_empty());
+    }
+
+    #[test]
+    fn merge_is_order_independent_and_honours_purges() {
+        let room = Room::new();
+        let params = room.params();
+        let (first, second) = (room.dm(1), room.dm(2));
+
+        let mut a = InboxStateV1::default();
+        a.merge(&params, inbox(vec![first.clone()])).unwrap();
+        a.merge(&params, inbox(vec![second.clone()])).unwrap();
+        let mut b = InboxStateV1::default();
+        b.merge(&params, inbox(vec![second, first.clone()]))
+            .unwrap();
+        assert_eq!(a, b);
+        assert!(a.verify(&params).is_ok());
+
+        let purges = advance_recipient_purges(
+            &room.bob,
+            &room.owner.verifying_key(),
+            None,
+            [first.message.purge_token()],
+        )
+        .unwrap();
+        a.merge(
+            &params,
+            InboxStateV1 {
+                purges: Some(purges.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(a.messages.len(), 1);
+        // A stale peer re-offering the purged DM does not bring it back.
+        a.merge(&params, inbox(vec![first])).unwrap();
+        assert_eq!(a.messages.len(), 1);
+
+        // A newer envelope that drops the token is refused.
+        let mut shrunk = purges.clone();
+        shrunk.state.purged.clear();
+        shrunk.state.version = 2;
+        assert!(a
+            .merge(
+                &params,
+                InboxStateV1 {
+                    purges: Some(shrunk),
+                    ..Default::default()
+                },
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn per_sender_cap_keeps_the_newest_and_delta_respects_it() {
+        let room = Room::new();
+        let params = room.params();
+        let over = (0..MAX_DM_MESSAGES_PER_PAIR as u64 + 3).map(|t| room.dm(t));
+
+        let mut full = InboxStateV1::default();
+        full.merge(&params, inbox(over.collect())).unwrap();
+        assert_eq!(full.messages.len(), MAX_DM_MESSAGES_PER_PAIR);
+        assert_eq!(full.messages[0].message.message.timestamp, 3);
+        assert!(full.verify(&params).is_ok());
+
+        // An older DM the full side would drop is never offered to it.
+        let mut older = InboxStateV1::default();
+        older.merge(&params, inbox(vec![room.dm(0)])).unwrap();
+        assert!(older.delta(&full.summarize()).is_empty());
+        assert!(full.delta(&full.summarize()).is_empty());
+        assert_eq!(
+            full.delta(&InboxSummaryV1::default()).messages.len(),
+            MAX_DM_MESSAGES_PER_PAIR
+        );
+    }
+
+    #[test]
+    fn a_flood_through_invited_identities_evicts_only_its_own_subtree() {
+        let room = Room::new();
+        let params = room.params();
+        let unread: Vec<_> = (0..5).map(|t| room.dm(t)).collect();
+
+        // Mallory invites enough identities to fill the inbox on their own,
+        // each staying under the per-sender cap, all newer than Alice's DMs.
+        let mallory = SigningKey::from_bytes(&[5; 32]);
+        let mallory_member = room.member(&mallory, &room.owner);
+        let sybils = MAX_INBOX_MESSAGES / MAX_DM_MESSAGES_PER_PAIR + 1;
+        let flood: Vec<_> = (0..sybils)
+            .flat_map(|i| {
+                let sybil = SigningKey::from_bytes(&[10 + i as u8; 32]);
+                let chain = vec![room.member(&sybil, &mallory), mallory_member.clone()];
+                (0..MAX_DM_MESSAGES_PER_PAIR as u64)
+                    .map(|t| room.dm_from(&sybil, chain.clone(), 100 + t))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut state = InboxStateV1::default();
+        state.merge(&params, inbox(unread.clone())).unwrap();
+        state.merge(&params, inbox(flood)).unwrap();
+        assert_eq!(state.messages.len(), MAX_INBOX_MESSAGES);
+        assert!(unread.iter().all(|dm| state.messages.contains(dm)));
+        assert!(state.verify(&params).is_ok());
+    }
+
+    #[test]
+    fn purges_past_the_cap_compact_behind_a_horizon() {
+        let room = Room::new();
+        let params = room.params();
+        let mut state = inbox((1..=5).map(|t| room.dm(t)).collect());
+
+        // An envelope already at the cap, full of tokens for long-gone DMs.
+        let full = advance_recipient_purges(
+            &room.bob,
+            &room.owner.verifying_key(),
+            None,
+            (0..MAX_PURGED_TOMBSTONES_PER_RECIPIENT as u32).map(|i| {
+                let mut token = [0; 16];
+                token[..4].copy_from_slice(&i.to_le_bytes());
+                PurgeToken(token)
+            }),
+        )
+        .unwrap();
+        state
+            .merge(
+                &params,
+                InboxStateV1 {
+                    purges: Some(full.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(advance_recipient_purges(
+            &room.bob,
+            &room.owner.verifying_key(),
+            Some(&full),
+            [room.dm(1).message.purge_token()],
+        )
+        .is_err());
+
+        // Purging the two oldest moves the horizon up to the oldest kept DM.
+        let compacted = state
+            .advance_purges(
+                &room.bob,
+                &room.owner.verifying_key(),
+                [room.dm(1), room.dm(2)].map(|dm| dm.message.purge_token()),
+            )
+            .unwrap();
+        assert_eq!(compacted.state.purged_before, 3);
+        assert!(compacted.state.purged.is_empty());
+        state
+            .merge(
+                &params,
+                InboxStateV1 {
+                    purges: Some(compacted.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(state.messages.len(), 3);
+        assert!(state.verify(&params).is_ok());
+        state.merge(&params, inbox(vec![room.dm(1)])).unwrap();
+        assert_eq!(state.messages.len(), 3);
+
+        // Below the cap tokens are simply added, and the horizon never drops.
+        let next = state
+            .advance_purges(
+                &room.bob,
+                &room.owner.verifying_key(),
+                [room.dm(4).message.purge_token()],
+            )
+            .unwrap();
+        assert_eq!(next.state.purged_before, 3);
+        assert_eq!(next.state.purged, vec![room.dm(4).message.purge_token()]);
+        let mut lowered = compacted;
+        lowered.state.purged_before = 0;
+        lowered.state.version += 1;
+        assert!(state
+            .merge(
+                &params,
+                InboxStateV1 {
+                    purges: Some(lowered),
+                    ..Default::default()
+                },
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn group_dms_reach_participants_only_and_purge_per_inbox() {
+        let room = Room::new();
+        let params = room.params();
+        let carol = SigningKey::from_bytes(&[4; 32]);
+        let group = InboxGroupMessageV1 {
+            sender_chain: vec![room.member(&room.alice, &room.owner)],
+            message: crate::group_dm::sign_group_direct_message(
+                &room.alice,
+                &room.owner.verifying_key(),
+                crate::group_dm::GroupDirectMessage {
+                    sender: room.alice.verifying_key().into(),
+                    participants: crate::group_dm::participant_set([
+                        room.alice.verifying_key().into(),
+                        room.bob.verifying_key().into(),
+                        carol.verifying_key().into(),
+                    ]),
+                    timestamp: 7,
+                    wrapped_keys: vec![vec![0; crate::group_dm::WRAPPED_KEY_BYTES]; 3],
+                    nonce: [0; 12],
+                    ciphertext: vec![7; 8],
+                },
+            )
+            .unwrap(),
+        };
+        let offer = || InboxStateV1 {
+            group_messages: vec![group.clone()],
+            ..Default::default()
+        };
+
+        // Bob and the sender hold it; a non-participant's inbox refuses it.
+        let mut bob = InboxStateV1::default();
+        bob.merge(&params, offer()).unwrap();
+        assert_eq!(bob.group_messages.len(), 1);
+        assert!(bob.verify(&params).is_ok());
+        let mut alice = InboxStateV1::default();
+        let alice_params = InboxParametersV1 {
+            recipient: room.alice.verifying_key(),
+            ..params
+        };
+        alice.merge(&alice_params, offer()).unwrap();
+        let outsider = InboxParametersV1 {
+            recipient: SigningKey::from_bytes(&[9; 32]).verifying_key(),
+            ..params
+        };
+        assert!(InboxStateV1::default().merge(&outsider, offer()).is_err());
+        assert!(bob.delta(&bob.summarize()).is_empty());
+        assert_eq!(
+            bob.tokens_from(&HashSet::from([room.alice.verifying_key().into()])),
+            vec![group.message.purge_token()]
+        );
+        assert!(bob.tokens_from(&HashSet::from([room.bob_id()])).is_empty());
+
+        // Bob purging it leaves Alice's copy alone.
+        let purges = advance_recipient_purges(
+            &room.bob,
+            &room.owner.verifying_key(),
+            None,
+            [group.message.purge_token()],
+        )
+        .unwrap();
+        bob.merge(
+            &params,
+            InboxStateV1 {
+                purges: Some(purges),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        bob.merge(&params, offer()).unwrap();
+        assert!(bob.group_messages.is_empty());
+        assert_eq!(alice.group_messages.len(), 1);
+    }
+
+    #[test]
+    fn receipts_come_from_members_and_only_move_forward() {
+        let room = Room::new();
+        // Alice's inbox, where Bob reports on her DMs.
+        let params = InboxParametersV1 {
+            room_owner: room.owner.verifying_key(),
+            recipient: room.alice.verifying_key(),
+        };
+        let alice_id = MemberId::from(&room.alice.verifying_key());
+        let bob_chain = vec![room.member(&room.bob, &room.owner)];
+        let receipts = |previous: Option<&AuthorizedDmReceipts>, delivered, read| InboxStateV1 {
+            receipts: vec![InboxReceiptsV1 {
+                reader_chain: bob_chain.clone(),
+                receipts: advance_dm_receipts(
+                    &room.bob,
+                    alice_id,
+                    &room.owner.verifying_key(),
+                    previous.map(|p| &p.state),
+                    delivered,
+                    read,
+                )
+                .unwrap()
+                .unwrap(),
+            }],
+            ..Default::default()
+        };
+
+        let mut inbox = InboxStateV1::default();
+        let delivered = receipts(None, 10, 0);
+        inbox.merge(&params, delivered.clone()).unwrap();
+        let read = receipts(inbox.receipts_from(room.bob_id()), 0, 10);
+        inbox.merge(&params, read.clone()).unwrap();
+        // A stale copy arriving late changes nothing.
+        inbox.merge(&params, delivered.clone()).unwrap();
+        assert_eq!(inbox.receipts.len(), 1);
+        assert_eq!(
+            inbox.receipts_from(room.bob_id()).unwrap().status_of(10),
+            Some(DmReceiptStatus::Read)
+        );
+        inbox.verify(&params).unwrap();
+
+        // A peer that already has the read envelope is offered nothing.
+        let mut peer = InboxStateV1::default();
+        peer.merge(&params, read.clone()).unwrap();
+        assert!(inbox.delta(&peer.summarize()).receipts.is_empty());
+        assert_eq!(inbox.delta(&InboxSummaryV1::default()).receipts.len(), 1);
+
+        // Not about this inbox's owner, or from a stranger: refused.
+        assert!(inbox.clone().merge(&room.params(), read.clone()).is_err());
+        let mut forged = read;
+        forged.receipts[0].reader_chain = vec![room.member(&room.bob, &room.alice)];
+        assert!(inbox.merge(&params, forged).is_err());
+    }
+
+    #[test]
+    fn state_and_summary_roundtrip_through_bytes() {
+        let room = Room::new();
+        let state = inbox(vec![room.dm(5)]);
+        assert_eq!(InboxStateV1::from_bytes(&state.to_bytes()).unwrap(), state);
+        assert!(InboxStateV1::from_bytes(&[]).unwrap().is_empty());
+        let summary = state.summarize();
+        assert_eq!(
+            InboxSummaryV1::from_bytes(&summary.to_bytes()).unwrap(),
+            summary
+        );
+        let params = room.params();
+        assert_eq!(
+            InboxParametersV1::from_bytes(&params.to_bytes()).unwrap(),
+            params
+        );
+    }
+}
@@ -1,7 +1,28 @@
+/// Local append-only message archive. Client-only, gated like `mention`.
+#[cfg(feature = "archive")]
+pub mod archive;
+/// Content-addressed attachment blobs stored in their own contracts.
+pub mod blob;
 pub mod chat_delegate;
+/// Confusable-name (impersonation) detection. Client-only, gated on `names`.
+#[cfg(feature = "names")]
+pub mod confusable;
 pub mod crypto_values;
+/// Display-time nickname sanitiser. Client-only, gated on `names`.
+#[cfg(feature = "names")]
+pub mod display_name;
+/// Forward-secret DM sessions. Needs a CSPRNG, so gated on
+/// `ecies-randomized` like the DM send path.
+#[cfg(feature = "ecies-randomized")]
+pub mod dm_ratchet;
+/// Reader-signed DM delivery and read receipts.
+pub mod dm_receipts;
 #[cfg(feature = "ecies")]
 pub mod ecies;
+/// Group direct messages sealed once per participant.
+pub mod group_dm;
+/// Per-member DM inbox contracts.
+pub mod inbox;
 pub mod key_derivation;
 /// `@mention` codec (freenet/river @mentions feature). Gated on the `mentions`
 /// feature so the room-contract / chat-delegate WASM builds (which do not
@@ -14,7 +35,13 @@ pub mod mention;
 /// do not enable it) keep byte-identical WASM and stable keys.
 #[cfg(feature = "migration")]
 pub mod migration;
+/// Generated default nicknames. Client-only, gated on `names`.
+#[cfg(feature = "names")]
+pub mod nickname;
 pub mod room_state;
+/// Full-text search over room history. Client-only, gated like `mention`.
+#[cfg(feature = "search")]
+pub mod search;
 pub mod util;
 pub mod web_container;
 
@@ -177,7 +177,7 @@ pub fn generate_default_nickname(vk: &VerifyingKey) -> String {
 
 /// Whether `name` is one of the 10,000 handles this module can assign.
 ///
-/// Used by [`crate::util::confusable`] to tell an *assigned* name from a
+/// Used by [`crate::confusable`] to tell an *assigned* name from a
 /// *chosen* one. A member who never typed a nickname is wearing a handle River
 /// derived from their key, so two members holding neighbouring assignments
 /// (`Amber Worm` / `Ember Worm`) is a collision River created, not an imitation
@@ -202,7 +202,7 @@ pub fn is_generated_handle(name: &str) -> bool {
 ///
 /// **A guard must be at least as wide as the thing it guards.**
 /// [`is_generated_handle`] compares exact strings; the impersonation matcher it
-/// used to gate compares [`crate::util::confusable::comparison_folds`]. Every
+/// used to gate compares [`crate::confusable::comparison_folds`]. Every
 /// one of `amber worm`, `AMBER WORM`, `AmberWorm`, `Amber\u{3000}Worm`,
 /// `Arnber Worm`, `Amber W0rm` and `Amber Worrn` is "not a generated handle" to
 /// `==`, and every one of them is a tier-1 match for the member River assigned
@@ -229,10 +229,7 @@ pub fn is_generated_handle(name: &str) -> bool {
 /// rejected.
 pub fn folds_to_generated_handle(name: &str) -> bool {
     let pools = handle_skeletons();
-    for (fold_index, candidate) in crate::util::confusable::comparison_folds(name)
-        .iter()
-        .enumerate()
-    {
+    for (fold_index, candidate) in crate::confusable::comparison_folds(name).iter().enumerate() {
         if candidate.is_empty() {
             continue;
         }
@@ -254,7 +251,7 @@ pub fn folds_to_generated_handle(name: &str) -> bool {
 /// The folded word pools backing [`folds_to_generated_handle`], built once.
 ///
 /// Indexed by fold: `[0]` is the visual fold, `[1]` the case-insensitive one,
-/// matching the order [`crate::util::confusable::comparison_folds`] returns.
+/// matching the order [`crate::confusable::comparison_folds`] returns.
 struct HandleSkeletons {
     first: [std::collections::HashSet<String>; 2],
     last: [std::collections::HashSet<String>; 2],
@@ -269,7 +266,7 @@ fn handle_skeletons() -> &'static HandleSkeletons {
                 std::collections::HashSet::new(),
             ];
             for word in words {
-                for (i, folded) in crate::util::confusable::comparison_folds(word)
+                for (i, folded) in crate::confusable::comparison_folds(word)
                     .into_iter()
                     .enumerate()
                 {
@@ -433,8 +430,7 @@ mod tests {
         // room's `max_nickname_size`, so a generated default that does not
         // fit would make the join / self-heal UPDATE fail outright. Check
         // against the real default rather than a hardcoded copy of it.
-        let limit =
-            river_core::room_state::configuration::Configuration::default().max_nickname_size;
+        let limit = crate::room_state::configuration::Configuration::default().max_nickname_size;
         for handle in all_handles() {
             assert!(
                 handle.len() <= limit,
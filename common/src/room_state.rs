@@ -1,24 +1,42 @@
 pub mod ban;
+pub mod ban_lapse;
+pub mod ban_revocation;
 pub mod configuration;
 pub mod content;
+pub mod departure;
 pub mod direct_messages;
 pub mod dm_body;
 pub mod identity;
+pub mod invite;
+pub mod invite_use;
+pub mod join_request;
 pub mod member;
 pub mod member_info;
 pub mod message;
+pub mod mute;
 pub mod privacy;
 pub mod secret;
+pub mod signed_record;
+#[cfg(test)]
+mod test_room;
 pub mod upgrade;
 pub mod version;
 
 use crate::room_state::ban::BansV1;
+use crate::room_state::ban_lapse::BanLapsesV1;
+use crate::room_state::ban_revocation::BanRevocationsV1;
 use crate::room_state::configuration::AuthorizedConfigurationV1;
+use crate::room_state::departure::DeparturesV1;
 use crate::room_state::direct_messages::DirectMessagesV1;
+use crate::room_state::invite::InvitesV1;
+use crate::room_state::invite_use::InviteUsesV1;
+use crate::room_state::join_request::JoinRequestsV1;
 use crate::room_state::member::{MemberId, MembersV1};
 use crate::room_state::member_info::MemberInfoV1;
 use crate::room_state::message::MessagesV1;
+use crate::room_state::mute::MutesV1;
 use crate::room_state::secret::RoomSecretsV1;
+use crate::room_state::signed_record::Overflow;
 use crate::room_state::upgrade::OptionalUpgradeV1;
 use crate::room_state::version::StateVersion;
 use ed25519_dalek::VerifyingKey;
@@ -30,7 +48,8 @@ use std::collections::HashSet;
 #[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
 pub struct ChatRoomStateV1 {
     // WARNING: The order of these fields is important for the purposes of the #[composable] macro.
-    // `configuration` must be first, followed by `bans`, `members`, `member_info`, `secrets`,
+    // `configuration` must be first, followed by `bans`, `ban_revocations`, `ban_lapses`,
+    // `invites`, `invite_uses`, `members`, `departures`, `member_info`, `mutes`, `join_requests`, `secrets`,
     // and then `recent_messages`.
     // This is due to interdependencies between the fields and the order in which they must be applied in
     // the `apply_delta` function. DO NOT reorder fields without fully understanding the implications.
@@ -41,12 +60,58 @@ pub struct ChatRoomStateV1 {
     /// members list and will be removed from it ifc necessary.
     pub bans: BansV1,
 
+    /// Signed revocations of bans. Must come before `members` so a member
+    /// re-invited after an unban is not removed by the revoked ban while the
+    /// delta is applied. `#[serde(default)]` keeps older states compatible.
+    #[serde(default)]
+    pub ban_revocations: BanRevocationsV1,
+
+    /// Temporary bans that have run out, each with the message that ended it,
+    /// so the ban stays lifted once that message is trimmed. Must come before
+    /// `members` for the same reason as `ban_revocations`.
+    /// `#[serde(default)]` keeps older states compatible.
+    #[serde(default)]
+    pub ban_lapses: BanLapsesV1,
+
+    /// Signed invitation policies (expiry, revocation). Must come before
+    /// `members` so an invitee whose invitation was revoked is refused as
+    /// they first appear. `#[serde(default)]` keeps older states compatible.
+    #[serde(default)]
+    pub invites: InvitesV1,
+
+    /// Members admitted through a limited invitation, each with the record
+    /// and their join event, so the expiry and use limit keep holding once
+    /// the join event is trimmed. Must come before `members`, which reads the
+    /// records they carry. `#[serde(default)]` keeps older states compatible.
+    #[serde(default)]
+    pub invite_uses: InviteUsesV1,
+
     /// The members in the chat room along with who invited them
     pub members: MembersV1,
 
+    /// Members who left, kept so their messages and nickname still verify.
+    /// Must come after `members`, whose keys check the entries, and before
+    /// `member_info` and `recent_messages`, which read the leavers' keys.
+    /// `#[serde(default)]` keeps older states compatible.
+    #[serde(default)]
+    pub departures: DeparturesV1,
+
     /// Metadata about members like their nickname, can be updated by members themselves.
     pub member_info: MemberInfoV1,
 
+    /// Signed mutes and unmutes. Must come after `member_info`, which decides
+    /// who may mute whom, and before `recent_messages`, which drops messages
+    /// muted members send. `#[serde(default)]` keeps older states compatible.
+    #[serde(default)]
+    pub mutes: MutesV1,
+
+    /// Join requests from people without an invitation, and members'
+    /// approvals and denials. Must come after `member_info` so a decision by a
+    /// member admitted in the same delta has their key available.
+    /// `#[serde(default)]` keeps older states compatible.
+    #[serde(default)]
+    pub join_requests: JoinRequestsV1,
+
     /// Secret distribution for private rooms. Must come before recent_messages so message
     /// validation can check secret version consistency.
     pub secrets: RoomSecretsV1,
@@ -81,8 +146,10 @@ impl ChatRoomStateV1 {
     /// or are in the invite chain of someone who qualifies. The owner is
     /// never in the members list (they're implicit via parameters).
     ///
-    /// Bans are only removed if the banner was themselves BANNED (orphaned ban).
-    /// If the banner was merely pruned for inactivity, their bans persist.
+    /// Bans are only removed if the banner was themselves BANNED (orphaned ban),
+    /// or if an effective ban revocation names them or they have expired (step
+    /// 0-revoke, which records a lapse so they stay expired). If the
+    /// banner was merely pruned for inactivity, their bans persist.
     ///
     /// IDEMPOTENCE / CONVERGENCE INVARIANT: this function MUST be idempotent
     /// (`cleanup(S) == cleanup(cleanup(S))`) and a pure function of the converged
@@ -101,6 +168,77 @@ impl ChatRoomStateV1 {
     pub fn post_apply_cleanup(&mut self, parameters: &ChatRoomParametersV1) -> Result<(), String> {
         let owner_id = MemberId::from(&parameters.owner);
 
+        // 0-revoke. Cap the stored revocations at `max_user_bans` (oldest
+        //     evicted first), then drop every ban an effective revocation names
+        //     (see `BanRevocationsV1::revocation_is_effective`) and every
+        //     temporary ban that has lapsed (`BansV1::ban_has_expired`, which
+        //     reads message timestamps, never the wall clock). An expired ban
+        //     first gets a lapse recorded (`BanLapsesV1::record`), which keeps
+        //     it lifted after its witness message is trimmed. This runs
+        //     BEFORE the ban cap so lifted bans neither take cap slots nor
+        //     exempt their banner at step 2, and before enforcement so the
+        //     unbanned member is no longer removed. Bans and revocations only
+        //     shrink here, so a second pass finds nothing more to remove: the
+        //     lifted ban is gone, a surviving revocation keeps its revoker
+        //     (step 2 exemption) and hence its signature check (step 5a), and
+        //     the later steps only ever remove messages, which cannot make
+        //     another ban expire. Lapses are settled here and only swept at
+        //     step 5a, whose predicate also gates their signers' exemption.
+        //     Like the ban cap below, effectiveness reads the intermediate
+        //     member set `apply_delta` left, so anti-entropy, not a single
+        //     delta, is what makes peers byte-equal.
+        //     `revoked_at` is signed by the revoker and can be future-dated, so
+        //     a member can flood revocations that outlive older ones; an evicted
+        //     revocation only matters if some peer still re-offers its ban.
+        signed_record::cap(
+            &mut self.ban_revocations.0,
+            self.configuration.configuration.max_user_bans,
+            Overflow::EvictOldest,
+        );
+        {
+            let members_by_id = self.members.members_by_member_id();
+            self.ban_lapses.record(
+                &self.bans,
+                &self.recent_messages,
+                &members_by_id,
+                &self.member_info,
+                owner_id,
+                &parameters.owner,
+                self.configuration.configuration.max_user_bans,
+            );
+            let revocations = &self.ban_revocations;
+            let lapses = &self.ban_lapses;
+            let member_info = &self.member_info;
+            let messages = &self.recent_messages;
+            self.bans.0.retain(|ban| {
+                let revoked = revocations.0.iter().any(|revocation| {
+                    BanRevocationsV1::revocation_is_effective(
+                        revocation,
+                        ban,
+                        &members_by_id,
+                        member_info,
+                        owner_id,
+                        &parameters.owner,
+                    )
+                });
+                !revoked
+                    && !lapses.lifts(
+                        ban,
+                        &members_by_id,
+                        member_info,
+                        owner_id,
+                        &parameters.owner,
+                    )
+                    && !BansV1::ban_has_expired(
+                        ban,
+                        messages,
+                        &members_by_id,
+                        member_info,
+                        owner_id,
+                    )
+            });
+        }
+
         // 0-cap. Enforce `max_user_bans` FIRST — BEFORE ban enforcement (step 0)
         //     and the banner inactivity-prune exemption (step 2) — so both read
         //     the FINAL surviving (post-cap) ban set (#411 round 7 / Codex P1
@@ -191,7 +329,8 @@ impl ChatRoomStateV1 {
         // members whose deputy was revoked (the deputizer removed them from
         // `MemberInfo.deputies` at a higher version). Because the removal set
         // is a pure function of the converged (members + deputies + bans)
-        // state, and bans stay an add-only CRDT (never pruned here), every peer
+        // state, and bans only leave through the cap, a revocation (step
+        // 0-revoke) or the signature sweep (step 5), every peer
         // converges to the same member set regardless of delta order. Kept in
         // post_apply_cleanup (NOT verify) so verify stays stable across
         // ban/deputy changes — mirrors the DM ban-sweep precedent.
@@ -202,6 +341,112 @@ impl ChatRoomStateV1 {
             .members
             .retain(|m| !enforced_banned_ids.contains(&m.member.id()));
 
+        // 0-invite. Settle the invitation policies against the post-ban
+        //     member set (`InvitesV1::settled`: the newest effective record
+        //     per invitee judges the members in through its invitation by
+        //     their join events, used records move into the uses they
+        //     produce, the rest are capped at `max_members`), then remove
+        //     every member a record refuses, with the members they invited.
+        //     Records and uses whose signers went with them stop being
+        //     effective and are dropped in the same pass. Idempotent: a second
+        //     pass finds the refused members absent and reads the same join
+        //     events from the stored uses, so it settles to the same records
+        //     and uses and refuses no one new.
+        {
+            let members_by_id = self.members.members_by_member_id();
+            let settled = self.invites.settled(
+                &self.invite_uses,
+                &members_by_id,
+                &self.member_info,
+                &self.recent_messages,
+                owner_id,
+                &parameters.owner,
+                self.configuration.configuration.max_members,
+            );
+            self.invites.0 = settled.records;
+            self.invite_uses.0 = settled.uses;
+            if !settled.refused.is_empty() {
+                let mut removed = settled.refused.clone();
+                for member in &settled.refused {
+                    removed.extend(self.members.get_downstream_members(*member));
+                }
+                self.members
+                    .members
+                    .retain(|m| !removed.contains(&m.member.id()));
+                let members_by_id = self.members.members_by_member_id();
+                let member_info = &self.member_info;
+                self.invites.0.retain(|record| {
+                    InvitesV1::record_is_effective(
+                        record,
+                        &members_by_id,
+                        member_info,
+                        owner_id,
+                        &parameters.owner,
+                    )
+                });
+                self.invite_uses.0.retain(|invite_use| {
+                    InviteUsesV1::use_is_valid(
+                        invite_use,
+                        &members_by_id,
+                        owner_id,
+                        &parameters.owner,
+                    ) && InvitesV1::record_is_effective(
+                        &invite_use.invite,
+                        &members_by_id,
+                        member_info,
+                        owner_id,
+                        &parameters.owner,
+                    )
+                });
+            }
+        }
+
+        // 0-join. Settle the join requests against the post-enforcement
+        //     member set (`JoinRequestsV1::settled`): requests and decisions
+        //     for requesters who are now members are dropped as used, the
+        //     newest effective decision per requester is kept (capped at
+        //     `max_members`), and the newest request per requester survives
+        //     only while they are not banned or denied (capped at
+        //     `MAX_PENDING_JOIN_REQUESTS`). Idempotent: nothing later in this
+        //     function adds members or bans, and every kept decider is kept
+        //     at step 2, so a second pass settles to the same records.
+        {
+            let members_by_id = self.members.members_by_member_id();
+            let banned: HashSet<MemberId> =
+                self.bans.0.iter().map(|ban| ban.ban.banned_user).collect();
+            self.join_requests = self.join_requests.settled(
+                &members_by_id,
+                &banned,
+                owner_id,
+                &parameters.owner,
+                self.configuration.configuration.max_members,
+            );
+        }
+
+        // 0-mute. Settle the mute records against the post-enforcement member
+        //     set (`MutesV1::settled`: the newest effective record per member,
+        //     capped at `max_members`), then drop every message a muted member
+        //     sent inside their mute's window. `MessagesV1::apply_delta`
+        //     already refuses those, but a mute can arrive after the messages
+        //     it covers. Idempotent: the windows depend on the records alone,
+        //     not on the messages removed here.
+        let mute_windows = {
+            let members_by_id = self.members.members_by_member_id();
+            self.mutes.0 = self.mutes.settled(
+                &members_by_id,
+                &self.member_info,
+                owner_id,
+                &parameters.owner,
+                self.configuration.configuration.max_members,
+            );
+            MutesV1::windows(&self.mutes.0)
+        };
+        if !mute_windows.is_empty() {
+            self.recent_messages
+                .messages
+                .retain(|m| !MutesV1::silences(&mute_windows, m));
+        }
+
         // 1. Collect message author IDs + DM participants + secret recipients.
         //
         // Secret recipients (i.e. members for whom the owner has issued an
@@ -224,13 +469,33 @@ impl ChatRoomStateV1 {
         // invite chain ancestor set exempt from cleanup forever,
         // defeating the prune. See IMPORTANT item #5 on PR #272
         // review round 2.
+        //
+        // Members who LEFT (latest message is a leave event) get none of these
+        // three exemptions, so they are pruned exactly like an inactive member:
+        // still kept if an active invitee's chain or a surviving ban of theirs
+        // needs them (the two exemptions below), removed otherwise. Dropping
+        // the secret-recipient exemption matters for private rooms — without
+        // it a leaver who holds the current secret would stay listed forever,
+        // and the owner's delegate would never see the member-set change that
+        // triggers rotation. A removed leaver's entry moves to `departures`
+        // (step 3a), so what they said stays. Idempotent: a kept leaver keeps
+        // their leave event, a removed one is held only by their departure,
+        // which step 2 and step 3a judge by the same predicate.
+        let departed = self.recent_messages.departed_members();
+        let banned: HashSet<MemberId> = self.bans.0.iter().map(|ban| ban.ban.banned_user).collect();
         let message_authors: HashSet<MemberId> = self
             .recent_messages
             .messages
             .iter()
             .map(|m| m.message.author)
+            .filter(|author| !departed.contains(author))
+            .collect();
+        let dm_participants: HashSet<MemberId> = self
+            .direct_messages
+            .active_participants()
+            .into_iter()
+            .filter(|participant| !departed.contains(participant))
             .collect();
-        let dm_participants: HashSet<MemberId> = self.direct_messages.active_participants();
         let current_secret_version = self.secrets.current_version;
         let secret_recipients: HashSet<MemberId> = self
             .secrets
@@ -238,6 +503,7 @@ impl ChatRoomStateV1 {
             .iter()
             .filter(|s| s.secret.secret_version == current_secret_version)
             .map(|s| s.secret.member_id)
+            .filter(|recipient| !departed.contains(recipient))
             .collect();
 
         // 2. Compute required members: authors + DM participants + secret
@@ -307,30 +573,209 @@ impl ChatRoomStateV1 {
                 }
             }
 
-            // Walk invite chains upward, adding all ancestors (stop at owner)
+            // Likewise a member who issued a surviving ban revocation is exempt,
+            // gated on the same predicate as the step-5a sweep, so an inactive
+            // moderator's unban keeps holding against peers that still offer
+            // the ban.
+            for revocation in &self.ban_revocations.0 {
+                let revoker = revocation.revoked_by;
+                if revoker != owner_id
+                    && BanRevocationsV1::signature_matches_current_key(
+                        revocation,
+                        &members_by_id,
+                        owner_id,
+                        &parameters.owner,
+                    )
+                {
+                    required_ids.insert(revoker);
+                }
+            }
+
+            // Likewise the banner and witness of a surviving lapse, so an
+            // expired ban stays expired against peers that still offer it.
+            for lapse in &self.ban_lapses.0 {
+                if BanLapsesV1::signatures_match_current_keys(
+                    lapse,
+                    &members_by_id,
+                    owner_id,
+                    &parameters.owner,
+                ) {
+                    for signer in [lapse.ban.banned_by, lapse.witness.message.author] {
+                        if signer != owner_id {
+                            required_ids.insert(signer);
+                        }
+                    }
+                }
+            }
+
+            // A muted member is kept in the room for as long as the mute
+            // lasts: muting is not meant to prune them once their last message
+            // ages out, nor to be shed by leaving and rejoining. The issuer of
+            // every surviving record, lapsed mutes included, is kept too, so
+            // its signature can still be checked and it keeps holding against
+            // peers re-offering an older record. Whether a mute has lapsed
+            // reads messages, which later steps only remove, so a member
+            // pruned here is not exempted on a second pass.
+            for record in &self.mutes.0 {
+                if record.mute.action.is_mute()
+                    && members_by_id.contains_key(&record.mute.muted_user)
+                    && !MutesV1::mute_has_expired(
+                        record,
+                        &self.recent_messages,
+                        &members_by_id,
+                        &self.member_info,
+                        owner_id,
+                    )
+                {
+                    required_ids.insert(record.mute.muted_user);
+                }
+                if record.muted_by != owner_id && members_by_id.contains_key(&record.muted_by) {
+                    required_ids.insert(record.muted_by);
+                }
+            }
+
+            // The issuer of every outstanding invite record is kept, so the
+            // record stays effective and its link stays closed or limited.
+            // The records are capped at `max_members`, which bounds this.
+            for record in &self.invites.0 {
+                if record.issued_by != owner_id && members_by_id.contains_key(&record.issued_by) {
+                    required_ids.insert(record.issued_by);
+                }
+            }
+
+            // The decider of every outstanding join decision is kept, so an
+            // approval stays valid until the requester uses it and a denial
+            // keeps holding. Bounded by the `max_members` cap on decisions.
+            for record in &self.join_requests.decisions {
+                if record.decided_by != owner_id && members_by_id.contains_key(&record.decided_by) {
+                    required_ids.insert(record.decided_by);
+                }
+            }
+
+            // The inviter of every leaver whose departure will hold is kept,
+            // so the departure's signature can still be checked and the
+            // leaver's messages stay. Gated on the same predicate as the
+            // step-3a sweep; a leaver who is still a member is judged by the
+            // entry step 3a would record for them.
+            for member_id in &departed {
+                let entry = members_by_id.get(member_id).copied().or_else(|| {
+                    self.departures
+                        .0
+                        .iter()
+                        .find(|entry| entry.member.id() == *member_id)
+                });
+                if let Some(entry) = entry {
+                    let inviter = entry.member.invited_by;
+                    if inviter != owner_id
+                        && DeparturesV1::departure_is_valid(
+                            entry,
+                            &members_by_id,
+                            &departed,
+                            &banned,
+                            owner_id,
+                            &parameters.owner,
+                        )
+                    {
+                        required_ids.insert(inviter);
+                    }
+                }
+            }
+
+            // Walk invite chains upward, adding all ancestors (stop at owner).
+            //
+            // The issuer of a record carried by an invite use is kept while
+            // the use's member is, gated on the same predicate as the step-5b
+            // sweep, so the record keeps limiting the invitation once its
+            // issuer goes quiet. Whether the member is kept is only known once
+            // the chains are walked, and keeping an issuer can keep another
+            // use's member, so this repeats until nothing more is added.
             let mut to_process: Vec<MemberId> = required_ids.iter().cloned().collect();
-            while let Some(member_id) = to_process.pop() {
-                if let Some(member) = members_by_id.get(&member_id) {
-                    let inviter_id = member.member.invited_by;
-                    if inviter_id != owner_id && !required_ids.contains(&inviter_id) {
-                        required_ids.insert(inviter_id);
-                        to_process.push(inviter_id);
+            loop {
+                while let Some(member_id) = to_process.pop() {
+                    if let Some(member) = members_by_id.get(&member_id) {
+                        let inviter_id = member.member.invited_by;
+                        if inviter_id != owner_id && !required_ids.contains(&inviter_id) {
+                            required_ids.insert(inviter_id);
+                            to_process.push(inviter_id);
+                        }
                     }
                 }
+                for invite_use in &self.invite_uses.0 {
+                    let issuer = invite_use.invite.issued_by;
+                    if issuer != owner_id
+                        && !required_ids.contains(&issuer)
+                        && required_ids.contains(&invite_use.member())
+                        && InviteUsesV1::use_is_valid(
+                            invite_use,
+                            &members_by_id,
+                            owner_id,
+                            &parameters.owner,
+                        )
+                    {
+                        required_ids.insert(issuer);
+                        to_process.push(issuer);
+                    }
+                }
+                if to_process.is_empty() {
+                    break;
+                }
             }
 
             required_ids
         };
 
         // 3. Prune members not in required set
-        self.members
-            .members
-            .retain(|m| required_ids.contains(&m.member.id()));
+        let mut pruned = Vec::new();
+        self.members.members.retain(|m| {
+            let keep = required_ids.contains(&m.member.id());
+            if !keep {
+                pruned.push(m.clone());
+            }
+            keep
+        });
+
+        // 3a. Record a departure for every leaver pruned above, then keep only
+        //     the departures that hold against the post-prune member set
+        //     (`DeparturesV1::departure_is_valid`): the leaver is not back,
+        //     not banned, their leave event is still their latest message and
+        //     their inviter's current key signs the entry.
+        let departed_ids: HashSet<MemberId> = {
+            let mut recorded: HashSet<MemberId> = self
+                .departures
+                .0
+                .iter()
+                .map(|entry| entry.member.id())
+                .collect();
+            for member in pruned {
+                if departed.contains(&member.member.id()) && recorded.insert(member.member.id()) {
+                    self.departures.0.push(member);
+                }
+            }
+            let members_by_id = self.members.members_by_member_id();
+            self.departures.0.retain(|entry| {
+                !members_by_id.contains_key(&entry.member.id())
+                    && DeparturesV1::departure_is_valid(
+                        entry,
+                        &members_by_id,
+                        &departed,
+                        &banned,
+                        owner_id,
+                        &parameters.owner,
+                    )
+            });
+            self.departures.0.sort_by_key(|entry| entry.member.id());
+            self.departures
+                .0
+                .iter()
+                .map(|entry| entry.member.id())
+                .collect()
+        };
 
-        // 4. Clean member_info for pruned members
+        // 4. Clean member_info for pruned members, keeping the departed
         self.member_info.member_info.retain(|info| {
             info.member_info.member_id == owner_id
                 || required_ids.contains(&info.member_info.member_id)
+                || departed_ids.contains(&info.member_info.member_id)
         });
 
         // 4a. Collapse duplicate member_info records to the single canonical
@@ -355,11 +800,13 @@ impl ChatRoomStateV1 {
         //     after the recent_messages field has been applied) would otherwise
         //     leave orphaned messages that fail `MessagesV1::verify`
         //     ("Message author not found"). Owner-authored messages are always
-        //     valid.
+        //     valid, and a departed member's are kept with their departure.
         let current_member_ids: HashSet<MemberId> =
             self.members.members.iter().map(|m| m.member.id()).collect();
         self.recent_messages.messages.retain(|m| {
-            m.message.author == owner_id || current_member_ids.contains(&m.message.author)
+            m.message.author == owner_id
+                || current_member_ids.contains(&m.message.author)
+                || departed_ids.contains(&m.message.author)
         });
 
         // Rebuild the PUBLIC `actions_state` cache now that removed authors'
@@ -401,6 +848,54 @@ impl ChatRoomStateV1 {
             )
         });
 
+        // 5a. Same sweep for ban revocations and lapses: keep one only while
+        //     its signatures verify against the signers' current keys.
+        self.ban_revocations.0.retain(|revocation| {
+            BanRevocationsV1::signature_matches_current_key(
+                revocation,
+                &members_by_id_for_ban_sweep,
+                owner_id,
+                &parameters.owner,
+            )
+        });
+
+        self.ban_lapses.0.retain(|lapse| {
+            BanLapsesV1::signatures_match_current_keys(
+                lapse,
+                &members_by_id_for_ban_sweep,
+                owner_id,
+                &parameters.owner,
+            )
+        });
+
+        // 5b. Drop mute records that stopped being effective in the prune:
+        //     only an unmute whose target was pruned can, since muted members
+        //     and every issuer were kept at step 2. Older records for that
+        //     member are already gone, so this never re-mutes anyone.
+        {
+            let members_by_id = self.members.members_by_member_id();
+            let member_info = &self.member_info;
+            self.mutes.0.retain(|record| {
+                MutesV1::record_is_effective(
+                    record,
+                    &members_by_id,
+                    member_info,
+                    owner_id,
+                    &parameters.owner,
+                )
+            });
+        }
+
+        // 5c. Drop invite uses whose member was pruned, and with them the
+        //     issuer's exemption, which step 2 gated on the same predicate.
+        //     The member's slot is free again from here on.
+        {
+            let members_by_id = self.members.members_by_member_id();
+            self.invite_uses.0.retain(|invite_use| {
+                InviteUsesV1::use_is_valid(invite_use, &members_by_id, owner_id, &parameters.owner)
+            });
+        }
+
         // (The `max_user_bans` cap runs at the TOP of this function now — step
         // "0-cap" — so ban enforcement and the banner exemption read the final
         // surviving ban set. This signature sweep only shrinks the set further,
@@ -587,6 +1082,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: std::time::SystemTime::now(),
                 banned_user: b_id,
+                reason: None,
+                duration: None,
             },
             a_id,
             &a_sk,
@@ -615,6 +1112,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: std::time::SystemTime::now() + std::time::Duration::from_secs(1),
                 banned_user: a_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -844,6 +1343,7 @@ mod tests {
             version: 0,
             preferred_nickname: SealedBytes::public("NewUser".to_string().into_bytes()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         let authorized_info = AuthorizedMemberInfo::new_with_member_key(member_info, &joiner_sk);
 
@@ -1033,6 +1533,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: c_id,
+                reason: None,
+                duration: None,
             },
             a_id,
             &a_sk,
@@ -1072,6 +1574,105 @@ mod tests {
         assert_eq!(state.bans.0[0].banned_by, a_id);
     }
 
+    /// A temporary ban removes its target until someone able to lift it posts
+    /// a message dated past the expiry; cleanup then drops the ban, the member
+    /// can rejoin, and a further cleanup pass changes nothing.
+    #[test]
+    fn temporary_ban_lapses_on_owner_message_and_allows_rejoin() {
+        let rng = &mut rand::thread_rng();
+        let owner_sk = SigningKey::generate(rng);
+        let owner_vk = owner_sk.verifying_key();
+        let owner_id = MemberId::from(&owner_vk);
+        let params = ChatRoomParametersV1 { owner: owner_vk };
+
+        let c_sk = SigningKey::generate(rng);
+        let c_vk = c_sk.verifying_key();
+        let c_id = MemberId::from(&c_vk);
+
+        let base = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        let member_c = AuthorizedMember::new(
+            Member {
+                owner_member_id: owner_id,
+                invited_by: owner_id,
+                member_vk: c_vk,
+            },
+            &owner_sk,
+        );
+        let message = |author: MemberId, sk: &SigningKey, secs: u64| {
+            AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: owner_id,
+                    author,
+                    time: base + std::time::Duration::from_secs(secs),
+                    content: RoomMessageBody::public("hi".to_string()),
+                },
+                sk,
+            )
+        };
+
+        let mut state = ChatRoomStateV1 {
+            configuration: AuthorizedConfigurationV1::new(
+                Configuration {
+                    max_members: 10,
+                    max_user_bans: 10,
+                    max_recent_messages: 100,
+                    ..Default::default()
+                },
+                &owner_sk,
+            ),
+            members: MembersV1 {
+                members: vec![member_c.clone()],
+            },
+            bans: BansV1(vec![AuthorizedUserBan::new(
+                UserBan {
+                    owner_member_id: owner_id,
+                    banned_at: base,
+                    banned_user: c_id,
+                    reason: None,
+                    duration: Some(std::time::Duration::from_secs(3600)),
+                },
+                owner_id,
+                &owner_sk,
+            )]),
+            recent_messages: MessagesV1 {
+                messages: vec![message(c_id, &c_sk, 0)],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        state.post_apply_cleanup(&params).unwrap();
+        assert!(state.members.members.is_empty(), "C is banned and removed");
+        assert_eq!(state.bans.0.len(), 1);
+
+        // The owner posts before the expiry: the ban holds.
+        state
+            .recent_messages
+            .messages
+            .push(message(owner_id, &owner_sk, 1800));
+        state.post_apply_cleanup(&params).unwrap();
+        assert_eq!(state.bans.0.len(), 1, "ban has not lapsed yet");
+
+        // The owner posts after the expiry: the ban lapses and C rejoins.
+        state
+            .recent_messages
+            .messages
+            .push(message(owner_id, &owner_sk, 7200));
+        state.members.members.push(member_c);
+        state
+            .recent_messages
+            .messages
+            .push(message(c_id, &c_sk, 7201));
+        state.post_apply_cleanup(&params).unwrap();
+        assert!(state.bans.0.is_empty(), "expired ban is dropped");
+        assert_eq!(state.members.members.len(), 1, "C may rejoin");
+        assert_eq!(state.members.members[0].member.id(), c_id);
+
+        let mut again = state.clone();
+        again.post_apply_cleanup(&params).unwrap();
+        assert_eq!(again, state, "cleanup is idempotent after expiry");
+    }
+
     /// #411 round 7 / Codex P1 #1: an over-cap ban that WILL be evicted by the
     /// `max_user_bans` cap must NOT one-shot-remove its target. If enforcement
     /// ran before the cap (the bug), the evicted ban would still have removed a
@@ -1123,6 +1724,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: base,
                 banned_user: t_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -1134,6 +1737,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: base + std::time::Duration::from_secs(10),
                 banned_user: absent,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -1209,6 +1814,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: base + std::time::Duration::from_secs(10),
                 banned_user: absent1,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -1218,6 +1825,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: base + std::time::Duration::from_secs(11),
                 banned_user: absent2,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -1230,6 +1839,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: base,
                 banned_user: c_id,
+                reason: None,
+                duration: None,
             },
             b_id,
             &b_sk,
@@ -1353,6 +1964,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: r_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -1435,6 +2048,8 @@ mod tests {
                             owner_member_id: owner_id,
                             banned_at: base + std::time::Duration::from_secs(i as u64),
                             banned_user: absent,
+                            reason: None,
+                            duration: None,
                         },
                         MemberId::from(&sk.verifying_key()),
                         sk,
@@ -1477,6 +2092,8 @@ mod tests {
                         owner_member_id: owner_id,
                         banned_at: base,
                         banned_user: b_id,
+                        reason: None,
+                        duration: None,
                     },
                     a_id,
                     &a_sk,
@@ -1487,6 +2104,8 @@ mod tests {
                         owner_member_id: owner_id,
                         banned_at: base + std::time::Duration::from_secs(1),
                         banned_user: a_id,
+                        reason: None,
+                        duration: None,
                     },
                     owner_id,
                     &owner_sk,
@@ -1499,6 +2118,8 @@ mod tests {
                         owner_member_id: owner_id,
                         banned_at: base + std::time::Duration::from_secs(100 + i as u64),
                         banned_user: x_id,
+                        reason: None,
+                        duration: None,
                     },
                     MemberId::from(&sk.verifying_key()),
                     sk,
@@ -1759,6 +2380,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: a_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -1953,6 +2576,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: x_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -1,5 +1,7 @@
 use crate::room_state::member::{AuthorizedMember, MemberId, MembersV1};
 use crate::room_state::member_info::MemberInfoV1;
+use crate::room_state::message::{AuthorizedMessageV1, MessagesV1};
+use crate::room_state::privacy::SealedBytes;
 use crate::room_state::ChatRoomParametersV1;
 use crate::util::{sign_struct, verify_struct};
 use crate::ChatRoomStateV1;
@@ -10,7 +12,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 /// Represents a collection of user bans in a chat room
 ///
@@ -59,6 +61,8 @@ pub enum BanValidationError {
     /// The banning member is not in the current member list AND was themselves
     /// banned — an orphaned ban.
     BannerNotFound(MemberId),
+    /// The ban reason is longer than [`MAX_BAN_REASON_BYTES`].
+    ReasonTooLong(usize),
 }
 
 impl fmt::Display for BanValidationError {
@@ -67,10 +71,18 @@ impl fmt::Display for BanValidationError {
             BanValidationError::BannerNotFound(id) => {
                 write!(f, "Banning member not found in member list: {:?}", id)
             }
+            BanValidationError::ReasonTooLong(len) => write!(
+                f,
+                "Ban reason of {} bytes exceeds the maximum of {}",
+                len, MAX_BAN_REASON_BYTES
+            ),
         }
     }
 }
 
+/// Upper bound on a ban reason, in (declared plaintext) bytes.
+pub const MAX_BAN_REASON_BYTES: usize = 500;
+
 impl BansV1 {
     /// Validates the per-ban orphan constraints and returns a map of invalid
     /// bans with errors. Does NOT enforce the `max_user_bans` ceiling — that is
@@ -121,6 +133,16 @@ impl BansV1 {
         invalid_bans: &mut HashMap<BanId, BanValidationError>,
         banned_user_ids: &HashSet<MemberId>,
     ) {
+        if let Some(reason) = &ban.ban.reason {
+            if reason.declared_len() > MAX_BAN_REASON_BYTES {
+                invalid_bans.insert(
+                    ban.id(),
+                    BanValidationError::ReasonTooLong(reason.declared_len()),
+                );
+                return;
+            }
+        }
+
         // If the banned member is no longer present they were already removed
         // (e.g. by this ban, a cascade, or an inactivity prune); nothing left
         // to enforce, so the ban is valid.
@@ -277,6 +299,80 @@ impl BansV1 {
             true
         }
     }
+
+    /// Whether `member` may lift `ban`: they issued it, or they are authorized
+    /// to ban its banner ([`MembersV1::is_ban_authorized`]: the owner, anyone
+    /// above the banner in the invite chain, or their deputies). Only the owner
+    /// can lift the owner's bans. Decides both who may revoke a ban
+    /// ([`crate::room_state::ban_revocation`]) and whose messages count towards
+    /// a temporary ban's expiry ([`Self::ban_has_expired`]).
+    pub fn can_lift(
+        ban: &AuthorizedUserBan,
+        member: MemberId,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+    ) -> bool {
+        member == ban.banned_by
+            || MembersV1::is_ban_authorized(
+                member,
+                ban.banned_by,
+                members_by_id,
+                member_info,
+                owner_id,
+            )
+    }
+
+    /// Whether a temporary ban has lapsed.
+    ///
+    /// Expiry cannot read the wall clock: cleanup must be a pure function of
+    /// state, and peers disagree about "now". Instead the room's own messages
+    /// act as the clock. A ban has expired once `recent_messages` holds a
+    /// message timestamped at or after [`UserBan::expires_at`] from someone
+    /// who could lift the ban anyway ([`Self::can_lift`]). Message timestamps
+    /// are author-chosen, so counting anyone else's would let any member end
+    /// any temporary ban early with one future-dated message; counting only
+    /// these authors gives nobody a power they lacked. The price is that a
+    /// temporary ban lapses only once its banner, an ancestor of theirs, a
+    /// deputy or the owner next posts.
+    ///
+    /// Once expired, cleanup records a [`crate::room_state::ban_lapse`] so the
+    /// ban stays lifted after that message is trimmed.
+    pub fn ban_has_expired(
+        ban: &AuthorizedUserBan,
+        messages: &MessagesV1,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+    ) -> bool {
+        Self::expiry_witness(ban, messages, members_by_id, member_info, owner_id).is_some()
+    }
+
+    /// The earliest message in `messages` that ends `ban`
+    /// (see [`Self::ban_has_expired`]), if any.
+    pub fn expiry_witness<'a>(
+        ban: &AuthorizedUserBan,
+        messages: &'a MessagesV1,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+    ) -> Option<&'a AuthorizedMessageV1> {
+        let expires_at = ban.ban.expires_at()?;
+        messages
+            .messages
+            .iter()
+            .filter(|message| {
+                message.message.time >= expires_at
+                    && Self::can_lift(
+                        ban,
+                        message.message.author,
+                        members_by_id,
+                        member_info,
+                        owner_id,
+                    )
+            })
+            .min_by_key(|message| message.order_key())
+    }
 }
 
 impl ComposableState for BansV1 {
@@ -497,12 +593,34 @@ impl AuthorizedUserBan {
 
 /// Contains the core information about a user ban
 ///
-/// Includes the room owner's ID, the time of the ban, and the ID of the banned user
+/// Includes the room owner's ID, the time of the ban, and the ID of the banned user,
+/// plus an optional reason and duration. Those two are skipped when `None`, so a
+/// ban without them serializes to the same bytes as before they existed and
+/// older signatures still verify.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct UserBan {
     pub owner_member_id: MemberId,
     pub banned_at: SystemTime,
     pub banned_user: MemberId,
+    /// Why the member was banned, at most [`MAX_BAN_REASON_BYTES`]. Sealed
+    /// under the room secret in a private room, like nicknames.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<SealedBytes>,
+    /// How long the ban lasts; `None` is permanent. See
+    /// [`BansV1::ban_has_expired`] for when a temporary ban actually lapses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Duration>,
+}
+
+impl UserBan {
+    /// The time a temporary ban lapses, `None` for a permanent one.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.duration.map(|duration| {
+            self.banned_at
+                .checked_add(duration)
+                .unwrap_or(self.banned_at)
+        })
+    }
 }
 
 /// A unique identifier for a ban
@@ -589,6 +707,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: member1_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_key,
@@ -609,6 +729,8 @@ mod tests {
                     owner_member_id: owner_id,
                     banned_at: SystemTime::now(),
                     banned_user: member1_id,
+                    reason: None,
+                    duration: None,
                 },
                 owner_id,
                 &owner_key,
@@ -630,6 +752,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: member2_id,
+                reason: None,
+                duration: None,
             },
             pruned_id,
             &pruned_key,
@@ -650,6 +774,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: member2_id,
+                reason: None,
+                duration: None,
             },
             orphaned_id,
             &orphaned_key,
@@ -660,6 +786,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: orphaned_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_key,
@@ -676,6 +804,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: member2_id,
+                reason: None,
+                duration: None,
             },
             member1_id,
             &member1_key,
@@ -701,6 +831,8 @@ mod tests {
                 owner_member_id: id,
                 banned_at: SystemTime::now(),
                 banned_user: id,
+                reason: None,
+                duration: None,
             },
             id,
             &key,
@@ -711,6 +843,8 @@ mod tests {
                 owner_member_id: id,
                 banned_at: SystemTime::now() + Duration::from_secs(1),
                 banned_user: id,
+                reason: None,
+                duration: None,
             },
             id,
             &key,
@@ -737,6 +871,8 @@ mod tests {
                 owner_member_id: id,
                 banned_at: SystemTime::now(),
                 banned_user: id,
+                reason: None,
+                duration: None,
             },
             id,
             &key,
@@ -747,6 +883,8 @@ mod tests {
                 owner_member_id: id,
                 banned_at: SystemTime::now() + Duration::from_secs(1),
                 banned_user: id,
+                reason: None,
+                duration: None,
             },
             id,
             &key,
@@ -808,6 +946,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: member_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_key,
@@ -842,6 +982,8 @@ mod tests {
                     owner_member_id: owner_id,
                     banned_at: SystemTime::now() + Duration::from_secs(i as u64 + 10),
                     banned_user: member_id,
+                    reason: None,
+                    duration: None,
                 },
                 owner_id,
                 &owner_key,
@@ -887,6 +1029,8 @@ mod tests {
                     owner_member_id: owner_id,
                     banned_at: SystemTime::now() + Duration::from_secs(i as u64 + 100),
                     banned_user: member_id,
+                    reason: None,
+                    duration: None,
                 },
                 owner_id,
                 &owner_key,
@@ -916,6 +1060,8 @@ mod tests {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member_id,
+            reason: None,
+            duration: None,
         };
 
         let authorized_ban = AuthorizedUserBan::new(ban.clone(), owner_id, &owner_key);
@@ -942,10 +1088,207 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now() + Duration::from_secs(1),
                 banned_user: member_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_key,
         );
         assert_ne!(authorized_ban.id(), another_ban.id());
     }
+
+    /// A ban without reason or duration serializes exactly like a ban signed
+    /// before those fields existed, so old signatures keep verifying.
+    #[test]
+    fn test_ban_without_reason_or_duration_keeps_legacy_signature() {
+        #[derive(Serialize)]
+        struct LegacyUserBan {
+            owner_member_id: MemberId,
+            banned_at: SystemTime,
+            banned_user: MemberId,
+        }
+
+        let owner_key = SigningKey::generate(&mut rand::thread_rng());
+        let owner_id: MemberId = owner_key.verifying_key().into();
+        let member_id: MemberId = SigningKey::generate(&mut rand::thread_rng())
+            .verifying_key()
+            .into();
+        let banned_at = SystemTime::now();
+
+        let legacy_signature = sign_struct(
+            &LegacyUserBan {
+                owner_member_id: owner_id,
+                banned_at,
+                banned_user: member_id,
+            },
+            &owner_key,
+        );
+        let ban = AuthorizedUserBan::with_signature(
+            UserBan {
+                owner_member_id: owner_id,
+                banned_at,
+                banned_user: member_id,
+                reason: None,
+                duration: None,
+            },
+            owner_id,
+            legacy_signature,
+        );
+        assert!(ban.verify_signature(&owner_key.verifying_key()).is_ok());
+
+        // Setting either field changes the signed bytes.
+        let mut with_reason = ban.clone();
+        with_reason.ban.reason = Some(SealedBytes::public(b"spam".to_vec()));
+        assert!(with_reason
+            .verify_signature(&owner_key.verifying_key())
+            .is_err());
+        let mut with_duration = ban;
+        with_duration.ban.duration = Some(Duration::from_secs(60));
+        assert!(with_duration
+            .verify_signature(&owner_key.verifying_key())
+            .is_err());
+    }
+
+    #[test]
+    fn test_ban_reason_too_long_is_invalid() {
+        let mut state = create_test_chat_room_state();
+        let owner_key = SigningKey::generate(&mut rand::thread_rng());
+        let owner_id: MemberId = owner_key.verifying_key().into();
+        let params = ChatRoomParametersV1 {
+            owner: owner_key.verifying_key(),
+        };
+        let member_key = SigningKey::generate(&mut rand::thread_rng());
+        let member_id: MemberId = member_key.verifying_key().into();
+        state.members.members.push(AuthorizedMember::new(
+            Member {
+                owner_member_id: owner_id,
+                invited_by: owner_id,
+                member_vk: member_key.verifying_key(),
+            },
+            &owner_key,
+        ));
+
+        let ban_with_reason = |len: usize| {
+            AuthorizedUserBan::new(
+                UserBan {
+                    owner_member_id: owner_id,
+                    banned_at: SystemTime::now(),
+                    banned_user: member_id,
+                    reason: Some(SealedBytes::public(vec![b'x'; len])),
+                    duration: None,
+                },
+                owner_id,
+                &owner_key,
+            )
+        };
+
+        state.bans = BansV1(vec![ban_with_reason(MAX_BAN_REASON_BYTES)]);
+        assert!(state.bans.verify(&state, &params).is_ok());
+
+        state.bans = BansV1(vec![ban_with_reason(MAX_BAN_REASON_BYTES + 1)]);
+        let invalid = state.bans.get_invalid_bans(&state, &params);
+        assert!(matches!(
+            invalid.values().next(),
+            Some(BanValidationError::ReasonTooLong(len)) if *len == MAX_BAN_REASON_BYTES + 1
+        ));
+    }
+
+    #[test]
+    fn test_temporary_ban_expires_on_lifter_message_only() {
+        use crate::room_state::message::{AuthorizedMessageV1, MessageV1, RoomMessageBody};
+
+        let owner_key = SigningKey::generate(&mut rand::thread_rng());
+        let owner_id: MemberId = owner_key.verifying_key().into();
+        let banner_key = SigningKey::generate(&mut rand::thread_rng());
+        let banner_id: MemberId = banner_key.verifying_key().into();
+        let bystander_key = SigningKey::generate(&mut rand::thread_rng());
+        let bystander_id: MemberId = bystander_key.verifying_key().into();
+        let target_id: MemberId = SigningKey::generate(&mut rand::thread_rng())
+            .verifying_key()
+            .into();
+
+        let members = MembersV1 {
+            members: [&banner_key, &bystander_key]
+                .into_iter()
+                .map(|key| {
+                    AuthorizedMember::new(
+                        Member {
+                            owner_member_id: owner_id,
+                            invited_by: owner_id,
+                            member_vk: key.verifying_key(),
+                        },
+                        &owner_key,
+                    )
+                })
+                .collect(),
+        };
+        let members_by_id = members.members_by_member_id();
+        let member_info = MemberInfoV1::default();
+
+        let banned_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let ban = |duration: Option<Duration>| {
+            AuthorizedUserBan::new(
+                UserBan {
+                    owner_member_id: owner_id,
+                    banned_at,
+                    banned_user: target_id,
+                    reason: None,
+                    duration,
+                },
+                banner_id,
+                &banner_key,
+            )
+        };
+        let temporary = ban(Some(Duration::from_secs(3600)));
+        let permanent = ban(None);
+        assert_eq!(
+            temporary.ban.expires_at(),
+            Some(banned_at + Duration::from_secs(3600))
+        );
+        assert_eq!(permanent.ban.expires_at(), None);
+
+        let message = |author: MemberId, key: &SigningKey, secs: u64| {
+            AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: owner_id,
+                    author,
+                    time: banned_at + Duration::from_secs(secs),
+                    content: RoomMessageBody::public("hi".to_string()),
+                },
+                key,
+            )
+        };
+        let has_expired = |ban: &AuthorizedUserBan, messages: Vec<AuthorizedMessageV1>| {
+            let messages = MessagesV1 {
+                messages,
+                ..Default::default()
+            };
+            BansV1::ban_has_expired(ban, &messages, &members_by_id, &member_info, owner_id)
+        };
+
+        // Before expiry nobody's message lifts the ban.
+        assert!(!has_expired(
+            &temporary,
+            vec![message(banner_id, &banner_key, 3599)]
+        ));
+        // A bystander cannot end the ban with a (possibly future-dated) message.
+        assert!(!has_expired(
+            &temporary,
+            vec![message(bystander_id, &bystander_key, 1_000_000)]
+        ));
+        // The banner's or the owner's message past the expiry does.
+        assert!(has_expired(
+            &temporary,
+            vec![message(banner_id, &banner_key, 3600)]
+        ));
+        assert!(has_expired(
+            &temporary,
+            vec![message(owner_id, &owner_key, 7200)]
+        ));
+        // A permanent ban never expires.
+        assert!(!has_expired(
+            &permanent,
+            vec![message(owner_id, &owner_key, 1_000_000)]
+        ));
+    }
 }
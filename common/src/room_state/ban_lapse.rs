@@ -0,0 +1,438 @@
+use crate::room_state::ban::{AuthorizedUserBan, BanId, BansV1};
+use crate::room_state::member::{AuthorizedMember, MemberId};
+use crate::room_state::member_info::MemberInfoV1;
+use crate::room_state::message::{AuthorizedMessageV1, MessageOrderKey, MessagesV1};
+use crate::room_state::signed_record;
+use crate::room_state::ChatRoomParametersV1;
+use crate::ChatRoomStateV1;
+use ed25519_dalek::VerifyingKey;
+use freenet_scaffold::util::{fast_hash, FastHash};
+use freenet_scaffold::ComposableState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::SystemTime;
+
+/// Temporary bans that have run out.
+///
+/// A temporary ban lapses by the room's message clock: once someone who could
+/// lift it posts a message dated at or after its end
+/// ([`BansV1::ban_has_expired`]). That message is trimmed from
+/// `recent_messages` sooner or later, while a peer still holding the ban
+/// re-offers it on every exchange, so dropping the ban alone would let it come
+/// back. A lapse is the tombstone that keeps it dropped: the ban together with
+/// the message that ended it, its witness. `ChatRoomStateV1::post_apply_cleanup`
+/// records one, with the earliest witness it holds, whenever it drops an
+/// expired ban, and from then on the ban stays lifted whether or not the
+/// witness is still retained.
+///
+/// A lapse lifts its ban while [`Self::lapse_is_effective`] holds. Both
+/// signatures must verify against their signers' CURRENT keys, and a banner or
+/// witness author holding a retained lapse is exempt from inactivity-prune so
+/// they do; lapses whose signatures no longer verify are swept. Peers that
+/// recorded different witnesses for one ban keep the earliest effective one.
+///
+/// Stored lapses are capped at `max_user_bans`, oldest ban evicted first. An
+/// evicted lapse only matters if some peer still re-offers its ban.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct BanLapsesV1(pub Vec<BanLapse>);
+
+impl BanLapsesV1 {
+    /// Whether the ban's and the witness's signatures both verify against
+    /// their signers' CURRENT keys. Decides both the signers' prune exemption
+    /// and whether the lapse survives cleanup, which keeps cleanup idempotent.
+    pub fn signatures_match_current_keys(
+        lapse: &BanLapse,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        BansV1::ban_signature_matches_current_key(&lapse.ban, members_by_id, owner_id, owner_vk)
+            && signed_record::signer_key(
+                lapse.witness.message.author,
+                members_by_id,
+                owner_id,
+                owner_vk,
+            )
+            .is_some_and(|key| lapse.witness.validate(&key).is_ok())
+    }
+
+    /// Whether `lapse` currently lifts `ban`: it names the ban, its signatures
+    /// match, and its witness is a message of this room, dated at or after the
+    /// ban's end, from someone who may lift the ban ([`BansV1::can_lift`]).
+    pub fn lapse_is_effective(
+        lapse: &BanLapse,
+        ban: &AuthorizedUserBan,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        let Some(expires_at) = ban.ban.expires_at() else {
+            return false;
+        };
+        let witness = &lapse.witness.message;
+        lapse.ban.id() == ban.id()
+            && witness.room_owner == owner_id
+            && witness.time >= expires_at
+            && Self::signatures_match_current_keys(lapse, members_by_id, owner_id, owner_vk)
+            && BansV1::can_lift(ban, witness.author, members_by_id, member_info, owner_id)
+    }
+
+    /// A lapse recorded for `ban`, effective or not.
+    pub fn lapse_for<'a>(&'a self, ban: &AuthorizedUserBan) -> Option<&'a BanLapse> {
+        let ban_id = ban.id();
+        self.0.iter().find(|lapse| lapse.ban.id() == ban_id)
+    }
+
+    /// Whether a stored lapse currently lifts `ban`.
+    pub fn lifts(
+        &self,
+        ban: &AuthorizedUserBan,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        self.0.iter().any(|lapse| {
+            Self::lapse_is_effective(lapse, ban, members_by_id, member_info, owner_id, owner_vk)
+        })
+    }
+
+    /// Records a lapse for every ban in `bans` that `messages` shows has
+    /// expired and that no stored lapse lifts yet. Then keeps one lapse per
+    /// ban among those whose signatures match, preferring effective ones and
+    /// then the earliest witness, capped at `max`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &mut self,
+        bans: &BansV1,
+        messages: &MessagesV1,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+        max: usize,
+    ) {
+        for ban in &bans.0 {
+            if self.lifts(ban, members_by_id, member_info, owner_id, owner_vk) {
+                continue;
+            }
+            if let Some(witness) =
+                BansV1::expiry_witness(ban, messages, members_by_id, member_info, owner_id)
+            {
+                self.0.push(BanLapse {
+                    ban: ban.clone(),
+                    witness: witness.clone(),
+                });
+            }
+        }
+
+        let preference = |lapse: &BanLapse| {
+            let effective = Self::lapse_is_effective(
+                lapse,
+                &lapse.ban,
+                members_by_id,
+                member_info,
+                owner_id,
+                owner_vk,
+            );
+            (!effective, lapse.witness.order_key())
+        };
+        let mut best: HashMap<BanId, &BanLapse> = HashMap::new();
+        for lapse in self.0.iter().filter(|lapse| {
+            Self::signatures_match_current_keys(lapse, members_by_id, owner_id, owner_vk)
+        }) {
+            let entry = best.entry(lapse.ban.id()).or_insert(lapse);
+            if preference(lapse) < preference(entry) {
+                *entry = lapse;
+            }
+        }
+        let mut kept: Vec<BanLapse> = best.into_values().cloned().collect();
+        kept.sort_by_key(|lapse| lapse.order_key());
+        if kept.len() > max {
+            kept.drain(0..kept.len() - max);
+        }
+        self.0 = kept;
+    }
+}
+
+impl ComposableState for BanLapsesV1 {
+    type ParentState = ChatRoomStateV1;
+    // BTreeSet for canonical summary bytes; see the note on `BansV1`.
+    type Summary = BTreeSet<LapseId>;
+    type Delta = Vec<BanLapse>;
+    type Parameters = ChatRoomParametersV1;
+
+    /// Checks the count and, for every signer who is the owner or a current
+    /// member, the signature. Other signers are skipped as for bans; cleanup
+    /// sweeps the lapse if their key never matches.
+    fn verify(
+        &self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+    ) -> Result<(), String> {
+        let max = parent_state.configuration.configuration.max_user_bans;
+        if self.0.len() > max {
+            return Err(format!(
+                "Number of ban lapses ({}) exceeds the maximum allowed ({})",
+                self.0.len(),
+                max
+            ));
+        }
+        verify_signatures(&self.0, parent_state, parameters)
+    }
+
+    fn summarize(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+    ) -> Self::Summary {
+        self.0.iter().map(|lapse| lapse.id()).collect()
+    }
+
+    fn delta(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+        old_state_summary: &Self::Summary,
+    ) -> Option<Self::Delta> {
+        let delta: Vec<BanLapse> = self
+            .0
+            .iter()
+            .filter(|lapse| !old_state_summary.contains(&lapse.id()))
+            .cloned()
+            .collect();
+        if delta.is_empty() {
+            None
+        } else {
+            Some(delta)
+        }
+    }
+
+    /// Adds new lapses after checking their signatures. As for revocations,
+    /// the count cap is left to `post_apply_cleanup`, and a delta larger than
+    /// the cap is refused as a flood.
+    fn apply_delta(
+        &mut self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+        delta: &Option<Self::Delta>,
+    ) -> Result<(), String> {
+        let Some(delta) = delta else {
+            return Ok(());
+        };
+        let max = parent_state.configuration.configuration.max_user_bans;
+        if delta.len() > max {
+            return Err(format!(
+                "Ban lapse delta of {} exceeds max_user_bans ({}); refusing to process a flood",
+                delta.len(),
+                max
+            ));
+        }
+        let mut known: BTreeSet<LapseId> = self.0.iter().map(|lapse| lapse.id()).collect();
+        let new: Vec<BanLapse> = delta
+            .iter()
+            .filter(|lapse| known.insert(lapse.id()))
+            .cloned()
+            .collect();
+        verify_signatures(&new, parent_state, parameters)
+            .map_err(|e| format!("Invalid delta: {}", e))?;
+        self.0.extend(new);
+        self.0.sort_by_key(|lapse| lapse.order_key());
+        Ok(())
+    }
+}
+
+fn verify_signatures(
+    lapses: &[BanLapse],
+    parent_state: &ChatRoomStateV1,
+    parameters: &ChatRoomParametersV1,
+) -> Result<(), String> {
+    let members_by_id = parent_state.members.members_by_member_id();
+    let owner_id = parameters.owner_id();
+    for lapse in lapses {
+        if let Some(key) = signed_record::signer_key(
+            lapse.ban.banned_by,
+            &members_by_id,
+            owner_id,
+            &parameters.owner,
+        ) {
+            lapse.ban.verify_signature(&key)?;
+        }
+        if let Some(key) = signed_record::signer_key(
+            lapse.witness.message.author,
+            &members_by_id,
+            owner_id,
+            &parameters.owner,
+        ) {
+            lapse
+                .witness
+                .validate(&key)
+                .map_err(|e| format!("Invalid ban lapse witness signature: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// A lifted temporary ban and the message that ended it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BanLapse {
+    pub ban: AuthorizedUserBan,
+    pub witness: AuthorizedMessageV1,
+}
+
+impl BanLapse {
+    pub fn id(&self) -> LapseId {
+        let mut bytes = self.ban.signature.to_bytes().to_vec();
+        bytes.extend_from_slice(&self.witness.signature.to_bytes());
+        LapseId(fast_hash(&bytes))
+    }
+
+    /// Stored order: by when the ban was issued, then its id, then witness.
+    fn order_key(&self) -> (SystemTime, BanId, MessageOrderKey) {
+        (
+            self.ban.ban.banned_at,
+            self.ban.id(),
+            self.witness.order_key(),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, Debug, Ord, PartialOrd)]
+pub struct LapseId(pub FastHash);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_state::ban::UserBan;
+    use crate::room_state::member::MembersDelta;
+    use crate::room_state::test_room::{at, id, Room};
+    use crate::room_state::ChatRoomStateV1Delta;
+    use ed25519_dalek::SigningKey;
+    use std::time::Duration;
+
+    /// A bans B at `at(10)` for 60 seconds, and the ban is enforced.
+    fn ban_b_for_a_minute(room: &mut Room) -> AuthorizedUserBan {
+        let a_sk = room.a_sk.clone();
+        let ban = AuthorizedUserBan::new(
+            UserBan {
+                owner_member_id: room.params.owner_id(),
+                banned_at: at(10),
+                banned_user: id(&room.b_sk),
+                reason: None,
+                duration: Some(Duration::from_secs(60)),
+            },
+            id(&a_sk),
+            &a_sk,
+        );
+        room.apply(ChatRoomStateV1Delta {
+            bans: Some(vec![ban.clone()]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!room.is_member(&room.b_sk), "the ban should remove B");
+        ban
+    }
+
+    fn rejoin_b(room: &mut Room, secs: u64) {
+        let b_message = room.message(&room.b_sk, secs);
+        room.apply(ChatRoomStateV1Delta {
+            members: Some(MembersDelta::new(vec![room.member(&room.b_sk, &room.a_sk)])),
+            recent_messages: Some(vec![b_message]),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    /// Drops every message by `author`, as retention eventually does.
+    fn trim(room: &mut Room, author: &SigningKey) {
+        let author = id(author);
+        room.state
+            .recent_messages
+            .messages
+            .retain(|m| m.message.author != author);
+        room.state.post_apply_cleanup(&room.params).unwrap();
+    }
+
+    #[test]
+    fn lapsed_ban_stays_lifted_after_its_witness_is_trimmed() {
+        let mut room = Room::new();
+        let ban = ban_b_for_a_minute(&mut room);
+
+        let witness = room.message(&room.a_sk, 100);
+        room.post(witness.clone());
+        assert!(room.state.bans.0.is_empty(), "A's post ends the ban");
+        assert_eq!(
+            room.state.ban_lapses.0,
+            vec![BanLapse {
+                ban: ban.clone(),
+                witness
+            }]
+        );
+        rejoin_b(&mut room, 110);
+        assert!(room.is_member(&room.b_sk));
+
+        // A's messages, the witness among them, age out; the lapse keeps A.
+        let a_sk = room.a_sk.clone();
+        trim(&mut room, &a_sk);
+        assert!(room.is_member(&a_sk));
+        assert_eq!(room.state.ban_lapses.0.len(), 1);
+
+        // A peer that missed the expiry re-offers the old ban.
+        room.apply(ChatRoomStateV1Delta {
+            bans: Some(vec![ban]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(room.state.bans.0.is_empty(), "the lapse outlives re-offers");
+        assert!(room.is_member(&room.b_sk));
+        room.assert_settled();
+    }
+
+    #[test]
+    fn peers_keep_the_earliest_witness() {
+        let mut room = Room::new();
+        let ban = ban_b_for_a_minute(&mut room);
+        let (a_sk, owner_sk) = (room.a_sk.clone(), room.owner_sk.clone());
+
+        let later = BanLapse {
+            ban: ban.clone(),
+            witness: room.message(&owner_sk, 200),
+        };
+        let earlier = BanLapse {
+            ban,
+            witness: room.message(&a_sk, 100),
+        };
+        room.apply(ChatRoomStateV1Delta {
+            ban_lapses: Some(vec![later]),
+            ..Default::default()
+        })
+        .unwrap();
+        room.apply(ChatRoomStateV1Delta {
+            ban_lapses: Some(vec![earlier.clone()]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(room.state.bans.0.is_empty());
+        assert_eq!(room.state.ban_lapses.0, vec![earlier]);
+        room.assert_settled();
+    }
+
+    #[test]
+    fn witness_who_cannot_lift_the_ban_does_not_end_it() {
+        let mut room = Room::new();
+        let ban = ban_b_for_a_minute(&mut room);
+
+        let forged = BanLapse {
+            ban: ban.clone(),
+            witness: room.message(&room.c_sk, 100),
+        };
+        room.apply(ChatRoomStateV1Delta {
+            ban_lapses: Some(vec![forged]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(room.state.bans.0, vec![ban], "C is not above A");
+        assert!(!room.is_member(&room.b_sk));
+    }
+}
@@ -0,0 +1,394 @@
+use crate::room_state::ban::{AuthorizedUserBan, BanId, BansV1};
+use crate::room_state::member::{AuthorizedMember, MemberId};
+use crate::room_state::member_info::MemberInfoV1;
+use crate::room_state::signed_record::{self, SignedRecord};
+use crate::room_state::ChatRoomParametersV1;
+use crate::util::{sign_struct, verify_struct};
+use crate::ChatRoomStateV1;
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use freenet_scaffold::util::{fast_hash, FastHash};
+use freenet_scaffold::ComposableState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::SystemTime;
+
+/// Signed revocations of bans ("unbans").
+///
+/// `BansV1` is an add-only set: a peer still holding a ban re-offers it on
+/// every exchange, so an unban cannot simply delete the ban. A revocation is
+/// the tombstone that keeps it deleted. `ChatRoomStateV1::post_apply_cleanup`
+/// removes every ban named by an effective revocation, before the
+/// `max_user_bans` cap and ban enforcement run, so the unbanned member is no
+/// longer removed and can be re-invited.
+///
+/// A revocation is effective while [`Self::revocation_is_effective`] holds:
+/// its signature verifies against the revoker's CURRENT key, it describes the
+/// ban it names, and the revoker may lift the ban ([`BansV1::can_lift`]: the
+/// original banner, the owner, anyone above the banner in the invite chain, or
+/// their deputies). Like bans, authority is
+/// decided from converged state in cleanup, never in `verify`, and a revoker
+/// holding a retained revocation is exempt from inactivity-prune so the
+/// revocation keeps working; revocations whose signature no longer verifies
+/// are swept.
+///
+/// Stored revocations are capped at `max_user_bans`, oldest evicted first. An
+/// evicted revocation only matters if some peer still re-offers the ban it
+/// revoked, in which case that ban comes back.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct BanRevocationsV1(pub Vec<AuthorizedBanRevocation>);
+
+impl BanRevocationsV1 {
+    /// Whether `revocation`'s signature verifies against the revoker's CURRENT
+    /// key: the owner's, or a current member's. The same predicate decides
+    /// both the revoker's prune exemption and whether the revocation survives
+    /// cleanup, which is what keeps cleanup idempotent.
+    pub fn signature_matches_current_key(
+        revocation: &AuthorizedBanRevocation,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        signed_record::signed_by_current_key(revocation, members_by_id, owner_id, owner_vk)
+    }
+
+    /// Whether `revocation` currently lifts `ban`.
+    pub fn revocation_is_effective(
+        revocation: &AuthorizedBanRevocation,
+        ban: &AuthorizedUserBan,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        let details = &revocation.revocation;
+        if details.ban_id != ban.id()
+            || details.banned_user != ban.ban.banned_user
+            || details.banned_by != ban.banned_by
+        {
+            return false;
+        }
+        if !Self::signature_matches_current_key(revocation, members_by_id, owner_id, owner_vk) {
+            return false;
+        }
+        BansV1::can_lift(
+            ban,
+            revocation.revoked_by,
+            members_by_id,
+            member_info,
+            owner_id,
+        )
+    }
+
+    /// The revocation lifting `ban`, if any.
+    pub fn revocation_for<'a>(
+        &'a self,
+        ban: &AuthorizedUserBan,
+    ) -> Option<&'a AuthorizedBanRevocation> {
+        let ban_id = ban.id();
+        self.0.iter().find(|r| r.revocation.ban_id == ban_id)
+    }
+}
+
+impl ComposableState for BanRevocationsV1 {
+    type ParentState = ChatRoomStateV1;
+    // BTreeSet for canonical summary bytes; see the note on `BansV1`.
+    type Summary = BTreeSet<RevocationId>;
+    type Delta = Vec<AuthorizedBanRevocation>;
+    type Parameters = ChatRoomParametersV1;
+
+    fn verify(
+        &self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+    ) -> Result<(), String> {
+        signed_record::verify_signatures(&self.0, parent_state, parameters)?;
+        let max = parent_state.configuration.configuration.max_user_bans;
+        if self.0.len() > max {
+            return Err(format!(
+                "Number of ban revocations ({}) exceeds the maximum allowed ({})",
+                self.0.len(),
+                max
+            ));
+        }
+        Ok(())
+    }
+
+    fn summarize(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+    ) -> Self::Summary {
+        signed_record::summarize(&self.0)
+    }
+
+    fn delta(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+        old_state_summary: &Self::Summary,
+    ) -> Option<Self::Delta> {
+        signed_record::delta(&self.0, old_state_summary)
+    }
+
+    /// Adds new revocations after checking their signatures. Like
+    /// `BansV1::apply_delta`, the count cap is left to `post_apply_cleanup`,
+    /// and a delta larger than the cap is refused as a flood.
+    fn apply_delta(
+        &mut self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+        delta: &Option<Self::Delta>,
+    ) -> Result<(), String> {
+        signed_record::merge(
+            &mut self.0,
+            delta.as_deref(),
+            parent_state.configuration.configuration.max_user_bans,
+            "max_user_bans",
+            parent_state,
+            parameters,
+            |_| Ok(()),
+        )
+    }
+}
+
+/// A ban revocation with the revoker's signature.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuthorizedBanRevocation {
+    pub revocation: BanRevocation,
+    pub revoked_by: MemberId,
+    pub signature: Signature,
+}
+
+impl AuthorizedBanRevocation {
+    pub fn new(revocation: BanRevocation, revoked_by: MemberId, signing_key: &SigningKey) -> Self {
+        assert_eq!(MemberId::from(signing_key.verifying_key()), revoked_by);
+        let signature = sign_struct(&revocation, signing_key);
+        Self {
+            revocation,
+            revoked_by,
+            signature,
+        }
+    }
+
+    /// Create an AuthorizedBanRevocation with a pre-computed signature.
+    /// Use this when signing is done externally (e.g., via delegate).
+    pub fn with_signature(
+        revocation: BanRevocation,
+        revoked_by: MemberId,
+        signature: Signature,
+    ) -> Self {
+        Self {
+            revocation,
+            revoked_by,
+            signature,
+        }
+    }
+
+    pub fn verify_signature(&self, revoker_verifying_key: &VerifyingKey) -> Result<(), String> {
+        verify_struct(&self.revocation, &self.signature, revoker_verifying_key)
+            .map_err(|e| format!("Invalid ban revocation signature: {}", e))
+    }
+
+    pub fn id(&self) -> RevocationId {
+        RevocationId(fast_hash(&self.signature.to_bytes()))
+    }
+}
+
+impl SignedRecord for AuthorizedBanRevocation {
+    type Id = RevocationId;
+    const KIND: &'static str = "Ban revocation";
+
+    fn id(&self) -> RevocationId {
+        Self::id(self)
+    }
+
+    fn signer(&self) -> MemberId {
+        self.revoked_by
+    }
+
+    fn subject(&self) -> MemberId {
+        self.revocation.banned_user
+    }
+
+    fn issued_at(&self) -> SystemTime {
+        self.revocation.revoked_at
+    }
+
+    fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String> {
+        Self::verify_signature(self, verifying_key)
+    }
+}
+
+/// What is being revoked. Carries the ban's target and banner alongside its
+/// id so the revocation can be displayed, and checked against the ban, without
+/// the ban itself.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BanRevocation {
+    pub owner_member_id: MemberId,
+    pub ban_id: BanId,
+    pub banned_user: MemberId,
+    pub banned_by: MemberId,
+    pub revoked_at: SystemTime,
+}
+
+impl BanRevocation {
+    /// A revocation of `ban`. The caller supplies the time, as `SystemTime::now`
+    /// is unavailable in the browser.
+    pub fn of(ban: &AuthorizedUserBan, revoked_at: SystemTime) -> Self {
+        Self {
+            owner_member_id: ban.ban.owner_member_id,
+            ban_id: ban.id(),
+            banned_user: ban.ban.banned_user,
+            banned_by: ban.banned_by,
+            revoked_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, Debug, Ord, PartialOrd)]
+pub struct RevocationId(pub FastHash);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_state::ban::UserBan;
+    use crate::room_state::member::MembersDelta;
+    use crate::room_state::test_room::{at, id, Room};
+    use crate::room_state::ChatRoomStateV1Delta;
+
+    /// `banner` bans B, and the ban is applied and enforced.
+    fn ban_b(room: &mut Room, banner: &SigningKey) -> AuthorizedUserBan {
+        let ban = AuthorizedUserBan::new(
+            UserBan {
+                owner_member_id: room.params.owner_id(),
+                banned_at: at(10),
+                banned_user: id(&room.b_sk),
+                reason: None,
+                duration: None,
+            },
+            id(banner),
+            banner,
+        );
+        room.apply(ChatRoomStateV1Delta {
+            bans: Some(vec![ban.clone()]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!room.is_member(&room.b_sk), "the ban should remove B");
+        ban
+    }
+
+    fn revoke(
+        room: &mut Room,
+        ban: &AuthorizedUserBan,
+        revoker: &SigningKey,
+    ) -> Result<(), String> {
+        let revocation =
+            AuthorizedBanRevocation::new(BanRevocation::of(ban, at(20)), id(revoker), revoker);
+        room.apply(ChatRoomStateV1Delta {
+            ban_revocations: Some(vec![revocation]),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn banner_can_revoke_and_the_member_can_be_reinvited() {
+        let mut room = Room::new();
+        let a_sk = room.a_sk.clone();
+        let ban = ban_b(&mut room, &a_sk);
+
+        revoke(&mut room, &ban, &a_sk).unwrap();
+        assert!(room.state.bans.0.is_empty());
+        assert_eq!(room.state.ban_revocations.0.len(), 1);
+
+        // A peer that missed the unban re-offers the ban alongside B's re-invite.
+        let b_message = room.message(&room.b_sk, 30);
+        room.apply(ChatRoomStateV1Delta {
+            bans: Some(vec![ban]),
+            members: Some(MembersDelta::new(vec![room.member(&room.b_sk, &room.a_sk)])),
+            recent_messages: Some(vec![b_message]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(
+            room.state.bans.0.is_empty(),
+            "the revocation outlives re-offers"
+        );
+        assert!(room.is_member(&room.b_sk));
+        room.assert_settled();
+    }
+
+    #[test]
+    fn owner_can_revoke_a_members_ban_but_not_the_other_way_round() {
+        let mut room = Room::new();
+        let (a_sk, owner_sk) = (room.a_sk.clone(), room.owner_sk.clone());
+
+        let ban = ban_b(&mut room, &a_sk);
+        revoke(&mut room, &ban, &owner_sk).unwrap();
+        assert!(
+            room.state.bans.0.is_empty(),
+            "an ancestor of the banner may revoke"
+        );
+
+        let mut room = Room::new();
+        let (a_sk, owner_sk) = (room.a_sk.clone(), room.owner_sk.clone());
+        let ban = ban_b(&mut room, &owner_sk);
+        revoke(&mut room, &ban, &a_sk).unwrap();
+        assert_eq!(
+            room.state.bans.0,
+            vec![ban],
+            "only the owner lifts the owner's ban"
+        );
+    }
+
+    #[test]
+    fn unrelated_member_cannot_revoke() {
+        let mut room = Room::new();
+        let (a_sk, c_sk) = (room.a_sk.clone(), room.c_sk.clone());
+        let ban = ban_b(&mut room, &a_sk);
+
+        revoke(&mut room, &ban, &c_sk).unwrap();
+        assert_eq!(
+            room.state.bans.0,
+            vec![ban],
+            "C is neither above A nor A's deputy"
+        );
+        assert!(!room.is_member(&room.b_sk));
+    }
+
+    #[test]
+    fn forged_revocation_is_rejected() {
+        let mut room = Room::new();
+        let (a_sk, c_sk) = (room.a_sk.clone(), room.c_sk.clone());
+        let ban = ban_b(&mut room, &a_sk);
+
+        // Claims to be from A but is signed by C.
+        let revocation = BanRevocation::of(&ban, at(20));
+        let signature = sign_struct(&revocation, &c_sk);
+        let forged = AuthorizedBanRevocation::with_signature(revocation, id(&a_sk), signature);
+        let result = room.apply(ChatRoomStateV1Delta {
+            ban_revocations: Some(vec![forged]),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+        assert_eq!(room.state.bans.0, vec![ban]);
+    }
+
+    #[test]
+    fn inactive_revoker_is_kept_so_the_revocation_holds() {
+        let mut room = Room::new();
+        let a_sk = room.a_sk.clone();
+        let ban = ban_b(&mut room, &a_sk);
+        revoke(&mut room, &ban, &a_sk).unwrap();
+
+        // A's only message ages out; the revocation still keeps A.
+        let a_id = id(&a_sk);
+        room.state
+            .recent_messages
+            .messages
+            .retain(|m| m.message.author != a_id);
+        room.state.post_apply_cleanup(&room.params).unwrap();
+        assert!(room.is_member(&a_sk));
+        assert_eq!(room.state.ban_revocations.0.len(), 1);
+    }
+}
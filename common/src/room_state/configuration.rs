@@ -1,4 +1,5 @@
 use crate::room_state::member::MemberId;
+use crate::room_state::message::MessageV1;
 use crate::room_state::privacy::{PrivacyMode, RoomDisplayMetadata};
 use crate::room_state::ChatRoomParametersV1;
 use crate::util::truncated_base64;
@@ -8,6 +9,7 @@ use freenet_scaffold::util::{fast_hash, FastHash};
 use freenet_scaffold::ComposableState;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::time::SystemTime;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct AuthorizedConfigurationV1 {
@@ -86,6 +88,15 @@ impl ComposableState for AuthorizedConfigurationV1 {
                 || delta.configuration.max_room_name == 0
                 || delta.configuration.max_room_description == 0
                 || delta.configuration.max_direct_messages == Some(0)
+                || delta
+                    .configuration
+                    .slow_mode
+                    .is_some_and(|slow_mode| !slow_mode.is_valid())
+                || matches!(
+                    &delta.configuration.posting_policy,
+                    Some(PostingPolicy::Allowlist(posters))
+                        if posters.len() > delta.configuration.max_members
+                )
             {
                 return Err("Invalid configuration values".to_string());
             }
@@ -189,6 +200,9 @@ impl Default for Configuration {
             // gives new rooms the same bound while keeping the serialized
             // default configuration byte-identical to pre-#519 bytes.
             max_direct_messages: None,
+            slow_mode: None,
+            posting_policy: None,
+            posting_policy_since: None,
         }
     }
 }
@@ -256,6 +270,175 @@ pub struct Configuration {
     /// never sees state carrying this field. Do not weaken that coupling.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_direct_messages: Option<usize>,
+
+    /// Per-member posting budget, enforced by `MessagesV1::apply_delta`.
+    /// `None` (and every configuration signed before the field existed) means
+    /// no limit. Follows the `Option` + `skip_serializing_if` pattern above for
+    /// the same reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_mode: Option<SlowMode>,
+
+    /// Who may post, enforced by `MessagesV1::apply_delta`. `None` (and every
+    /// configuration signed before the field existed) means every member.
+    /// Follows the `Option` + `skip_serializing_if` pattern above.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub posting_policy: Option<PostingPolicy>,
+
+    /// When the owner set `posting_policy`; the policy judges only messages
+    /// from then on (see [`Configuration::posting_permits`]). `None` (and
+    /// every configuration signed before the field existed) judges every
+    /// retained message. Follows the `Option` + `skip_serializing_if` pattern
+    /// above.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub posting_policy_since: Option<SystemTime>,
+}
+
+impl Configuration {
+    /// Whether `message` stands under the room's posting policy: there is
+    /// none, the message is older than the policy, or the policy permits it.
+    pub fn posting_permits(&self, message: &MessageV1, owner_id: MemberId) -> bool {
+        match &self.posting_policy {
+            None => true,
+            Some(policy) => {
+                self.posting_policy_since
+                    .is_some_and(|since| message.time < since)
+                    || policy.permits(message, owner_id)
+            }
+        }
+    }
+
+    /// Set the posting policy, restarting its cutoff at `now` if it changed.
+    pub fn set_posting_policy(&mut self, policy: Option<PostingPolicy>, now: SystemTime) {
+        if self.posting_policy != policy {
+            self.posting_policy_since = policy.as_ref().map(|_| now);
+            self.posting_policy = policy;
+        }
+    }
+}
+
+/// Who may post in an announcement-style room.
+///
+/// Restricts ordinary messages only: every member may still send actions
+/// (reactions, poll votes) and events (joins, leaves). A private room's actions
+/// are encrypted, so the contract cannot tell a reaction from an edit; that is
+/// harmless, because edits and deletes only ever apply to the sender's own
+/// messages. The owner may always post.
+///
+/// Judges the messages timestamped from `Configuration::posting_policy_since`
+/// on, re-checked on every apply like pin authority: what members said before
+/// the room became an announcement room stays. That is what makes it
+/// converge: a peer that saw a later message before the new configuration
+/// drops it just as one that saw them the other way round refuses it. The
+/// cutoff reads the author's timestamp, so a member left out can still slip
+/// in a post back-dated to before the change; it lands in history behind
+/// everything said since, and retention trims it first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum PostingPolicy {
+    /// Only the room owner posts.
+    OwnerOnly,
+    /// The owner and these members post. At most `max_members` entries.
+    Allowlist(Vec<MemberId>),
+}
+
+impl PostingPolicy {
+    /// Whether `member` may post ordinary messages.
+    pub fn may_post(&self, member: MemberId, owner_id: MemberId) -> bool {
+        member == owner_id
+            || match self {
+                PostingPolicy::OwnerOnly => false,
+                PostingPolicy::Allowlist(posters) => posters.contains(&member),
+            }
+    }
+
+    /// Whether `message` is allowed under this policy: it is an action or an
+    /// event, or its author may post.
+    pub fn permits(&self, message: &MessageV1, owner_id: MemberId) -> bool {
+        message.content.is_action()
+            || message.content.is_event()
+            || self.may_post(message.author, owner_id)
+    }
+}
+
+impl fmt::Display for PostingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PostingPolicy::OwnerOnly => write!(f, "owner only"),
+            PostingPolicy::Allowlist(posters) => {
+                write!(f, "owner and {} listed member(s)", posters.len())
+            }
+        }
+    }
+}
+
+/// How many messages one member may have in a room at a time.
+///
+/// Enforced deterministically in `MessagesV1::apply_delta`, before the
+/// `max_recent_messages` trim: of each member's messages, only the newest
+/// `max_messages` within any [`SlowModeWindow`] are kept. A flood therefore
+/// displaces the flooder's own earlier messages rather than everyone else's
+/// history. The room owner and event messages (joins, leaves) are exempt, as
+/// are pinned messages and their pin actions.
+///
+/// Keeping the NEWEST messages, not the first ones, is what keeps the merge
+/// convergent: whether a message survives depends only on that member's newer
+/// messages, and any peer still holding it will also be offered those (they
+/// lie above its retention horizon), so every peer drops it. Keeping the
+/// oldest would make a message's fate depend on older messages a peer at
+/// capacity may never be offered, and re-open the resend loop described on
+/// `RetentionHorizon`.
+///
+/// Honest clients check [`crate::room_state::message::MessagesV1::slow_mode_retry_after`]
+/// before sending, so a member sees "slow mode" instead of silently losing an
+/// earlier message.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlowMode {
+    pub max_messages: u32,
+    pub window: SlowModeWindow,
+}
+
+/// The span a [`SlowMode`] budget covers. [`Retained`](Self::Retained) is the
+/// default: it is the only window the contract can hold a member to.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SlowModeWindow {
+    /// A sliding window of this many seconds, measured on the messages' own
+    /// timestamps (a contract has no clock). Advisory: the author picks those
+    /// timestamps, so a member who spaces them out, back-dating a burst, gets
+    /// past it. It paces honest clients, which wait out
+    /// `slow_mode_retry_after`; use `Retained` to bound a flood.
+    Seconds(u64),
+    /// The room's retained messages: at most `max_messages` of
+    /// `recent_messages` belong to any one member, whatever their timestamps.
+    #[default]
+    Retained,
+}
+
+impl SlowMode {
+    /// The window length, or `None` for [`SlowModeWindow::Retained`].
+    pub fn window_duration(&self) -> Option<std::time::Duration> {
+        match self.window {
+            SlowModeWindow::Seconds(secs) => Some(std::time::Duration::from_secs(secs)),
+            SlowModeWindow::Retained => None,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.max_messages > 0 && self.window != SlowModeWindow::Seconds(0)
+    }
+}
+
+impl fmt::Display for SlowMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.window {
+            SlowModeWindow::Seconds(secs) => {
+                write!(f, "{} message(s) per {}s", self.max_messages, secs)
+            }
+            SlowModeWindow::Retained => write!(
+                f,
+                "{} message(s) among the retained messages",
+                self.max_messages
+            ),
+        }
+    }
 }
 
 /// Global cap applied to `direct_messages.messages` when a room's
@@ -300,12 +483,13 @@ pub struct Configuration {
 ///
 /// # This bound is interim
 ///
-/// The intended long-term fix is moving DMs out of the room contract entirely,
-/// into dedicated per-member contracts. This cap exists to bound the damage
-/// until then, so it is deliberately the simplest correct thing: a count, a
+/// DMs now go to per-member inbox contracts ([`crate::inbox`]), which bound
+/// each inbox on its own, and clients move the in-room DMs addressed to them
+/// into their inbox. This cap only bounds what older clients still post to
+/// the room, so it stays deliberately the simplest correct thing: a count, a
 /// deterministic order, and a horizon. The trim lives wholly inside
 /// `direct_messages.rs` and `post_apply_cleanup` was not touched, so the
-/// retention logic is a clean deletion later.
+/// retention logic is a clean deletion once no client writes DMs here.
 ///
 /// One piece does NOT delete cleanly, and it is worth knowing now:
 /// [`Configuration::max_direct_messages`] is a signed wire field. Once any
@@ -603,4 +787,91 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Invalid configuration values");
     }
+
+    #[test]
+    fn posting_policy_round_trips_and_bounds_its_allowlist() {
+        let owner_signing_key = SigningKey::generate(&mut OsRng);
+        let parameters = ChatRoomParametersV1 {
+            owner: owner_signing_key.verifying_key(),
+        };
+        let configuration = Configuration {
+            max_members: 2,
+            ..Configuration::default()
+        };
+        let authorized_configuration =
+            AuthorizedConfigurationV1::new(configuration.clone(), &owner_signing_key);
+        let parent_state = ChatRoomStateV1 {
+            configuration: authorized_configuration.clone(),
+            ..Default::default()
+        };
+        let member = |n: i64| MemberId(FastHash(n));
+
+        for (policy, valid) in [
+            (PostingPolicy::OwnerOnly, true),
+            (PostingPolicy::Allowlist(vec![member(1), member(2)]), true),
+            (
+                PostingPolicy::Allowlist(vec![member(1), member(2), member(3)]),
+                false,
+            ),
+        ] {
+            let new_configuration = Configuration {
+                configuration_version: 2,
+                posting_policy: Some(policy.clone()),
+                ..configuration.clone()
+            };
+            let signed = AuthorizedConfigurationV1::new(new_configuration, &owner_signing_key);
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(&signed, &mut bytes).unwrap();
+            let decoded: AuthorizedConfigurationV1 =
+                ciborium::de::from_reader(bytes.as_slice()).unwrap();
+            assert!(decoded.verify_signature(&parameters.owner).is_ok());
+
+            let result = authorized_configuration.clone().apply_delta(
+                &parent_state,
+                &parameters,
+                &Some(signed),
+            );
+            assert_eq!(result.is_ok(), valid, "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn test_apply_delta_rejects_an_empty_slow_mode_budget() {
+        let owner_signing_key = SigningKey::generate(&mut OsRng);
+        let parameters = ChatRoomParametersV1 {
+            owner: owner_signing_key.verifying_key(),
+        };
+        let configuration = Configuration::default();
+        let authorized_configuration =
+            AuthorizedConfigurationV1::new(configuration.clone(), &owner_signing_key);
+        let parent_state = ChatRoomStateV1 {
+            configuration: authorized_configuration.clone(),
+            ..Default::default()
+        };
+
+        for (slow_mode, valid) in [
+            ((0, SlowModeWindow::Seconds(60)), false),
+            ((5, SlowModeWindow::Seconds(0)), false),
+            ((5, SlowModeWindow::Seconds(60)), true),
+            ((5, SlowModeWindow::Retained), true),
+        ] {
+            let new_configuration = Configuration {
+                configuration_version: 2,
+                slow_mode: Some(SlowMode {
+                    max_messages: slow_mode.0,
+                    window: slow_mode.1,
+                }),
+                ..configuration.clone()
+            };
+            let result = authorized_configuration.clone().apply_delta(
+                &parent_state,
+                &parameters,
+                &Some(AuthorizedConfigurationV1::new(
+                    new_configuration,
+                    &owner_signing_key,
+                )),
+            );
+            assert_eq!(result.is_ok(), valid, "{slow_mode:?}");
+        }
+    }
 }
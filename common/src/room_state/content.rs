@@ -11,14 +11,17 @@
 //! - Breaking format changes: Bump the version constant for that type
 
 use crate::room_state::message::MessageId;
+use crate::room_state::privacy::SealedBytes;
 use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
 
 /// Content type constants
 pub const CONTENT_TYPE_TEXT: u32 = 1;
 pub const CONTENT_TYPE_ACTION: u32 = 2;
 pub const CONTENT_TYPE_REPLY: u32 = 3;
 pub const CONTENT_TYPE_EVENT: u32 = 4;
-// Future: CONTENT_TYPE_BLOB = 5, CONTENT_TYPE_POLL = 6, etc.
+pub const CONTENT_TYPE_BLOB: u32 = 5;
+pub const CONTENT_TYPE_POLL: u32 = 6;
 
 /// Current version for text content
 pub const TEXT_CONTENT_VERSION: u32 = 1;
@@ -32,16 +35,32 @@ pub const REPLY_CONTENT_VERSION: u32 = 1;
 /// Current version for event content
 pub const EVENT_CONTENT_VERSION: u32 = 1;
 
+/// Current version for blob content
+pub const BLOB_CONTENT_VERSION: u32 = 1;
+
+/// Current version for poll content
+pub const POLL_CONTENT_VERSION: u32 = 1;
+
 /// Event type constants
 pub const EVENT_TYPE_JOIN: u32 = 1;
-// Future: EVENT_TYPE_LEAVE = 2, etc.
+/// A member leaving voluntarily. A member whose latest message is a leave
+/// event is pruned like an inactive one, while their messages stay; see
+/// `MessagesV1::departed_members` and `DeparturesV1`.
+pub const EVENT_TYPE_LEAVE: u32 = 2;
 
 /// Action type constants
 pub const ACTION_TYPE_EDIT: u32 = 1;
 pub const ACTION_TYPE_DELETE: u32 = 2;
 pub const ACTION_TYPE_REACTION: u32 = 3;
 pub const ACTION_TYPE_REMOVE_REACTION: u32 = 4;
-// Future: ACTION_TYPE_PIN = 5, ACTION_TYPE_REPLY = 6, etc.
+/// Pin and unpin are the only actions the contract itself reads: they are
+/// always sent public (even in private rooms), restricted to the owner and
+/// owner-appointed deputies, and exempt their target from retention. See
+/// `MessagesV1::pins`.
+pub const ACTION_TYPE_PIN: u32 = 5;
+// Future: ACTION_TYPE_REPLY = 6, etc.
+pub const ACTION_TYPE_POLL_VOTE: u32 = 7;
+pub const ACTION_TYPE_UNPIN: u32 = 8;
 
 /// Text message content (content_type = 1)
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -227,6 +246,29 @@ impl ActionContentV1 {
         }
     }
 
+    /// Create a pin action
+    pub fn pin(target: MessageId) -> Self {
+        Self {
+            action_type: ACTION_TYPE_PIN,
+            target,
+            payload: Vec::new(),
+        }
+    }
+
+    /// Create an unpin action
+    pub fn unpin(target: MessageId) -> Self {
+        Self {
+            action_type: ACTION_TYPE_UNPIN,
+            target,
+            payload: Vec::new(),
+        }
+    }
+
+    /// Check if this is a pin or unpin action
+    pub fn is_pin_action(&self) -> bool {
+        self.action_type == ACTION_TYPE_PIN || self.action_type == ACTION_TYPE_UNPIN
+    }
+
     /// Encode to CBOR bytes
     pub fn encode(&self) -> Vec<u8> {
         encode_cbor(self)
@@ -246,6 +288,18 @@ impl ActionContentV1 {
         }
     }
 
+    /// Create a poll vote action.
+    ///
+    /// `choices` are zero-based indices into [`PollContentV1::options`]. An
+    /// empty list retracts the voter's earlier ballot.
+    pub fn poll_vote(target: MessageId, choices: Vec<u32>) -> Self {
+        Self {
+            action_type: ACTION_TYPE_POLL_VOTE,
+            target,
+            payload: encode_cbor(&PollVotePayload { choices }),
+        }
+    }
+
     /// Get the reaction payload if this is a reaction or remove_reaction action
     pub fn reaction_payload(&self) -> Option<ReactionPayload> {
         if self.action_type == ACTION_TYPE_REACTION
@@ -256,6 +310,15 @@ impl ActionContentV1 {
             None
         }
     }
+
+    /// Get the vote payload if this is a poll vote action
+    pub fn poll_vote_payload(&self) -> Option<PollVotePayload> {
+        if self.action_type == ACTION_TYPE_POLL_VOTE {
+            ciborium::from_reader(&self.payload[..]).ok()
+        } else {
+            None
+        }
+    }
 }
 
 /// Payload for edit actions
@@ -270,6 +333,13 @@ pub struct ReactionPayload {
     pub emoji: String,
 }
 
+/// Payload for poll vote actions
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PollVotePayload {
+    /// Zero-based option indices. Empty means "retract my vote".
+    pub choices: Vec<u32>,
+}
+
 /// Reply message content (content_type = 3)
 ///
 /// A reply references a target message by `target_message_id`. It ALSO carries
@@ -340,6 +410,12 @@ impl EventContentV1 {
         }
     }
 
+    pub fn leave() -> Self {
+        Self {
+            event_type: EVENT_TYPE_LEAVE,
+        }
+    }
+
     pub fn encode(&self) -> Vec<u8> {
         encode_cbor(self)
     }
@@ -349,6 +425,121 @@ impl EventContentV1 {
     }
 }
 
+/// Attachment message content (content_type = 5)
+///
+/// The bytes live in a separate blob contract (see [`crate::blob`]); this is
+/// only the reference. Readers fetch `contract_id`, check the bytes against
+/// `hash`, and — when `key` is set — unseal the per-attachment key with the
+/// room secret and decrypt.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct BlobContentV1 {
+    /// Instance id of the blob contract holding the stored bytes.
+    pub contract_id: [u8; 32],
+    /// BLAKE3 hash of the stored bytes (the blob contract's parameters).
+    pub hash: [u8; 32],
+    /// Size of the attachment itself, before any encryption.
+    pub size: u64,
+    pub mime_type: String,
+    pub filename: String,
+    /// Per-attachment key, sealed with the room secret. `None` when the
+    /// stored bytes are the plaintext (public rooms).
+    pub key: Option<SealedBytes>,
+}
+
+impl BlobContentV1 {
+    pub fn encode(&self) -> Vec<u8> {
+        encode_cbor(self)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        decode_cbor(data, "BlobContentV1")
+    }
+
+    /// Whether this attachment should be previewed inline as an image.
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
+
+    /// `📎 name (size)` — what a client without a preview shows.
+    pub fn summary(&self) -> String {
+        format!("📎 {} ({})", self.filename, format_byte_size(self.size))
+    }
+}
+
+/// Human-readable byte count, e.g. `512 B`, `12.3 KiB`, `4.0 MiB`.
+pub fn format_byte_size(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b < KIB {
+        format!("{} B", bytes)
+    } else if b < KIB * KIB {
+        format!("{:.1} KiB", b / KIB)
+    } else {
+        format!("{:.1} MiB", b / (KIB * KIB))
+    }
+}
+
+/// Poll message content (content_type = 6)
+///
+/// The poll itself is an ordinary message; votes are
+/// [`ACTION_TYPE_POLL_VOTE`] actions targeting it, folded by
+/// `MessagesV1::rebuild_actions_state` and counted by `MessagesV1::poll_tally`.
+/// The contract never reads this struct, so option counts and close times are
+/// enforced when tallying, not on arrival.
+///
+/// `closes_at` is compared against each vote message's own signed `time`, never
+/// against the wall clock, so every peer arrives at the same tally.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PollContentV1 {
+    pub question: String,
+    pub options: Vec<String>,
+    /// Whether a ballot may select more than one option.
+    pub multiple_choice: bool,
+    /// Votes cast after this time are ignored. `None` means the poll never closes.
+    pub closes_at: Option<SystemTime>,
+}
+
+impl PollContentV1 {
+    pub fn new(
+        question: String,
+        options: Vec<String>,
+        multiple_choice: bool,
+        closes_at: Option<SystemTime>,
+    ) -> Self {
+        Self {
+            question,
+            options,
+            multiple_choice,
+            closes_at,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        encode_cbor(self)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        decode_cbor(data, "PollContentV1")
+    }
+
+    /// Whether a vote cast at `time` still counts.
+    pub fn is_open_at(&self, time: SystemTime) -> bool {
+        self.closes_at.is_none_or(|closes_at| time <= closes_at)
+    }
+
+    /// Whether `choices` is a well-formed ballot for this poll: every index is
+    /// in range, none repeats, and a single-choice poll gets at most one.
+    /// An empty ballot (a retraction) is always well-formed.
+    pub fn accepts_choices(&self, choices: &[u32]) -> bool {
+        if !self.multiple_choice && choices.len() > 1 {
+            return false;
+        }
+        choices.iter().enumerate().all(|(i, &choice)| {
+            (choice as usize) < self.options.len() && !choices[..i].contains(&choice)
+        })
+    }
+}
+
 /// Decoded message content for client-side processing
 #[derive(Clone, PartialEq, Debug)]
 pub enum DecodedContent {
@@ -360,6 +551,10 @@ pub enum DecodedContent {
     Reply(ReplyContentV1),
     /// Room event (join, leave, etc.)
     Event(EventContentV1),
+    /// Attachment stored in a separate blob contract
+    Blob(BlobContentV1),
+    /// Poll (votes arrive as separate actions)
+    Poll(PollContentV1),
     /// Unknown content type - preserved for round-tripping but displayed as placeholder
     Unknown {
         content_type: u32,
@@ -386,11 +581,13 @@ impl DecodedContent {
         }
     }
 
-    /// Get the text content if this is a text or reply message
+    /// Get the text content if this is a text or reply message, or the
+    /// question if this is a poll
     pub fn as_text(&self) -> Option<&str> {
         match self {
             Self::Text(text) => Some(&text.text),
             Self::Reply(reply) => Some(&reply.text),
+            Self::Poll(poll) => Some(&poll.question),
             _ => None,
         }
     }
@@ -417,6 +614,9 @@ impl DecodedContent {
                         .unwrap_or_else(|| "?".to_string());
                     format!("[Remove reaction {} from {}]", emoji, action.target)
                 }
+                ACTION_TYPE_POLL_VOTE => format!("[Vote on poll {}]", action.target),
+                ACTION_TYPE_PIN => format!("[Pin message {}]", action.target),
+                ACTION_TYPE_UNPIN => format!("[Unpin message {}]", action.target),
                 _ => format!(
                     "[Unknown action type {} on {}]",
                     action.action_type, action.target
@@ -424,8 +624,11 @@ impl DecodedContent {
             },
             Self::Event(event) => match event.event_type {
                 EVENT_TYPE_JOIN => "joined the room".to_string(),
+                EVENT_TYPE_LEAVE => "left the room".to_string(),
                 _ => format!("[Unknown event type {}]", event.event_type),
             },
+            Self::Blob(blob) => blob.summary(),
+            Self::Poll(poll) => format!("📊 {} ({})", poll.question, poll.options.join(" / ")),
             Self::Unknown {
                 content_type,
                 content_version,
@@ -739,6 +942,19 @@ mod tests {
         assert_eq!(decoded.action_type, ACTION_TYPE_DELETE);
     }
 
+    #[test]
+    fn test_pin_and_unpin_action_roundtrip() {
+        for action in [
+            ActionContentV1::pin(test_message_id()),
+            ActionContentV1::unpin(test_message_id()),
+        ] {
+            let decoded = ActionContentV1::decode(&action.encode()).unwrap();
+            assert_eq!(action, decoded);
+            assert!(decoded.is_pin_action());
+        }
+        assert!(!ActionContentV1::delete(test_message_id()).is_pin_action());
+    }
+
     #[test]
     fn test_reaction_action_roundtrip() {
         let action = ActionContentV1::reaction(test_message_id(), "👍".to_string());
@@ -792,6 +1008,59 @@ mod tests {
         assert!(unknown.to_display_string().contains("Unsupported"));
     }
 
+    #[test]
+    fn test_poll_content_roundtrip() {
+        let poll = PollContentV1::new(
+            "Ship on Friday?".to_string(),
+            vec!["Yes".to_string(), "No".to_string()],
+            false,
+            Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_800_000_000)),
+        );
+        let decoded = PollContentV1::decode(&poll.encode()).unwrap();
+        assert_eq!(poll, decoded);
+
+        let dc = DecodedContent::Poll(decoded);
+        assert_eq!(dc.to_display_string(), "📊 Ship on Friday? (Yes / No)");
+        assert_eq!(dc.as_text(), Some("Ship on Friday?"));
+    }
+
+    #[test]
+    fn test_poll_vote_action_roundtrip() {
+        let action = ActionContentV1::poll_vote(test_message_id(), vec![0, 2]);
+        let decoded = ActionContentV1::decode(&action.encode()).unwrap();
+        assert_eq!(action, decoded);
+        assert_eq!(decoded.poll_vote_payload().unwrap().choices, vec![0, 2]);
+        assert!(
+            decoded.reaction_payload().is_none(),
+            "a vote must not be mistaken for a reaction"
+        );
+    }
+
+    #[test]
+    fn test_poll_accepts_choices() {
+        let options = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let single = PollContentV1::new("q".to_string(), options.clone(), false, None);
+        let multi = PollContentV1::new("q".to_string(), options, true, None);
+
+        assert!(single.accepts_choices(&[]), "a retraction is always valid");
+        assert!(single.accepts_choices(&[2]));
+        assert!(!single.accepts_choices(&[0, 1]));
+        assert!(!single.accepts_choices(&[3]), "out of range");
+
+        assert!(multi.accepts_choices(&[0, 2]));
+        assert!(!multi.accepts_choices(&[1, 1]), "duplicate choice");
+        assert!(!multi.accepts_choices(&[0, 7]));
+    }
+
+    #[test]
+    fn test_poll_close_time_is_inclusive() {
+        let closes_at = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(100);
+        let poll = PollContentV1::new("q".to_string(), vec![], false, Some(closes_at));
+        assert!(poll.is_open_at(closes_at));
+        assert!(!poll.is_open_at(closes_at + std::time::Duration::from_secs(1)));
+        assert!(PollContentV1::new("q".to_string(), vec![], false, None).is_open_at(closes_at));
+    }
+
     #[test]
     fn test_event_content_roundtrip() {
         let event = EventContentV1::join();
@@ -806,6 +1075,22 @@ mod tests {
         assert_eq!(dc.to_display_string(), "joined the room");
     }
 
+    #[test]
+    fn test_leave_event_roundtrip() {
+        let event = EventContentV1::leave();
+        let decoded = EventContentV1::decode(&event.encode()).unwrap();
+        assert_eq!(decoded.event_type, EVENT_TYPE_LEAVE);
+        assert_eq!(
+            DecodedContent::Event(decoded).to_display_string(),
+            "left the room"
+        );
+
+        let body = crate::room_state::message::RoomMessageBody::leave_event();
+        assert!(body.is_event());
+        assert!(body.is_leave_event());
+        assert!(!crate::room_state::message::RoomMessageBody::join_event().is_leave_event());
+    }
+
     #[test]
     fn test_join_event_message_body() {
         let body = crate::room_state::message::RoomMessageBody::join_event();
@@ -814,4 +1099,28 @@ mod tests {
         let decoded = body.decode_content().unwrap();
         assert!(matches!(decoded, DecodedContent::Event(_)));
     }
+
+    #[test]
+    fn test_blob_content_roundtrip() {
+        let blob = BlobContentV1 {
+            contract_id: [7; 32],
+            hash: [9; 32],
+            size: 12_595,
+            mime_type: "image/png".to_string(),
+            filename: "screenshot.png".to_string(),
+            key: Some(SealedBytes::Private {
+                ciphertext: vec![1, 2, 3],
+                nonce: [0; 12],
+                secret_version: 2,
+                declared_len_bytes: 32,
+            }),
+        };
+        let decoded = BlobContentV1::decode(&blob.encode()).unwrap();
+        assert_eq!(decoded, blob);
+        assert!(decoded.is_image());
+        assert_eq!(
+            DecodedContent::Blob(decoded).to_display_string(),
+            "📎 screenshot.png (12.3 KiB)"
+        );
+    }
 }
@@ -0,0 +1,180 @@
+use crate::room_state::member::{AuthorizedMember, MemberId};
+use crate::room_state::signed_record;
+use crate::room_state::ChatRoomParametersV1;
+use crate::ChatRoomStateV1;
+use ed25519_dalek::VerifyingKey;
+use freenet_scaffold::ComposableState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Members who left the room, kept so what they said stays readable.
+///
+/// A member leaves by posting a leave event
+/// ([`MessagesV1::departed_members`]), and `ChatRoomStateV1::post_apply_cleanup`
+/// then prunes them from `members` like an inactive member. Their messages,
+/// the leave event among them, are not theirs to lose with the membership:
+/// the room keeps the leaver's member entry here instead, so the messages and
+/// their `member_info` still verify against the leaver's key. Messages they
+/// post after leaving are refused.
+///
+/// A departure lasts while the member is not back in the room, is not banned,
+/// their latest retained message is still the leave event, and the entry's
+/// signature verifies against the inviter's CURRENT key. The inviter is
+/// exempt from inactivity-prune for as long, so it does. Once retention trims
+/// the leave event the departure goes, and with it the leaver's nickname.
+/// Every departure needs a retained leave event, so there are at most
+/// `max_recent_messages` of them.
+///
+/// [`MessagesV1::departed_members`]: crate::room_state::message::MessagesV1::departed_members
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct DeparturesV1(pub Vec<AuthorizedMember>);
+
+impl DeparturesV1 {
+    /// Whether `entry` keeps its member's messages once they are out of
+    /// `members`: they are `departed`, not `banned`, and the entry verifies
+    /// against the inviter's CURRENT key. Decides both the inviter's prune
+    /// exemption and whether the departure survives cleanup.
+    pub fn departure_is_valid(
+        entry: &AuthorizedMember,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        departed: &HashSet<MemberId>,
+        banned: &HashSet<MemberId>,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        let member_id = entry.member.id();
+        entry.member.owner_member_id == owner_id
+            && departed.contains(&member_id)
+            && !banned.contains(&member_id)
+            && signed_record::signer_key(entry.member.invited_by, members_by_id, owner_id, owner_vk)
+                .is_some_and(|key| entry.verify_signature(&key).is_ok())
+    }
+
+    /// The key each departed member signed with, by member id.
+    pub fn keys(&self) -> HashMap<MemberId, VerifyingKey> {
+        self.0
+            .iter()
+            .map(|entry| (entry.member.id(), entry.member.member_vk))
+            .collect()
+    }
+}
+
+impl ComposableState for DeparturesV1 {
+    type ParentState = ChatRoomStateV1;
+    // BTreeSet for canonical summary bytes; see the note on `BansV1`.
+    type Summary = BTreeSet<MemberId>;
+    type Delta = Vec<AuthorizedMember>;
+    type Parameters = ChatRoomParametersV1;
+
+    /// Checks the count, that every departure is of someone whose latest
+    /// retained message is a leave event and who is not a member, and each
+    /// entry's signature when the inviter is the owner or a current member.
+    /// Other inviters are skipped as for bans; cleanup drops the departure if
+    /// their key never matches.
+    fn verify(
+        &self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+    ) -> Result<(), String> {
+        let max = parent_state.configuration.configuration.max_recent_messages;
+        if self.0.len() > max {
+            return Err(format!(
+                "Number of departures ({}) exceeds the maximum allowed ({})",
+                self.0.len(),
+                max
+            ));
+        }
+        let members_by_id = parent_state.members.members_by_member_id();
+        let departed = parent_state.recent_messages.departed_members();
+        for entry in &self.0 {
+            let member_id = entry.member.id();
+            if !departed.contains(&member_id) || members_by_id.contains_key(&member_id) {
+                return Err(format!(
+                    "Departure recorded for {:?}, who has not left",
+                    member_id
+                ));
+            }
+        }
+        verify_signatures(&self.0, parent_state, parameters)
+    }
+
+    fn summarize(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+    ) -> Self::Summary {
+        self.0.iter().map(|entry| entry.member.id()).collect()
+    }
+
+    fn delta(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+        old_state_summary: &Self::Summary,
+    ) -> Option<Self::Delta> {
+        let delta: Vec<AuthorizedMember> = self
+            .0
+            .iter()
+            .filter(|entry| !old_state_summary.contains(&entry.member.id()))
+            .cloned()
+            .collect();
+        if delta.is_empty() {
+            None
+        } else {
+            Some(delta)
+        }
+    }
+
+    /// Adds new departures after checking their signatures. Whether the
+    /// member has left is left to `post_apply_cleanup`, and a delta larger
+    /// than the cap is refused as a flood.
+    fn apply_delta(
+        &mut self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+        delta: &Option<Self::Delta>,
+    ) -> Result<(), String> {
+        let Some(delta) = delta else {
+            return Ok(());
+        };
+        let max = parent_state.configuration.configuration.max_recent_messages;
+        if delta.len() > max {
+            return Err(format!(
+                "Departure delta of {} exceeds max_recent_messages ({}); refusing to process a flood",
+                delta.len(),
+                max
+            ));
+        }
+        let mut known: BTreeSet<MemberId> = self.0.iter().map(|entry| entry.member.id()).collect();
+        let new: Vec<AuthorizedMember> = delta
+            .iter()
+            .filter(|entry| known.insert(entry.member.id()))
+            .cloned()
+            .collect();
+        verify_signatures(&new, parent_state, parameters)
+            .map_err(|e| format!("Invalid delta: {}", e))?;
+        self.0.extend(new);
+        self.0.sort_by_key(|entry| entry.member.id());
+        Ok(())
+    }
+}
+
+fn verify_signatures(
+    entries: &[AuthorizedMember],
+    parent_state: &ChatRoomStateV1,
+    parameters: &ChatRoomParametersV1,
+) -> Result<(), String> {
+    let members_by_id = parent_state.members.members_by_member_id();
+    let owner_id = parameters.owner_id();
+    for entry in entries {
+        if let Some(key) = signed_record::signer_key(
+            entry.member.invited_by,
+            &members_by_id,
+            owner_id,
+            &parameters.owner,
+        ) {
+            entry.verify_signature(&key)?;
+        }
+    }
+    Ok(())
+}
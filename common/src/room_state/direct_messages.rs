@@ -6,6 +6,11 @@
 //! contract, DMs live in the room contract and are scoped to the room
 //! they're sent in by design.
 //!
+//! Current clients send DMs to the recipient's per-member inbox instead
+//! ([`crate::inbox`]), which reuses this module's message and purge
+//! formats, and move the DMs addressed to them out of this set. It stays
+//! for older clients and for DMs not yet moved.
+//!
 //! # State shape
 //!
 //! - [`DirectMessagesV1::messages`]: a flat list of
@@ -96,9 +101,9 @@
 //! - `Configuration::effective_max_direct_messages`: owner-tunable GLOBAL cap
 //!   on `messages`, defaulting to
 //!   [`crate::room_state::configuration::DEFAULT_MAX_DIRECT_MESSAGES`] (300).
-//!   An INTERIM bound — the intended fix is moving DMs into per-member
-//!   contracts — so it is deliberately the simplest correct thing and is kept
-//!   wholly inside this module. Added
+//!   An INTERIM bound — new DMs go to per-member inbox contracts
+//!   ([`crate::inbox`]) — so it is deliberately the simplest correct thing
+//!   and is kept wholly inside this module. Added
 //!   for freenet/river#519: the per-pair cap below bounds any one
 //!   conversation but nothing bounded the set as a whole, and because
 //!   `ChatRoomStateV1::post_apply_cleanup` exempts every DM participant from
@@ -312,6 +317,18 @@ pub struct RecipientPurges {
     /// [`build_recipient_purges_signed_bytes`]).
     #[serde(default)]
     pub purged: Vec<PurgeToken>,
+
+    /// Unix timestamp below which every DM counts as purged, token or not,
+    /// so a recipient can drop the tokens of older DMs instead of carrying
+    /// them forever. `0` (no horizon) is left out of the encoding and the
+    /// signed bytes, so envelopes without one are unchanged. Only inboxes
+    /// ([`crate::inbox`]) honour it; the room refuses envelopes that set it.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub purged_before: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
 }
 
 // ---------------------------------------------------------------------------
@@ -369,6 +386,7 @@ pub fn build_direct_message_signed_bytes(
 ///     version_le_u64              ( 8 bytes)
 ///     purged_count_le_u32         ( 4 bytes)
 ///     purged                      (16 bytes per entry, in declared order)
+///     purged_before_le_u64        ( 8 bytes, only when non-zero)
 /// ```
 ///
 /// Each `purged` entry is encoded as 16 raw bytes (the [`PurgeToken`])
@@ -395,6 +413,9 @@ pub fn build_recipient_purges_signed_bytes(
     for entry in &state.purged {
         out.extend_from_slice(&entry.0);
     }
+    if state.purged_before != 0 {
+        out.extend_from_slice(&state.purged_before.to_le_bytes());
+    }
     Ok(out)
 }
 
@@ -613,6 +634,7 @@ pub fn advance_recipient_purges(
         RecipientPurges {
             version: next_version,
             purged: combined,
+            purged_before: previous.map_or(0, |p| p.state.purged_before),
         },
     )
 }
@@ -828,6 +850,12 @@ impl ComposableState for DirectMessagesV1 {
                     purges.recipient_id
                 ));
             }
+            if purges.state.purged_before != 0 {
+                return Err(format!(
+                    "DM purges for {:?}: purge horizons are for inboxes only",
+                    purges.recipient_id
+                ));
+            }
             if purges.state.purged.len() > MAX_PURGED_TOMBSTONES_PER_RECIPIENT {
                 return Err(format!(
                     "DM purges for {:?} exceed cap: {} > {}",
@@ -1075,6 +1103,12 @@ impl ComposableState for DirectMessagesV1 {
                     advance.recipient_id
                 ));
             }
+            if advance.state.purged_before != 0 {
+                return Err(format!(
+                    "DM purges for {:?}: purge horizons are for inboxes only",
+                    advance.recipient_id
+                ));
+            }
             if advance.state.purged.len() > MAX_PURGED_TOMBSTONES_PER_RECIPIENT {
                 return Err(format!(
                     "DM purges for {:?} exceed cap: {} > {}",
@@ -0,0 +1,747 @@
+use crate::room_state::invite_use::{InviteUse, InviteUsesV1};
+use crate::room_state::member::{AuthorizedMember, MemberId, MembersV1};
+use crate::room_state::member_info::MemberInfoV1;
+use crate::room_state::message::{AuthorizedMessageV1, MessagesV1};
+use crate::room_state::privacy::SealedBytes;
+use crate::room_state::signed_record::{self, Overflow, SignedRecord};
+use crate::room_state::ChatRoomParametersV1;
+use crate::util::{sign_struct, verify_struct};
+use crate::ChatRoomStateV1;
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use freenet_scaffold::util::{fast_hash, FastHash};
+use freenet_scaffold::ComposableState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::SystemTime;
+
+/// Upper bound on an invite label, in (declared plaintext) bytes.
+pub const MAX_INVITE_LABEL_BYTES: usize = 100;
+
+/// Signed policies for outstanding invitations.
+///
+/// An `Invitation` is a bearer credential: it carries the invitee's signing
+/// key and an `AuthorizedMember` the inviter signed, and on its own it stays
+/// valid forever. Whoever issues one may also publish a record here, keyed by
+/// the invitee's member id, that limits it: an expiry time, a use limit, or a
+/// revocation. Without a use limit an invitation admits exactly one member
+/// identity, the invitee. With one, each holder after the first joins as a
+/// fresh member the invitation's key signs in, and the limit counts the
+/// invitee and those members together.
+///
+/// Records form a last-writer-wins register per invitee, ordered by
+/// `(issued_at, id)`. A record is effective while its signature verifies
+/// against the issuer's CURRENT key and, once the invitee is in the room, the
+/// issuer may moderate them ([`MembersV1::is_ban_authorized`], which includes
+/// whoever signed the invitation). `ChatRoomStateV1::post_apply_cleanup` judges
+/// the members who came in through each invitation by its newest effective
+/// record ([`Self::settled`]) and removes those it refuses. A refused
+/// invitee's record stays behind as the tombstone that keeps the link closed.
+///
+/// Expiry and the use limit are judged by each member's join event, the
+/// message `invite accept` posts with the membership: a member is admitted if
+/// it is dated before the expiry and falls within the limit, in join order
+/// with the invitee first. The join is signed by the member, so a modified
+/// client holding an expired link can still back-date it; revocation has no
+/// such gap. A member a limited record admits gets an [`InviteUse`] recorded,
+/// which carries the record and keeps the judgement once the join event is
+/// trimmed.
+///
+/// Stored records are capped at `max_members`. At the cap a newer record is
+/// refused, not the oldest evicted, so a flood cannot reopen a closed link;
+/// `issued_at` is signed by the issuer, though, so a back-dated record still
+/// ranks as old.
+///
+/// [`InviteUse`]: crate::room_state::invite_use::InviteUse
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct InvitesV1(pub Vec<AuthorizedInvite>);
+
+/// What [`InvitesV1::settled`] keeps and refuses.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct SettledInvites {
+    /// Records still to be used or refusing someone, in stored order.
+    pub records: Vec<AuthorizedInvite>,
+    /// One per member a limited record admits, in stored order.
+    pub uses: Vec<InviteUse>,
+    /// Members to remove, with whoever they invited.
+    pub refused: HashSet<MemberId>,
+}
+
+impl InvitesV1 {
+    /// Whether `record`'s signature verifies against the issuer's CURRENT key
+    /// and, if the invitee is a member, the issuer may moderate them.
+    pub fn record_is_effective(
+        record: &AuthorizedInvite,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        signed_record::signed_by_current_key(record, members_by_id, owner_id, owner_vk)
+            && (!members_by_id.contains_key(&record.invite.invitee)
+                || MembersV1::is_ban_authorized(
+                    record.issued_by,
+                    record.invite.invitee,
+                    members_by_id,
+                    member_info,
+                    owner_id,
+                ))
+    }
+
+    /// The records and uses `post_apply_cleanup` keeps, and the members they
+    /// refuse.
+    ///
+    /// The newest effective record per invitee, among the stored records and
+    /// those carried by `uses`, judges the members in through its invitation:
+    /// the invitee and, under a use limit, the members the invitee signed in.
+    /// Each is judged by their earliest join event in `uses` or `messages`
+    /// ([`InvitePolicy::admits`]). A record that admits someone is used: a
+    /// limited one moves into the uses it produces, an unlimited one is
+    /// dropped. The remaining records are capped at `max_members`, keeping
+    /// the oldest; a record past the cap refuses no one.
+    #[allow(clippy::too_many_arguments)]
+    pub fn settled(
+        &self,
+        uses: &InviteUsesV1,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        messages: &MessagesV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+        max_members: usize,
+    ) -> SettledInvites {
+        let candidates: Vec<AuthorizedInvite> = self
+            .0
+            .iter()
+            .chain(uses.0.iter().map(|invite_use| &invite_use.invite))
+            .cloned()
+            .collect();
+        let newest = signed_record::settle(
+            &candidates,
+            |record| {
+                Self::record_is_effective(record, members_by_id, member_info, owner_id, owner_vk)
+            },
+            |_| true,
+            usize::MAX,
+            Overflow::RefuseNewest,
+        );
+        let join_of = |member: MemberId| {
+            let key = members_by_id.get(&member)?.member.member_vk;
+            uses.0
+                .iter()
+                .map(|invite_use| &invite_use.join)
+                .chain(&messages.messages)
+                .filter(|m| {
+                    m.message.author == member
+                        && m.message.room_owner == owner_id
+                        && m.message.content.is_join_event()
+                        && m.validate(&key).is_ok()
+                })
+                .min_by_key(|m| m.order_key())
+        };
+
+        let mut settled = SettledInvites::default();
+        let mut pending = Vec::new();
+        let mut refused_by: HashMap<InviteId, Vec<MemberId>> = HashMap::new();
+        for record in newest {
+            let policy = &record.invite.policy;
+            let invitee = record.invite.invitee;
+            let mut through: Vec<(MemberId, Option<&AuthorizedMessageV1>)> = members_by_id
+                .values()
+                .map(|member| member.member.id())
+                .filter(|member| {
+                    *member == invitee
+                        || (policy.max_uses().is_some()
+                            && members_by_id[member].member.invited_by == invitee)
+                })
+                .map(|member| (member, join_of(member)))
+                .collect();
+            through.sort_by_key(|(member, join)| {
+                (
+                    *member != invitee,
+                    join.map(|join| join.order_key()),
+                    *member,
+                )
+            });
+
+            let mut used = 0;
+            let mut admitted_invitee = false;
+            let mut record_uses = Vec::new();
+            let mut refused = Vec::new();
+            for (member, join) in through {
+                if policy.admits(join, used) {
+                    used += 1;
+                    admitted_invitee |= member == invitee;
+                    if let (true, Some(join)) = (policy.is_limited(), join) {
+                        record_uses.push(InviteUse {
+                            invite: record.clone(),
+                            join: join.clone(),
+                        });
+                    }
+                } else {
+                    refused.push(member);
+                }
+            }
+
+            if !record_uses.is_empty() || (admitted_invitee && !policy.is_limited()) {
+                settled.uses.extend(record_uses);
+                settled.refused.extend(refused);
+            } else {
+                refused_by.insert(record.id(), refused);
+                pending.push(record);
+            }
+        }
+
+        signed_record::cap(&mut pending, max_members, Overflow::RefuseNewest);
+        for record in &pending {
+            settled
+                .refused
+                .extend(refused_by.remove(&record.id()).unwrap_or_default());
+        }
+        settled.records = pending;
+        settled
+            .uses
+            .sort_by_key(|invite_use| invite_use.order_key());
+        settled
+    }
+
+    /// Members whose newest record, stored or carried by one of `uses`, is a
+    /// revocation validly signed by the owner or someone above them in the
+    /// invite chain. `MembersV1` removes these when they first appear;
+    /// deputies and expiry need the converged state and are left to
+    /// `post_apply_cleanup`. Every record is counted when picking the newest,
+    /// so a member removed here is one cleanup would also refuse.
+    pub fn revoked_members(
+        &self,
+        uses: &InviteUsesV1,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> HashSet<MemberId> {
+        let no_deputies = MemberInfoV1::default();
+        members_by_id
+            .keys()
+            .filter(|member| {
+                self.latest_for(uses, **member).is_some_and(|record| {
+                    record.invite.policy == InvitePolicy::Revoked
+                        && Self::record_is_effective(
+                            record,
+                            members_by_id,
+                            &no_deputies,
+                            owner_id,
+                            owner_vk,
+                        )
+                })
+            })
+            .copied()
+            .collect()
+    }
+
+    /// The newest record for `invitee`, stored or carried by one of `uses`,
+    /// effective or not. For display; the contract reads [`Self::settled`].
+    pub fn latest_for<'a>(
+        &'a self,
+        uses: &'a InviteUsesV1,
+        invitee: MemberId,
+    ) -> Option<&'a AuthorizedInvite> {
+        self.0
+            .iter()
+            .chain(uses.0.iter().map(|invite_use| &invite_use.invite))
+            .filter(|record| record.invite.invitee == invitee)
+            .max_by_key(|record| record.order_key())
+    }
+}
+
+impl ComposableState for InvitesV1 {
+    type ParentState = ChatRoomStateV1;
+    // BTreeSet for canonical summary bytes; see the note on `BansV1`.
+    type Summary = BTreeSet<InviteId>;
+    type Delta = Vec<AuthorizedInvite>;
+    type Parameters = ChatRoomParametersV1;
+
+    fn verify(
+        &self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+    ) -> Result<(), String> {
+        signed_record::verify_signatures(&self.0, parent_state, parameters)?;
+        let max = parent_state.configuration.configuration.max_members;
+        if self.0.len() > max {
+            return Err(format!(
+                "Number of invite records ({}) exceeds the maximum allowed ({})",
+                self.0.len(),
+                max
+            ));
+        }
+        for record in &self.0 {
+            record.invite.policy.check_label()?;
+        }
+        Ok(())
+    }
+
+    fn summarize(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+    ) -> Self::Summary {
+        signed_record::summarize(&self.0)
+    }
+
+    fn delta(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+        old_state_summary: &Self::Summary,
+    ) -> Option<Self::Delta> {
+        signed_record::delta(&self.0, old_state_summary)
+    }
+
+    /// Adds new records after checking their signatures and labels.
+    /// Superseded and used records, and the count cap, are left to
+    /// `post_apply_cleanup`; a delta larger than the cap is refused as a
+    /// flood.
+    fn apply_delta(
+        &mut self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+        delta: &Option<Self::Delta>,
+    ) -> Result<(), String> {
+        signed_record::merge(
+            &mut self.0,
+            delta.as_deref(),
+            parent_state.configuration.configuration.max_members,
+            "max_members",
+            parent_state,
+            parameters,
+            |record| record.invite.policy.check_label(),
+        )
+    }
+}
+
+/// An invite policy with the issuer's signature.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuthorizedInvite {
+    pub invite: Invite,
+    pub issued_by: MemberId,
+    pub signature: Signature,
+}
+
+impl AuthorizedInvite {
+    pub fn new(invite: Invite, issued_by: MemberId, signing_key: &SigningKey) -> Self {
+        assert_eq!(MemberId::from(signing_key.verifying_key()), issued_by);
+        let signature = sign_struct(&invite, signing_key);
+        Self {
+            invite,
+            issued_by,
+            signature,
+        }
+    }
+
+    /// Create an AuthorizedInvite with a pre-computed signature.
+    /// Use this when signing is done externally (e.g., via delegate).
+    pub fn with_signature(invite: Invite, issued_by: MemberId, signature: Signature) -> Self {
+        Self {
+            invite,
+            issued_by,
+            signature,
+        }
+    }
+
+    pub fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String> {
+        verify_struct(&self.invite, &self.signature, verifying_key)
+            .map_err(|e| format!("Invalid invite signature: {}", e))
+    }
+
+    pub fn id(&self) -> InviteId {
+        InviteId(fast_hash(&self.signature.to_bytes()))
+    }
+}
+
+impl SignedRecord for AuthorizedInvite {
+    type Id = InviteId;
+    const KIND: &'static str = "Invite";
+
+    fn id(&self) -> InviteId {
+        Self::id(self)
+    }
+
+    fn signer(&self) -> MemberId {
+        self.issued_by
+    }
+
+    fn subject(&self) -> MemberId {
+        self.invite.invitee
+    }
+
+    fn issued_at(&self) -> SystemTime {
+        self.invite.issued_at
+    }
+
+    fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String> {
+        Self::verify_signature(self, verifying_key)
+    }
+}
+
+/// Sets the policy for the invitation whose invitee is `invitee`, as of
+/// `issued_at`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Invite {
+    pub owner_member_id: MemberId,
+    pub invitee: MemberId,
+    pub issued_at: SystemTime,
+    pub policy: InvitePolicy,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum InvitePolicy {
+    /// The invitation may be used, until `expires_at` if set, by up to
+    /// `max_uses` members if set. The label, at most
+    /// [`MAX_INVITE_LABEL_BYTES`], is sealed like a nickname and only helps
+    /// the issuer tell their invitations apart.
+    Open {
+        expires_at: Option<SystemTime>,
+        label: Option<SealedBytes>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_uses: Option<u32>,
+    },
+    /// The invitation may no longer be used, and removes the invitee if they
+    /// already joined.
+    Revoked,
+}
+
+impl InvitePolicy {
+    /// Whether the policy admits a member who joined at `join`, when `used`
+    /// members have been admitted before them. A revoked invitation admits
+    /// no one; an unlimited one admits its invitee whatever their messages; a
+    /// limited one needs a join event dated before the expiry and a free use.
+    pub fn admits(&self, join: Option<&AuthorizedMessageV1>, used: u32) -> bool {
+        match self {
+            InvitePolicy::Open {
+                expires_at,
+                max_uses,
+                ..
+            } => {
+                !self.is_limited()
+                    || (join.is_some_and(|join| {
+                        expires_at.is_none_or(|expires_at| join.message.time < expires_at)
+                    }) && max_uses.is_none_or(|max_uses| used < max_uses))
+            }
+            InvitePolicy::Revoked => false,
+        }
+    }
+
+    /// Whether the policy is open with an expiry or a use limit, and so
+    /// records a use for each member it admits.
+    pub fn is_limited(&self) -> bool {
+        matches!(
+            self,
+            InvitePolicy::Open { expires_at, max_uses, .. }
+                if expires_at.is_some() || max_uses.is_some()
+        )
+    }
+
+    pub fn max_uses(&self) -> Option<u32> {
+        match self {
+            InvitePolicy::Open { max_uses, .. } => *max_uses,
+            InvitePolicy::Revoked => None,
+        }
+    }
+
+    pub fn label(&self) -> Option<&SealedBytes> {
+        match self {
+            InvitePolicy::Open { label, .. } => label.as_ref(),
+            InvitePolicy::Revoked => None,
+        }
+    }
+
+    fn check_label(&self) -> Result<(), String> {
+        match self.label() {
+            Some(label) if label.declared_len() > MAX_INVITE_LABEL_BYTES => Err(format!(
+                "Invite label of {} bytes exceeds the maximum of {}",
+                label.declared_len(),
+                MAX_INVITE_LABEL_BYTES
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, Debug, Ord, PartialOrd)]
+pub struct InviteId(pub FastHash);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+    use crate::room_state::member::MembersDelta;
+    use crate::room_state::message::RoomMessageBody;
+    use crate::room_state::test_room::{at, id, Room};
+    use crate::room_state::ChatRoomStateV1Delta;
+
+    /// `issuer`'s record for the invitation A issued to X, as of `secs`.
+    fn record(
+        room: &Room,
+        issuer: &SigningKey,
+        secs: u64,
+        policy: InvitePolicy,
+    ) -> AuthorizedInvite {
+        AuthorizedInvite::new(
+            Invite {
+                owner_member_id: room.params.owner_id(),
+                invitee: id(&room.x_sk),
+                issued_at: at(secs),
+                policy,
+            },
+            id(issuer),
+            issuer,
+        )
+    }
+
+    fn publish(room: &mut Room, issuer: &SigningKey, secs: u64, policy: InvitePolicy) {
+        let record = record(room, issuer, secs, policy);
+        room.apply(ChatRoomStateV1Delta {
+            invites: Some(vec![record]),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    /// X joins with a join message dated `secs`, as `invite accept` does.
+    fn join(room: &mut Room, secs: u64) {
+        let (x_sk, a_sk) = (room.x_sk.clone(), room.a_sk.clone());
+        let join = room.message_with(&x_sk, secs, RoomMessageBody::join_event());
+        room.apply(ChatRoomStateV1Delta {
+            members: Some(MembersDelta::new(vec![room.member(&x_sk, &a_sk)])),
+            recent_messages: Some(vec![join]),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    fn expiring(secs: u64) -> InvitePolicy {
+        InvitePolicy::Open {
+            expires_at: Some(at(secs)),
+            label: Some(SealedBytes::public(b"forum post".to_vec())),
+            max_uses: None,
+        }
+    }
+
+    #[test]
+    fn invite_used_before_expiry_is_consumed() {
+        let mut room = Room::new();
+        let (a_sk, x_sk) = (room.a_sk.clone(), room.x_sk.clone());
+        publish(&mut room, &a_sk, 10, expiring(100));
+        assert_eq!(room.state.invites.0.len(), 1, "pending until used");
+        room.assert_settled();
+
+        join(&mut room, 50);
+        assert!(room.is_member(&x_sk));
+        assert!(
+            room.state.invites.0.is_empty(),
+            "the used record moves into the use"
+        );
+        assert_eq!(room.state.invite_uses.count_for(id(&x_sk)), 1);
+        room.assert_settled();
+    }
+
+    #[test]
+    fn join_time_outlives_the_join_event() {
+        let mut room = Room::new();
+        let (a_sk, x_sk) = (room.a_sk.clone(), room.x_sk.clone());
+        publish(&mut room, &a_sk, 10, expiring(100));
+        join(&mut room, 50);
+        room.post(room.message(&x_sk, 200));
+
+        // The join event ages out, and a peer that missed the join re-offers
+        // the record.
+        let stale = room.state.invite_uses.0[0].invite.clone();
+        room.state
+            .recent_messages
+            .messages
+            .retain(|m| !m.message.content.is_join_event());
+        room.state.post_apply_cleanup(&room.params).unwrap();
+        room.apply(ChatRoomStateV1Delta {
+            invites: Some(vec![stale]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(room.is_member(&x_sk), "the use keeps the join time");
+        assert!(room.state.invites.0.is_empty());
+        room.assert_settled();
+    }
+
+    #[test]
+    fn use_limit_refuses_members_past_it() {
+        let mut room = Room::new();
+        let (a_sk, x_sk) = (room.a_sk.clone(), room.x_sk.clone());
+        publish(
+            &mut room,
+            &a_sk,
+            10,
+            InvitePolicy::Open {
+                expires_at: None,
+                label: None,
+                max_uses: Some(2),
+            },
+        );
+        join(&mut room, 20);
+
+        // Later holders of the link join as members its key signs in.
+        let [d_sk, e_sk] = [0; 2].map(|_| SigningKey::generate(&mut rand::thread_rng()));
+        for (sk, secs) in [(&d_sk, 30), (&e_sk, 40)] {
+            let join = room.message_with(sk, secs, RoomMessageBody::join_event());
+            room.apply(ChatRoomStateV1Delta {
+                members: Some(MembersDelta::new(vec![room.member(sk, &x_sk)])),
+                recent_messages: Some(vec![join]),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+        assert!(room.is_member(&x_sk));
+        assert!(room.is_member(&d_sk));
+        assert!(!room.is_member(&e_sk), "the third use is past the limit");
+        assert_eq!(room.state.invite_uses.count_for(id(&x_sk)), 2);
+        room.assert_settled();
+    }
+
+    #[test]
+    fn records_past_the_cap_are_refused() {
+        let mut room = Room::new();
+        let a_sk = room.a_sk.clone();
+        room.state.configuration = AuthorizedConfigurationV1::new(
+            Configuration {
+                max_members: 4,
+                ..Default::default()
+            },
+            &room.owner_sk,
+        );
+        let for_stranger = |room: &Room, secs: u64| {
+            let invitee = SigningKey::generate(&mut rand::thread_rng());
+            AuthorizedInvite::new(
+                Invite {
+                    owner_member_id: room.params.owner_id(),
+                    invitee: id(&invitee),
+                    issued_at: at(secs),
+                    policy: InvitePolicy::Revoked,
+                },
+                id(&a_sk),
+                &a_sk,
+            )
+        };
+        let held: Vec<AuthorizedInvite> = (0..4).map(|i| for_stranger(&room, 10 + i)).collect();
+        room.apply(ChatRoomStateV1Delta {
+            invites: Some(held.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let newer = for_stranger(&room, 20);
+        room.apply(ChatRoomStateV1Delta {
+            invites: Some(vec![newer]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(room.state.invites.0, held, "the closed links stay closed");
+        room.assert_settled();
+    }
+
+    #[test]
+    fn expired_invite_is_refused() {
+        let mut room = Room::new();
+        let (a_sk, x_sk) = (room.a_sk.clone(), room.x_sk.clone());
+        publish(&mut room, &a_sk, 10, expiring(100));
+
+        // A back-dated ordinary message does not stand in for the join.
+        room.apply(ChatRoomStateV1Delta {
+            members: Some(MembersDelta::new(vec![room.member(&x_sk, &a_sk)])),
+            recent_messages: Some(vec![room.message(&x_sk, 50)]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!room.is_member(&x_sk));
+
+        join(&mut room, 150);
+        assert!(!room.is_member(&x_sk));
+        assert!(room
+            .state
+            .recent_messages
+            .messages
+            .iter()
+            .all(|m| m.message.author != id(&x_sk)));
+        assert_eq!(
+            room.state.invites.0.len(),
+            1,
+            "the record keeps the link closed"
+        );
+        room.assert_settled();
+    }
+
+    #[test]
+    fn revoked_invite_is_refused_and_removes_an_invitee_who_joined() {
+        let mut room = Room::new();
+        let (a_sk, x_sk) = (room.a_sk.clone(), room.x_sk.clone());
+        publish(&mut room, &a_sk, 10, expiring(100));
+        publish(&mut room, &a_sk, 20, InvitePolicy::Revoked);
+        join(&mut room, 30);
+        assert!(!room.is_member(&x_sk), "refused as they first appear");
+        room.assert_settled();
+
+        // A revocation that arrives after the join removes the invitee, and
+        // whoever they invited in the meantime.
+        let mut room = Room::new();
+        let (a_sk, x_sk) = (room.a_sk.clone(), room.x_sk.clone());
+        join(&mut room, 30);
+        let d_sk = SigningKey::generate(&mut rand::thread_rng());
+        room.apply(ChatRoomStateV1Delta {
+            members: Some(MembersDelta::new(vec![room.member(&d_sk, &x_sk)])),
+            recent_messages: Some(vec![room.message(&d_sk, 40)]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(room.is_member(&d_sk));
+        publish(&mut room, &a_sk, 50, InvitePolicy::Revoked);
+        assert!(!room.is_member(&x_sk));
+        assert!(!room.is_member(&d_sk));
+        room.assert_settled();
+    }
+
+    #[test]
+    fn unrelated_member_cannot_revoke_a_joined_invitee() {
+        let mut room = Room::new();
+        let (c_sk, x_sk) = (room.c_sk.clone(), room.x_sk.clone());
+        join(&mut room, 30);
+
+        publish(&mut room, &c_sk, 50, InvitePolicy::Revoked);
+        assert!(room.is_member(&x_sk), "C is neither above X nor a deputy");
+        assert!(room.state.invites.0.is_empty());
+        room.assert_settled();
+    }
+
+    #[test]
+    fn forged_or_oversized_records_are_rejected() {
+        let mut room = Room::new();
+        let (a_sk, c_sk) = (room.a_sk.clone(), room.c_sk.clone());
+
+        // Claims to be from A but is signed by C.
+        let invite = record(&room, &c_sk, 10, InvitePolicy::Revoked).invite;
+        let signature = sign_struct(&invite, &c_sk);
+        let forged = AuthorizedInvite::with_signature(invite, id(&a_sk), signature);
+        let long_label = record(
+            &room,
+            &a_sk,
+            10,
+            InvitePolicy::Open {
+                expires_at: None,
+                label: Some(SealedBytes::public(vec![b'x'; MAX_INVITE_LABEL_BYTES + 1])),
+                max_uses: None,
+            },
+        );
+        for record in [forged, long_label] {
+            let result = room.apply(ChatRoomStateV1Delta {
+                invites: Some(vec![record]),
+                ..Default::default()
+            });
+            assert!(result.is_err());
+        }
+        assert!(room.state.invites.0.is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::room_state::invite::AuthorizedInvite;
+use crate::room_state::member::{AuthorizedMember, MemberId};
+use crate::room_state::message::AuthorizedMessageV1;
+use crate::room_state::signed_record::{self, SignedRecord};
+use crate::room_state::ChatRoomParametersV1;
+use crate::ChatRoomStateV1;
+use ed25519_dalek::VerifyingKey;
+use freenet_scaffold::util::{fast_hash, FastHash};
+use freenet_scaffold::ComposableState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::SystemTime;
+
+/// Members admitted through a limited invitation.
+///
+/// An invite record with an expiry or a use limit judges each member who came
+/// in through its invitation by their join event ([`InvitePolicy`] has the
+/// rules). Join events are trimmed from `recent_messages` like any other
+/// message, while a peer may keep re-offering the record, so the judgement
+/// has to outlive them. A use is the tombstone that keeps it: the record
+/// together with the join event of one member it admitted.
+/// `ChatRoomStateV1::post_apply_cleanup` records one for every member a
+/// limited record admits, and reads the join time and the use count from the
+/// stored uses once the join events are gone.
+///
+/// A use lasts while its member is in the room and both signatures verify
+/// against their signers' CURRENT keys; the issuer of a record carried by a
+/// use whose member is kept is exempt from inactivity-prune. A member pruned
+/// for inactivity therefore frees their slot. At most one use is kept per
+/// member, so the count is bounded by `max_members`.
+///
+/// [`InvitePolicy`]: crate::room_state::invite::InvitePolicy
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct InviteUsesV1(pub Vec<InviteUse>);
+
+impl InviteUsesV1 {
+    /// Whether `invite_use` still holds: its member is the owner or a current
+    /// member, its join event verifies against their key, and the record's
+    /// signature verifies against the issuer's CURRENT key. Decides both the
+    /// issuer's prune exemption and whether the use survives cleanup.
+    pub fn use_is_valid(
+        invite_use: &InviteUse,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        invite_use.member() != owner_id
+            && members_by_id.contains_key(&invite_use.member())
+            && signed_record::signed_by_current_key(invite_use, members_by_id, owner_id, owner_vk)
+            && signed_record::signed_by_current_key(
+                &invite_use.invite,
+                members_by_id,
+                owner_id,
+                owner_vk,
+            )
+    }
+
+    /// How many members are in through the invitation for `invitee`.
+    pub fn count_for(&self, invitee: MemberId) -> usize {
+        self.0
+            .iter()
+            .filter(|invite_use| invite_use.invite.invite.invitee == invitee)
+            .count()
+    }
+}
+
+impl ComposableState for InviteUsesV1 {
+    type ParentState = ChatRoomStateV1;
+    // BTreeSet for canonical summary bytes; see the note on `BansV1`.
+    type Summary = BTreeSet<InviteUseId>;
+    type Delta = Vec<InviteUse>;
+    type Parameters = ChatRoomParametersV1;
+
+    /// Checks the count and, for every signer who is the owner or a current
+    /// member, both signatures. Other signers are skipped as for bans;
+    /// cleanup sweeps the use if their key never matches.
+    fn verify(
+        &self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+    ) -> Result<(), String> {
+        let max = parent_state.configuration.configuration.max_members;
+        if self.0.len() > max {
+            return Err(format!(
+                "Number of invite uses ({}) exceeds the maximum allowed ({})",
+                self.0.len(),
+                max
+            ));
+        }
+        let records: Vec<AuthorizedInvite> = self
+            .0
+            .iter()
+            .map(|invite_use| invite_use.invite.clone())
+            .collect();
+        signed_record::verify_signatures(&records, parent_state, parameters)?;
+        signed_record::verify_signatures(&self.0, parent_state, parameters)
+    }
+
+    fn summarize(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+    ) -> Self::Summary {
+        signed_record::summarize(&self.0)
+    }
+
+    fn delta(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+        old_state_summary: &Self::Summary,
+    ) -> Option<Self::Delta> {
+        signed_record::delta(&self.0, old_state_summary)
+    }
+
+    /// Adds new uses after checking that each carries a join event of this
+    /// room and that both signatures verify. Whether the record admits the
+    /// member, and the count cap, are left to `post_apply_cleanup`; a delta
+    /// larger than the cap is refused as a flood.
+    fn apply_delta(
+        &mut self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+        delta: &Option<Self::Delta>,
+    ) -> Result<(), String> {
+        let owner_id = parameters.owner_id();
+        let records: Vec<AuthorizedInvite> = delta
+            .iter()
+            .flatten()
+            .map(|invite_use| invite_use.invite.clone())
+            .collect();
+        signed_record::verify_signatures(&records, parent_state, parameters)
+            .map_err(|e| format!("Invalid delta: {}", e))?;
+        signed_record::merge(
+            &mut self.0,
+            delta.as_deref(),
+            parent_state.configuration.configuration.max_members,
+            "max_members",
+            parent_state,
+            parameters,
+            |invite_use| {
+                let join = &invite_use.join.message;
+                if join.room_owner != owner_id || !join.content.is_join_event() {
+                    return Err("Invite use does not carry a join event".to_string());
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// An invite record and the join event of a member it admitted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InviteUse {
+    pub invite: AuthorizedInvite,
+    pub join: AuthorizedMessageV1,
+}
+
+impl InviteUse {
+    pub fn id(&self) -> InviteUseId {
+        let mut bytes = self.invite.signature.to_bytes().to_vec();
+        bytes.extend_from_slice(&self.join.signature.to_bytes());
+        InviteUseId(fast_hash(&bytes))
+    }
+
+    /// The member admitted, who signed the join event.
+    pub fn member(&self) -> MemberId {
+        self.join.message.author
+    }
+}
+
+impl SignedRecord for InviteUse {
+    type Id = InviteUseId;
+    const KIND: &'static str = "Invite use";
+
+    fn id(&self) -> InviteUseId {
+        Self::id(self)
+    }
+
+    fn signer(&self) -> MemberId {
+        self.member()
+    }
+
+    fn subject(&self) -> MemberId {
+        self.member()
+    }
+
+    fn issued_at(&self) -> SystemTime {
+        self.join.message.time
+    }
+
+    fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String> {
+        self.join
+            .validate(verifying_key)
+            .map_err(|e| format!("Invalid invite use join signature: {}", e))
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, Debug, Ord, PartialOrd)]
+pub struct InviteUseId(pub FastHash);
@@ -0,0 +1,708 @@
+use crate::room_state::member::{AuthorizedMember, MemberId};
+use crate::room_state::signed_record::{self, Overflow, SignedRecord};
+use crate::room_state::ChatRoomParametersV1;
+use crate::util::{sign_struct, verify_struct};
+use crate::ChatRoomStateV1;
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use freenet_scaffold::util::{fast_hash, FastHash};
+use freenet_scaffold::ComposableState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::SystemTime;
+
+/// Upper bound on pending join requests held in the room.
+pub const MAX_PENDING_JOIN_REQUESTS: usize = 20;
+
+/// Upper bound on the message attached to a join request, in bytes.
+pub const MAX_JOIN_REQUEST_MESSAGE_BYTES: usize = 500;
+
+/// Join requests ("knocks") from people who hold no invitation, and members'
+/// decisions on them.
+///
+/// A prospective member generates a key pair and publishes a request signed
+/// with it, carrying the nickname they would like and a short message. Any
+/// member may answer with a signed decision: an approval carries the
+/// `AuthorizedMember` the approver signed for the requester's key, exactly
+/// what an invitation would have carried, and the requester's client then
+/// joins with it. A denial stays behind as the tombstone that keeps the
+/// requester's later requests out. Decisions form a last-writer-wins register
+/// per requester, ordered by `(decided_at, id)`, so a later approval overrides
+/// a denial and the reverse.
+///
+/// Requests are public: their nickname and message are readable by anyone who
+/// can read the room's state, private room or not, because the requester holds
+/// no room secret yet.
+///
+/// `ChatRoomStateV1::post_apply_cleanup` drops every request and decision once
+/// the requester is a member, drops requests from a banned or denied
+/// requester, keeps the newest request per requester, and caps the queue at
+/// [`MAX_PENDING_JOIN_REQUESTS`] by keeping the earliest. Requests are signed by
+/// non-members, so anyone can fill the queue; the cap bounds the room state,
+/// not the nuisance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct JoinRequestsV1 {
+    pub requests: Vec<AuthorizedJoinRequest>,
+    pub decisions: Vec<AuthorizedJoinDecision>,
+}
+
+impl JoinRequestsV1 {
+    /// Signature checks for every request, and for every decision whose
+    /// decider is the owner or a current member; a non-member decider is
+    /// skipped, as for bans, and the decision is dropped by cleanup if the key
+    /// never matches.
+    fn verify_signatures(
+        &self,
+        parent_state: &ChatRoomStateV1,
+        parameters: &ChatRoomParametersV1,
+    ) -> Result<(), String> {
+        for request in &self.requests {
+            request.verify_signature()?;
+        }
+        signed_record::verify_signatures(&self.decisions, parent_state, parameters)
+    }
+
+    /// Size checks for the requests in `requests`.
+    fn check_requests<'a>(
+        requests: impl IntoIterator<Item = &'a AuthorizedJoinRequest>,
+        max_nickname_size: usize,
+    ) -> Result<(), String> {
+        for request in requests {
+            if request.request.nickname.len() > max_nickname_size {
+                return Err(format!(
+                    "Join request nickname of {} bytes exceeds the maximum of {}",
+                    request.request.nickname.len(),
+                    max_nickname_size
+                ));
+            }
+            if request.request.message.len() > MAX_JOIN_REQUEST_MESSAGE_BYTES {
+                return Err(format!(
+                    "Join request message of {} bytes exceeds the maximum of {}",
+                    request.request.message.len(),
+                    MAX_JOIN_REQUEST_MESSAGE_BYTES
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `decision`'s signature verifies against the decider's CURRENT
+    /// key and, for an approval, the enclosed `AuthorizedMember` admits the
+    /// requester as invited by the decider.
+    pub fn decision_is_effective(
+        decision: &AuthorizedJoinDecision,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        let Some(key) =
+            signed_record::signer_key(decision.decided_by, members_by_id, owner_id, owner_vk)
+        else {
+            return false;
+        };
+        if decision.verify_signature(&key).is_err() {
+            return false;
+        }
+        match &decision.decision.outcome {
+            JoinOutcome::Approved(member) => {
+                member.member.member_vk == decision.decision.requester
+                    && member.member.invited_by == decision.decided_by
+                    && member.member.owner_member_id == owner_id
+                    && member.verify_signature(&key).is_ok()
+            }
+            JoinOutcome::Denied => true,
+        }
+    }
+
+    /// What `post_apply_cleanup` keeps, given the current members and the
+    /// members named by a ban: the newest effective decision per non-member
+    /// requester, capped at `max_members` with the oldest evicted, and the
+    /// newest request per requester who is neither a member, banned nor
+    /// denied, capped at [`MAX_PENDING_JOIN_REQUESTS`] keeping the earliest.
+    pub fn settled(
+        &self,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        banned: &HashSet<MemberId>,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+        max_members: usize,
+    ) -> JoinRequestsV1 {
+        let decisions = signed_record::settle(
+            &self.decisions,
+            |decision| {
+                decision.decision.owner_member_id == owner_id
+                    && !members_by_id.contains_key(&decision.requester_id())
+                    && Self::decision_is_effective(decision, members_by_id, owner_id, owner_vk)
+            },
+            |_| true,
+            max_members,
+            Overflow::EvictOldest,
+        );
+        let denied: HashSet<MemberId> = decisions
+            .iter()
+            .filter(|decision| decision.decision.outcome == JoinOutcome::Denied)
+            .map(|decision| decision.requester_id())
+            .collect();
+
+        let mut newest: HashMap<MemberId, &AuthorizedJoinRequest> = HashMap::new();
+        for request in &self.requests {
+            let requester = request.requester_id();
+            if request.request.owner_member_id != owner_id
+                || members_by_id.contains_key(&requester)
+                || requester == owner_id
+                || banned.contains(&requester)
+                || denied.contains(&requester)
+            {
+                continue;
+            }
+            let entry = newest.entry(requester).or_insert(request);
+            if request.order_key() > entry.order_key() {
+                *entry = request;
+            }
+        }
+        let mut requests: Vec<AuthorizedJoinRequest> = newest.into_values().cloned().collect();
+        requests.sort_by_key(|request| request.order_key());
+        requests.truncate(MAX_PENDING_JOIN_REQUESTS);
+
+        JoinRequestsV1 {
+            requests,
+            decisions,
+        }
+    }
+
+    /// Requests no member has decided on yet, earliest first.
+    pub fn pending(&self) -> impl Iterator<Item = &AuthorizedJoinRequest> {
+        self.requests.iter().filter(|request| {
+            !self
+                .decisions
+                .iter()
+                .any(|decision| decision.requester_id() == request.requester_id())
+        })
+    }
+
+    /// The newest stored decision for `requester`, effective or not. For the
+    /// requester's client; the contract reads [`Self::settled`].
+    pub fn latest_decision_for(&self, requester: MemberId) -> Option<&AuthorizedJoinDecision> {
+        signed_record::latest_for(&self.decisions, requester)
+    }
+
+    fn sort(&mut self) {
+        self.requests.sort_by_key(|request| request.order_key());
+        self.decisions.sort_by_key(|decision| decision.order_key());
+    }
+}
+
+impl ComposableState for JoinRequestsV1 {
+    type ParentState = ChatRoomStateV1;
+    // BTreeSet for canonical summary bytes; see the note on `BansV1`. Request
+    // and decision ids are both signature hashes, so one set holds both.
+    type Summary = BTreeSet<JoinRequestId>;
+    type Delta = JoinRequestsV1;
+    type Parameters = ChatRoomParametersV1;
+
+    fn verify(
+        &self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+    ) -> Result<(), String> {
+        self.verify_signatures(parent_state, parameters)?;
+        if self.requests.len() > MAX_PENDING_JOIN_REQUESTS {
+            return Err(format!(
+                "Number of join requests ({}) exceeds the maximum allowed ({})",
+                self.requests.len(),
+                MAX_PENDING_JOIN_REQUESTS
+            ));
+        }
+        let max = parent_state.configuration.configuration.max_members;
+        if self.decisions.len() > max {
+            return Err(format!(
+                "Number of join decisions ({}) exceeds the maximum allowed ({})",
+                self.decisions.len(),
+                max
+            ));
+        }
+        Self::check_requests(
+            &self.requests,
+            parent_state.configuration.configuration.max_nickname_size,
+        )
+    }
+
+    fn summarize(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+    ) -> Self::Summary {
+        self.requests
+            .iter()
+            .map(|r| r.id())
+            .chain(self.decisions.iter().map(|d| d.id()))
+            .collect()
+    }
+
+    fn delta(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+        old_state_summary: &Self::Summary,
+    ) -> Option<Self::Delta> {
+        let delta = JoinRequestsV1 {
+            requests: self
+                .requests
+                .iter()
+                .filter(|r| !old_state_summary.contains(&r.id()))
+                .cloned()
+                .collect(),
+            decisions: self
+                .decisions
+                .iter()
+                .filter(|d| !old_state_summary.contains(&d.id()))
+                .cloned()
+                .collect(),
+        };
+        if delta.requests.is_empty() && delta.decisions.is_empty() {
+            None
+        } else {
+            Some(delta)
+        }
+    }
+
+    /// Adds new requests and decisions after checking their signatures and
+    /// sizes. Superseded, decided-on and excess entries are left to
+    /// `post_apply_cleanup`; a delta larger than the caps is refused as a
+    /// flood.
+    fn apply_delta(
+        &mut self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+        delta: &Option<Self::Delta>,
+    ) -> Result<(), String> {
+        if let Some(delta) = delta {
+            let max = parent_state.configuration.configuration.max_members;
+            if delta.requests.len() > MAX_PENDING_JOIN_REQUESTS || delta.decisions.len() > max {
+                return Err(format!(
+                    "Join request delta of {} requests and {} decisions exceeds the caps \
+                     ({} and {}); refusing to process a flood",
+                    delta.requests.len(),
+                    delta.decisions.len(),
+                    MAX_PENDING_JOIN_REQUESTS,
+                    max
+                ));
+            }
+            let known: HashSet<JoinRequestId> = self
+                .summarize(parent_state, parameters)
+                .into_iter()
+                .collect();
+            let new = JoinRequestsV1 {
+                requests: delta
+                    .requests
+                    .iter()
+                    .filter(|r| !known.contains(&r.id()))
+                    .cloned()
+                    .collect(),
+                decisions: delta
+                    .decisions
+                    .iter()
+                    .filter(|d| !known.contains(&d.id()))
+                    .cloned()
+                    .collect(),
+            };
+            new.verify_signatures(parent_state, parameters)
+                .map_err(|e| format!("Invalid delta: {}", e))?;
+            Self::check_requests(
+                &new.requests,
+                parent_state.configuration.configuration.max_nickname_size,
+            )
+            .map_err(|e| format!("Invalid delta: {}", e))?;
+            let mut seen = known;
+            for request in new.requests {
+                if seen.insert(request.id()) {
+                    self.requests.push(request);
+                }
+            }
+            for decision in new.decisions {
+                if seen.insert(decision.id()) {
+                    self.decisions.push(decision);
+                }
+            }
+        }
+        self.sort();
+        Ok(())
+    }
+}
+
+/// A join request signed with the requester's own new key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuthorizedJoinRequest {
+    pub request: JoinRequest,
+    pub signature: Signature,
+}
+
+impl AuthorizedJoinRequest {
+    pub fn new(request: JoinRequest, signing_key: &SigningKey) -> Self {
+        assert_eq!(signing_key.verifying_key(), request.requester);
+        let signature = sign_struct(&request, signing_key);
+        Self { request, signature }
+    }
+
+    pub fn verify_signature(&self) -> Result<(), String> {
+        verify_struct(&self.request, &self.signature, &self.request.requester)
+            .map_err(|e| format!("Invalid join request signature: {}", e))
+    }
+
+    /// The member id the requester would have once admitted.
+    pub fn requester_id(&self) -> MemberId {
+        MemberId::from(&self.request.requester)
+    }
+
+    pub fn id(&self) -> JoinRequestId {
+        JoinRequestId(fast_hash(&self.signature.to_bytes()))
+    }
+
+    fn order_key(&self) -> (SystemTime, JoinRequestId) {
+        (self.request.requested_at, self.id())
+    }
+}
+
+/// Asks to join as `requester`, under `nickname`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JoinRequest {
+    pub owner_member_id: MemberId,
+    pub requester: VerifyingKey,
+    /// At most the room's `max_nickname_size` bytes.
+    pub nickname: String,
+    /// At most [`MAX_JOIN_REQUEST_MESSAGE_BYTES`].
+    pub message: String,
+    pub requested_at: SystemTime,
+}
+
+/// A member's decision on a join request, with their signature.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuthorizedJoinDecision {
+    pub decision: JoinDecision,
+    pub decided_by: MemberId,
+    pub signature: Signature,
+}
+
+impl AuthorizedJoinDecision {
+    pub fn new(decision: JoinDecision, decided_by: MemberId, signing_key: &SigningKey) -> Self {
+        assert_eq!(MemberId::from(signing_key.verifying_key()), decided_by);
+        let signature = sign_struct(&decision, signing_key);
+        Self {
+            decision,
+            decided_by,
+            signature,
+        }
+    }
+
+    /// Create an AuthorizedJoinDecision with a pre-computed signature.
+    /// Use this when signing is done externally (e.g., via delegate).
+    pub fn with_signature(
+        decision: JoinDecision,
+        decided_by: MemberId,
+        signature: Signature,
+    ) -> Self {
+        Self {
+            decision,
+            decided_by,
+            signature,
+        }
+    }
+
+    pub fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String> {
+        verify_struct(&self.decision, &self.signature, verifying_key)
+            .map_err(|e| format!("Invalid join decision signature: {}", e))
+    }
+
+    pub fn requester_id(&self) -> MemberId {
+        MemberId::from(&self.decision.requester)
+    }
+
+    pub fn id(&self) -> JoinRequestId {
+        JoinRequestId(fast_hash(&self.signature.to_bytes()))
+    }
+}
+
+impl SignedRecord for AuthorizedJoinDecision {
+    type Id = JoinRequestId;
+    const KIND: &'static str = "Join decision";
+
+    fn id(&self) -> JoinRequestId {
+        Self::id(self)
+    }
+
+    fn signer(&self) -> MemberId {
+        self.decided_by
+    }
+
+    fn subject(&self) -> MemberId {
+        self.requester_id()
+    }
+
+    fn issued_at(&self) -> SystemTime {
+        self.decision.decided_at
+    }
+
+    fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String> {
+        Self::verify_signature(self, verifying_key)
+    }
+}
+
+/// Approves or denies `requester`'s join request as of `decided_at`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JoinDecision {
+    pub owner_member_id: MemberId,
+    /// The key the requester signed their request with. Kept here so a
+    /// denied requester, whose request is dropped, can still be approved.
+    pub requester: VerifyingKey,
+    pub decided_at: SystemTime,
+    pub outcome: JoinOutcome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum JoinOutcome {
+    /// Admit the requester: the member entry the decider signed for their key.
+    /// Boxed to keep `Denied` small (clippy::large_enum_variant); the wire
+    /// encoding is the same.
+    Approved(Box<AuthorizedMember>),
+    Denied,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, Debug, Ord, PartialOrd)]
+pub struct JoinRequestId(pub FastHash);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_state::ban::{AuthorizedUserBan, UserBan};
+    use crate::room_state::member::MembersDelta;
+    use crate::room_state::message::RoomMessageBody;
+    use crate::room_state::test_room::{at, id, Room};
+    use crate::room_state::ChatRoomStateV1Delta;
+
+    fn request(room: &Room, sk: &SigningKey, secs: u64, message: &str) -> AuthorizedJoinRequest {
+        AuthorizedJoinRequest::new(
+            JoinRequest {
+                owner_member_id: room.params.owner_id(),
+                requester: sk.verifying_key(),
+                nickname: "Rosa".to_string(),
+                message: message.to_string(),
+                requested_at: at(secs),
+            },
+            sk,
+        )
+    }
+
+    /// `decider`'s decision on X's request, as of `secs`.
+    fn decision(
+        room: &Room,
+        decider: &SigningKey,
+        secs: u64,
+        approve: bool,
+    ) -> AuthorizedJoinDecision {
+        let outcome = if approve {
+            JoinOutcome::Approved(Box::new(room.member(&room.x_sk, decider)))
+        } else {
+            JoinOutcome::Denied
+        };
+        AuthorizedJoinDecision::new(
+            JoinDecision {
+                owner_member_id: room.params.owner_id(),
+                requester: room.x_sk.verifying_key(),
+                decided_at: at(secs),
+                outcome,
+            },
+            id(decider),
+            decider,
+        )
+    }
+
+    fn publish(
+        room: &mut Room,
+        requests: Vec<AuthorizedJoinRequest>,
+        decisions: Vec<AuthorizedJoinDecision>,
+    ) -> Result<(), String> {
+        room.apply(ChatRoomStateV1Delta {
+            join_requests: Some(JoinRequestsV1 {
+                requests,
+                decisions,
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn pending_ids(room: &Room) -> Vec<MemberId> {
+        room.state
+            .join_requests
+            .pending()
+            .map(|r| r.requester_id())
+            .collect()
+    }
+
+    #[test]
+    fn approved_requester_joins_and_the_records_are_consumed() {
+        let mut room = Room::new();
+        let x_sk = room.x_sk.clone();
+        let request = request(&room, &x_sk, 1, "friend of A");
+        publish(&mut room, vec![request], vec![]).unwrap();
+        assert_eq!(pending_ids(&room), vec![id(&x_sk)]);
+        room.assert_settled();
+
+        let a_sk = room.a_sk.clone();
+        let approval = decision(&room, &a_sk, 2, true);
+        publish(&mut room, vec![], vec![approval]).unwrap();
+        assert!(pending_ids(&room).is_empty());
+        room.assert_settled();
+
+        // X's client picks the approval up and joins with it.
+        let approved = match &room
+            .state
+            .join_requests
+            .latest_decision_for(id(&x_sk))
+            .unwrap()
+            .decision
+            .outcome
+        {
+            JoinOutcome::Approved(member) => (**member).clone(),
+            JoinOutcome::Denied => panic!("expected an approval"),
+        };
+        let join = room.message_with(&x_sk, 3, RoomMessageBody::join_event());
+        room.apply(ChatRoomStateV1Delta {
+            members: Some(MembersDelta::new(vec![approved])),
+            recent_messages: Some(vec![join]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(room
+            .state
+            .members
+            .members_by_member_id()
+            .contains_key(&id(&x_sk)));
+        assert_eq!(room.state.join_requests, JoinRequestsV1::default());
+        room.assert_settled();
+    }
+
+    #[test]
+    fn denial_keeps_the_requester_out_until_a_later_approval() {
+        let mut room = Room::new();
+        let (x_sk, a_sk, owner_sk) = (room.x_sk.clone(), room.a_sk.clone(), room.owner_sk.clone());
+        let first = request(&room, &x_sk, 1, "please");
+        let denial = decision(&room, &owner_sk, 2, false);
+        publish(&mut room, vec![first], vec![denial]).unwrap();
+        assert!(room.state.join_requests.requests.is_empty());
+        assert_eq!(room.state.join_requests.decisions.len(), 1);
+        room.assert_settled();
+
+        // Knocking again does not get past the denial.
+        let second = request(&room, &x_sk, 3, "please, again");
+        publish(&mut room, vec![second.clone()], vec![]).unwrap();
+        assert!(room.state.join_requests.requests.is_empty());
+        room.assert_settled();
+
+        // A later approval by any member overrides it; an older one does not.
+        let stale = decision(&room, &a_sk, 1, true);
+        publish(&mut room, vec![second.clone()], vec![stale]).unwrap();
+        assert!(room.state.join_requests.requests.is_empty());
+        let approval = decision(&room, &a_sk, 4, true);
+        publish(&mut room, vec![second], vec![approval.clone()]).unwrap();
+        assert_eq!(room.state.join_requests.decisions, vec![approval]);
+        assert_eq!(room.state.join_requests.requests.len(), 1);
+        assert!(pending_ids(&room).is_empty());
+        room.assert_settled();
+    }
+
+    #[test]
+    fn newest_request_wins_and_banned_requesters_are_dropped() {
+        let mut room = Room::new();
+        let (x_sk, owner_sk) = (room.x_sk.clone(), room.owner_sk.clone());
+        let older = request(&room, &x_sk, 1, "first");
+        let newer = request(&room, &x_sk, 2, "second");
+        publish(&mut room, vec![newer.clone(), older], vec![]).unwrap();
+        assert_eq!(room.state.join_requests.requests, vec![newer]);
+        room.assert_settled();
+
+        let ban = AuthorizedUserBan::new(
+            UserBan {
+                owner_member_id: room.params.owner_id(),
+                banned_at: at(3),
+                banned_user: id(&x_sk),
+                reason: None,
+                duration: None,
+            },
+            id(&owner_sk),
+            &owner_sk,
+        );
+        room.apply(ChatRoomStateV1Delta {
+            bans: Some(vec![ban]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(room.state.join_requests.requests.is_empty());
+        room.assert_settled();
+    }
+
+    #[test]
+    fn queue_is_capped_and_floods_are_refused() {
+        let mut room = Room::new();
+        let rng = &mut rand::thread_rng();
+        let knockers: Vec<SigningKey> = (0..MAX_PENDING_JOIN_REQUESTS + 5)
+            .map(|_| SigningKey::generate(rng))
+            .collect();
+        let requests: Vec<AuthorizedJoinRequest> = knockers
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| request(&room, sk, i as u64, ""))
+            .collect();
+
+        let flood = publish(&mut room, requests.clone(), vec![]);
+        assert!(flood.unwrap_err().contains("flood"));
+
+        publish(&mut room, requests[10..].to_vec(), vec![]).unwrap();
+        publish(&mut room, requests[..10].to_vec(), vec![]).unwrap();
+        assert_eq!(
+            room.state.join_requests.requests,
+            requests[..MAX_PENDING_JOIN_REQUESTS].to_vec(),
+            "the earliest requests keep their place in the queue"
+        );
+        room.assert_settled();
+    }
+
+    #[test]
+    fn forged_and_oversized_records_are_rejected() {
+        let mut room = Room::new();
+        let (x_sk, a_sk, owner_sk) = (room.x_sk.clone(), room.a_sk.clone(), room.owner_sk.clone());
+
+        // A request signed by someone other than the requester key.
+        let mut forged = request(&room, &x_sk, 1, "");
+        forged.signature = request(&room, &a_sk, 1, "").signature;
+        assert!(publish(&mut room, vec![forged], vec![]).is_err());
+
+        let mut long = request(
+            &room,
+            &x_sk,
+            1,
+            &"x".repeat(MAX_JOIN_REQUEST_MESSAGE_BYTES + 1),
+        );
+        assert!(publish(&mut room, vec![long.clone()], vec![]).is_err());
+        long.request.message.clear();
+        long.request.nickname =
+            "n".repeat(room.state.configuration.configuration.max_nickname_size + 1);
+        let long = AuthorizedJoinRequest::new(long.request, &x_sk);
+        assert!(publish(&mut room, vec![long], vec![]).is_err());
+
+        // An approval whose member entry names the owner as inviter although
+        // A decided it is not effective, and is dropped by cleanup.
+        let request = request(&room, &x_sk, 1, "");
+        let mut borrowed = decision(&room, &a_sk, 2, true);
+        borrowed.decision.outcome = JoinOutcome::Approved(Box::new(room.member(&x_sk, &owner_sk)));
+        let borrowed = AuthorizedJoinDecision::new(borrowed.decision, id(&a_sk), &a_sk);
+        publish(&mut room, vec![request], vec![borrowed]).unwrap();
+        assert!(room.state.join_requests.decisions.is_empty());
+        assert_eq!(pending_ids(&room), vec![id(&x_sk)]);
+
+        // A decision by a non-member is dropped too.
+        let outsider = SigningKey::generate(&mut rand::thread_rng());
+        let denial = decision(&room, &outsider, 3, false);
+        publish(&mut room, vec![], vec![denial]).unwrap();
+        assert!(room.state.join_requests.decisions.is_empty());
+        room.assert_settled();
+    }
+}
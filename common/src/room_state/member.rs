@@ -150,7 +150,53 @@ impl ComposableState for MembersV1 {
         // stable owner/ancestor authority. The full deputy-aware enforcement
         // (which needs the converged deputy state) runs afterwards in
         // `ChatRoomStateV1::post_apply_cleanup`. See #410.
-        self.remove_banned_members(&parent_state.bans, &MemberInfoV1::default(), parameters);
+        //
+        // Bans named by a stored revocation or lapse, and temporary bans that
+        // have already lapsed, are skipped: a member re-invited after an unban
+        // or expiry must not be removed here by a peer re-offering the lifted
+        // ban. Revocations and lapses are matched by name only, without
+        // checking their signers' authority. Skipping is safe because cleanup drops only bans that are
+        // really lifted and enforces the rest itself.
+        let members_by_id = parent_state.members.members_by_member_id();
+        let owner_id = parameters.owner_id();
+        let in_force = BansV1(
+            parent_state
+                .bans
+                .0
+                .iter()
+                .filter(|ban| {
+                    parent_state.ban_revocations.revocation_for(ban).is_none()
+                        && parent_state.ban_lapses.lapse_for(ban).is_none()
+                        && !BansV1::ban_has_expired(
+                            ban,
+                            &parent_state.recent_messages,
+                            &members_by_id,
+                            &parent_state.member_info,
+                            owner_id,
+                        )
+                })
+                .cloned()
+                .collect(),
+        );
+        self.remove_banned_members(&in_force, &MemberInfoV1::default(), parameters);
+
+        // Refuse members whose invitation was revoked, with anyone they
+        // invited. Only owner/ancestor revocations are acted on here; expiry
+        // and deputy revocations need the converged state and are enforced by
+        // `ChatRoomStateV1::post_apply_cleanup`, which refuses a superset.
+        let revoked = parent_state.invites.revoked_members(
+            &parent_state.invite_uses,
+            &self.members_by_member_id(),
+            owner_id,
+            &parameters.owner,
+        );
+        if !revoked.is_empty() {
+            let mut removed = revoked.clone();
+            for invitee in &revoked {
+                removed.extend(self.get_downstream_members(*invitee));
+            }
+            self.members.retain(|m| !removed.contains(&m.member.id()));
+        }
 
         // Always enforce max members limit
         self.remove_excess_members(parameters, max_members);
@@ -398,8 +444,28 @@ impl MembersV1 {
         false
     }
 
+    /// Whether `actor` may pin or unpin messages.
+    ///
+    /// A pin is room-wide, so only the room-wide grants of
+    /// [`Self::is_ban_authorized`] apply: the owner (1) and owner-appointed
+    /// global moderators (3), the latter only while they are current members.
+    /// Subtree authority (2, 5) does not extend to pins — a sub-inviter
+    /// moderates their subtree, not what the whole room sees first. Also
+    /// unlike bans, this deliberately does not depend on WHOSE message is
+    /// pinned, so a pin's validity never changes when its target ages out.
+    pub fn is_pin_authorized(
+        actor: MemberId,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+    ) -> bool {
+        actor == owner_id
+            || (members_by_id.contains_key(&actor)
+                && member_info.deputies_of(owner_id).contains(&actor))
+    }
+
     /// Helper function to get all downstream members of a given member
-    fn get_downstream_members(&self, member_id: MemberId) -> HashSet<MemberId> {
+    pub(crate) fn get_downstream_members(&self, member_id: MemberId) -> HashSet<MemberId> {
         let mut downstream = HashSet::new();
         let mut to_check = vec![member_id];
         while let Some(current) = to_check.pop() {
@@ -1114,6 +1180,8 @@ mod tests {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member2.id(),
+            reason: None,
+            duration: None,
         };
         let authorized_ban = AuthorizedUserBan::new(banned_member, owner_id, &owner_signing_key);
         let bans = BansV1(vec![authorized_ban]);
@@ -1159,6 +1227,8 @@ mod tests {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member2.id(),
+            reason: None,
+            duration: None,
         };
         let authorized_ban = AuthorizedUserBan::new(banned_member, owner_id, &owner_signing_key);
         let bans = BansV1(vec![authorized_ban]);
@@ -1194,6 +1264,8 @@ mod tests {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member4.id(),
+            reason: None,
+            duration: None,
         };
         let authorized_ban = AuthorizedUserBan::new(banned_member, owner_id, &owner_signing_key);
         let bans = BansV1(vec![authorized_ban]);
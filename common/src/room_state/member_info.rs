@@ -133,6 +133,7 @@ impl ComposableState for MemberInfoV1 {
         parameters: &Self::Parameters,
     ) -> Result<(), String> {
         let members_by_id = parent_state.members.members_by_member_id();
+        let departed_keys = parent_state.departures.keys();
         let owner_id = parameters.owner_id();
 
         // NOTE (#411 round 7 / Codex P1 #3): `verify` deliberately does NOT reject
@@ -165,13 +166,18 @@ impl ComposableState for MemberInfoV1 {
                 // If this is the owner's member info, verify against owner's key
                 member_info.verify_signature(parameters)?;
             } else {
-                // For non-owner members, verify they exist in members list
-                let member = members_by_id.get(&member_id).ok_or_else(|| {
-                    format!("MemberInfo exists for non-existent member: {:?}", member_id)
-                })?;
+                // For non-owner members, verify they exist in members list, or
+                // left the room and are kept in `departures`
+                let member_vk = members_by_id
+                    .get(&member_id)
+                    .map(|member| member.member.member_vk)
+                    .or_else(|| departed_keys.get(&member_id).copied())
+                    .ok_or_else(|| {
+                        format!("MemberInfo exists for non-existent member: {:?}", member_id)
+                    })?;
 
                 // Verify the signature with member's key
-                member_info.verify_signature_with_key(&member.member.member_vk)?;
+                member_info.verify_signature_with_key(&member_vk)?;
             }
         }
         Ok(())
@@ -283,15 +289,19 @@ impl ComposableState for MemberInfoV1 {
                     // If it's the owner, verify against the room owner's key
                     member_info.verify_signature(parameters)?;
                 } else {
-                    // For non-owners, verify they exist and check their signature.
-                    // If the member was removed (e.g. banned or max_members), skip
-                    // this entry — retention cleanup below will handle it.
+                    // For non-owners, verify they exist (or departed) and check
+                    // their signature. If the member was removed (e.g. banned or
+                    // max_members), skip this entry — retention cleanup below
+                    // will handle it.
                     let members = parent_state.members.members_by_member_id();
-                    let member = match members.get(member_id) {
-                        Some(m) => m,
-                        None => continue,
+                    let member_vk = match members.get(member_id) {
+                        Some(m) => m.member.member_vk,
+                        None => match parent_state.departures.keys().get(member_id) {
+                            Some(vk) => *vk,
+                            None => continue,
+                        },
                     };
-                    member_info.verify_signature_with_key(&member.member.member_vk)?;
+                    member_info.verify_signature_with_key(&member_vk)?;
                 }
 
                 // Update or add the member info. Conflict resolution uses the
@@ -321,10 +331,13 @@ impl ComposableState for MemberInfoV1 {
             }
         }
         // Always remove any member info that is not in parent_state.members
+        // or parent_state.departures
         let member_map = parent_state.members.members_by_member_id();
+        let departed_keys = parent_state.departures.keys();
         self.member_info.retain(|info| {
             parameters.owner_id() == info.member_info.member_id
                 || member_map.contains_key(&info.member_info.member_id)
+                || departed_keys.contains_key(&info.member_info.member_id)
         });
 
         // Write-side dedup (#411 round 8 Task 2 / item A): the update path above
@@ -403,18 +416,37 @@ pub struct MemberInfo {
     /// subtree (deputy ban authority, #410). Empty for the vast majority of
     /// members.
     ///
-    /// LOAD-BEARING: this MUST be the LAST field and MUST keep BOTH
-    /// `#[serde(default)]` (so pre-#410 records — which have no `deputies`
-    /// key — still deserialize) AND `skip_serializing_if = "Vec::is_empty"`
-    /// (so an EMPTY list serializes byte-identically to the old 3-field
-    /// record). `MemberInfo` is INDIVIDUALLY signed over its ciborium bytes
-    /// (`AuthorizedMemberInfo`), so a plain `#[serde(default)]` alone would
-    /// re-serialize every existing member's record with an extra field,
-    /// breaking their signature on migration and stranding every existing
-    /// room. Never reorder the first three fields. Pinned by
+    /// LOAD-BEARING: this and every field after it MUST come after the first
+    /// three and MUST keep BOTH `#[serde(default)]` (so pre-#410 records —
+    /// which have no `deputies` key — still deserialize) AND
+    /// `skip_serializing_if = "Vec::is_empty"` (so an EMPTY list serializes
+    /// byte-identically to the old 3-field record). `MemberInfo` is
+    /// INDIVIDUALLY signed over its ciborium bytes (`AuthorizedMemberInfo`),
+    /// so a plain `#[serde(default)]` alone would re-serialize every existing
+    /// member's record with an extra field, breaking their signature on
+    /// migration and stranding every existing room. Never reorder the first
+    /// three fields. Pinned by
     /// `empty_deputies_serializes_identically_to_legacy_member_info`.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub deputies: Vec<MemberId>,
+    /// The member's published prekey for forward-secret DM sessions
+    /// (`crate::dm_ratchet`). `None` for members who have not opted in; DMs
+    /// to them stay sealed to their member key. Same serde rules as
+    /// `deputies`, pinned by
+    /// `absent_dm_prekey_serializes_identically_to_legacy_member_info`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dm_prekey: Option<DmPrekeyV1>,
+}
+
+/// An X25519 public key a member publishes in their `MemberInfo` so others
+/// can open a ratcheting DM session with them without a round trip. Signed
+/// with the rest of the record, so it is as authentic as the nickname.
+/// `id` names the matching secret the member keeps locally; it grows by one
+/// per rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DmPrekeyV1 {
+    pub id: u32,
+    pub public_key: [u8; 32],
 }
 
 impl MemberInfo {
@@ -425,6 +457,7 @@ impl MemberInfo {
             version,
             preferred_nickname: SealedBytes::public(nickname.into_bytes()),
             deputies: Vec::new(),
+            dm_prekey: None,
         }
     }
 
@@ -447,6 +480,7 @@ impl MemberInfo {
                 declared_len,
             ),
             deputies: Vec::new(),
+            dm_prekey: None,
         }
     }
 }
@@ -506,6 +540,7 @@ mod tests {
             version: 7,
             preferred_nickname: nickname.clone(),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
 
         // (a) direct byte-identity of the ciborium serialization.
@@ -535,6 +570,7 @@ mod tests {
             version: 7,
             preferred_nickname: nickname,
             deputies: vec![member_id],
+            dm_prekey: None,
         };
         let mut with_deputy_bytes = Vec::new();
         ciborium::ser::into_writer(&with_deputy, &mut with_deputy_bytes).unwrap();
@@ -544,6 +580,51 @@ mod tests {
         );
     }
 
+    /// Same pin as above for `dm_prekey`: a record without a prekey must keep
+    /// the bytes (and so the signatures) of a record from before the field,
+    /// and one with a prekey must round-trip it.
+    #[test]
+    fn absent_dm_prekey_serializes_identically_to_legacy_member_info() {
+        use crate::util::verify_struct;
+
+        #[derive(Serialize)]
+        struct OldMemberInfo {
+            member_id: MemberId,
+            version: u32,
+            preferred_nickname: SealedBytes,
+            deputies: Vec<MemberId>,
+        }
+
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let member_id: MemberId = signing_key.verifying_key().into();
+        let nickname = SealedBytes::public(b"LegacyNick".to_vec());
+        let old = OldMemberInfo {
+            member_id,
+            version: 3,
+            preferred_nickname: nickname.clone(),
+            deputies: vec![member_id],
+        };
+        let mut new = MemberInfo {
+            member_id,
+            version: 3,
+            preferred_nickname: nickname,
+            deputies: vec![member_id],
+            dm_prekey: None,
+        };
+        let signature = sign_struct(&old, &signing_key);
+        assert!(verify_struct(&new, &signature, &signing_key.verifying_key()).is_ok());
+
+        new.dm_prekey = Some(DmPrekeyV1 {
+            id: 1,
+            public_key: [9; 32],
+        });
+        assert!(verify_struct(&new, &signature, &signing_key.verifying_key()).is_err());
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&new, &mut bytes).unwrap();
+        let decoded: MemberInfo = ciborium::de::from_reader(&bytes[..]).unwrap();
+        assert_eq!(decoded, new);
+    }
+
     #[test]
     fn test_member_info_v1_default() {
         let default_member_info = MemberInfoV1::default();
@@ -1,4 +1,6 @@
-use crate::room_state::member::MemberId;
+use crate::room_state::configuration::SlowMode;
+use crate::room_state::member::{MemberId, MembersV1};
+use crate::room_state::mute::MutesV1;
 use crate::room_state::privacy::{PrivacyMode, SecretVersion};
 use crate::room_state::ChatRoomParametersV1;
 use crate::util::sign_struct;
@@ -8,9 +10,9 @@ use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
 use freenet_scaffold::util::{fast_hash, FastHash};
 use freenet_scaffold::ComposableState;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 /// Ciphertext overhead added by AES-256-GCM (`encrypt_with_symmetric_key`):
 /// the 16-byte authentication tag appended to the plaintext. The nonce lives
@@ -19,7 +21,13 @@ use std::time::SystemTime;
 /// by the `measure_*_matches_private_*` tests (feature `ecies-randomized`).
 pub const ENCRYPTION_TAG_OVERHEAD: usize = 16;
 
-/// Computed state for message actions (edits, deletes, reactions)
+/// Maximum number of messages that can be pinned at once. Each pin exempts
+/// its target (and the pin action itself) from `max_recent_messages`
+/// trimming, so this bounds how far a room can exceed that cap. When more
+/// are pinned, the most recently pinned win; see [`MessagesV1::pins`].
+pub const MAX_PINNED_MESSAGES: usize = 10;
+
+/// Computed state for message actions (edits, deletes, reactions, poll votes, pins)
 /// This is rebuilt from action messages and not serialized
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct MessageActionsState {
@@ -29,6 +37,115 @@ pub struct MessageActionsState {
     pub deleted: std::collections::HashSet<MessageId>,
     /// Reactions on messages: message_id -> (emoji -> list of reactors)
     pub reactions: HashMap<MessageId, HashMap<String, Vec<MemberId>>>,
+    /// Poll ballots: poll message_id -> (voter -> ballots in message order).
+    ///
+    /// Every ballot is kept, not just the latest, because which one counts
+    /// depends on the poll's options and close time, and those live in the
+    /// poll body — which in a private room only the client can decrypt. See
+    /// [`MessagesV1::poll_tally`].
+    pub poll_votes: HashMap<MessageId, HashMap<MemberId, Vec<PollBallot>>>,
+    /// Messages currently pinned and not deleted. See [`MessagesV1::pins`]
+    /// for pin order and who pinned them.
+    pub pinned: HashSet<MessageId>,
+}
+
+/// An in-effect pin, as computed by [`MessagesV1::pins`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Pin {
+    /// The pinned message.
+    pub target: MessageId,
+    /// The pin action that put it there.
+    pub pin_action: MessageId,
+    /// Author of the pin action.
+    pub pinned_by: MemberId,
+    /// Signed timestamp of the pin action.
+    pub pinned_at: SystemTime,
+}
+
+/// One vote action as folded into [`MessageActionsState::poll_votes`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct PollBallot {
+    /// Zero-based option indices; empty is a retraction.
+    pub choices: Vec<u32>,
+    /// The vote message's signed timestamp, checked against the poll's close time.
+    pub time: SystemTime,
+}
+
+/// Reply threads, as computed by [`MessagesV1::thread_index`].
+///
+/// A message's parent is the message its `ReplyContentV1` targets. A thread
+/// is a root (a message with no parent) and everything that replies to it,
+/// directly or through other replies.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ThreadIndex {
+    /// Parent of each reply.
+    parents: HashMap<MessageId, MessageId>,
+    /// Direct replies to each message, in display order.
+    children: HashMap<MessageId, Vec<MessageId>>,
+    /// Display order of every indexed message.
+    positions: HashMap<MessageId, usize>,
+}
+
+impl ThreadIndex {
+    /// The message `id` replies to, if it is in the index.
+    pub fn parent(&self, id: &MessageId) -> Option<&MessageId> {
+        self.parents.get(id)
+    }
+
+    /// Direct replies to `id`, oldest first.
+    pub fn children(&self, id: &MessageId) -> &[MessageId] {
+        self.children.get(id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The root of the thread `id` belongs to (`id` itself for a root).
+    pub fn root_of(&self, id: &MessageId) -> MessageId {
+        let mut current = id;
+        while let Some(parent) = self.parents.get(current) {
+            current = parent;
+        }
+        current.clone()
+    }
+
+    /// Every message replying to `root`, directly or indirectly, oldest first.
+    pub fn descendants(&self, root: &MessageId) -> Vec<MessageId> {
+        let mut found = Vec::new();
+        let mut pending = vec![root];
+        while let Some(id) = pending.pop() {
+            for child in self.children(id) {
+                found.push(child.clone());
+                pending.push(child);
+            }
+        }
+        found.sort_by_key(|id| self.positions.get(id).copied());
+        found
+    }
+
+    /// Number of messages in the thread under `root`, not counting `root`.
+    pub fn reply_count(&self, root: &MessageId) -> usize {
+        self.descendants(root).len()
+    }
+
+    /// Whether `id` has no parent in the index.
+    pub fn is_root(&self, id: &MessageId) -> bool {
+        !self.parents.contains_key(id)
+    }
+}
+
+/// Vote counts for one poll, as computed by [`MessagesV1::poll_tally`].
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct PollTally {
+    /// Voters per option, indexed like [`crate::room_state::content::PollContentV1::options`].
+    /// Each list is sorted so every peer renders the same order.
+    pub voters: Vec<Vec<MemberId>>,
+    /// Number of members whose counted ballot selects at least one option.
+    pub total_voters: usize,
+}
+
+impl PollTally {
+    /// Vote count per option.
+    pub fn counts(&self) -> Vec<usize> {
+        self.voters.iter().map(|v| v.len()).collect()
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
@@ -126,6 +243,7 @@ impl ComposableState for MessagesV1 {
         parameters: &Self::Parameters,
     ) -> Result<(), String> {
         let members_by_id = parent_state.members.members_by_member_id();
+        let departed_keys = parent_state.departures.keys();
         let owner_id = parameters.owner_id();
 
         for message in &self.messages {
@@ -135,6 +253,9 @@ impl ComposableState for MessagesV1 {
             } else if let Some(member) = members_by_id.get(&message.message.author) {
                 // Regular member messages are validated against their member key
                 &member.member.member_vk
+            } else if let Some(member_vk) = departed_keys.get(&message.message.author) {
+                // A member who left keeps what they said up to their leave event
+                member_vk
             } else {
                 return Err(format!(
                     "Message author not found: {:?}",
@@ -183,6 +304,10 @@ impl ComposableState for MessagesV1 {
     ) -> Option<Self::Delta> {
         // A message the receiver would prune the instant it applied it must
         // never be offered, or the pair loops forever re-sending it.
+        // Pinned messages and their pin actions are exempt from trimming, so
+        // they go regardless of horizon (the receiver gets both together and
+        // exempts them the same way once its member state agrees).
+        let exempt = self.retention_exempt_ids();
         let retained_by_receiver = |m: &AuthorizedMessageV1| match &old_state_summary.horizon {
             RetentionHorizon::Open => true,
             RetentionHorizon::OldestRetained(oldest) => m.order_key() > *oldest,
@@ -193,7 +318,7 @@ impl ComposableState for MessagesV1 {
             .messages
             .iter()
             .filter(|m| !old_state_summary.message_ids.contains(&m.id()))
-            .filter(|m| retained_by_receiver(m))
+            .filter(|m| exempt.contains(&m.id()) || retained_by_receiver(m))
             .cloned()
             .collect();
         if delta.is_empty() {
@@ -287,8 +412,12 @@ impl ComposableState for MessagesV1 {
                     }
                     RoomMessageBody::Public { .. } => {
                         // In private mode, reject public messages (everything must be encrypted)
-                        // Exception: event messages (joins, etc.) contain no sensitive content
-                        if *privacy_mode == PrivacyMode::Private && !content.is_event() {
+                        // Exceptions: event messages (joins, etc.) contain no sensitive
+                        // content, and pin actions must be readable here to drive retention
+                        if *privacy_mode == PrivacyMode::Private
+                            && !content.is_event()
+                            && !content.is_pin_action()
+                        {
                             return Err("Cannot send public messages in private room".to_string());
                         }
                     }
@@ -311,13 +440,70 @@ impl ComposableState for MessagesV1 {
         self.messages
             .retain(|m| m.message.content.content_len() <= max_message_size);
 
-        // Ensure all messages are signed by a valid member or the room owner, remove if not
+        // Ensure all messages are signed by a valid member or the room owner, remove if not.
+        // A member who left keeps their messages up to their latest leave event;
+        // anything they post after it is refused.
         let members_by_id = parent_state.members.members_by_member_id();
         let owner_id = MemberId::from(&parameters.owner);
+        let departed_keys = parent_state.departures.keys();
+        let mut left_at: HashMap<MemberId, MessageOrderKey> = HashMap::new();
+        for m in &self.messages {
+            if m.message.content.is_leave_event() && departed_keys.contains_key(&m.message.author) {
+                let key = m.order_key();
+                let latest = left_at
+                    .entry(m.message.author)
+                    .or_insert_with(|| key.clone());
+                if key > *latest {
+                    *latest = key;
+                }
+            }
+        }
         self.messages.retain(|m| {
-            members_by_id.contains_key(&m.message.author) || m.message.author == owner_id
+            members_by_id.contains_key(&m.message.author)
+                || m.message.author == owner_id
+                || left_at
+                    .get(&m.message.author)
+                    .is_some_and(|latest| m.order_key() <= *latest)
         });
 
+        // Pins override retention, so only pin-authorized members may author
+        // pin/unpin actions. Re-checked on every apply so that revoking a
+        // deputy also drops their pins.
+        self.messages.retain(|m| {
+            !m.message.content.is_pin_action()
+                || MembersV1::is_pin_authorized(
+                    m.message.author,
+                    &members_by_id,
+                    &parent_state.member_info,
+                    owner_id,
+                )
+        });
+
+        // An announcement room's posting policy, over the messages since it
+        // was set. Re-checked on every apply, like pin authority, so a policy
+        // change converges; see `PostingPolicy`.
+        let configuration = &parent_state.configuration.configuration;
+        if configuration.posting_policy.is_some() {
+            self.messages
+                .retain(|m| configuration.posting_permits(&m.message, owner_id));
+        }
+
+        // Muted members' messages from inside their mute's window are not
+        // accepted; their history from before and after stays.
+        // `post_apply_cleanup` applies the same rule to messages that arrived
+        // before the mute did.
+        let mute_windows = parent_state.mutes.mute_windows(
+            &members_by_id,
+            &parent_state.member_info,
+            owner_id,
+            &parameters.owner,
+            parent_state.configuration.configuration.max_members,
+        );
+        if !mute_windows.is_empty() {
+            self.messages
+                .retain(|m| !MutesV1::silences(&mute_windows, m));
+        }
+
         // Sort messages by time, with MessageId as secondary sort for deterministic ordering
         // (CRDT convergence requirement - without this, ties produce non-deterministic order)
         self.messages.sort_by(|a, b| {
@@ -327,6 +513,12 @@ impl ComposableState for MessagesV1 {
                 .then_with(|| a.id().cmp(&b.id()))
         });
 
+        // Slow mode runs before the cap, so a flood displaces the flooder's
+        // own messages instead of evicting everyone else's history.
+        if let Some(slow_mode) = &parent_state.configuration.configuration.slow_mode {
+            self.enforce_slow_mode(slow_mode, owner_id);
+        }
+
         // Remove oldest messages if there are too many.
         //
         // This removal is what makes the merge non-monotonic, so it MUST stay
@@ -334,9 +526,27 @@ impl ComposableState for MessagesV1 {
         // `delta` filters on — see [`RetentionHorizon`]. Changing the retention
         // rule here (a different sort key, a cap on a different axis) without
         // teaching `retention_horizon` about it re-opens the resend loop.
-        if self.messages.len() > max_recent_messages {
-            self.messages
-                .drain(0..self.messages.len() - max_recent_messages);
+        //
+        // Pinned messages and their pin actions don't count toward the cap and
+        // are never dropped. Dropping only non-exempt messages cannot change
+        // which pins are in effect: a superseding unpin is always newer than
+        // the pin it cancels, so the pin is dropped first.
+        let exempt = self.retention_exempt_ids();
+        let prunable = self
+            .messages
+            .iter()
+            .filter(|m| !exempt.contains(&m.id()))
+            .count();
+        if prunable > max_recent_messages {
+            let mut excess = prunable - max_recent_messages;
+            self.messages.retain(|m| {
+                if excess > 0 && !exempt.contains(&m.id()) {
+                    excess -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
         }
 
         // Rebuild computed state from action messages
@@ -346,6 +556,19 @@ impl ComposableState for MessagesV1 {
     }
 }
 
+/// Whether `msg` uses up its author's [`SlowMode`] budget. The owner, event
+/// messages, pin actions and retention-exempt messages never do.
+fn counts_toward_slow_mode(
+    msg: &AuthorizedMessageV1,
+    owner_id: MemberId,
+    exempt: &HashSet<MessageId>,
+) -> bool {
+    msg.message.author != owner_id
+        && !msg.message.content.is_event()
+        && !msg.message.content.is_pin_action()
+        && !exempt.contains(&msg.id())
+}
+
 impl MessagesV1 {
     /// The [`RetentionHorizon`] this peer publishes for the given
     /// `max_recent_messages`.
@@ -361,21 +584,192 @@ impl MessagesV1 {
     ///   sorted, but `verify` does not enforce that, so a hand-built or hostile
     ///   full-state PUT could arrive unsorted; taking the min is correct either
     ///   way and avoids an out-of-bounds index.
+    ///
+    /// Pinned messages and their pin actions are left out of both the count
+    /// and the minimum, matching the trim in `apply_delta`; `delta` offers
+    /// them irrespective of the horizon.
     pub fn retention_horizon(&self, max_recent_messages: usize) -> RetentionHorizon {
         if max_recent_messages == 0 {
             return RetentionHorizon::Closed;
         }
-        if self.messages.len() < max_recent_messages {
+        let exempt = self.retention_exempt_ids();
+        let prunable = || self.messages.iter().filter(|m| !exempt.contains(&m.id()));
+        if prunable().count() < max_recent_messages {
             return RetentionHorizon::Open;
         }
-        match self.messages.iter().map(|m| m.order_key()).min() {
+        match prunable().map(|m| m.order_key()).min() {
             Some(oldest) => RetentionHorizon::OldestRetained(oldest),
-            // Unreachable: len >= max_recent_messages >= 1 means non-empty.
+            // Unreachable: prunable >= max_recent_messages >= 1 means non-empty.
             // `Open` is the safe fallback (offers more, never drops).
             None => RetentionHorizon::Open,
         }
     }
 
+    /// Drop each member's messages beyond the [`SlowMode`] budget, keeping the
+    /// newest; see [`SlowMode`] for why the newest. Expects `self.messages`
+    /// sorted by `(time, id)`, as `apply_delta` leaves it.
+    fn enforce_slow_mode(&mut self, slow_mode: &SlowMode, owner_id: MemberId) {
+        let budget = slow_mode.max_messages as usize;
+        let window = slow_mode.window_duration();
+        let exempt = self.retention_exempt_ids();
+        // Per author, the times of the messages kept so far, newest first.
+        let mut kept: HashMap<MemberId, VecDeque<SystemTime>> = HashMap::new();
+        let mut dropped: HashSet<MessageId> = HashSet::new();
+        for msg in self.messages.iter().rev() {
+            if !counts_toward_slow_mode(msg, owner_id, &exempt) {
+                continue;
+            }
+            let time = msg.message.time;
+            let times = kept.entry(msg.message.author).or_default();
+            // Forget kept messages a full window or more newer than this one.
+            if let Some(window_end) = window.and_then(|w| time.checked_add(w)) {
+                while times.front().is_some_and(|t| *t >= window_end) {
+                    times.pop_front();
+                }
+            }
+            if times.len() < budget {
+                times.push_back(time);
+            } else {
+                dropped.insert(msg.id());
+            }
+        }
+        if !dropped.is_empty() {
+            self.messages.retain(|m| !dropped.contains(&m.id()));
+        }
+    }
+
+    /// How long `author` should wait before a message sent at `now` fits the
+    /// room's [`SlowMode`], or `None` if it fits now. Sending anyway is not
+    /// rejected; it displaces the author's own oldest message in the window.
+    ///
+    /// Always `None` for a
+    /// [`Retained`](crate::room_state::configuration::SlowModeWindow::Retained)
+    /// budget, where waiting does not help: the budget frees up only as the
+    /// room's history moves on.
+    pub fn slow_mode_retry_after(
+        &self,
+        slow_mode: &SlowMode,
+        author: MemberId,
+        owner_id: MemberId,
+        now: SystemTime,
+    ) -> Option<Duration> {
+        let window = slow_mode.window_duration()?;
+        if author == owner_id || slow_mode.max_messages == 0 {
+            return None;
+        }
+        let exempt = self.retention_exempt_ids();
+        let window_start = now.checked_sub(window).unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut recent: Vec<SystemTime> = self
+            .messages
+            .iter()
+            .filter(|m| m.message.author == author && m.message.time > window_start)
+            .filter(|m| counts_toward_slow_mode(m, owner_id, &exempt))
+            .map(|m| m.message.time)
+            .collect();
+        let budget = slow_mode.max_messages as usize;
+        if recent.len() < budget {
+            return None;
+        }
+        recent.sort_unstable_by(|a, b| b.cmp(a));
+        let frees_at = recent[budget - 1].checked_add(window)?;
+        Some(frees_at.duration_since(now).unwrap_or_default())
+    }
+
+    /// The pins currently in effect, oldest pin first.
+    ///
+    /// Folds PUBLIC pin/unpin actions in `(time, id)` order; the latest one
+    /// per target wins. Only pins on a present, non-action message count, and
+    /// of those only the [`MAX_PINNED_MESSAGES`] most recently pinned. Pin
+    /// actions are always public, so this reads the same on every peer and in
+    /// the contract. Authorization is not checked here: `apply_delta` drops
+    /// pin actions whose author is not [`MembersV1::is_pin_authorized`].
+    ///
+    /// Does not consult deletions (a delete may be encrypted), so a pinned
+    /// message its author deleted stays pinned — and retained — until
+    /// unpinned; [`MessageActionsState::pinned`] hides it.
+    pub fn pins(&self) -> Vec<Pin> {
+        use crate::room_state::content::{DecodedContent, ACTION_TYPE_PIN};
+
+        let targets: HashSet<MessageId> = self
+            .messages
+            .iter()
+            .filter(|m| !m.message.content.is_action())
+            .map(|m| m.id())
+            .collect();
+
+        let mut pin_actions: Vec<&AuthorizedMessageV1> = self
+            .messages
+            .iter()
+            .filter(|m| m.message.content.is_pin_action())
+            .collect();
+        // `apply_delta` keeps messages sorted, but `verify` doesn't require it.
+        pin_actions.sort_by_key(|m| m.order_key());
+
+        let mut in_effect: HashMap<MessageId, Pin> = HashMap::new();
+        for msg in pin_actions {
+            let Some(DecodedContent::Action(action)) = msg.message.content.decode_content() else {
+                continue;
+            };
+            if !targets.contains(&action.target) {
+                continue;
+            }
+            if action.action_type == ACTION_TYPE_PIN {
+                in_effect.insert(
+                    action.target.clone(),
+                    Pin {
+                        target: action.target,
+                        pin_action: msg.id(),
+                        pinned_by: msg.message.author,
+                        pinned_at: msg.message.time,
+                    },
+                );
+            } else {
+                in_effect.remove(&action.target);
+            }
+        }
+
+        let mut pins: Vec<Pin> = in_effect.into_values().collect();
+        pins.sort_by(|a, b| {
+            a.pinned_at
+                .cmp(&b.pinned_at)
+                .then_with(|| a.pin_action.cmp(&b.pin_action))
+        });
+        if pins.len() > MAX_PINNED_MESSAGES {
+            pins.drain(0..pins.len() - MAX_PINNED_MESSAGES);
+        }
+        pins
+    }
+
+    /// Members whose latest message, in `(time, id)` order, is a leave event.
+    ///
+    /// `post_apply_cleanup` treats them as inactive, so they are pruned from
+    /// the room unless something else (an invitee, a ban they issued) still
+    /// needs them. Posting anything after leaving — a join event on rejoin,
+    /// say — cancels the departure.
+    pub fn departed_members(&self) -> HashSet<MemberId> {
+        let mut latest: HashMap<MemberId, &AuthorizedMessageV1> = HashMap::new();
+        for msg in &self.messages {
+            let entry = latest.entry(msg.message.author).or_insert(msg);
+            if msg.order_key() > entry.order_key() {
+                *entry = msg;
+            }
+        }
+        latest
+            .into_iter()
+            .filter(|(_, msg)| msg.message.content.is_leave_event())
+            .map(|(author, _)| author)
+            .collect()
+    }
+
+    /// Messages that retention never drops: every pinned message and the pin
+    /// action keeping it pinned.
+    fn retention_exempt_ids(&self) -> HashSet<MessageId> {
+        self.pins()
+            .into_iter()
+            .flat_map(|pin| [pin.target, pin.pin_action])
+            .collect()
+    }
+
     /// Rebuild the computed actions state by scanning all action messages.
     ///
     /// This method only processes PUBLIC action messages. For private rooms,
@@ -399,7 +793,7 @@ impl MessagesV1 {
     ) {
         use crate::room_state::content::{
             ActionContentV1, DecodedContent, ACTION_TYPE_DELETE, ACTION_TYPE_EDIT,
-            ACTION_TYPE_REACTION, ACTION_TYPE_REMOVE_REACTION,
+            ACTION_TYPE_POLL_VOTE, ACTION_TYPE_REACTION, ACTION_TYPE_REMOVE_REACTION,
         };
 
         // Clear existing computed state
@@ -413,6 +807,15 @@ impl MessagesV1 {
             .map(|m| (m.id(), m.message.author))
             .collect();
 
+        // Poll messages, identified by their plaintext content_type so that
+        // votes on private (encrypted) polls are folded too.
+        let polls: HashSet<MessageId> = self
+            .messages
+            .iter()
+            .filter(|m| m.message.content.is_poll())
+            .map(|m| m.id())
+            .collect();
+
         // Process action messages in timestamp order (messages are already sorted)
         for msg in &self.messages {
             let actor = msg.message.author;
@@ -510,11 +913,37 @@ impl MessagesV1 {
                         }
                     }
                 }
+                ACTION_TYPE_POLL_VOTE => {
+                    // Any member can vote on a non-deleted poll. Ballots are
+                    // validated against the poll body at tally time.
+                    if polls.contains(target) && !self.actions_state.deleted.contains(target) {
+                        if let Some(payload) = action.poll_vote_payload() {
+                            self.actions_state
+                                .poll_votes
+                                .entry(target.clone())
+                                .or_default()
+                                .entry(actor)
+                                .or_default()
+                                .push(PollBallot {
+                                    choices: payload.choices,
+                                    time: msg.message.time,
+                                });
+                        }
+                    }
+                }
                 _ => {
-                    // Unknown action type - ignore for forward compatibility
+                    // Pins are folded by `pins` below; anything else is an
+                    // unknown action type - ignore for forward compatibility
                 }
             }
         }
+
+        self.actions_state.pinned = self
+            .pins()
+            .into_iter()
+            .map(|pin| pin.target)
+            .filter(|target| !self.actions_state.deleted.contains(target))
+            .collect();
     }
 
     /// Check if a message has been edited
@@ -527,6 +956,11 @@ impl MessagesV1 {
         self.actions_state.deleted.contains(message_id)
     }
 
+    /// Check if a message is pinned
+    pub fn is_pinned(&self, message_id: &MessageId) -> bool {
+        self.actions_state.pinned.contains(message_id)
+    }
+
     /// Get the effective text content for a message (edited content if edited, original otherwise)
     /// Returns the text content as a string, or None if the message is encrypted/undecodable
     pub fn effective_text(&self, message: &AuthorizedMessageV1) -> Option<String> {
@@ -544,6 +978,88 @@ impl MessagesV1 {
         self.actions_state.reactions.get(message_id)
     }
 
+    /// Count the votes on `poll_id`, whose decoded body is `poll`.
+    ///
+    /// Each voter's counted ballot is their LATEST one that was cast while the
+    /// poll was open and is well-formed for its options; a later malformed or
+    /// post-close ballot never erases an earlier valid one. A counted empty
+    /// ballot is a retraction. Only signed message timestamps are consulted,
+    /// so the result is identical on every peer.
+    pub fn poll_tally(
+        &self,
+        poll_id: &MessageId,
+        poll: &crate::room_state::content::PollContentV1,
+    ) -> PollTally {
+        let mut tally = PollTally {
+            voters: vec![Vec::new(); poll.options.len()],
+            total_voters: 0,
+        };
+        let Some(ballots_by_voter) = self.actions_state.poll_votes.get(poll_id) else {
+            return tally;
+        };
+        for (voter, ballots) in ballots_by_voter {
+            let counted = ballots
+                .iter()
+                .rev()
+                .find(|b| poll.is_open_at(b.time) && poll.accepts_choices(&b.choices));
+            let Some(ballot) = counted else { continue };
+            if ballot.choices.is_empty() {
+                continue;
+            }
+            tally.total_voters += 1;
+            for &choice in &ballot.choices {
+                tally.voters[choice as usize].push(*voter);
+            }
+        }
+        for voters in &mut tally.voters {
+            voters.sort();
+        }
+        tally
+    }
+
+    /// Build the reply threads of the public messages. See
+    /// [`Self::thread_index_with`] for rooms with private replies.
+    pub fn thread_index(&self) -> ThreadIndex {
+        self.thread_index_with(|m| m.message.content.reply_target_id())
+    }
+
+    /// Build the reply threads, using `reply_target` to find the message each
+    /// message replies to. Clients pass a function that also decrypts private
+    /// replies with the room secrets.
+    ///
+    /// Only displayed messages take part (see [`Self::display_messages`]); a
+    /// reply whose target was deleted or has been pruned is the root of its
+    /// own thread.
+    pub fn thread_index_with(
+        &self,
+        reply_target: impl Fn(&AuthorizedMessageV1) -> Option<MessageId>,
+    ) -> ThreadIndex {
+        let mut index = ThreadIndex::default();
+        for (position, msg) in self.display_messages().enumerate() {
+            index.positions.insert(msg.id(), position);
+        }
+        for msg in self.display_messages() {
+            let id = msg.id();
+            let Some(parent) = reply_target(msg) else {
+                continue;
+            };
+            // Only earlier messages can be parents, which also rules out cycles.
+            let is_earlier = match (index.positions.get(&parent), index.positions.get(&id)) {
+                (Some(p), Some(c)) => p < c,
+                _ => false,
+            };
+            if is_earlier {
+                index
+                    .children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(id.clone());
+                index.parents.insert(id, parent);
+            }
+        }
+        index
+    }
+
     /// Get all non-deleted, non-action messages for display
     pub fn display_messages(&self) -> impl Iterator<Item = &AuthorizedMessageV1> {
         self.messages.iter().filter(|m| {
@@ -564,6 +1080,7 @@ impl MessagesV1 {
 /// - `content_type = 4`: Room event like join/leave (EventContentV1)
 ///   - Allowed as Public even in private rooms (contains no sensitive content)
 ///   - Old clients display as "[Unsupported message type 4.1 - please upgrade]"
+/// - `content_type = 6`: Poll (PollContentV1); votes are actions on it
 /// - Future types can be added without contract changes
 ///
 /// # Extensibility
@@ -640,6 +1157,19 @@ impl RoomMessageBody {
         }
     }
 
+    /// Create a leave event message
+    pub fn leave_event() -> Self {
+        use crate::room_state::content::{
+            EventContentV1, CONTENT_TYPE_EVENT, EVENT_CONTENT_VERSION,
+        };
+        let content = EventContentV1::leave();
+        Self::Public {
+            content_type: CONTENT_TYPE_EVENT,
+            content_version: EVENT_CONTENT_VERSION,
+            data: content.encode(),
+        }
+    }
+
     /// Create a new public message with raw content
     pub fn public_raw(content_type: u32, content_version: u32, data: Vec<u8>) -> Self {
         Self::Public {
@@ -734,6 +1264,65 @@ impl RoomMessageBody {
         }
     }
 
+    /// Create a pin action (always public, even in private rooms)
+    pub fn pin(target: MessageId) -> Self {
+        use crate::room_state::content::{
+            ActionContentV1, ACTION_CONTENT_VERSION, CONTENT_TYPE_ACTION,
+        };
+        let action = ActionContentV1::pin(target);
+        Self::Public {
+            content_type: CONTENT_TYPE_ACTION,
+            content_version: ACTION_CONTENT_VERSION,
+            data: action.encode(),
+        }
+    }
+
+    /// Create an unpin action (always public, even in private rooms)
+    pub fn unpin(target: MessageId) -> Self {
+        use crate::room_state::content::{
+            ActionContentV1, ACTION_CONTENT_VERSION, CONTENT_TYPE_ACTION,
+        };
+        let action = ActionContentV1::unpin(target);
+        Self::Public {
+            content_type: CONTENT_TYPE_ACTION,
+            content_version: ACTION_CONTENT_VERSION,
+            data: action.encode(),
+        }
+    }
+
+    /// Create a public attachment message
+    pub fn blob(blob: crate::room_state::content::BlobContentV1) -> Self {
+        use crate::room_state::content::{BLOB_CONTENT_VERSION, CONTENT_TYPE_BLOB};
+        Self::Public {
+            content_type: CONTENT_TYPE_BLOB,
+            content_version: BLOB_CONTENT_VERSION,
+            data: blob.encode(),
+        }
+    }
+
+    /// Create a public poll message
+    pub fn poll(poll: crate::room_state::content::PollContentV1) -> Self {
+        use crate::room_state::content::{CONTENT_TYPE_POLL, POLL_CONTENT_VERSION};
+        Self::Public {
+            content_type: CONTENT_TYPE_POLL,
+            content_version: POLL_CONTENT_VERSION,
+            data: poll.encode(),
+        }
+    }
+
+    /// Create a poll vote action (public)
+    pub fn poll_vote(target: MessageId, choices: Vec<u32>) -> Self {
+        use crate::room_state::content::{
+            ActionContentV1, ACTION_CONTENT_VERSION, CONTENT_TYPE_ACTION,
+        };
+        let action = ActionContentV1::poll_vote(target, choices);
+        Self::Public {
+            content_type: CONTENT_TYPE_ACTION,
+            content_version: ACTION_CONTENT_VERSION,
+            data: action.encode(),
+        }
+    }
+
     /// Create a public reply message
     pub fn reply(
         text: String,
@@ -821,12 +1410,48 @@ impl RoomMessageBody {
         self.content_type() == CONTENT_TYPE_EVENT
     }
 
+    /// Check if this is a public join event
+    pub fn is_join_event(&self) -> bool {
+        use crate::room_state::content::{DecodedContent, EVENT_TYPE_JOIN};
+        self.is_event()
+            && matches!(self.decode_content(), Some(DecodedContent::Event(e)) if e.event_type == EVENT_TYPE_JOIN)
+    }
+
+    /// Check if this is a public leave event
+    pub fn is_leave_event(&self) -> bool {
+        use crate::room_state::content::{DecodedContent, EVENT_TYPE_LEAVE};
+        self.is_event()
+            && matches!(self.decode_content(), Some(DecodedContent::Event(e)) if e.event_type == EVENT_TYPE_LEAVE)
+    }
+
+    /// Check if this is an attachment message (content_type = BLOB)
+    pub fn is_blob(&self) -> bool {
+        use crate::room_state::content::CONTENT_TYPE_BLOB;
+        self.content_type() == CONTENT_TYPE_BLOB
+    }
+
+    /// Check if this is a poll message (content_type = POLL)
+    pub fn is_poll(&self) -> bool {
+        use crate::room_state::content::CONTENT_TYPE_POLL;
+        self.content_type() == CONTENT_TYPE_POLL
+    }
+
+    /// Check if this is a public pin or unpin action. Private pin actions
+    /// are not a thing: the contract has to read them.
+    pub fn is_pin_action(&self) -> bool {
+        use crate::room_state::content::DecodedContent;
+        self.is_public()
+            && self.is_action()
+            && matches!(self.decode_content(), Some(DecodedContent::Action(a)) if a.is_pin_action())
+    }
+
     /// Decode the content (for public messages only)
     /// Returns None for private messages - decrypt first
     pub fn decode_content(&self) -> Option<crate::room_state::content::DecodedContent> {
         use crate::room_state::content::{
-            ActionContentV1, DecodedContent, EventContentV1, ReplyContentV1, TextContentV1,
-            CONTENT_TYPE_ACTION, CONTENT_TYPE_EVENT, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
+            ActionContentV1, BlobContentV1, DecodedContent, EventContentV1, PollContentV1,
+            ReplyContentV1, TextContentV1, CONTENT_TYPE_ACTION, CONTENT_TYPE_BLOB,
+            CONTENT_TYPE_EVENT, CONTENT_TYPE_POLL, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
         };
         match self {
             Self::Public {
@@ -840,6 +1465,8 @@ impl RoomMessageBody {
                     .map(DecodedContent::Action),
                 CONTENT_TYPE_REPLY => ReplyContentV1::decode(data).ok().map(DecodedContent::Reply),
                 CONTENT_TYPE_EVENT => EventContentV1::decode(data).ok().map(DecodedContent::Event),
+                CONTENT_TYPE_BLOB => BlobContentV1::decode(data).ok().map(DecodedContent::Blob),
+                CONTENT_TYPE_POLL => PollContentV1::decode(data).ok().map(DecodedContent::Poll),
                 _ => Some(DecodedContent::Unknown {
                     content_type: *content_type,
                     content_version: *content_version,
@@ -862,6 +1489,20 @@ impl RoomMessageBody {
         }
     }
 
+    /// Get the replied-to message ID if this is a public reply.
+    /// Returns None for private messages - decrypt first
+    pub fn reply_target_id(&self) -> Option<MessageId> {
+        use crate::room_state::content::{ReplyContentV1, CONTENT_TYPE_REPLY};
+        match self {
+            Self::Public {
+                content_type, data, ..
+            } if *content_type == CONTENT_TYPE_REPLY => ReplyContentV1::decode(data)
+                .ok()
+                .map(|r| r.target_message_id),
+            _ => None,
+        }
+    }
+
     /// Get the content length for validation (contract uses this for size limits)
     pub fn content_len(&self) -> usize {
         match self {
@@ -1620,6 +2261,311 @@ mod tests {
         );
     }
 
+    fn slow_mode_room(
+        slow_mode: SlowMode,
+    ) -> (
+        ChatRoomStateV1,
+        ChatRoomParametersV1,
+        SigningKey,
+        SigningKey,
+        SigningKey,
+    ) {
+        let owner_sk = SigningKey::generate(&mut OsRng);
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let alice_sk = SigningKey::generate(&mut OsRng);
+        let bob_sk = SigningKey::generate(&mut OsRng);
+        let mut parent_state = ChatRoomStateV1::default();
+        parent_state.configuration.configuration.slow_mode = Some(slow_mode);
+        parent_state.members.members = [&alice_sk, &bob_sk]
+            .iter()
+            .map(|sk| crate::room_state::member::AuthorizedMember {
+                member: crate::room_state::member::Member {
+                    owner_member_id: owner_id,
+                    invited_by: owner_id,
+                    member_vk: sk.verifying_key(),
+                },
+                signature: owner_sk.try_sign(&[0; 32]).unwrap(),
+            })
+            .collect();
+        let parameters = ChatRoomParametersV1 {
+            owner: owner_sk.verifying_key(),
+        };
+        (parent_state, parameters, owner_sk, alice_sk, bob_sk)
+    }
+
+    fn message_at(author_sk: &SigningKey, owner_sk: &SigningKey, secs: u64) -> AuthorizedMessageV1 {
+        AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: MemberId::from(&owner_sk.verifying_key()),
+                author: MemberId::from(&author_sk.verifying_key()),
+                time: SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + secs),
+                content: RoomMessageBody::public(format!("at {secs}")),
+            },
+            author_sk,
+        )
+    }
+
+    #[test]
+    fn slow_mode_keeps_each_members_newest_messages_in_the_window() {
+        let slow_mode = SlowMode {
+            max_messages: 2,
+            window: crate::room_state::configuration::SlowModeWindow::Seconds(10),
+        };
+        let (parent_state, parameters, owner_sk, alice_sk, bob_sk) = slow_mode_room(slow_mode);
+        let flood: Vec<_> = [0, 1, 2, 3, 100]
+            .iter()
+            .map(|&secs| message_at(&alice_sk, &owner_sk, secs))
+            .collect();
+        let bob = message_at(&bob_sk, &owner_sk, 2);
+        let owner: Vec<_> = (0..4)
+            .map(|secs| message_at(&owner_sk, &owner_sk, secs))
+            .collect();
+
+        let mut delta = flood.clone();
+        delta.push(bob.clone());
+        delta.extend(owner.iter().cloned());
+        let mut messages = MessagesV1::default();
+        messages
+            .apply_delta(&parent_state, &parameters, &Some(delta))
+            .unwrap();
+
+        // Alice keeps her newest two within any 10s, plus the later one.
+        for kept in [&flood[2], &flood[3], &flood[4], &bob] {
+            assert!(messages.messages.contains(kept));
+        }
+        assert!(!messages.messages.contains(&flood[0]));
+        assert!(!messages.messages.contains(&flood[1]));
+        // The owner is never limited.
+        for kept in &owner {
+            assert!(messages.messages.contains(kept));
+        }
+    }
+
+    #[test]
+    fn slow_mode_converges_regardless_of_delivery_order() {
+        for window in [
+            crate::room_state::configuration::SlowModeWindow::Seconds(10),
+            crate::room_state::configuration::SlowModeWindow::Retained,
+        ] {
+            let slow_mode = SlowMode {
+                max_messages: 2,
+                window,
+            };
+            let (parent_state, parameters, owner_sk, alice_sk, _) = slow_mode_room(slow_mode);
+            let flood: Vec<_> = [0, 4, 8, 12, 30, 31]
+                .iter()
+                .map(|&secs| message_at(&alice_sk, &owner_sk, secs))
+                .collect();
+
+            let mut all_at_once = MessagesV1::default();
+            all_at_once
+                .apply_delta(&parent_state, &parameters, &Some(flood.clone()))
+                .unwrap();
+
+            let mut one_by_one = MessagesV1::default();
+            for msg in flood.iter().rev().chain(flood.iter()) {
+                one_by_one
+                    .apply_delta(&parent_state, &parameters, &Some(vec![msg.clone()]))
+                    .unwrap();
+            }
+            assert_eq!(all_at_once.messages, one_by_one.messages, "{window:?}");
+            assert!(all_at_once.messages.contains(&flood[5]));
+        }
+    }
+
+    #[test]
+    fn forged_spaced_timestamps_pass_seconds_but_not_retained() {
+        use crate::room_state::configuration::SlowModeWindow;
+        // One burst, back-dated so each message sits a full window apart.
+        let burst = |alice_sk: &SigningKey, owner_sk: &SigningKey| -> Vec<_> {
+            (0..6)
+                .map(|i| message_at(alice_sk, owner_sk, i * 10))
+                .collect()
+        };
+
+        let advisory = SlowMode {
+            max_messages: 1,
+            window: SlowModeWindow::Seconds(10),
+        };
+        let (parent_state, parameters, owner_sk, alice_sk, _) = slow_mode_room(advisory);
+        let forged = burst(&alice_sk, &owner_sk);
+        let mut messages = MessagesV1::default();
+        messages
+            .apply_delta(&parent_state, &parameters, &Some(forged.clone()))
+            .unwrap();
+        assert_eq!(messages.messages, forged, "the timestamps fool `Seconds`");
+
+        let enforced = SlowMode {
+            max_messages: 1,
+            window: SlowModeWindow::default(),
+        };
+        let (parent_state, parameters, owner_sk, alice_sk, _) = slow_mode_room(enforced);
+        let forged = burst(&alice_sk, &owner_sk);
+        let mut messages = MessagesV1::default();
+        messages
+            .apply_delta(&parent_state, &parameters, &Some(forged.clone()))
+            .unwrap();
+        assert_eq!(
+            messages.messages,
+            vec![forged[5].clone()],
+            "`Retained` keeps only the newest, whatever the timestamps"
+        );
+    }
+
+    #[test]
+    fn slow_mode_retry_after_reports_when_the_budget_frees_up() {
+        let slow_mode = SlowMode {
+            max_messages: 2,
+            window: crate::room_state::configuration::SlowModeWindow::Seconds(10),
+        };
+        let (parent_state, parameters, owner_sk, alice_sk, bob_sk) = slow_mode_room(slow_mode);
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let alice_id = MemberId::from(&alice_sk.verifying_key());
+        let mut messages = MessagesV1::default();
+        messages
+            .apply_delta(
+                &parent_state,
+                &parameters,
+                &Some(vec![
+                    message_at(&alice_sk, &owner_sk, 0),
+                    message_at(&alice_sk, &owner_sk, 4),
+                    message_at(&bob_sk, &owner_sk, 5),
+                ]),
+            )
+            .unwrap();
+        let at = |secs: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + secs);
+
+        assert_eq!(
+            messages.slow_mode_retry_after(&slow_mode, alice_id, owner_id, at(6)),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(
+            messages.slow_mode_retry_after(&slow_mode, alice_id, owner_id, at(10)),
+            None
+        );
+        let bob_id = MemberId::from(&bob_sk.verifying_key());
+        assert_eq!(
+            messages.slow_mode_retry_after(&slow_mode, bob_id, owner_id, at(6)),
+            None
+        );
+    }
+
+    #[test]
+    fn posting_policy_keeps_reactions_and_events_from_non_posters() {
+        use crate::room_state::configuration::PostingPolicy;
+        let slow_mode = SlowMode {
+            max_messages: 100,
+            window: crate::room_state::configuration::SlowModeWindow::Retained,
+        };
+        let (mut parent_state, parameters, owner_sk, alice_sk, bob_sk) = slow_mode_room(slow_mode);
+        let alice_id = MemberId::from(&alice_sk.verifying_key());
+        let announcement = message_at(&alice_sk, &owner_sk, 1);
+        let owner_post = message_at(&owner_sk, &owner_sk, 2);
+        let bob_post = message_at(&bob_sk, &owner_sk, 3);
+        let with_body = |body: RoomMessageBody, secs: u64| {
+            AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: MemberId::from(&owner_sk.verifying_key()),
+                    author: MemberId::from(&bob_sk.verifying_key()),
+                    time: SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + secs),
+                    content: body,
+                },
+                &bob_sk,
+            )
+        };
+        let bob_reaction = with_body(RoomMessageBody::reaction(announcement.id(), "👍".into()), 4);
+        let bob_join = with_body(RoomMessageBody::join_event(), 5);
+        let all = vec![
+            announcement.clone(),
+            owner_post.clone(),
+            bob_post.clone(),
+            bob_reaction.clone(),
+            bob_join.clone(),
+        ];
+
+        // Bob's post arrived before the policy did; the next apply drops it.
+        let mut messages = MessagesV1::default();
+        messages
+            .apply_delta(&parent_state, &parameters, &Some(all.clone()))
+            .unwrap();
+        assert!(messages.messages.contains(&bob_post));
+
+        parent_state.configuration.configuration.posting_policy =
+            Some(PostingPolicy::Allowlist(vec![alice_id]));
+        messages
+            .apply_delta(&parent_state, &parameters, &None)
+            .unwrap();
+        let mut refused_up_front = MessagesV1::default();
+        refused_up_front
+            .apply_delta(&parent_state, &parameters, &Some(all))
+            .unwrap();
+        assert_eq!(messages.messages, refused_up_front.messages);
+        for kept in [&announcement, &owner_post, &bob_reaction, &bob_join] {
+            assert!(messages.messages.contains(kept));
+        }
+        assert!(!messages.messages.contains(&bob_post));
+
+        parent_state.configuration.configuration.posting_policy = Some(PostingPolicy::OwnerOnly);
+        messages
+            .apply_delta(&parent_state, &parameters, &None)
+            .unwrap();
+        assert!(!messages.messages.contains(&announcement));
+        assert!(messages.messages.contains(&owner_post));
+    }
+
+    #[test]
+    fn posting_policy_keeps_posts_from_before_it_was_set() {
+        use crate::room_state::configuration::PostingPolicy;
+        let slow_mode = SlowMode {
+            max_messages: 100,
+            window: crate::room_state::configuration::SlowModeWindow::Retained,
+        };
+        let (mut parent_state, parameters, owner_sk, alice_sk, bob_sk) = slow_mode_room(slow_mode);
+        let alice_id = MemberId::from(&alice_sk.verifying_key());
+        let earlier = message_at(&bob_sk, &owner_sk, 1);
+        let later = message_at(&bob_sk, &owner_sk, 20);
+        let allowed = message_at(&alice_sk, &owner_sk, 21);
+        let all = vec![earlier.clone(), later.clone(), allowed.clone()];
+
+        let mut messages = MessagesV1::default();
+        messages
+            .apply_delta(&parent_state, &parameters, &Some(all.clone()))
+            .unwrap();
+
+        parent_state.configuration.configuration.set_posting_policy(
+            Some(PostingPolicy::Allowlist(vec![alice_id])),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_010),
+        );
+        messages
+            .apply_delta(&parent_state, &parameters, &None)
+            .unwrap();
+        let mut refused_up_front = MessagesV1::default();
+        refused_up_front
+            .apply_delta(&parent_state, &parameters, &Some(all))
+            .unwrap();
+        assert_eq!(messages.messages, refused_up_front.messages);
+        assert!(messages.messages.contains(&earlier), "history stays");
+        assert!(messages.messages.contains(&allowed));
+        assert!(!messages.messages.contains(&later));
+
+        // Setting the same policy again keeps its cutoff.
+        let since = parent_state
+            .configuration
+            .configuration
+            .posting_policy_since;
+        parent_state.configuration.configuration.set_posting_policy(
+            Some(PostingPolicy::Allowlist(vec![alice_id])),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_030),
+        );
+        assert_eq!(
+            parent_state
+                .configuration
+                .configuration
+                .posting_policy_since,
+            since
+        );
+    }
+
     #[test]
     fn test_message_author_preservation_across_users() {
         // Create two users
@@ -2049,6 +2995,424 @@ mod tests {
         assert!(!messages.is_edited(&original_id));
     }
 
+    #[test]
+    fn test_poll_votes_are_tallied() {
+        use crate::room_state::content::PollContentV1;
+
+        let owner_sk = SigningKey::generate(&mut OsRng);
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let voter_sk = SigningKey::generate(&mut OsRng);
+        let voter_id = MemberId::from(&voter_sk.verifying_key());
+        let base = SystemTime::now();
+
+        let poll = PollContentV1::new(
+            "Lunch?".to_string(),
+            vec![
+                "Pizza".to_string(),
+                "Sushi".to_string(),
+                "Tacos".to_string(),
+            ],
+            false,
+            None,
+        );
+        let auth_poll = AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: owner_id,
+                author: owner_id,
+                time: base,
+                content: RoomMessageBody::poll(poll.clone()),
+            },
+            &owner_sk,
+        );
+        let poll_id = auth_poll.id();
+
+        let vote = |sk: &SigningKey, secs: u64, choices: Vec<u32>| {
+            AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: owner_id,
+                    author: MemberId::from(&sk.verifying_key()),
+                    time: base + Duration::from_secs(secs),
+                    content: RoomMessageBody::poll_vote(poll_id.clone(), choices),
+                },
+                sk,
+            )
+        };
+
+        let mut messages = MessagesV1 {
+            messages: vec![
+                auth_poll,
+                vote(&owner_sk, 1, vec![0]),
+                vote(&voter_sk, 2, vec![1]),
+                // The voter changes their mind; only the latest ballot counts.
+                vote(&voter_sk, 3, vec![0]),
+                // Malformed for a single-choice poll: ignored, not a retraction.
+                vote(&voter_sk, 4, vec![1, 2]),
+            ],
+            ..Default::default()
+        };
+        messages.rebuild_actions_state();
+
+        let tally = messages.poll_tally(&poll_id, &poll);
+        assert_eq!(tally.counts(), vec![2, 0, 0]);
+        assert_eq!(tally.total_voters, 2);
+        let mut expected = vec![owner_id, voter_id];
+        expected.sort();
+        assert_eq!(tally.voters[0], expected);
+
+        // A retraction removes the owner's vote.
+        messages.messages.push(vote(&owner_sk, 5, vec![]));
+        messages.rebuild_actions_state();
+        let tally = messages.poll_tally(&poll_id, &poll);
+        assert_eq!(tally.counts(), vec![1, 0, 0]);
+        assert_eq!(tally.total_voters, 1);
+    }
+
+    #[test]
+    fn test_thread_index_groups_nested_replies_under_their_root() {
+        let owner_sk = SigningKey::generate(&mut OsRng);
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let base = SystemTime::now();
+        let signed = |secs: u64, content: RoomMessageBody| {
+            AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: owner_id,
+                    author: owner_id,
+                    time: base + Duration::from_secs(secs),
+                    content,
+                },
+                &owner_sk,
+            )
+        };
+        let reply = |secs: u64, target: &AuthorizedMessageV1| {
+            signed(
+                secs,
+                RoomMessageBody::reply(
+                    format!("reply {}", secs),
+                    target.id(),
+                    "owner".to_string(),
+                    "preview".to_string(),
+                ),
+            )
+        };
+
+        let root = signed(0, RoomMessageBody::public("root".to_string()));
+        let other = signed(1, RoomMessageBody::public("unrelated".to_string()));
+        let first = reply(2, &root);
+        let nested = reply(3, &first);
+        let second = reply(4, &root);
+        let deleted = signed(5, RoomMessageBody::public("gone".to_string()));
+        let orphan = reply(6, &deleted);
+        let delete = signed(7, RoomMessageBody::delete(deleted.id()));
+
+        let mut messages = MessagesV1 {
+            messages: vec![
+                root.clone(),
+                other.clone(),
+                first.clone(),
+                nested.clone(),
+                second.clone(),
+                deleted,
+                orphan.clone(),
+                delete,
+            ],
+            ..Default::default()
+        };
+        messages.rebuild_actions_state();
+        let index = messages.thread_index();
+
+        assert_eq!(
+            index.descendants(&root.id()),
+            vec![first.id(), nested.id(), second.id()]
+        );
+        assert_eq!(index.children(&root.id()), &[first.id(), second.id()]);
+        assert_eq!(index.reply_count(&root.id()), 3);
+        assert_eq!(index.root_of(&nested.id()), root.id());
+        assert_eq!(index.parent(&nested.id()), Some(&first.id()));
+        assert_eq!(index.reply_count(&other.id()), 0);
+        // The reply to a deleted message starts its own thread.
+        assert!(index.is_root(&orphan.id()));
+        assert_eq!(index.root_of(&orphan.id()), orphan.id());
+    }
+
+    #[test]
+    fn test_poll_votes_after_close_are_ignored() {
+        use crate::room_state::content::PollContentV1;
+
+        let sk = SigningKey::generate(&mut OsRng);
+        let id = MemberId::from(&sk.verifying_key());
+        let base = SystemTime::now();
+        let poll = PollContentV1::new(
+            "Closed soon".to_string(),
+            vec!["a".to_string(), "b".to_string()],
+            true,
+            Some(base + Duration::from_secs(10)),
+        );
+        let auth_poll = AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: id,
+                author: id,
+                time: base,
+                content: RoomMessageBody::poll(poll.clone()),
+            },
+            &sk,
+        );
+        let poll_id = auth_poll.id();
+        let vote = |secs: u64, choices: Vec<u32>| {
+            AuthorizedMessageV1::new(
+                MessageV1 {
+                    room_owner: id,
+                    author: id,
+                    time: base + Duration::from_secs(secs),
+                    content: RoomMessageBody::poll_vote(poll_id.clone(), choices),
+                },
+                &sk,
+            )
+        };
+
+        let mut messages = MessagesV1 {
+            messages: vec![auth_poll, vote(5, vec![0, 1]), vote(20, vec![])],
+            ..Default::default()
+        };
+        messages.rebuild_actions_state();
+
+        // The late retraction must not erase the in-time ballot.
+        let tally = messages.poll_tally(&poll_id, &poll);
+        assert_eq!(tally.counts(), vec![1, 1]);
+        assert_eq!(tally.total_voters, 1);
+    }
+
+    #[test]
+    fn test_poll_vote_on_non_poll_message_ignored() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let id = MemberId::from(&sk.verifying_key());
+        let text = AuthorizedMessageV1::new(create_test_message(id, id), &sk);
+        let text_id = text.id();
+        let vote = AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: id,
+                author: id,
+                time: SystemTime::now() + Duration::from_secs(1),
+                content: RoomMessageBody::poll_vote(text_id.clone(), vec![0]),
+            },
+            &sk,
+        );
+
+        let mut messages = MessagesV1 {
+            messages: vec![text, vote],
+            ..Default::default()
+        };
+        messages.rebuild_actions_state();
+        assert!(messages.actions_state.poll_votes.is_empty());
+    }
+
+    /// Parent state with `max_recent_messages = 3`, the owner, and one plain
+    /// member (`author_sk`), for the pin tests.
+    fn pin_test_parent(owner_sk: &SigningKey, author_sk: &SigningKey) -> ChatRoomStateV1 {
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let mut parent_state = ChatRoomStateV1::default();
+        parent_state.configuration.configuration.max_recent_messages = 3;
+        parent_state.members.members = vec![crate::room_state::member::AuthorizedMember::new(
+            crate::room_state::member::Member {
+                owner_member_id: owner_id,
+                invited_by: owner_id,
+                member_vk: author_sk.verifying_key(),
+            },
+            owner_sk,
+        )];
+        parent_state
+    }
+
+    fn pin_test_message(
+        sk: &SigningKey,
+        owner_id: MemberId,
+        time: SystemTime,
+        content: RoomMessageBody,
+    ) -> AuthorizedMessageV1 {
+        AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: owner_id,
+                author: MemberId::from(&sk.verifying_key()),
+                time,
+                content,
+            },
+            sk,
+        )
+    }
+
+    #[test]
+    fn pinned_message_survives_retention_until_unpinned() {
+        let owner_sk = SigningKey::generate(&mut OsRng);
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let author_sk = SigningKey::generate(&mut OsRng);
+        let parent_state = pin_test_parent(&owner_sk, &author_sk);
+        let parameters = ChatRoomParametersV1 {
+            owner: owner_sk.verifying_key(),
+        };
+        let base = SystemTime::now();
+        let at = |secs: u64| base + Duration::from_secs(secs);
+
+        let rules = pin_test_message(
+            &author_sk,
+            owner_id,
+            at(0),
+            RoomMessageBody::public("Room rules".to_string()),
+        );
+        let pin = pin_test_message(&owner_sk, owner_id, at(1), RoomMessageBody::pin(rules.id()));
+        let chatter: Vec<_> = (2..6)
+            .map(|i| {
+                pin_test_message(
+                    &author_sk,
+                    owner_id,
+                    at(i),
+                    RoomMessageBody::public(format!("chatter {i}")),
+                )
+            })
+            .collect();
+
+        let mut messages = MessagesV1::default();
+        let mut delta = vec![rules.clone(), pin.clone()];
+        delta.extend(chatter.iter().cloned());
+        messages
+            .apply_delta(&parent_state, &parameters, &Some(delta))
+            .unwrap();
+
+        assert!(messages.messages.contains(&rules), "pinned message kept");
+        assert!(messages.messages.contains(&pin), "pin action kept");
+        assert!(messages.is_pinned(&rules.id()));
+        assert_eq!(
+            messages.messages.len(),
+            5,
+            "3 recent messages plus the exempt pair"
+        );
+        assert_eq!(
+            messages.retention_horizon(3),
+            RetentionHorizon::OldestRetained(chatter[1].order_key()),
+            "the horizon ignores exempt messages"
+        );
+
+        // A peer that only has the recent window still gets the pinned pair,
+        // even though it sorts below that peer's horizon.
+        let mut receiver = MessagesV1::default();
+        receiver
+            .apply_delta(&parent_state, &parameters, &Some(chatter[1..].to_vec()))
+            .unwrap();
+        let summary = receiver.summarize(&parent_state, &parameters);
+        let offered = messages
+            .delta(&parent_state, &parameters, &summary)
+            .unwrap();
+        assert_eq!(offered.len(), 2);
+        assert!(offered.contains(&rules) && offered.contains(&pin));
+        receiver
+            .apply_delta(&parent_state, &parameters, &Some(offered))
+            .unwrap();
+        assert_eq!(receiver, messages, "both peers converge");
+
+        // Unpinning makes both ordinary again, and they age out.
+        let unpin = pin_test_message(
+            &owner_sk,
+            owner_id,
+            at(6),
+            RoomMessageBody::unpin(rules.id()),
+        );
+        messages
+            .apply_delta(&parent_state, &parameters, &Some(vec![unpin]))
+            .unwrap();
+        assert!(!messages.is_pinned(&rules.id()));
+        assert!(!messages.messages.contains(&rules));
+        assert!(!messages.messages.contains(&pin));
+        assert_eq!(messages.messages.len(), 3);
+    }
+
+    #[test]
+    fn pin_requires_owner_or_owner_deputy() {
+        use crate::room_state::member_info::{AuthorizedMemberInfo, MemberInfo};
+
+        let owner_sk = SigningKey::generate(&mut OsRng);
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let author_sk = SigningKey::generate(&mut OsRng);
+        let author_id = MemberId::from(&author_sk.verifying_key());
+        let mut parent_state = pin_test_parent(&owner_sk, &author_sk);
+        let parameters = ChatRoomParametersV1 {
+            owner: owner_sk.verifying_key(),
+        };
+        let now = SystemTime::now();
+
+        let target = pin_test_message(
+            &author_sk,
+            owner_id,
+            now,
+            RoomMessageBody::public("Read me".to_string()),
+        );
+        let pin = pin_test_message(
+            &author_sk,
+            owner_id,
+            now + Duration::from_secs(1),
+            RoomMessageBody::pin(target.id()),
+        );
+        let delta = Some(vec![target.clone(), pin.clone()]);
+
+        let mut messages = MessagesV1::default();
+        messages
+            .apply_delta(&parent_state, &parameters, &delta)
+            .unwrap();
+        assert!(
+            !messages.messages.contains(&pin),
+            "a plain member's pin is dropped"
+        );
+        assert!(!messages.is_pinned(&target.id()));
+
+        let mut owner_info = MemberInfo::new_public(owner_id, 1, "owner".to_string());
+        owner_info.deputies = vec![author_id];
+        parent_state.member_info.member_info = vec![AuthorizedMemberInfo::new_with_member_key(
+            owner_info, &owner_sk,
+        )];
+
+        let mut messages = MessagesV1::default();
+        messages
+            .apply_delta(&parent_state, &parameters, &delta)
+            .unwrap();
+        assert!(
+            messages.is_pinned(&target.id()),
+            "an owner-appointed deputy may pin"
+        );
+    }
+
+    #[test]
+    fn pins_are_capped_at_max_pinned_messages() {
+        let owner_sk = SigningKey::generate(&mut OsRng);
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let base = SystemTime::now();
+
+        let mut messages = MessagesV1::default();
+        let mut targets = Vec::new();
+        for i in 0..(MAX_PINNED_MESSAGES as u64 + 2) {
+            let target = pin_test_message(
+                &owner_sk,
+                owner_id,
+                base + Duration::from_secs(2 * i),
+                RoomMessageBody::public(format!("notice {i}")),
+            );
+            let pin = pin_test_message(
+                &owner_sk,
+                owner_id,
+                base + Duration::from_secs(2 * i + 1),
+                RoomMessageBody::pin(target.id()),
+            );
+            targets.push(target.id());
+            messages.messages.push(target);
+            messages.messages.push(pin);
+        }
+        messages.rebuild_actions_state();
+
+        let pinned: Vec<MessageId> = messages.pins().into_iter().map(|p| p.target).collect();
+        assert_eq!(
+            pinned,
+            targets[2..],
+            "the oldest pins fall off, in pin order"
+        );
+        assert!(!messages.is_pinned(&targets[0]));
+    }
+
     #[test]
     fn test_display_messages_filters_actions() {
         let signing_key = SigningKey::generate(&mut OsRng);
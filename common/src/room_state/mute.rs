@@ -0,0 +1,548 @@
+use crate::room_state::member::{AuthorizedMember, MemberId, MembersV1};
+use crate::room_state::member_info::MemberInfoV1;
+use crate::room_state::message::{AuthorizedMessageV1, MessagesV1};
+use crate::room_state::signed_record::{self, Overflow, SignedRecord};
+use crate::room_state::ChatRoomParametersV1;
+use crate::util::{sign_struct, verify_struct};
+use crate::ChatRoomStateV1;
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use freenet_scaffold::util::{fast_hash, FastHash};
+use freenet_scaffold::ComposableState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, SystemTime};
+
+/// Signed mute and unmute records.
+///
+/// A mute is the lighter alternative to a ban: the member stays in the room
+/// and keeps their history, but messages they send while muted are not
+/// accepted into `recent_messages`. Only messages timestamped inside the mute's
+/// window ([`MuteWindow`]) are dropped, and event messages (joins, leaves) never
+/// are.
+///
+/// Records form a last-writer-wins register per muted member. A record is
+/// effective while its signature verifies against the issuer's CURRENT key and
+/// the issuer may moderate the member ([`MembersV1::is_ban_authorized`]: the
+/// owner, anyone above the member in the invite chain, or their deputies), so
+/// anyone who may mute a member may also unmute them.
+/// `ChatRoomStateV1::post_apply_cleanup` keeps only the newest effective record
+/// per member. An unmute, and a temporary mute that has run out, stay behind as
+/// the tombstone that stops peers re-offering an older mute.
+///
+/// A temporary mute silences only messages dated before its end, so its
+/// records never need dropping for it to stop. Message times are chosen by
+/// their author, so a muted member can date a message past the end, just as
+/// they can date one before the mute; either way it is out of order with the
+/// room. Whether the mute still counts for display and for keeping the member
+/// from inactivity-prune is judged like ban expiry
+/// ([`crate::room_state::ban::BansV1::ban_has_expired`]), by the room's message
+/// clock: it has lapsed once someone who may lift it posts a message dated at
+/// or after its end.
+///
+/// Stored records are capped at `max_members`, oldest evicted first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct MutesV1(pub Vec<AuthorizedMute>);
+
+impl MutesV1 {
+    /// Whether `record`'s signature verifies against the issuer's CURRENT key
+    /// and the issuer may moderate the muted member.
+    pub fn record_is_effective(
+        record: &AuthorizedMute,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+    ) -> bool {
+        signed_record::signed_by_current_key(record, members_by_id, owner_id, owner_vk)
+            && MembersV1::is_ban_authorized(
+                record.muted_by,
+                record.mute.muted_user,
+                members_by_id,
+                member_info,
+                owner_id,
+            )
+    }
+
+    /// Whether a temporary mute has lapsed: `messages` holds a message dated
+    /// at or after [`Mute::expires_at`] from someone who may moderate the
+    /// muted member. Always false for an unmute or a mute without a duration.
+    pub fn mute_has_expired(
+        record: &AuthorizedMute,
+        messages: &MessagesV1,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+    ) -> bool {
+        let Some(expires_at) = record.mute.expires_at() else {
+            return false;
+        };
+        messages.messages.iter().any(|message| {
+            message.message.time >= expires_at
+                && MembersV1::is_ban_authorized(
+                    message.message.author,
+                    record.mute.muted_user,
+                    members_by_id,
+                    member_info,
+                    owner_id,
+                )
+        })
+    }
+
+    /// The records `post_apply_cleanup` keeps: for each muted member the
+    /// newest effective record, lapsed or not, capped at `max_members` with
+    /// the oldest evicted first. Returned in stored `(issued_at, id)` order.
+    pub fn settled(
+        &self,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+        max_members: usize,
+    ) -> Vec<AuthorizedMute> {
+        signed_record::settle(
+            &self.0,
+            |record| {
+                Self::record_is_effective(record, members_by_id, member_info, owner_id, owner_vk)
+            },
+            |_| true,
+            max_members,
+            Overflow::EvictOldest,
+        )
+    }
+
+    /// The window of every member whose newest effective record is a mute.
+    pub fn mute_windows(
+        &self,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+        max_members: usize,
+    ) -> HashMap<MemberId, MuteWindow> {
+        Self::windows(&self.settled(members_by_id, member_info, owner_id, owner_vk, max_members))
+    }
+
+    /// The window of each mute among already settled `records`.
+    pub fn windows(records: &[AuthorizedMute]) -> HashMap<MemberId, MuteWindow> {
+        records
+            .iter()
+            .filter(|record| record.mute.action.is_mute())
+            .map(|record| {
+                (
+                    record.mute.muted_user,
+                    MuteWindow {
+                        muted_at: record.mute.muted_at,
+                        expires_at: record.mute.expires_at(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Members muted now, by the message clock, each with the time their mute
+    /// began. For display; the contract silences by [`Self::mute_windows`].
+    pub fn muted_members(
+        &self,
+        members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+        member_info: &MemberInfoV1,
+        messages: &MessagesV1,
+        owner_id: MemberId,
+        owner_vk: &VerifyingKey,
+        max_members: usize,
+    ) -> HashMap<MemberId, SystemTime> {
+        self.settled(members_by_id, member_info, owner_id, owner_vk, max_members)
+            .into_iter()
+            .filter(|record| {
+                record.mute.action.is_mute()
+                    && !Self::mute_has_expired(
+                        record,
+                        messages,
+                        members_by_id,
+                        member_info,
+                        owner_id,
+                    )
+            })
+            .map(|record| (record.mute.muted_user, record.mute.muted_at))
+            .collect()
+    }
+
+    /// Whether `message` was sent while its author was muted, per
+    /// [`Self::mute_windows`].
+    pub fn silences(
+        windows: &HashMap<MemberId, MuteWindow>,
+        message: &AuthorizedMessageV1,
+    ) -> bool {
+        !message.message.content.is_event()
+            && windows
+                .get(&message.message.author)
+                .is_some_and(|window| window.covers(message.message.time))
+    }
+
+    /// The newest stored record for `member`, effective or not. For display;
+    /// the contract reads [`Self::muted_members`].
+    pub fn latest_for(&self, member: MemberId) -> Option<&AuthorizedMute> {
+        signed_record::latest_for(&self.0, member)
+    }
+}
+
+impl ComposableState for MutesV1 {
+    type ParentState = ChatRoomStateV1;
+    // BTreeSet for canonical summary bytes; see the note on `BansV1`.
+    type Summary = BTreeSet<MuteId>;
+    type Delta = Vec<AuthorizedMute>;
+    type Parameters = ChatRoomParametersV1;
+
+    fn verify(
+        &self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+    ) -> Result<(), String> {
+        signed_record::verify_signatures(&self.0, parent_state, parameters)?;
+        let max = parent_state.configuration.configuration.max_members;
+        if self.0.len() > max {
+            return Err(format!(
+                "Number of mute records ({}) exceeds the maximum allowed ({})",
+                self.0.len(),
+                max
+            ));
+        }
+        Ok(())
+    }
+
+    fn summarize(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+    ) -> Self::Summary {
+        signed_record::summarize(&self.0)
+    }
+
+    fn delta(
+        &self,
+        _parent_state: &Self::ParentState,
+        _parameters: &Self::Parameters,
+        old_state_summary: &Self::Summary,
+    ) -> Option<Self::Delta> {
+        signed_record::delta(&self.0, old_state_summary)
+    }
+
+    /// Adds new records after checking their signatures. Superseded records,
+    /// and the count cap, are left to `post_apply_cleanup`;
+    /// a delta larger than the cap is refused as a flood.
+    fn apply_delta(
+        &mut self,
+        parent_state: &Self::ParentState,
+        parameters: &Self::Parameters,
+        delta: &Option<Self::Delta>,
+    ) -> Result<(), String> {
+        signed_record::merge(
+            &mut self.0,
+            delta.as_deref(),
+            parent_state.configuration.configuration.max_members,
+            "max_members",
+            parent_state,
+            parameters,
+            |_| Ok(()),
+        )
+    }
+}
+
+/// A mute or unmute with the issuer's signature.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuthorizedMute {
+    pub mute: Mute,
+    pub muted_by: MemberId,
+    pub signature: Signature,
+}
+
+impl AuthorizedMute {
+    pub fn new(mute: Mute, muted_by: MemberId, signing_key: &SigningKey) -> Self {
+        assert_eq!(MemberId::from(signing_key.verifying_key()), muted_by);
+        let signature = sign_struct(&mute, signing_key);
+        Self {
+            mute,
+            muted_by,
+            signature,
+        }
+    }
+
+    /// Create an AuthorizedMute with a pre-computed signature.
+    /// Use this when signing is done externally (e.g., via delegate).
+    pub fn with_signature(mute: Mute, muted_by: MemberId, signature: Signature) -> Self {
+        Self {
+            mute,
+            muted_by,
+            signature,
+        }
+    }
+
+    pub fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String> {
+        verify_struct(&self.mute, &self.signature, verifying_key)
+            .map_err(|e| format!("Invalid mute signature: {}", e))
+    }
+
+    pub fn id(&self) -> MuteId {
+        MuteId(fast_hash(&self.signature.to_bytes()))
+    }
+}
+
+impl SignedRecord for AuthorizedMute {
+    type Id = MuteId;
+    const KIND: &'static str = "Mute";
+
+    fn id(&self) -> MuteId {
+        Self::id(self)
+    }
+
+    fn signer(&self) -> MemberId {
+        self.muted_by
+    }
+
+    fn subject(&self) -> MemberId {
+        self.mute.muted_user
+    }
+
+    fn issued_at(&self) -> SystemTime {
+        self.mute.muted_at
+    }
+
+    fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String> {
+        Self::verify_signature(self, verifying_key)
+    }
+}
+
+/// Mutes or unmutes `muted_user` as of `muted_at`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Mute {
+    pub owner_member_id: MemberId,
+    pub muted_user: MemberId,
+    pub muted_at: SystemTime,
+    pub action: MuteAction,
+}
+
+impl Mute {
+    /// The time a temporary mute lapses; `None` for a mute without a duration
+    /// and for an unmute.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        match self.action {
+            MuteAction::Mute {
+                duration: Some(duration),
+            } => Some(self.muted_at.checked_add(duration).unwrap_or(self.muted_at)),
+            _ => None,
+        }
+    }
+}
+
+/// The messages a mute silences: those dated from `muted_at` up to, but not
+/// including, `expires_at`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MuteWindow {
+    pub muted_at: SystemTime,
+    pub expires_at: Option<SystemTime>,
+}
+
+impl MuteWindow {
+    pub fn covers(&self, time: SystemTime) -> bool {
+        time >= self.muted_at && self.expires_at.is_none_or(|expires_at| time < expires_at)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MuteAction {
+    /// Mute, until unmuted or for `duration`.
+    Mute { duration: Option<Duration> },
+    /// Lift an earlier mute.
+    Unmute,
+}
+
+impl MuteAction {
+    pub fn is_mute(&self) -> bool {
+        matches!(self, MuteAction::Mute { .. })
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Hash, Debug, Ord, PartialOrd)]
+pub struct MuteId(pub FastHash);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_state::test_room::{at, id, Room};
+    use crate::room_state::ChatRoomStateV1Delta;
+
+    /// `issuer`'s record muting or unmuting B as of `secs`.
+    fn record(room: &Room, issuer: &SigningKey, secs: u64, action: MuteAction) -> AuthorizedMute {
+        AuthorizedMute::new(
+            Mute {
+                owner_member_id: room.params.owner_id(),
+                muted_user: id(&room.b_sk),
+                muted_at: at(secs),
+                action,
+            },
+            id(issuer),
+            issuer,
+        )
+    }
+
+    fn set_mute(room: &mut Room, issuer: &SigningKey, secs: u64, action: MuteAction) {
+        let record = record(room, issuer, secs, action);
+        room.apply(ChatRoomStateV1Delta {
+            mutes: Some(vec![record]),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    const MUTE: MuteAction = MuteAction::Mute { duration: None };
+
+    #[test]
+    fn muted_member_stays_with_history_but_cannot_post_until_unmuted() {
+        let mut room = Room::new();
+        let (a_sk, b_sk) = (room.a_sk.clone(), room.b_sk.clone());
+        let history = room.message(&b_sk, 0);
+
+        set_mute(&mut room, &a_sk, 10, MUTE);
+        let while_muted = room.message(&b_sk, 20);
+        room.post(while_muted.clone());
+        assert!(
+            !room.has(&while_muted),
+            "a muted member's new message is refused"
+        );
+        assert!(room.has(&history), "history stays");
+        room.assert_settled();
+
+        set_mute(&mut room, &a_sk, 30, MuteAction::Unmute);
+        let after = room.message(&b_sk, 40);
+        room.post(after.clone());
+        assert!(room.has(&after));
+        assert_eq!(room.state.mutes.0.len(), 1, "only the unmute remains");
+
+        // A peer that missed the unmute re-offers the mute; the unmute holds.
+        set_mute(&mut room, &a_sk, 10, MUTE);
+        assert!(room.has(&after));
+        assert_eq!(room.state.mutes.0[0].mute.action, MuteAction::Unmute);
+        room.assert_settled();
+    }
+
+    #[test]
+    fn muted_member_is_not_pruned_for_inactivity() {
+        let mut room = Room::new();
+        let (a_sk, b_sk) = (room.a_sk.clone(), room.b_sk.clone());
+        set_mute(&mut room, &a_sk, 10, MUTE);
+
+        // B's last message ages out; leaving must not shed the mute.
+        room.state
+            .recent_messages
+            .messages
+            .retain(|m| m.message.author != id(&b_sk));
+        room.state.post_apply_cleanup(&room.params).unwrap();
+        assert!(room.is_member(&b_sk));
+        room.assert_settled();
+
+        // Once unmuted, B is an ordinary inactive member again.
+        set_mute(&mut room, &a_sk, 30, MuteAction::Unmute);
+        assert!(!room.is_member(&b_sk));
+        assert!(room.state.mutes.0.is_empty());
+        room.assert_settled();
+    }
+
+    #[test]
+    fn mute_arriving_after_the_messages_removes_them() {
+        let mut room = Room::new();
+        let (owner_sk, b_sk) = (room.owner_sk.clone(), room.b_sk.clone());
+        let before = room.message(&b_sk, 5);
+        let after = room.message(&b_sk, 15);
+        room.post(before.clone());
+        room.post(after.clone());
+
+        set_mute(&mut room, &owner_sk, 10, MUTE);
+        assert!(room.has(&before));
+        assert!(!room.has(&after));
+        room.assert_settled();
+    }
+
+    #[test]
+    fn unrelated_member_cannot_mute() {
+        let mut room = Room::new();
+        let (b_sk, c_sk) = (room.b_sk.clone(), room.c_sk.clone());
+
+        set_mute(&mut room, &c_sk, 10, MUTE);
+        let message = room.message(&b_sk, 20);
+        room.post(message.clone());
+        assert!(room.has(&message), "C is neither above B nor a deputy");
+        assert!(room.state.mutes.0.is_empty());
+        room.assert_settled();
+    }
+
+    #[test]
+    fn temporary_mute_silences_only_its_window_and_stays_as_a_tombstone() {
+        let mut room = Room::new();
+        let (a_sk, b_sk, c_sk) = (room.a_sk.clone(), room.b_sk.clone(), room.c_sk.clone());
+        let is_muted = |room: &Room| {
+            let state = &room.state;
+            state
+                .mutes
+                .muted_members(
+                    &state.members.members_by_member_id(),
+                    &state.member_info,
+                    &state.recent_messages,
+                    room.params.owner_id(),
+                    &room.params.owner,
+                    state.configuration.configuration.max_members,
+                )
+                .contains_key(&id(&room.b_sk))
+        };
+        let temporary = MuteAction::Mute {
+            duration: Some(Duration::from_secs(60)),
+        };
+        set_mute(&mut room, &a_sk, 10, temporary);
+
+        let inside = room.message(&b_sk, 50);
+        let after = room.message(&b_sk, 80);
+        room.post(inside.clone());
+        room.post(after.clone());
+        assert!(!room.has(&inside), "silenced inside [10, 70)");
+        assert!(room.has(&after), "accepted once the window has ended");
+
+        // C's message, however late, does not count as the mute's end.
+        room.post(room.message(&c_sk, 1_000));
+        assert!(is_muted(&room));
+        room.post(room.message(&a_sk, 70));
+        assert!(!is_muted(&room), "A's later message ends the mute");
+        assert_eq!(room.state.mutes.0.len(), 1, "the lapsed mute stays");
+        room.assert_settled();
+
+        // Once the moderator's messages are trimmed, a peer re-offering an
+        // older open-ended mute is still superseded by the lapsed one.
+        room.state
+            .recent_messages
+            .messages
+            .retain(|m| m.message.author != id(&a_sk));
+        room.state.post_apply_cleanup(&room.params).unwrap();
+        set_mute(&mut room, &a_sk, 5, MUTE);
+        assert_eq!(room.state.mutes.0.len(), 1);
+        assert_eq!(room.state.mutes.0[0].mute.action, temporary);
+        assert!(room.has(&after));
+        let later = room.message(&b_sk, 90);
+        room.post(later.clone());
+        assert!(room.has(&later));
+        room.assert_settled();
+    }
+
+    #[test]
+    fn forged_mute_is_rejected() {
+        let mut room = Room::new();
+        let (a_sk, c_sk) = (room.a_sk.clone(), room.c_sk.clone());
+
+        // Claims to be from A but is signed by C.
+        let mute = record(&room, &c_sk, 10, MUTE).mute;
+        let signature = sign_struct(&mute, &c_sk);
+        let forged = AuthorizedMute::with_signature(mute, id(&a_sk), signature);
+        let result = room.apply(ChatRoomStateV1Delta {
+            mutes: Some(vec![forged]),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+        assert!(room.state.mutes.0.is_empty());
+    }
+}
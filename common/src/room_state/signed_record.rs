@@ -0,0 +1,205 @@
+use crate::room_state::member::{AuthorizedMember, MemberId};
+use crate::room_state::ChatRoomParametersV1;
+use crate::ChatRoomStateV1;
+use ed25519_dalek::VerifyingKey;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use std::time::SystemTime;
+
+/// A moderation record one member signs about another: a ban revocation, a
+/// mute, an invite policy or a join decision.
+///
+/// These share their plumbing: signatures are checked against the signer's
+/// CURRENT key, the summary is the set of record ids, a delta is the records
+/// the other side lacks, and most of them form a last-writer-wins register per
+/// [`Self::subject`], ordered by [`Self::order_key`]. The helpers below hold
+/// that logic once; each collection supplies what makes one of its records
+/// effective.
+pub trait SignedRecord: Clone {
+    type Id: Ord + Hash + Clone;
+
+    /// Name used in error messages, e.g. "Mute".
+    const KIND: &'static str;
+
+    /// Hash of the signature, unique per record.
+    fn id(&self) -> Self::Id;
+
+    /// Who signed the record.
+    fn signer(&self) -> MemberId;
+
+    /// The member the record is about, which keys its register.
+    fn subject(&self) -> MemberId;
+
+    /// The signer-chosen time the record takes effect.
+    fn issued_at(&self) -> SystemTime;
+
+    fn verify_signature(&self, verifying_key: &VerifyingKey) -> Result<(), String>;
+
+    /// Stored and last-writer-wins order.
+    fn order_key(&self) -> (SystemTime, Self::Id) {
+        (self.issued_at(), self.id())
+    }
+}
+
+/// What a cap does with the records past it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Keep the newest; an older record makes way for a newer one.
+    EvictOldest,
+    /// Keep the oldest; a newer record is refused while the cap is full.
+    RefuseNewest,
+}
+
+/// The key `signer` signs with: the owner's, or a current member's.
+pub fn signer_key(
+    signer: MemberId,
+    members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+    owner_id: MemberId,
+    owner_vk: &VerifyingKey,
+) -> Option<VerifyingKey> {
+    if signer == owner_id {
+        Some(*owner_vk)
+    } else {
+        members_by_id
+            .get(&signer)
+            .map(|member| member.member.member_vk)
+    }
+}
+
+/// Whether `record`'s signature verifies against its signer's CURRENT key.
+pub fn signed_by_current_key<R: SignedRecord>(
+    record: &R,
+    members_by_id: &HashMap<MemberId, &AuthorizedMember>,
+    owner_id: MemberId,
+    owner_vk: &VerifyingKey,
+) -> bool {
+    signer_key(record.signer(), members_by_id, owner_id, owner_vk)
+        .is_some_and(|key| record.verify_signature(&key).is_ok())
+}
+
+/// Signature checks for every record whose signer is the owner or a current
+/// member. A non-member signer is skipped, as for bans: they may have been
+/// pruned, or be re-added later in the same delta, and cleanup drops the
+/// record if the key never matches.
+pub fn verify_signatures<R: SignedRecord>(
+    records: &[R],
+    parent_state: &ChatRoomStateV1,
+    parameters: &ChatRoomParametersV1,
+) -> Result<(), String> {
+    let members_by_id = parent_state.members.members_by_member_id();
+    let owner_id = parameters.owner_id();
+    for record in records {
+        if let Some(key) = signer_key(record.signer(), &members_by_id, owner_id, &parameters.owner)
+        {
+            record.verify_signature(&key)?;
+        }
+    }
+    Ok(())
+}
+
+/// Caps `records`, which are in stored order, at `max`.
+pub fn cap<R: SignedRecord>(records: &mut Vec<R>, max: usize, overflow: Overflow) {
+    if records.len() > max {
+        match overflow {
+            Overflow::EvictOldest => {
+                records.drain(0..records.len() - max);
+            }
+            Overflow::RefuseNewest => records.truncate(max),
+        }
+    }
+}
+
+/// Settles a last-writer-wins register: for each subject the newest record
+/// `is_effective` accepts, then those `keep` accepts, capped at `max`.
+/// Returned in stored order.
+pub fn settle<R: SignedRecord>(
+    records: &[R],
+    is_effective: impl Fn(&R) -> bool,
+    keep: impl Fn(&R) -> bool,
+    max: usize,
+    overflow: Overflow,
+) -> Vec<R> {
+    let mut newest: HashMap<MemberId, &R> = HashMap::new();
+    for record in records.iter().filter(|record| is_effective(record)) {
+        let entry = newest.entry(record.subject()).or_insert(record);
+        if record.order_key() > entry.order_key() {
+            *entry = record;
+        }
+    }
+    let mut kept: Vec<R> = newest
+        .into_values()
+        .filter(|record| keep(record))
+        .cloned()
+        .collect();
+    kept.sort_by_key(|record| record.order_key());
+    cap(&mut kept, max, overflow);
+    kept
+}
+
+/// The newest stored record about `subject`, effective or not.
+pub fn latest_for<R: SignedRecord>(records: &[R], subject: MemberId) -> Option<&R> {
+    records
+        .iter()
+        .filter(|record| record.subject() == subject)
+        .max_by_key(|record| record.order_key())
+}
+
+pub fn summarize<R: SignedRecord>(records: &[R]) -> BTreeSet<R::Id> {
+    records.iter().map(|record| record.id()).collect()
+}
+
+/// The records missing from `old_state_summary`, or `None` if there are none.
+pub fn delta<R: SignedRecord>(
+    records: &[R],
+    old_state_summary: &BTreeSet<R::Id>,
+) -> Option<Vec<R>> {
+    let delta: Vec<R> = records
+        .iter()
+        .filter(|record| !old_state_summary.contains(&record.id()))
+        .cloned()
+        .collect();
+    if delta.is_empty() {
+        None
+    } else {
+        Some(delta)
+    }
+}
+
+/// Adds the records of `delta`, if any, that `records` lacks, after checking their
+/// signatures and `check`, and restores stored order. A delta larger than
+/// `max` (the cap named `cap_name`) is refused as a flood; superseded records
+/// and the cap itself are left to `post_apply_cleanup`.
+pub fn merge<R: SignedRecord>(
+    records: &mut Vec<R>,
+    delta: Option<&[R]>,
+    max: usize,
+    cap_name: &str,
+    parent_state: &ChatRoomStateV1,
+    parameters: &ChatRoomParametersV1,
+    check: impl Fn(&R) -> Result<(), String>,
+) -> Result<(), String> {
+    let delta = delta.unwrap_or_default();
+    if delta.len() > max {
+        return Err(format!(
+            "{} delta of {} exceeds {} ({}); refusing to process a flood",
+            R::KIND,
+            delta.len(),
+            cap_name,
+            max
+        ));
+    }
+    let mut known = summarize(records);
+    let new: Vec<R> = delta
+        .iter()
+        .filter(|record| known.insert(record.id()))
+        .cloned()
+        .collect();
+    verify_signatures(&new, parent_state, parameters)
+        .map_err(|e| format!("Invalid delta: {}", e))?;
+    for record in &new {
+        check(record).map_err(|e| format!("Invalid delta: {}", e))?;
+    }
+    records.extend(new);
+    records.sort_by_key(|record| record.order_key());
+    Ok(())
+}
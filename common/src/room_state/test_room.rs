@@ -0,0 +1,141 @@
+//! The room the moderation-record tests (ban revocations, mutes, invites,
+//! join requests) run against.
+
+use crate::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+use crate::room_state::member::{AuthorizedMember, Member, MemberId, MembersV1};
+use crate::room_state::message::{AuthorizedMessageV1, MessageV1, MessagesV1, RoomMessageBody};
+use crate::room_state::{ChatRoomParametersV1, ChatRoomStateV1Delta};
+use crate::ChatRoomStateV1;
+use ed25519_dalek::SigningKey;
+use freenet_scaffold::ComposableState;
+use std::time::{Duration, SystemTime};
+
+/// Message times are offsets from here, so tests can name them in seconds.
+pub const BASE: u64 = 1_000_000;
+
+pub fn at(secs: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(BASE + secs)
+}
+
+pub fn id(sk: &SigningKey) -> MemberId {
+    sk.verifying_key().into()
+}
+
+/// Owner O invites A and C; A invites B. A, B and C have each posted at
+/// `BASE`. X holds no invitation.
+pub struct Room {
+    pub state: ChatRoomStateV1,
+    pub params: ChatRoomParametersV1,
+    pub owner_sk: SigningKey,
+    pub a_sk: SigningKey,
+    pub b_sk: SigningKey,
+    pub c_sk: SigningKey,
+    pub x_sk: SigningKey,
+}
+
+impl Room {
+    pub fn new() -> Self {
+        let rng = &mut rand::thread_rng();
+        let owner_sk = SigningKey::generate(rng);
+        let params = ChatRoomParametersV1 {
+            owner: owner_sk.verifying_key(),
+        };
+        let [a_sk, b_sk, c_sk, x_sk] = [0; 4].map(|_| SigningKey::generate(rng));
+        let mut room = Room {
+            state: ChatRoomStateV1 {
+                configuration: AuthorizedConfigurationV1::new(Configuration::default(), &owner_sk),
+                ..Default::default()
+            },
+            params,
+            owner_sk,
+            a_sk,
+            b_sk,
+            c_sk,
+            x_sk,
+        };
+        room.state.members = MembersV1 {
+            members: vec![
+                room.member(&room.a_sk, &room.owner_sk),
+                room.member(&room.b_sk, &room.a_sk),
+                room.member(&room.c_sk, &room.owner_sk),
+            ],
+        };
+        room.state.recent_messages = MessagesV1 {
+            messages: vec![
+                room.message(&room.a_sk, 0),
+                room.message(&room.b_sk, 0),
+                room.message(&room.c_sk, 0),
+            ],
+            ..Default::default()
+        };
+        room
+    }
+
+    /// The member entry `inviter` signs for `sk`.
+    pub fn member(&self, sk: &SigningKey, inviter: &SigningKey) -> AuthorizedMember {
+        AuthorizedMember::new(
+            Member {
+                owner_member_id: self.params.owner_id(),
+                invited_by: id(inviter),
+                member_vk: sk.verifying_key(),
+            },
+            inviter,
+        )
+    }
+
+    /// A public text message from `author` dated `secs`.
+    pub fn message(&self, author: &SigningKey, secs: u64) -> AuthorizedMessageV1 {
+        self.message_with(author, secs, RoomMessageBody::public(format!("at {secs}")))
+    }
+
+    pub fn message_with(
+        &self,
+        author: &SigningKey,
+        secs: u64,
+        content: RoomMessageBody,
+    ) -> AuthorizedMessageV1 {
+        AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: self.params.owner_id(),
+                author: id(author),
+                time: at(secs),
+                content,
+            },
+            author,
+        )
+    }
+
+    /// Applies `delta` as a peer would, cleanup included.
+    pub fn apply(&mut self, delta: ChatRoomStateV1Delta) -> Result<(), String> {
+        let parent = self.state.clone();
+        self.state.apply_delta(&parent, &self.params, &Some(delta))
+    }
+
+    pub fn post(&mut self, message: AuthorizedMessageV1) {
+        self.apply(ChatRoomStateV1Delta {
+            recent_messages: Some(vec![message]),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    pub fn has(&self, message: &AuthorizedMessageV1) -> bool {
+        self.state.recent_messages.messages.contains(message)
+    }
+
+    pub fn is_member(&self, sk: &SigningKey) -> bool {
+        self.state
+            .members
+            .members
+            .iter()
+            .any(|m| m.member.id() == id(sk))
+    }
+
+    /// The state verifies and a further cleanup pass changes nothing.
+    pub fn assert_settled(&mut self) {
+        assert!(self.state.verify(&self.state, &self.params).is_ok());
+        let once = self.state.clone();
+        self.state.post_apply_cleanup(&self.params).unwrap();
+        assert_eq!(self.state, once, "cleanup must be idempotent");
+    }
+}
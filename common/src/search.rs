@@ -0,0 +1,339 @@
+//! Full-text search over a room's message history.
+//!
+//! Searches run over [`MessagesV1::display_messages`], so deleted messages,
+//! actions and room events are never hits, and match against
+//! [`MessagesV1::effective_text`], so an edited message is found by its
+//! current text only. Private bodies are opaque to `river-core` without the
+//! room secrets: [`MessagesV1::search`] skips them, and clients holding the
+//! secrets use [`MessagesV1::search_with_secrets`] (or supply their own
+//! decryption to [`MessagesV1::search_with`]).
+
+use crate::room_state::content::DecodedContent;
+use crate::room_state::member::MemberId;
+use crate::room_state::message::{AuthorizedMessageV1, MessagesV1};
+use std::time::SystemTime;
+
+/// What to look for. Every set field must match; the default query matches
+/// every message.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SearchQuery {
+    /// Whitespace-separated terms that must all appear in the message text,
+    /// compared case-insensitively. Empty matches any text.
+    pub text: String,
+    /// Only messages written by this member.
+    pub author: Option<MemberId>,
+    /// Only messages signed at or after this time.
+    pub since: Option<SystemTime>,
+    /// Only messages signed before this time.
+    pub until: Option<SystemTime>,
+    /// Only messages that `@mention` this member.
+    pub mentions: Option<MemberId>,
+    /// Only messages of this `CONTENT_TYPE_*`.
+    pub content_type: Option<u32>,
+}
+
+impl SearchQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the query has nothing to match on, so every message is a hit.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether `text` contains every search term.
+    pub fn matches_text(&self, text: &str) -> bool {
+        let haystack = text.to_lowercase();
+        self.text
+            .split_whitespace()
+            .all(|term| haystack.contains(&term.to_lowercase()))
+    }
+
+    /// Whether `message`, whose searchable text is `text`, is a hit.
+    fn matches(&self, message: &AuthorizedMessageV1, text: &str) -> bool {
+        let msg = &message.message;
+        self.author.is_none_or(|author| msg.author == author)
+            && self.since.is_none_or(|since| msg.time >= since)
+            && self.until.is_none_or(|until| msg.time < until)
+            && self
+                .content_type
+                .is_none_or(|content_type| msg.content.content_type() == content_type)
+            && self
+                .mentions
+                .is_none_or(|id| crate::mention::contains_mention_of(text, id))
+            && self.matches_text(text)
+    }
+}
+
+/// A message that matched a [`SearchQuery`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct SearchHit<'a> {
+    pub message: &'a AuthorizedMessageV1,
+    /// The text the query was matched against.
+    pub text: String,
+}
+
+/// Searchable text of decoded content: the message text, a poll's question
+/// and options, or an attachment's file name.
+fn decoded_text(content: &DecodedContent) -> Option<String> {
+    match content {
+        DecodedContent::Poll(poll) => Some(
+            std::iter::once(&poll.question)
+                .chain(&poll.options)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        DecodedContent::Blob(blob) => Some(blob.filename.clone()),
+        other => other.as_text().map(str::to_string),
+    }
+}
+
+impl MessagesV1 {
+    /// Public messages matching `query`, oldest first. Private bodies are
+    /// skipped; see [`Self::search_with_secrets`].
+    pub fn search(&self, query: &SearchQuery) -> Vec<SearchHit<'_>> {
+        self.search_with(query, |msg| {
+            msg.message
+                .content
+                .decode_content()
+                .as_ref()
+                .and_then(decoded_text)
+        })
+    }
+
+    /// Messages matching `query`, oldest first, with `text_of` giving the
+    /// original (unedited) text of each message, or `None` to skip it. Edited
+    /// messages are matched against their edited text instead.
+    pub fn search_with(
+        &self,
+        query: &SearchQuery,
+        text_of: impl Fn(&AuthorizedMessageV1) -> Option<String>,
+    ) -> Vec<SearchHit<'_>> {
+        self.display_messages()
+            .filter(|msg| !msg.message.content.is_event())
+            .filter_map(|msg| {
+                let edited = self
+                    .actions_state
+                    .edited_content
+                    .contains_key(&msg.id())
+                    .then(|| self.effective_text(msg))
+                    .flatten();
+                let text = edited.or_else(|| text_of(msg))?;
+                query
+                    .matches(msg, &text)
+                    .then_some(SearchHit { message: msg, text })
+            })
+            .collect()
+    }
+
+    /// Like [`Self::search`], but private bodies are decrypted with the room
+    /// `secrets` (by secret version). For private edits to be honoured the
+    /// actions state must have been rebuilt from the decrypted actions (see
+    /// [`Self::rebuild_actions_state_with_decrypted`]).
+    #[cfg(feature = "ecies")]
+    pub fn search_with_secrets(
+        &self,
+        query: &SearchQuery,
+        secrets: &std::collections::HashMap<u32, [u8; 32]>,
+    ) -> Vec<SearchHit<'_>> {
+        use crate::room_state::message::RoomMessageBody;
+
+        self.search_with(query, |msg| match &msg.message.content {
+            RoomMessageBody::Public { .. } => msg
+                .message
+                .content
+                .decode_content()
+                .as_ref()
+                .and_then(decoded_text),
+            RoomMessageBody::Private {
+                content_type,
+                content_version,
+                ciphertext,
+                nonce,
+                secret_version,
+            } => {
+                let secret = secrets.get(secret_version)?;
+                let plaintext =
+                    crate::ecies::decrypt_with_symmetric_key(secret, ciphertext, nonce).ok()?;
+                // Decode the plaintext exactly as the public body would be.
+                RoomMessageBody::Public {
+                    content_type: *content_type,
+                    content_version: *content_version,
+                    data: plaintext,
+                }
+                .decode_content()
+                .as_ref()
+                .and_then(decoded_text)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_state::content::CONTENT_TYPE_REPLY;
+    use crate::room_state::message::{MessageV1, RoomMessageBody};
+    use ed25519_dalek::SigningKey;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs)
+    }
+
+    fn signed(sk: &SigningKey, secs: u64, content: RoomMessageBody) -> AuthorizedMessageV1 {
+        let owner = MemberId::from(&SigningKey::from_bytes(&[1; 32]).verifying_key());
+        AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: owner,
+                author: MemberId::from(&sk.verifying_key()),
+                time: at(secs),
+                content,
+            },
+            sk,
+        )
+    }
+
+    #[test]
+    fn search_matches_terms_and_filters() {
+        let alice = SigningKey::from_bytes(&[2; 32]);
+        let bob = SigningKey::from_bytes(&[3; 32]);
+        let bob_id = MemberId::from(&bob.verifying_key());
+        let mention = crate::mention::encode_mention(bob_id, "Bob");
+
+        let deploy = signed(
+            &alice,
+            0,
+            RoomMessageBody::public("Deploy the Contract tonight".to_string()),
+        );
+        let ping = signed(
+            &alice,
+            10,
+            RoomMessageBody::public(format!("{} can you review the contract?", mention)),
+        );
+        let reply = signed(
+            &bob,
+            20,
+            RoomMessageBody::reply(
+                "contract looks fine".to_string(),
+                ping.id(),
+                "Alice".to_string(),
+                "review".to_string(),
+            ),
+        );
+        let messages = MessagesV1 {
+            messages: vec![deploy.clone(), ping.clone(), reply.clone()],
+            ..Default::default()
+        };
+        let ids = |query: &SearchQuery| -> Vec<_> {
+            messages
+                .search(query)
+                .iter()
+                .map(|hit| hit.message.id())
+                .collect()
+        };
+
+        assert_eq!(
+            ids(&SearchQuery::new("contract")),
+            vec![deploy.id(), ping.id(), reply.id()]
+        );
+        assert_eq!(
+            ids(&SearchQuery::new("CONTRACT tonight")),
+            vec![deploy.id()]
+        );
+        assert!(ids(&SearchQuery::new("contract yesterday")).is_empty());
+
+        let by_bob = SearchQuery {
+            author: Some(bob_id),
+            ..SearchQuery::new("contract")
+        };
+        assert_eq!(ids(&by_bob), vec![reply.id()]);
+
+        let mentioning_bob = SearchQuery {
+            mentions: Some(bob_id),
+            ..Default::default()
+        };
+        assert_eq!(ids(&mentioning_bob), vec![ping.id()]);
+
+        let window = SearchQuery {
+            since: Some(at(10)),
+            until: Some(at(20)),
+            ..Default::default()
+        };
+        assert_eq!(ids(&window), vec![ping.id()]);
+
+        let replies = SearchQuery {
+            content_type: Some(CONTENT_TYPE_REPLY),
+            ..Default::default()
+        };
+        assert_eq!(ids(&replies), vec![reply.id()]);
+    }
+
+    #[test]
+    fn search_respects_edits_and_deletes() {
+        let alice = SigningKey::from_bytes(&[2; 32]);
+        let edited = signed(&alice, 0, RoomMessageBody::public("teh typo".to_string()));
+        let deleted = signed(&alice, 1, RoomMessageBody::public("the secret".to_string()));
+        let mut messages = MessagesV1 {
+            messages: vec![
+                edited.clone(),
+                deleted.clone(),
+                signed(
+                    &alice,
+                    2,
+                    RoomMessageBody::edit(edited.id(), "the fix".to_string()),
+                ),
+                signed(&alice, 3, RoomMessageBody::delete(deleted.id())),
+            ],
+            ..Default::default()
+        };
+        messages.rebuild_actions_state();
+
+        assert!(messages.search(&SearchQuery::new("teh")).is_empty());
+        assert!(messages.search(&SearchQuery::new("secret")).is_empty());
+        let hits = messages.search(&SearchQuery::new("the"));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message.id(), edited.id());
+        assert_eq!(hits[0].text, "the fix");
+    }
+
+    #[cfg(feature = "ecies-randomized")]
+    #[test]
+    fn search_with_secrets_finds_private_messages() {
+        use crate::room_state::content::{TextContentV1, CONTENT_TYPE_TEXT, TEXT_CONTENT_VERSION};
+
+        let alice = SigningKey::from_bytes(&[2; 32]);
+        let secret = [9u8; 32];
+        let (ciphertext, nonce) = crate::ecies::encrypt_with_symmetric_key(
+            &secret,
+            &TextContentV1::new("sealed launch plan".to_string()).encode(),
+        );
+        let private = signed(
+            &alice,
+            0,
+            RoomMessageBody::private(
+                CONTENT_TYPE_TEXT,
+                TEXT_CONTENT_VERSION,
+                ciphertext,
+                nonce,
+                4,
+            ),
+        );
+        let messages = MessagesV1 {
+            messages: vec![private.clone()],
+            ..Default::default()
+        };
+
+        let query = SearchQuery::new("launch");
+        assert!(messages.search(&query).is_empty());
+        let secrets = std::collections::HashMap::from([(4, secret)]);
+        let hits = messages.search_with_secrets(&query, &secrets);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message.id(), private.id());
+    }
+}
@@ -96,6 +96,8 @@ fn ban(target: MemberId, banner: &Peer, owner_id: MemberId) -> AuthorizedUserBan
             owner_member_id: owner_id,
             banned_at: SystemTime::UNIX_EPOCH,
             banned_user: target,
+            reason: None,
+            duration: None,
         },
         banner.id,
         &banner.sk,
@@ -316,6 +316,8 @@ fn test_ban_excess_order_convergence() {
             owner_member_id: owner_id,
             banned_at: same_time,
             banned_user: member_a.id(),
+            reason: None,
+            duration: None,
         },
         owner_id,
         &owner_signing_key,
@@ -326,6 +328,8 @@ fn test_ban_excess_order_convergence() {
             owner_member_id: owner_id,
             banned_at: same_time,
             banned_user: member_b.id(),
+            reason: None,
+            duration: None,
         },
         owner_id,
         &owner_signing_key,
@@ -336,6 +340,8 @@ fn test_ban_excess_order_convergence() {
             owner_member_id: owner_id,
             banned_at: same_time,
             banned_user: member_c.id(),
+            reason: None,
+            duration: None,
         },
         owner_id,
         &owner_signing_key,
@@ -887,6 +893,8 @@ fn test_ban_convergence_stress_same_timestamps() {
                 owner_member_id: owner_id,
                 banned_at: same_time,
                 banned_user: member.member.id(),
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_signing_key,
@@ -1444,6 +1452,8 @@ fn test_concurrent_adds_and_bans() {
                 owner_member_id: owner_id,
                 banned_at: ban_time,
                 banned_user: members[0].member.id(),
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_signing_key,
@@ -1453,6 +1463,8 @@ fn test_concurrent_adds_and_bans() {
                 owner_member_id: owner_id,
                 banned_at: ban_time,
                 banned_user: members[1].member.id(),
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_signing_key,
@@ -1675,6 +1687,8 @@ fn test_regression_ban_excess_identification() {
                 owner_member_id: owner_id,
                 banned_at: same_time,
                 banned_user: member.member.id(),
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_signing_key,
@@ -2045,6 +2059,8 @@ fn test_regression_combined_scenario() {
                 owner_member_id: owner_id,
                 banned_at: ban_time,                        // Same timestamp
                 banned_user: members[25 + i].0.member.id(), // Ban level-2 members
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_signing_key,
@@ -2209,6 +2225,8 @@ fn test_merge_with_bans_from_member_not_in_other_state() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member_y_id,
+            reason: None,
+            duration: None,
         },
         member_x_id,
         &member_x_sk,
@@ -2320,6 +2338,8 @@ fn test_merge_commutativity_with_owner_bans() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member_a_id,
+            reason: None,
+            duration: None,
         },
         owner_id,
         &owner_sk,
@@ -2331,6 +2351,8 @@ fn test_merge_commutativity_with_owner_bans() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now() + std::time::Duration::from_secs(1),
             banned_user: member_d_id,
+            reason: None,
+            duration: None,
         },
         owner_id,
         &owner_sk,
@@ -2479,6 +2501,8 @@ fn test_merge_cascade_ban_cleanup() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member_b_id,
+            reason: None,
+            duration: None,
         },
         member_a_id,
         &member_a_sk,
@@ -2490,6 +2514,8 @@ fn test_merge_cascade_ban_cleanup() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now() + std::time::Duration::from_secs(1),
             banned_user: member_a_id,
+            reason: None,
+            duration: None,
         },
         owner_id,
         &owner_sk,
@@ -2618,6 +2644,8 @@ fn test_merge_cascade_ban_commutativity() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member_b_id,
+            reason: None,
+            duration: None,
         },
         member_a_id,
         &member_a_sk,
@@ -2629,6 +2657,8 @@ fn test_merge_cascade_ban_commutativity() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now() + std::time::Duration::from_secs(1),
             banned_user: member_a_id,
+            reason: None,
+            duration: None,
         },
         owner_id,
         &owner_sk,
@@ -2747,6 +2777,8 @@ fn test_merge_owner_ban_across_diverged_states() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: member_a_id,
+            reason: None,
+            duration: None,
         },
         owner_id,
         &owner_sk,
@@ -82,6 +82,8 @@ fn ban(target: MemberId, banner: &Peer, owner_id: MemberId) -> AuthorizedUserBan
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: target,
+            reason: None,
+            duration: None,
         },
         banner.id,
         &banner.sk,
@@ -903,6 +905,8 @@ fn ban_at(target: MemberId, banner: &Peer, owner_id: MemberId, secs: u64) -> Aut
             owner_member_id: owner_id,
             banned_at: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs),
             banned_user: target,
+            reason: None,
+            duration: None,
         },
         banner.id,
         &banner.sk,
@@ -1067,6 +1071,8 @@ fn garbage_sig_ban_from_present_member_rejected_by_verify() {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::now(),
                 banned_user: t.id,
+                reason: None,
+                duration: None,
             },
             m.id,
             ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
@@ -1356,6 +1362,7 @@ fn new_verify_accepts_legacy_state_with_nonmember_banner_bans() {
             version: 0,
             preferred_nickname: nick,
             deputies: vec![],
+            dm_prekey: None,
         };
         AuthorizedMemberInfo::with_signature(new_mi, sig)
     };
@@ -1440,6 +1447,8 @@ fn same_delta_replayed_deputy_forged_ban_is_inert_and_swept() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: v.id,
+            reason: None,
+            duration: None,
         },
         d.id,
         ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
@@ -1682,6 +1691,8 @@ fn post_apply_cleanup_is_idempotent_for_garbage_sig_member_banner() {
             owner_member_id: owner_id,
             banned_at: SystemTime::now(),
             banned_user: absent.id,
+            reason: None,
+            duration: None,
         },
         p_member.id,
         ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
@@ -176,6 +176,7 @@ fn json_round_trip_with_populated_purges_does_not_drop_fields() {
         RecipientPurges {
             version: 7,
             purged: vec![tok(0xAA), tok(0xBB)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -203,6 +204,7 @@ fn json_round_trip_of_full_chat_room_state_preserves_dms() {
         RecipientPurges {
             version: 1,
             purged: vec![tok(0xCC)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -233,6 +235,7 @@ fn json_round_trip_of_summary_does_not_drop_fields() {
         RecipientPurges {
             version: 9,
             purged: vec![tok(0xDE)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -443,6 +446,7 @@ fn pruned_sender_can_dm_when_bundling_rejoin_delta() {
             version: 0,
             preferred_nickname: SealedBytes::public(b"Alice".to_vec()),
             deputies: Vec::new(),
+            dm_prekey: None,
         },
         &f.alice_sk,
     );
@@ -594,6 +598,8 @@ fn ban_then_existing_dm_state_still_verifies() {
             owner_member_id: f.owner_id,
             banned_at: SystemTime::now(),
             banned_user: f.alice_id,
+            reason: None,
+            duration: None,
         },
         f.owner_id,
         &f.owner_sk,
@@ -620,6 +626,8 @@ fn post_apply_cleanup_sweeps_banned_sender_dms() {
             owner_member_id: f.owner_id,
             banned_at: SystemTime::now(),
             banned_user: f.alice_id,
+            reason: None,
+            duration: None,
         },
         f.owner_id,
         &f.owner_sk,
@@ -645,6 +653,8 @@ fn post_apply_cleanup_sweeps_banned_recipient_dms() {
             owner_member_id: f.owner_id,
             banned_at: SystemTime::now(),
             banned_user: f.bob_id,
+            reason: None,
+            duration: None,
         },
         f.owner_id,
         &f.owner_sk,
@@ -709,6 +719,7 @@ fn cleanup_retains_purge_envelope_when_recipient_has_no_other_activity() {
         RecipientPurges {
             version: 1,
             purged: vec![token],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -785,6 +796,7 @@ fn cleanup_drops_purge_envelope_for_non_member() {
         RecipientPurges {
             version: 1,
             purged: vec![tok(0x11)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -798,6 +810,8 @@ fn cleanup_drops_purge_envelope_for_non_member() {
             owner_member_id: f.owner_id,
             banned_at: SystemTime::now(),
             banned_user: f.bob_id,
+            reason: None,
+            duration: None,
         },
         f.owner_id,
         &f.owner_sk,
@@ -826,6 +840,7 @@ fn tombstone_blocks_remerge_of_purged_message() {
         RecipientPurges {
             version: 1,
             purged: vec![purge_token],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -873,6 +888,7 @@ fn purge_advance_retroactively_drops_already_installed_message() {
         RecipientPurges {
             version: 1,
             purged: vec![token],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -905,6 +921,7 @@ fn recipient_purges_signature_failure_rejected() {
         RecipientPurges {
             version: 1,
             purged: vec![tok(1), tok(2), tok(3)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -933,6 +950,7 @@ fn non_recipient_signing_purges_rejected() {
     let state_purges = RecipientPurges {
         version: 1,
         purged: vec![tok(42)],
+        ..Default::default()
     };
     let bytes =
         build_recipient_purges_signed_bytes(f.bob_id, &f.params.owner, &state_purges).unwrap();
@@ -966,6 +984,7 @@ fn purge_envelope_version_zero_rejected_in_verify() {
         RecipientPurges {
             version: 0,
             purged: vec![],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -990,6 +1009,7 @@ fn purge_envelope_monotonic_version_apply_delta() {
         RecipientPurges {
             version: 2,
             purged: vec![tok(10)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1002,6 +1022,7 @@ fn purge_envelope_monotonic_version_apply_delta() {
         RecipientPurges {
             version: 1,
             purged: vec![tok(10), tok(20)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1035,6 +1056,7 @@ fn purge_envelope_same_version_different_content_silently_dropped() {
         RecipientPurges {
             version: 2,
             purged: vec![tok(10)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1047,6 +1069,7 @@ fn purge_envelope_same_version_different_content_silently_dropped() {
         RecipientPurges {
             version: 2,
             purged: vec![tok(20)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1084,6 +1107,7 @@ fn purge_version_bump_must_be_superset_silent_drop() {
         RecipientPurges {
             version: 1,
             purged: vec![tok(10), tok(20)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1096,6 +1120,7 @@ fn purge_version_bump_must_be_superset_silent_drop() {
         RecipientPurges {
             version: 2,
             purged: vec![tok(10)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1123,6 +1148,7 @@ fn purge_envelope_version_zero_rejected_in_apply_delta() {
         RecipientPurges {
             version: 0,
             purged: vec![],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1223,6 +1249,7 @@ fn purge_list_cap_just_over_rejected() {
         RecipientPurges {
             version: 1,
             purged: huge,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1245,6 +1272,7 @@ fn duplicate_recipient_purges_envelope_rejected() {
         RecipientPurges {
             version: 1,
             purged: vec![tok(1)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1255,6 +1283,7 @@ fn duplicate_recipient_purges_envelope_rejected() {
         RecipientPurges {
             version: 2,
             purged: vec![tok(1), tok(2)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1314,6 +1343,7 @@ fn direct_messages_wire_format_locked() {
         RecipientPurges {
             version: 5,
             purged: vec![PurgeToken([0xAA; 16]), PurgeToken([0xBB; 16])],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1371,6 +1401,7 @@ fn signed_bytes_carry_domain_tag() {
         &RecipientPurges {
             version: 1,
             purged: vec![tok(0xAB)],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -1453,6 +1484,7 @@ fn apply_delta_commutativity_message_then_purge_vs_purge_then_message() {
         RecipientPurges {
             version: 1,
             purged: vec![msg.purge_token()],
+            ..Default::default()
         },
     )
     .unwrap();
@@ -0,0 +1,255 @@
+//! Voluntary leave tests.
+//!
+//! A member leaves by posting a signed leave event. `post_apply_cleanup`
+//! treats a member whose latest message is a leave event as inactive, so they
+//! drop out of `MembersV1` without a ban — unless an active invitee's chain
+//! still needs them, exactly as with inactivity pruning. What they said,
+//! the leave event included, stays: their entry moves to `departures`.
+
+use ed25519_dalek::SigningKey;
+use freenet_scaffold::ComposableState;
+use rand::rngs::OsRng;
+use river_core::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+use river_core::room_state::member::{AuthorizedMember, Member, MemberId, MembersV1};
+use river_core::room_state::message::{
+    AuthorizedMessageV1, MessageV1, MessagesV1, RoomMessageBody,
+};
+use river_core::room_state::secret::{
+    AuthorizedEncryptedSecretForMember, EncryptedSecretForMemberV1, RoomSecretsV1,
+};
+use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1};
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+struct Peer {
+    sk: SigningKey,
+    id: MemberId,
+}
+
+impl Peer {
+    fn new() -> Self {
+        let sk = SigningKey::generate(&mut OsRng);
+        let id = sk.verifying_key().into();
+        Self { sk, id }
+    }
+}
+
+fn member(who: &Peer, inviter: &Peer, owner_id: MemberId) -> AuthorizedMember {
+    AuthorizedMember::new(
+        Member {
+            owner_member_id: owner_id,
+            invited_by: inviter.id,
+            member_vk: who.sk.verifying_key(),
+        },
+        &inviter.sk,
+    )
+}
+
+fn authored(
+    who: &Peer,
+    owner_id: MemberId,
+    secs: u64,
+    content: RoomMessageBody,
+) -> AuthorizedMessageV1 {
+    AuthorizedMessageV1::new(
+        MessageV1 {
+            room_owner: owner_id,
+            author: who.id,
+            time: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs),
+            content,
+        },
+        &who.sk,
+    )
+}
+
+fn params(owner: &Peer) -> ChatRoomParametersV1 {
+    ChatRoomParametersV1 {
+        owner: owner.sk.verifying_key(),
+    }
+}
+
+fn state(
+    owner: &Peer,
+    members: Vec<AuthorizedMember>,
+    messages: Vec<AuthorizedMessageV1>,
+) -> ChatRoomStateV1 {
+    ChatRoomStateV1 {
+        configuration: AuthorizedConfigurationV1::new(
+            Configuration {
+                max_members: 100,
+                max_recent_messages: 100,
+                ..Default::default()
+            },
+            &owner.sk,
+        ),
+        members: MembersV1 { members },
+        recent_messages: MessagesV1 {
+            messages,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn member_ids(state: &ChatRoomStateV1) -> HashSet<MemberId> {
+    state
+        .members
+        .members
+        .iter()
+        .map(|m| m.member.id())
+        .collect()
+}
+
+#[test]
+fn leaving_member_is_removed_but_their_messages_stay() {
+    let owner = Peer::new();
+    let a = Peer::new();
+    let b = Peer::new();
+    let owner_id = owner.id;
+
+    let mut room = state(
+        &owner,
+        vec![member(&a, &owner, owner_id), member(&b, &owner, owner_id)],
+        vec![
+            authored(&a, owner_id, 1, RoomMessageBody::public("hi".into())),
+            authored(&b, owner_id, 2, RoomMessageBody::public("hello".into())),
+            authored(&a, owner_id, 3, RoomMessageBody::leave_event()),
+        ],
+    );
+
+    room.post_apply_cleanup(&params(&owner)).unwrap();
+
+    assert_eq!(member_ids(&room), HashSet::from([b.id]));
+    let from_a: Vec<&AuthorizedMessageV1> = room
+        .recent_messages
+        .messages
+        .iter()
+        .filter(|m| m.message.author == a.id)
+        .collect();
+    assert_eq!(from_a.len(), 2, "the leaver's messages stay");
+    assert!(
+        from_a[1].message.content.is_leave_event(),
+        "the leave event stays"
+    );
+    assert_eq!(
+        room.departures
+            .0
+            .iter()
+            .map(|entry| entry.member.id())
+            .collect::<Vec<_>>(),
+        vec![a.id]
+    );
+
+    let once = room.clone();
+    room.post_apply_cleanup(&params(&owner)).unwrap();
+    assert_eq!(room, once, "cleanup is idempotent after a leave");
+    room.verify(&room, &params(&owner)).unwrap();
+}
+
+#[test]
+fn message_after_leaving_is_refused() {
+    let owner = Peer::new();
+    let a = Peer::new();
+    let owner_id = owner.id;
+
+    let mut room = state(
+        &owner,
+        vec![member(&a, &owner, owner_id)],
+        vec![authored(&a, owner_id, 1, RoomMessageBody::leave_event())],
+    );
+    room.post_apply_cleanup(&params(&owner)).unwrap();
+    assert_eq!(room.departures.0.len(), 1);
+
+    let late = authored(&a, owner_id, 2, RoomMessageBody::public("back?".into()));
+    let mut messages = room.recent_messages.clone();
+    messages
+        .apply_delta(&room, &params(&owner), &Some(vec![late]))
+        .unwrap();
+    assert_eq!(
+        messages, room.recent_messages,
+        "a departed member cannot post"
+    );
+}
+
+#[test]
+fn leaver_with_active_invitee_stays_as_chain_anchor() {
+    let owner = Peer::new();
+    let a = Peer::new();
+    let t = Peer::new();
+    let owner_id = owner.id;
+
+    // owner -> A -> T; A leaves but T is still talking.
+    let mut room = state(
+        &owner,
+        vec![member(&a, &owner, owner_id), member(&t, &a, owner_id)],
+        vec![
+            authored(&a, owner_id, 1, RoomMessageBody::leave_event()),
+            authored(
+                &t,
+                owner_id,
+                2,
+                RoomMessageBody::public("still here".into()),
+            ),
+        ],
+    );
+
+    room.post_apply_cleanup(&params(&owner)).unwrap();
+
+    assert_eq!(member_ids(&room), HashSet::from([a.id, t.id]));
+    room.verify(&room, &params(&owner)).unwrap();
+}
+
+#[test]
+fn posting_after_leaving_cancels_the_departure() {
+    let owner = Peer::new();
+    let a = Peer::new();
+    let owner_id = owner.id;
+
+    let mut room = state(
+        &owner,
+        vec![member(&a, &owner, owner_id)],
+        vec![
+            authored(&a, owner_id, 1, RoomMessageBody::leave_event()),
+            authored(&a, owner_id, 2, RoomMessageBody::join_event()),
+        ],
+    );
+
+    assert!(room.recent_messages.departed_members().is_empty());
+    room.post_apply_cleanup(&params(&owner)).unwrap();
+    assert_eq!(member_ids(&room), HashSet::from([a.id]));
+}
+
+/// A current-version secret recipient is normally exempt from pruning; a
+/// leaver must not be, or they would stay listed (and the owner's delegate
+/// would never rotate the secret away from them).
+#[test]
+fn leaving_secret_recipient_is_still_removed() {
+    let owner = Peer::new();
+    let a = Peer::new();
+    let owner_id = owner.id;
+
+    let mut room = state(
+        &owner,
+        vec![member(&a, &owner, owner_id)],
+        vec![authored(&a, owner_id, 1, RoomMessageBody::leave_event())],
+    );
+    room.secrets = RoomSecretsV1 {
+        current_version: 0,
+        versions: Vec::new(),
+        encrypted_secrets: vec![AuthorizedEncryptedSecretForMember::new(
+            EncryptedSecretForMemberV1 {
+                member_id: a.id,
+                secret_version: 0,
+                ciphertext: vec![0; 48],
+                nonce: [0; 12],
+                sender_ephemeral_public_key: [0; 32],
+                provider: owner_id,
+            },
+            &owner.sk,
+        )],
+    };
+
+    room.post_apply_cleanup(&params(&owner)).unwrap();
+
+    assert!(member_ids(&room).is_empty());
+}
@@ -511,6 +511,8 @@ fn test_ban_member_excludes_from_new_secrets() {
         owner_member_id: owner_id,
         banned_at: SystemTime::now(),
         banned_user: member1_id,
+        reason: None,
+        duration: None,
     };
 
     room_state
@@ -967,6 +969,7 @@ fn issue_318_member_info_apply_delta_has_no_contract_privacy_guard() {
         version: 1,
         preferred_nickname: SealedBytes::public(b"PlaintextNick".to_vec()),
         deputies: Vec::new(),
+        dm_prekey: None,
     };
     let authorized = AuthorizedMemberInfo::new_with_member_key(public_nickname, &member_sk);
 
@@ -657,6 +657,8 @@ fn ban_sweep_reopening_the_horizon_still_loops_known_gap() {
             owner_member_id: r.owner_id,
             banned_at: SystemTime::UNIX_EPOCH + Duration::from_secs(BASE_SECS + 500),
             banned_user: x_id,
+            reason: None,
+            duration: None,
         },
         r.owner_id,
         &r.owner_sk,
@@ -19,14 +19,20 @@ use ed25519_dalek::Signature;
 use freenet_scaffold::util::FastHash;
 use freenet_scaffold::ComposableState;
 use river_core::room_state::ban::{BanId, BansV1};
+use river_core::room_state::ban_lapse::LapseId;
+use river_core::room_state::ban_revocation::RevocationId;
 use river_core::room_state::direct_messages::{
     DirectMessagesSummary, DmOrderKey, DmPairHorizon, DmRetentionHorizon, SignatureBytes,
 };
+use river_core::room_state::invite::InviteId;
+use river_core::room_state::invite_use::InviteUseId;
+use river_core::room_state::join_request::JoinRequestId;
 use river_core::room_state::member::{MemberId, MembersV1};
 use river_core::room_state::member_info::MemberInfoV1;
 use river_core::room_state::message::{
     MessageId, MessageOrderKey, MessagesSummary, MessagesV1, RetentionHorizon,
 };
+use river_core::room_state::mute::MuteId;
 use river_core::room_state::secret::SecretsSummary;
 use river_core::room_state::ChatRoomStateV1Summary;
 use std::time::{Duration, SystemTime};
@@ -254,7 +260,14 @@ fn top_level_summary_serialization_is_order_independent() {
     fn build(reversed: bool) -> ChatRoomStateV1Summary {
         let order = |i: i64| if reversed { N - 1 - i } else { i };
         let bans = (0..N).map(|i| ban_id(order(i))).collect();
+        let ban_revocations = (0..N).map(|i| RevocationId(FastHash(order(i)))).collect();
+        let ban_lapses = (0..N).map(|i| LapseId(FastHash(order(i)))).collect();
         let members = (0..N).map(|i| member_id(order(i))).collect();
+        let departures = (0..N).map(|i| member_id(N + order(i))).collect();
+        let invites = (0..N).map(|i| InviteId(FastHash(order(i)))).collect();
+        let invite_uses = (0..N).map(|i| InviteUseId(FastHash(order(i)))).collect();
+        let mutes = (0..N).map(|i| MuteId(FastHash(order(i)))).collect();
+        let join_requests = (0..N).map(|i| JoinRequestId(FastHash(order(i)))).collect();
         let member_info = (0..N)
             .map(|i| {
                 let j = order(i);
@@ -306,8 +319,15 @@ fn top_level_summary_serialization_is_order_independent() {
         ChatRoomStateV1Summary {
             configuration: 7,
             bans,
+            ban_revocations,
+            ban_lapses,
+            invites,
+            invite_uses,
             members,
+            departures,
             member_info,
+            mutes,
+            join_requests,
             secrets,
             recent_messages,
             direct_messages,
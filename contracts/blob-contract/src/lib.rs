@@ -0,0 +1,143 @@
+//! Attachment blob contract.
+//!
+//! Parameters are a [`BlobParametersV1`] (the BLAKE3 hash of the blob) and the
+//! state is the blob's bytes. A blob is immutable: the first valid state
+//! wins and every later update is a no-op, so the contract key is a stable
+//! name for one attachment. See `river_core::blob`.
+
+use freenet_stdlib::prelude::*;
+use river_core::blob::{verify_blob, BlobParametersV1};
+
+fn blob_parameters(parameters: &Parameters<'static>) -> Result<BlobParametersV1, ContractError> {
+    BlobParametersV1::from_bytes(parameters.as_ref()).map_err(ContractError::Deser)
+}
+
+#[allow(dead_code)]
+struct Contract;
+
+#[contract]
+impl ContractInterface for Contract {
+    fn validate_state(
+        parameters: Parameters<'static>,
+        state: State<'static>,
+        _related: RelatedContracts<'static>,
+    ) -> Result<ValidateResult, ContractError> {
+        let bytes = state.as_ref();
+        // allow an empty placeholder, as the room contract does
+        if bytes.is_empty() {
+            return Ok(ValidateResult::Valid);
+        }
+        let parameters = blob_parameters(&parameters)?;
+        verify_blob(&parameters, bytes)
+            .map(|_| ValidateResult::Valid)
+            .map_err(|reason| ContractError::InvalidUpdateWithInfo { reason })
+    }
+
+    fn update_state(
+        parameters: Parameters<'static>,
+        state: State<'static>,
+        data: Vec<UpdateData<'static>>,
+    ) -> Result<UpdateModification<'static>, ContractError> {
+        let parameters = blob_parameters(&parameters)?;
+        let mut blob = state.as_ref().to_vec();
+
+        for update in data {
+            let candidate = match &update {
+                UpdateData::State(new_state) => new_state.as_ref(),
+                UpdateData::Delta(delta) => delta.as_ref(),
+                UpdateData::RelatedState { .. } => continue,
+                // See room-contract: reject unknown variants instead of panicking.
+                _ => return Err(ContractError::InvalidUpdate),
+            };
+            if candidate.is_empty() || !blob.is_empty() {
+                continue;
+            }
+            verify_blob(&parameters, candidate)
+                .map_err(|reason| ContractError::InvalidUpdateWithInfo { reason })?;
+            blob = candidate.to_vec();
+        }
+
+        Ok(UpdateModification::valid(blob.into()))
+    }
+
+    fn summarize_state(
+        _parameters: Parameters<'static>,
+        state: State<'static>,
+    ) -> Result<StateSummary<'static>, ContractError> {
+        // The only thing a peer can lack is the whole blob.
+        let have = if state.as_ref().is_empty() {
+            vec![]
+        } else {
+            vec![1]
+        };
+        Ok(StateSummary::from(have))
+    }
+
+    fn get_state_delta(
+        _parameters: Parameters<'static>,
+        state: State<'static>,
+        summary: StateSummary<'static>,
+    ) -> Result<StateDelta<'static>, ContractError> {
+        if summary.as_ref().is_empty() {
+            Ok(StateDelta::from(state.as_ref().to_vec()))
+        } else {
+            Ok(StateDelta::from(vec![]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(bytes: &[u8]) -> Parameters<'static> {
+        Parameters::from(BlobParametersV1::for_bytes(bytes).to_bytes())
+    }
+
+    fn updated(blob: &[u8], current: &[u8], update: &[u8]) -> Result<Vec<u8>, ContractError> {
+        let modification = Contract::update_state(
+            params(blob),
+            State::from(current.to_vec()),
+            vec![UpdateData::State(State::from(update.to_vec()))],
+        )?;
+        Ok(modification.new_state.unwrap().as_ref().to_vec())
+    }
+
+    #[test]
+    fn only_the_hashed_bytes_are_a_valid_state() {
+        let validate = |state: &[u8]| {
+            Contract::validate_state(
+                params(b"log"),
+                State::from(state.to_vec()),
+                RelatedContracts::default(),
+            )
+        };
+        assert!(matches!(validate(b"log"), Ok(ValidateResult::Valid)));
+        assert!(matches!(validate(b""), Ok(ValidateResult::Valid)));
+        assert!(validate(b"forged").is_err());
+    }
+
+    #[test]
+    fn first_valid_upload_is_final() {
+        assert_eq!(updated(b"log", b"", b"log").unwrap(), b"log");
+        assert!(updated(b"log", b"", b"forged").is_err());
+        // Once stored, later updates change nothing.
+        assert_eq!(updated(b"log", b"log", b"forged").unwrap(), b"log");
+    }
+
+    #[test]
+    fn delta_is_the_whole_blob_for_a_peer_without_it() {
+        let delta = |summary: Vec<u8>| {
+            Contract::get_state_delta(
+                params(b"log"),
+                State::from(b"log".to_vec()),
+                StateSummary::from(summary),
+            )
+            .unwrap()
+            .as_ref()
+            .to_vec()
+        };
+        assert_eq!(delta(vec![]), b"log");
+        assert!(delta(vec![1]).is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! Per-member DM inbox contract.
+//!
+//! Parameters are an [`InboxParametersV1`] (the room owner key and the
+//! recipient's member key) and the state is an [`InboxStateV1`]: the DMs
+//! addressed to that member, each with its sender's invite chain, and the
+//! recipient's purge envelope. A delta has the same shape as the state, so
+//! full-state and delta updates merge alike. See `river_core::inbox`.
+
+use freenet_stdlib::prelude::*;
+use river_core::inbox::{InboxParametersV1, InboxStateV1, InboxSummaryV1};
+
+fn inbox_parameters(parameters: &Parameters<'static>) -> Result<InboxParametersV1, ContractError> {
+    InboxParametersV1::from_bytes(parameters.as_ref()).map_err(ContractError::Deser)
+}
+
+fn inbox_state(bytes: &[u8]) -> Result<InboxStateV1, ContractError> {
+    InboxStateV1::from_bytes(bytes).map_err(ContractError::Deser)
+}
+
+#[allow(dead_code)]
+struct Contract;
+
+#[contract]
+impl ContractInterface for Contract {
+    fn validate_state(
+        parameters: Parameters<'static>,
+        state: State<'static>,
+        _related: RelatedContracts<'static>,
+    ) -> Result<ValidateResult, ContractError> {
+        let parameters = inbox_parameters(&parameters)?;
+        inbox_state(state.as_ref())?
+            .verify(&parameters)
+            .map(|_| ValidateResult::Valid)
+            .map_err(|reason| ContractError::InvalidUpdateWithInfo { reason })
+    }
+
+    fn update_state(
+        parameters: Parameters<'static>,
+        state: State<'static>,
+        data: Vec<UpdateData<'static>>,
+    ) -> Result<UpdateModification<'static>, ContractError> {
+        let parameters = inbox_parameters(&parameters)?;
+        let mut inbox = inbox_state(state.as_ref())?;
+
+        for update in data {
+            let incoming = match &update {
+                UpdateData::State(new_state) => inbox_state(new_state.as_ref())?,
+                UpdateData::Delta(delta) => inbox_state(delta.as_ref())?,
+                UpdateData::RelatedState { .. } => continue,
+                // See room-contract: reject unknown variants instead of panicking.
+                _ => return Err(ContractError::InvalidUpdate),
+            };
+            inbox
+                .merge(&parameters, incoming)
+                .map_err(|reason| ContractError::InvalidUpdateWithInfo { reason })?;
+        }
+
+        Ok(UpdateModification::valid(inbox.to_bytes().into()))
+    }
+
+    fn summarize_state(
+        _parameters: Parameters<'static>,
+        state: State<'static>,
+    ) -> Result<StateSummary<'static>, ContractError> {
+        Ok(StateSummary::from(
+            inbox_state(state.as_ref())?.summarize().to_bytes(),
+        ))
+    }
+
+    fn get_state_delta(
+        _parameters: Parameters<'static>,
+        state: State<'static>,
+        summary: StateSummary<'static>,
+    ) -> Result<StateDelta<'static>, ContractError> {
+        let summary = InboxSummaryV1::from_bytes(summary.as_ref()).map_err(ContractError::Deser)?;
+        let delta = inbox_state(state.as_ref())?.delta(&summary);
+        if delta.is_empty() {
+            Ok(StateDelta::from(vec![]))
+        } else {
+            Ok(StateDelta::from(delta.to_bytes()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use river_core::inbox::InboxMessageV1;
+    use river_core::room_state::direct_messages::sign_direct_message;
+    use river_core::room_state::member::{AuthorizedMember, Member};
+
+    fn keys() -> (SigningKey, SigningKey, SigningKey) {
+        (
+            SigningKey::from_bytes(&[1; 32]),
+            SigningKey::from_bytes(&[2; 32]),
+            SigningKey::from_bytes(&[3; 32]),
+        )
+    }
+
+    /// Bob's inbox parameters and a DM to him from Alice.
+    fn fixture() -> (Parameters<'static>, InboxMessageV1) {
+        let (owner, alice, bob) = keys();
+        let params = InboxParametersV1 {
+            room_owner: owner.verifying_key(),
+            recipient: bob.verifying_key(),
+        };
+        let alice_member = AuthorizedMember::new(
+            Member {
+                owner_member_id: owner.verifying_key().into(),
+                invited_by: owner.verifying_key().into(),
+                member_vk: alice.verifying_key(),
+            },
+            &owner,
+        );
+        let message = sign_direct_message(
+            &alice,
+            alice.verifying_key().into(),
+            bob.verifying_key().into(),
+            &owner.verifying_key(),
+            10,
+            b"sealed".to_vec(),
+        )
+        .unwrap();
+        (
+            Parameters::from(params.to_bytes()),
+            InboxMessageV1 {
+                sender_chain: vec![alice_member],
+                message,
+            },
+        )
+    }
+
+    fn state_with(messages: Vec<InboxMessageV1>) -> Vec<u8> {
+        InboxStateV1 {
+            messages,
+            ..Default::default()
+        }
+        .to_bytes()
+    }
+
+    #[test]
+    fn updates_merge_and_forged_ones_are_rejected() {
+        let (params, dm) = fixture();
+        let update = |current: Vec<u8>, data: UpdateData<'static>| {
+            Contract::update_state(params.clone(), State::from(current), vec![data])
+                .map(|m| m.new_state.unwrap().as_ref().to_vec())
+        };
+
+        let one = update(
+            vec![],
+            UpdateData::Delta(StateDelta::from(state_with(vec![dm.clone()]))),
+        )
+        .unwrap();
+        // The same DM arriving again as a full state changes nothing.
+        let again = update(
+            one.clone(),
+            UpdateData::State(State::from(state_with(vec![dm.clone()]))),
+        )
+        .unwrap();
+        assert_eq!(one, again);
+
+        // Alice's signature over other bytes.
+        let mut forged = dm.clone();
+        forged.message.message.ciphertext = b"tampered".to_vec();
+        forged.message.sender_signature = ed25519_dalek::Signer::sign(&keys().1, b"something else");
+        assert!(update(
+            one.clone(),
+            UpdateData::Delta(StateDelta::from(state_with(vec![forged]))),
+        )
+        .is_err());
+
+        let valid = Contract::validate_state(
+            params.clone(),
+            State::from(one),
+            RelatedContracts::default(),
+        );
+        assert!(matches!(valid, Ok(ValidateResult::Valid)));
+    }
+
+    #[test]
+    fn delta_is_empty_for_a_peer_that_has_everything() {
+        let (params, dm) = fixture();
+        let state = state_with(vec![dm]);
+        let delta = |summary: Vec<u8>| {
+            Contract::get_state_delta(
+                params.clone(),
+                State::from(state.clone()),
+                StateSummary::from(summary),
+            )
+            .unwrap()
+            .as_ref()
+            .to_vec()
+        };
+        assert_eq!(delta(vec![]), state);
+        let summary = Contract::summarize_state(params.clone(), State::from(state.clone()))
+            .unwrap()
+            .as_ref()
+            .to_vec();
+        assert!(delta(summary).is_empty());
+    }
+}
@@ -471,8 +471,15 @@ pub(crate) fn handle_contract_notification(
     let delta = river_core::room_state::ChatRoomStateV1Delta {
         configuration: None,
         bans: None,
+        ban_revocations: None,
+        ban_lapses: None,
+        invites: None,
+        invite_uses: None,
         members: None,
+        departures: None,
         member_info: None,
+        mutes: None,
+        join_requests: None,
         secrets: Some(SecretsDelta {
             current_version: Some(new_version),
             new_versions: vec![authorized_record],
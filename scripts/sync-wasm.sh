@@ -7,7 +7,7 @@ set -euo pipefail
 REPO_ROOT="$(cd "$(dirname "$0")/.." && pwd)"
 cd "$REPO_ROOT"
 
-echo "Building room-contract and chat-delegate WASMs..."
+echo "Building room-contract, chat-delegate, blob-contract and inbox-contract WASMs..."
 # `--locked` is critical: if Cargo.lock drifts on a release machine, the
 # produced WASM bytes (and therefore the delegate / contract key) drift
 # silently. Anyone running this script must be operating from the
@@ -15,13 +15,25 @@ echo "Building room-contract and chat-delegate WASMs..."
 # what CI verified.
 cargo build --locked --release --target wasm32-unknown-unknown -p room-contract -p chat-delegate --target-dir target
 
+# The blob and inbox contracts are built in their own invocations so they
+# can never change the feature unification (and so the keys) of the WASMs
+# above.
+cargo build --locked --release --target wasm32-unknown-unknown -p blob-contract --target-dir target
+cargo build --locked --release --target wasm32-unknown-unknown -p inbox-contract --target-dir target
+
 SRC_CONTRACT="target/wasm32-unknown-unknown/release/room_contract.wasm"
+SRC_BLOB="target/wasm32-unknown-unknown/release/blob_contract.wasm"
+SRC_INBOX="target/wasm32-unknown-unknown/release/inbox_contract.wasm"
 SRC_DELEGATE="target/wasm32-unknown-unknown/release/chat_delegate.wasm"
 
 copies=(
     "$SRC_CONTRACT:ui/public/contracts/room_contract.wasm"
     "$SRC_CONTRACT:cli/contracts/room_contract.wasm"
     "$SRC_DELEGATE:ui/public/contracts/chat_delegate.wasm"
+    "$SRC_BLOB:ui/public/contracts/blob_contract.wasm"
+    "$SRC_BLOB:cli/contracts/blob_contract.wasm"
+    "$SRC_INBOX:ui/public/contracts/inbox_contract.wasm"
+    "$SRC_INBOX:cli/contracts/inbox_contract.wasm"
 )
 
 for pair in "${copies[@]}"; do
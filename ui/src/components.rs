@@ -96,9 +96,13 @@ mod volatile_value_binding_audit {
     const EXPECTED_EDITABLE: &[&str] = &[
         r#"components/conversation.rs <textarea> "{edit_text}""#,
         r#"components/conversation/message_input.rs <textarea> "{message_text}""#,
+        r#"components/conversation/search_panel.rs <input> "{from_date}""#,
+        r#"components/conversation/search_panel.rs <input> "{text}""#,
+        r#"components/conversation/search_panel.rs <input> "{to_date}""#,
         r#"components/direct_messages/dm_thread_modal.rs <textarea> "{draft.read()}""#,
         r#"components/direct_messages/invite_via_dm_picker_modal.rs <textarea> "{personal_message_value}""#,
         r#"components/members.rs <textarea> "{token_input}""#,
+        r#"components/members/member_info_modal/ban_button.rs <input> "{reason_input}""#,
         r#"components/members/member_info_modal/nickname_field.rs <input> "{temp_nickname}""#,
         r#"components/room_list/create_room_modal.rs <input> "{nickname}""#,
         r#"components/room_list/create_room_modal.rs <input> "{room_name}""#,
@@ -1,6 +1,9 @@
+pub mod block_list;
 pub mod chat_delegate;
+pub mod dm_ratchet;
 pub mod document_title;
 pub mod freenet_api;
+pub mod message_archive;
 pub mod notifications;
 pub mod receive_times;
 pub mod sync_info;
@@ -399,16 +402,24 @@ pub fn App() -> Element {
         crate::components::direct_messages::seed_dm_last_seen_if_needed();
     });
 
-    // Outbound DM cache hygiene (#256): whenever ROOMS changes, drop
-    // cached plaintext entries whose ciphertext is no longer present
-    // in any room (recipient purged them, or per-pair-cap eviction in
-    // the contract dropped them). The prune helper only writes when
-    // something is actually removed, so it doesn't re-trigger itself.
-    // We deliberately do NOT subscribe to OUTBOUND_DMS here — that
-    // would loop, since prune mutates OUTBOUND_DMS.
+    // Local message archive: keep every message ROOMS has held, past the
+    // rooms' retention limits. Records only what is new, so its own writes
+    // (to MESSAGE_ARCHIVES, which it does not subscribe to) cannot loop.
     use_effect(|| {
         let _rooms_marker = ROOMS.try_read().map(|r| r.map.len()).unwrap_or(0);
-        crate::components::app::chat_delegate::prune_outbound_dms_for_purges();
+        crate::components::app::message_archive::record_room_messages();
+    });
+
+    // Forward-secret DMs: open inbound ratcheted DMs once, as they arrive,
+    // since each one's key is deleted when it is used. Writes only
+    // OPENED_RATCHET_DMS, which this effect does not read.
+    use_effect(|| {
+        let _rooms_marker = ROOMS.try_read().map(|r| r.map.len()).unwrap_or(0);
+        let _inbox_marker = crate::components::app::freenet_api::inbox_sync::INBOXES
+            .try_read()
+            .map(|i| i.len())
+            .unwrap_or(0);
+        crate::components::app::dm_ratchet::open_ratchet_dms();
     });
 
     #[cfg(not(feature = "no-sync"))]
@@ -0,0 +1,168 @@
+//! The local member's block list: members whose room messages this client
+//! hides and whose DMs it purges, per identity and room.
+//!
+//! Nothing is published. The list rides in the chat delegate's outbound-DM
+//! blob (`OutboundDmStore::blocked_members`), like the archived-thread list,
+//! so it survives a reload and reaches the user's other devices; it is loaded
+//! with that blob ([`hydrate_blocked_members`]) and saved with it. Blocking
+//! someone purges what they already sent to our inbox at once, and every
+//! later inbox read purges whatever else arrives
+//! (`inbox_sync::purge_blocked_senders`).
+
+use super::chat_delegate::save_outbound_dms_to_delegate;
+use super::freenet_api::inbox_sync::purge_blocked_senders;
+use super::ROOMS;
+use dioxus::logger::tracing::warn;
+use dioxus::prelude::*;
+use ed25519_dalek::VerifyingKey;
+use river_core::chat_delegate::{blocked_by, BlockedMemberEntry};
+use river_core::room_state::member::MemberId;
+use std::collections::HashSet;
+
+/// Every block made by any of our identities, as stored in the delegate.
+pub static BLOCKED_MEMBERS: GlobalSignal<Vec<BlockedMemberEntry>> = Global::new(Vec::new);
+
+/// The members `blocker` has blocked in `room`. `None` when the list is
+/// being written; callers in a memo retry via `signal_guard::schedule_nudge`.
+pub fn blocked_in_room(room: VerifyingKey, blocker: MemberId) -> Option<HashSet<MemberId>> {
+    let blocked = BLOCKED_MEMBERS.try_read().ok()?;
+    Some(blocked_by(&blocked, &room.to_bytes(), blocker))
+}
+
+/// Our identity in `room`, or `None` if the room is not loaded.
+fn self_id_in(room: VerifyingKey) -> Option<MemberId> {
+    ROOMS
+        .try_read()
+        .ok()?
+        .map
+        .get(&room)
+        .map(|room_data| MemberId::from(&room_data.self_sk.verifying_key()))
+}
+
+/// Block `member` in `room` for our current identity there, purge what they
+/// sent to our inbox and save the list.
+pub fn block_member(room: VerifyingKey, member: MemberId) {
+    let Some(blocker) = self_id_in(room) else {
+        return;
+    };
+    let entry = BlockedMemberEntry {
+        room_owner_vk: room.to_bytes(),
+        blocker,
+        member,
+        blocked_at: crate::util::get_current_system_time()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    crate::util::defer(move || {
+        let added = BLOCKED_MEMBERS.with_mut(|blocked| {
+            if blocked_by(blocked, &entry.room_owner_vk, blocker).contains(&member) {
+                return false;
+            }
+            blocked.push(entry);
+            true
+        });
+        if added {
+            purge_blocked_senders(room);
+            persist();
+        }
+    });
+}
+
+/// Lift our current identity's block on `member` in `room` and save the
+/// list. DMs already purged stay purged.
+pub fn unblock_member(room: VerifyingKey, member: MemberId) {
+    let Some(blocker) = self_id_in(room) else {
+        return;
+    };
+    crate::util::defer(move || {
+        let removed = BLOCKED_MEMBERS.with_mut(|blocked| {
+            let before = blocked.len();
+            blocked.retain(|b| !is_block(b, &room.to_bytes(), blocker, member));
+            blocked.len() != before
+        });
+        if removed {
+            persist();
+        }
+    });
+}
+
+/// Add the blocks loaded from a delegate to the ones already held, then
+/// purge the inboxes of every room with a block in it. Returns how many
+/// entries were loaded.
+pub fn hydrate_blocked_members(entries: Vec<BlockedMemberEntry>) -> usize {
+    let count = entries.len();
+    if count == 0 {
+        return 0;
+    }
+    crate::util::defer(move || {
+        let rooms = BLOCKED_MEMBERS.with_mut(|blocked| merge_blocks(blocked, entries));
+        for room in rooms {
+            match VerifyingKey::from_bytes(&room) {
+                Ok(room) => purge_blocked_senders(room),
+                Err(e) => warn!("Skipping block list entry with invalid room VK: {}", e),
+            }
+        }
+    });
+    count
+}
+
+fn is_block(
+    entry: &BlockedMemberEntry,
+    room: &[u8; 32],
+    blocker: MemberId,
+    member: MemberId,
+) -> bool {
+    &entry.room_owner_vk == room && entry.blocker == blocker && entry.member == member
+}
+
+/// Add to `held` each of `incoming` it lacks. Returns the rooms of the
+/// blocks added.
+fn merge_blocks(
+    held: &mut Vec<BlockedMemberEntry>,
+    incoming: Vec<BlockedMemberEntry>,
+) -> HashSet<[u8; 32]> {
+    let mut rooms = HashSet::new();
+    for entry in incoming {
+        if !held
+            .iter()
+            .any(|b| is_block(b, &entry.room_owner_vk, entry.blocker, entry.member))
+        {
+            rooms.insert(entry.room_owner_vk);
+            held.push(entry);
+        }
+    }
+    rooms
+}
+
+fn persist() {
+    crate::util::safe_spawn_local(async {
+        if let Err(e) = save_outbound_dms_to_delegate().await {
+            warn!("Failed to persist the block list: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freenet_scaffold::util::FastHash;
+
+    fn block(blocker: i64, member: i64) -> BlockedMemberEntry {
+        BlockedMemberEntry {
+            room_owner_vk: [9; 32],
+            blocker: MemberId(FastHash(blocker)),
+            member: MemberId(FastHash(member)),
+            blocked_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn merging_loaded_blocks_keeps_one_of_each() {
+        let mut held = vec![block(1, 2)];
+        let rooms = merge_blocks(&mut held, vec![block(1, 2), block(1, 3), block(1, 3)]);
+        assert_eq!(held, vec![block(1, 2), block(1, 3)]);
+        assert_eq!(rooms, HashSet::from([[9; 32]]));
+        assert!(merge_blocks(&mut held, vec![block(1, 2)]).is_empty());
+    }
+}
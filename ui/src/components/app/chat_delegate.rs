@@ -84,7 +84,7 @@ static CHAT_DELEGATE_KEY: LazyLock<DelegateKey> = LazyLock::new(|| {
 /// across call sites impossible at the type level (skeptical-review
 /// M3 on PR #311). Each save path declares one `static` of this type
 /// and hands a `&CoalesceState` to [`coalesce_save`].
-struct CoalesceState {
+pub(crate) struct CoalesceState {
     /// Serializes the critical section so at most one save runs at a
     /// time. `futures::lock::Mutex` (async-aware) is held across the
     /// entire dirty-check loop, which closes the TOCTOU window the
@@ -111,7 +111,7 @@ impl CoalesceState {
     /// const-callable: `futures::lock::Mutex::new`, `AtomicBool::new`,
     /// and `std::sync::Mutex::new` are all `const` (the last one since
     /// Rust 1.63).
-    const fn new() -> Self {
+    pub(crate) const fn new() -> Self {
         Self {
             mutex: futures::lock::Mutex::new(()),
             dirty: AtomicBool::new(false),
@@ -179,7 +179,7 @@ impl CoalesceState {
 /// permanently poison it; we recover via `PoisonError::into_inner` at
 /// both lock sites so the bug-fix this helper exists for doesn't
 /// silently regress in non-release profiles.
-async fn coalesce_save<F, Fut>(
+pub(crate) async fn coalesce_save<F, Fut>(
     state: &CoalesceState,
     label: &'static str,
     do_save: F,
@@ -2468,18 +2468,19 @@ mod tests {
     /// (freenet/river#398 moved codegen to `freenet-migrate-build`) must
     /// reproduce it byte-identically, or every user silently re-runs legacy
     /// migration once. Pinned to the value computed from the current
-    /// `legacy_delegates.toml` (26 entries spanning V1..V29 — V4–V6 removed —
+    /// `legacy_delegates.toml` (27 entries spanning V1..V30 — V4–V6 removed —
     /// in file order). This value
     /// SHOULD change when a genuinely new legacy entry is added — update the
     /// constant then — but must NEVER change from a codegen/tooling swap.
     ///
-    /// Updated for V29 (freenet/river#519, the global DM cap): the change moves
-    /// the delegate WASM, so the added entry legitimately re-fingerprints the
-    /// set and every user re-probes the legacy delegates once. That is the
-    /// intended behaviour for a real new generation, not a codegen artefact.
+    /// Updated for V30 (the last released delegate, which this series of
+    /// unreleased changes replaces): the added entry legitimately
+    /// re-fingerprints the set and every user re-probes the legacy delegates
+    /// once. That is the intended behaviour for a real new generation, not a
+    /// codegen artefact.
     #[test]
     fn legacy_set_fingerprint_is_stable_across_codegen_changes() {
-        assert_eq!(legacy_set_fingerprint(), "323a2f640bfd7a7f");
+        assert_eq!(legacy_set_fingerprint(), "c43e66ee147e3739");
     }
 
     /// The "migration in progress" and "migration done" localStorage keys MUST
@@ -4089,9 +4090,10 @@ fn should_defer_cache_only_save(elapsed_ms: f64) -> bool {
 }
 
 /// Hash of the room's UNRECOVERABLE fields — everything persisted EXCEPT the
-/// reconstructible cache (`room_state`, `last_read_message_id`).
+/// reconstructible cache (`room_state` and the read markers
+/// `last_read_message_id` / `thread_read`).
 ///
-/// Computed structurally, by blanking the two cache fields on a clone and
+/// Computed structurally, by blanking the cache fields on a clone and
 /// hashing the rest, rather than by listing the critical fields. That direction
 /// is deliberate and load-bearing: a field added to `RoomData` later is
 /// automatically treated as CRITICAL (written immediately) instead of silently
@@ -4102,6 +4104,7 @@ fn critical_hash(room_data: &RoomData) -> Option<u64> {
     let mut probe = room_data.clone();
     probe.room_state = Default::default();
     probe.last_read_message_id = None;
+    probe.thread_read.clear();
     let mut buf = Vec::new();
     ciborium::ser::into_writer(&probe, &mut buf).ok()?;
     Some(content_hash(&buf))
@@ -5388,9 +5391,19 @@ async fn do_save_outbound_dms_to_delegate() -> Result<(), String> {
                 .then_with(|| a.peer.cmp(&b.peer))
         });
 
+        // The block list is a `Vec` already; sorted for the same reason.
+        let mut blocked_members = super::block_list::BLOCKED_MEMBERS.read().clone();
+        blocked_members.sort_by(|a, b| {
+            a.room_owner_vk
+                .cmp(&b.room_owner_vk)
+                .then_with(|| a.blocker.cmp(&b.blocker))
+                .then_with(|| a.member.cmp(&b.member))
+        });
+
         OutboundDmStore {
             entries,
             hidden_threads,
+            blocked_members,
         }
     };
 
@@ -5475,98 +5488,6 @@ pub fn save_outbound_dm(
     });
 }
 
-/// Drop cached outbound-DM entries whose token appears in some
-/// recipient's purge envelope on a loaded room. This is the explicit
-/// tombstone signal — the recipient signed an `AuthorizedRecipientPurges`
-/// listing this token, so the contract has already dropped the
-/// matching ciphertext via `post_apply_cleanup` and we should drop the
-/// matching plaintext in lockstep.
-///
-/// Called from a `use_effect` in `App()` that subscribes to ROOMS, so
-/// it fires after every room-state mutation. Persists the trimmed
-/// cache if anything was actually removed.
-///
-/// **Why we ONLY act on purge envelopes (not on "ciphertext missing
-/// from `direct_messages.messages`")** — original PR-review BLOCKING
-/// finding: prune-clobbers-cache on cold start. If we pruned on the
-/// negative "no longer in `messages`" signal, then a cold-start
-/// sequence where `OUTBOUND_DMS` hydrates BEFORE the room contract
-/// state finishes loading (which can happen any time `rooms_data` and
-/// `outbound_dms` were last saved in different orders, or when the
-/// network-backed `direct_messages` state lags behind the delegate's
-/// `outbound_dms` blob) would wipe every cached entry and persist the
-/// empty result — destroying the user's outbound plaintext history.
-///
-/// Purge envelopes don't have that race: a recipient envelope is
-/// monotonically versioned and never disappears, so its presence is
-/// always a true tombstone signal regardless of how fresh the rest of
-/// the room state is.
-pub fn prune_outbound_dms_for_purges() {
-    use crate::components::direct_messages::OUTBOUND_DMS;
-
-    // Collect the tombstoned `(room, recipient, token)` triples from
-    // every loaded room's purge envelopes. `try_read` so we cooperate
-    // with any in-flight write.
-    let purged_keys: std::collections::HashSet<(
-        ed25519_dalek::VerifyingKey,
-        MemberId,
-        PurgeToken,
-    )> = {
-        let Ok(rooms) = ROOMS.try_read() else {
-            return;
-        };
-        let mut keys = std::collections::HashSet::new();
-        for (owner_vk, room_data) in &rooms.map {
-            for envelope in &room_data.room_state.direct_messages.purges {
-                for token in &envelope.state.purged {
-                    keys.insert((*owner_vk, envelope.recipient_id, *token));
-                }
-            }
-        }
-        keys
-    };
-    if purged_keys.is_empty() {
-        return;
-    }
-
-    // Compute the intersection via a read-only borrow so we can skip
-    // the entire defer/with_mut path when nothing needs removal.
-    // Skipping the `with_mut` is required for the App() use_effect
-    // not to loop on its own writes — see fn doc.
-    let to_remove: Vec<(ed25519_dalek::VerifyingKey, MemberId, PurgeToken)> = {
-        let Ok(cache) = OUTBOUND_DMS.try_read() else {
-            return;
-        };
-        cache
-            .by_token
-            .keys()
-            .filter(|k| purged_keys.contains(k))
-            .copied()
-            .collect()
-    };
-    if to_remove.is_empty() {
-        return;
-    }
-
-    let removed = to_remove.len();
-    crate::util::defer(move || {
-        OUTBOUND_DMS.with_mut(|cache| {
-            for key in &to_remove {
-                cache.by_token.remove(key);
-            }
-        });
-        info!(
-            "Pruned {} outbound-DM cache entries against purge envelopes",
-            removed
-        );
-        crate::util::safe_spawn_local(async {
-            if let Err(e) = save_outbound_dms_to_delegate().await {
-                warn!("Failed to persist pruned outbound DM cache: {}", e);
-            }
-        });
-    });
-}
-
 /// Drop every cached outbound-DM plaintext entry and archived-thread entry for
 /// `owner_vk`'s room from the in-memory `OUTBOUND_DMS` / `HIDDEN_DM_THREADS`
 /// signals and persist the trimmed store.
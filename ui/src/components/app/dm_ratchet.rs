@@ -0,0 +1,362 @@
+//! Forward-secret DM state (`river_core::dm_ratchet`), kept in the chat
+//! delegate under one key per room.
+//!
+//! The [`RatchetStore`]s live in a thread-local rather than a signal: a send
+//! must advance its session and the next send must see that, with no
+//! deferred write in between, or two DMs would share a message key. What the
+//! UI renders — the plaintext of opened ratcheted DMs — is mirrored into
+//! [`OPENED_RATCHET_DMS`].
+//!
+//! A room's store is loaded from the delegate before anything touches it, so
+//! a save can never replace stored sessions with an empty store: inbound
+//! ratcheted DMs wait for the load ([`open_ratchet_dms`]), and a send awaits
+//! it ([`compose_dm`]).
+
+use super::chat_delegate::{coalesce_save, send_delegate_request, CoalesceState};
+use super::freenet_api::inbox_sync::INBOXES;
+use super::ROOMS;
+use dioxus::logger::tracing::{info, warn};
+use dioxus::prelude::*;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use river_core::chat_delegate::{ChatDelegateKey, ChatDelegateRequestMsg, ChatDelegateResponseMsg};
+use river_core::dm_ratchet::{is_ratchet_envelope, RatchetStore};
+use river_core::room_state::direct_messages::{AuthorizedDirectMessage, PurgeToken};
+use river_core::room_state::member_info::DmPrekeyV1;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+/// Prefix for per-room ratchet keys: `dm_ratchet:<base58(owner_vk)>`.
+pub const RATCHET_KEY_PREFIX: &str = "dm_ratchet:";
+
+pub fn ratchet_storage_key(owner_vk: &VerifyingKey) -> Vec<u8> {
+    format!(
+        "{RATCHET_KEY_PREFIX}{}",
+        bs58::encode(owner_vk.to_bytes()).into_string()
+    )
+    .into_bytes()
+}
+
+pub fn is_ratchet_storage_key(key: &[u8]) -> bool {
+    key.starts_with(RATCHET_KEY_PREFIX.as_bytes())
+}
+
+/// What the UI shows for one room's inbound ratcheted DMs.
+#[derive(Clone, Default, PartialEq)]
+pub struct OpenedRatchetDms {
+    pub opened: HashMap<PurgeToken, Vec<u8>>,
+    /// Why a DM did not open. Retried as more DMs arrive: a reply can land
+    /// before the message that opened its session.
+    pub failed: HashMap<PurgeToken, String>,
+}
+
+/// Opened ratcheted DMs by room owner key.
+pub static OPENED_RATCHET_DMS: GlobalSignal<HashMap<VerifyingKey, OpenedRatchetDms>> =
+    Global::new(HashMap::new);
+
+/// One room's store and where it stands with the delegate.
+#[derive(Default)]
+struct RoomRatchet {
+    store: RatchetStore,
+    /// The stored store has been read (or there was none).
+    loaded: bool,
+    /// A load is in flight or finished; cleared on failure so it is retried.
+    load_requested: bool,
+}
+
+thread_local! {
+    static RATCHETS: RefCell<HashMap<VerifyingKey, RoomRatchet>> = RefCell::new(HashMap::new());
+    /// Rooms whose store has changed since it was last saved.
+    static DIRTY_RATCHETS: RefCell<HashSet<VerifyingKey>> = RefCell::new(HashSet::new());
+}
+
+static RATCHET_SAVE_STATE: CoalesceState = CoalesceState::new();
+
+/// Open the inbound ratcheted DMs in ROOMS and INBOXES that have not been
+/// opened yet, oldest first, and start loading the store of any room seen
+/// for the first time. Writes nothing when nothing changed, so the effect
+/// driving it does not loop.
+pub fn open_ratchet_dms() {
+    let mut pending: Vec<(VerifyingKey, SigningKey, Vec<AuthorizedDirectMessage>)> = Vec::new();
+    let mut to_load = Vec::new();
+    {
+        let Ok(rooms) = ROOMS.try_read() else {
+            return;
+        };
+        let Ok(inboxes) = INBOXES.try_read() else {
+            return;
+        };
+        RATCHETS.with(|ratchets| {
+            let mut ratchets = ratchets.borrow_mut();
+            for (owner_vk, room_data) in &rooms.map {
+                let room = ratchets.entry(*owner_vk).or_default();
+                if !room.load_requested {
+                    room.load_requested = true;
+                    to_load.push(*owner_vk);
+                }
+                if !room.loaded {
+                    continue;
+                }
+                let mut dms: Vec<AuthorizedDirectMessage> =
+                    crate::components::direct_messages::inbound_dms(
+                        room_data,
+                        inboxes.get(owner_vk),
+                    )
+                    .into_iter()
+                    .filter(|m| {
+                        is_ratchet_envelope(&m.message.ciphertext)
+                            && room.store.opened(&m.purge_token()).is_none()
+                    })
+                    .cloned()
+                    .collect();
+                if !dms.is_empty() {
+                    dms.sort_by_key(|m| m.message.timestamp);
+                    pending.push((*owner_vk, room_data.self_sk.clone(), dms));
+                }
+            }
+        });
+    }
+    for owner_vk in to_load {
+        crate::util::safe_spawn_local(async move {
+            if let Err(e) = load_room_ratchet(owner_vk).await {
+                warn!("Failed to load DM sessions: {}", e);
+            }
+        });
+    }
+
+    let mut updates = Vec::new();
+    for (owner_vk, self_sk, dms) in pending {
+        let failed: HashMap<PurgeToken, String> = with_store(&owner_vk, |store| {
+            dms.iter()
+                .filter_map(|msg| {
+                    store
+                        .open(&self_sk, &owner_vk, msg)
+                        .err()
+                        .map(|e| (msg.purge_token(), e))
+                })
+                .collect()
+        });
+        updates.push((owner_vk, failed));
+    }
+    publish(updates);
+}
+
+/// [`RatchetStore::compose_direct_message`] with this room's store, loading
+/// it first if needed.
+pub async fn compose_dm(
+    owner_vk: VerifyingKey,
+    self_sk: &SigningKey,
+    peer_vk: &VerifyingKey,
+    peer_prekey: Option<DmPrekeyV1>,
+    now: u64,
+    body: &[u8],
+) -> Result<AuthorizedDirectMessage, String> {
+    load_room_ratchet(owner_vk)
+        .await
+        .map_err(|e| format!("DM sessions are not loaded: {}", e))?;
+    with_store(&owner_vk, |store| {
+        store.compose_direct_message(
+            self_sk,
+            peer_vk,
+            peer_prekey.as_ref(),
+            &owner_vk,
+            now,
+            now,
+            body,
+        )
+    })
+}
+
+/// Rotate this room's prekey, returning the new one to publish, after its
+/// store has loaded.
+pub async fn rotate_prekey(owner_vk: VerifyingKey) -> Result<DmPrekeyV1, String> {
+    load_room_ratchet(owner_vk).await?;
+    Ok(with_store(&owner_vk, RatchetStore::rotate_prekey))
+}
+
+/// Delete this room's prekey secrets once the member has stopped publishing
+/// one.
+pub async fn clear_prekeys(owner_vk: VerifyingKey) -> Result<(), String> {
+    load_room_ratchet(owner_vk).await?;
+    with_store(&owner_vk, RatchetStore::clear_prekeys);
+    Ok(())
+}
+
+/// Delete the plaintext of purged DMs, leaving them unreadable.
+pub fn forget(owner_vk: VerifyingKey, tokens: &[PurgeToken]) {
+    let loaded = RATCHETS.with(|r| r.borrow().get(&owner_vk).is_some_and(|room| room.loaded));
+    if !loaded {
+        return;
+    }
+    with_store(&owner_vk, |store| store.forget(tokens));
+    publish(vec![(owner_vk, HashMap::new())]);
+}
+
+/// Run `f` on a loaded room's store, saving it if `f` changed it.
+fn with_store<T>(owner_vk: &VerifyingKey, f: impl FnOnce(&mut RatchetStore) -> T) -> T {
+    let (out, changed) = RATCHETS.with(|ratchets| {
+        let mut ratchets = ratchets.borrow_mut();
+        let room = ratchets.entry(*owner_vk).or_default();
+        let before = room.store.clone();
+        let out = f(&mut room.store);
+        (out, room.store != before)
+    });
+    if changed {
+        DIRTY_RATCHETS.with(|dirty| dirty.borrow_mut().insert(*owner_vk));
+        spawn_save();
+    }
+    out
+}
+
+/// Mirror the opened plaintext of these rooms, with their latest failures,
+/// into [`OPENED_RATCHET_DMS`].
+fn publish(updates: Vec<(VerifyingKey, HashMap<PurgeToken, String>)>) {
+    let updates: Vec<(VerifyingKey, OpenedRatchetDms)> = RATCHETS.with(|ratchets| {
+        let ratchets = ratchets.borrow();
+        updates
+            .into_iter()
+            .filter_map(|(owner_vk, failed)| {
+                let room = ratchets.get(&owner_vk)?;
+                let opened = room
+                    .store
+                    .opened_dms()
+                    .iter()
+                    .map(|o| (o.purge_token, o.plaintext.clone()))
+                    .collect();
+                Some((owner_vk, OpenedRatchetDms { opened, failed }))
+            })
+            .collect()
+    });
+    if updates.is_empty() {
+        return;
+    }
+    crate::util::defer(move || {
+        let changed = {
+            let current = OPENED_RATCHET_DMS.peek();
+            updates
+                .iter()
+                .any(|(owner_vk, room)| current.get(owner_vk) != Some(room))
+        };
+        if changed {
+            OPENED_RATCHET_DMS.with_mut(|all| all.extend(updates));
+        }
+    });
+}
+
+/// Read a room's stored store from the delegate, unless it has been already.
+async fn load_room_ratchet(owner_vk: VerifyingKey) -> Result<(), String> {
+    if RATCHETS.with(|r| r.borrow().get(&owner_vk).is_some_and(|room| room.loaded)) {
+        return Ok(());
+    }
+    let request = ChatDelegateRequestMsg::GetRequest {
+        key: ChatDelegateKey::new(ratchet_storage_key(&owner_vk)),
+    };
+    let result = match send_delegate_request(request).await {
+        Ok(ChatDelegateResponseMsg::GetResponse { value, .. }) => match value {
+            Some(bytes) => ciborium::de::from_reader::<RatchetStore, _>(&bytes[..])
+                // Do not save over a blob we could not read: the room stays
+                // unloaded and its ratcheted DMs unopened.
+                .map(Some)
+                .map_err(|e| format!("Failed to deserialize DM sessions: {}", e)),
+            None => Ok(None),
+        },
+        Ok(other) => Err(format!("Unexpected response: {:?}", other)),
+        Err(e) => Err(e),
+    };
+    let stored = match result {
+        Ok(stored) => stored,
+        Err(e) => {
+            RATCHETS.with(|r| {
+                if let Some(room) = r.borrow_mut().get_mut(&owner_vk) {
+                    room.load_requested = false;
+                }
+            });
+            return Err(e);
+        }
+    };
+    RATCHETS.with(|ratchets| {
+        let mut ratchets = ratchets.borrow_mut();
+        let room = ratchets.entry(owner_vk).or_default();
+        // A concurrent load may have finished first; nothing has touched an
+        // unloaded store, so either copy is the stored one.
+        if !room.loaded {
+            room.store = stored.unwrap_or_default();
+            room.loaded = true;
+            room.load_requested = true;
+            info!(
+                "Loaded DM sessions with {} opened message(s)",
+                room.store.opened_dms().len()
+            );
+        }
+    });
+    publish(vec![(owner_vk, HashMap::new())]);
+    // Open whatever arrived while the store was loading.
+    crate::util::defer(open_ratchet_dms);
+    Ok(())
+}
+
+fn spawn_save() {
+    crate::util::safe_spawn_local(async {
+        if let Err(e) = save_ratchets_to_delegate().await {
+            warn!("Failed to save DM sessions: {}", e);
+        }
+    });
+}
+
+/// Persist every changed store. Coalesced like the other delegate saves.
+pub async fn save_ratchets_to_delegate() -> Result<(), String> {
+    coalesce_save(&RATCHET_SAVE_STATE, "DM-ratchet", do_save_ratchets).await
+}
+
+async fn do_save_ratchets() -> Result<(), String> {
+    // Snapshot inside the save (see `coalesce_save`).
+    let blobs: Vec<(VerifyingKey, Vec<u8>)> = {
+        let dirty: Vec<VerifyingKey> =
+            DIRTY_RATCHETS.with(|dirty| dirty.borrow_mut().drain().collect());
+        RATCHETS.with(|ratchets| {
+            let ratchets = ratchets.borrow();
+            let mut blobs = Vec::new();
+            for owner_vk in dirty {
+                let Some(room) = ratchets.get(&owner_vk).filter(|room| room.loaded) else {
+                    continue;
+                };
+                let mut buffer = Vec::new();
+                ciborium::ser::into_writer(&room.store, &mut buffer)
+                    .map_err(|e| format!("Failed to serialize DM sessions: {}", e))?;
+                blobs.push((owner_vk, buffer));
+            }
+            Ok::<_, String>(blobs)
+        })?
+    };
+
+    for (owner_vk, value) in blobs {
+        let request = ChatDelegateRequestMsg::StoreRequest {
+            key: ChatDelegateKey::new(ratchet_storage_key(&owner_vk)),
+            value,
+        };
+        let result = match send_delegate_request(request).await {
+            Ok(ChatDelegateResponseMsg::StoreResponse { result, .. }) => result,
+            Ok(other) => Err(format!("Unexpected response: {:?}", other)),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            // Leave it dirty so the next change retries.
+            DIRTY_RATCHETS.with(|dirty| dirty.borrow_mut().insert(owner_vk));
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratchet_keys_are_distinct_from_other_delegate_keys() {
+        let vk = ed25519_dalek::SigningKey::from_bytes(&[7; 32]).verifying_key();
+        let key = ratchet_storage_key(&vk);
+        assert!(is_ratchet_storage_key(&key));
+        assert!(!super::super::message_archive::is_archive_storage_key(&key));
+        assert!(super::super::chat_delegate::parse_room_storage_key(&key).is_none());
+    }
+}
@@ -488,7 +488,15 @@ fn count_unread_dms(rooms: &crate::room_data::Rooms) -> usize {
         Ok(g) => g.clone(),
         Err(_) => return 0,
     };
-    count_unread_dms_with(&rooms.map, &last_seen, &hidden)
+    let inboxes = match crate::components::app::freenet_api::inbox_sync::INBOXES.try_read() {
+        Ok(g) => g.clone(),
+        Err(_) => return 0,
+    };
+    let blocked = match crate::components::app::block_list::BLOCKED_MEMBERS.try_read() {
+        Ok(g) => g.clone(),
+        Err(_) => return 0,
+    };
+    count_unread_dms_with(&rooms.map, &inboxes, &last_seen, &hidden, &blocked)
 }
 
 /// Pure core of [`count_unread_dms`], mirroring the DM rail's per-thread
@@ -499,14 +507,20 @@ fn count_unread_dms(rooms: &crate::room_data::Rooms) -> usize {
 /// `filter_rail_entries` applies. Revival is INBOUND-only, exactly like the
 /// rail's `last_inbound_ts` (issue freenet/river#526). Without this
 /// filter the unread tallies (title, hamburger badge) count messages the
-/// user has no visible thread for and no way to clear.
+/// user has no visible thread for and no way to clear. DMs from members
+/// in `blocked` are hidden from the rail, so they never count.
 fn count_unread_dms_with(
     map: &std::collections::HashMap<ed25519_dalek::VerifyingKey, crate::room_data::RoomData>,
+    inboxes: &std::collections::HashMap<
+        ed25519_dalek::VerifyingKey,
+        river_core::inbox::InboxStateV1,
+    >,
     last_seen: &std::collections::HashMap<(ed25519_dalek::VerifyingKey, MemberId), u64>,
     hidden: &std::collections::HashMap<
         (ed25519_dalek::VerifyingKey, MemberId),
         river_core::chat_delegate::HiddenDmThreadEntry,
     >,
+    blocked: &[river_core::chat_delegate::BlockedMemberEntry],
 ) -> usize {
     let mut total = 0usize;
     for (owner_key, room_data) in map {
@@ -522,27 +536,28 @@ fn count_unread_dms_with(
         }
         let mut per_peer: std::collections::HashMap<MemberId, Acc> =
             std::collections::HashMap::new();
-        for msg in &room_data.room_state.direct_messages.messages {
-            let is_self_sender = msg.message.sender == self_id;
-            let is_self_recipient = msg.message.recipient == self_id;
+        for (sender, recipient, timestamp) in crate::components::direct_messages::dm_triples(
+            room_data,
+            inboxes.get(owner_key),
+            None,
+            &river_core::chat_delegate::blocked_by(blocked, &owner_key.to_bytes(), self_id),
+        ) {
+            let is_self_sender = sender == self_id;
+            let is_self_recipient = recipient == self_id;
             if !is_self_sender && !is_self_recipient {
                 continue;
             }
-            let peer = if is_self_sender {
-                msg.message.recipient
-            } else {
-                msg.message.sender
-            };
+            let peer = if is_self_sender { recipient } else { sender };
             let acc = per_peer.entry(peer).or_insert(Acc {
                 last_inbound_ts: 0,
                 unread: 0,
             });
             if is_self_recipient {
-                if msg.message.timestamp > acc.last_inbound_ts {
-                    acc.last_inbound_ts = msg.message.timestamp;
+                if timestamp > acc.last_inbound_ts {
+                    acc.last_inbound_ts = timestamp;
                 }
                 let cutoff = last_seen.get(&(*owner_key, peer)).copied().unwrap_or(0);
-                if msg.message.timestamp > cutoff {
+                if timestamp > cutoff {
                     acc.unread += 1;
                 }
             }
@@ -714,6 +729,70 @@ pub fn mark_current_room_as_read() {
     update_document_title();
 }
 
+/// Mark the thread rooted at `root` in the current room as read up to its
+/// newest reply, by advancing the room's `thread_read` marker for it.
+///
+/// Markers for threads whose root is no longer displayed are dropped on the
+/// way, so the map stays bounded by the room's message history.
+pub fn mark_thread_as_read(root: &MessageId) {
+    let Some(owner_key) = CURRENT_ROOM.read().owner_key else {
+        return;
+    };
+
+    let newest = {
+        let Ok(rooms) = ROOMS.try_read() else {
+            return;
+        };
+        let Some(room_data) = rooms.map.get(&owner_key) else {
+            return;
+        };
+        let messages = &room_data.room_state.recent_messages;
+        let threads = messages.thread_index_with(|m| {
+            crate::components::conversation::extract_reply_target_id(
+                &m.message.content,
+                &room_data.secrets,
+            )
+        });
+        let replies: std::collections::HashSet<MessageId> =
+            threads.descendants(root).into_iter().collect();
+        let Some(newest) = messages
+            .display_messages()
+            .filter(|m| replies.contains(&m.id()))
+            .map(|m| m.message.time)
+            .max()
+        else {
+            return;
+        };
+        if room_data
+            .thread_read
+            .get(root)
+            .is_some_and(|seen| *seen >= newest)
+        {
+            return; // Already marked as read
+        }
+        newest
+    };
+
+    ROOMS.with_mut(|rooms| {
+        if let Some(room_data) = rooms.map.get_mut(&owner_key) {
+            let displayed: std::collections::HashSet<MessageId> = room_data
+                .room_state
+                .recent_messages
+                .display_messages()
+                .map(|m| m.id())
+                .collect();
+            room_data.thread_read.retain(|id, _| displayed.contains(id));
+            room_data.thread_read.insert(root.clone(), newest);
+        }
+    });
+
+    crate::util::safe_spawn_local(async {
+        if let Err(e) = save_rooms_to_delegate().await {
+            warn!("Failed to save rooms after marking thread as read: {}", e);
+        }
+    });
+}
+
 /// Mark every room as read up to its latest currently-known message.
 ///
 /// Called when the tab transitions from visible to hidden: the user had the
@@ -984,6 +1063,7 @@ mod tests {
             self_sk,
             contract_key,
             last_read_message_id,
+            thread_read: HashMap::new(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -1889,13 +1969,16 @@ mod tests {
 
         // No last-seen: both inbound messages count, outbound doesn't.
         assert_eq!(
-            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new()),
+            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new(), &HashMap::new(), &[]),
             2
         );
         // Seen up to ts=100: only the ts=200 inbound counts.
         let mut seen = HashMap::new();
         seen.insert((owner_vk, peer_id), 100u64);
-        assert_eq!(count_unread_dms_with(&map, &seen, &HashMap::new()), 1);
+        assert_eq!(
+            count_unread_dms_with(&map, &HashMap::new(), &seen, &HashMap::new(), &[]),
+            1
+        );
     }
 
     #[test]
@@ -1925,7 +2008,10 @@ mod tests {
             },
         );
         // Hidden at the newest message's ts (<=): thread invisible → 0.
-        assert_eq!(count_unread_dms_with(&map, &HashMap::new(), &hidden), 0);
+        assert_eq!(
+            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new(), &hidden, &[]),
+            0
+        );
 
         // A strictly newer inbound message revives the thread: both its
         // unread messages count again (matching the rail badge).
@@ -1935,7 +2021,10 @@ mod tests {
             .direct_messages
             .messages
             .push(dm(peer_id, self_id, 150, &peer_sk));
-        assert_eq!(count_unread_dms_with(&map, &HashMap::new(), &hidden), 2);
+        assert_eq!(
+            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new(), &hidden, &[]),
+            2
+        );
     }
 
     #[test]
@@ -1978,7 +2067,7 @@ mod tests {
         // last_inbound_ts = 90 <= hidden_at 90 -> still archived. The
         // outbound at 150 does NOT drag it back into the tally.
         assert_eq!(
-            count_unread_dms_with(&map, &HashMap::new(), &hidden),
+            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new(), &hidden, &[]),
             0,
             "an outbound reply must not revive the thread via the timestamp \
              filter (#526) - revival comes from the explicit unhide"
@@ -1987,7 +2076,7 @@ mod tests {
         // What `do_send` actually does: drop the entry. Now it counts.
         hidden.remove(&(owner_vk, peer_id));
         assert_eq!(
-            count_unread_dms_with(&map, &HashMap::new(), &hidden),
+            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new(), &hidden, &[]),
             1,
             "the explicit unhide on outbound send is what revives the thread"
         );
@@ -2021,7 +2110,10 @@ mod tests {
             },
         );
         // last_inbound_ts = 150 > 90 -> revived, and BOTH inbound DMs count.
-        assert_eq!(count_unread_dms_with(&map, &HashMap::new(), &hidden), 2);
+        assert_eq!(
+            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new(), &hidden, &[]),
+            2
+        );
     }
 
     #[test]
@@ -2041,7 +2133,7 @@ mod tests {
         map.insert(owner_vk, rd);
 
         assert_eq!(
-            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new()),
+            count_unread_dms_with(&map, &HashMap::new(), &HashMap::new(), &HashMap::new(), &[]),
             0
         );
     }
@@ -4,11 +4,13 @@
 //! and processes state updates.
 
 pub mod backward_probe;
+pub mod blob_fetch;
 pub mod connection_manager;
 pub mod connection_watchdog;
 pub mod constants;
 pub mod error;
 pub mod freenet_synchronizer;
+pub mod inbox_sync;
 pub mod response_handler;
 pub mod room_synchronizer;
 
@@ -0,0 +1,86 @@
+//! Fetching attachment blobs (`CONTENT_TYPE_BLOB`) for preview.
+//!
+//! An attachment's bytes live in their own blob contract, named by the
+//! `contract_id` in its `BlobContentV1` (see `river_core::blob`). The
+//! conversation view calls [`request_blob`] when it renders one; the GET
+//! response is routed here by `handle_get_response` (the same way
+//! backward-probe responses are, since a blob id is in neither `SYNC_INFO`
+//! nor `ROOMS`) and lands in [`BLOB_CACHE`]. Blobs are immutable, so a
+//! fetched entry never needs refreshing.
+
+use crate::components::app::WEB_API;
+use dioxus::logger::tracing::{info, warn};
+use dioxus::prelude::*;
+use freenet_stdlib::client_api::{ClientRequest, ContractRequest};
+use freenet_stdlib::prelude::ContractInstanceId;
+use std::collections::HashMap;
+
+/// Where a blob fetch stands.
+#[derive(Clone, PartialEq, Debug)]
+pub enum BlobFetch {
+    Pending,
+    /// The stored bytes, not yet checked against the hash or decrypted —
+    /// that needs the message's `BlobContentV1` and room secrets.
+    Fetched(Vec<u8>),
+    Failed(String),
+}
+
+/// Stored blob bytes by contract id.
+pub static BLOB_CACHE: GlobalSignal<HashMap<ContractInstanceId, BlobFetch>> =
+    Global::new(HashMap::new);
+
+/// Whether `instance_id` is a blob we are waiting for. Used by
+/// `handle_get_response` to route blob responses.
+pub fn is_blob_instance(instance_id: &ContractInstanceId) -> bool {
+    matches!(BLOB_CACHE.peek().get(instance_id), Some(BlobFetch::Pending))
+}
+
+/// Record the GET response for a blob.
+pub fn deliver_blob_response(instance_id: ContractInstanceId, state: Vec<u8>) {
+    let entry = if state.is_empty() {
+        BlobFetch::Failed("Attachment is not available on the network".to_string())
+    } else {
+        BlobFetch::Fetched(state)
+    };
+    BLOB_CACHE.write().insert(instance_id, entry);
+}
+
+/// Start fetching `contract_id` unless it is already fetched or in flight.
+/// A failed fetch is retried on the next call.
+///
+/// Deferred like every other signal write from a render path (see AGENTS.md
+/// "Dioxus WASM Signal Safety Rules").
+pub fn request_blob(contract_id: [u8; 32]) {
+    let instance_id = ContractInstanceId::new(contract_id);
+    if matches!(
+        BLOB_CACHE.peek().get(&instance_id),
+        Some(BlobFetch::Pending | BlobFetch::Fetched(_))
+    ) {
+        return;
+    }
+    crate::util::defer(move || {
+        BLOB_CACHE.write().insert(instance_id, BlobFetch::Pending);
+        let get_request = ContractRequest::Get {
+            key: instance_id,
+            return_contract_code: false,
+            subscribe: false,
+            blocking_subscribe: false,
+        };
+        wasm_bindgen_futures::spawn_local(async move {
+            let sent = match WEB_API.write().as_mut() {
+                Some(web_api) => web_api
+                    .send(ClientRequest::ContractOp(get_request))
+                    .await
+                    .map_err(|e| e.to_string()),
+                None => Err("Not connected to Freenet".to_string()),
+            };
+            match sent {
+                Ok(()) => info!("Requested attachment blob {}", instance_id),
+                Err(e) => {
+                    warn!("Failed to request attachment blob {}: {}", instance_id, e);
+                    BLOB_CACHE.write().insert(instance_id, BlobFetch::Failed(e));
+                }
+            }
+        });
+    });
+}
@@ -0,0 +1,709 @@
+//! The local member's DM inboxes (`river_core::inbox`), one per room.
+//!
+//! DMs go to the recipient's inbox contract rather than into the room state.
+//! For every room whose state we fetch, [`request_inbox`] GETs and subscribes
+//! to our own inbox in it, and what arrives is kept in [`INBOXES`]. Responses
+//! for inbox contracts are routed here by the response handlers before their
+//! room lookups, the same way blob and backward-probe responses are, since an
+//! inbox id is in neither `SYNC_INFO` nor `ROOMS`. A DM to someone else is a
+//! fire-and-forget PUT into their inbox ([`send_to_inbox`]); the sender's own
+//! view of it comes from the outbound-DM cache. A group DM is PUT into every
+//! participant's inbox, ours included ([`send_to_group_inboxes`]), so it
+//! needs no cache.
+//!
+//! DMs to us that older clients left in the room state are moved over by
+//! [`migrate_room_dms`]: PUT into the inbox, then purged from the room once a
+//! read of the inbox shows them held, so a failed PUT never loses one.
+//!
+//! Receipts (`river_core::dm_receipts`) for a peer's DMs go into the peer's
+//! inbox ([`send_receipts`]): delivered when ours is read, read when their
+//! thread is open. The first time in a session we read the peer's inbox for
+//! the envelope it already holds from us, so ours continues its version.
+//!
+//! DMs from members we have blocked (`block_list`) are purged from the inbox
+//! as soon as it is read or the block is made ([`purge_blocked_senders`]),
+//! folded into the same signed purge envelope a manual delete uses.
+
+use crate::components::app::block_list::blocked_in_room;
+use crate::components::app::chat_delegate::unhide_dm_thread_if_dm_is_newer;
+use crate::components::app::dm_ratchet;
+use crate::components::app::{mark_needs_sync, ROOMS, WEB_API};
+use crate::components::direct_messages::seed_dm_last_seen_from_inbox;
+use crate::constants::INBOX_CONTRACT_WASM;
+use crate::room_data::RoomData;
+use dioxus::logger::tracing::{error, info, warn};
+use dioxus::prelude::*;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use freenet_scaffold::ComposableState;
+use freenet_stdlib::client_api::{ClientRequest, ContractRequest};
+use freenet_stdlib::prelude::{
+    ContractCode, ContractContainer, ContractInstanceId, ContractKey, ContractWasmAPIVersion,
+    Parameters, WrappedContract, WrappedState,
+};
+use river_core::dm_receipts::{advance_dm_receipts, DmReceipts};
+use river_core::group_dm::AuthorizedGroupDirectMessage;
+use river_core::inbox::{
+    messages_from_room, InboxGroupMessageV1, InboxMessageV1, InboxParametersV1, InboxReceiptsV1,
+    InboxStateV1,
+};
+use river_core::room_state::direct_messages::{
+    advance_recipient_purges, AuthorizedDirectMessage, DirectMessagesDelta, PurgeToken,
+};
+use river_core::room_state::member::{AuthorizedMember, MemberId};
+use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1Delta};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+
+/// Our own inbox in each loaded room, by room owner key, as last read from
+/// the network. Not persisted: re-read whenever the room is.
+pub static INBOXES: GlobalSignal<HashMap<VerifyingKey, InboxStateV1>> = Global::new(HashMap::new);
+
+/// Our own inbox contract ids, to `(room owner, our member key)`. Plain
+/// `Mutex` bookkeeping with no UI reactivity, like `backward_probe`'s routes.
+static OWN_INBOXES: LazyLock<Mutex<HashMap<ContractInstanceId, (VerifyingKey, VerifyingKey)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Inbox contracts with a PUT in flight, ours or other members'.
+static INBOX_PUTS: LazyLock<Mutex<HashSet<ContractInstanceId>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Receipt bookkeeping for one peer's inbox, by its contract id.
+struct ReceiptPeer {
+    room: VerifyingKey,
+    peer_vk: VerifyingKey,
+    self_id: MemberId,
+    /// What the peer's inbox holds from us; `None` until it has been read.
+    held: Option<Option<DmReceipts>>,
+    /// A read of the peer's inbox is in flight.
+    reading: bool,
+    /// The marks we want the peer to see: `(delivered, read)`.
+    wanted: (u64, u64),
+}
+
+static RECEIPT_PEERS: LazyLock<Mutex<HashMap<ContractInstanceId, ReceiptPeer>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn receipt_peers() -> MutexGuard<'static, HashMap<ContractInstanceId, ReceiptPeer>> {
+    RECEIPT_PEERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn own_inboxes() -> MutexGuard<'static, HashMap<ContractInstanceId, (VerifyingKey, VerifyingKey)>> {
+    OWN_INBOXES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn inbox_puts() -> MutexGuard<'static, HashSet<ContractInstanceId>> {
+    INBOX_PUTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The contract key of the inbox named by `params`.
+pub fn inbox_contract_key(params: &InboxParametersV1) -> ContractKey {
+    let contract_code = ContractCode::from(INBOX_CONTRACT_WASM);
+    ContractKey::from_params_and_code(Parameters::from(params.to_bytes()), &contract_code)
+}
+
+/// Whether `instance_id` is an inbox contract we are reading or writing.
+/// Used by the response handlers to route inbox responses here.
+pub fn is_inbox_instance(instance_id: &ContractInstanceId) -> bool {
+    own_inboxes().contains_key(instance_id)
+        || inbox_puts().contains(instance_id)
+        || receipt_peers().contains_key(instance_id)
+}
+
+/// GET and subscribe to our own inbox in `room`. Called whenever the room's
+/// own state is fetched, which also covers resubscribing after a reconnect.
+pub fn request_inbox(room: VerifyingKey, self_vk: VerifyingKey) {
+    let params = InboxParametersV1 {
+        room_owner: room,
+        recipient: self_vk,
+    };
+    let instance_id = *inbox_contract_key(&params).id();
+    own_inboxes().insert(instance_id, (room, self_vk));
+    send_request(
+        ContractRequest::Get {
+            key: instance_id,
+            return_contract_code: false,
+            subscribe: true,
+            blocking_subscribe: false,
+        },
+        instance_id,
+    );
+}
+
+/// PUT `update` into the inbox named by `params`. The contract merges it into
+/// whatever the inbox holds, so the first DM to a member creates their inbox.
+fn put_inbox(params: InboxParametersV1, update: InboxStateV1) {
+    let contract_key = inbox_contract_key(&params);
+    let contract = ContractContainer::from(ContractWasmAPIVersion::V1(WrappedContract::new(
+        Arc::new(ContractCode::from(INBOX_CONTRACT_WASM)),
+        Parameters::from(params.to_bytes()),
+    )));
+    inbox_puts().insert(*contract_key.id());
+    send_request(
+        ContractRequest::Put {
+            contract,
+            state: WrappedState::new(update.to_bytes()),
+            related_contracts: Default::default(),
+            subscribe: false,
+            blocking_subscribe: false,
+        },
+        *contract_key.id(),
+    );
+}
+
+/// Deferred like every other signal access from a handler (see AGENTS.md
+/// "Dioxus WASM Signal Safety Rules").
+fn send_request(request: ContractRequest<'static>, instance_id: ContractInstanceId) {
+    crate::util::defer(move || {
+        wasm_bindgen_futures::spawn_local(async move {
+            let sent = match WEB_API.write().as_mut() {
+                Some(web_api) => web_api
+                    .send(ClientRequest::ContractOp(request))
+                    .await
+                    .map_err(|e| e.to_string()),
+                None => Err("Not connected to Freenet".to_string()),
+            };
+            if let Err(e) = sent {
+                warn!("Failed to send inbox request for {}: {}", instance_id, e);
+                inbox_puts().remove(&instance_id);
+                if let Some(peer) = receipt_peers().get_mut(&instance_id) {
+                    peer.reading = false;
+                }
+            }
+        });
+    });
+}
+
+/// Handle a GET response or update notification for an inbox. Both carry an
+/// [`InboxStateV1`] (a delta is a partial state), merged into [`INBOXES`].
+pub fn deliver_inbox_state(instance_id: ContractInstanceId, bytes: &[u8]) {
+    let Some((room, self_vk)) = own_inboxes().get(&instance_id).copied() else {
+        deliver_receipt_peer_inbox(instance_id, bytes);
+        return;
+    };
+    let incoming = match InboxStateV1::from_bytes(bytes) {
+        Ok(incoming) => incoming,
+        Err(e) => {
+            warn!(
+                "Ignoring undecodable inbox state for {}: {}",
+                instance_id, e
+            );
+            return;
+        }
+    };
+    crate::util::defer(move || merge_inbox(room, self_vk, incoming));
+}
+
+/// Handle a GET for one of our inboxes that nobody has written to yet.
+pub fn deliver_inbox_not_found(instance_id: ContractInstanceId) {
+    if let Some((room, self_vk)) = own_inboxes().get(&instance_id).copied() {
+        crate::util::defer(move || merge_inbox(room, self_vk, InboxStateV1::default()));
+    } else {
+        deliver_receipt_peer_inbox(instance_id, &[]);
+    }
+}
+
+/// Handle the response to an inbox PUT. When it was our own inbox (a
+/// migration), re-read it so the moved DMs can be purged from the room.
+pub fn deliver_inbox_put(instance_id: ContractInstanceId) {
+    inbox_puts().remove(&instance_id);
+    if let Some((room, self_vk)) = own_inboxes().get(&instance_id).copied() {
+        request_inbox(room, self_vk);
+    }
+}
+
+fn merge_inbox(room: VerifyingKey, self_vk: VerifyingKey, incoming: InboxStateV1) {
+    let params = InboxParametersV1 {
+        room_owner: room,
+        recipient: self_vk,
+    };
+    let Ok(inboxes) = INBOXES.try_read() else {
+        // Retried by the next notification or room read.
+        return;
+    };
+    let first_read = !inboxes.contains_key(&room);
+    let mut inbox = inboxes.get(&room).cloned().unwrap_or_default();
+    drop(inboxes);
+
+    let held: HashSet<PurgeToken> = inbox
+        .messages
+        .iter()
+        .map(|m| m.message.purge_token())
+        .collect();
+    if let Err(e) = inbox.merge(&params, incoming) {
+        warn!(
+            "Rejected inbox update for room {:?}: {}",
+            MemberId::from(room),
+            e
+        );
+        return;
+    }
+
+    // Nothing from a member we have blocked is shown, counted or receipted.
+    if let Some(self_sk) = ROOMS
+        .try_read()
+        .ok()
+        .and_then(|r| r.map.get(&room).map(|rd| rd.self_sk.clone()))
+    {
+        purge_blocked_in(room, &self_sk, &mut inbox);
+    }
+
+    // What the first read of the session finds was there before this page
+    // load: seed it as seen. After that, revive a hidden thread whose peer
+    // just wrote (issue freenet/river#267), gated on the archive cutoff
+    // like the room-state path (#526).
+    if first_read {
+        seed_dm_last_seen_from_inbox(room, &inbox);
+    }
+    let mut newly_landed: HashMap<MemberId, u64> = HashMap::new();
+    for m in &inbox.messages {
+        if !first_read && !held.contains(&m.message.purge_token()) {
+            let ts = newly_landed.entry(m.message.message.sender).or_insert(0);
+            *ts = (*ts).max(m.message.message.timestamp);
+        }
+    }
+
+    let mut delivered: HashMap<MemberId, u64> = HashMap::new();
+    for m in &inbox.messages {
+        let ts = delivered.entry(m.message.message.sender).or_insert(0);
+        *ts = (*ts).max(m.message.message.timestamp);
+    }
+
+    INBOXES.write().insert(room, inbox);
+    for (sender, ts) in delivered {
+        send_receipts(room, sender, ts, 0);
+    }
+    for (sender, ts) in newly_landed {
+        unhide_dm_thread_if_dm_is_newer(room, sender, ts);
+    }
+    crate::components::app::document_title::update_document_title();
+    migrate_room_dms(room);
+}
+
+/// Move the DMs to us still held in `room`'s state into our inbox: PUT the
+/// ones the inbox lacks, and purge from the room the ones it already holds.
+fn migrate_room_dms(room: VerifyingKey) {
+    let Some(room_data) = ROOMS
+        .try_read()
+        .ok()
+        .and_then(|r| r.map.get(&room).cloned())
+    else {
+        return;
+    };
+    let Some(inbox) = INBOXES.try_read().ok().and_then(|i| i.get(&room).cloned()) else {
+        return;
+    };
+    let self_id = MemberId::from(&room_data.self_sk.verifying_key());
+    let room_params = ChatRoomParametersV1 { owner: room };
+    let legacy = messages_from_room(&room_data.room_state, &room_params, self_id);
+    if legacy.is_empty() {
+        return;
+    }
+
+    let held: HashSet<PurgeToken> = inbox
+        .messages
+        .iter()
+        .map(|m| m.message.purge_token())
+        .collect();
+    // A blocked member's DMs are purged from the room without being moved.
+    let blocked = blocked_in_room(room, self_id).unwrap_or_default();
+    let (moved, missing): (Vec<InboxMessageV1>, Vec<InboxMessageV1>) =
+        legacy.into_iter().partition(|m| {
+            held.contains(&m.message.purge_token()) || blocked.contains(&m.message.message.sender)
+        });
+
+    let params = InboxParametersV1 {
+        room_owner: room,
+        recipient: room_data.self_sk.verifying_key(),
+    };
+    if !missing.is_empty() && !inbox_puts().contains(inbox_contract_key(&params).id()) {
+        info!("Moving {} room DMs into our inbox", missing.len());
+        put_inbox(
+            params,
+            InboxStateV1 {
+                messages: missing,
+                ..Default::default()
+            },
+        );
+    }
+
+    if moved.is_empty() {
+        return;
+    }
+    let previous = room_data
+        .room_state
+        .direct_messages
+        .purges
+        .iter()
+        .find(|p| p.recipient_id == self_id);
+    let tokens = moved.iter().map(|m| m.message.purge_token());
+    let envelope = match advance_recipient_purges(&room_data.self_sk, &room, previous, tokens) {
+        Ok(envelope) => envelope,
+        Err(e) => {
+            warn!("Failed to purge moved DMs from the room: {}", e);
+            return;
+        }
+    };
+    let delta = ChatRoomStateV1Delta {
+        direct_messages: Some(DirectMessagesDelta {
+            new_messages: vec![],
+            advanced_purges: vec![envelope],
+        }),
+        ..Default::default()
+    };
+    let applied = ROOMS.with_mut(|rooms| {
+        let Some(rd) = rooms.map.get_mut(&room) else {
+            return false;
+        };
+        let parent = rd.room_state.clone();
+        if let Err(e) = rd
+            .room_state
+            .apply_delta(&parent, &room_params, &Some(delta))
+        {
+            error!("Purging moved DMs from the room failed: {:?}", e);
+            return false;
+        }
+        // #310: keep private edits/reactions across the apply_delta.
+        rd.rebuild_private_actions_state();
+        true
+    });
+    if applied {
+        mark_needs_sync(room);
+    }
+}
+
+/// The local member's proof of membership for a DM: nothing for the owner,
+/// otherwise their invite chain from the room state, or the one stored at
+/// join time if they have been pruned. `None` when neither is available.
+pub fn own_sender_chain(room_data: &RoomData) -> Option<Vec<AuthorizedMember>> {
+    let self_vk = room_data.self_sk.verifying_key();
+    if self_vk == room_data.owner_vk {
+        return Some(Vec::new());
+    }
+    let params = ChatRoomParametersV1 {
+        owner: room_data.owner_vk,
+    };
+    match room_data
+        .room_state
+        .members
+        .members
+        .iter()
+        .find(|m| m.member.member_vk == self_vk)
+    {
+        Some(member) => {
+            river_core::inbox::sender_chain(&room_data.room_state, &params, member).ok()
+        }
+        None => room_data.self_authorized_member.as_ref().map(|member| {
+            std::iter::once(member.clone())
+                .chain(room_data.invite_chain.iter().cloned())
+                .collect()
+        }),
+    }
+}
+
+/// PUT `message` into `recipient`'s inbox in `room`, with `sender_chain`
+/// (from [`own_sender_chain`]) as the sender's proof of membership. Checked
+/// first exactly as the inbox contract will, so a DM it would reject fails
+/// here with a reason.
+pub fn send_to_inbox(
+    room: VerifyingKey,
+    recipient: VerifyingKey,
+    sender_chain: Vec<AuthorizedMember>,
+    message: AuthorizedDirectMessage,
+) -> Result<(), String> {
+    let params = InboxParametersV1 {
+        room_owner: room,
+        recipient,
+    };
+    let message = InboxMessageV1 {
+        sender_chain,
+        message,
+    };
+    message.verify(&params)?;
+    put_inbox(
+        params,
+        InboxStateV1 {
+            messages: vec![message],
+            ..Default::default()
+        },
+    );
+    Ok(())
+}
+
+/// PUT the group DM `message` into the inbox of each of `participants` (our
+/// own member key among them) in `room`, and merge it into our local copy
+/// of ours so it shows at once. Checked against every inbox before anything
+/// is sent, so one refusal sends nothing.
+pub fn send_to_group_inboxes(
+    room: VerifyingKey,
+    participants: &[VerifyingKey],
+    sender_chain: Vec<AuthorizedMember>,
+    message: AuthorizedGroupDirectMessage,
+) -> Result<(), String> {
+    let message = InboxGroupMessageV1 {
+        sender_chain,
+        message,
+    };
+    let inboxes: Vec<InboxParametersV1> = participants
+        .iter()
+        .map(|recipient| InboxParametersV1 {
+            room_owner: room,
+            recipient: *recipient,
+        })
+        .collect();
+    for params in &inboxes {
+        message.verify(params)?;
+    }
+    let sender = message.message.message.sender;
+    let update = InboxStateV1 {
+        group_messages: vec![message],
+        ..Default::default()
+    };
+    if let Some(own) = inboxes.iter().find(|p| p.recipient_id() == sender) {
+        let own = *own;
+        let local = update.clone();
+        crate::util::defer(move || {
+            INBOXES.with_mut(|inboxes| {
+                if let Err(e) = inboxes.entry(room).or_default().merge(&own, local) {
+                    warn!("Merging our group DM locally failed: {}", e);
+                }
+            });
+        });
+    }
+    for params in inboxes {
+        put_inbox(params, update.clone());
+    }
+    Ok(())
+}
+
+/// Tell `peer` in `room` we have their DMs up to `delivered` and have read
+/// them up to `read` (timestamps; 0 for no news), by PUTting a receipt
+/// envelope into their inbox. Nothing is sent until their inbox has been
+/// read once for the envelope it holds from us, nor when neither mark moves
+/// past it. Call from a deferred context: it reads `ROOMS`.
+pub fn send_receipts(room: VerifyingKey, peer: MemberId, delivered: u64, read: u64) {
+    let Some(room_data) = ROOMS
+        .try_read()
+        .ok()
+        .and_then(|r| r.map.get(&room).cloned())
+    else {
+        return;
+    };
+    let peer_vk = if peer == MemberId::from(&room) {
+        room
+    } else {
+        match room_data
+            .room_state
+            .members
+            .members
+            .iter()
+            .find(|m| m.member.id() == peer)
+        {
+            Some(member) => member.member.member_vk,
+            None => return,
+        }
+    };
+    let params = InboxParametersV1 {
+        room_owner: room,
+        recipient: peer_vk,
+    };
+    let instance_id = *inbox_contract_key(&params).id();
+    let read_first = {
+        let mut peers = receipt_peers();
+        let entry = peers.entry(instance_id).or_insert(ReceiptPeer {
+            room,
+            peer_vk,
+            self_id: MemberId::from(&room_data.self_sk.verifying_key()),
+            held: None,
+            reading: false,
+            wanted: (0, 0),
+        });
+        entry.wanted = (entry.wanted.0.max(delivered), entry.wanted.1.max(read));
+        let read_first = entry.held.is_none() && !entry.reading;
+        if read_first {
+            entry.reading = true;
+        }
+        read_first
+    };
+    if read_first {
+        send_request(
+            ContractRequest::Get {
+                key: instance_id,
+                return_contract_code: false,
+                subscribe: false,
+                blocking_subscribe: false,
+            },
+            instance_id,
+        );
+    } else {
+        flush_receipts(instance_id, &room_data);
+    }
+}
+
+/// Handle the read of a peer's inbox that [`send_receipts`] asked for.
+fn deliver_receipt_peer_inbox(instance_id: ContractInstanceId, bytes: &[u8]) {
+    let room = {
+        let mut peers = receipt_peers();
+        let Some(peer) = peers.get_mut(&instance_id) else {
+            return;
+        };
+        let inbox = match InboxStateV1::from_bytes(bytes) {
+            Ok(inbox) => inbox,
+            Err(e) => {
+                warn!("Ignoring undecodable peer inbox {}: {}", instance_id, e);
+                peer.reading = false;
+                return;
+            }
+        };
+        peer.reading = false;
+        peer.held = Some(inbox.receipts_from(peer.self_id).map(|r| r.state.clone()));
+        peer.room
+    };
+    crate::util::defer(move || {
+        let Some(room_data) = ROOMS
+            .try_read()
+            .ok()
+            .and_then(|r| r.map.get(&room).cloned())
+        else {
+            return;
+        };
+        flush_receipts(instance_id, &room_data);
+    });
+}
+
+/// Sign and PUT the receipts wanted for the peer at `instance_id`, if they
+/// move past what their inbox holds from us.
+fn flush_receipts(instance_id: ContractInstanceId, room_data: &RoomData) {
+    let (peer_vk, previous, (delivered, read)) = {
+        let peers = receipt_peers();
+        let Some(ReceiptPeer {
+            peer_vk,
+            held: Some(previous),
+            wanted,
+            ..
+        }) = peers.get(&instance_id)
+        else {
+            return;
+        };
+        (*peer_vk, previous.clone(), *wanted)
+    };
+    let Some(reader_chain) = own_sender_chain(room_data) else {
+        return;
+    };
+    let room = room_data.owner_vk;
+    let receipts = match advance_dm_receipts(
+        &room_data.self_sk,
+        MemberId::from(&peer_vk),
+        &room,
+        previous.as_ref(),
+        delivered,
+        read,
+    ) {
+        Ok(Some(receipts)) => receipts,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to build DM receipts: {}", e);
+            return;
+        }
+    };
+    if let Some(peer) = receipt_peers().get_mut(&instance_id) {
+        peer.held = Some(Some(receipts.state.clone()));
+    }
+    put_inbox(
+        InboxParametersV1 {
+            room_owner: room,
+            recipient: peer_vk,
+        },
+        InboxStateV1 {
+            receipts: vec![InboxReceiptsV1 {
+                reader_chain,
+                receipts,
+            }],
+            ..Default::default()
+        },
+    );
+}
+
+/// Purge from `inbox`, in place, every DM from a member we have blocked in
+/// `room`: advance its purge envelope (compacting it once full), apply it
+/// and PUT it, and drop the plaintext of any that were ratcheted. Returns
+/// whether anything was purged; DMs it could not purge stay hidden by the
+/// DM views.
+fn purge_blocked_in(room: VerifyingKey, self_sk: &SigningKey, inbox: &mut InboxStateV1) -> bool {
+    let Some(blocked) = blocked_in_room(room, MemberId::from(&self_sk.verifying_key())) else {
+        return false;
+    };
+    let tokens = inbox.tokens_from(&blocked);
+    if tokens.is_empty() {
+        return false;
+    }
+    let params = InboxParametersV1 {
+        room_owner: room,
+        recipient: self_sk.verifying_key(),
+    };
+    let envelope = match inbox.advance_purges(self_sk, &room, tokens.iter().copied()) {
+        Ok(envelope) => envelope,
+        Err(e) => {
+            warn!("Failed to purge DMs from blocked members: {}", e);
+            return false;
+        }
+    };
+    let update = InboxStateV1 {
+        purges: Some(envelope),
+        ..Default::default()
+    };
+    if let Err(e) = inbox.merge(&params, update.clone()) {
+        warn!("Failed to purge DMs from blocked members: {}", e);
+        return false;
+    }
+    info!("Purging {} DMs from blocked members", tokens.len());
+    dm_ratchet::forget(room, &tokens);
+    put_inbox(params, update);
+    true
+}
+
+/// Run [`purge_blocked_in`] on our held inbox in `room`, for a block just
+/// made or loaded. A no-op until the inbox has been read; the first read
+/// purges then.
+pub fn purge_blocked_senders(room: VerifyingKey) {
+    let Some(self_sk) = ROOMS
+        .try_read()
+        .ok()
+        .and_then(|r| r.map.get(&room).map(|rd| rd.self_sk.clone()))
+    else {
+        return;
+    };
+    let Some(mut inbox) = INBOXES.try_read().ok().and_then(|i| i.get(&room).cloned()) else {
+        return;
+    };
+    if purge_blocked_in(room, &self_sk, &mut inbox) {
+        crate::util::defer(move || {
+            INBOXES.write().insert(room, inbox);
+        });
+    }
+}
+
+/// Purge `tokens` from our inbox in `room`: advance its purge envelope
+/// (compacting it once full), apply it locally and PUT it.
+pub fn purge_from_inbox(
+    room: VerifyingKey,
+    self_sk: &SigningKey,
+    inbox: &InboxStateV1,
+    tokens: Vec<PurgeToken>,
+) -> Result<(), String> {
+    let params = InboxParametersV1 {
+        room_owner: room,
+        recipient: self_sk.verifying_key(),
+    };
+    let envelope = inbox.advance_purges(self_sk, &room, tokens)?;
+    let update = InboxStateV1 {
+        purges: Some(envelope),
+        ..Default::default()
+    };
+    let mut purged = inbox.clone();
+    purged.merge(&params, update.clone())?;
+    crate::util::defer(move || {
+        INBOXES.write().insert(room, purged);
+    });
+    put_inbox(params, update);
+    Ok(())
+}
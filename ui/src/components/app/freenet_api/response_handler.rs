@@ -9,6 +9,7 @@ mod update_notification;
 mod update_response;
 
 use super::error::SynchronizerError;
+use super::inbox_sync;
 use super::room_synchronizer::RoomSynchronizer;
 use crate::components::app::chat_delegate::{
     arm_legacy_migration_recovery, await_delegate_response, cas_store_correlation_key,
@@ -19,8 +20,8 @@ use crate::components::app::chat_delegate::{
     hydrate_hidden_dm_threads, hydrate_outbound_dms_cache, is_legacy_delegate_key,
     is_legacy_migration_in_progress, legacy_scoped_correlation, load_state_after_probe_legacy,
     mark_legacy_migration_done, mark_legacy_migration_in_progress, parse_room_storage_key,
-    per_room_terminal, prune_outbound_dms_for_purges, request_legacy_seal_on_quiescence,
-    room_storage_key, save_outbound_dms_to_delegate, save_rooms_to_delegate, send_delegate_request,
+    per_room_terminal, request_legacy_seal_on_quiescence, room_storage_key,
+    save_outbound_dms_to_delegate, save_rooms_to_delegate, send_delegate_request,
     send_delegate_request_to, set_load_state_if_current, source_rank_for_delegate_key,
     LegacyMigrationAction, LoadWorkerGuard, PendingDelegateRequest, RoomsLoadState,
     OUTBOUND_DMS_STORAGE_KEY, ROOMS_META_KEY, ROOMS_STORAGE_KEY,
@@ -114,6 +115,16 @@ impl ResponseHandler {
                 ContractResponse::UpdateResponse { key, summary } => {
                     handle_update_response(key, summary.to_vec());
                 }
+                ContractResponse::SubscribeResponse { key, .. }
+                    if inbox_sync::is_inbox_instance(key.id()) =>
+                {
+                    // Inbox subscriptions ride on their GET; nothing to re-PUT.
+                }
+                ContractResponse::NotFound { instance_id }
+                    if inbox_sync::is_inbox_instance(&instance_id) =>
+                {
+                    inbox_sync::deliver_inbox_not_found(instance_id);
+                }
                 ContractResponse::SubscribeResponse { key, subscribed } => {
                     flags.needs_reput = handle_subscribe_response(key, subscribed);
                     if subscribed {
@@ -456,9 +467,16 @@ impl ResponseHandler {
                                             );
                                         } else if key.as_bytes() == ROOMS_META_KEY
                                             || parse_room_storage_key(key.as_bytes()).is_some()
+                                            || crate::components::app::message_archive::is_archive_storage_key(
+                                                key.as_bytes(),
+                                            )
+                                            || crate::components::app::dm_ratchet::is_ratchet_storage_key(
+                                                key.as_bytes(),
+                                            )
                                         {
                                             // Per-room load responses (room:<vk> /
-                                            // rooms_meta) are consumed by the awaiting
+                                            // rooms_meta / message_archive:<vk> /
+                                            // dm_ratchet:<vk>) are consumed by the awaiting
                                             // orchestration task (load_rooms_per_room)
                                             // via the pending-request registry. The
                                             // processing match still runs for every
@@ -1845,18 +1863,21 @@ fn handle_outbound_dms_get_response(value: Option<Vec<u8>>, is_legacy_delegate:
     match from_reader::<OutboundDmStore, _>(&bytes[..]) {
         Ok(store) => {
             let hidden_count = hydrate_hidden_dm_threads(store.hidden_threads);
+            let blocked_count =
+                crate::components::app::block_list::hydrate_blocked_members(store.blocked_members);
             let count = hydrate_outbound_dms_cache(store.entries);
             info!(
-                "Hydrated {} outbound-DM entries and {} hidden DM thread entries from {} delegate",
+                "Hydrated {} outbound-DM entries, {} hidden DM thread entries and {} blocks from {} delegate",
                 count,
                 hidden_count,
+                blocked_count,
                 if is_legacy_delegate {
                     "legacy"
                 } else {
                     "current"
                 }
             );
-            if is_legacy_delegate && (count > 0 || hidden_count > 0) {
+            if is_legacy_delegate && (count > 0 || hidden_count > 0 || blocked_count > 0) {
                 // Persist the merged cache under the current delegate
                 // key so subsequent loads find the data without
                 // re-hitting the legacy delegate.
@@ -1866,26 +1887,6 @@ fn handle_outbound_dms_get_response(value: Option<Vec<u8>>, is_legacy_delegate:
                     }
                 });
             }
-
-            // Codex P2 fix on PR #259 re-review: when this response
-            // (outbound_dms) arrives AFTER rooms_data, the
-            // ROOMS-subscribed `App()` prune effect already fired on
-            // an empty cache, and intentionally does NOT subscribe to
-            // OUTBOUND_DMS (would loop on its own writes). Without a
-            // follow-up prune here, hydrated entries whose tokens are
-            // already listed in a recipient's purge envelope persist
-            // in the cache until some unrelated ROOMS change happens
-            // to re-trigger the effect.
-            //
-            // Defer so this runs AFTER `hydrate_outbound_dms_cache`'s
-            // own internal `defer(with_mut)` insert has fired —
-            // setTimeout(0) macrotasks execute in FIFO enqueue order,
-            // so the prune sees the freshly-hydrated entries.
-            if count > 0 {
-                crate::util::defer(|| {
-                    prune_outbound_dms_for_purges();
-                });
-            }
         }
         Err(e) => {
             error!("Failed to deserialize outbound-DMs blob: {}", e);
@@ -48,6 +48,19 @@ pub async fn handle_get_response(
         return Ok(());
     }
 
+    // Attachment blobs (`CONTENT_TYPE_BLOB`) are not rooms either; hand
+    // them to the blob cache.
+    if crate::components::app::freenet_api::blob_fetch::is_blob_instance(key.id()) {
+        crate::components::app::freenet_api::blob_fetch::deliver_blob_response(*key.id(), state);
+        return Ok(());
+    }
+
+    // Member DM inboxes likewise.
+    if crate::components::app::freenet_api::inbox_sync::is_inbox_instance(key.id()) {
+        crate::components::app::freenet_api::inbox_sync::deliver_inbox_state(*key.id(), &state);
+        return Ok(());
+    }
+
     // First try to find the owner_vk from SYNC_INFO
     let owner_vk = SYNC_INFO.read().get_owner_vk_for_instance_id(key.id());
 
@@ -376,6 +389,7 @@ pub async fn handle_get_response(
                             version: 0,
                             preferred_nickname,
                             deputies: Vec::new(),
+                            dm_prekey: None,
                         },
                         &self_sk,
                     )
@@ -463,6 +477,7 @@ pub async fn handle_get_response(
                             self_sk: self_sk.clone(),
                             contract_key: key,
                             last_read_message_id: None,
+                            thread_read: Default::default(),
                             secrets: std::collections::HashMap::new(),
                             current_secret_version: None,
                             last_secret_rotation: None,
@@ -1186,6 +1201,18 @@ pub async fn handle_get_response(
                 });
             }
         }
+
+        // Every read of a room also (re)reads and subscribes to our DM
+        // inbox in it. Deferred so the room adopted above is in ROOMS.
+        crate::util::defer(move || {
+            let self_vk = ROOMS
+                .try_read()
+                .ok()
+                .and_then(|rooms| rooms.map.get(&owner_vk).map(|r| r.self_sk.verifying_key()));
+            if let Some(self_vk) = self_vk {
+                crate::components::app::freenet_api::inbox_sync::request_inbox(owner_vk, self_vk);
+            }
+        });
     }
 
     Ok(())
@@ -1578,6 +1605,7 @@ mod tests {
                 version: 0,
                 preferred_nickname: SealedBytes::public(b"Tester".to_vec()),
                 deputies: Vec::new(),
+                dm_prekey: None,
             },
             sk,
         )
@@ -15,6 +15,12 @@ pub async fn handle_put_response(
     let contract_id = key.id();
     info!("Received PutResponse for contract ID: {}", contract_id);
 
+    // A DM delivered to a member's inbox, not a room.
+    if crate::components::app::freenet_api::inbox_sync::is_inbox_instance(contract_id) {
+        crate::components::app::freenet_api::inbox_sync::deliver_inbox_put(*contract_id);
+        return Ok(());
+    }
+
     // Get the owner VK first, then release the read lock
     let owner_vk_opt = {
         let sync_info = SYNC_INFO.read();
@@ -227,6 +227,24 @@ pub fn handle_update_notification(
     update: UpdateData,
 ) -> Result<(), SynchronizerError> {
     info!("Received update notification for key: {key}");
+    if crate::components::app::freenet_api::inbox_sync::is_inbox_instance(key.id()) {
+        match &update {
+            UpdateData::State(state) => {
+                crate::components::app::freenet_api::inbox_sync::deliver_inbox_state(
+                    *key.id(),
+                    state.as_ref(),
+                )
+            }
+            UpdateData::Delta(delta) => {
+                crate::components::app::freenet_api::inbox_sync::deliver_inbox_state(
+                    *key.id(),
+                    delta.as_ref(),
+                )
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
     // Get contract info, return early if not found
     let room_owner_vk = match SYNC_INFO.read().get_owner_vk_for_instance_id(key.id()) {
         Some(vk) => vk,
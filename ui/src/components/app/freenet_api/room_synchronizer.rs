@@ -155,16 +155,24 @@ fn outbound_summary(
     // freenet/river#519 through: the top-level destructure catches a new field
     // on `ChatRoomStateV1Summary` ITSELF, and the two leaf destructures below
     // catch one added to `MessagesSummary` or `DirectMessagesSummary`. The other
-    // seven leaf summaries — `members`, `bans`, `member_info`, `secrets`,
-    // `configuration`, `upgrade`, `version` — are bound whole and are NOT
-    // guarded. So when the `MembersV1` follow-up adds `MembersSummary.horizon`,
+    // fourteen leaf summaries — `members`, `bans`, `ban_revocations`, `ban_lapses`, `invites`,
+    // `invite_uses`, `departures`, `member_info`, `mutes`, `join_requests`, `secrets`,
+    // `configuration`, `upgrade`, `version` — are
+    // bound whole and are NOT guarded. So when the `MembersV1` follow-up adds `MembersSummary.horizon`,
     // nothing here will fail to compile; whoever writes it must remember to
     // neutralise it and destructure that leaf too.
     let ChatRoomStateV1Summary {
         configuration,
         bans,
+        ban_revocations,
+        ban_lapses,
+        invites,
+        invite_uses,
         members,
+        departures,
         member_info,
+        mutes,
+        join_requests,
         secrets,
         recent_messages,
         direct_messages,
@@ -211,8 +219,15 @@ fn outbound_summary(
     ChatRoomStateV1Summary {
         configuration,
         bans,
+        ban_revocations,
+        ban_lapses,
+        invites,
+        invite_uses,
         members,
+        departures,
         member_info,
+        mutes,
+        join_requests,
         secrets,
         recent_messages,
         direct_messages,
@@ -285,6 +300,47 @@ pub(crate) fn send_member_info_heal_update(
     });
 }
 
+/// Broadcast a signed leave event for a room we are about to drop locally.
+///
+/// Sent straight to the contract rather than through `ROOMS` +
+/// `mark_needs_sync`: the room is removed from `ROOMS` right after, so the
+/// synchronizer would never pick the change up. The contract's
+/// `post_apply_cleanup` treats the author as departed and prunes them; the
+/// owner's delegate then rotates the secret on the resulting member-set
+/// change. Best effort — if the send fails we still leave locally, and the
+/// member ages out through ordinary inactivity pruning instead.
+pub(crate) fn send_leave_event_update(owner_vk: VerifyingKey, leave: AuthorizedMessageV1) {
+    let key = owner_vk_to_contract_key(&owner_vk);
+    let leave_delta = ChatRoomStateV1Delta {
+        recent_messages: Some(vec![leave]),
+        ..Default::default()
+    };
+    let update_request = ContractRequest::Update {
+        key,
+        data: UpdateData::Delta(to_cbor_vec(&leave_delta).into()),
+    };
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Some(web_api) = WEB_API.write().as_mut() {
+            match web_api
+                .send(ClientRequest::ContractOp(update_request))
+                .await
+            {
+                Ok(_) => info!("Sent leave event for room {:?}", MemberId::from(owner_vk)),
+                Err(e) => warn!(
+                    "Failed to send leave event for room {:?}: {}",
+                    MemberId::from(owner_vk),
+                    e
+                ),
+            }
+        } else {
+            warn!(
+                "WebAPI not available — leave event for room {:?} not sent",
+                MemberId::from(owner_vk)
+            );
+        }
+    });
+}
+
 /// Collapse a batch of newly-landed `(sender, timestamp)` inbound DMs to one
 /// entry per sender, keeping the sender's LARGEST timestamp.
 ///
@@ -1782,6 +1838,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::UNIX_EPOCH + Duration::from_secs(4_000),
                 banned_user: banned,
+                reason: None,
+                duration: None,
             },
             owner_id,
             owner_sk,
@@ -2363,6 +2421,8 @@ mod tests {
                 owner_member_id: owner_id,
                 banned_at: SystemTime::UNIX_EPOCH + Duration::from_secs(9_000),
                 banned_user: banned_id,
+                reason: None,
+                duration: None,
             },
             owner_id,
             &owner_sk,
@@ -0,0 +1,266 @@
+//! Local archive of every message this client has seen, kept in the chat
+//! delegate so history survives the room's `max_recent_messages` limit.
+//!
+//! Each room's archive lives under its own delegate key and only ever grows:
+//! [`record_room_messages`] adds what ROOMS holds that the archive does not,
+//! and the first time a room is seen its stored archive is loaded and merged
+//! in. A room's archive is not saved until that load has finished, so a save
+//! can never replace the stored history with just this session's messages.
+
+use super::chat_delegate::{coalesce_save, send_delegate_request, CoalesceState};
+use super::ROOMS;
+use dioxus::logger::tracing::{info, warn};
+use dioxus::prelude::*;
+use ed25519_dalek::VerifyingKey;
+use river_core::archive::{ArchivedMessage, MessageArchive};
+use river_core::chat_delegate::{ChatDelegateKey, ChatDelegateRequestMsg, ChatDelegateResponseMsg};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+/// Prefix for per-room archive keys: `message_archive:<base58(owner_vk)>`.
+/// Base58 for the same reason as `room:` keys: the delegate's origin key is
+/// utf8-lossy.
+pub const ARCHIVE_KEY_PREFIX: &str = "message_archive:";
+
+pub fn archive_storage_key(owner_vk: &VerifyingKey) -> Vec<u8> {
+    format!(
+        "{ARCHIVE_KEY_PREFIX}{}",
+        bs58::encode(owner_vk.to_bytes()).into_string()
+    )
+    .into_bytes()
+}
+
+pub fn is_archive_storage_key(key: &[u8]) -> bool {
+    key.starts_with(ARCHIVE_KEY_PREFIX.as_bytes())
+}
+
+/// One room's archive and where it stands with the delegate.
+#[derive(Clone, Default, PartialEq)]
+pub struct RoomArchive {
+    pub archive: MessageArchive,
+    /// The stored archive has been merged in (or there was none).
+    pub loaded: bool,
+    /// A load is in flight or finished; cleared on failure so it is retried.
+    load_requested: bool,
+}
+
+/// Archives by room owner key.
+pub static MESSAGE_ARCHIVES: GlobalSignal<HashMap<VerifyingKey, RoomArchive>> =
+    Global::new(HashMap::new);
+
+thread_local! {
+    /// Rooms whose archive has changed since it was last saved.
+    static DIRTY_ARCHIVES: RefCell<HashSet<VerifyingKey>> = RefCell::new(HashSet::new());
+}
+
+static ARCHIVE_SAVE_STATE: CoalesceState = CoalesceState::new();
+
+/// Archive the messages in ROOMS that are not archived yet, and start loading
+/// the stored archive of any room seen for the first time. Writes nothing when
+/// there is nothing new, so the ROOMS effect driving it does not loop.
+pub fn record_room_messages() {
+    let (new_entries, to_load) = {
+        let Ok(rooms) = ROOMS.try_read() else {
+            return;
+        };
+        let archives = MESSAGE_ARCHIVES.peek();
+        let mut new_entries: Vec<(VerifyingKey, Vec<ArchivedMessage>)> = Vec::new();
+        let mut to_load = Vec::new();
+        for (owner_vk, room_data) in &rooms.map {
+            let archived = archives.get(owner_vk);
+            if !archived.is_some_and(|a| a.load_requested) {
+                to_load.push(*owner_vk);
+            }
+            let entries = river_core::archive::new_entries(owner_vk, &room_data.room_state, |id| {
+                archived.is_some_and(|a| a.archive.contains(id))
+            });
+            if !entries.is_empty() {
+                new_entries.push((*owner_vk, entries));
+            }
+        }
+        (new_entries, to_load)
+    };
+    if new_entries.is_empty() && to_load.is_empty() {
+        return;
+    }
+
+    crate::util::defer(move || {
+        let mut save = false;
+        MESSAGE_ARCHIVES.with_mut(|archives| {
+            for (owner_vk, entries) in new_entries {
+                let room = archives.entry(owner_vk).or_default();
+                let mut added = false;
+                for entry in entries {
+                    added |= room.archive.insert(entry).unwrap_or(false);
+                }
+                if added {
+                    DIRTY_ARCHIVES.with(|dirty| dirty.borrow_mut().insert(owner_vk));
+                    save |= room.loaded;
+                }
+            }
+            for owner_vk in &to_load {
+                archives.entry(*owner_vk).or_default().load_requested = true;
+            }
+        });
+        for owner_vk in to_load {
+            crate::util::safe_spawn_local(load_room_archive(owner_vk));
+        }
+        if save {
+            spawn_save();
+        }
+    });
+}
+
+/// Read a room's stored archive from the delegate and merge it in.
+async fn load_room_archive(owner_vk: VerifyingKey) {
+    let request = ChatDelegateRequestMsg::GetRequest {
+        key: ChatDelegateKey::new(archive_storage_key(&owner_vk)),
+    };
+    let stored = match send_delegate_request(request).await {
+        Ok(ChatDelegateResponseMsg::GetResponse { value, .. }) => value,
+        Ok(other) => {
+            warn!("Unexpected response loading message archive: {:?}", other);
+            return retry_load_later(owner_vk);
+        }
+        Err(e) => {
+            warn!("Failed to load message archive: {}", e);
+            return retry_load_later(owner_vk);
+        }
+    };
+    let entries: Vec<ArchivedMessage> = match stored {
+        Some(bytes) => match ciborium::de::from_reader(&bytes[..]) {
+            Ok(entries) => entries,
+            Err(e) => {
+                // Do not save over a blob we could not read.
+                warn!("Failed to deserialize message archive: {}", e);
+                return;
+            }
+        },
+        None => Vec::new(),
+    };
+    let (stored, rejected) = MessageArchive::from_entries(entries);
+    if rejected > 0 {
+        warn!(
+            "Dropped {} archived message(s) that failed signature verification",
+            rejected
+        );
+    }
+
+    crate::util::defer(move || {
+        let mut save = false;
+        MESSAGE_ARCHIVES.with_mut(|archives| {
+            let room = archives.entry(owner_vk).or_default();
+            // Anything recorded this session that the stored copy lacks still
+            // needs saving.
+            save = room
+                .archive
+                .entries()
+                .iter()
+                .any(|e| !stored.contains(&e.message.id()));
+            let session = std::mem::replace(&mut room.archive, stored);
+            for entry in session.entries() {
+                let _ = room.archive.insert(entry.clone());
+            }
+            room.loaded = true;
+            info!(
+                "Loaded message archive with {} message(s)",
+                room.archive.len()
+            );
+        });
+        if save {
+            DIRTY_ARCHIVES.with(|dirty| dirty.borrow_mut().insert(owner_vk));
+            spawn_save();
+        }
+    });
+}
+
+fn retry_load_later(owner_vk: VerifyingKey) {
+    crate::util::defer(move || {
+        MESSAGE_ARCHIVES.with_mut(|archives| {
+            if let Some(room) = archives.get_mut(&owner_vk) {
+                room.load_requested = false;
+            }
+        });
+    });
+}
+
+fn spawn_save() {
+    crate::util::safe_spawn_local(async {
+        if let Err(e) = save_message_archives_to_delegate().await {
+            warn!("Failed to save message archive: {}", e);
+        }
+    });
+}
+
+/// Persist every changed, loaded archive. Coalesced like the other delegate
+/// saves.
+pub async fn save_message_archives_to_delegate() -> Result<(), String> {
+    coalesce_save(
+        &ARCHIVE_SAVE_STATE,
+        "Message-archive",
+        do_save_message_archives,
+    )
+    .await
+}
+
+async fn do_save_message_archives() -> Result<(), String> {
+    // Snapshot inside the save (see `coalesce_save`): take the dirty rooms
+    // whose stored archive has been merged in; the rest stay dirty until then.
+    let blobs: Vec<(VerifyingKey, Vec<u8>)> = {
+        let archives = MESSAGE_ARCHIVES.peek();
+        let ready: Vec<VerifyingKey> = DIRTY_ARCHIVES.with(|dirty| {
+            let mut dirty = dirty.borrow_mut();
+            let ready: Vec<_> = dirty
+                .iter()
+                .filter(|vk| archives.get(vk).is_some_and(|a| a.loaded))
+                .copied()
+                .collect();
+            for vk in &ready {
+                dirty.remove(vk);
+            }
+            ready
+        });
+        let mut blobs = Vec::new();
+        for owner_vk in ready {
+            let mut buffer = Vec::new();
+            ciborium::ser::into_writer(archives[&owner_vk].archive.entries(), &mut buffer)
+                .map_err(|e| format!("Failed to serialize message archive: {}", e))?;
+            blobs.push((owner_vk, buffer));
+        }
+        blobs
+    };
+
+    for (owner_vk, value) in blobs {
+        let request = ChatDelegateRequestMsg::StoreRequest {
+            key: ChatDelegateKey::new(archive_storage_key(&owner_vk)),
+            value,
+        };
+        let result = match send_delegate_request(request).await {
+            Ok(ChatDelegateResponseMsg::StoreResponse { result, .. }) => result,
+            Ok(other) => Err(format!("Unexpected response: {:?}", other)),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            // Leave it dirty so the next change retries.
+            DIRTY_ARCHIVES.with(|dirty| dirty.borrow_mut().insert(owner_vk));
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_keys_are_distinct_from_room_keys() {
+        let vk = ed25519_dalek::SigningKey::from_bytes(&[7; 32]).verifying_key();
+        let key = archive_storage_key(&vk);
+        assert!(is_archive_storage_key(&key));
+        assert!(super::super::chat_delegate::parse_room_storage_key(&key).is_none());
+        assert!(!is_archive_storage_key(
+            &super::super::chat_delegate::room_storage_key(&vk)
+        ));
+    }
+}
@@ -1038,10 +1038,14 @@ pub fn notify_new_messages(
     //     return;
     // }
 
-    // Filter to messages from other users, excluding room events.
+    // Filter to messages from other users, excluding room events and
+    // members the user has blocked.
+    let blocked = crate::components::app::block_list::blocked_in_room(*room_key, self_member_id)
+        .unwrap_or_default();
     let external_messages: Vec<_> = new_messages
         .iter()
         .filter(|msg| is_notifiable_external_message(msg, self_member_id))
+        .filter(|msg| !blocked.contains(&msg.message.author))
         .collect();
 
     info!(
@@ -1172,6 +1176,7 @@ fn get_message_preview(
         ActionContentV1, EventContentV1, ReplyContentV1, TextContentV1, ACTION_TYPE_DELETE,
         ACTION_TYPE_EDIT, ACTION_TYPE_REACTION, ACTION_TYPE_REMOVE_REACTION, CONTENT_TYPE_ACTION,
         CONTENT_TYPE_EVENT, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT, EVENT_TYPE_JOIN,
+        EVENT_TYPE_LEAVE,
     };
 
     let text = match content {
@@ -1199,6 +1204,7 @@ fn get_message_preview(
             CONTENT_TYPE_EVENT => EventContentV1::decode(data)
                 .map(|event| match event.event_type {
                     EVENT_TYPE_JOIN => "joined the room".to_string(),
+                    EVENT_TYPE_LEAVE => "left the room".to_string(),
                     _ => format!("[Event type {}]", event.event_type),
                 })
                 .unwrap_or_else(|_| "[Event]".to_string()),
@@ -1224,6 +1230,7 @@ fn get_message_preview(
                         CONTENT_TYPE_EVENT => EventContentV1::decode(&bytes)
                             .map(|event| match event.event_type {
                                 EVENT_TYPE_JOIN => "joined the room".to_string(),
+                                EVENT_TYPE_LEAVE => "left the room".to_string(),
                                 _ => format!("[Event type {}]", event.event_type),
                             })
                             .unwrap_or_else(|_| "[Event]".to_string()),
@@ -14,39 +14,48 @@ use crate::components::members::{
     privilege_in_view, DeputyBadge,
 };
 use crate::room_data::{NotificationMode, SendMessageError};
-use crate::util::confusable::{ImpersonationChecker, ImpersonationWarning};
 use crate::util::display_name::{display_nickname, sanitize_display_name};
 use crate::util::ecies::{encrypt_with_symmetric_key, unseal_bytes_with_secrets};
 use crate::util::{
     date_separator_labels, format_utc_as_full_datetime, format_utc_as_local_time,
     get_current_system_time, local_message_date, local_today,
 };
+use river_core::confusable::{ImpersonationChecker, ImpersonationWarning};
+mod archive_panel;
+mod attachment_preview;
 mod emoji_picker;
 mod mention;
 mod message_actions;
 mod message_input;
 mod not_member_notification;
+mod search_panel;
+mod thread_panel;
+use self::archive_panel::ArchivePanel;
+use self::attachment_preview::{Attachment, AttachmentPreview};
 use self::emoji_picker::FREQUENT_EMOJIS;
 use self::not_member_notification::NotMemberNotification;
+use self::search_panel::SearchPanel;
+use self::thread_panel::ThreadPanel;
 use crate::components::conversation::message_input::MessageInput;
 use chrono::{DateTime, Utc};
 use dioxus::logger::tracing::*;
 use dioxus::prelude::*;
 use dioxus_free_icons::icons::fa_solid_icons::{
-    FaBars, FaBell, FaBellSlash, FaChevronDown, FaCircleInfo, FaEllipsisVertical, FaFaceSmile,
-    FaPenToSquare, FaReply, FaTrashCan, FaTriangleExclamation, FaUsers,
+    FaBars, FaBell, FaBellSlash, FaChevronDown, FaCircleInfo, FaClockRotateLeft,
+    FaEllipsisVertical, FaFaceSmile, FaMagnifyingGlass, FaPenToSquare, FaReply, FaTrashCan,
+    FaTriangleExclamation, FaUsers,
 };
 use dioxus_free_icons::Icon;
 use freenet_scaffold::ComposableState;
 use river_core::room_state::member::{MemberId, MembersDelta};
 use river_core::room_state::member_info::{AuthorizedMemberInfo, MemberInfoV1};
 use river_core::room_state::message::{
-    AuthorizedMessageV1, MessageId, MessageV1, MessagesV1, RoomMessageBody,
+    AuthorizedMessageV1, MessageId, MessageV1, MessagesV1, RoomMessageBody, ThreadIndex,
 };
 use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1Delta};
 use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 use std::time::Duration;
@@ -171,11 +180,29 @@ struct GroupedMessage {
     /// What the reply-quote strip should render, already resolved against live
     /// room state — see [`ReplyStrip`] and [`resolve_reply_strip`].
     reply_strip: ReplyStrip,
+    /// Set for an attachment message; rendered as a preview instead of the body.
+    attachment: Option<Attachment>,
+    /// Where this message sits in its reply thread.
+    thread: ThreadPosition,
     /// Propagation delay in seconds (send → receive), if known and significant
     #[allow(dead_code)]
     receive_delay_secs: Option<i64>,
 }
 
+/// A message's place in the reply threads of its room (see
+/// [`river_core::room_state::message::ThreadIndex`]).
+#[derive(Clone, PartialEq, Debug)]
+struct ThreadPosition {
+    root: MessageId,
+    /// Replies between this message and the root (0 for the root itself).
+    depth: usize,
+    /// For a root, the number of replies in its thread; 0 otherwise.
+    replies: usize,
+    /// For a root the reader has opened in the thread panel, the replies from
+    /// others newer than the last one they saw there; 0 otherwise.
+    unread: usize,
+}
+
 /// An item in the conversation display — either a message group or an event summary
 #[derive(Clone, PartialEq)]
 enum DisplayItem {
@@ -183,10 +210,11 @@ enum DisplayItem {
     Event(EventSummary),
 }
 
-/// Summary of consecutive room events (e.g. joins)
+/// Summary of consecutive room events of one kind (joins, or leaves)
 #[derive(Clone, PartialEq)]
 struct EventSummary {
     names: Vec<String>,
+    left: bool,
     id: String,
     last_time: DateTime<Utc>,
 }
@@ -426,7 +454,8 @@ fn target_plaintext(
     secrets: &HashMap<u32, [u8; 32]>,
 ) -> Option<String> {
     use river_core::room_state::content::{
-        ReplyContentV1, TextContentV1, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
+        BlobContentV1, PollContentV1, ReplyContentV1, TextContentV1, CONTENT_TYPE_BLOB,
+        CONTENT_TYPE_POLL, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
     };
 
     // An edit supersedes the body, and reaches `actions_state` already
@@ -457,6 +486,8 @@ fn target_plaintext(
     match *content_type {
         CONTENT_TYPE_TEXT => TextContentV1::decode(&plaintext).ok().map(|c| c.text),
         CONTENT_TYPE_REPLY => ReplyContentV1::decode(&plaintext).ok().map(|r| r.text),
+        CONTENT_TYPE_POLL => PollContentV1::decode(&plaintext).ok().map(|p| p.question),
+        CONTENT_TYPE_BLOB => BlobContentV1::decode(&plaintext).ok().map(|b| b.summary()),
         _ => None,
     }
 }
@@ -549,11 +580,43 @@ fn group_messages(
     // The room owner, so an author line can tell whether the member it is
     // flagging holds privilege themselves — see `privilege_in_view`.
     owner_id: MemberId,
+    // The reader's per-thread read markers (`RoomData::thread_read`).
+    thread_read: &HashMap<MessageId, std::time::SystemTime>,
+    // Members the reader has blocked (`block_list`): their messages are left
+    // out entirely, replies included.
+    blocked: &HashSet<MemberId>,
     clock: MessageClock<'_>,
 ) -> Vec<DisplayItem> {
     let mut items: Vec<DisplayItem> = Vec::new();
     let group_threshold = Duration::from_secs(5 * 60); // 5 minutes
 
+    // Drop a blocked member's messages before anything else looks, so thread
+    // counts skip them too and a reply to one of them stands on its own, as
+    // it does in riverctl.
+    let unblocked;
+    let messages_state = if blocked.is_empty() {
+        messages_state
+    } else {
+        unblocked = MessagesV1 {
+            messages: messages_state
+                .messages
+                .iter()
+                .filter(|m| !blocked.contains(&m.message.author))
+                .cloned()
+                .collect(),
+            actions_state: messages_state.actions_state.clone(),
+        };
+        &unblocked
+    };
+
+    // Threads are built once per pass, with private replies decrypted.
+    let threads =
+        messages_state.thread_index_with(|m| extract_reply_target_id(&m.message.content, secrets));
+    let by_id: HashMap<MessageId, &AuthorizedMessageV1> = messages_state
+        .display_messages()
+        .map(|m| (m.id(), m))
+        .collect();
+
     // Inputs to the per-message HTML cache (see MESSAGE_HTML_CACHE). The member
     // fingerprint is computed once per render, not per message.
     let members_fp = member_names_fingerprint(member_names);
@@ -576,12 +639,15 @@ fn group_messages(
 
         let author_name = resolve_member_nickname(member_info, author_id, secrets);
 
-        // Handle event messages (join, etc.) — summarize consecutive events within 1 hour
+        // Handle event messages (join, leave) — summarize consecutive events
+        // of the same kind within 1 hour
         if message.message.content.is_event() {
             let msg_id_str = format!("{:?}", message_id.0);
+            let left = message.message.content.is_leave_event();
             let event_group_threshold = Duration::from_secs(60 * 60);
             let should_merge = matches!(items.last(), Some(DisplayItem::Event(ref s))
-                if (message_time - s.last_time).to_std().unwrap_or(Duration::MAX) < event_group_threshold);
+                if s.left == left
+                    && (message_time - s.last_time).to_std().unwrap_or(Duration::MAX) < event_group_threshold);
             if should_merge {
                 if let Some(DisplayItem::Event(ref mut summary)) = items.last_mut() {
                     summary.names.push(author_name);
@@ -590,6 +656,7 @@ fn group_messages(
             } else {
                 items.push(DisplayItem::Event(EventSummary {
                     names: vec![author_name],
+                    left,
                     id: msg_id_str,
                     last_time: message_time,
                 }));
@@ -603,6 +670,15 @@ fn group_messages(
         let content_text = messages_state
             .effective_text(message)
             .unwrap_or_else(|| decrypt_message_content(&message.message.content, secrets));
+        // A poll renders its options and live counts under the question.
+        let content_text = match decode_poll(&message.message.content, secrets) {
+            Some(poll) => poll_markdown(&poll, &messages_state.poll_tally(&message_id, &poll)),
+            None => content_text,
+        };
+        let attachment = decode_blob(&message.message.content, secrets).map(|blob| Attachment {
+            blob,
+            secrets: secrets.clone(),
+        });
         seen_message_ids.insert(message_id.clone());
         let content_html = render_message_html_cached(
             &message_id,
@@ -634,6 +710,17 @@ fn group_messages(
         let receive_delay_secs =
             get_delay_secs_from(clock.receive_times, &message_id, send_time_ms);
 
+        let thread = thread_position(&threads, &message_id, |replies| {
+            let Some(seen) = thread_read.get(&message_id) else {
+                return 0;
+            };
+            replies
+                .iter()
+                .filter_map(|id| by_id.get(id))
+                .filter(|m| m.message.author != self_member_id && m.message.time > *seen)
+                .count()
+        });
+
         let grouped_message = GroupedMessage {
             content_text: content_text.clone(),
             content_html,
@@ -644,6 +731,8 @@ fn group_messages(
             edited,
             reactions,
             reply_strip,
+            attachment,
+            thread,
             receive_delay_secs,
         };
 
@@ -703,12 +792,76 @@ fn group_messages(
     items
 }
 
+/// Where `id` sits in `threads`. `count_unread` is given a root's replies
+/// (oldest first) and returns how many of them are unread.
+fn thread_position(
+    threads: &ThreadIndex,
+    id: &MessageId,
+    count_unread: impl FnOnce(&[MessageId]) -> usize,
+) -> ThreadPosition {
+    let mut depth = 0;
+    let mut current = id;
+    while let Some(parent) = threads.parent(current) {
+        depth += 1;
+        current = parent;
+    }
+    let (replies, unread) = if depth == 0 {
+        let replies = threads.descendants(id);
+        let unread = if replies.is_empty() {
+            0
+        } else {
+            count_unread(&replies)
+        };
+        (replies.len(), unread)
+    } else {
+        (0, 0)
+    };
+    ThreadPosition {
+        root: current.clone(),
+        depth,
+        replies,
+        unread,
+    }
+}
+
+/// One message in the thread panel, with the author line it shows under.
+#[derive(Clone, PartialEq)]
+struct ThreadEntry {
+    author_name: String,
+    is_self: bool,
+    message: GroupedMessage,
+}
+
+/// The messages of the thread rooted at `root`, oldest first. Empty if the
+/// root is no longer displayed.
+fn thread_entries(items: &[DisplayItem], root: &MessageId) -> Vec<ThreadEntry> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Messages(group) => Some(group),
+            DisplayItem::Event(_) => None,
+        })
+        .flat_map(|group| {
+            group
+                .messages
+                .iter()
+                .filter(|msg| msg.thread.root == *root)
+                .map(|msg| ThreadEntry {
+                    author_name: group.author_name.clone(),
+                    is_self: group.is_self,
+                    message: msg.clone(),
+                })
+        })
+        .collect()
+}
+
 /// Format an event summary like "Alice joined the room" or "3 people joined the room"
-fn format_event_summary(names: &[String]) -> String {
+fn format_event_summary(names: &[String], left: bool) -> String {
+    let verb = if left { "left" } else { "joined" };
     match names.len() {
-        1 => format!("{} joined the room", names[0]),
-        2 => format!("{} and {} joined the room", names[0], names[1]),
-        n => format!("{} people joined the room", n),
+        1 => format!("{} {verb} the room", names[0]),
+        2 => format!("{} and {} {verb} the room", names[0], names[1]),
+        n => format!("{} people {verb} the room", n),
     }
 }
 
@@ -727,7 +880,8 @@ pub(crate) fn try_decrypt_message_content(
     secrets: &HashMap<u32, [u8; 32]>,
 ) -> Option<String> {
     use river_core::room_state::content::{
-        ReplyContentV1, TextContentV1, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
+        BlobContentV1, PollContentV1, ReplyContentV1, TextContentV1, CONTENT_TYPE_BLOB,
+        CONTENT_TYPE_POLL, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
     };
 
     match content {
@@ -759,6 +913,16 @@ pub(crate) fn try_decrypt_message_content(
                     return Some(reply.text);
                 }
             }
+            if *content_type == CONTENT_TYPE_POLL {
+                if let Ok(poll) = PollContentV1::decode(&plaintext) {
+                    return Some(poll.question);
+                }
+            }
+            if *content_type == CONTENT_TYPE_BLOB {
+                if let Ok(blob) = BlobContentV1::decode(&plaintext) {
+                    return Some(blob.summary());
+                }
+            }
             Some(String::from_utf8_lossy(&plaintext).to_string())
         }
     }
@@ -769,7 +933,8 @@ pub(crate) fn decrypt_message_content(
     secrets: &HashMap<u32, [u8; 32]>,
 ) -> String {
     use river_core::room_state::content::{
-        ReplyContentV1, TextContentV1, CONTENT_TYPE_ACTION, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
+        PollContentV1, ReplyContentV1, TextContentV1, CONTENT_TYPE_ACTION, CONTENT_TYPE_POLL,
+        CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
     };
 
     match content {
@@ -792,6 +957,12 @@ pub(crate) fn decrypt_message_content(
                     return reply.text;
                 }
             }
+            // Polls - the question is the text; options render separately
+            if *content_type == CONTENT_TYPE_POLL {
+                if let Ok(poll) = PollContentV1::decode(data) {
+                    return poll.question;
+                }
+            }
             // Unknown content type
             content.to_string_lossy()
         }
@@ -846,6 +1017,99 @@ pub(crate) fn decrypt_message_content(
     }
 }
 
+/// The attachment reference carried by `content`, decrypted with `secrets`
+/// when private. `None` for any other content type or an unreadable body.
+fn decode_blob(
+    content: &RoomMessageBody,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> Option<river_core::room_state::content::BlobContentV1> {
+    use river_core::room_state::content::{BlobContentV1, DecodedContent};
+
+    if !content.is_blob() {
+        return None;
+    }
+    match content {
+        RoomMessageBody::Public { .. } => match content.decode_content()? {
+            DecodedContent::Blob(blob) => Some(blob),
+            _ => None,
+        },
+        RoomMessageBody::Private {
+            ciphertext,
+            nonce,
+            secret_version,
+            ..
+        } => {
+            let secret = secrets.get(secret_version)?;
+            let plaintext = crate::util::ecies::decrypt_with_symmetric_key(
+                secret,
+                ciphertext.as_slice(),
+                nonce,
+            )
+            .ok()?;
+            BlobContentV1::decode(&plaintext).ok()
+        }
+    }
+}
+
+/// The poll carried by `content`, decrypted with `secrets` when private.
+/// `None` for any other content type or an unreadable body.
+fn decode_poll(
+    content: &RoomMessageBody,
+    secrets: &HashMap<u32, [u8; 32]>,
+) -> Option<river_core::room_state::content::PollContentV1> {
+    use river_core::room_state::content::{DecodedContent, PollContentV1};
+
+    if !content.is_poll() {
+        return None;
+    }
+    match content {
+        RoomMessageBody::Public { .. } => match content.decode_content()? {
+            DecodedContent::Poll(poll) => Some(poll),
+            _ => None,
+        },
+        RoomMessageBody::Private {
+            ciphertext,
+            nonce,
+            secret_version,
+            ..
+        } => {
+            let secret = secrets.get(secret_version)?;
+            let plaintext = crate::util::ecies::decrypt_with_symmetric_key(
+                secret,
+                ciphertext.as_slice(),
+                nonce,
+            )
+            .ok()?;
+            PollContentV1::decode(&plaintext).ok()
+        }
+    }
+}
+
+/// Markdown for a poll message: the question, then one line per option with
+/// its vote count. Option text is user-supplied, so it is escaped the same way
+/// any message body is when the markdown renders.
+fn poll_markdown(
+    poll: &river_core::room_state::content::PollContentV1,
+    tally: &river_core::room_state::message::PollTally,
+) -> String {
+    let mut out = format!("📊 **{}**\n", poll.question.trim());
+    for (i, (option, voters)) in poll.options.iter().zip(&tally.voters).enumerate() {
+        out.push_str(&format!(
+            "\n{}. {} — {}",
+            i + 1,
+            option.trim(),
+            voters.len()
+        ));
+    }
+    let kind = if poll.multiple_choice {
+        "multiple choice"
+    } else {
+        "single choice"
+    };
+    out.push_str(&format!("\n\n_{} voter(s) · {kind}_", tally.total_voters));
+    out
+}
+
 /// Clean a quoted reply-preview snapshot for display: resolve `@[name](rv:id)`
 /// mention tokens to plain `@name` (using each member's *current* nickname, with
 /// the token snapshot as fallback) and strip markdown formatting, so the preview
@@ -2473,6 +2737,10 @@ pub fn Conversation() -> Element {
     // a time across the whole history — opening one closes any other (#402).
     let open_action_menu: Signal<Option<String>> = use_signal(|| None);
     let mut replying_to: Signal<Option<ReplyContext>> = use_signal(|| None);
+    // Root of the thread shown in the thread panel, if it is open.
+    let mut open_thread: Signal<Option<MessageId>> = use_signal(|| None);
+    let mut search_open = use_signal(|| false);
+    let mut archive_open = use_signal(|| false);
 
     // State for delete confirmation modal
     let mut pending_delete: Signal<Option<MessageId>> = use_signal(|| None);
@@ -2601,6 +2869,12 @@ pub fn Conversation() -> Element {
                     .is_some()
                 {
                     let self_member_id = MemberId::from(&room_data.self_sk.verifying_key());
+                    let Some(blocked) =
+                        crate::components::app::block_list::blocked_in_room(key, self_member_id)
+                    else {
+                        crate::util::signal_guard::schedule_nudge();
+                        return None;
+                    };
                     // Build member name lookup (reaction tooltips, @mention chips).
                     let member_names: HashMap<MemberId, String> = room_state
                         .member_info
@@ -2652,6 +2926,8 @@ pub fn Conversation() -> Element {
                         &deputy_badges,
                         &impersonation,
                         MemberId::from(&key),
+                        &room_data.thread_read,
+                        &blocked,
                         MessageClock {
                             receive_times: &receive_times,
                             // One "now" for the whole pass. Only reached by
@@ -2666,6 +2942,18 @@ pub fn Conversation() -> Element {
         None
     });
 
+    // Everything in the open thread has been seen: advance its read marker
+    // when the panel opens and whenever new replies arrive while it is open.
+    use_effect(move || {
+        let Some(root) = open_thread() else {
+            return;
+        };
+        let _ = message_groups.read();
+        crate::util::defer(move || {
+            crate::components::app::document_title::mark_thread_as_read(&root)
+        });
+    });
+
     // Use IntersectionObserver to track whether the user is near the bottom of the
     // chat scroll container.  This replaces the old `onscroll` handler that performed
     // DOM queries (scrollTop / clientHeight / scrollHeight) on every scroll event,
@@ -3060,6 +3348,10 @@ pub fn Conversation() -> Element {
                 prev_room.set(room);
                 force_scroll.set(true);
                 is_at_bottom.set(true);
+                // A thread panel belongs to the room it was opened in.
+                open_thread.set(None);
+                search_open.set(false);
+                archive_open.set(false);
                 // Opening a room starts you at its newest message, so the pin
                 // starts armed. Set here rather than left to the snap below,
                 // because a room with no messages produces no scroll at all
@@ -3823,6 +4115,28 @@ pub fn Conversation() -> Element {
                                                 Icon { icon: FaCircleInfo, width: 16, height: 16 }
                                             }
                                         }
+                                        button {
+                                            "data-testid": "search-button",
+                                            class: "flex-shrink-0 p-1.5 rounded-lg text-text-muted hover:text-accent hover:bg-surface transition-colors",
+                                            title: "Search messages",
+                                            "aria-label": "Search messages",
+                                            onclick: move |_| {
+                                                archive_open.set(false);
+                                                search_open.toggle();
+                                            },
+                                            Icon { icon: FaMagnifyingGlass, width: 16, height: 16 }
+                                        }
+                                        button {
+                                            "data-testid": "archive-button",
+                                            class: "flex-shrink-0 p-1.5 rounded-lg text-text-muted hover:text-accent hover:bg-surface transition-colors",
+                                            title: "Archived history",
+                                            "aria-label": "Archived history",
+                                            onclick: move |_| {
+                                                search_open.set(false);
+                                                archive_open.toggle();
+                                            },
+                                            Icon { icon: FaClockRotateLeft, width: 16, height: 16 }
+                                        }
                                         // Per-room notification preference. Icon reflects state:
                                         // bell = notifying, bell-slash = muted; the tooltip names
                                         // the exact mode. Opens the compact NotificationModal.
@@ -4220,7 +4534,7 @@ pub fn Conversation() -> Element {
                                                         }
                                                     },
                                                     DisplayRow::Item(DisplayItem::Event(summary)) => {
-                                                        let text = format_event_summary(&summary.names);
+                                                        let text = format_event_summary(&summary.names, summary.left);
                                                         let key = summary.id.clone();
                                                         rsx! {
                                                             div {
@@ -4280,6 +4594,7 @@ pub fn Conversation() -> Element {
                                                                         }
                                                                     }
                                                                 },
+                                                                on_open_thread: move |root| open_thread.set(Some(root)),
                                                                 open_action_menu: open_action_menu,
                                                                 }
                                                             }
@@ -4421,6 +4736,8 @@ pub fn Conversation() -> Element {
                         match room_data.can_participate() {
                             Ok(()) => {
                                 let max_msg_size = room_data.room_state.configuration.configuration.max_message_size;
+                                let slow_mode = room_data.room_state.configuration.configuration.slow_mode;
+                                let posting_restriction = room_data.posting_restriction();
                                 let room_is_private = room_data.is_private();
                                 // Mentionable members for the @ autocomplete: every member
                                 // with a (decrypted) nickname except self, sorted by name.
@@ -4448,6 +4765,13 @@ pub fn Conversation() -> Element {
                                 mention_members
                                     .sort_by(|a, b| a.1.to_lowercase().cmp(&b.1.to_lowercase()));
                                 rsx! {
+                                    if let (Some(slow_mode), None) = (slow_mode, posting_restriction) {
+                                        div {
+                                            class: "px-4 pb-1 text-xs text-text-muted",
+                                            "data-testid": "slow-mode-notice",
+                                            "Slow mode: {slow_mode}. Posting more removes your own earliest messages."
+                                        }
+                                    }
                                     MessageInput {
                                         handle_send_message: move |msg: (String, Option<ReplyContext>)| {
                                             let mut handle = handle_send_message.clone();
@@ -4458,6 +4782,7 @@ pub fn Conversation() -> Element {
                                         max_message_size: max_msg_size,
                                         is_private: room_is_private,
                                         members: mention_members,
+                                        posting_restriction: posting_restriction,
                                     }
                                 }
                             },
@@ -4598,6 +4923,70 @@ pub fn Conversation() -> Element {
                 }
             }
 
+            // Thread side panel. Renders nothing once its root leaves the
+            // displayed messages (deleted, pruned, or another room opened).
+            {
+                let thread = open_thread().and_then(|root| {
+                    let groups = message_groups.read();
+                    let (items, _, member_names) = groups.as_ref()?;
+                    let entries = thread_entries(items, &root);
+                    (!entries.is_empty()).then(|| (entries, member_names.clone()))
+                });
+                thread.map(|(entries, member_names)| rsx! {
+                    ThreadPanel {
+                        entries,
+                        member_names,
+                        on_close: move |_| open_thread.set(None),
+                        on_reply: move |ctx: ReplyContext| {
+                            replying_to.set(Some(ctx));
+                            // The panel covers the input on a phone, so get it
+                            // out of the way; on wider screens it stays open.
+                            let narrow = web_sys::window()
+                                .and_then(|w| w.inner_width().ok())
+                                .and_then(|w| w.as_f64())
+                                .is_some_and(|w| w < 768.0);
+                            if narrow {
+                                open_thread.set(None);
+                            }
+                            if let Some(el) = web_sys::window()
+                                .and_then(|w| w.document())
+                                .and_then(|doc| doc.get_element_by_id("message-input"))
+                            {
+                                if let Some(el) = el.dyn_ref::<web_sys::HtmlElement>() {
+                                    let _ = el.focus();
+                                }
+                            }
+                        },
+                    }
+                })
+            }
+
+            if archive_open() {
+                ArchivePanel { on_close: move |_| archive_open.set(false) }
+            }
+
+            if search_open() {
+                SearchPanel {
+                    on_close: move |_| search_open.set(false),
+                    on_open: move |message_id: MessageId| {
+                        let narrow = web_sys::window()
+                            .and_then(|w| w.inner_width().ok())
+                            .and_then(|w| w.as_f64())
+                            .is_some_and(|w| w < 768.0);
+                        if narrow {
+                            search_open.set(false);
+                        }
+                        if let Some(el) = web_sys::window()
+                            .and_then(|w| w.document())
+                            .and_then(|doc| doc.get_element_by_id(&format!("msg-{:?}", message_id.0)))
+                        {
+                            el.scroll_into_view();
+                            let _ = el.class_list().add_1("reply-highlight");
+                        }
+                    },
+                }
+            }
+
             // Delete confirmation modal
             if pending_delete.read().is_some() {
                 div {
@@ -4652,6 +5041,8 @@ fn MessageGroupComponent(
     on_request_delete: EventHandler<MessageId>,
     on_edit: EventHandler<(MessageId, String)>,
     on_reply: EventHandler<ReplyContext>,
+    /// Open the thread panel on the given thread root.
+    on_open_thread: EventHandler<MessageId>,
     // Shared across all groups so only one action menu is open at a time (#402).
     open_action_menu: Signal<Option<String>>,
 ) -> Element {
@@ -4794,7 +5185,7 @@ fn MessageGroupComponent(
                                         class: "text-sm cursor-default",
                                         title: "{tooltip}",
                                         "aria-label": "{tooltip}",
-                                        {crate::util::confusable::WARNING_GLYPH}
+                                        {river_core::confusable::WARNING_GLYPH}
                                     }
                                 }
                             }
@@ -5202,9 +5593,13 @@ fn MessageGroupComponent(
                                                     // the bubble to fit.
                                                     div {
                                                         class: "px-3 py-2 min-w-0",
-                                                        div {
-                                                            class: "prose prose-sm dark:prose-invert max-w-none [overflow-wrap:anywhere]",
-                                                            dangerous_inner_html: "{msg.content_html}"
+                                                        if let Some(attachment) = msg.attachment.clone() {
+                                                            AttachmentPreview { attachment, is_self }
+                                                        } else {
+                                                            div {
+                                                                class: "prose prose-sm dark:prose-invert max-w-none [overflow-wrap:anywhere]",
+                                                                dangerous_inner_html: "{msg.content_html}"
+                                                            }
                                                         }
                                                         if msg.edited {
                                                             span {
@@ -5656,6 +6051,31 @@ fn MessageGroupComponent(
                                         }
                                     }
                                 }
+                                // Thread summary under a root with replies; opens
+                                // the thread panel.
+                                if msg.thread.replies > 0 {
+                                    {
+                                        let root = msg.message_id.clone();
+                                        let replies = msg.thread.replies;
+                                        let unread = msg.thread.unread;
+                                        let label = if replies == 1 { "1 reply".to_string() } else { format!("{replies} replies") };
+                                        rsx! {
+                                            button {
+                                                class: "mt-0.5 inline-flex items-center gap-1.5 text-xs font-medium text-accent hover:underline",
+                                                "data-testid": "thread-replies-button",
+                                                onclick: move |_| on_open_thread.call(root.clone()),
+                                                "{label}"
+                                                if unread > 0 {
+                                                    span {
+                                                        class: "flex items-center justify-center min-w-4 h-4 px-1 rounded-full bg-accent text-white text-[10px] font-semibold leading-none",
+                                                        title: "{unread} unread",
+                                                        "{unread}"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
                             }
                         }
                     })
@@ -8402,6 +8822,8 @@ mod group_messages_clock_tests {
             // about the clock, and an empty checker keeps them about only that.
             &ImpersonationChecker::default(),
             me,
+            &HashMap::new(),
+            &HashSet::new(),
             MessageClock {
                 receive_times,
                 fallback_now,
@@ -8646,6 +9068,131 @@ mod group_messages_clock_tests {
     }
 }
 
+#[cfg(test)]
+mod thread_tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use std::time::{Duration as StdDuration, SystemTime, UNIX_EPOCH};
+
+    fn signed(
+        sk: &SigningKey,
+        owner: MemberId,
+        secs: u64,
+        content: RoomMessageBody,
+    ) -> AuthorizedMessageV1 {
+        AuthorizedMessageV1::new(
+            MessageV1 {
+                room_owner: owner,
+                author: MemberId::from(&sk.verifying_key()),
+                time: UNIX_EPOCH + StdDuration::from_secs(1_700_000_000 + secs),
+                content,
+            },
+            sk,
+        )
+    }
+
+    fn reply_to(target: &AuthorizedMessageV1) -> RoomMessageBody {
+        RoomMessageBody::reply(
+            "reply".to_string(),
+            target.id(),
+            "someone".to_string(),
+            "preview".to_string(),
+        )
+    }
+
+    fn grouped(
+        messages: &MessagesV1,
+        me: MemberId,
+        thread_read: &HashMap<MessageId, SystemTime>,
+        blocked: &HashSet<MemberId>,
+    ) -> Vec<DisplayItem> {
+        let receive_times = ReceiveTimes::default();
+        group_messages(
+            messages,
+            &MemberInfoV1::default(),
+            me,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &ImpersonationChecker::default(),
+            me,
+            thread_read,
+            blocked,
+            MessageClock {
+                receive_times: &receive_times,
+                fallback_now: Utc::now(),
+            },
+        )
+    }
+
+    #[test]
+    fn replies_are_counted_on_the_root_and_listed_in_its_thread() {
+        let me_sk = SigningKey::from_bytes(&[1; 32]);
+        let alice_sk = SigningKey::from_bytes(&[2; 32]);
+        let me = MemberId::from(&me_sk.verifying_key());
+
+        let root = signed(&me_sk, me, 0, RoomMessageBody::public("root".to_string()));
+        let first = signed(&alice_sk, me, 1, reply_to(&root));
+        let nested = signed(&alice_sk, me, 2, reply_to(&first));
+        let messages = MessagesV1 {
+            messages: vec![root.clone(), first.clone(), nested.clone()],
+            actions_state: Default::default(),
+        };
+
+        // Never opened: a reply count but no unread count.
+        let items = grouped(&messages, me, &HashMap::new(), &HashSet::new());
+        let entries = thread_entries(&items, &root.id());
+        let ids: Vec<_> = entries
+            .iter()
+            .map(|e| e.message.message_id.clone())
+            .collect();
+        assert_eq!(ids, vec![root.id(), first.id(), nested.id()]);
+        let depths: Vec<_> = entries.iter().map(|e| e.message.thread.depth).collect();
+        assert_eq!(depths, vec![0, 1, 2]);
+        assert_eq!(entries[0].message.thread.replies, 2);
+        assert_eq!(entries[0].message.thread.unread, 0);
+        assert_eq!(entries[2].message.thread.replies, 0);
+
+        // Opened after the first reply: only the nested one is unread.
+        let read = HashMap::from([(root.id(), first.message.time)]);
+        let items = grouped(&messages, me, &read, &HashSet::new());
+        let entries = thread_entries(&items, &root.id());
+        assert_eq!(entries[0].message.thread.unread, 1);
+    }
+
+    #[test]
+    fn a_blocked_members_messages_and_replies_are_left_out() {
+        let me_sk = SigningKey::from_bytes(&[1; 32]);
+        let alice_sk = SigningKey::from_bytes(&[2; 32]);
+        let bob_sk = SigningKey::from_bytes(&[3; 32]);
+        let me = MemberId::from(&me_sk.verifying_key());
+        let alice = MemberId::from(&alice_sk.verifying_key());
+
+        let root = signed(&me_sk, me, 0, RoomMessageBody::public("root".to_string()));
+        let heckle = signed(&alice_sk, me, 1, reply_to(&root));
+        let answer = signed(&bob_sk, me, 2, reply_to(&root));
+        let messages = MessagesV1 {
+            messages: vec![root.clone(), heckle, answer.clone()],
+            actions_state: Default::default(),
+        };
+
+        let read = HashMap::from([(root.id(), root.message.time)]);
+        let items = grouped(&messages, me, &read, &HashSet::from([alice]));
+        let entries = thread_entries(&items, &root.id());
+        let ids: Vec<_> = entries
+            .iter()
+            .map(|e| e.message.message_id.clone())
+            .collect();
+        assert_eq!(ids, vec![root.id(), answer.id()]);
+        assert_eq!(entries[0].message.thread.replies, 1);
+        assert_eq!(entries[0].message.thread.unread, 1);
+        assert!(items.iter().all(|item| match item {
+            DisplayItem::Messages(group) => group.author_id != alice,
+            _ => true,
+        }));
+    }
+}
+
 /// Source-grep pins for the auto-scroll wiring in [`Conversation`].
 ///
 /// The behaviour these guard is only observable in a browser (it is measured
@@ -0,0 +1,159 @@
+use super::clean_reply_preview;
+use crate::components::app::message_archive::MESSAGE_ARCHIVES;
+use crate::components::app::{CURRENT_ROOM, ROOMS};
+use crate::util::display_name::display_nickname;
+use crate::util::format_utc_as_full_datetime;
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+use dioxus_free_icons::icons::fa_solid_icons::FaXmark;
+use dioxus_free_icons::Icon;
+use river_core::room_state::member::MemberId;
+use river_core::room_state::message::MessageId;
+use river_core::search::SearchQuery;
+use std::collections::{HashMap, HashSet};
+
+/// Archived messages revealed per "Show earlier" click.
+const PAGE_SIZE: usize = 50;
+
+/// One archived message, ready to render.
+#[derive(Clone, PartialEq)]
+struct ArchiveEntry {
+    message_id: MessageId,
+    author_name: String,
+    time: DateTime<Utc>,
+    text: String,
+}
+
+/// Messages the room no longer holds, oldest first, and whether the stored
+/// archive has finished loading.
+type ArchiveView = (Vec<ArchiveEntry>, bool);
+
+/// Side panel listing the open room's archived history: every message this
+/// client has seen that has since fallen out of the room's recent messages.
+/// Edits and deletes are replayed from the archived actions, and private
+/// bodies are decrypted with the room secrets.
+#[component]
+pub(super) fn ArchivePanel(on_close: EventHandler<()>) -> Element {
+    let mut shown = use_signal(|| PAGE_SIZE);
+
+    let view = use_memo(move || -> Option<ArchiveView> {
+        // Anchor before the fallible reads; see `signal_guard` (freenet/river#555).
+        crate::util::signal_guard::anchor();
+        let key = CURRENT_ROOM.read().owner_key?;
+        let Ok(rooms) = ROOMS.try_read() else {
+            crate::util::signal_guard::schedule_nudge();
+            return None;
+        };
+        let Ok(archives) = MESSAGE_ARCHIVES.try_read() else {
+            crate::util::signal_guard::schedule_nudge();
+            return None;
+        };
+        let room_data = rooms.map.get(&key)?;
+        let Some(room_archive) = archives.get(&key) else {
+            return Some((Vec::new(), false));
+        };
+
+        let mut messages = room_archive.archive.to_messages();
+        if room_data.is_private() {
+            let decrypted = room_data.decrypt_private_actions(&messages);
+            messages.rebuild_actions_state_with_decrypted(&decrypted);
+        }
+        let member_names: HashMap<MemberId, String> = room_data
+            .room_state
+            .member_info
+            .member_info
+            .iter()
+            .map(|ami| {
+                (
+                    ami.member_info.member_id,
+                    display_nickname(&ami.member_info.preferred_nickname, &room_data.secrets),
+                )
+            })
+            .collect();
+        // Only what the conversation can no longer show.
+        let live: HashSet<MessageId> = room_data
+            .room_state
+            .recent_messages
+            .messages
+            .iter()
+            .map(|m| m.id())
+            .collect();
+
+        let entries = messages
+            .search_with_secrets(&SearchQuery::default(), &room_data.secrets)
+            .into_iter()
+            .filter(|hit| !live.contains(&hit.message.id()))
+            .map(|hit| {
+                let author = hit.message.message.author;
+                ArchiveEntry {
+                    message_id: hit.message.id(),
+                    author_name: member_names
+                        .get(&author)
+                        .cloned()
+                        .unwrap_or_else(|| author.to_string()),
+                    time: DateTime::<Utc>::from(hit.message.message.time),
+                    text: clean_reply_preview(&hit.text, &member_names),
+                }
+            })
+            .collect();
+        Some((entries, room_archive.loaded))
+    });
+
+    let (entries, loaded) = view.read().clone().unwrap_or_default();
+    let start = entries.len().saturating_sub(shown());
+
+    rsx! {
+        div {
+            class: "fixed inset-y-0 right-0 z-40 w-full md:w-96 flex flex-col bg-panel border-l border-border shadow-xl",
+            "data-testid": "archive-panel",
+            div { class: "flex-shrink-0 flex items-center justify-between gap-2 px-4 py-3 border-b border-border",
+                div { class: "min-w-0",
+                    h3 { class: "text-base font-semibold text-text", "Archived history" }
+                    p { class: "text-xs text-text-muted",
+                        "Older messages kept on this device after the room dropped them."
+                    }
+                }
+                button {
+                    class: "p-1.5 rounded-lg text-text-muted hover:text-accent hover:bg-surface transition-colors",
+                    "aria-label": "Close archived history",
+                    onclick: move |_| on_close.call(()),
+                    Icon { icon: FaXmark, width: 16, height: 16 }
+                }
+            }
+            div { class: "flex-1 overflow-y-auto px-4 py-3 space-y-3",
+                if start > 0 {
+                    button {
+                        class: "w-full py-1.5 rounded-lg text-sm text-accent hover:bg-surface transition-colors",
+                        onclick: move |_| shown += PAGE_SIZE,
+                        "Show earlier ({start} more)"
+                    }
+                }
+                if entries.is_empty() {
+                    p { class: "text-sm text-text-muted",
+                        if loaded {
+                            "Nothing archived beyond what the room still holds."
+                        } else {
+                            "Loading the archive…"
+                        }
+                    }
+                }
+                for entry in entries.into_iter().skip(start) {
+                    {
+                        let time_str = format_utc_as_full_datetime(entry.time.timestamp_millis());
+                        rsx! {
+                            div {
+                                key: "{entry.message_id:?}",
+                                "data-testid": "archived-message",
+                                div { class: "flex items-baseline gap-2",
+                                    span { class: "text-sm font-medium text-text", "{entry.author_name}" }
+                                    span { class: "text-xs text-text-muted", "{time_str}" }
+                                }
+                                p { class: "text-sm text-text whitespace-pre-wrap [overflow-wrap:anywhere]", "{entry.text}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::components::app::freenet_api::blob_fetch::{request_blob, BlobFetch, BLOB_CACHE};
+use base64::Engine;
+use dioxus::prelude::*;
+use freenet_stdlib::prelude::ContractInstanceId;
+use river_core::room_state::content::BlobContentV1;
+use std::collections::HashMap;
+
+/// An attachment message (`CONTENT_TYPE_BLOB`) with what is needed to open it.
+#[derive(Clone, PartialEq)]
+pub(super) struct Attachment {
+    pub blob: BlobContentV1,
+    /// The room secrets, for unsealing the per-attachment key in a private
+    /// room. Empty for a public room.
+    pub secrets: HashMap<u32, [u8; 32]>,
+}
+
+/// Inline preview of an attachment: the image itself for `image/*`, a
+/// download link for anything else. The blob is fetched on first render and
+/// checked against its content hash before it is shown.
+#[component]
+pub(super) fn AttachmentPreview(attachment: Attachment, is_self: bool) -> Element {
+    let contract_id = attachment.blob.contract_id;
+    use_effect(move || request_blob(contract_id));
+
+    let blob = &attachment.blob;
+    let summary = blob.summary();
+    let muted = if is_self {
+        "text-white/70"
+    } else {
+        "text-text-muted"
+    };
+
+    let fetched = BLOB_CACHE
+        .read()
+        .get(&ContractInstanceId::new(contract_id))
+        .cloned();
+    let opened = match fetched {
+        None | Some(BlobFetch::Pending) => None,
+        Some(BlobFetch::Failed(e)) => Some(Err(e)),
+        Some(BlobFetch::Fetched(stored)) => Some(river_core::blob::open_blob(
+            blob,
+            &stored,
+            &attachment.secrets,
+        )),
+    };
+
+    match opened {
+        None => rsx! {
+            div { class: "text-sm {muted}", "{summary} — loading…" }
+        },
+        Some(Err(e)) => rsx! {
+            div {
+                class: "text-sm {muted}",
+                title: "{e}",
+                "{summary} — unavailable"
+            }
+        },
+        Some(Ok(data)) => {
+            let encoded = base64::engine::general_purpose::STANDARD.encode(&data);
+            if blob.is_image() {
+                rsx! {
+                    img {
+                        class: "max-w-full max-h-80 rounded",
+                        src: "data:{blob.mime_type};base64,{encoded}",
+                        alt: "{blob.filename}",
+                    }
+                }
+            } else {
+                // Offered as an opaque download whatever the declared type, so
+                // a sender-chosen MIME type (e.g. text/html) is never rendered.
+                rsx! {
+                    a {
+                        class: "text-sm underline",
+                        href: "data:application/octet-stream;base64,{encoded}",
+                        download: "{blob.filename}",
+                        "{summary}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -140,6 +140,9 @@ pub fn MessageInput(
     /// name. Drives the `@` autocomplete. Changes only when membership changes,
     /// so it does not affect keystroke-level re-rendering.
     members: Vec<(MemberId, String)>,
+    /// Set when the user may not post (muted, or left out by the room's
+    /// posting policy); the composer is replaced by this explanation.
+    posting_restriction: Option<&'static str>,
 ) -> Element {
     // Own the message state locally - keystrokes only re-render this component
     let mut message_text = use_signal(String::new);
@@ -168,6 +171,16 @@ pub fn MessageInput(
         message_text.set(format!("{}{}", current, emoji));
     };
 
+    if let Some(restriction) = posting_restriction {
+        return rsx! {
+            div {
+                class: "px-4 py-3 mx-4 mb-4 bg-surface rounded-lg text-sm text-text-muted",
+                "data-testid": "posting-restricted-notice",
+                "{restriction}"
+            }
+        };
+    }
+
     rsx! {
         // Backdrop for emoji picker - outside the message bar to avoid z-index issues
         if show_emoji_picker() {
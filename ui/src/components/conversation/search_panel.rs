@@ -0,0 +1,254 @@
+use super::clean_reply_preview;
+use crate::components::app::{CURRENT_ROOM, ROOMS};
+use crate::util::display_name::display_nickname;
+use crate::util::{format_utc_as_full_datetime, local_message_date};
+use chrono::{DateTime, NaiveDate, Utc};
+use dioxus::prelude::*;
+use dioxus_free_icons::icons::fa_solid_icons::FaXmark;
+use dioxus_free_icons::Icon;
+use river_core::room_state::content::{
+    CONTENT_TYPE_BLOB, CONTENT_TYPE_POLL, CONTENT_TYPE_REPLY, CONTENT_TYPE_TEXT,
+};
+use river_core::room_state::member::MemberId;
+use river_core::room_state::message::MessageId;
+use river_core::search::SearchQuery;
+use std::collections::HashMap;
+
+/// Most results listed; the newest are kept.
+const MAX_RESULTS: usize = 100;
+
+/// Characters of message text shown per result.
+const SNIPPET_CHARS: usize = 200;
+
+/// One search result, ready to render.
+#[derive(Clone, PartialEq)]
+struct SearchResult {
+    message_id: MessageId,
+    author_name: String,
+    time: DateTime<Utc>,
+    snippet: String,
+}
+
+/// The author picker's members, and the results (`None` while there is
+/// nothing to search for).
+type SearchView = (Vec<(MemberId, String)>, Option<Vec<SearchResult>>);
+
+/// The `type="date"` input value as a date, if one is set.
+fn parse_date_input(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// Side panel for searching the open room's history. Matching runs through
+/// `river_core::search`, with private bodies decrypted; the date range is
+/// applied to the reader's local calendar days here, since that is what the
+/// date inputs mean.
+#[component]
+pub(super) fn SearchPanel(on_close: EventHandler<()>, on_open: EventHandler<MessageId>) -> Element {
+    let mut text = use_signal(String::new);
+    let mut author = use_signal(|| None::<MemberId>);
+    let mut mentions_me = use_signal(|| false);
+    let mut content_type = use_signal(|| None::<u32>);
+    let mut from_date = use_signal(String::new);
+    let mut to_date = use_signal(String::new);
+
+    let search = use_memo(move || -> Option<SearchView> {
+        // Anchor before the fallible read; see `signal_guard` (freenet/river#555).
+        crate::util::signal_guard::anchor();
+        let key = CURRENT_ROOM.read().owner_key?;
+        let Ok(rooms) = ROOMS.try_read() else {
+            crate::util::signal_guard::schedule_nudge();
+            return None;
+        };
+        let room_data = rooms.map.get(&key)?;
+        let room_state = &room_data.room_state;
+        let self_id = MemberId::from(&room_data.self_sk.verifying_key());
+
+        let member_names: HashMap<MemberId, String> = room_state
+            .member_info
+            .member_info
+            .iter()
+            .map(|ami| {
+                (
+                    ami.member_info.member_id,
+                    display_nickname(&ami.member_info.preferred_nickname, &room_data.secrets),
+                )
+            })
+            .collect();
+        let mut members: Vec<(MemberId, String)> = member_names
+            .iter()
+            .map(|(id, name)| (*id, name.clone()))
+            .collect();
+        members.sort_by(|a, b| a.1.to_lowercase().cmp(&b.1.to_lowercase()));
+
+        let query = SearchQuery {
+            text: text(),
+            author: author(),
+            mentions: mentions_me().then_some(self_id),
+            content_type: content_type(),
+            ..Default::default()
+        };
+        let from = parse_date_input(&from_date());
+        let to = parse_date_input(&to_date());
+        if query.is_empty() && from.is_none() && to.is_none() {
+            return Some((members, None));
+        }
+
+        let mut results: Vec<SearchResult> = room_state
+            .recent_messages
+            .search_with_secrets(&query, &room_data.secrets)
+            .into_iter()
+            .map(|hit| {
+                let time = DateTime::<Utc>::from(hit.message.message.time);
+                (hit, time)
+            })
+            .filter(|(_, time)| {
+                let day = local_message_date(time.timestamp_millis());
+                from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to)
+            })
+            .map(|(hit, time)| SearchResult {
+                message_id: hit.message.id(),
+                author_name: member_names
+                    .get(&hit.message.message.author)
+                    .cloned()
+                    .unwrap_or_else(|| hit.message.message.author.to_string()),
+                time,
+                snippet: clean_reply_preview(&hit.text, &member_names)
+                    .chars()
+                    .take(SNIPPET_CHARS)
+                    .collect(),
+            })
+            .collect();
+        // Newest first, which is what a reader looking something up wants.
+        results.reverse();
+        results.truncate(MAX_RESULTS);
+        Some((members, Some(results)))
+    });
+
+    let (members, results) = search.read().clone().unwrap_or_default();
+    let input_class = "w-full px-2 py-1.5 rounded-lg bg-surface border border-border text-sm text-text focus:outline-none focus:border-accent";
+
+    rsx! {
+        div {
+            class: "fixed inset-y-0 right-0 z-40 w-full md:w-96 flex flex-col bg-panel border-l border-border shadow-xl",
+            "data-testid": "search-panel",
+            div { class: "flex-shrink-0 flex items-center justify-between gap-2 px-4 py-3 border-b border-border",
+                h3 { class: "text-base font-semibold text-text", "Search" }
+                button {
+                    class: "p-1.5 rounded-lg text-text-muted hover:text-accent hover:bg-surface transition-colors",
+                    "aria-label": "Close search",
+                    onclick: move |_| on_close.call(()),
+                    Icon { icon: FaXmark, width: 16, height: 16 }
+                }
+            }
+            div { class: "flex-shrink-0 px-4 py-3 space-y-2 border-b border-border",
+                input {
+                    class: input_class,
+                    "data-testid": "search-input",
+                    r#type: "search",
+                    placeholder: "Search messages",
+                    autofocus: true,
+                    value: "{text}",
+                    oninput: move |e| text.set(e.value()),
+                }
+                div { class: "flex gap-2",
+                    select {
+                        class: input_class,
+                        "aria-label": "Author",
+                        onchange: move |e| {
+                            let value = e.value();
+                            author.set(members_by_index(&search, &value));
+                        },
+                        option { value: "", "Anyone" }
+                        for (index, (_, name)) in members.iter().enumerate() {
+                            option { value: "{index}", "{name}" }
+                        }
+                    }
+                    select {
+                        class: input_class,
+                        "aria-label": "Message type",
+                        onchange: move |e| {
+                            content_type.set(match e.value().as_str() {
+                                "text" => Some(CONTENT_TYPE_TEXT),
+                                "reply" => Some(CONTENT_TYPE_REPLY),
+                                "poll" => Some(CONTENT_TYPE_POLL),
+                                "attachment" => Some(CONTENT_TYPE_BLOB),
+                                _ => None,
+                            });
+                        },
+                        option { value: "", "Any type" }
+                        option { value: "text", "Messages" }
+                        option { value: "reply", "Replies" }
+                        option { value: "poll", "Polls" }
+                        option { value: "attachment", "Attachments" }
+                    }
+                }
+                div { class: "flex items-center gap-2 text-xs text-text-muted",
+                    input {
+                        class: input_class,
+                        "aria-label": "From date",
+                        r#type: "date",
+                        value: "{from_date}",
+                        oninput: move |e| from_date.set(e.value()),
+                    }
+                    span { "–" }
+                    input {
+                        class: input_class,
+                        "aria-label": "To date",
+                        r#type: "date",
+                        value: "{to_date}",
+                        oninput: move |e| to_date.set(e.value()),
+                    }
+                }
+                label { class: "flex items-center gap-2 text-sm text-text-muted",
+                    input {
+                        r#type: "checkbox",
+                        checked: mentions_me(),
+                        onchange: move |e| mentions_me.set(e.checked()),
+                    }
+                    "Mentions of me"
+                }
+            }
+            div { class: "flex-1 overflow-y-auto px-4 py-3 space-y-3",
+                match results {
+                    None => rsx! {
+                        p { class: "text-sm text-text-muted", "Type a word or pick a filter to search this room." }
+                    },
+                    Some(results) if results.is_empty() => rsx! {
+                        p { class: "text-sm text-text-muted", "No matching messages." }
+                    },
+                    Some(results) => rsx! {
+                        for result in results {
+                            {
+                                let time_str = format_utc_as_full_datetime(result.time.timestamp_millis());
+                                let message_id = result.message_id.clone();
+                                rsx! {
+                                    button {
+                                        key: "{result.message_id:?}",
+                                        class: "block w-full text-left p-2 rounded-lg hover:bg-surface transition-colors",
+                                        "data-testid": "search-result",
+                                        onclick: move |_| on_open.call(message_id.clone()),
+                                        div { class: "flex items-baseline gap-2",
+                                            span { class: "text-sm font-medium text-text", "{result.author_name}" }
+                                            span { class: "text-xs text-text-muted", "{time_str}" }
+                                        }
+                                        p { class: "text-sm text-text-muted [overflow-wrap:anywhere]", "{result.snippet}" }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// The member at position `value` in the author picker, which lists the
+/// memo's members in order; `None` for "Anyone".
+fn members_by_index(search: &Memo<Option<SearchView>>, value: &str) -> Option<MemberId> {
+    let index: usize = value.parse().ok()?;
+    search
+        .peek()
+        .as_ref()
+        .and_then(|(members, _)| members.get(index).map(|(id, _)| *id))
+}
@@ -0,0 +1,88 @@
+use super::attachment_preview::AttachmentPreview;
+use super::{clean_reply_preview, ReplyContext, ThreadEntry};
+use crate::util::format_utc_as_local_time;
+use dioxus::prelude::*;
+use dioxus_free_icons::icons::fa_solid_icons::{FaReply, FaXmark};
+use dioxus_free_icons::Icon;
+use river_core::room_state::member::MemberId;
+use std::collections::HashMap;
+
+/// Deepest indentation step; deeper replies line up with it so a long chain
+/// of replies-to-replies does not squeeze the text off the panel.
+const MAX_INDENT_LEVEL: usize = 4;
+
+/// Side panel with one reply thread: the root message and every reply under
+/// it, oldest first, each indented by its reply depth.
+#[component]
+pub(super) fn ThreadPanel(
+    entries: Vec<ThreadEntry>,
+    member_names: HashMap<MemberId, String>,
+    on_close: EventHandler<()>,
+    on_reply: EventHandler<ReplyContext>,
+) -> Element {
+    let replies = entries.len().saturating_sub(1);
+    let reply_label = if replies == 1 { "reply" } else { "replies" };
+
+    rsx! {
+        div {
+            class: "fixed inset-y-0 right-0 z-40 w-full md:w-96 flex flex-col bg-panel border-l border-border shadow-xl",
+            "data-testid": "thread-panel",
+            div { class: "flex-shrink-0 flex items-center justify-between gap-2 px-4 py-3 border-b border-border",
+                div { class: "flex items-baseline gap-2 min-w-0",
+                    h3 { class: "text-base font-semibold text-text", "Thread" }
+                    span { class: "text-xs text-text-muted", "{replies} {reply_label}" }
+                }
+                button {
+                    class: "p-1.5 rounded-lg text-text-muted hover:text-accent hover:bg-surface transition-colors",
+                    "aria-label": "Close thread",
+                    "data-testid": "thread-panel-close",
+                    onclick: move |_| on_close.call(()),
+                    Icon { icon: FaXmark, width: 16, height: 16 }
+                }
+            }
+            div { class: "flex-1 overflow-y-auto px-4 py-3 space-y-3",
+                {entries.into_iter().map(|entry| {
+                    let msg = entry.message;
+                    let indent = msg.thread.depth.min(MAX_INDENT_LEVEL);
+                    let time_str = format_utc_as_local_time(msg.time.timestamp_millis());
+                    let reply_ctx = ReplyContext {
+                        message_id: msg.message_id.clone(),
+                        author_name: entry.author_name.clone(),
+                        content_preview: clean_reply_preview(&msg.content_text, &member_names)
+                            .chars()
+                            .take(100)
+                            .collect(),
+                    };
+                    rsx! {
+                        div {
+                            key: "{msg.id}",
+                            class: if indent > 0 { "border-l-2 border-border pl-3" } else { "" },
+                            style: "margin-left: {indent * 12}px",
+                            div { class: "flex items-baseline gap-2",
+                                span {
+                                    class: if entry.is_self { "text-sm font-medium text-accent" } else { "text-sm font-medium text-text" },
+                                    "{entry.author_name}"
+                                }
+                                span { class: "text-xs text-text-muted", "{time_str}" }
+                                button {
+                                    class: "ml-auto text-text-muted hover:text-accent transition-colors",
+                                    title: "Reply",
+                                    onclick: move |_| on_reply.call(reply_ctx.clone()),
+                                    Icon { icon: FaReply, width: 12, height: 12 }
+                                }
+                            }
+                            if let Some(attachment) = msg.attachment.clone() {
+                                AttachmentPreview { attachment, is_self: false }
+                            } else {
+                                div {
+                                    class: "prose prose-sm dark:prose-invert max-w-none [overflow-wrap:anywhere]",
+                                    dangerous_inner_html: "{msg.content_html}"
+                                }
+                            }
+                        }
+                    }
+                })}
+            }
+        }
+    }
+}
@@ -11,8 +11,15 @@
 //! that (room, peer). Replaces the earlier per-room inbox button in the
 //! members panel (zorolin feedback, 2026-05-16).
 //!
-//! Persistence model: all message state lives in `ChatRoomStateV1`. This
-//! module only adds *view* state — currently open thread, last-seen
+//! Group DMs (`river_core::group_dm`) between three or more members open in
+//! the same modal, addressed by their participant set instead of one peer
+//! ([`OPEN_GROUP_DM_THREAD`]), and get rail rows of their own.
+//!
+//! Persistence model: DMs to the local user live in their inbox contract in
+//! each room (see [`crate::components::app::freenet_api::inbox_sync`]), DMs
+//! from older clients may still be in `ChatRoomStateV1`, and the user's own
+//! sent DMs are rendered from the outbound plaintext cache. This module
+//! only adds *view* state — currently open thread, last-seen
 //! timestamps per peer for unread tracking. Last-seen state is purely
 //! in-memory; reloading the page seeds it from the room state (see
 //! [`seed_dm_last_seen_if_needed`]) so previously-read DMs don't pop
@@ -24,17 +31,33 @@ mod invite_via_dm_picker_modal;
 pub use dm_thread_modal::DmThreadModal;
 pub use invite_via_dm_picker_modal::InviteViaDmPickerModal;
 
+use crate::components::app::freenet_api::inbox_sync::{send_receipts, INBOXES};
+use crate::room_data::RoomData;
 use dioxus::prelude::*;
 use ed25519_dalek::VerifyingKey;
 use river_core::chat_delegate::{HiddenDmThreadEntry, OutboundDmEntry};
-use river_core::room_state::direct_messages::PurgeToken;
+use river_core::group_dm::{participant_set, AuthorizedGroupDirectMessage};
+use river_core::inbox::InboxStateV1;
+use river_core::room_state::direct_messages::{AuthorizedDirectMessage, PurgeToken};
 use river_core::room_state::member::MemberId;
-use std::collections::HashMap;
+use river_core::room_state::ChatRoomParametersV1;
+use std::collections::{HashMap, HashSet};
 
 /// Currently-open DM thread, addressed by (room_owner_vk, counterparty).
 /// `None` means no DM modal is open.
 pub static OPEN_DM_THREAD: GlobalSignal<Option<(VerifyingKey, MemberId)>> = Global::new(|| None);
 
+/// Currently-open group DM thread, addressed by (room_owner_vk, participant
+/// set including the local member). Never open at the same time as
+/// [`OPEN_DM_THREAD`].
+pub static OPEN_GROUP_DM_THREAD: GlobalSignal<Option<(VerifyingKey, Vec<MemberId>)>> =
+    Global::new(|| None);
+
+/// Per-(room, participant set) counterpart of [`DM_LAST_SEEN`] for group
+/// threads: group DMs from others newer than this are unread.
+pub static GROUP_DM_LAST_SEEN: GlobalSignal<HashMap<(VerifyingKey, Vec<MemberId>), u64>> =
+    Global::new(HashMap::new);
+
 /// Per-(room, peer) timestamp (unix seconds) of the most recent DM the local
 /// user has actually viewed in [`DmThreadModal`]. Anything in
 /// `room.direct_messages.messages` addressed to the local user with
@@ -61,7 +84,8 @@ pub(crate) fn thread_read_needs_write(current: Option<u64>, up_to_ts: u64) -> bo
 }
 
 /// Mark every DM from `peer` in `room` as seen up to (and including) the
-/// most recent inbound message timestamp known to the synchronizer.
+/// most recent inbound message timestamp known to the synchronizer, and tell
+/// the peer with a read receipt.
 pub fn mark_thread_read(room: VerifyingKey, peer: MemberId, up_to_ts: u64) {
     crate::util::defer(move || {
         // Skip the write when the stored cutoff would not advance —
@@ -91,6 +115,9 @@ pub fn mark_thread_read(room: VerifyingKey, peer: MemberId, up_to_ts: u64) {
                 *entry = up_to_ts;
             }
         });
+        // Only here, never from the page-load seeding: the peer learns the
+        // thread was read because it was actually open.
+        send_receipts(room, peer, 0, up_to_ts);
     });
 }
 
@@ -98,10 +125,41 @@ pub fn mark_thread_read(room: VerifyingKey, peer: MemberId, up_to_ts: u64) {
 /// thread first.
 pub fn open_dm_thread(room: VerifyingKey, peer: MemberId) {
     crate::util::defer(move || {
+        *OPEN_GROUP_DM_THREAD.write() = None;
         *OPEN_DM_THREAD.write() = Some((room, peer));
     });
 }
 
+/// Open the group thread between `participants` (the local member may be
+/// left out; the set is normalised) in `room`, closing any other thread.
+pub fn open_group_dm_thread(room: VerifyingKey, participants: Vec<MemberId>) {
+    let participants = participant_set(participants);
+    crate::util::defer(move || {
+        *OPEN_DM_THREAD.write() = None;
+        *OPEN_GROUP_DM_THREAD.write() = Some((room, participants));
+    });
+}
+
+/// Group counterpart of [`mark_thread_read`], gated the same way.
+pub fn mark_group_thread_read(room: VerifyingKey, participants: Vec<MemberId>, up_to_ts: u64) {
+    crate::util::defer(move || {
+        let key = (room, participants);
+        let needs_write = GROUP_DM_LAST_SEEN
+            .try_peek()
+            .map(|seen| thread_read_needs_write(seen.get(&key).copied(), up_to_ts))
+            .unwrap_or(false);
+        if !needs_write {
+            return;
+        }
+        GROUP_DM_LAST_SEEN.with_mut(|seen| {
+            let entry = seen.entry(key).or_insert(0);
+            if up_to_ts > *entry {
+                *entry = up_to_ts;
+            }
+        });
+    });
+}
+
 /// "Share an invite via DM…" picker state. When `Some((room, peer))`, the
 /// [`InviteViaDmPickerModal`] is visible and offers to generate an invite
 /// for ANOTHER room and pre-fill a DM to `peer` in `room` with the invite
@@ -241,6 +299,117 @@ pub fn lookup_outbound_plaintext(
         .ok_or(())
 }
 
+/// The DMs to the local member in `room_data`: those held by their inbox
+/// (less banned senders), plus any still in the room state that the inbox
+/// does not hold yet (sent by an older client, not migrated yet).
+pub(crate) fn inbound_dms<'a>(
+    room_data: &'a RoomData,
+    inbox: Option<&'a InboxStateV1>,
+) -> Vec<&'a AuthorizedDirectMessage> {
+    let self_id = MemberId::from(&room_data.self_sk.verifying_key());
+    let params = ChatRoomParametersV1 {
+        owner: room_data.owner_vk,
+    };
+    let mut dms: Vec<&AuthorizedDirectMessage> = inbox
+        .map(|i| i.visible_messages(&room_data.room_state, &params).collect())
+        .unwrap_or_default();
+    let held: HashSet<PurgeToken> = dms.iter().map(|m| m.purge_token()).collect();
+    dms.extend(
+        room_data
+            .room_state
+            .direct_messages
+            .messages
+            .iter()
+            .filter(|m| m.message.recipient == self_id && !held.contains(&m.purge_token())),
+    );
+    dms
+}
+
+/// Every DM the local member can see in `room_data` as `(sender,
+/// recipient, timestamp)`: [`inbound_dms`] less those from `blocked`,
+/// outbound DMs still in the room state, and outbound DMs known only from
+/// the plaintext cache (sent to an inbox, which only its recipient reads).
+pub(crate) fn dm_triples(
+    room_data: &RoomData,
+    inbox: Option<&InboxStateV1>,
+    outbound: Option<&OutboundDmsCache>,
+    blocked: &HashSet<MemberId>,
+) -> Vec<(MemberId, MemberId, u64)> {
+    let self_id = MemberId::from(&room_data.self_sk.verifying_key());
+    let room_outbound: Vec<&AuthorizedDirectMessage> = room_data
+        .room_state
+        .direct_messages
+        .messages
+        .iter()
+        .filter(|m| m.message.sender == self_id)
+        .collect();
+    let in_room: HashSet<PurgeToken> = room_outbound.iter().map(|m| m.purge_token()).collect();
+    let mut triples: Vec<(MemberId, MemberId, u64)> = inbound_dms(room_data, inbox)
+        .into_iter()
+        .filter(|m| !blocked.contains(&m.message.sender))
+        .chain(room_outbound)
+        .map(|m| (m.message.sender, m.message.recipient, m.message.timestamp))
+        .collect();
+    if let Some(cache) = outbound {
+        triples.extend(
+            cache
+                .by_token
+                .iter()
+                .filter(|((room, _, token), entry)| {
+                    *room == room_data.owner_vk
+                        && entry.sender == self_id
+                        && !in_room.contains(token)
+                })
+                .map(|(_, entry)| (entry.sender, entry.recipient, entry.timestamp)),
+        );
+    }
+    triples
+}
+
+/// One group conversation as the rail lists it.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct GroupDmThread {
+    pub(crate) participants: Vec<MemberId>,
+    pub(crate) last_ts: u64,
+    /// Group DMs from others newer than the thread's
+    /// [`GROUP_DM_LAST_SEEN`] entry.
+    pub(crate) unread: usize,
+}
+
+/// Group `messages` (those visible in `room`'s inbox) into threads by
+/// participant set. `last_seen` is `None` when the cutoffs could not be
+/// read, which counts nothing as unread for that pass.
+pub(crate) fn group_dm_threads<'a>(
+    room: VerifyingKey,
+    self_id: MemberId,
+    messages: impl IntoIterator<Item = &'a AuthorizedGroupDirectMessage>,
+    last_seen: Option<&HashMap<(VerifyingKey, Vec<MemberId>), u64>>,
+) -> Vec<GroupDmThread> {
+    let mut threads: HashMap<&[MemberId], GroupDmThread> = HashMap::new();
+    for msg in messages {
+        let thread = threads
+            .entry(msg.message.participants.as_slice())
+            .or_insert_with(|| GroupDmThread {
+                participants: msg.message.participants.clone(),
+                last_ts: 0,
+                unread: 0,
+            });
+        thread.last_ts = thread.last_ts.max(msg.message.timestamp);
+        if msg.message.sender != self_id {
+            if let Some(seen) = last_seen {
+                let cutoff = seen
+                    .get(&(room, msg.message.participants.clone()))
+                    .copied()
+                    .unwrap_or(0);
+                if msg.message.timestamp > cutoff {
+                    thread.unread += 1;
+                }
+            }
+        }
+    }
+    threads.into_values().collect()
+}
+
 /// Open the invite-via-DM picker for the given target peer in the current
 /// room.
 ///
@@ -275,14 +444,11 @@ static DM_LAST_SEEN_SEEDED: GlobalSignal<bool> = Global::new(|| false);
 
 /// Result of [`send_structured_dm`] — surfaced to the caller so the
 /// picker / DM modal can either close + toast on success or render an
-/// inline error string. Mirrors the `ApplyOutcome` shape used inside
-/// `dm_thread_modal.rs`, but kept as its own enum here because the
-/// surface is callable from outside the modal and we don't want the
-/// modal-specific names leaking.
+/// inline error string.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SendDmOutcome {
-    /// Delta applied locally, `mark_needs_sync` queued, outbound cache
-    /// updated. The caller should clear its composer / close its modal.
+    /// PUT to the recipient's inbox queued, outbound cache updated. The
+    /// caller should clear its composer / close its modal.
     Sent,
     /// The room we tried to send in is no longer in `ROOMS` (unloaded).
     RoomGone,
@@ -291,26 +457,20 @@ pub enum SendDmOutcome {
     /// Sender == recipient.
     SelfDm,
     /// The local user has been pruned from the room's member list AND
-    /// no rejoin credentials are available. The contract would silent-
-    /// drop the DM. See `dm_thread_modal.rs`'s `SilentDrop` arm for the
-    /// full diagnostic — same root cause.
+    /// no invite chain is stored for them, so nothing can prove their
+    /// membership to the recipient's inbox contract.
     SenderMissingRejoin,
     /// Body encoding failed (CBOR serialize error) or the resulting
     /// envelope exceeds `MAX_DM_CIPHERTEXT_BYTES`. Carries the error
     /// string from the underlying helper.
     BodyTooLargeOrEncodeFailed(String),
-    /// `apply_delta` returned Err (signature / membership / tombstone /
-    /// cap inside the contract layer). Deterministic — retrying byte-
-    /// identical input gives the same result. Carries the diagnostic.
-    DeltaFailed(String),
-    /// `apply_delta` returned Ok but the message wasn't actually present
-    /// in `direct_messages.messages` after the merge. Sender or
-    /// recipient missing from members AND no rejoin bundle could fix
-    /// it. See `ApplyOutcome::SilentDrop` in `dm_thread_modal.rs`.
-    SilentDrop,
+    /// The DM failed the checks the recipient's inbox contract applies
+    /// (signature / membership chain / size). Deterministic — retrying
+    /// byte-identical input gives the same result. Carries the diagnostic.
+    Rejected(String),
 }
 
-/// Compose, locally-apply, and queue for network sync a single direct
+/// Compose and send to the recipient's inbox a single direct
 /// message with a structured `DirectMessageBody` (Text or Invite). This
 /// is the canonical send-a-DM entry point for callers that need the
 /// structured form — currently:
@@ -326,9 +486,9 @@ pub enum SendDmOutcome {
 /// re-checking the wire-byte equality.
 ///
 /// Returns an outcome the caller renders inline (no panicking, no
-/// `expect`). Side effects on `Sent`: writes to `ROOMS`, calls
-/// `mark_needs_sync`, calls `unhide_dm_thread`, persists outbound
-/// plaintext into the delegate-backed cache.
+/// `expect`). Side effects on `Sent`: queues the inbox PUT, calls
+/// `unhide_dm_thread`, persists outbound plaintext into the
+/// delegate-backed cache.
 ///
 /// **Plaintext stored in the outbound cache.** The cache key is
 /// `(room, recipient, purge_token)`; the value is the user-facing
@@ -342,10 +502,8 @@ pub async fn send_structured_dm(
     body: river_core::room_state::dm_body::DirectMessageBody,
 ) -> SendDmOutcome {
     use crate::components::app::chat_delegate::{save_outbound_dm, unhide_dm_thread};
-    use crate::components::app::{mark_needs_sync, ROOMS};
-    use freenet_scaffold::ComposableState;
-    use river_core::room_state::direct_messages::{compose_direct_message, DirectMessagesDelta};
-    use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1Delta};
+    use crate::components::app::freenet_api::inbox_sync::{own_sender_chain, send_to_inbox};
+    use crate::components::app::ROOMS;
 
     // Snapshot what we need from ROOMS. The pre-flight reads go
     // through `defer` because this function is called from
@@ -363,15 +521,15 @@ pub async fn send_structured_dm(
         self_sk: ed25519_dalek::SigningKey,
         self_id: MemberId,
         peer_vk: VerifyingKey,
-        rejoin_members: Option<river_core::room_state::member::MembersDelta>,
-        rejoin_member_info: Option<Vec<river_core::room_state::member_info::AuthorizedMemberInfo>>,
+        peer_prekey: Option<river_core::room_state::member_info::DmPrekeyV1>,
+        sender_chain: Vec<river_core::room_state::member::AuthorizedMember>,
     }
     // Boxed `Ready` variant — `PreflightSnapshot` contains a `SigningKey`
-    // and `VerifyingKey` plus two `Option<...>` rejoin fields, well over
-    // 100 bytes. The `Reject` variant is just a small enum, so the
-    // unboxed enum would carry ~200 bytes regardless of which variant
-    // is active (clippy::large_enum_variant). Box the larger variant so
-    // both fit in a discriminant + pointer.
+    // and `VerifyingKey` plus the sender chain, well over 100 bytes. The
+    // `Reject` variant is just a small enum, so the unboxed enum would
+    // carry the larger size regardless of which variant is active
+    // (clippy::large_enum_variant). Box the larger variant so both fit in
+    // a discriminant + pointer.
     enum PreflightOutcome {
         Ready(Box<PreflightSnapshot>),
         Reject(SendDmOutcome),
@@ -407,29 +565,25 @@ pub async fn send_structured_dm(
                     None => return PreflightOutcome::Reject(SendDmOutcome::RecipientNotMember),
                 }
             };
-            // NO per-pair cap guard — the contract's cap is newest-N now, so a
-            // send at the cap succeeds (evicting the pair's oldest) instead of
-            // being silently dropped. See `dm.rs`'s note for the full rationale.
-            // Rejoin bundle: matches `dm_thread_modal.rs::do_send`.
-            // Bug #1 (Ivvor, 2026-05-16) — pruned-but-invited senders
-            // silently fail without this.
-            let (rejoin_members, rejoin_member_info) = room_data.build_rejoin_delta();
-            let self_in_members = self_id == owner_id
-                || room_data
-                    .room_state
-                    .members
-                    .members
-                    .iter()
-                    .any(|m| m.member.id() == self_id);
-            if !self_in_members && rejoin_members.is_none() {
+            // NO per-pair cap guard — the inbox keeps the newest DMs per
+            // sender, so a send at the cap succeeds (evicting the sender's
+            // oldest). See `dm.rs`'s note for the full rationale.
+            // Same sender chain as `dm_thread_modal.rs::do_send`: a pruned
+            // sender proves membership with the chain stored at join time.
+            let Some(sender_chain) = own_sender_chain(&room_data) else {
                 return PreflightOutcome::Reject(SendDmOutcome::SenderMissingRejoin);
-            }
+            };
+            let peer_prekey = room_data
+                .room_state
+                .member_info
+                .canonical(peer)
+                .and_then(|info| info.member_info.dm_prekey);
             PreflightOutcome::Ready(Box::new(PreflightSnapshot {
                 self_sk,
                 self_id,
                 peer_vk,
-                rejoin_members,
-                rejoin_member_info,
+                peer_prekey,
+                sender_chain,
             }))
         })();
         let _ = preflight_tx.send(outcome);
@@ -439,17 +593,15 @@ pub async fn send_structured_dm(
         Ok(PreflightOutcome::Ready(s)) => *s,
         Ok(PreflightOutcome::Reject(r)) => return r,
         Err(_) => {
-            return SendDmOutcome::DeltaFailed(
-                "deferred preflight aborted before completion".into(),
-            );
+            return SendDmOutcome::Rejected("deferred preflight aborted before completion".into());
         }
     };
     let PreflightSnapshot {
         self_sk,
         self_id,
         peer_vk,
-        rejoin_members,
-        rejoin_member_info,
+        peer_prekey,
+        sender_chain,
     } = snapshot;
 
     // Encode the body and capture the plaintext-summary BEFORE moving it.
@@ -460,68 +612,40 @@ pub async fn send_structured_dm(
     let plaintext_summary = summarise_body_for_outbound_cache(&body);
 
     let now = unix_now();
-    let auth = match compose_direct_message(&self_sk, &peer_vk, &room, now, now, &body_bytes) {
+    let auth = match crate::components::app::dm_ratchet::compose_dm(
+        room,
+        &self_sk,
+        &peer_vk,
+        peer_prekey,
+        now,
+        &body_bytes,
+    )
+    .await
+    {
         Ok(a) => a,
         Err(e) => return SendDmOutcome::BodyTooLargeOrEncodeFailed(e),
     };
 
     let purge_token = auth.purge_token();
     let dm_timestamp = auth.message.timestamp;
-    let auth_sig = auth.sender_signature;
-
-    let delta = ChatRoomStateV1Delta {
-        members: rejoin_members,
-        member_info: rejoin_member_info,
-        direct_messages: Some(DirectMessagesDelta {
-            new_messages: vec![auth],
-            advanced_purges: vec![],
-        }),
-        ..Default::default()
-    };
-    let params = ChatRoomParametersV1 { owner: room };
 
-    // Apply the delta under a write-lock on ROOMS. We `await` the
-    // `apply_delta` result via a oneshot channel because Dioxus signal
-    // writes must be deferred (AGENTS.md "Dioxus WASM Signal Safety
-    // Rules"). The picker awaits us and reacts to the outcome.
+    // The PUT and the cache/unhide writes go through `defer` because
+    // signal access must (AGENTS.md "Dioxus WASM Signal Safety Rules").
+    // The picker awaits us and reacts to the outcome.
     let (tx, rx) = futures::channel::oneshot::channel::<SendDmOutcome>();
-    let plaintext_for_cache = plaintext_summary.clone();
     crate::util::defer(move || {
-        let outcome = ROOMS.with_mut(|rooms| {
-            let Some(rd) = rooms.map.get_mut(&room) else {
-                return SendDmOutcome::RoomGone;
-            };
-            let parent = rd.room_state.clone();
-            if let Err(e) = rd.room_state.apply_delta(&parent, &params, &Some(delta)) {
-                return SendDmOutcome::DeltaFailed(format!("{:?}", e));
-            }
-            // Verify the DM actually landed (defence-in-depth against
-            // contract-side silent drop).
-            let landed = rd
-                .room_state
-                .direct_messages
-                .messages
-                .iter()
-                .any(|m| m.sender_signature == auth_sig);
-            if !landed {
-                return SendDmOutcome::SilentDrop;
-            }
-            // #310: apply_delta's MessagesV1 step re-runs the public-only
-            // rebuild_actions_state, dropping private edits/reactions.
-            // Re-derive them with decryption. No-op on public rooms.
-            rd.rebuild_private_actions_state();
-            SendDmOutcome::Sent
-        });
-
+        let outcome = match send_to_inbox(room, peer_vk, sender_chain, auth) {
+            Ok(()) => SendDmOutcome::Sent,
+            Err(e) => SendDmOutcome::Rejected(e),
+        };
         if matches!(outcome, SendDmOutcome::Sent) {
-            mark_needs_sync(room);
             save_outbound_dm(
                 room,
                 self_id,
                 peer,
                 purge_token,
                 dm_timestamp,
-                plaintext_for_cache,
+                plaintext_summary,
             );
             unhide_dm_thread(room, peer);
         }
@@ -530,7 +654,7 @@ pub async fn send_structured_dm(
 
     // Wait for the deferred work to land. Defer schedules via
     // setTimeout(0), so this is one macrotask away.
-    rx.await.unwrap_or(SendDmOutcome::DeltaFailed(
+    rx.await.unwrap_or(SendDmOutcome::Rejected(
         "deferred send aborted before completion".into(),
     ))
 }
@@ -562,17 +686,15 @@ fn unix_now() -> u64 {
 }
 
 /// Pure helper: compute the max inbound DM timestamp per `(room, peer)` in
-/// `rooms`. Split from the signal-touching wrapper so it's unit-testable.
+/// `rooms`, counting both room-state DMs and those in `inboxes`. Split from
+/// the signal-touching wrapper so it's unit-testable.
 pub(crate) fn compute_dm_last_seen(
     rooms: &crate::room_data::Rooms,
+    inboxes: &HashMap<VerifyingKey, InboxStateV1>,
 ) -> HashMap<(VerifyingKey, MemberId), u64> {
     let mut updates: HashMap<(VerifyingKey, MemberId), u64> = HashMap::new();
     for (owner_vk, room_data) in &rooms.map {
-        let self_id: MemberId = room_data.self_sk.verifying_key().into();
-        for msg in &room_data.room_state.direct_messages.messages {
-            if msg.message.recipient != self_id {
-                continue;
-            }
+        for msg in inbound_dms(room_data, inboxes.get(owner_vk)) {
             let key = (*owner_vk, msg.message.sender);
             let entry = updates.entry(key).or_insert(0);
             if msg.message.timestamp > *entry {
@@ -600,7 +722,10 @@ pub fn reseed_dm_last_seen_for_room(owner_vk: VerifyingKey) {
         let Ok(rooms) = crate::components::app::ROOMS.try_read() else {
             return;
         };
-        compute_dm_last_seen(&rooms)
+        let Ok(inboxes) = INBOXES.try_read() else {
+            return;
+        };
+        compute_dm_last_seen(&rooms, &inboxes)
     };
     crate::util::defer(move || {
         DM_LAST_SEEN.with_mut(|seen| {
@@ -665,7 +790,13 @@ pub fn seed_dm_last_seen_if_needed() {
         // ROOMS hasn't hydrated yet; wait for the next ROOMS change.
         return;
     }
-    let updates = compute_dm_last_seen(&rooms);
+    // Inboxes not read yet are seeded on arrival instead, by
+    // `seed_dm_last_seen_from_inbox`.
+    let Ok(inboxes) = INBOXES.try_read() else {
+        return;
+    };
+    let updates = compute_dm_last_seen(&rooms, &inboxes);
+    drop(inboxes);
     drop(rooms);
 
     // Latch the seeded flag synchronously so any parallel re-run of this
@@ -693,6 +824,50 @@ pub fn seed_dm_last_seen_if_needed() {
     });
 }
 
+/// [`seed_dm_last_seen_if_needed`] for a room's inbox, which is read from
+/// the network after that seed has run: the first time this session the
+/// inbox arrives, what it already holds counts as seen. Later arrivals are
+/// not seeded, so they show as unread.
+pub fn seed_dm_last_seen_from_inbox(owner_vk: VerifyingKey, inbox: &InboxStateV1) {
+    let mut updates: HashMap<MemberId, u64> = HashMap::new();
+    for m in &inbox.messages {
+        let entry = updates.entry(m.message.message.sender).or_insert(0);
+        *entry = (*entry).max(m.message.message.timestamp);
+    }
+    let mut group_updates: HashMap<Vec<MemberId>, u64> = HashMap::new();
+    for m in &inbox.group_messages {
+        let entry = group_updates
+            .entry(m.message.message.participants.clone())
+            .or_insert(0);
+        *entry = (*entry).max(m.message.message.timestamp);
+    }
+    if updates.is_empty() && group_updates.is_empty() {
+        return;
+    }
+    crate::util::defer(move || {
+        if !updates.is_empty() {
+            DM_LAST_SEEN.with_mut(|seen| {
+                for (peer, ts) in updates {
+                    let entry = seen.entry((owner_vk, peer)).or_insert(0);
+                    if ts > *entry {
+                        *entry = ts;
+                    }
+                }
+            });
+        }
+        if !group_updates.is_empty() {
+            GROUP_DM_LAST_SEEN.with_mut(|seen| {
+                for (participants, ts) in group_updates {
+                    let entry = seen.entry((owner_vk, participants)).or_insert(0);
+                    if ts > *entry {
+                        *entry = ts;
+                    }
+                }
+            });
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -926,6 +1101,7 @@ mod tests {
                 self_sk: self_sk.clone(),
                 contract_key,
                 last_read_message_id: None,
+                thread_read: Default::default(),
                 secrets: std::collections::HashMap::new(),
                 current_secret_version: None,
                 last_secret_rotation: None,
@@ -969,7 +1145,7 @@ mod tests {
 
     #[test]
     fn compute_dm_last_seen_returns_empty_for_empty_rooms() {
-        let updates = compute_dm_last_seen(&empty_rooms());
+        let updates = compute_dm_last_seen(&empty_rooms(), &HashMap::new());
         assert!(updates.is_empty());
     }
 
@@ -1024,7 +1200,7 @@ mod tests {
         push_dm(&mut rooms, &owner_vk, &bob, &me.verifying_key(), 200);
         push_dm(&mut rooms, &owner_vk, &me, &alice.verifying_key(), 250);
 
-        let updates = compute_dm_last_seen(&rooms);
+        let updates = compute_dm_last_seen(&rooms, &HashMap::new());
         let alice_id: MemberId = (&alice.verifying_key()).into();
         let bob_id: MemberId = (&bob.verifying_key()).into();
         assert_eq!(updates.get(&(owner_vk, alice_id)), Some(&100));
@@ -1047,11 +1223,116 @@ mod tests {
         push_dm(&mut rooms, &owner_vk, &alice, &me.verifying_key(), 1_000);
         push_dm(&mut rooms, &owner_vk, &alice, &me.verifying_key(), 500);
 
-        let updates = compute_dm_last_seen(&rooms);
+        let updates = compute_dm_last_seen(&rooms, &HashMap::new());
         let alice_id: MemberId = (&alice.verifying_key()).into();
         assert_eq!(updates.get(&(owner_vk, alice_id)), Some(&1_000));
     }
 
+    /// DMs held in the inbox count like room-state ones, and a DM in both
+    /// (already moved, room copy not yet purged) is counted once.
+    #[test]
+    fn inbound_dms_merges_inbox_and_room_copies() {
+        let owner = fixed_sk(21);
+        let me = fixed_sk(22);
+        let alice = fixed_sk(23);
+        let owner_vk = owner.verifying_key();
+        let mut rooms = make_rooms(&owner, &me, &[&alice]);
+        push_dm(&mut rooms, &owner_vk, &alice, &me.verifying_key(), 100);
+
+        let moved = rooms.map[&owner_vk].room_state.direct_messages.messages[0].clone();
+        let newer = sign_direct_message(
+            &alice,
+            (&alice.verifying_key()).into(),
+            (&me.verifying_key()).into(),
+            &owner_vk,
+            300,
+            b"opaque".to_vec(),
+        )
+        .unwrap();
+        let inbox = InboxStateV1 {
+            messages: [moved, newer]
+                .into_iter()
+                .map(|message| river_core::inbox::InboxMessageV1 {
+                    sender_chain: vec![],
+                    message,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(inbound_dms(&rooms.map[&owner_vk], Some(&inbox)).len(), 2);
+        let updates = compute_dm_last_seen(&rooms, &HashMap::from([(owner_vk, inbox)]));
+        let alice_id: MemberId = (&alice.verifying_key()).into();
+        assert_eq!(updates.get(&(owner_vk, alice_id)), Some(&300));
+    }
+
+    /// A blocked member's DMs drop out of the rail's triples while still
+    /// held, and everyone else's stay.
+    #[test]
+    fn dm_triples_hide_blocked_senders() {
+        let owner = fixed_sk(31);
+        let me = fixed_sk(32);
+        let (alice, bob) = (fixed_sk(33), fixed_sk(34));
+        let owner_vk = owner.verifying_key();
+        let mut rooms = make_rooms(&owner, &me, &[&alice, &bob]);
+        push_dm(&mut rooms, &owner_vk, &alice, &me.verifying_key(), 100);
+        push_dm(&mut rooms, &owner_vk, &bob, &me.verifying_key(), 200);
+
+        let room_data = &rooms.map[&owner_vk];
+        let bob_id: MemberId = (&bob.verifying_key()).into();
+        assert_eq!(dm_triples(room_data, None, None, &HashSet::new()).len(), 2);
+        let triples = dm_triples(room_data, None, None, &HashSet::from([bob_id]));
+        assert_eq!(triples.len(), 1);
+        assert_ne!(triples[0].0, bob_id);
+    }
+
+    /// Group DMs thread by participant set; only others' messages newer
+    /// than the thread's cutoff are unread, and a contended cutoff read
+    /// counts none.
+    #[test]
+    fn group_dm_threads_split_by_participants_and_count_unread() {
+        let owner_vk = fixed_sk(21).verifying_key();
+        let (me, alice, bob, carol) = (fixed_sk(22), fixed_sk(23), fixed_sk(24), fixed_sk(25));
+        let id = |sk: &SigningKey| MemberId::from(&sk.verifying_key());
+        let group = |sender: &SigningKey, members: &[&SigningKey], timestamp: u64| {
+            let participants = participant_set(members.iter().map(|sk| id(sk)));
+            river_core::group_dm::sign_group_direct_message(
+                sender,
+                &owner_vk,
+                river_core::group_dm::GroupDirectMessage {
+                    sender: id(sender),
+                    wrapped_keys: vec![
+                        vec![0; river_core::group_dm::WRAPPED_KEY_BYTES];
+                        participants.len()
+                    ],
+                    participants,
+                    timestamp,
+                    nonce: [0; 12],
+                    ciphertext: b"opaque".to_vec(),
+                },
+            )
+            .unwrap()
+        };
+        let messages = [
+            group(&alice, &[&me, &alice, &bob], 100),
+            group(&me, &[&me, &alice, &bob], 200),
+            group(&bob, &[&me, &alice, &bob], 300),
+            group(&carol, &[&me, &alice, &carol], 150),
+        ];
+        let trio = participant_set([id(&me), id(&alice), id(&bob)]);
+        let seen = HashMap::from([((owner_vk, trio.clone()), 100)]);
+
+        let mut threads = group_dm_threads(owner_vk, id(&me), &messages, Some(&seen));
+        threads.sort_by_key(|t| t.last_ts);
+        assert_eq!(threads.len(), 2);
+        assert_eq!((threads[0].last_ts, threads[0].unread), (150, 1));
+        assert_eq!(threads[1].participants, trio);
+        assert_eq!((threads[1].last_ts, threads[1].unread), (300, 1));
+
+        let contended = group_dm_threads(owner_vk, id(&me), &messages, None);
+        assert!(contended.iter().all(|t| t.unread == 0));
+    }
+
     fn sample_outbound_entry(
         room_vk: VerifyingKey,
         recipient: MemberId,
@@ -1,18 +1,29 @@
 //! Per-pair DM thread modal: decrypts inbound DMs, composes outbound ones,
 //! and offers a "Delete their messages" button that produces a fresh
 //! `AuthorizedRecipientPurges` envelope tombstoning every inbound DM in the
-//! current view.
+//! current view, in the inbox and (for DMs not yet moved) the room.
 //!
 //! The header used to carry a "Hide" button alongside the close ✕; that was
 //! moved to the per-row rollover ✕ in [`crate::components::room_list::dm_rail_section`]
 //! after #266 reported it was visually ambiguous with the close button.
 //! "Delete their messages" now goes through a confirmation modal before
 //! firing the destructive `purge_thread` flow.
+//!
+//! The same modal shows group threads ([`OPEN_GROUP_DM_THREAD`]): every
+//! member of the group reads and writes one shared thread, and "Delete
+//! conversation" purges it from the local member's inbox only. A pair
+//! thread's "Add people" starts a group with the peer.
 
+use crate::components::app::block_list::blocked_in_room;
 use crate::components::app::chat_delegate::{save_outbound_dm, unhide_dm_thread};
+use crate::components::app::dm_ratchet::{self, OPENED_RATCHET_DMS};
+use crate::components::app::freenet_api::inbox_sync::{
+    own_sender_chain, purge_from_inbox, send_to_group_inboxes, send_to_inbox, INBOXES,
+};
 use crate::components::app::{mark_needs_sync, ROOMS};
 use crate::components::direct_messages::{
-    lookup_outbound_plaintext, mark_thread_read, DM_DRAFT, OPEN_DM_THREAD, OUTBOUND_DMS,
+    inbound_dms, lookup_outbound_plaintext, mark_group_thread_read, mark_thread_read,
+    open_group_dm_thread, DM_DRAFT, OPEN_DM_THREAD, OPEN_GROUP_DM_THREAD, OUTBOUND_DMS,
 };
 use crate::components::members::Invitation;
 use crate::components::room_list::receive_invitation_modal::present_invitation;
@@ -22,9 +33,13 @@ use dioxus::logger::tracing::{error, info, warn};
 use dioxus::prelude::*;
 use ed25519_dalek::VerifyingKey;
 use freenet_scaffold::ComposableState;
+use river_core::dm_ratchet::is_ratchet_envelope;
+use river_core::group_dm::{
+    compose_group_direct_message, open_group_direct_message, MAX_GROUP_DM_PARTICIPANTS,
+};
 use river_core::room_state::direct_messages::{
-    advance_recipient_purges, compose_direct_message, open_direct_message, DirectMessagesDelta,
-    PurgeToken, MAX_DM_CIPHERTEXT_BYTES,
+    advance_recipient_purges, open_direct_message, DirectMessagesDelta, PurgeToken,
+    MAX_DM_CIPHERTEXT_BYTES,
 };
 use river_core::room_state::dm_body::{decode_body, DirectMessageBody, InvitePayload};
 use river_core::room_state::member::MemberId;
@@ -52,31 +67,28 @@ const DM_BODY_BYTE_CAP: usize = MAX_DM_CIPHERTEXT_BYTES - 256;
 /// WASM Signal Safety Rules", a subscriber's `.read()` can panic if
 /// the write that triggered the notification still holds the write
 /// guard's RefCell borrow at notify time. The subscription is supplied
-/// instead by the parent memo's read of `ROOMS` (the bump always
-/// happens in the same defer block as the apply_delta that grows
-/// message_count, so the effect re-fires via the message_count path).
-/// Any future writer that bumps this counter WITHOUT also growing
-/// `message_count` must add an explicit re-render trigger or the
-/// auto-scroll will miss its bump.
+/// instead by the parent memo's read of `OUTBOUND_DMS` (the bump always
+/// happens in the same defer block as the `save_outbound_dm` that adds
+/// the sent bubble, so the effect re-fires via the new bubble's mount).
+/// Any future writer that bumps this counter WITHOUT also adding a
+/// bubble must add an explicit re-render trigger or the auto-scroll
+/// will miss its bump.
 static OUTBOUND_SEND_COUNTER: GlobalSignal<u64> = Global::new(|| 0);
 
-/// Result of applying an outbound DM to the local `ROOMS` state. The
-/// `send` closure uses this to map back to a user-facing error after the
-/// write-lock drops.
-enum ApplyOutcome {
-    Applied,
-    /// Room was unloaded between compose and apply.
-    RoomGone,
-    DeltaFailed,
-    /// `apply_delta` returned Ok but the DM did not actually land in
-    /// `direct_messages.messages` — the contract silently dropped it
-    /// (typically: sender or recipient is not in members and no rejoin
-    /// bundle was supplied). Codex P2 defence-in-depth (PR #269 review).
-    SilentDrop,
-}
-
 #[component]
 pub fn DmThreadModal() -> Element {
+    if let Some((room, participants)) = OPEN_GROUP_DM_THREAD.read().clone() {
+        // Keyed so switching groups remounts the body: its memo and
+        // callbacks capture the participant set.
+        let key = participants
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join("-");
+        return rsx! {
+            GroupDmThreadModalBody { key: "{key}", room, participants }
+        };
+    }
     let active = *OPEN_DM_THREAD.read();
     let Some((room, peer)) = active else {
         return rsx! {};
@@ -211,9 +223,56 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                 }
             };
 
+            // Third fallible read, nudged for the same reason: a DM landing
+            // in our inbox only moves INBOXES.
+            let inbox = match INBOXES.try_read() {
+                Ok(g) => g.get(&room).cloned(),
+                Err(_) => {
+                    crate::util::signal_guard::schedule_nudge();
+                    None
+                }
+            };
+
+            // Fourth: ratcheted DMs (`river_core::dm_ratchet`) are opened once,
+            // outside this memo, and read back from here.
+            let ratchet = match OPENED_RATCHET_DMS.try_read() {
+                Ok(g) => g.get(&room).cloned(),
+                Err(_) => {
+                    crate::util::signal_guard::schedule_nudge();
+                    None
+                }
+            };
+
+            // Fifth: a blocked peer's DMs stay hidden until they are purged,
+            // and after, if our purge envelope had no room for them.
+            let blocked = match blocked_in_room(room, self_id) {
+                Some(blocked) => blocked,
+                None => {
+                    crate::util::signal_guard::schedule_nudge();
+                    std::collections::HashSet::new()
+                }
+            };
+
+            // Their DMs to us come from our inbox (and the room state, for
+            // any not moved yet); ours to them from the room state when an
+            // older client sent them, else from the outbound cache below.
+            let ours_in_room: Vec<_> = room_data
+                .room_state
+                .direct_messages
+                .messages
+                .iter()
+                .filter(|m| m.message.sender == self_id && m.message.recipient == peer)
+                .collect();
+            let room_tokens: std::collections::HashSet<PurgeToken> =
+                ours_in_room.iter().map(|m| m.purge_token()).collect();
+            let thread_dms = inbound_dms(room_data, inbox.as_ref())
+                .into_iter()
+                .filter(|m| m.message.sender == peer && !blocked.contains(&peer))
+                .chain(ours_in_room);
+
             let mut latest_inbound_ts: u64 = 0;
             let mut rendered: Vec<RenderedDm> = Vec::new();
-            for msg in &room_data.room_state.direct_messages.messages {
+            for msg in thread_dms {
                 let is_self_sender = msg.message.sender == self_id;
                 let is_self_recipient = msg.message.recipient == self_id;
                 let between_us = (is_self_sender && msg.message.recipient == peer)
@@ -227,7 +286,19 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                 }
 
                 let (body, kind) = if is_self_recipient {
-                    match open_direct_message(&self_sk, msg) {
+                    let opened = if is_ratchet_envelope(&msg.message.ciphertext) {
+                        let token = msg.purge_token();
+                        let ratchet = ratchet.as_ref();
+                        match ratchet.and_then(|r| r.opened.get(&token)) {
+                            Some(plaintext) => Ok(plaintext.clone()),
+                            None => Err(ratchet
+                                .and_then(|r| r.failed.get(&token).cloned())
+                                .unwrap_or_else(|| "not opened on this device yet".into())),
+                        }
+                    } else {
+                        open_direct_message(&self_sk, msg)
+                    };
+                    match opened {
                         Ok(bytes) => match decode_body(&bytes) {
                             Ok(DirectMessageBody::Text { text }) => (text, BodyKind::Plaintext),
                             Ok(DirectMessageBody::Invite(payload)) => {
@@ -309,6 +380,7 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                 };
 
                 rendered.push(RenderedDm {
+                    sender_label: None,
                     outgoing: is_self_sender,
                     timestamp: msg.message.timestamp,
                     body,
@@ -316,13 +388,54 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                     token: msg.purge_token(),
                 });
             }
+            if let Some(cache) = outbound_cache.as_ref() {
+                for ((cached_room, recipient, token), entry) in &cache.by_token {
+                    if *cached_room == room
+                        && *recipient == peer
+                        && entry.sender == self_id
+                        && !room_tokens.contains(token)
+                    {
+                        rendered.push(RenderedDm {
+                            sender_label: None,
+                            outgoing: true,
+                            timestamp: entry.timestamp,
+                            body: entry.plaintext.clone(),
+                            kind: BodyKind::Plaintext,
+                            token: *token,
+                        });
+                    }
+                }
+            }
             rendered.sort_by_key(|d| d.timestamp);
 
+            // Who "Add people" can bring into a group with `peer`.
+            let mut group_candidates: Vec<(MemberId, String)> = room_data
+                .room_state
+                .members
+                .members
+                .iter()
+                .map(|m| m.member.id())
+                .chain(std::iter::once(owner_id))
+                .filter(|id| *id != self_id && *id != peer)
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .map(|id| {
+                    let name = nicknames
+                        .get(&id)
+                        .cloned()
+                        .unwrap_or_else(|| short_member_id(&id));
+                    (id, name)
+                })
+                .collect();
+            group_candidates.sort_by(|a, b| a.1.cmp(&b.1));
+
             Some(ViewData {
                 peer_nickname,
                 peer_still_member,
                 messages: rendered,
                 latest_inbound_ts,
+                self_id,
+                group_candidates,
             })
         }
     });
@@ -438,6 +551,7 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
     // (#266). The confirmation gate forces a deliberate second click; the
     // primary Cancel/Esc path closes it without mutating anything.
     let mut confirm_delete_open: Signal<bool> = use_signal(|| false);
+    let mut add_people_open: Signal<bool> = use_signal(|| false);
 
     // No-arg send callback so both `onclick` and `onkeydown` (Enter)
     // can invoke it. `mut` because Dioxus signal `.set()` borrows the
@@ -483,42 +597,20 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
         }
 
         // NO per-pair cap guard, and no "this thread is full" error. The
-        // contract's per-pair cap is newest-N now (`trim_pairs_to_cap`), so a
-        // send at the cap is ADMITTED and the pair's oldest DM is evicted. The
-        // old first-come-wins cap silently discarded the arrival, which is what
-        // this block existed to surface. Blocking here would keep the user
-        // unable to send for a purely client-side reason.
+        // inbox contract keeps the newest DMs per sender, so a send at the
+        // cap is ADMITTED and this sender's oldest DM there is evicted.
+        // Blocking here would keep the user unable to send for a purely
+        // client-side reason.
 
         let plaintext = body.clone();
         let body_bytes = body.into_bytes();
         // Bug #1 (Ivvor, Matrix 2026-05-16): an invited-but-inactive sender
-        // can be pruned from `members.members` by `post_apply_cleanup`,
-        // after which the contract's `DirectMessagesV1::apply_delta`
-        // silent-drops any DM whose sender isn't currently in members. The
-        // regular message-send path bundles a rejoin delta
-        // (`MembersDelta` + `member_info`) to re-add the pruned sender —
-        // do the same here so DMs from a pruned-but-invited sender land
-        // atomically. `MembersV1` precedes `DirectMessagesV1` in
-        // `ChatRoomStateV1`'s field order, so by the time the DM
-        // sub-state apply runs the sender is back in members. Pinned by
-        // `pruned_sender_can_dm_when_bundling_rejoin_delta` in
-        // `common/tests/direct_messages_test.rs`. Returns `(None, None)`
-        // when not pruned, which `ChatRoomStateV1Delta` accepts as no-op.
-        let (rejoin_members, rejoin_member_info) = room_data.build_rejoin_delta();
-        // Codex P2 (PR #269 review): if the sender is pruned AND
-        // `build_rejoin_delta` returned no credentials (e.g. an imported
-        // identity missing `self_authorized_member`), the contract will
-        // silent-drop the DM. The local `apply_delta` succeeds in that
-        // case but the message never lands, leaving the composer
-        // empty-looking-successful. Surface the failure up front instead.
-        let self_in_members = self_id == MemberId::from(&room)
-            || room_data
-                .room_state
-                .members
-                .members
-                .iter()
-                .any(|m| m.member.id() == self_id);
-        if !self_in_members && rejoin_members.is_none() {
+        // can be pruned from `members.members` by `post_apply_cleanup`. The
+        // recipient's inbox contract checks the sender's invite chain, not
+        // the room's member list, so a pruned sender still proves
+        // membership with the chain stored at join time. With neither, the
+        // contract would reject the DM — surface that up front instead.
+        let Some(sender_chain) = own_sender_chain(&room_data) else {
             send_error.set(Some(
                 "You're not currently in this room's member list and no \
                  rejoin credentials are stored locally. Reload the room or \
@@ -526,78 +618,43 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                     .into(),
             ));
             return;
-        }
+        };
+        // Forward-secret if the peer publishes a prekey (or we already have
+        // a session with them), else sealed to their member key.
+        let peer_prekey = room_data
+            .room_state
+            .member_info
+            .canonical(peer)
+            .and_then(|info| info.member_info.dm_prekey);
         wasm_bindgen_futures::spawn_local(async move {
             let now = unix_now();
-            let auth =
-                match compose_direct_message(&self_sk, &peer_vk, &room, now, now, &body_bytes) {
-                    Ok(a) => a,
-                    Err(e) => {
-                        error!("compose_direct_message failed: {}", e);
-                        send_error.set(Some(format!("Failed to compose DM: {}", e)));
-                        return;
-                    }
-                };
+            let auth = match dm_ratchet::compose_dm(
+                room,
+                &self_sk,
+                &peer_vk,
+                peer_prekey,
+                now,
+                &body_bytes,
+            )
+            .await
+            {
+                Ok(a) => a,
+                Err(e) => {
+                    error!("compose_dm failed: {}", e);
+                    send_error.set(Some(format!("Failed to compose DM: {}", e)));
+                    return;
+                }
+            };
 
             // Capture the metadata we need for the outbound-plaintext
-            // cache (#256) BEFORE moving `auth` into the delta below.
+            // cache (#256) BEFORE moving `auth` into the inbox message.
             let purge_token = auth.purge_token();
             let dm_timestamp = auth.message.timestamp;
 
-            let delta = ChatRoomStateV1Delta {
-                members: rejoin_members,
-                member_info: rejoin_member_info,
-                direct_messages: Some(DirectMessagesDelta {
-                    new_messages: vec![auth.clone()],
-                    advanced_purges: vec![],
-                }),
-                ..Default::default()
-            };
-
-            let params = ChatRoomParametersV1 { owner: room };
-            let auth_sig = auth.sender_signature;
             crate::util::defer(move || {
-                let outcome = ROOMS.with_mut(|rooms| {
-                    let Some(rd) = rooms.map.get_mut(&room) else {
-                        return ApplyOutcome::RoomGone;
-                    };
-                    let parent = rd.room_state.clone();
-                    if let Err(e) = rd.room_state.apply_delta(&parent, &params, &Some(delta)) {
-                        error!("DM apply_delta failed: {:?}", e);
-                        return ApplyOutcome::DeltaFailed;
-                    }
-                    // Codex P2 defence-in-depth (PR #269 review):
-                    // `DirectMessagesV1::apply_delta` silently drops
-                    // DMs whose sender or recipient is not in members
-                    // and returns Ok. Verify our DM actually landed
-                    // before reporting success — otherwise the UI
-                    // would clear the composer and the user would
-                    // think the message was sent.
-                    let landed = rd
-                        .room_state
-                        .direct_messages
-                        .messages
-                        .iter()
-                        .any(|m| m.sender_signature == auth_sig);
-                    if !landed {
-                        warn!(
-                            "DM apply_delta returned Ok but the message was \
-                             silently dropped by the contract (membership check?)"
-                        );
-                        return ApplyOutcome::SilentDrop;
-                    }
-                    // #310: apply_delta's MessagesV1 step always re-runs the
-                    // public-only rebuild_actions_state, which wipes private
-                    // edits/deletes/reactions. Re-derive them with decryption
-                    // so sending a DM doesn't transiently revert an edited
-                    // message. No-op on public rooms.
-                    rd.rebuild_private_actions_state();
-                    ApplyOutcome::Applied
-                });
-                match outcome {
-                    ApplyOutcome::Applied => {
-                        info!("DM appended locally; marking room for sync");
-                        mark_needs_sync(room);
+                match send_to_inbox(room, peer_vk, sender_chain, auth) {
+                    Ok(()) => {
+                        info!("DM queued for the recipient's inbox");
                         // Bump the outbound-send counter so the
                         // auto-scroll effect notices the user just sent
                         // a message and snaps to the bottom (regardless
@@ -613,8 +670,10 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                         let next_outbound_counter = OUTBOUND_SEND_COUNTER.peek().wrapping_add(1);
                         *OUTBOUND_SEND_COUNTER.write() = next_outbound_counter;
                         // Persist plaintext for the sender's own view
-                        // (#256). Cache write + delegate save happen
-                        // inside `save_outbound_dm` via `defer` /
+                        // (#256) — the only copy the sender can read, as
+                        // the inbox DM is sealed to the recipient. Cache
+                        // write + delegate save happen inside
+                        // `save_outbound_dm` via `defer` /
                         // `safe_spawn_local`.
                         save_outbound_dm(room, self_id, peer, purge_token, dm_timestamp, plaintext);
                         // Issue freenet/river#261 (Codex P1): if the
@@ -631,33 +690,13 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                         unhide_dm_thread(room, peer);
                         draft.set(String::new());
                     }
-                    ApplyOutcome::RoomGone => {
-                        send_error.set(Some("This room is no longer loaded.".into()));
-                    }
-                    ApplyOutcome::DeltaFailed => {
-                        // No "please try again" — `apply_delta` failures
-                        // are deterministic (signature / membership /
-                        // tombstone / cap), so retrying byte-identical
-                        // input gives the same result. See the
-                        // `warn!`/`error!` log for the diagnostic.
-                        send_error.set(Some(
-                            "Couldn't send this message — something went wrong.".into(),
-                        ));
-                    }
-                    ApplyOutcome::SilentDrop => {
-                        // The contract accepted the delta but dropped
-                        // the DM. The most likely cause is that the
-                        // sender or recipient is not in members and
-                        // the rejoin bundle was insufficient (or
-                        // empty). Don't clear the composer — the user
-                        // can adjust and retry.
-                        send_error.set(Some(
-                            "This message couldn't be added to the room \
-                             (your member entry may be missing). Try \
-                             posting a message in the room first, then \
-                             retry the DM."
-                                .into(),
-                        ));
+                    Err(e) => {
+                        // No "please try again" — the inbox checks are
+                        // deterministic (signature / membership chain /
+                        // size), so retrying byte-identical input gives
+                        // the same result.
+                        warn!("DM rejected before sending: {}", e);
+                        send_error.set(Some(format!("Couldn't send this message: {}", e)));
                     }
                 }
             });
@@ -689,7 +728,20 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
             };
             let self_sk = room_data.self_sk.clone();
             let self_id: MemberId = (&self_sk.verifying_key()).into();
+            let inbox = INBOXES
+                .try_read()
+                .ok()
+                .and_then(|i| i.get(&room).cloned())
+                .unwrap_or_default();
 
+            // Their DMs in our inbox are purged there; any an older client
+            // left in the room state are purged from the room.
+            let inbox_tokens: Vec<PurgeToken> = inbox
+                .messages
+                .iter()
+                .filter(|m| m.message.message.sender == peer)
+                .map(|m| m.message.purge_token())
+                .collect();
             let tokens: Vec<PurgeToken> = room_data
                 .room_state
                 .direct_messages
@@ -698,10 +750,25 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                 .filter(|m| m.message.recipient == self_id && m.message.sender == peer)
                 .map(|m| m.purge_token())
                 .collect();
-            if tokens.is_empty() {
+            if inbox_tokens.is_empty() && tokens.is_empty() {
                 send_error.set(Some("No messages from them to delete.".into()));
                 return;
             }
+            if !inbox_tokens.is_empty() {
+                // A ratcheted DM's key is gone already; dropping its
+                // plaintext leaves nothing that reads it.
+                dm_ratchet::forget(room, &inbox_tokens);
+                if let Err(e) = purge_from_inbox(room, &self_sk, &inbox, inbox_tokens) {
+                    warn!("Purging DMs from the inbox failed: {}", e);
+                    send_error.set(Some(
+                        "Couldn't delete those messages — something went wrong.".into(),
+                    ));
+                    return;
+                }
+            }
+            if tokens.is_empty() {
+                return;
+            }
 
             let previous = room_data
                 .room_state
@@ -808,10 +875,30 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
                         "Direct messages with "
                         span { class: "text-accent", "{peer_label}" }
                     }
-                    button {
-                        class: "p-1 text-text-muted hover:text-text transition-colors text-xl",
-                        onclick: close,
-                        "✕"
+                    div { class: "flex items-center gap-2",
+                        if peer_still_member && !view_data.group_candidates.is_empty() {
+                            button {
+                                class: "text-xs text-text-muted hover:text-text transition-colors",
+                                title: "Start a group conversation with {peer_label} and others",
+                                onclick: move |_| {
+                                    let next = !*add_people_open.peek();
+                                    add_people_open.set(next);
+                                },
+                                "Add people"
+                            }
+                        }
+                        button {
+                            class: "p-1 text-text-muted hover:text-text transition-colors text-xl",
+                            onclick: close,
+                            "✕"
+                        }
+                    }
+                }
+                if *add_people_open.read() {
+                    AddPeoplePanel {
+                        room,
+                        base: vec![view_data.self_id, peer],
+                        candidates: view_data.group_candidates.clone(),
                     }
                 }
 
@@ -991,12 +1078,453 @@ fn DmThreadModalBody(room: VerifyingKey, peer: MemberId) -> Element {
     }
 }
 
+/// "Add people" picker under a pair thread's header: tick up to
+/// `MAX_GROUP_DM_PARTICIPANTS - 2` more members and open the group thread
+/// with them, the peer and the local member (`base`).
+#[component]
+fn AddPeoplePanel(
+    room: VerifyingKey,
+    base: Vec<MemberId>,
+    candidates: Vec<(MemberId, String)>,
+) -> Element {
+    let mut selected: Signal<Vec<MemberId>> = use_signal(Vec::new);
+    let room_left = MAX_GROUP_DM_PARTICIPANTS - base.len();
+    let chosen = selected.read().clone();
+    let full = chosen.len() >= room_left;
+
+    rsx! {
+        div { class: "px-5 py-3 border-b border-border space-y-2",
+            p { class: "text-xs text-text-muted",
+                "Pick up to {room_left} more members for a group conversation."
+            }
+            div { class: "max-h-32 overflow-y-auto space-y-1",
+                for (id , name) in candidates.into_iter() {
+                    label {
+                        key: "{id}",
+                        class: "flex items-center gap-2 text-sm text-text",
+                        input {
+                            r#type: "checkbox",
+                            checked: chosen.contains(&id),
+                            disabled: full && !chosen.contains(&id),
+                            onchange: move |_| {
+                                selected.with_mut(|s| {
+                                    if let Some(i) = s.iter().position(|m| *m == id) {
+                                        s.remove(i);
+                                    } else {
+                                        s.push(id);
+                                    }
+                                });
+                            },
+                        }
+                        "{name}"
+                    }
+                }
+            }
+            div { class: "flex justify-end",
+                button {
+                    class: "px-3 py-1.5 bg-accent hover:bg-accent-hover disabled:opacity-50 text-white text-xs font-medium rounded-lg transition-colors",
+                    disabled: chosen.is_empty(),
+                    onclick: move |_| {
+                        let members = base.iter().copied().chain(selected.peek().iter().copied()).collect();
+                        open_group_dm_thread(room, members);
+                    },
+                    "Start group"
+                }
+            }
+        }
+    }
+}
+
+/// A group thread: everyone's messages to the group, decrypted from our
+/// inbox, and a composer that delivers to every participant's inbox.
+#[component]
+fn GroupDmThreadModalBody(room: VerifyingKey, participants: Vec<MemberId>) -> Element {
+    let mut draft = use_signal(String::new);
+    let mut send_error: Signal<Option<String>> = use_signal(|| None);
+    let mut confirm_delete_open: Signal<bool> = use_signal(|| false);
+
+    let thread = participants.clone();
+    let view = use_memo(move || {
+        // Same anchoring and contention handling as the pair thread's memo;
+        // a group DM landing only moves INBOXES.
+        crate::util::signal_guard::anchor();
+        let Ok(rooms) = ROOMS.try_read() else {
+            crate::util::signal_guard::schedule_nudge();
+            return None;
+        };
+        let room_data = rooms.map.get(&room)?;
+        let inbox = match INBOXES.try_read() {
+            Ok(g) => g.get(&room).cloned().unwrap_or_default(),
+            Err(_) => {
+                crate::util::signal_guard::schedule_nudge();
+                return None;
+            }
+        };
+
+        let self_sk = room_data.self_sk.clone();
+        let self_id = MemberId::from(&self_sk.verifying_key());
+        let Some(blocked) = blocked_in_room(room, self_id) else {
+            crate::util::signal_guard::schedule_nudge();
+            return None;
+        };
+        let name_of = |id: &MemberId| {
+            room_data
+                .room_state
+                .member_info
+                .member_info
+                .iter()
+                .find(|info| info.member_info.member_id == *id)
+                .map(|info| {
+                    crate::util::display_name::display_nickname(
+                        &info.member_info.preferred_nickname,
+                        &room_data.secrets,
+                    )
+                })
+                .unwrap_or_else(|| short_member_id(id))
+        };
+        let others: Vec<MemberId> = thread.iter().copied().filter(|p| *p != self_id).collect();
+        let missing: Vec<String> = others
+            .iter()
+            .filter(|p| resolve_peer_vk(room_data, room, **p).is_none())
+            .map(&name_of)
+            .collect();
+
+        let params = ChatRoomParametersV1 { owner: room };
+        let mut latest_inbound_ts = 0;
+        let mut messages: Vec<RenderedDm> = inbox
+            .visible_group_messages(&room_data.room_state, &params)
+            .filter(|m| m.message.participants == thread && !blocked.contains(&m.message.sender))
+            .map(|msg| {
+                let outgoing = msg.message.sender == self_id;
+                if !outgoing {
+                    latest_inbound_ts = latest_inbound_ts.max(msg.message.timestamp);
+                }
+                let (body, kind) = match open_group_direct_message(&self_sk, msg) {
+                    Ok(bytes) => match decode_body(&bytes) {
+                        Ok(DirectMessageBody::Text { text }) => (text, BodyKind::Plaintext),
+                        // The UI never sends invites to a group; another
+                        // client might.
+                        Ok(DirectMessageBody::Invite(_)) => (
+                            "invitation — ask for it in a direct message".to_string(),
+                            BodyKind::Placeholder,
+                        ),
+                        Err(err) => (
+                            format!("unable to decode message: {}", err),
+                            BodyKind::Placeholder,
+                        ),
+                    },
+                    Err(err) => (format!("unable to decrypt: {}", err), BodyKind::Placeholder),
+                };
+                RenderedDm {
+                    sender_label: (!outgoing).then(|| name_of(&msg.message.sender)),
+                    outgoing,
+                    timestamp: msg.message.timestamp,
+                    body,
+                    kind,
+                    token: msg.purge_token(),
+                }
+            })
+            .collect();
+        messages.sort_by_key(|d| d.timestamp);
+
+        Some(GroupViewData {
+            title: others.iter().map(&name_of).collect::<Vec<_>>().join(", "),
+            missing,
+            messages,
+            latest_inbound_ts,
+        })
+    });
+
+    let view_value = view.read();
+    let Some(view_data) = view_value.as_ref() else {
+        return rsx! { div { "Room state not available" } };
+    };
+    if view_data.latest_inbound_ts > 0 {
+        mark_group_thread_read(room, participants.clone(), view_data.latest_inbound_ts);
+    }
+
+    // Follow new bubbles like the pair thread: jump on open, always after
+    // our own send, otherwise only when the reader is at the bottom.
+    let last_dm_bubble: Signal<Option<Rc<MountedData>>> = use_signal(|| None);
+    let first_scroll_done = use_hook(|| Rc::new(std::cell::Cell::new(false)));
+    let prev_outbound_bump = use_hook(|| Rc::new(std::cell::Cell::new(0u64)));
+    #[cfg(target_arch = "wasm32")]
+    {
+        let first_scroll_done = first_scroll_done.clone();
+        let prev_outbound_bump = prev_outbound_bump.clone();
+        use_effect(move || {
+            if last_dm_bubble().is_none() {
+                return;
+            }
+            let bump = *OUTBOUND_SEND_COUNTER.peek();
+            let outbound_changed = bump != prev_outbound_bump.replace(bump);
+            let is_first = !first_scroll_done.replace(true);
+            if !(is_first || outbound_changed || is_near_bottom("dm-scroll-container", 50.0)) {
+                return;
+            }
+            let behavior = if is_first {
+                web_sys::ScrollBehavior::Instant
+            } else {
+                web_sys::ScrollBehavior::Smooth
+            };
+            crate::util::safe_spawn_local(async move {
+                scroll_dm_container_to_bottom(behavior);
+            });
+        });
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = (&last_dm_bubble, &first_scroll_done, &prev_outbound_bump);
+    }
+
+    let title = view_data.title.clone();
+    let can_send = view_data.missing.is_empty();
+    let close = move |_| {
+        crate::util::defer(move || {
+            *OPEN_GROUP_DM_THREAD.write() = None;
+        });
+    };
+
+    // A signal, so the callbacks below stay `Copy` like the pair thread's.
+    let thread_participants = use_signal(|| participants.clone());
+    let mut do_send = move || {
+        let body = draft.read().clone();
+        if body.trim().is_empty() {
+            return;
+        }
+        if body.len() > DM_BODY_BYTE_CAP {
+            send_error.set(Some(format!(
+                "Message too long: {} bytes (cap is {} bytes)",
+                body.len(),
+                DM_BODY_BYTE_CAP
+            )));
+            return;
+        }
+        send_error.set(None);
+
+        let Some(room_data) = ROOMS
+            .try_read()
+            .ok()
+            .and_then(|r| r.map.get(&room).cloned())
+        else {
+            error!("Group DM send: room data missing");
+            return;
+        };
+        let self_sk = room_data.self_sk.clone();
+        let self_id = MemberId::from(&self_sk.verifying_key());
+        let Some(others) = thread_participants
+            .peek()
+            .iter()
+            .filter(|p| **p != self_id)
+            .map(|p| resolve_peer_vk(&room_data, room, *p))
+            .collect::<Option<Vec<VerifyingKey>>>()
+        else {
+            send_error.set(Some(
+                "Someone in this group is no longer a member of the room.".into(),
+            ));
+            return;
+        };
+        // As for a pair DM (Bug #1): the invite chain proves membership.
+        let Some(sender_chain) = own_sender_chain(&room_data) else {
+            send_error.set(Some(
+                "You're not currently in this room's member list and no \
+                 rejoin credentials are stored locally. Reload the room or \
+                 re-accept your invitation before sending a DM."
+                    .into(),
+            ));
+            return;
+        };
+        wasm_bindgen_futures::spawn_local(async move {
+            let now = unix_now();
+            let auth = match compose_group_direct_message(
+                &self_sk,
+                &others,
+                &room,
+                now,
+                now,
+                body.as_bytes(),
+            ) {
+                Ok(a) => a,
+                Err(e) => {
+                    error!("compose_group_direct_message failed: {}", e);
+                    send_error.set(Some(format!("Failed to compose DM: {}", e)));
+                    return;
+                }
+            };
+            let inboxes: Vec<VerifyingKey> = std::iter::once(self_sk.verifying_key())
+                .chain(others)
+                .collect();
+            crate::util::defer(move || {
+                match send_to_group_inboxes(room, &inboxes, sender_chain, auth) {
+                    Ok(()) => {
+                        info!("Group DM queued for {} inboxes", inboxes.len());
+                        let next_outbound_counter = OUTBOUND_SEND_COUNTER.peek().wrapping_add(1);
+                        *OUTBOUND_SEND_COUNTER.write() = next_outbound_counter;
+                        draft.set(String::new());
+                    }
+                    Err(e) => {
+                        warn!("Group DM rejected before sending: {}", e);
+                        send_error.set(Some(format!("Couldn't send this message: {}", e)));
+                    }
+                }
+            });
+        });
+    };
+
+    let purge_conversation = move |_| {
+        confirm_delete_open.set(false);
+        let Some(self_sk) = ROOMS
+            .try_read()
+            .ok()
+            .and_then(|r| r.map.get(&room).map(|rd| rd.self_sk.clone()))
+        else {
+            error!("Group DM purge: room data missing");
+            return;
+        };
+        let inbox = INBOXES
+            .try_read()
+            .ok()
+            .and_then(|i| i.get(&room).cloned())
+            .unwrap_or_default();
+        let tokens: Vec<PurgeToken> = inbox
+            .group_messages
+            .iter()
+            .filter(|m| m.message.message.participants == *thread_participants.peek())
+            .map(|m| m.message.purge_token())
+            .collect();
+        if tokens.is_empty() {
+            send_error.set(Some("No messages to delete.".into()));
+            return;
+        }
+        if let Err(e) = purge_from_inbox(room, &self_sk, &inbox, tokens) {
+            warn!("Purging group DMs from the inbox failed: {}", e);
+            send_error.set(Some(
+                "Couldn't delete those messages — something went wrong.".into(),
+            ));
+        }
+    };
+
+    rsx! {
+        div { class: "fixed inset-0 z-50 flex items-center justify-center",
+            div { class: "absolute inset-0 bg-black/50", onclick: close }
+            div { class: "relative z-10 w-full max-w-lg mx-4 bg-panel rounded-xl shadow-xl border border-border flex flex-col max-h-[80vh]",
+                div { class: "flex items-center justify-between px-5 py-4 border-b border-border",
+                    h2 { class: "text-lg font-semibold text-text",
+                        "Group with "
+                        span { class: "text-accent", "{title}" }
+                    }
+                    button {
+                        class: "p-1 text-text-muted hover:text-text transition-colors text-xl",
+                        onclick: close,
+                        "✕"
+                    }
+                }
+                div {
+                    id: "dm-scroll-container",
+                    class: "flex-1 overflow-y-auto px-5 py-4 space-y-2",
+                    if view_data.messages.is_empty() {
+                        p { class: "text-sm text-text-muted italic",
+                            "No messages yet. Say hello!"
+                        }
+                    } else {
+                        {
+                            let messages_len = view_data.messages.len();
+                            view_data.messages.iter().enumerate().map(move |(idx, m)| {
+                                let on_mount = (idx + 1 == messages_len).then_some(last_dm_bubble);
+                                rsx! {
+                                    DmBubble {
+                                        key: "{idx}_{m.timestamp}",
+                                        message: m.clone(),
+                                        last_bubble_sink: on_mount,
+                                    }
+                                }
+                            })
+                        }
+                    }
+                }
+                div { class: "border-t border-border px-5 py-3 space-y-2",
+                    if let Some(err) = send_error.read().as_ref() {
+                        div { class: "text-xs text-red-400", "{err}" }
+                    }
+                    if !can_send {
+                        div { class: "text-xs text-yellow-400",
+                            "{view_data.missing.join(\", \")} left the room — the group can't be written to any more."
+                        }
+                    }
+                    div { class: "flex items-end gap-2",
+                        textarea {
+                            class: "flex-1 px-3 py-2 bg-surface border border-border rounded-lg text-sm text-text resize-none min-h-[2.5rem] max-h-32",
+                            placeholder: "Message the group...",
+                            value: "{draft.read()}",
+                            oninput: move |e| draft.set(e.value()),
+                            onkeydown: move |e| {
+                                if e.key() == Key::Enter && !e.modifiers().shift() {
+                                    e.prevent_default();
+                                    if !draft.read().trim().is_empty() && can_send {
+                                        do_send();
+                                    }
+                                }
+                            },
+                            disabled: !can_send,
+                        }
+                        button {
+                            class: "px-3 py-2 bg-accent hover:bg-accent-hover disabled:opacity-50 text-white text-sm font-medium rounded-lg transition-colors",
+                            disabled: draft.read().trim().is_empty() || !can_send,
+                            onclick: move |_| do_send(),
+                            "Send"
+                        }
+                    }
+                    div { class: "flex justify-between items-center pt-1",
+                        span { class: "text-[10px] text-text-muted",
+                            "Only the members of this group can read these messages."
+                        }
+                        if *confirm_delete_open.read() {
+                            div { class: "flex gap-2",
+                                button {
+                                    class: "text-xs text-text-muted hover:text-text transition-colors",
+                                    onclick: move |_| confirm_delete_open.set(false),
+                                    "Cancel"
+                                }
+                                button {
+                                    class: "text-xs text-red-400 hover:text-red-300 transition-colors",
+                                    onclick: purge_conversation,
+                                    "Delete for me"
+                                }
+                            }
+                        } else {
+                            button {
+                                class: "text-xs text-text-muted hover:text-red-400 transition-colors",
+                                onclick: move |_| confirm_delete_open.set(true),
+                                title: "Removes this conversation from your inbox. The others keep their copies. Cannot be undone.",
+                                "Delete conversation"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct GroupViewData {
+    /// The other participants' names, for the header.
+    title: String,
+    /// Participants no longer in the room; sending needs all of them.
+    missing: Vec<String>,
+    messages: Vec<RenderedDm>,
+    latest_inbound_ts: u64,
+}
+
 #[derive(Clone, PartialEq)]
 struct ViewData {
     peer_nickname: String,
     peer_still_member: bool,
     messages: Vec<RenderedDm>,
     latest_inbound_ts: u64,
+    self_id: MemberId,
+    /// Other members, with display names, offered by "Add people".
+    group_candidates: Vec<(MemberId, String)>,
 }
 
 /// Inbound DM body presentation.
@@ -1089,6 +1617,9 @@ fn classify_invite_card_state(
 
 #[derive(Clone, PartialEq)]
 struct RenderedDm {
+    /// Who wrote an inbound bubble in a group thread; `None` in a pair
+    /// thread, where it is always the peer.
+    sender_label: Option<String>,
     outgoing: bool,
     timestamp: u64,
     body: String,
@@ -1161,6 +1692,9 @@ fn DmBubble(
                     sink.set(Some(cx.data()));
                 }
             },
+            if let Some(label) = message.sender_label.as_ref() {
+                span { class: "self-start text-[10px] text-accent mb-0.5", "{label}" }
+            }
             {bubble_body}
             span {
                 class: if message.outgoing { "self-end text-[10px] text-text-muted mt-0.5" } else { "self-start text-[10px] text-text-muted mt-0.5" },
@@ -684,15 +684,7 @@ async fn drive_send(
             "Couldn't send invite — body too large or encode failed: {}",
             e
         )),
-        SendDmOutcome::DeltaFailed(e) => Err(format!(
-            "Couldn't send invite — local apply_delta failed: {}",
-            e
-        )),
-        SendDmOutcome::SilentDrop => Err(
-            "Invite couldn't be added to the room (your member entry may be \
-             missing). Try posting a message in the room first, then retry."
-                .into(),
-        ),
+        SendDmOutcome::Rejected(e) => Err(format!("Couldn't send invite: {}", e)),
     }
 }
 
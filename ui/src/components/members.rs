@@ -2,19 +2,20 @@ use crate::components::app::freenet_api::freenet_synchronizer::SynchronizerStatu
 use crate::components::app::{
     MobileView, CURRENT_ROOM, MEMBER_INFO_MODAL, MOBILE_VIEW, ROOMS, SYNC_STATUS,
 };
-use crate::util::confusable::{
-    ConfusableTier, ImpersonationChecker, ImpersonationWarning, ProtectedName, ProtectedRole,
-};
 use crate::util::display_name::display_nickname;
 use crate::util::ecies::unseal_bytes_with_secrets;
 use dioxus::prelude::*;
 use dioxus_free_icons::icons::fa_solid_icons::{FaArrowLeft, FaFileExport, FaUserPlus, FaUsers};
 use dioxus_free_icons::Icon;
 use ed25519_dalek::{SigningKey, VerifyingKey};
+use river_core::confusable::{
+    ConfusableTier, ImpersonationChecker, ImpersonationWarning, ProtectedName, ProtectedRole,
+};
+use river_core::room_state::ban::AuthorizedUserBan;
 use river_core::room_state::identity::IdentityExport;
 use river_core::room_state::member::MembersV1;
 use river_core::room_state::member::{AuthorizedMember, MemberId};
-use river_core::room_state::ChatRoomParametersV1;
+use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
@@ -23,8 +24,10 @@ use crate::util::to_cbor_vec;
 use freenet_stdlib::prelude::{ContractCode, ContractKey, Parameters};
 
 pub mod invite_member_modal;
+pub mod join_request_buttons;
 pub mod member_info_modal;
 use self::invite_member_modal::InviteMemberModal;
+use self::join_request_buttons::JoinRequestButtons;
 
 /// Pill-shaped indicator showing the live WebSocket connection state to
 /// the local Freenet node. Rendered in `RoomList`'s bottom section so it
@@ -280,7 +283,7 @@ fn member_display_parts(member: &MemberDisplay) -> MemberDisplayParts {
     // badge on both surfaces.
     if let Some(warning) = member.impersonation.as_ref() {
         tags.push(MemberTag::new(
-            crate::util::confusable::WARNING_GLYPH,
+            river_core::confusable::WARNING_GLYPH,
             "member-list-impersonation-warning",
             warning.tooltip(),
         ));
@@ -847,6 +850,42 @@ pub(crate) fn ban_gate(
     }
 }
 
+/// The bans an Unban by `viewer` would lift: every ban on `target`, provided
+/// the viewer may revoke each one. The contract honours a revocation from the
+/// original banner, anyone above them in the invite chain, or their deputies
+/// (`BanRevocationsV1::revocation_is_effective`), so this asks
+/// [`MembersV1::is_ban_authorized`] about the BANNER, not the target.
+///
+/// `None` when `target` has no ban, or when one of their bans is out of the
+/// viewer's reach: lifting some of the bans would still keep them out, so the
+/// action is not offered.
+pub(crate) fn unban_gate(
+    room_state: &ChatRoomStateV1,
+    viewer: MemberId,
+    target: MemberId,
+    owner_id: MemberId,
+) -> Option<Vec<AuthorizedUserBan>> {
+    let bans: Vec<AuthorizedUserBan> = room_state
+        .bans
+        .0
+        .iter()
+        .filter(|ban| ban.ban.banned_user == target)
+        .cloned()
+        .collect();
+    let members_by_id = room_state.members.members_by_member_id();
+    let revocable = |ban: &AuthorizedUserBan| {
+        viewer == ban.banned_by
+            || MembersV1::is_ban_authorized(
+                viewer,
+                ban.banned_by,
+                &members_by_id,
+                &room_state.member_info,
+                owner_id,
+            )
+    };
+    (!bans.is_empty() && bans.iter().all(revocable)).then_some(bans)
+}
+
 /// The 🛡 shield one member shows in one viewer's view.
 ///
 /// Ian's semantics for the shield (2026-07): *"a deputy shield indicates 'this
@@ -1041,7 +1080,7 @@ pub(crate) fn deputy_badges_for_viewer(
 ///   sanitises to nothing, shared by a whole class of members.
 /// * **A generated handle ⇒ not protected.** A member who never typed a
 ///   nickname wears one of the 10,000 handles River derived from their key
-///   ([`crate::nickname::is_generated_handle`]). Two members can be assigned the
+///   ([`river_core::nickname::is_generated_handle`]). Two members can be assigned the
 ///   same one, which is a collision River created rather than an imitation
 ///   either of them performed; with ~120 members in a room it is more likely
 ///   than not that some pair shares a handle. Protecting an unclaimed name would
@@ -1097,7 +1136,7 @@ pub(crate) fn impersonation_checker_for_viewer(
     // `Unkn0wn` and `Unknovvn` are all `!= "Unknown"` yet all three tier-1 match
     // every member rendering as `Unknown`, and the whole `Amber Worm` family
     // (`amber worm`, `AmberWorm`, `Arnber Worm`, ...) does the same to a
-    // generated handle. See [`crate::nickname::folds_to_generated_handle`].
+    // generated handle. See [`river_core::nickname::folds_to_generated_handle`].
     let claimed_name = |id: MemberId| -> Option<String> {
         let sealed = &member_info.canonical(id)?.member_info.preferred_nickname;
         if unseal_bytes_with_secrets(sealed, room_secrets).is_err() {
@@ -1108,11 +1147,11 @@ pub(crate) fn impersonation_checker_for_viewer(
             crate::util::display_name::UNNAMED,
             crate::util::display_name::UNKNOWN_MEMBER,
         ] {
-            if crate::util::confusable::folds_together(&name, placeholder) {
+            if river_core::confusable::folds_together(&name, placeholder) {
                 return None;
             }
         }
-        (!crate::nickname::folds_to_generated_handle(&name)).then_some(name)
+        (!river_core::nickname::folds_to_generated_handle(&name)).then_some(name)
     };
 
     let mut protected = Vec::new();
@@ -1206,7 +1245,7 @@ pub(crate) fn impersonation_checker_for_viewer(
 /// `Amber Worm` / `Ember Worm` are one edit apart, so the near-miss tier accuses
 /// a pair of members River itself created — before any attacker acts. Tier 1 is
 /// clean on the same population (no two handles share a skeleton). Both facts
-/// are pinned in [`crate::util::confusable`] by
+/// are pinned in [`river_core::confusable`] by
 /// `generated_handles_never_fold_to_the_same_skeleton` and
 /// `generated_handles_are_within_the_near_miss_budget`.
 ///
@@ -1224,7 +1263,7 @@ pub(crate) fn impersonation_checker_for_viewer(
 /// drops spaces, so `Host Fat` matches `HostFat` and `Jo Anna` matches
 /// `Joanna` — names a reader can plainly tell apart. That rule earns its place
 /// by catching `IanClarke`, and its cost is enumerated in
-/// [`crate::util::confusable`]'s "Honest limits" and pinned row by row in
+/// [`river_core::confusable`]'s "Honest limits" and pinned row by row in
 /// `documented_accepted_collisions`.
 pub(crate) fn impersonation_warning_for_display(
     checker: &ImpersonationChecker,
@@ -1495,6 +1534,78 @@ pub fn MemberList() -> Element {
     })()
     .unwrap_or_default();
 
+    // Banned members this viewer could unban. They are no longer members, so
+    // this is the only place they can be reached from; clicking one opens the
+    // member-info modal, which offers Unban.
+    let unbannable = use_memo(move || {
+        crate::util::signal_guard::anchor();
+        let room_owner = CURRENT_ROOM.read().owner_key?;
+        let Ok(rooms_read) = ROOMS.try_read() else {
+            crate::util::signal_guard::schedule_nudge();
+            return None;
+        };
+        let room_data = rooms_read.map.get(&room_owner)?;
+        let room_state = &room_data.room_state;
+        let self_member_id: MemberId = room_data.self_sk.verifying_key().into();
+        let present: HashSet<MemberId> = room_state
+            .members
+            .members
+            .iter()
+            .map(|m| m.member.id())
+            .collect();
+        let targets: Vec<MemberId> = room_state
+            .bans
+            .0
+            .iter()
+            .map(|ban| ban.ban.banned_user)
+            .filter(|target| !present.contains(target))
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .filter(|target| {
+                unban_gate(room_state, self_member_id, *target, room_owner.into()).is_some()
+            })
+            .collect();
+        Some(targets)
+    })()
+    .unwrap_or_default();
+
+    // Join requests waiting for a decision, shown to members only: anyone in
+    // the room may approve or deny them. The nickname and message come from a
+    // non-member, so they are sanitised like any nickname.
+    let join_requests = use_memo(move || {
+        crate::util::signal_guard::anchor();
+        let room_owner = CURRENT_ROOM.read().owner_key?;
+        let Ok(rooms_read) = ROOMS.try_read() else {
+            crate::util::signal_guard::schedule_nudge();
+            return None;
+        };
+        let room_data = rooms_read.map.get(&room_owner)?;
+        let room_state = &room_data.room_state;
+        let self_vk = room_data.self_sk.verifying_key();
+        let self_member_id: MemberId = self_vk.into();
+        if self_vk != room_owner
+            && !room_state
+                .members
+                .members_by_member_id()
+                .contains_key(&self_member_id)
+        {
+            return None;
+        }
+        let requests: Vec<(VerifyingKey, String, String)> = room_state
+            .join_requests
+            .pending()
+            .map(|request| {
+                (
+                    request.request.requester,
+                    crate::util::display_name::sanitize_display_name(&request.request.nickname),
+                    crate::util::display_name::sanitize_display_name(&request.request.message),
+                )
+            })
+            .collect();
+        Some(requests)
+    })()
+    .unwrap_or_default();
+
     let handle_member_click = move |member_id| {
         crate::util::defer(move || {
             MEMBER_INFO_MODAL.with_mut(|signal| {
@@ -1587,6 +1698,54 @@ pub fn MemberList() -> Element {
                 }
             }
 
+            if !unbannable.is_empty() {
+                div {
+                    "data-testid": "banned-member-list",
+                    class: "px-2 py-2 border-t border-border flex-shrink-0 max-h-40 overflow-y-auto",
+                    h3 { class: "px-3 pb-1 text-xs font-semibold text-text-muted uppercase tracking-wide",
+                        "Banned"
+                    }
+                    for member_id in unbannable {
+                        button {
+                            key: "{member_id}",
+                            "data-testid": "banned-member-item-{member_id}",
+                            class: "w-full text-left px-3 py-1 rounded-lg text-sm text-text-muted hover:bg-surface transition-colors truncate",
+                            title: "Member ID: {member_id}",
+                            onclick: move |_| handle_member_click(member_id),
+                            "{member_id}"
+                        }
+                    }
+                }
+            }
+
+            if !join_requests.is_empty() {
+                div {
+                    "data-testid": "join-request-list",
+                    class: "px-2 py-2 border-t border-border flex-shrink-0 max-h-48 overflow-y-auto",
+                    h3 { class: "px-3 pb-1 text-xs font-semibold text-text-muted uppercase tracking-wide",
+                        "Join requests"
+                    }
+                    for (requester, nickname, message) in join_requests {
+                        div {
+                            key: "{MemberId::from(&requester)}",
+                            "data-testid": "join-request-item-{MemberId::from(&requester)}",
+                            class: "px-3 py-1 text-sm",
+                            div { class: "flex items-center gap-2 min-w-0",
+                                span {
+                                    class: "truncate min-w-0 flex-1 text-text",
+                                    title: "Requester ID: {MemberId::from(&requester)}",
+                                    "{nickname}"
+                                }
+                                JoinRequestButtons { requester }
+                            }
+                            if !message.is_empty() {
+                                p { class: "text-xs text-text-muted break-words", "{message}" }
+                            }
+                        }
+                    }
+                }
+            }
+
             // Action buttons - fixed at bottom
             div { class: "p-3 border-t border-border flex-shrink-0 space-y-2",
                 button {
@@ -1855,6 +2014,7 @@ fn build_imported_room_data(export: IdentityExport) -> crate::room_data::RoomDat
         self_sk: export.signing_key,
         contract_key,
         last_read_message_id: None,
+        thread_read: Default::default(),
         secrets: HashMap::new(),
         current_secret_version: None,
         last_secret_rotation: None,
@@ -1892,6 +2052,7 @@ fn build_imported_room_data(export: IdentityExport) -> crate::room_data::RoomDat
 /// self_sk                 REPLACE (the imported identity)
 /// contract_key            KEEP (owner+WASM derived)
 /// last_read_message_id    KEEP (local read-tracking preference)
+/// thread_read             KEEP (local read-tracking preference)
 /// secrets                 CLEAR+RECOMPUTE different-key (repopulate_secrets_from_state) / KEEP same-key
 /// current_secret_version  CLEAR+RECOMPUTE different-key / KEEP same-key
 /// last_secret_rotation    CLEAR different-key / KEEP same-key
@@ -1911,6 +2072,7 @@ fn _room_data_swap_classification(rd: crate::room_data::RoomData) {
         self_sk: _,
         contract_key: _,
         last_read_message_id: _,
+        thread_read: _,
         secrets: _,
         current_secret_version: _,
         last_secret_rotation: _,
@@ -2619,6 +2781,7 @@ mod tests {
                 nickname.as_bytes().to_vec(),
             ),
             deputies,
+            dm_prekey: None,
         };
         AuthorizedMemberInfo::new_with_member_key(mi, sk)
     }
@@ -2659,6 +2822,53 @@ mod tests {
     /// routes to the overwrite-confirm path, NOT a hard error. The component
     /// branches on `import_room_identity_exists`, so pin that decision: true
     /// when the room is present, false when absent.
+    /// Unban is offered to the banner and anyone above them, but not to an
+    /// unrelated member, and not at all for someone who was never banned.
+    #[test]
+    fn unban_gate_follows_the_banner_not_the_target() {
+        use river_core::room_state::ban::{BansV1, UserBan};
+        let owner_sk = SigningKey::from_bytes(&[61u8; 32]);
+        let a_sk = SigningKey::from_bytes(&[62u8; 32]);
+        let b_sk = SigningKey::from_bytes(&[63u8; 32]);
+        let c_sk = SigningKey::from_bytes(&[64u8; 32]);
+        let owner_id = MemberId::from(&owner_sk.verifying_key());
+        let [a_id, b_id, c_id] =
+            [&a_sk, &b_sk, &c_sk].map(|sk| MemberId::from(&sk.verifying_key()));
+
+        let ban = AuthorizedUserBan::new(
+            UserBan {
+                owner_member_id: owner_id,
+                banned_at: std::time::SystemTime::UNIX_EPOCH,
+                banned_user: b_id,
+                reason: None,
+                duration: None,
+            },
+            a_id,
+            &a_sk,
+        );
+        let state = ChatRoomStateV1 {
+            members: MembersV1 {
+                members: vec![
+                    authorized_member(&owner_sk, &a_sk.verifying_key()),
+                    authorized_member(&owner_sk, &c_sk.verifying_key()),
+                ],
+            },
+            bans: BansV1(vec![ban.clone()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            unban_gate(&state, a_id, b_id, owner_id),
+            Some(vec![ban.clone()])
+        );
+        assert_eq!(
+            unban_gate(&state, owner_id, b_id, owner_id),
+            Some(vec![ban])
+        );
+        assert_eq!(unban_gate(&state, c_id, b_id, owner_id), None);
+        assert_eq!(unban_gate(&state, a_id, c_id, owner_id), None);
+    }
+
     #[test]
     fn existing_room_import_routes_to_confirm() {
         let owner_sk = SigningKey::from_bytes(&[41u8; 32]);
@@ -2859,6 +3069,7 @@ mod tests {
                 b"nick".to_vec(),
             ),
             deputies: vec![],
+            dm_prekey: None,
         };
         AuthorizedMemberInfo::new_with_member_key(mi, sk)
     }
@@ -3622,6 +3833,7 @@ mod tests {
                     b"nick".to_vec(),
                 ),
                 deputies,
+                dm_prekey: None,
             };
             AuthorizedMemberInfo::new_with_member_key(mi, sk)
         };
@@ -4833,6 +5045,7 @@ mod tests {
                     b"n".to_vec(),
                 ),
                 deputies,
+                dm_prekey: None,
             };
             AuthorizedMemberInfo::new_with_member_key(mi, sk)
         };
@@ -5261,7 +5474,7 @@ mod tests {
     }
 
     // ---------------------------------------------------------------
-    // Impersonation warning (⚠) — wiring of `crate::util::confusable`
+    // Impersonation warning (⚠) — wiring of `river_core::confusable`
     // ---------------------------------------------------------------
 
     /// A room with `owner` (nickname `Room Owner`), a global moderator `mod`
@@ -5443,6 +5656,7 @@ mod tests {
                     version: 0,
                     preferred_nickname: nickname,
                     deputies: vec![],
+                    dm_prekey: None,
                 },
                 sk,
             )
@@ -5468,6 +5682,7 @@ mod tests {
                             version: 0,
                             preferred_nickname: sealed(12, 0),
                             deputies: vec![id(&mod_sk)],
+                            dm_prekey: None,
                         },
                         &owner_sk,
                     )
@@ -6017,7 +6232,7 @@ mod tests {
             "Amber Worrn",
         ] {
             assert!(
-                !crate::nickname::is_generated_handle(styled),
+                !river_core::nickname::is_generated_handle(styled),
                 "precondition: `{styled}` is NOT an exact generated handle, \
                  which is why the exact filter passed it"
             );
@@ -6409,11 +6624,11 @@ mod tests {
         // happens not to match.
         let handle = format!(
             "{} {}",
-            crate::nickname::FIRST_NAMES[0],
-            crate::nickname::LAST_NAMES[0]
+            river_core::nickname::FIRST_NAMES[0],
+            river_core::nickname::LAST_NAMES[0]
         );
         assert!(
-            crate::nickname::is_generated_handle(&handle),
+            river_core::nickname::is_generated_handle(&handle),
             "precondition: {handle:?} must be a handle River can assign"
         );
 
@@ -6778,7 +6993,7 @@ mod tests {
         let (glyph, tooltip) = (&first.glyph, &first.tooltip);
         assert_eq!(
             *glyph,
-            crate::util::confusable::WARNING_GLYPH,
+            river_core::confusable::WARNING_GLYPH,
             "the impersonation warning must be the FIRST tag, next to the name; \
              tags were {:?}",
             parts.tags.iter().map(|t| t.glyph).collect::<Vec<_>>()
@@ -6796,7 +7011,7 @@ mod tests {
         assert!(!member_display_parts(&display)
             .tags
             .iter()
-            .any(|t| t.glyph == crate::util::confusable::WARNING_GLYPH));
+            .any(|t| t.glyph == river_core::confusable::WARNING_GLYPH));
     }
 
     /// **The residual #488 knowingly left open, and this warning is its only
@@ -6850,8 +7065,8 @@ mod tests {
         //    `is_display_hidden` directly and the selectors are still inside
         //    its plane-14 range.
         assert_eq!(
-            crate::util::confusable::skeleton(&clone),
-            crate::util::confusable::skeleton(real),
+            river_core::confusable::skeleton(&clone),
+            river_core::confusable::skeleton(real),
             "the confusable fold must see through an IVS, or the warning \
              cannot fire on this residual"
         );
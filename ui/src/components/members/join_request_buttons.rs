@@ -0,0 +1,125 @@
+use crate::components::app::{CURRENT_ROOM, ROOMS};
+use crate::util::get_current_system_time;
+use dioxus::logger::tracing::{error, info};
+use dioxus::prelude::*;
+use ed25519_dalek::VerifyingKey;
+use freenet_scaffold::ComposableState;
+use river_core::room_state::join_request::{
+    AuthorizedJoinDecision, JoinDecision, JoinOutcome, JoinRequestsV1,
+};
+use river_core::room_state::member::{AuthorizedMember, Member, MemberId};
+use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1Delta};
+
+/// Approve and Deny for one join request. An approval carries the member
+/// entry the viewer signs for the requester's key, so the requester joins as
+/// invited by the viewer. Any member may decide; the newest decision stands.
+#[component]
+pub fn JoinRequestButtons(requester: VerifyingKey) -> Element {
+    let decide = move |approve: bool| {
+        let Some(current_room) = CURRENT_ROOM.read().owner_key else {
+            return;
+        };
+        let Some(room_data) = ROOMS
+            .try_read()
+            .ok()
+            .and_then(|rooms| rooms.map.get(&current_room).cloned())
+        else {
+            return;
+        };
+        let room_key = room_data.room_key();
+        let self_sk = room_data.self_sk.clone();
+        let decided_by = MemberId::from(&self_sk.verifying_key());
+        let owner_member_id = MemberId::from(&current_room);
+
+        crate::util::safe_spawn_local(async move {
+            let outcome = if approve {
+                let member = Member {
+                    owner_member_id,
+                    invited_by: decided_by,
+                    member_vk: requester,
+                };
+                let mut member_bytes = Vec::new();
+                if let Err(e) = ciborium::ser::into_writer(&member, &mut member_bytes) {
+                    error!("Failed to serialize member for signing: {:?}", e);
+                    return;
+                }
+                let signature =
+                    crate::signing::sign_member_with_fallback(room_key, member_bytes, &self_sk)
+                        .await;
+                JoinOutcome::Approved(Box::new(AuthorizedMember::with_signature(
+                    member, signature,
+                )))
+            } else {
+                JoinOutcome::Denied
+            };
+            let decision = JoinDecision {
+                owner_member_id,
+                requester,
+                decided_at: get_current_system_time(),
+                outcome,
+            };
+            let mut decision_bytes = Vec::new();
+            if let Err(e) = ciborium::ser::into_writer(&decision, &mut decision_bytes) {
+                error!("Failed to serialize join decision for signing: {:?}", e);
+                return;
+            }
+            // Signed through the ban request, as mutes are: the delegate
+            // signs whatever bytes it is handed.
+            let signature =
+                crate::signing::sign_ban_with_fallback(room_key, decision_bytes, &self_sk).await;
+
+            let delta = ChatRoomStateV1Delta {
+                join_requests: Some(JoinRequestsV1 {
+                    requests: Vec::new(),
+                    decisions: vec![AuthorizedJoinDecision::with_signature(
+                        decision, decided_by, signature,
+                    )],
+                }),
+                ..Default::default()
+            };
+
+            crate::util::defer(move || {
+                ROOMS.with_mut(|rooms| {
+                    if let Some(room_data_mut) = rooms.map.get_mut(&current_room) {
+                        let parent = room_data_mut.room_state.clone();
+                        if let Err(e) = room_data_mut.room_state.apply_delta(
+                            &parent,
+                            &ChatRoomParametersV1 {
+                                owner: current_room,
+                            },
+                            &Some(delta),
+                        ) {
+                            error!("Failed to apply join decision delta: {:?}", e);
+                        } else {
+                            info!(
+                                "Applied join decision for {:?} (approved: {})",
+                                MemberId::from(&requester),
+                                approve
+                            );
+                        }
+                    }
+                });
+                crate::components::app::mark_needs_sync(current_room);
+            });
+        });
+    };
+
+    rsx! {
+        div { class: "flex gap-1 flex-shrink-0",
+            button {
+                "data-testid": "approve-join-request",
+                class: "px-2 py-0.5 bg-accent hover:bg-accent-hover text-white text-xs rounded transition-colors",
+                title: "Let them in, as invited by you",
+                onclick: move |_| decide(true),
+                "Approve"
+            }
+            button {
+                "data-testid": "deny-join-request",
+                class: "px-2 py-0.5 bg-surface hover:bg-surface-hover text-text-muted text-xs rounded transition-colors border border-border",
+                title: "Turn the request down; they cannot ask again until someone approves them",
+                onclick: move |_| decide(false),
+                "Deny"
+            }
+        }
+    }
+}
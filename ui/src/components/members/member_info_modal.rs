@@ -1,15 +1,21 @@
 mod ban_button;
+mod block_button;
 mod deputy_button;
+mod dm_prekey_toggle;
 mod invited_by_field;
+mod mute_button;
 mod nickname_field;
 
 use crate::components::app::{CURRENT_ROOM, MEMBER_INFO_MODAL, ROOMS};
 use crate::components::direct_messages::{open_dm_thread, open_invite_via_dm_picker};
-use crate::components::members::member_info_modal::ban_button::BanButton;
+use crate::components::members::member_info_modal::ban_button::{BanButton, UnbanButton};
+use crate::components::members::member_info_modal::block_button::BlockButton;
 use crate::components::members::member_info_modal::deputy_button::DeputyButton;
+use crate::components::members::member_info_modal::dm_prekey_toggle::DmPrekeyToggle;
 use crate::components::members::member_info_modal::invited_by_field::InvitedByField;
+use crate::components::members::member_info_modal::mute_button::MuteButton;
 use crate::components::members::member_info_modal::nickname_field::NicknameField;
-use crate::components::members::{ban_gate, BanGate};
+use crate::components::members::{ban_gate, unban_gate, BanGate};
 use crate::util::display_name::display_nickname;
 use dioxus::logger::tracing::*;
 use dioxus::prelude::*;
@@ -114,6 +120,85 @@ pub fn MemberInfoModal() -> Element {
         // read a losing (e.g. revoked) record (freenet/river#411 round 8).
         let member_info = match member_info_v1.canonical(member_id) {
             Some(mi) => mi,
+            // A banned member's `member_info` is pruned along with them, so the
+            // rail's Banned list opens this modal with nothing to show but the
+            // ban itself.
+            None if !members_list.iter().any(|m| m.member.id() == member_id) => {
+                let Some(bans) = unban_gate(
+                    &room_state.room_state,
+                    self_member_id,
+                    member_id,
+                    room_state.owner_id(),
+                ) else {
+                    return rsx! {};
+                };
+                // Reason and term of each ban, as its banner recorded them.
+                let ban_details: Vec<(String, Option<String>)> = bans
+                    .iter()
+                    .map(|ban| {
+                        let term = match ban.ban.expires_at() {
+                            Some(expires_at) => {
+                                let ms = expires_at
+                                    .duration_since(std::time::UNIX_EPOCH)
+                                    .map(|d| d.as_millis() as i64)
+                                    .unwrap_or(0);
+                                format!(
+                                    "Temporary, ends {}",
+                                    crate::util::format_utc_as_full_datetime(ms)
+                                )
+                            }
+                            None => "Permanent".to_string(),
+                        };
+                        let reason = ban.ban.reason.as_ref().map(|reason| {
+                            crate::util::ecies::unseal_bytes_with_secrets(
+                                reason,
+                                &room_state.secrets,
+                            )
+                            .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+                            .unwrap_or_else(|_| reason.to_string_lossy())
+                        });
+                        (term, reason)
+                    })
+                    .collect();
+                return rsx! {
+                    div {
+                        class: "fixed inset-0 z-50 flex items-center justify-center",
+                        div {
+                            class: "absolute inset-0 bg-black/50",
+                            onclick: handle_close_modal
+                        }
+                        div {
+                            "data-testid": "member-info-modal",
+                            class: "relative z-10 w-full max-w-md mx-4 bg-panel rounded-xl shadow-xl border border-border",
+                            div {
+                                class: "p-6",
+                                h1 { class: "text-xl font-semibold text-text mb-4", "Banned Member" }
+                                p { class: "text-sm text-text-muted mb-4",
+                                    "Member ID: "
+                                    code { class: "text-xs bg-surface px-1 rounded", "{member_id}" }
+                                }
+                                for (term, reason) in ban_details {
+                                    div {
+                                        "data-testid": "ban-details",
+                                        class: "text-sm mb-4",
+                                        p { class: "text-text", "{term}" }
+                                        if let Some(reason) = reason {
+                                            p { class: "text-text-muted", "Reason: {reason}" }
+                                        }
+                                    }
+                                }
+                                UnbanButton { banned_member: member_id, bans }
+                            }
+                            button {
+                                "data-testid": "member-info-close-button",
+                                class: "absolute top-3 right-3 p-1 text-text-muted hover:text-text transition-colors",
+                                onclick: handle_close_modal,
+                                "✕"
+                            }
+                        }
+                    }
+                };
+            }
             None => {
                 error!("Member info not found for member {member_id}");
                 return rsx! {
@@ -195,6 +280,19 @@ pub fn MemberInfoModal() -> Element {
             BanGate::Allowed | BanGate::NoAuthority => None,
         };
 
+        // Mute authority is the contract's: `is_ban_authorized`, without
+        // `ban_gate`'s self-removal rule, since a mute removes nobody.
+        let can_mute = owner_key_signal.as_ref().is_some_and(|owner| {
+            river_core::room_state::member::MembersV1::is_ban_authorized(
+                self_member_id,
+                member_id,
+                &room_state.room_state.members.members_by_member_id(),
+                &room_state.room_state.member_info,
+                MemberId::from(&*owner),
+            )
+        });
+        let is_muted = room_state.muted_members().contains_key(&member_id);
+
         info!(
             "Rendering MemberInfoModal for member_id: {:?} is_owner: {:?} is_downstream: {:?} can_ban: {:?}",
             member_id, is_owner, is_downstream, can_ban
@@ -336,7 +434,7 @@ pub fn MemberInfoModal() -> Element {
         });
         let impersonation_tooltip = impersonation
             .as_ref()
-            .map(crate::util::confusable::ImpersonationWarning::tooltip)
+            .map(river_core::confusable::ImpersonationWarning::tooltip)
             .unwrap_or_default();
         // The chip's label is the tooltip's OWN leading clause (everything
         // before the first colon: "Impersonation warning" or "Name conflict").
@@ -446,7 +544,7 @@ pub fn MemberInfoModal() -> Element {
                                     class: "inline-flex items-center px-2.5 py-0.5 rounded-full text-sm font-medium bg-amber-500/20 text-amber-400",
                                     title: "{impersonation_tooltip}",
                                     "aria-label": "{impersonation_tooltip}",
-                                    "{crate::util::confusable::WARNING_GLYPH} {impersonation_label}"
+                                    "{river_core::confusable::WARNING_GLYPH} {impersonation_label}"
                                 }
                             }
                         }
@@ -518,6 +616,12 @@ pub fn MemberInfoModal() -> Element {
                             member_info: member_info.clone()
                         }
 
+                        if member_id == self_member_id {
+                            DmPrekeyToggle {
+                                enabled: member_info.member_info.dm_prekey.is_some()
+                            }
+                        }
+
                         div {
                             class: "mb-4",
                             label { class: "block text-sm font-medium text-text-muted mb-2", "Member ID" }
@@ -542,6 +646,11 @@ pub fn MemberInfoModal() -> Element {
                             {
                                 let dm_room = owner_key_signal.unwrap();
                                 let share_button_enabled = other_rooms_count > 0;
+                                let blocked = crate::components::app::block_list::blocked_in_room(
+                                    dm_room,
+                                    self_member_id,
+                                )
+                                .is_some_and(|blocked| blocked.contains(&member_id));
                                 rsx! {
                                     div { class: "mb-4 flex gap-2",
                                         button {
@@ -586,6 +695,7 @@ pub fn MemberInfoModal() -> Element {
                                             },
                                             "Share invite"
                                         }
+                                        BlockButton { room: dm_room, target: member_id, blocked }
                                     }
                                 }
                             }
@@ -643,6 +753,10 @@ pub fn MemberInfoModal() -> Element {
                                         }
                                     }
 
+                                    if can_mute {
+                                        MuteButton { target: member_id, muted: is_muted }
+                                    }
+
                                     // Deputize / revoke-deputy (#410). Any non-owner
                                     // member (except self) may be deputized; the action
                                     // hides itself when the viewer lacks authority.
@@ -1,13 +1,25 @@
 use crate::components::app::{CURRENT_ROOM, MEMBER_INFO_MODAL, ROOMS};
 use crate::room_data::RoomData;
+use crate::util::ecies::seal_for_room;
 use crate::util::get_current_system_time;
 use dioxus::logger::tracing::{error, info, warn};
 use dioxus::prelude::*;
 use freenet_scaffold::ComposableState;
-use river_core::room_state::ban::{AuthorizedUserBan, UserBan};
+use river_core::room_state::ban::{AuthorizedUserBan, UserBan, MAX_BAN_REASON_BYTES};
+use river_core::room_state::ban_revocation::{AuthorizedBanRevocation, BanRevocation};
 use river_core::room_state::member::MemberId;
 use river_core::room_state::privacy::PrivacyMode;
 use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1Delta};
+use std::time::Duration;
+
+/// Ban lengths offered in the confirmation dialog, in seconds; `0` is permanent.
+const BAN_DURATIONS: [(u64, &str); 5] = [
+    (0, "Permanent"),
+    (60 * 60, "1 hour"),
+    (24 * 60 * 60, "1 day"),
+    (7 * 24 * 60 * 60, "1 week"),
+    (30 * 24 * 60 * 60, "30 days"),
+];
 
 #[component]
 pub fn BanButton(member_to_ban: MemberId, can_ban: bool, nickname: String) -> Element {
@@ -29,6 +41,8 @@ pub fn BanButton(member_to_ban: MemberId, can_ban: bool, nickname: String) -> El
     });
 
     let mut show_confirmation = use_signal(|| false);
+    let mut reason_input = use_signal(String::new);
+    let mut duration_secs = use_signal(|| 0u64);
 
     let execute_ban = move |_| {
         if let (Some(current_room), Some(room_data)) = (
@@ -62,10 +76,35 @@ pub fn BanButton(member_to_ban: MemberId, can_ban: bool, nickname: String) -> El
                 return;
             }
 
+            // A reason is sealed like a nickname (freenet/river#299). If a
+            // private room's secret has not synced yet the ban still goes out,
+            // just without its reason: dropping the note beats leaking it or
+            // delaying the ban.
+            let reason_text = reason_input.read().trim().to_string();
+            let reason = if reason_text.is_empty() {
+                None
+            } else {
+                let sealed = seal_for_room(
+                    room_data.is_private(),
+                    room_data.get_secret(),
+                    reason_text.into_bytes(),
+                );
+                if sealed.is_none() {
+                    warn!("Room secret not available yet — banning without the reason");
+                }
+                sealed
+            };
+            let duration = match *duration_secs.read() {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            };
+
             let ban = UserBan {
                 owner_member_id: MemberId::from(&current_room),
                 banned_at: get_current_system_time(),
                 banned_user: member_to_ban,
+                reason,
+                duration,
             };
 
             // Whether the room is private — drives the synchronous
@@ -237,6 +276,34 @@ pub fn BanButton(member_to_ban: MemberId, can_ban: bool, nickname: String) -> El
                                     code { class: "text-sm bg-surface px-1 rounded", "{member_to_ban}" }
                                     ")? This action cannot be undone."
                                 }
+                                label { class: "block text-sm text-text-muted mt-4 mb-1", "Reason (optional)" }
+                                input {
+                                    class: "w-full px-3 py-2 bg-surface border border-border rounded-lg text-text",
+                                    maxlength: "{MAX_BAN_REASON_BYTES}",
+                                    value: "{reason_input}",
+                                    oninput: move |evt| {
+                                        let mut value = evt.value();
+                                        while value.len() > MAX_BAN_REASON_BYTES {
+                                            value.pop();
+                                        }
+                                        reason_input.set(value);
+                                    }
+                                }
+                                label { class: "block text-sm text-text-muted mt-3 mb-1", "Duration" }
+                                select {
+                                    class: "w-full px-3 py-2 bg-surface border border-border rounded-lg text-text",
+                                    oninput: move |evt| {
+                                        duration_secs.set(evt.value().parse().unwrap_or(0));
+                                    },
+                                    for (secs, label) in BAN_DURATIONS {
+                                        option { value: "{secs}", "{label}" }
+                                    }
+                                }
+                                if *duration_secs.read() != 0 {
+                                    p { class: "text-xs text-text-muted mt-2",
+                                        "A temporary ban lapses once you, the owner or another moderator above you posts after it ends."
+                                    }
+                                }
                             }
 
                             // Footer
@@ -262,3 +329,94 @@ pub fn BanButton(member_to_ban: MemberId, can_ban: bool, nickname: String) -> El
         rsx! { "" }
     }
 }
+
+/// Lifts `bans`, every ban on `banned_member`, by signing a revocation of each
+/// (see `river_core::room_state::ban_revocation`). The caller only renders this
+/// when `unban_gate` says the viewer may revoke them all.
+#[component]
+pub fn UnbanButton(banned_member: MemberId, bans: Vec<AuthorizedUserBan>) -> Element {
+    let execute_unban = move |_| {
+        let Some(current_room) = CURRENT_ROOM.read().owner_key else {
+            return;
+        };
+        let Some(room_data) = ROOMS
+            .try_read()
+            .ok()
+            .and_then(|rooms| rooms.map.get(&current_room).cloned())
+        else {
+            return;
+        };
+        let room_key = room_data.room_key();
+        let self_sk = room_data.self_sk.clone();
+        let revoked_by = MemberId::from(&self_sk.verifying_key());
+        let bans = bans.clone();
+
+        crate::util::defer(move || {
+            MEMBER_INFO_MODAL.with_mut(|modal| {
+                modal.member = None;
+            });
+        });
+
+        crate::util::safe_spawn_local(async move {
+            let revoked_at = get_current_system_time();
+            let mut revocations = Vec::with_capacity(bans.len());
+            for ban in &bans {
+                let revocation = BanRevocation::of(ban, revoked_at);
+                let mut revocation_bytes = Vec::new();
+                if let Err(e) = ciborium::ser::into_writer(&revocation, &mut revocation_bytes) {
+                    error!("Failed to serialize ban revocation for signing: {:?}", e);
+                    return;
+                }
+                // The delegate signs whatever bytes it is handed; the ban
+                // request is reused rather than adding a request type, which
+                // would change the delegate and its key.
+                let signature =
+                    crate::signing::sign_ban_with_fallback(room_key, revocation_bytes, &self_sk)
+                        .await;
+                revocations.push(AuthorizedBanRevocation::with_signature(
+                    revocation, revoked_by, signature,
+                ));
+            }
+
+            let delta = ChatRoomStateV1Delta {
+                ban_revocations: Some(revocations),
+                ..Default::default()
+            };
+
+            crate::util::defer(move || {
+                ROOMS.with_mut(|rooms| {
+                    if let Some(room_data_mut) = rooms.map.get_mut(&current_room) {
+                        let parent = room_data_mut.room_state.clone();
+                        if let Err(e) = room_data_mut.room_state.apply_delta(
+                            &parent,
+                            &ChatRoomParametersV1 {
+                                owner: current_room,
+                            },
+                            &Some(delta),
+                        ) {
+                            error!("Failed to apply ban revocation delta: {:?}", e);
+                        } else {
+                            info!(
+                                "Successfully applied ban revocation for member {:?}",
+                                banned_member
+                            );
+                            // Same #310 re-derivation as after a ban.
+                            room_data_mut.rebuild_private_actions_state();
+                        }
+                    }
+                });
+                crate::components::app::mark_needs_sync(current_room);
+            });
+        });
+    };
+
+    rsx! {
+        button {
+            "data-testid": "unban-button",
+            class: "px-4 py-2 bg-surface hover:bg-surface-hover text-text font-medium rounded-lg transition-colors border border-border whitespace-nowrap",
+            title: "Lift the ban so this member can be invited again",
+            onclick: execute_unban,
+            "Unban"
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Block (or unblock) a member for yourself. Unlike Ban and Mute this needs
+//! no authority and publishes nothing: their messages disappear from your
+//! conversation view and their DMs are purged from your inbox. See
+//! `app::block_list`.
+
+use crate::components::app::block_list;
+use dioxus::prelude::*;
+use ed25519_dalek::VerifyingKey;
+use river_core::room_state::member::MemberId;
+
+#[component]
+pub fn BlockButton(room: VerifyingKey, target: MemberId, blocked: bool) -> Element {
+    let toggle = move |_| {
+        if blocked {
+            block_list::unblock_member(room, target);
+        } else {
+            block_list::block_member(room, target);
+        }
+    };
+
+    rsx! {
+        button {
+            "data-testid": "member-info-block-button",
+            class: "flex-1 px-3 py-1.5 bg-surface hover:bg-surface-hover text-text text-sm font-medium rounded-lg transition-colors border border-border",
+            title: if blocked {
+                "Show this member's messages again. DMs already deleted stay deleted."
+            } else {
+                "Hide this member's messages from you and delete their DMs to you. Only you are affected."
+            },
+            onclick: toggle,
+            if blocked { "Unblock" } else { "Block" }
+        }
+    }
+}
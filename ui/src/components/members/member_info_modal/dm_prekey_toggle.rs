@@ -0,0 +1,88 @@
+//! Opt in to (or out of) forward-secret DMs, shown on the local member's own
+//! profile. Turning it on rotates the prekey in the chat delegate and then
+//! republishes the member's `MemberInfo` with its public half, like
+//! `deputy_button.rs`; turning it off republishes without one and only then
+//! deletes the prekey secrets, so a peer never holds a prekey this client
+//! cannot answer. Sessions already open carry on either way.
+
+use crate::components::app::dm_ratchet;
+use crate::components::app::{mark_needs_sync, CURRENT_ROOM, ROOMS};
+use dioxus::logger::tracing::warn;
+use dioxus::prelude::*;
+use river_core::room_state::member_info::DmPrekeyV1;
+
+#[component]
+pub fn DmPrekeyToggle(enabled: bool) -> Element {
+    let mut busy = use_signal(|| false);
+
+    let toggle = move |_| {
+        let Ok(Some(room)) = CURRENT_ROOM.try_read().map(|c| c.owner_key) else {
+            return;
+        };
+        busy.set(true);
+        crate::util::safe_spawn_local(async move {
+            let prekey: Option<DmPrekeyV1> = if enabled {
+                None
+            } else {
+                match dm_ratchet::rotate_prekey(room).await {
+                    Ok(prekey) => Some(prekey),
+                    Err(e) => {
+                        warn!("Couldn't make a DM prekey: {}", e);
+                        crate::util::defer(move || busy.set(false));
+                        return;
+                    }
+                }
+            };
+            let (tx, rx) = futures::channel::oneshot::channel::<bool>();
+            crate::util::defer(move || {
+                let applied = ROOMS.with_mut(|rooms| {
+                    rooms
+                        .map
+                        .get_mut(&room)
+                        .is_some_and(|room_data| room_data.apply_dm_prekey_change(prekey))
+                });
+                if applied {
+                    mark_needs_sync(room);
+                }
+                busy.set(false);
+                let _ = tx.send(applied);
+            });
+            if enabled && rx.await.unwrap_or(false) {
+                if let Err(e) = dm_ratchet::clear_prekeys(room).await {
+                    warn!("Couldn't delete the DM prekey: {}", e);
+                }
+            }
+        });
+    };
+
+    let (label, hint) = if enabled {
+        (
+            "Turn off",
+            "New DMs to you use a ratcheting session: each message has its own key, \
+             deleted once used, and deleting a message makes it unrecoverable.",
+        )
+    } else {
+        (
+            "Turn on",
+            "DMs to you are sealed to your member key. Turn this on so new ones \
+             become unrecoverable once you delete them.",
+        )
+    };
+
+    rsx! {
+        div { class: "mb-4",
+            label { class: "block text-sm font-medium text-text-muted mb-2", "Forward-secret DMs" }
+            div { class: "flex items-center gap-2",
+                button {
+                    "data-testid": "member-info-dm-prekey-toggle",
+                    class: "px-3 py-1.5 bg-surface hover:bg-surface-hover text-text text-sm font-medium rounded-lg transition-colors border border-border",
+                    disabled: busy(),
+                    onclick: toggle,
+                    "{label}"
+                }
+                span { class: "text-sm text-text", if enabled { "On" } else { "Off" } }
+            }
+            p { class: "mt-1 text-xs text-text-muted", "{hint}" }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::components::app::{CURRENT_ROOM, MEMBER_INFO_MODAL, ROOMS};
+use crate::util::get_current_system_time;
+use dioxus::logger::tracing::{error, info};
+use dioxus::prelude::*;
+use freenet_scaffold::ComposableState;
+use river_core::room_state::member::MemberId;
+use river_core::room_state::mute::{AuthorizedMute, Mute, MuteAction};
+use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1Delta};
+use std::time::Duration;
+
+/// Mute lengths offered next to the Mute button, in seconds; `0` lasts until
+/// someone unmutes the member.
+const MUTE_DURATIONS: [(u64, &str); 4] = [
+    (60 * 60, "1 hour"),
+    (24 * 60 * 60, "1 day"),
+    (7 * 24 * 60 * 60, "1 week"),
+    (0, "Until unmuted"),
+];
+
+/// Mutes `target`, or unmutes them when `muted`. The caller only renders this
+/// when the viewer may moderate the target (`MembersV1::is_ban_authorized`),
+/// which is the contract's rule for both actions.
+#[component]
+pub fn MuteButton(target: MemberId, muted: bool) -> Element {
+    let mut duration_secs = use_signal(|| MUTE_DURATIONS[0].0);
+
+    let execute = move |_| {
+        let Some(current_room) = CURRENT_ROOM.read().owner_key else {
+            return;
+        };
+        let Some(room_data) = ROOMS
+            .try_read()
+            .ok()
+            .and_then(|rooms| rooms.map.get(&current_room).cloned())
+        else {
+            return;
+        };
+        let room_key = room_data.room_key();
+        let self_sk = room_data.self_sk.clone();
+        let muted_by = MemberId::from(&self_sk.verifying_key());
+        let action = if muted {
+            MuteAction::Unmute
+        } else {
+            MuteAction::Mute {
+                duration: match *duration_secs.read() {
+                    0 => None,
+                    secs => Some(Duration::from_secs(secs)),
+                },
+            }
+        };
+        let mute = Mute {
+            owner_member_id: MemberId::from(&current_room),
+            muted_user: target,
+            muted_at: get_current_system_time(),
+            action,
+        };
+
+        crate::util::defer(move || {
+            MEMBER_INFO_MODAL.with_mut(|modal| {
+                modal.member = None;
+            });
+        });
+
+        crate::util::safe_spawn_local(async move {
+            let mut mute_bytes = Vec::new();
+            if let Err(e) = ciborium::ser::into_writer(&mute, &mut mute_bytes) {
+                error!("Failed to serialize mute for signing: {:?}", e);
+                return;
+            }
+            // Signed through the ban request, as revocations are: the
+            // delegate signs whatever bytes it is handed.
+            let signature =
+                crate::signing::sign_ban_with_fallback(room_key, mute_bytes, &self_sk).await;
+
+            let delta = ChatRoomStateV1Delta {
+                mutes: Some(vec![AuthorizedMute::with_signature(
+                    mute, muted_by, signature,
+                )]),
+                ..Default::default()
+            };
+
+            crate::util::defer(move || {
+                ROOMS.with_mut(|rooms| {
+                    if let Some(room_data_mut) = rooms.map.get_mut(&current_room) {
+                        let parent = room_data_mut.room_state.clone();
+                        if let Err(e) = room_data_mut.room_state.apply_delta(
+                            &parent,
+                            &ChatRoomParametersV1 {
+                                owner: current_room,
+                            },
+                            &Some(delta),
+                        ) {
+                            error!("Failed to apply mute delta: {:?}", e);
+                        } else {
+                            info!("Successfully applied mute delta for member {:?}", target);
+                            // Same #310 re-derivation as after a ban: a mute
+                            // can remove messages.
+                            room_data_mut.rebuild_private_actions_state();
+                        }
+                    }
+                });
+                crate::components::app::mark_needs_sync(current_room);
+            });
+        });
+    };
+
+    rsx! {
+        div { class: "flex items-center gap-2",
+            if !muted {
+                select {
+                    "data-testid": "mute-duration",
+                    class: "px-2 py-2 bg-surface border border-border rounded-lg text-text text-sm",
+                    oninput: move |evt| {
+                        duration_secs.set(evt.value().parse().unwrap_or(0));
+                    },
+                    for (secs, label) in MUTE_DURATIONS {
+                        option { value: "{secs}", "{label}" }
+                    }
+                }
+            }
+            button {
+                "data-testid": "mute-button",
+                class: "px-4 py-2 bg-surface hover:bg-surface-hover text-text font-medium rounded-lg transition-colors border border-border whitespace-nowrap",
+                title: if muted {
+                    "Let this member post again"
+                } else {
+                    "Stop accepting this member's messages; they stay in the room"
+                },
+                onclick: execute,
+                if muted { "Unmute" } else { "Mute" }
+            }
+        }
+    }
+}
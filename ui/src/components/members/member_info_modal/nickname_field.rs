@@ -212,6 +212,9 @@ pub fn NicknameField(member_info: AuthorizedMemberInfo) -> Element {
                         // Preserved from the CANONICAL base, not the stale
                         // prop, for the same reason as the version above.
                         deputies: canonical_base.member_info.deputies.clone(),
+                        // Likewise the DM prekey, or peers fall back to
+                        // non-ratcheted DMs.
+                        dm_prekey: canonical_base.member_info.dm_prekey,
                     };
                     let new_authorized_member_info =
                         AuthorizedMemberInfo::new_with_member_key(new_member_info, &signing_key);
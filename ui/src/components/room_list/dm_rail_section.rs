@@ -25,6 +25,16 @@
 //! the section lists currently-archived threads and offers per-row
 //! Un-archive, closing #266.
 //!
+//! Group threads (three or more members, see
+//! [`crate::components::direct_messages::OPEN_GROUP_DM_THREAD`]) follow the
+//! pair threads as their own rows, built by [`build_group_view`] from our
+//! inboxes. They have no archive ✕: "Delete conversation" in the thread
+//! removes one from the local inbox instead.
+//!
+//! A pair row whose newest message is ours says whether the peer has
+//! received or read it, from the receipts they put in our inbox
+//! (`river_core::dm_receipts`, see [`rail_receipt_status`]).
+//!
 //! Hidden when empty so the rail doesn't show an empty section on first
 //! load. Sorts unread threads first, then by most-recent message time.
 //!
@@ -34,10 +44,13 @@
 //! functional benefit. The user-facing surface is "Archive" everywhere
 //! visible.
 
+use crate::components::app::block_list::BLOCKED_MEMBERS;
 use crate::components::app::chat_delegate::{hide_dm_thread, unhide_dm_thread};
+use crate::components::app::freenet_api::inbox_sync::INBOXES;
 use crate::components::app::ROOMS;
 use crate::components::direct_messages::{
-    is_thread_hidden_for, open_dm_thread, DM_LAST_SEEN, HIDDEN_DM_THREADS,
+    group_dm_threads, is_thread_hidden_for, open_dm_thread, open_group_dm_thread, DM_LAST_SEEN,
+    GROUP_DM_LAST_SEEN, HIDDEN_DM_THREADS, OUTBOUND_DMS,
 };
 use crate::util::ecies::unseal_bytes_with_secrets;
 use dioxus::prelude::*;
@@ -46,7 +59,8 @@ use dioxus_free_icons::{
     Icon,
 };
 use ed25519_dalek::VerifyingKey;
-use river_core::chat_delegate::HiddenDmThreadEntry;
+use river_core::chat_delegate::{blocked_by, HiddenDmThreadEntry};
+use river_core::dm_receipts::{AuthorizedDmReceipts, DmReceiptStatus};
 use river_core::room_state::member::MemberId;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
@@ -94,6 +108,8 @@ const ARCHIVE_TOAST_DURATION_MS: u64 = 5_000;
 pub fn DmRailSection() -> Element {
     let threads = use_memo(build_view);
     let threads_value = threads.read().clone();
+    let groups = use_memo(build_group_view);
+    let groups_value = groups.read().clone();
 
     // Reading the toast signal here subscribes the rail to its writes so
     // a `set(None)` from the timeout reaction re-renders this component
@@ -134,7 +150,8 @@ pub fn DmRailSection() -> Element {
     // If there's nothing to show in the rail AND no archive entries AND
     // no active toast, render nothing — keeps the rail visually quiet on
     // first load.
-    if threads_value.is_empty() && archived_count == 0 && toast.is_none() {
+    if threads_value.is_empty() && groups_value.is_empty() && archived_count == 0 && toast.is_none()
+    {
         return rsx! {};
     }
 
@@ -155,6 +172,13 @@ pub fn DmRailSection() -> Element {
                 }
             }
         }
+        if !groups_value.is_empty() {
+            ul { class: "px-2 py-1 space-y-0.5",
+                for entry in groups_value.iter() {
+                    GroupDmRailRow { key: "{entry.room:?}_{entry.participants:?}", entry: entry.clone() }
+                }
+            }
+        }
         if archived_count > 0 {
             div { class: "px-3 pb-2",
                 button {
@@ -223,6 +247,15 @@ fn DmRailRow(entry: DmRailEntry) -> Element {
                         }
                         div { class: "truncate text-[10px] text-text-muted",
                             "in {entry.room_name}"
+                            match entry.receipt {
+                                Some(DmReceiptStatus::Read) => rsx! {
+                                    span { "data-testid": "dm-rail-receipt", " · Read" }
+                                },
+                                Some(DmReceiptStatus::Delivered) => rsx! {
+                                    span { "data-testid": "dm-rail-receipt", " · Delivered" }
+                                },
+                                None => rsx! {},
+                            }
                         }
                     }
                     if entry.unread > 0 {
@@ -250,6 +283,31 @@ fn DmRailRow(entry: DmRailEntry) -> Element {
     }
 }
 
+#[component]
+fn GroupDmRailRow(entry: GroupDmRailEntry) -> Element {
+    let room = entry.room;
+    let participants = entry.participants.clone();
+    rsx! {
+        li {
+            button {
+                class: "w-full text-left px-3 py-1.5 rounded-lg text-sm transition-colors text-text hover:bg-surface flex items-center gap-2",
+                onclick: move |_| open_group_dm_thread(room, participants.clone()),
+                div { class: "flex-1 min-w-0",
+                    div { class: "truncate text-sm", "{entry.label}" }
+                    div { class: "truncate text-[10px] text-text-muted",
+                        "group in {entry.room_name}"
+                    }
+                }
+                if entry.unread > 0 {
+                    span { class: "ml-2 inline-flex items-center justify-center px-2 py-0.5 rounded-full text-xs font-medium bg-accent text-white",
+                        "{entry.unread}"
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Pure helper extracted from `DmRailRow`'s archive ✕ click handler so
 /// the toast bookkeeping can be unit-tested without standing up a Dioxus
 /// runtime. Returns the toast that `ARCHIVE_TOAST` would be set to, or
@@ -282,10 +340,11 @@ fn build_archive_toast(
 fn current_last_inbound_ts(room: &VerifyingKey, peer: MemberId) -> Option<u64> {
     use dioxus::prelude::ReadableExt;
     let rooms = ROOMS.try_read().ok()?;
+    let sources = dm_side_sources()?;
     let room_data = rooms.map.get(room)?;
     let self_id = MemberId::from(&room_data.self_sk.verifying_key());
     Some(max_inbound_ts_from_triples(
-        dm_message_triples(room_data),
+        dm_message_triples(room, room_data, &sources),
         self_id,
         peer,
     ))
@@ -505,6 +564,21 @@ pub(crate) struct DmRailEntry {
     /// Newest INBOUND timestamp — what the archive filter compares against.
     pub(crate) last_inbound_ts: u64,
     pub(crate) unread: usize,
+    /// How far the peer has got with our newest DM, when it is the thread's
+    /// newest message.
+    pub(crate) receipt: Option<DmReceiptStatus>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct GroupDmRailEntry {
+    pub(crate) room: VerifyingKey,
+    /// The thread's participant set, the local member included.
+    pub(crate) participants: Vec<MemberId>,
+    /// The other participants' nicknames.
+    pub(crate) label: String,
+    pub(crate) room_name: String,
+    pub(crate) last_ts: u64,
+    pub(crate) unread: usize,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -607,9 +681,26 @@ pub(crate) struct PeerDmActivity {
     /// archive question is "has the peer written since I archived", and
     /// never "has anything happened".
     pub(crate) last_inbound_ts: u64,
+    /// Newest timestamp of a DM we sent to the peer. Drives the receipt
+    /// shown on the row.
+    pub(crate) last_outbound_ts: u64,
     pub(crate) unread: usize,
 }
 
+/// Pure helper: the receipt status a pair row shows. Only a thread whose
+/// newest message is ours gets one — once the peer has replied, they have
+/// evidently seen it — and only when the peer's `receipts` cover that DM.
+/// Pinned by the `rail_receipt_status_*` tests.
+pub(crate) fn rail_receipt_status(
+    activity: &PeerDmActivity,
+    receipts: Option<&AuthorizedDmReceipts>,
+) -> Option<DmReceiptStatus> {
+    if activity.last_outbound_ts == 0 || activity.last_outbound_ts < activity.last_inbound_ts {
+        return None;
+    }
+    receipts?.status_of(activity.last_outbound_ts)
+}
+
 /// Pure helper: fold one room's DM stream into per-peer activity.
 /// `messages` yields `(sender, recipient, timestamp)` triples; anything
 /// not involving `self_id` is skipped.
@@ -642,11 +733,15 @@ pub(crate) fn accumulate_peer_activity(
         let acc = per_peer.entry(peer).or_insert(PeerDmActivity {
             last_any_ts: 0,
             last_inbound_ts: 0,
+            last_outbound_ts: 0,
             unread: 0,
         });
         if timestamp > acc.last_any_ts {
             acc.last_any_ts = timestamp;
         }
+        if is_self_sender && timestamp > acc.last_outbound_ts {
+            acc.last_outbound_ts = timestamp;
+        }
         if is_self_recipient {
             if timestamp > acc.last_inbound_ts {
                 acc.last_inbound_ts = timestamp;
@@ -687,25 +782,43 @@ pub(crate) fn backfill_unread_from_last_good(
     }
 }
 
-/// Project a room's authorized DM list into the `(sender, recipient,
-/// timestamp)` triples [`accumulate_peer_activity`] consumes. Shared by
-/// all three builders so their per-pair `last_any_ts` scans can't
-/// drift apart.
+/// The DM sources outside the room state: our inboxes, by room, the
+/// outbound plaintext cache (the only record of DMs we sent to an inbox)
+/// and our block list, whose members' DMs are hidden.
+type DmSideSources = (
+    HashMap<VerifyingKey, river_core::inbox::InboxStateV1>,
+    crate::components::direct_messages::OutboundDmsCache,
+    Vec<river_core::chat_delegate::BlockedMemberEntry>,
+);
+
+/// Snapshot [`DmSideSources`] for one pass. `None` when any signal is
+/// contended — the caller degrades exactly as for a contended `ROOMS`,
+/// since building without them would drop threads from the rail (or show
+/// blocked ones).
+fn dm_side_sources() -> Option<DmSideSources> {
+    let inboxes = INBOXES.try_read().ok()?.clone();
+    let outbound = OUTBOUND_DMS.try_read().ok()?.clone();
+    let blocked = BLOCKED_MEMBERS.try_read().ok()?.clone();
+    Some((inboxes, outbound, blocked))
+}
+
+/// Project a room's DMs — room state, inbox and outbound cache, via
+/// [`crate::components::direct_messages::dm_triples`] — into the
+/// `(sender, recipient, timestamp)` triples [`accumulate_peer_activity`]
+/// consumes. Shared by all three builders so their per-pair
+/// `last_any_ts` scans can't drift apart.
 fn dm_message_triples(
+    owner_vk: &VerifyingKey,
     room_data: &crate::room_data::RoomData,
-) -> impl Iterator<Item = (MemberId, MemberId, u64)> + '_ {
-    room_data
-        .room_state
-        .direct_messages
-        .messages
-        .iter()
-        .map(|msg| {
-            (
-                msg.message.sender,
-                msg.message.recipient,
-                msg.message.timestamp,
-            )
-        })
+    sources: &DmSideSources,
+) -> Vec<(MemberId, MemberId, u64)> {
+    let self_id = MemberId::from(&room_data.self_sk.verifying_key());
+    crate::components::direct_messages::dm_triples(
+        room_data,
+        sources.0.get(owner_vk),
+        Some(&sources.1),
+        &blocked_by(&sources.2, &owner_vk.to_bytes(), self_id),
+    )
 }
 
 thread_local! {
@@ -725,6 +838,10 @@ thread_local! {
     /// would re-trigger reactivity.
     static LAST_GOOD_RAIL: RefCell<Vec<DmRailEntry>> = const { RefCell::new(Vec::new()) };
 
+    /// Last group rows built by a clean `build_group_view` pass, served
+    /// on contended passes like `LAST_GOOD_RAIL`.
+    static LAST_GOOD_GROUP_RAIL: RefCell<Vec<GroupDmRailEntry>> = const { RefCell::new(Vec::new()) };
+
     /// Last archived count computed by a clean `current_archived_count`
     /// pass. Served on contended passes: a transient 0 there could
     /// combine with an empty thread list to satisfy `DmRailSection`'s
@@ -755,7 +872,7 @@ fn set_last_good_archived_count(count: usize) {
     LAST_GOOD_ARCHIVED_COUNT.with(|c| c.set(count));
 }
 
-/// Rebuild-nudge channel for the rail's three builder memos (issue #499
+/// Rebuild-nudge channel for the rail's builder memos (issue #499
 /// review follow-up). Each builder anchors its subscription set with an
 /// infallible read of this tick BEFORE any fallible `try_read`, so a
 /// contended poll can never leave a memo with zero subscriptions — and,
@@ -889,6 +1006,10 @@ fn build_archived_view() -> Option<Vec<ArchivedEntry>> {
             return None;
         }
     };
+    let Some(sources) = dm_side_sources() else {
+        schedule_rail_nudge();
+        return None;
+    };
 
     // Materialise per-room display data once and compute the per-pair
     // max DM timestamp at the same time. Both decryption and the
@@ -939,9 +1060,12 @@ fn build_archived_view() -> Option<Vec<ArchivedEntry>> {
         // info irrelevant here, so `last_seen = None`). We do NOT
         // pre-filter by `hidden`; the strict-`<=` revival rule is
         // applied inside `build_archived_rows`.
-        for (peer, activity) in
-            accumulate_peer_activity(owner_vk, self_id, dm_message_triples(room_data), None)
-        {
+        for (peer, activity) in accumulate_peer_activity(
+            owner_vk,
+            self_id,
+            dm_message_triples(owner_vk, room_data, &sources),
+            None,
+        ) {
             last_inbound_ts.insert((*owner_vk, peer), activity.last_inbound_ts);
         }
     }
@@ -975,12 +1099,19 @@ fn current_archived_count() -> usize {
         schedule_rail_nudge();
         return last_good_archived_count();
     };
+    let Some(sources) = dm_side_sources() else {
+        schedule_rail_nudge();
+        return last_good_archived_count();
+    };
     let mut last_inbound_ts: HashMap<(VerifyingKey, MemberId), u64> = HashMap::new();
     for (owner_vk, room_data) in &rooms.map {
         let self_id: MemberId = room_data.self_sk.verifying_key().into();
-        for (peer, activity) in
-            accumulate_peer_activity(owner_vk, self_id, dm_message_triples(room_data), None)
-        {
+        for (peer, activity) in accumulate_peer_activity(
+            owner_vk,
+            self_id,
+            dm_message_triples(owner_vk, room_data, &sources),
+            None,
+        ) {
             last_inbound_ts.insert((*owner_vk, peer), activity.last_inbound_ts);
         }
     }
@@ -1015,6 +1146,12 @@ fn build_view() -> Vec<DmRailEntry> {
         set_last_good_rail(&[]);
         return Vec::new();
     }
+    // Inbox and sent-DM threads would vanish for a pass without these, so
+    // contention degrades like `ROOMS` above.
+    let Some(sources) = dm_side_sources() else {
+        schedule_rail_nudge();
+        return last_good_rail();
+    };
 
     // Unread cutoffs. Contention here must NOT abort the rail (#499
     // mechanism 1 — the old `?` blanked the entire active list while
@@ -1084,7 +1221,7 @@ fn build_view() -> Vec<DmRailEntry> {
         let per_peer = accumulate_peer_activity(
             owner_vk,
             self_id,
-            dm_message_triples(room_data),
+            dm_message_triples(owner_vk, room_data, &sources),
             last_seen.as_ref(),
         );
 
@@ -1107,6 +1244,10 @@ fn build_view() -> Vec<DmRailEntry> {
                 last_any_ts: activity.last_any_ts,
                 last_inbound_ts: activity.last_inbound_ts,
                 unread: activity.unread,
+                receipt: rail_receipt_status(
+                    &activity,
+                    sources.0.get(owner_vk).and_then(|i| i.receipts_from(peer)),
+                ),
             });
         }
     }
@@ -1147,6 +1288,95 @@ fn short_member_id(id: &MemberId) -> String {
     id.to_string().chars().take(8).collect()
 }
 
+/// Group thread rows across all rooms, unread first and then most recent.
+/// Anchored and degraded like [`build_view`], except that any contended
+/// read serves the last clean rows whole: there is no archive filter or
+/// unread backfill to keep apart.
+fn build_group_view() -> Vec<GroupDmRailEntry> {
+    let _ = RAIL_REBUILD_TICK.read();
+    let last_good = || LAST_GOOD_GROUP_RAIL.with(|c| c.borrow().clone());
+    let (Ok(rooms), Ok(inboxes), Ok(last_seen), Ok(block_list)) = (
+        ROOMS.try_read(),
+        INBOXES.try_read(),
+        GROUP_DM_LAST_SEEN.try_read(),
+        BLOCKED_MEMBERS.try_read(),
+    ) else {
+        schedule_rail_nudge();
+        return last_good();
+    };
+
+    let mut entries: Vec<GroupDmRailEntry> = Vec::new();
+    for (owner_vk, room_data) in &rooms.map {
+        let Some(inbox) = inboxes.get(owner_vk) else {
+            continue;
+        };
+        if inbox.group_messages.is_empty() {
+            continue;
+        }
+        let self_id: MemberId = room_data.self_sk.verifying_key().into();
+        let sealed_name = &room_data
+            .room_state
+            .configuration
+            .configuration
+            .display
+            .name;
+        let room_name = match unseal_bytes_with_secrets(sealed_name, &room_data.secrets) {
+            Ok(b) => String::from_utf8_lossy(&b).to_string(),
+            Err(_) => sealed_name.to_string_lossy(),
+        };
+        let nicknames: HashMap<MemberId, String> = room_data
+            .room_state
+            .member_info
+            .member_info
+            .iter()
+            .map(|info| {
+                (
+                    info.member_info.member_id,
+                    crate::util::display_name::display_nickname(
+                        &info.member_info.preferred_nickname,
+                        &room_data.secrets,
+                    ),
+                )
+            })
+            .collect();
+        let params = river_core::room_state::ChatRoomParametersV1 { owner: *owner_vk };
+        let blocked = blocked_by(&block_list, &owner_vk.to_bytes(), self_id);
+        let visible = inbox
+            .visible_group_messages(&room_data.room_state, &params)
+            .filter(|m| !blocked.contains(&m.message.sender));
+        for thread in group_dm_threads(*owner_vk, self_id, visible, Some(&last_seen)) {
+            let label = thread
+                .participants
+                .iter()
+                .filter(|p| **p != self_id)
+                .map(|p| {
+                    nicknames
+                        .get(p)
+                        .cloned()
+                        .unwrap_or_else(|| short_member_id(p))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            entries.push(GroupDmRailEntry {
+                room: *owner_vk,
+                participants: thread.participants,
+                label,
+                room_name: room_name.clone(),
+                last_ts: thread.last_ts,
+                unread: thread.unread,
+            });
+        }
+    }
+    entries.sort_by(|a, b| {
+        (b.unread > 0)
+            .cmp(&(a.unread > 0))
+            .then(b.last_ts.cmp(&a.last_ts))
+            .then_with(|| a.participants.cmp(&b.participants))
+    });
+    LAST_GOOD_GROUP_RAIL.with(|c| *c.borrow_mut() = entries.clone());
+    entries
+}
+
 fn unix_now_secs() -> u64 {
     crate::util::get_current_system_time()
         .duration_since(UNIX_EPOCH)
@@ -1468,6 +1698,7 @@ mod tests {
             // to differ use `entry_with_inbound`.
             last_inbound_ts: last_any_ts,
             unread,
+            receipt: None,
         }
     }
 
@@ -1898,6 +2129,55 @@ mod tests {
         );
     }
 
+    /// A row shows the peer's receipt for our newest DM only while it is the
+    /// newest message in the thread, and only as far as the receipt covers it.
+    #[test]
+    fn rail_receipt_status_follows_our_newest_dm() {
+        use river_core::dm_receipts::advance_dm_receipts;
+        let owner = sk(1).verifying_key();
+        let me = sk(2);
+        let peer = sk(3);
+        let receipts = advance_dm_receipts(
+            &peer,
+            MemberId::from(&me.verifying_key()),
+            &owner,
+            None,
+            300,
+            200,
+        )
+        .unwrap()
+        .unwrap();
+        let activity = |last_inbound_ts, last_outbound_ts| PeerDmActivity {
+            last_any_ts: u64::max(last_inbound_ts, last_outbound_ts),
+            last_inbound_ts,
+            last_outbound_ts,
+            unread: 0,
+        };
+
+        assert_eq!(
+            rail_receipt_status(&activity(100, 200), Some(&receipts)),
+            Some(DmReceiptStatus::Read)
+        );
+        assert_eq!(
+            rail_receipt_status(&activity(100, 300), Some(&receipts)),
+            Some(DmReceiptStatus::Delivered)
+        );
+        assert_eq!(
+            rail_receipt_status(&activity(100, 400), Some(&receipts)),
+            None
+        );
+        // The peer wrote after us, or we never wrote, or they send no receipts.
+        assert_eq!(
+            rail_receipt_status(&activity(250, 200), Some(&receipts)),
+            None
+        );
+        assert_eq!(
+            rail_receipt_status(&activity(100, 0), Some(&receipts)),
+            None
+        );
+        assert_eq!(rail_receipt_status(&activity(100, 200), None), None);
+    }
+
     /// Clean hide-list read: `resolve_active_entries` applies the #261
     /// archive filter exactly like `filter_rail_entries`.
     #[test]
@@ -1,13 +1,17 @@
 use super::room_name_field::RoomNameField;
 use crate::components::app::chat_delegate::save_rooms_to_delegate;
 use crate::components::app::{CURRENT_ROOM, EDIT_ROOM_MODAL, ROOMS};
+use crate::room_data::RoomData;
 use crate::util::ecies::{seal_for_room, unseal_bytes_with_secrets};
+use crate::util::get_current_system_time;
 use dioxus::logger::tracing::{error, info, warn};
 use dioxus::prelude::*;
 use dioxus_free_icons::icons::fa_solid_icons::FaCopy;
 use dioxus_free_icons::Icon;
 use freenet_scaffold::ComposableState;
 use river_core::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
+use river_core::room_state::member::MemberId;
+use river_core::room_state::message::{AuthorizedMessageV1, MessageV1, RoomMessageBody};
 use river_core::room_state::privacy::{PrivacyMode, RoomDisplayMetadata};
 use river_core::room_state::{ChatRoomParametersV1, ChatRoomStateV1Delta};
 use std::ops::Deref;
@@ -321,6 +325,20 @@ pub fn EditRoomModal() -> Element {
                                                 // Defer signal mutations to a clean execution
                                                 // context to prevent RefCell re-entrant borrow panics.
                                                 crate::util::defer(move || {
+                                                    // Announce the departure before dropping the
+                                                    // room, so other members see "left the room"
+                                                    // and the member is pruned without waiting for
+                                                    // inactivity. Owners can't leave their own room
+                                                    // this way, so they send nothing.
+                                                    let leave = ROOMS
+                                                        .read()
+                                                        .map
+                                                        .get(&room_vk)
+                                                        .and_then(build_leave_event);
+                                                    if let Some(leave) = leave {
+                                                        crate::components::app::freenet_api::room_synchronizer::send_leave_event_update(room_vk, leave);
+                                                    }
+
                                                     // `leave_room` removes from `map` AND adds the
                                                     // owner VK to `removed_rooms`. The tombstone
                                                     // is what makes leave survive across reloads /
@@ -926,3 +944,33 @@ fn MaxMembersField(
         }
     }
 }
+
+/// Sign a leave event for the current identity, or `None` when there is
+/// nothing to announce (we own the room, or were already pruned).
+fn build_leave_event(room_data: &RoomData) -> Option<AuthorizedMessageV1> {
+    let self_id = MemberId::from(&room_data.self_sk.verifying_key());
+    let owner_id = MemberId::from(&room_data.owner_vk);
+    if self_id == owner_id
+        || !room_data
+            .room_state
+            .members
+            .members
+            .iter()
+            .any(|m| m.member.id() == self_id)
+    {
+        return None;
+    }
+    let message = MessageV1 {
+        room_owner: owner_id,
+        author: self_id,
+        content: RoomMessageBody::leave_event(),
+        time: get_current_system_time(),
+    };
+    let mut message_bytes = Vec::new();
+    if let Err(e) = ciborium::ser::into_writer(&message, &mut message_bytes) {
+        error!("Failed to serialize leave event for signing: {:?}", e);
+        return None;
+    }
+    let signature = crate::signing::sign_message_locally(&message_bytes, &room_data.self_sk);
+    Some(AuthorizedMessageV1::with_signature(message, signature))
+}
@@ -845,7 +845,7 @@ fn render_new_invitation(inv: Invitation, invitation: Signal<Option<Invitation>>
     // Generate a default handle from the member's key (deterministic —
     // same key always yields the same handle).
     let default_nickname =
-        crate::nickname::generate_default_nickname(&inv.invitee.member.member_vk);
+        river_core::nickname::generate_default_nickname(&inv.invitee.member.member_vk);
 
     // Create a signal for the nickname
     let mut nickname = use_signal(|| default_nickname);
@@ -1000,7 +1000,7 @@ pub(crate) fn accept_invitation(inv: Invitation, nickname: String) {
     // Use the user-provided nickname
     let nickname = if nickname.trim().is_empty() {
         // Fallback to generated handle if somehow empty
-        crate::nickname::generate_default_nickname(&authorized_member.member.member_vk)
+        river_core::nickname::generate_default_nickname(&authorized_member.member.member_vk)
     } else {
         nickname
     };
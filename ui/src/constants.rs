@@ -6,6 +6,8 @@ pub const KEY_VERSION_PREFIX: &str = "river:v1";
 // These are the authoritative versions that match deployed contracts
 pub const ROOM_CONTRACT_WASM: &[u8] = include_bytes!("../public/contracts/room_contract.wasm");
 
+pub const INBOX_CONTRACT_WASM: &[u8] = include_bytes!("../public/contracts/inbox_contract.wasm");
+
 pub const CHAT_DELEGATE_WASM: &[u8] = include_bytes!("../public/contracts/chat_delegate.wasm");
 
 // pub const ROOM_CONTRACT_CODE_HASH: CodeHash = CodeHash::from_code(ROOM_CONTRACT_WASM);
@@ -270,6 +270,7 @@ fn create_room(
                     (random_full_name() + " (Owner) \u{1F6E1}\u{1F451}").into_bytes(),
                 ),
                 deputies: vec![other_member_id],
+                dm_prekey: None,
             },
             owner_sk,
         ));
@@ -298,6 +299,7 @@ fn create_room(
                         (random_full_name() + " (You)").into_bytes(),
                     ),
                     deputies: Vec::new(),
+                    dm_prekey: None,
                 },
                 &self_sk,
             ));
@@ -340,6 +342,7 @@ fn create_room(
                 version: 0,
                 preferred_nickname: SealedBytes::public(deputy_nickname.clone().into_bytes()),
                 deputies: Vec::new(),
+                dm_prekey: None,
             },
             &other_member_sk,
         ));
@@ -374,6 +377,7 @@ fn create_room(
                     confusable_variant(&deputy_nickname).into_bytes(),
                 ),
                 deputies: Vec::new(),
+                dm_prekey: None,
             },
             &impostor_sk,
         ));
@@ -430,6 +434,7 @@ fn create_room(
             self_sk: self_sk.clone(),
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: std::collections::HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -833,7 +838,7 @@ fn add_example_messages(
 ///   (`I`, `l`, `1`, `|`, `!` all fold to the same sentinel), so the VISUAL
 ///   skeleton is unchanged. This is the live 2026-07-25 attack, and the branch
 ///   the demo wants: the two names are near-indistinguishable on screen.
-/// * A letter-for-digit swap from [`crate::util::confusable`]'s ASCII table
+/// * A letter-for-digit swap from [`river_core::confusable`]'s ASCII table
 ///   (`o`->`0`, `e`->`3`, `s`->`5`, `a`->`4`, `t`->`7`), which that table folds
 ///   straight back, so the skeleton is unchanged.
 /// * Uppercasing, for ASCII, changes only case, so the CASE-INSENSITIVE
@@ -1045,7 +1050,7 @@ mod tests {
     /// while the ⚠ it is supposed to find never rendered at all.
     #[test]
     fn the_example_impostor_always_collides_with_the_deputy() {
-        use crate::util::confusable::folds_together;
+        use river_core::confusable::folds_together;
 
         // One name per branch of `confusable_variant`, so a future edit that
         // breaks a branch fails here rather than in a browser.
@@ -1450,6 +1455,7 @@ fn push_test_message(
                     version: 0,
                     preferred_nickname: SealedBytes::public(nickname.as_bytes().to_vec()),
                     deputies: Vec::new(),
+                    dm_prekey: None,
                 },
                 sk,
             ));
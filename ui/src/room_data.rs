@@ -12,8 +12,8 @@ use river_core::chat_delegate::RoomKey;
 use river_core::room_state::configuration::{AuthorizedConfigurationV1, Configuration};
 use river_core::room_state::member::AuthorizedMember;
 use river_core::room_state::member::MemberId;
-use river_core::room_state::member_info::{AuthorizedMemberInfo, MemberInfo};
-use river_core::room_state::message::MessageId;
+use river_core::room_state::member_info::{AuthorizedMemberInfo, DmPrekeyV1, MemberInfo};
+use river_core::room_state::message::{MessageId, MessagesV1};
 use river_core::room_state::privacy::{
     PrivacyMode, RoomCipherSpec, RoomDisplayMetadata, SealedBytes,
 };
@@ -114,6 +114,14 @@ pub struct RoomData {
     /// Persisted to delegate storage.
     #[serde(default)]
     pub last_read_message_id: Option<MessageId>,
+    /// Per-thread read markers: for each thread root the user has opened in
+    /// the thread panel, the signed time of the newest reply they have seen.
+    /// Replies from others after it count as unread in that thread. Threads
+    /// never opened have no marker and show no unread count.
+    ///
+    /// Persisted to delegate storage.
+    #[serde(default)]
+    pub thread_read: HashMap<MessageId, std::time::SystemTime>,
     /// All decrypted room secrets by version (if room is private)
     /// Maps secret_version -> decrypted 32-byte secret
     #[serde(skip)]
@@ -338,17 +346,24 @@ impl RoomData {
     /// `recent_messages`. No-op on public rooms (the public rebuild that
     /// `apply_delta` already ran is correct and complete).
     pub fn rebuild_private_actions_state(&mut self) {
-        use crate::util::ecies::decrypt_with_symmetric_key;
-        use river_core::room_state::message::RoomMessageBody;
-
         if !self.is_private() {
             return;
         }
-
-        // Decrypt all private action messages using version-aware lookup.
-        let decrypted_actions: HashMap<MessageId, Vec<u8>> = self
-            .room_state
+        let decrypted_actions = self.decrypt_private_actions(&self.room_state.recent_messages);
+        self.room_state
             .recent_messages
+            .rebuild_actions_state_with_decrypted(&decrypted_actions);
+    }
+
+    /// Decrypt the private action messages in `messages` with the room
+    /// secrets (version-aware), keyed by message id, for
+    /// `rebuild_actions_state_with_decrypted`. Takes the messages separately
+    /// so the local archive can be replayed the same way.
+    pub fn decrypt_private_actions(&self, messages: &MessagesV1) -> HashMap<MessageId, Vec<u8>> {
+        use crate::util::ecies::decrypt_with_symmetric_key;
+        use river_core::room_state::message::RoomMessageBody;
+
+        messages
             .messages
             .iter()
             .filter(|msg| msg.message.content.is_action())
@@ -370,11 +385,7 @@ impl RoomData {
                     None
                 }
             })
-            .collect();
-
-        self.room_state
-            .recent_messages
-            .rebuild_actions_state_with_decrypted(&decrypted_actions);
+            .collect()
     }
 
     /// Get a reference to the current secret (convenience method)
@@ -646,6 +657,38 @@ impl RoomData {
             .contains(&self_id)
     }
 
+    /// Members whose messages the contract currently refuses, each with the
+    /// time their mute began (see `river_core::room_state::mute`).
+    pub fn muted_members(&self) -> HashMap<MemberId, std::time::SystemTime> {
+        let state = &self.room_state;
+        state.mutes.muted_members(
+            &state.members.members_by_member_id(),
+            &state.member_info,
+            &state.recent_messages,
+            self.owner_id(),
+            &self.owner_vk,
+            state.configuration.configuration.max_members,
+        )
+    }
+
+    /// Why the user may not post ordinary messages right now, if they may
+    /// not: they are muted, or the room's posting policy leaves them out.
+    /// Reactions are still allowed either way.
+    pub fn posting_restriction(&self) -> Option<&'static str> {
+        let self_id = MemberId::from(&self.self_sk.verifying_key());
+        if self.muted_members().contains_key(&self_id) {
+            return Some(
+                "You have been muted in this room. Your messages will not be accepted until a moderator unmutes you.",
+            );
+        }
+        match &self.room_state.configuration.configuration.posting_policy {
+            Some(policy) if !policy.may_post(self_id, self.owner_id()) => Some(
+                "Only designated members can post in this room. You can still react to messages.",
+            ),
+            _ => None,
+        }
+    }
+
     /// Check if the user can send a message in the room.
     /// A user is considered a member if they are the owner, are in the active
     /// members list, or have a stored invitation (self_authorized_member).
@@ -835,7 +878,6 @@ impl RoomData {
     pub fn apply_deputy_change(&mut self, target: MemberId, add: bool) -> bool {
         use dioxus::logger::tracing::{error, info};
         use river_core::room_state::member_info::MAX_DEPUTIES;
-        use river_core::room_state::ChatRoomStateV1Delta;
 
         let self_id = MemberId::from(&self.self_sk.verifying_key());
 
@@ -868,12 +910,47 @@ impl RoomData {
             return false; // not a deputy, nothing to publish
         }
 
-        // Republish our own member_info at version+1, preserving the
-        // (already-sealed) nickname; only `deputies` changes. The new
-        // version is derived from the HIGHER of the canonical room_state
-        // version and the cached `self_member_info` version — not from
-        // room_state alone. On a stale/reset client the room_state max can
-        // collide at the SAME version as a still-propagating grant/revoke
+        if !self.republish_self_member_info(&current_self, |info| info.deputies = deputies) {
+            return false;
+        }
+        info!("Deputy change applied for {target:?} (deputize={add})");
+        true
+    }
+
+    /// Publish `prekey` as our DM prekey (`river_core::dm_ratchet`), or stop
+    /// publishing one with `None`, by republishing our own `member_info`
+    /// like [`Self::apply_deputy_change`]. Returns `true` when the change was
+    /// applied and the room needs a sync.
+    pub fn apply_dm_prekey_change(&mut self, prekey: Option<DmPrekeyV1>) -> bool {
+        use dioxus::logger::tracing::error;
+
+        let self_id = MemberId::from(&self.self_sk.verifying_key());
+        let Some(current_self) = self.room_state.member_info.canonical(self_id).cloned() else {
+            error!("Cannot publish a DM prekey: no member_info for self yet");
+            return false;
+        };
+        if current_self.member_info.dm_prekey == prekey {
+            return false;
+        }
+        self.republish_self_member_info(&current_self, |info| info.dm_prekey = prekey)
+    }
+
+    /// Republish our own member_info at version+1 with `edit` applied to
+    /// `current_self` (our canonical record), re-adding ourselves first if we
+    /// were pruned. Shared by [`Self::apply_deputy_change`] and
+    /// [`Self::apply_dm_prekey_change`].
+    fn republish_self_member_info(
+        &mut self,
+        current_self: &AuthorizedMemberInfo,
+        edit: impl FnOnce(&mut MemberInfo),
+    ) -> bool {
+        use dioxus::logger::tracing::error;
+        use river_core::room_state::ChatRoomStateV1Delta;
+
+        // The new version is derived from the HIGHER of the canonical
+        // room_state version and the cached `self_member_info` version — not
+        // from room_state alone. On a stale/reset client the room_state max
+        // can collide at the SAME version as a still-propagating grant/revoke
         // and lose the signature tiebreak, silently no-op'ing the change
         // (freenet/river#411 round 8).
         let cached_version = self
@@ -881,13 +958,9 @@ impl RoomData {
             .as_ref()
             .map(|cached| cached.member_info.version)
             .unwrap_or(0);
-        let next_version = current_self.member_info.version.max(cached_version) + 1;
-        let new_info = MemberInfo {
-            member_id: self_id,
-            version: next_version,
-            preferred_nickname: current_self.member_info.preferred_nickname.clone(),
-            deputies,
-        };
+        let mut new_info = current_self.member_info.clone();
+        new_info.version = current_self.member_info.version.max(cached_version) + 1;
+        edit(&mut new_info);
         let self_sk = self.self_sk.clone();
         let authorized = AuthorizedMemberInfo::new_with_member_key(new_info, &self_sk);
 
@@ -907,19 +980,18 @@ impl RoomData {
             },
             &Some(delta),
         ) {
-            error!("Failed to apply deputy delta: {e:?}");
+            error!("Failed to apply member_info delta: {e:?}");
             return false;
         }
 
         // Cache the just-signed record so a later inactivity-rejoin
-        // republishes the UPDATED deputies, not a stale record that still
-        // lists a revoked deputy (freenet/river#411 round 6 B).
+        // republishes the UPDATED record, not a stale one that still lists a
+        // revoked deputy (freenet/river#411 round 6 B) or an old prekey.
         self.self_member_info = Some(authorized);
 
         // apply_delta re-runs the public-only rebuild_actions_state, wiping
         // private edits/reactions; re-derive with decryption. No-op on public.
         self.rebuild_private_actions_state();
-        info!("Deputy change applied for {target:?} (deputize={add})");
         true
     }
 
@@ -1003,7 +1075,7 @@ impl RoomData {
                 let nickname = self
                     .self_nickname
                     .clone()
-                    .unwrap_or_else(|| crate::nickname::generate_default_nickname(&self_vk));
+                    .unwrap_or_else(|| river_core::nickname::generate_default_nickname(&self_vk));
                 // A private room's nickname must be encrypted. Seal it with
                 // the current room secret; if no secret is available publish
                 // NO member_info (the members delta still re-adds us) rather
@@ -1022,6 +1094,7 @@ impl RoomData {
                             version: existing_version,
                             preferred_nickname,
                             deputies: Vec::new(),
+                            dm_prekey: None,
                         },
                         &self.self_sk,
                     )
@@ -1129,7 +1202,7 @@ impl RoomData {
             let nickname = self
                 .self_nickname
                 .clone()
-                .unwrap_or_else(|| crate::nickname::generate_default_nickname(&self_vk));
+                .unwrap_or_else(|| river_core::nickname::generate_default_nickname(&self_vk));
             // version: 0 is safe — the heal only fires when no member_info
             // entry exists in `state` (the `has_member_info` check
             // above), so this is never version-compared against an
@@ -1139,6 +1212,7 @@ impl RoomData {
                 version: 0,
                 preferred_nickname: seal_bytes(nickname.as_bytes(), &secret, version),
                 deputies: Vec::new(),
+                dm_prekey: None,
             };
             return Some(AuthorizedMemberInfo::new_with_member_key(
                 info,
@@ -1158,12 +1232,13 @@ impl RoomData {
         let nickname = self
             .self_nickname
             .clone()
-            .unwrap_or_else(|| crate::nickname::generate_default_nickname(&self_vk));
+            .unwrap_or_else(|| river_core::nickname::generate_default_nickname(&self_vk));
         let info = MemberInfo {
             member_id,
             version: 0,
             preferred_nickname: SealedBytes::public(nickname.into_bytes()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         Some(AuthorizedMemberInfo::new_with_member_key(
             info,
@@ -1622,6 +1697,7 @@ pub(crate) fn test_minimal_room_data(owner_vk: VerifyingKey) -> RoomData {
         self_sk: SigningKey::from_bytes(&[1u8; 32]),
         contract_key,
         last_read_message_id: None,
+        thread_read: Default::default(),
         secrets: HashMap::new(),
         current_secret_version: None,
         last_secret_rotation: None,
@@ -1742,6 +1818,7 @@ impl Rooms {
                 SealedBytes::public(nickname.into_bytes())
             },
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         let authorized_owner_info = AuthorizedMemberInfo::new(owner_info, &self_sk);
         room_state
@@ -1773,6 +1850,7 @@ impl Rooms {
             self_sk,
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets,
             current_secret_version: room_secret_version,
             last_secret_rotation: if room_secret_version.is_some() {
@@ -2263,6 +2341,7 @@ mod tests {
             self_sk: stale_sk,
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -2321,6 +2400,7 @@ mod tests {
             version: 0,
             preferred_nickname: SealedBytes::public("Alice".to_string().into_bytes()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         let authorized_info = AuthorizedMemberInfo::new_with_member_key(info, &invitee_sk);
         room_state.member_info.member_info.push(authorized_info);
@@ -2337,6 +2417,7 @@ mod tests {
             self_sk: invitee_sk.clone(),
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -2365,6 +2446,7 @@ mod tests {
             version: 1,
             preferred_nickname: SealedBytes::public("Bob".to_string().into_bytes()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         let updated_authorized =
             AuthorizedMemberInfo::new_with_member_key(updated_info, &invitee_sk);
@@ -2407,6 +2489,7 @@ mod tests {
                 self_sk: sk,
                 contract_key,
                 last_read_message_id: None,
+                thread_read: Default::default(),
                 secrets: HashMap::new(),
                 current_secret_version: None,
                 last_secret_rotation: None,
@@ -2476,6 +2559,7 @@ mod tests {
             self_sk: invitee_sk.clone(),
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -2974,6 +3058,7 @@ mod tests {
             // --- Deliberately taken from the adopted copy; losing the local
             // value is cosmetic or self-correcting.
             last_read_message_id: _, // at worst some messages re-show as unread
+            thread_read: _,          // at worst some thread replies re-show as unread
             previous_contract_key: _, // re-derived by regenerate_contract_key
         } = room;
     }
@@ -3417,6 +3502,7 @@ mod tests {
             version: 2,
             preferred_nickname: SealedBytes::public(b"PlainLeak".to_vec()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         room.self_member_info = Some(AuthorizedMemberInfo::new_with_member_key(
             public_entry,
@@ -3464,6 +3550,7 @@ mod tests {
             version: 6,
             preferred_nickname: seal_bytes(b"SealedName", &v0_secret, 0),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         room.self_member_info = Some(AuthorizedMemberInfo::new_with_member_key(
             private_entry,
@@ -3503,6 +3590,7 @@ mod tests {
             version: 2,
             preferred_nickname: SealedBytes::public(b"Edited".to_vec()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         let edited = AuthorizedMemberInfo::new_with_member_key(edited, &invitee_sk);
 
@@ -3529,6 +3617,7 @@ mod tests {
             version: 1,
             preferred_nickname: SealedBytes::public(b"Other".to_vec()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         let other = AuthorizedMemberInfo::new_with_member_key(other, &other_sk);
 
@@ -3558,6 +3647,7 @@ mod tests {
             version: 5,
             preferred_nickname: SealedBytes::public("Alice".to_string().into_bytes()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         room.self_member_info = Some(AuthorizedMemberInfo::new_with_member_key(info, &invitee_sk));
 
@@ -3632,6 +3722,7 @@ mod tests {
             version: 0,
             preferred_nickname: SealedBytes::public(b"Present".to_vec()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         network_state
             .member_info
@@ -3677,6 +3768,7 @@ mod tests {
             version: 7,
             preferred_nickname: SealedBytes::public(b"ChosenName".to_vec()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         room.self_member_info = Some(AuthorizedMemberInfo::new_with_member_key(
             stored,
@@ -3804,6 +3896,7 @@ mod tests {
             version: 3,
             preferred_nickname: SealedBytes::public(b"PlainName".to_vec()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         room.self_member_info = Some(AuthorizedMemberInfo::new_with_member_key(
             public_entry,
@@ -3965,7 +4058,8 @@ mod tests {
             .expect("public-sealed nickname must unseal");
         assert_eq!(
             nickname,
-            crate::nickname::generate_default_nickname(&invitee_sk.verifying_key()).into_bytes(),
+            river_core::nickname::generate_default_nickname(&invitee_sk.verifying_key())
+                .into_bytes(),
             "with no recorded nickname the heal must use the generated default"
         );
     }
@@ -3987,6 +4081,7 @@ mod tests {
             version: 9,
             preferred_nickname: SealedBytes::public(b"PublishedName".to_vec()),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         room.self_member_info = Some(AuthorizedMemberInfo::new_with_member_key(
             stored,
@@ -4029,6 +4124,7 @@ mod tests {
             version: 4,
             preferred_nickname: seal_bytes(b"PublishedName", &v0_secret, 0),
             deputies: Vec::new(),
+            dm_prekey: None,
         };
         room.self_member_info = Some(AuthorizedMemberInfo::new_with_member_key(
             stored_info,
@@ -4152,6 +4248,7 @@ mod tests {
             self_sk: owner_sk.clone(),
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets,
             current_secret_version: Some(0),
             last_secret_rotation: Some(get_current_system_time()),
@@ -4458,6 +4555,7 @@ mod tests {
                 self_sk: member_sk.clone(),
                 contract_key,
                 last_read_message_id: None,
+                thread_read: Default::default(),
                 secrets: HashMap::new(),
                 current_secret_version: None,
                 last_secret_rotation: None,
@@ -4907,6 +5005,7 @@ mod tests {
                 self_sk: member_sk,
                 contract_key,
                 last_read_message_id: None,
+                thread_read: Default::default(),
                 secrets: HashMap::new(),
                 current_secret_version: None,
                 last_secret_rotation: None,
@@ -4977,6 +5076,7 @@ mod tests {
                 self_sk: member_sk,
                 contract_key,
                 last_read_message_id: None,
+                thread_read: Default::default(),
                 secrets: HashMap::new(),
                 current_secret_version: None,
                 last_secret_rotation: None,
@@ -5208,6 +5308,7 @@ mod tests {
             self_sk: invitee_sk.clone(),
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -5543,6 +5644,7 @@ mod tests {
             self_sk: member_sk,
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -5878,6 +5980,7 @@ mod tests {
                 version: 0,
                 preferred_nickname: SealedBytes::public(b"m".to_vec()),
                 deputies: Vec::new(),
+                dm_prekey: None,
             };
             room_state
                 .member_info
@@ -5938,6 +6041,7 @@ mod tests {
             self_sk: owner_sk.clone(),
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets,
             current_secret_version: if private { Some(0) } else { None },
             last_secret_rotation: if private {
@@ -5967,6 +6071,8 @@ mod tests {
             owner_member_id: room.owner_vk.into(),
             banned_at: get_current_system_time(),
             banned_user: target,
+            reason: None,
+            duration: None,
         };
         room.room_state
             .bans
@@ -6025,6 +6131,27 @@ mod tests {
         assert_eq!(room.can_participate(), Err(SendMessageError::UserBanned));
     }
 
+    #[test]
+    fn posting_policy_restricts_only_members_it_leaves_out() {
+        use river_core::room_state::configuration::PostingPolicy;
+        let mut rng = rand::thread_rng();
+        let owner_sk = SigningKey::generate(&mut rng);
+        let d_sk = SigningKey::generate(&mut rng);
+        let t_sk = SigningKey::generate(&mut rng);
+        let d_id = MemberId::from(&d_sk.verifying_key());
+
+        let mut room = make_room_owner_d_t(&owner_sk, &d_sk, &t_sk, false);
+        room.room_state.configuration.configuration.posting_policy =
+            Some(PostingPolicy::Allowlist(vec![d_id]));
+
+        assert_eq!(room.posting_restriction(), None, "the owner always posts");
+        room.self_sk = d_sk;
+        assert_eq!(room.posting_restriction(), None);
+        room.self_sk = t_sk;
+        assert!(room.posting_restriction().is_some());
+        assert_eq!(room.can_participate(), Ok(()), "T can still react");
+    }
+
     #[test]
     fn rotate_secret_keeps_inert_ban_target_but_drops_enforced_one() {
         let mut rng = rand::thread_rng();
@@ -6141,6 +6268,8 @@ mod tests {
             owner_member_id: owner_id,
             banned_at: get_current_system_time(),
             banned_user: s_id,
+            reason: None,
+            duration: None,
         };
         room_state
             .bans
@@ -6160,6 +6289,7 @@ mod tests {
             self_sk: s_sk,
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -6236,6 +6366,38 @@ mod tests {
         assert!(cached2.member_info.version > cached.member_info.version);
     }
 
+    /// Publishing a DM prekey keeps the deputy grants, and a deputy change
+    /// afterwards keeps the prekey: both republish the whole signed record.
+    #[test]
+    fn apply_dm_prekey_change_preserves_the_rest_of_member_info() {
+        let mut rng = rand::thread_rng();
+        let owner_sk = SigningKey::generate(&mut rng);
+        let d_sk = SigningKey::generate(&mut rng);
+        let t_sk = SigningKey::generate(&mut rng);
+        let t_id = MemberId::from(&t_sk.verifying_key());
+        let mut room = make_room_owner_d_t(&owner_sk, &d_sk, &t_sk, true);
+        room.self_sk = d_sk;
+        let prekey = DmPrekeyV1 {
+            id: 1,
+            public_key: [9; 32],
+        };
+
+        assert!(room.apply_deputy_change(t_id, true));
+        assert!(room.apply_dm_prekey_change(Some(prekey)));
+        assert!(
+            !room.apply_dm_prekey_change(Some(prekey)),
+            "an unchanged prekey publishes nothing"
+        );
+        assert!(room.apply_deputy_change(t_id, false));
+        let cached = room.self_member_info.clone().expect("self record cached");
+        assert_eq!(cached.member_info.dm_prekey, Some(prekey));
+        assert!(cached.member_info.deputies.is_empty());
+
+        assert!(room.apply_dm_prekey_change(None));
+        let cached = room.self_member_info.clone().expect("self record cached");
+        assert_eq!(cached.member_info.dm_prekey, None);
+    }
+
     // ------------------------------------------------------------------
     // #411 round 8 (Fix E): `apply_deputy_change` must route through the
     // CANONICAL member_info record (highest member_info_rank: version, then
@@ -6275,6 +6437,7 @@ mod tests {
                     version: 1,
                     preferred_nickname: SealedBytes::public(b"D".to_vec()),
                     deputies: vec![],
+                    dm_prekey: None,
                 };
                 let clean_authorized = AuthorizedMemberInfo::new_with_member_key(clean, &d_sk);
                 let stale_grant = MemberInfo {
@@ -6282,6 +6445,7 @@ mod tests {
                     version: 1,
                     preferred_nickname: SealedBytes::public(b"D".to_vec()),
                     deputies: vec![t_id],
+                    dm_prekey: None,
                 };
                 let stale_grant_authorized =
                     AuthorizedMemberInfo::new_with_member_key(stale_grant, &d_sk);
@@ -6354,6 +6518,7 @@ mod tests {
             self_sk: d_sk,
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -6439,6 +6604,7 @@ mod tests {
             version: 2,
             preferred_nickname: SealedBytes::public(b"D".to_vec()),
             deputies: vec![],
+            dm_prekey: None,
         };
         let authorized_v2 = AuthorizedMemberInfo::new_with_member_key(info_v2, &d_sk);
         room_state
@@ -6460,6 +6626,7 @@ mod tests {
             version: 5,
             preferred_nickname: SealedBytes::public(b"D".to_vec()),
             deputies: vec![],
+            dm_prekey: None,
         };
         let authorized_v5 = AuthorizedMemberInfo::new_with_member_key(info_v5, &d_sk);
 
@@ -6469,6 +6636,7 @@ mod tests {
             self_sk: d_sk,
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -6564,6 +6732,8 @@ mod tests {
             owner_member_id: owner_id,
             banned_at: get_current_system_time(),
             banned_user: r_id,
+            reason: None,
+            duration: None,
         };
         room_state
             .bans
@@ -6583,6 +6753,7 @@ mod tests {
             self_sk: s_sk,
             contract_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
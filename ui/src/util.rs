@@ -1,6 +1,5 @@
 #![allow(dead_code)]
 
-pub mod confusable;
 pub mod display_name;
 pub mod ecies;
 pub mod signal_guard;
@@ -1015,6 +1014,7 @@ mod tests {
             self_sk: owner_sk,
             contract_key: bogus_key,
             last_read_message_id: None,
+            thread_read: Default::default(),
             secrets: HashMap::new(),
             current_secret_version: None,
             last_secret_rotation: None,
@@ -142,11 +142,15 @@ mod tests {
             strip_line_comments(include_str!("../components/room_list/room_name_field.rs"));
         let edit_room_src =
             strip_line_comments(include_str!("../components/room_list/edit_room_modal.rs"));
+        let ban_reason_src = strip_line_comments(include_str!(
+            "../components/members/member_info_modal/ban_button.rs"
+        ));
 
         for (name, src) in [
             ("nickname_field.rs", &nickname_src),
             ("room_name_field.rs", &room_name_src),
             ("edit_room_modal.rs", &edit_room_src),
+            ("ban_button.rs", &ban_reason_src),
         ] {
             assert!(
                 src.contains("seal_for_room("),